@@ -35,6 +35,15 @@ impl Entity {
             false
         }
     }
+
+    /// Declared as `ABSTRACT` or `ABSTRACT SUPERTYPE` in the entity declaration.
+    /// `ABSTRACT SUPERTYPE` in `SUBTYPE_CONSTRAINT` is not taken into account.
+    pub fn is_abstract(&self) -> bool {
+        matches!(
+            self.constraint,
+            Some(Constraint::AbstractEntity | Constraint::AbstractSuperType(..))
+        )
+    }
 }
 
 /// Intermediate output of [entity_body]
@@ -63,7 +63,17 @@ impl SyntaxTree {
             .map(|(_start_space, schemas, _end_space)| schemas)
             .parse(input)
             .finish()?;
-        assert!(residual.is_empty());
+        if !residual.is_empty() {
+            // Report why the remaining schema cannot be parsed
+            return Err(schema_decl(residual).finish().err().unwrap_or_else(|| {
+                nom::error::VerboseError {
+                    errors: vec![(
+                        residual,
+                        nom::error::VerboseErrorKind::Nom(nom::error::ErrorKind::Eof),
+                    )],
+                }
+            }));
+        }
         Ok(SyntaxTree { schemas, remarks })
     }
 
@@ -41,13 +41,17 @@ pub enum Type {
     },
 
     // Constructed Types
+    /// `items` are added to ones of `based_on` type if `BASED_ON` is specified
     Enumeration {
         extensibility: Extensibility,
         items: Vec<String>,
+        based_on: Option<String>,
     },
+    /// `types` are added to ones of `based_on` type if `BASED_ON` is specified
     Select {
         extensibility: Extensibility,
         types: Vec<String>,
+        based_on: Option<String>,
     },
 
     // Parameter Types
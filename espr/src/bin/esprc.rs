@@ -1,6 +1,10 @@
 //! Executable for espr EXPRESS language compiler
 
-use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+use espr::{
+    ast::SyntaxTree,
+    codegen::rust::*,
+    ir::{Options, IR},
+};
 use std::{fs, path::*};
 use structopt::StructOpt;
 
@@ -10,6 +14,38 @@ struct Arguments {
     num_lines: usize,
     #[structopt(long = "check", help = "Check input EXPRESS definitions can be parsed")]
     check: bool,
+    #[structopt(
+        long = "no-collapse-alias-chains",
+        help = "Generate a wrapper type for each TYPE declaration renaming another type"
+    )]
+    no_collapse_alias_chains: bool,
+    #[structopt(
+        long = "no-derived-accessors",
+        help = "Do not generate accessors evaluating DERIVE attributes"
+    )]
+    no_derived_accessors: bool,
+    #[structopt(
+        long = "preserve-type-wrappers",
+        help = "Keep keywords of typed parameters, e.g. LABEL('x'), for attributes of simple types"
+    )]
+    preserve_type_wrappers: bool,
+    #[structopt(
+        long = "derive-serde",
+        help = "Derive Serialize and Deserialize for Tables to persist them by TablesCache"
+    )]
+    derive_serde: bool,
+    #[structopt(
+        long = "skip-unsupported",
+        help = "Skip ENTITY and TYPE declarations which cannot be generated instead of failing"
+    )]
+    skip_unsupported: bool,
+    #[structopt(
+        long = "formatter",
+        default_value = "auto",
+        help = "Formatter of generated code: auto, rustfmt, prettyplease, or none. \
+                auto uses rustfmt if found in PATH, and prettyplease otherwise"
+    )]
+    formatter: FormatterSelection,
     #[structopt(parse(from_os_str))]
     source: PathBuf,
 }
@@ -38,9 +74,25 @@ fn main() {
         return;
     }
 
-    let ir = IR::from_syntax_tree(&st).expect("Failed in semantic analysis phase");
-    println!(
-        "#![allow(dead_code)]\n{}",
+    let options = Options {
+        collapse_alias_chains: !args.no_collapse_alias_chains,
+        evaluate_derived_attributes: !args.no_derived_accessors,
+        preserve_type_wrappers: args.preserve_type_wrappers,
+        derive_serde: args.derive_serde,
+    };
+    let tokens = if args.skip_unsupported {
+        let outcome = generate_degraded(&st, &options, CratePrefix::External)
+            .expect("Failed in semantic analysis phase");
+        for item in &outcome.skipped {
+            eprintln!("warning: {}", item);
+        }
+        outcome.tokens
+    } else {
+        let ir = IR::from_syntax_tree_with_options(&st, &options)
+            .expect("Failed in semantic analysis phase");
         ir.to_token_stream(CratePrefix::External)
-    );
+    };
+    let code = format!("#![allow(dead_code)]\n{}", tokens);
+    let code = format(&code, args.formatter).expect("Failed to format generated code");
+    println!("{}", code.trim_end());
 }
@@ -0,0 +1,61 @@
+use inflector::Inflector;
+use proc_macro2::{Literal, TokenStream};
+use quote::*;
+
+use crate::{ast, ir::*};
+
+impl Constant {
+    /// Rust type and value of the constant if it is a literal of simple type,
+    /// e.g. `CONSTANT pi : REAL := 3.14; END_CONSTANT;`
+    fn literal(&self) -> Option<(TokenStream, TokenStream)> {
+        use ast::{Expression, SimpleType::*, UnaryOperator};
+        let TypeRef::SimpleType(SimpleType(ty)) = &self.ty else {
+            return None;
+        };
+        let (minus, expr) = match &self.expr {
+            Expression::Unary {
+                op: UnaryOperator::Minus,
+                arg,
+            } => (quote! { - }, arg.as_ref()),
+            expr => (quote! {}, expr),
+        };
+        let Expression::Literal(literal) = expr else {
+            return None;
+        };
+        match (ty, literal) {
+            (Real | Number, ast::Literal::Real(value)) => {
+                let value = Literal::f64_unsuffixed(*value);
+                Some((quote! { f64 }, quote! { #minus #value }))
+            }
+            (Integer, ast::Literal::Real(value)) if value.fract() == 0.0 => {
+                let value = Literal::i64_unsuffixed(*value as i64);
+                Some((quote! { i64 }, quote! { #minus #value }))
+            }
+            (String_ { .. }, ast::Literal::String(value)) if minus.is_empty() => {
+                Some((quote! { &str }, quote! { #value }))
+            }
+            (Boolen, ast::Literal::Logial(value)) if minus.is_empty() => match value {
+                ast::Logical::True => Some((quote! { bool }, quote! { true })),
+                ast::Logical::False => Some((quote! { bool }, quote! { false })),
+                ast::Logical::Unknown => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl ToTokens for Constant {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Other initializers, e.g. entity constructors, are kept only in IR
+        // since expressions are not evaluated in codegen.
+        let Some((ty, value)) = self.literal() else {
+            return;
+        };
+        let name = format_ident!("{}", self.name.to_screaming_snake_case());
+        let doc = format!("`{}` declared in `CONSTANT` block", self.name);
+        tokens.append_all(quote! {
+            #[doc = #doc]
+            pub const #name: #ty = #value;
+        });
+    }
+}
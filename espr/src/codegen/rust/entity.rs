@@ -1,8 +1,8 @@
-use crate::ir::*;
+use super::{holder_serde, snake_ident};
+use crate::{ast, ir::*};
 
-use check_keyword::CheckKeyword;
 use inflector::Inflector;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::*;
 use syn::parse_quote;
 
@@ -19,26 +19,48 @@ struct Field {
 ///
 /// - a simple type
 /// - an enumeration
-/// - a set or list whose base type use place holder
+/// - a set, bag, list, or array whose base type use place holder
 ///
-fn use_place_holder(ty: &TypeRef) -> bool {
+pub(super) fn use_place_holder(ty: &TypeRef) -> bool {
     match ty {
         TypeRef::SimpleType(..) => false,
         TypeRef::Named { is_enumerate, .. } => !*is_enumerate,
-        TypeRef::Set { base, .. } | TypeRef::List { base, .. } => use_place_holder(base),
+        TypeRef::Set { base, .. }
+        | TypeRef::Bag { base, .. }
+        | TypeRef::List { base, .. }
+        | TypeRef::Array { base, .. } => use_place_holder(base),
         _ => true,
     }
 }
 
 impl From<EntityAttribute> for Field {
     fn from(attr: EntityAttribute) -> Self {
-        let EntityAttribute { name, ty, optional } = attr;
-
-        let name = format_ident!("{}", name.into_safe());
-        let attributes = if use_place_holder(&ty) {
-            vec![parse_quote! { #[holder(use_place_holder)] }]
+        let EntityAttribute {
+            name,
+            ty,
+            optional,
+            wrapped,
+            boxed,
+        } = attr;
+
+        let name = snake_ident(&name);
+        let mut attributes = Vec::new();
+        if let Some(cycle) = &boxed {
+            let doc = format!(
+                " Boxed to break the reference cycle `{}`",
+                cycle.join(" -> ")
+            );
+            attributes.push(parse_quote! { #[doc = #doc] });
+        }
+        if use_place_holder(&ty) {
+            attributes.push(parse_quote! { #[holder(use_place_holder)] });
+        }
+        let ty: syn::Type = if wrapped {
+            parse_quote! { Wrapped<#ty> }
+        } else if boxed.is_some() {
+            parse_quote! { Box<#ty> }
         } else {
-            Vec::new()
+            parse_quote! { #ty }
         };
         let ty = if optional {
             parse_quote! { Option<#ty> }
@@ -83,14 +105,15 @@ impl Entity {
 
     /// Field identifier
     fn field_ident(&self) -> syn::Ident {
-        format_ident!("{}", self.name.as_str().into_safe())
+        snake_ident(&self.name)
     }
 
     /// Generate declaration of `XxxAny` enum
-    fn generate_any_enum(&self, tokens: &mut TokenStream) {
+    fn generate_any_enum(&self, tokens: &mut TokenStream, derive_serde: bool) {
         let any = self.any_ident();
+        let generate_serde = holder_serde(derive_serde);
 
-        let mut fields = vec![format_ident!("{}", self.name.as_str().into_safe())];
+        let mut fields = vec![snake_ident(&self.name)];
         let mut variants = vec![format_ident!("{}", self.name.to_pascal_case())];
         let mut constraints = vec![format_ident!("{}", self.name.to_pascal_case())];
 
@@ -99,7 +122,7 @@ impl Entity {
                 TypeRef::Entity {
                     name, is_supertype, ..
                 } => {
-                    fields.push(format_ident!("{}", name.as_str().into_safe()));
+                    fields.push(snake_ident(name));
                     variants.push(format_ident!("{}", name.to_pascal_case()));
                     if *is_supertype {
                         constraints.push(format_ident!("{}Any", name.to_pascal_case()));
@@ -115,6 +138,9 @@ impl Entity {
             #[derive(Debug, Clone, PartialEq, Holder)]
             #[holder(table = Tables)]
             #[holder(generate_deserialize)]
+            #[holder(generate_into_holder)]
+            #[holder(generate_to_record)]
+            #generate_serde
             pub enum #any {
                 #(
                 #[holder(use_place_holder)]
@@ -124,27 +150,36 @@ impl Entity {
         }); // tokens.append_all
     }
 
-    /// Generate `impl Into<SelfAny> for SubType` for self and all constraints
-    fn generate_into_any(&self, tokens: &mut TokenStream) {
+    /// Generate `impl From<SubType> for SelfAny` for self and all constraints
+    fn generate_from_for_any(&self, tokens: &mut TokenStream) {
         let any = self.any_ident();
         let name = self.name_ident();
 
         // `Self` to `SelfAny`
         tokens.append_all(quote! {
-            impl Into<#any> for #name {
-                fn into(self) -> #any {
-                    #any::#name(Box::new(self))
+            impl From<#name> for #any {
+                fn from(value: #name) -> Self {
+                    #any::#name(Box::new(value))
                 }
             }
         });
 
         for ty in &self.constraints {
-            if let TypeRef::Entity { name, .. } = ty {
+            if let TypeRef::Entity {
+                name, is_supertype, ..
+            } = ty
+            {
                 let name = format_ident!("{}", name.to_pascal_case());
+                // Convert into `SubTypeAny` if the subtype is also a supertype
+                let value = if *is_supertype {
+                    quote! { value.into() }
+                } else {
+                    quote! { value }
+                };
                 tokens.append_all(quote! {
-                    impl Into<#any> for #name {
-                        fn into(self) -> #any {
-                            #any::#name(Box::new(self.into()))
+                    impl From<#name> for #any {
+                        fn from(value: #name) -> Self {
+                            #any::#name(Box::new(#value))
                         }
                     }
                 });
@@ -216,10 +251,7 @@ impl Entity {
                 let (name, ty) = match ty {
                     TypeRef::Named { name, .. } | TypeRef::Entity { name, .. } => {
                         let ty = format_ident!("{}", name.to_pascal_case());
-                        (
-                            format_ident!("{}", name.as_str().into_safe()),
-                            parse_quote! { #ty },
-                        )
+                        (snake_ident(name), parse_quote! { #ty })
                     }
                     _ => unreachable!(),
                 };
@@ -233,6 +265,215 @@ impl Entity {
             .collect()
     }
 
+    /// Derived attributes generated as accessors, with whether they are REAL
+    pub(super) fn evaluated_derived(&self) -> Vec<(&DerivedAttribute, bool)> {
+        self.derived
+            .iter()
+            .filter_map(|derived| {
+                let is_real = match &derived.ty {
+                    TypeRef::SimpleType(SimpleType(ty)) => !matches!(ty, ast::SimpleType::Integer),
+                    _ => return None,
+                };
+                derived.expr.is_evaluated().then_some((derived, is_real))
+            })
+            .collect()
+    }
+
+    /// Fields memoizing the derived attributes, e.g. `half_cache` for `half`
+    fn derived_cache_fields(&self) -> Vec<Field> {
+        self.evaluated_derived()
+            .into_iter()
+            .map(|(derived, _)| {
+                let ty = &derived.ty;
+                Field {
+                    name: derived_cache_ident(derived),
+                    ty: parse_quote! { Memo<Option<#ty>> },
+                    attributes: vec![
+                        parse_quote! { #[holder(cache)] },
+                        parse_quote! { #[new(default)] },
+                    ],
+                }
+            })
+            .collect()
+    }
+
+    /// Generate accessor methods for evaluated derived attributes
+    ///
+    /// Accessors return `None` if the value is indeterminate,
+    /// e.g. an optional attribute referred in the expression is not provided.
+    /// The value is evaluated at the first call, and kept in `Memo` field of the struct.
+    fn generate_derived_accessors(&self, tokens: &mut TokenStream) {
+        let name = self.name_ident();
+        let mut accessors = Vec::new();
+        for (derived, is_real) in self.evaluated_derived() {
+            let method = snake_ident(&derived.name);
+            let evaluate = format_ident!("evaluate_{}", derived.name.to_lowercase());
+            let cache = derived_cache_ident(derived);
+            let ty = &derived.ty;
+            let bindings = derived
+                .expr
+                .attributes()
+                .into_iter()
+                .map(|(attr, optional)| {
+                    let wrapped = self.attributes.iter().any(|a| a.name == attr && a.wrapped);
+                    let attr = snake_ident(attr);
+                    match (optional, wrapped) {
+                        (true, true) => quote! { let #attr = self.#attr.as_ref()?.value; },
+                        (true, false) => quote! { let #attr = self.#attr?; },
+                        (false, true) => quote! { let #attr = self.#attr.value; },
+                        (false, false) => quote! { let #attr = self.#attr; },
+                    }
+                });
+            let body = derived_expression(&derived.expr, is_real).into_option();
+            accessors.push(quote! {
+                pub fn #method(&self) -> Option<#ty> {
+                    *self.#cache.get_or_init(|| self.#evaluate())
+                }
+
+                fn #evaluate(&self) -> Option<#ty> {
+                    #(#bindings)*
+                    #body
+                }
+            });
+        }
+        if !accessors.is_empty() {
+            tokens.append_all(quote! {
+                impl #name {
+                    #(#accessors)*
+                }
+            });
+        }
+    }
+
+    /// Generate `Tables::check_unique_xxx` reporting pairs of ids violating `UNIQUE` rules
+    ///
+    /// Only rules over explicit attributes of this entity are checked,
+    /// since inherited attributes are in the holders of supertypes.
+    pub(super) fn generate_check_unique(&self, ruststep_path: &syn::Path) -> Option<TokenStream> {
+        let rules: Vec<_> = self
+            .unique_rules
+            .iter()
+            .filter(|rule| {
+                rule.attributes.iter().all(|attr| {
+                    attr.group.is_none() && self.attributes.iter().any(|a| a.name == attr.name)
+                })
+            })
+            .map(|rule| {
+                let attrs = rule.attributes.iter().map(|attr| snake_ident(&attr.name));
+                quote! { #(x.#attrs == y.#attrs)&&* }
+            })
+            .collect();
+        if rules.is_empty() {
+            return None;
+        }
+        let method = format_ident!("check_unique_{}", self.name.to_lowercase());
+        let field = self.field_ident();
+        let doc = format!(
+            " Pairs of ids of `{}` violating `UNIQUE` rules in ascending order",
+            self.name
+        );
+        Some(quote! {
+            #[doc = #doc]
+            pub fn #method(&self) -> Vec<(u64, u64)> {
+                #ruststep_path::tables::unique_violations(&self.#field, |x, y| #(#rules)||*)
+            }
+        })
+    }
+
+    /// Generate `Tables::check_set_xxx` reporting ids whose `SET` attributes have duplicated elements
+    ///
+    /// `SET` is a `Vec` as `LIST`, and the uniqueness of its elements is not checked while reading.
+    /// Only explicit attributes of this entity declared as `SET` directly are checked,
+    /// and elements are compared as in holders, i.e. entity references are compared by ids.
+    pub(super) fn generate_check_set(&self, ruststep_path: &syn::Path) -> Option<TokenStream> {
+        let checks: Vec<_> = self
+            .attributes
+            .iter()
+            .filter(|attr| {
+                matches!(attr.ty, TypeRef::Set { .. }) && !attr.wrapped && attr.boxed.is_none()
+            })
+            .map(|attr| {
+                let name = snake_ident(&attr.name);
+                let has_duplicates = quote! { #ruststep_path::tables::has_duplicates };
+                if attr.optional {
+                    quote! { x.#name.as_deref().is_some_and(#has_duplicates) }
+                } else {
+                    quote! { #has_duplicates(&x.#name) }
+                }
+            })
+            .collect();
+        if checks.is_empty() {
+            return None;
+        }
+        let method = format_ident!("check_set_{}", self.name.to_lowercase());
+        let field = self.field_ident();
+        let doc = format!(
+            " Ids of `{}` having duplicated elements in `SET` attributes in ascending order",
+            self.name
+        );
+        Some(quote! {
+            #[doc = #doc]
+            pub fn #method(&self) -> Vec<u64> {
+                #ruststep_path::tables::set_violations(&self.#field, |x| #(#checks)||*)
+            }
+        })
+    }
+
+    /// Generate `Tables::xxx_yyy_of` returning entities referring an entity `xxx` for its `INVERSE` attribute `yyy`
+    ///
+    /// Only inverse attributes over explicit attributes of an entity in `entities` are generated,
+    /// and subtypes of the referring entity are not scanned.
+    pub(super) fn generate_inverse_accessors(
+        &self,
+        entities: &[Entity],
+        ruststep_path: &syn::Path,
+    ) -> TokenStream {
+        let mut accessors = TokenStream::new();
+        for inverse in &self.inverse {
+            let TypeRef::Entity { name: dest, .. } = &inverse.dest else {
+                continue;
+            };
+            let Some(dest) = entities.iter().find(|e| e.name == dest.as_str()) else {
+                continue;
+            };
+            if !dest.attributes.iter().any(|a| a.name == inverse.attribute) {
+                continue;
+            }
+            let method = format_ident!(
+                "{}_{}_of",
+                self.name.to_lowercase(),
+                inverse.name.to_lowercase()
+            );
+            let id = format_ident!("{}_id", self.name.to_lowercase());
+            let table = dest.field_ident();
+            let ty = dest.name_ident();
+            let attribute = snake_ident(&inverse.attribute);
+            let doc = format!(
+                " `{}` referring the `{}` by `{}`, i.e. `INVERSE` attribute `{}`, in ascending order of ids",
+                dest.name, self.name, inverse.attribute, inverse.name
+            );
+            accessors.append_all(quote! {
+                #[doc = #doc]
+                pub fn #method(&self, #id: u64) -> #ruststep_path::error::Result<Vec<#ty>> {
+                    #ruststep_path::tables::inverse_of(self, &self.#table, #id, |holder| &holder.#attribute)
+                }
+            });
+        }
+        accessors
+    }
+
+    /// Derive macros of `derive_more` used for this entity
+    pub(super) fn derive_more_derives(&self) -> Vec<&'static str> {
+        let mut derives = Vec::new();
+        if !self.supertypes.is_empty() {
+            derives.extend(["AsRef", "AsMut"]);
+        }
+        if self.supertypes.len() == 1 {
+            derives.extend(["Deref", "DerefMut"]);
+        }
+        derives
+    }
+
     fn derives(&self) -> Vec<syn::Path> {
         let mut derives = vec![
             syn::parse_str("Debug").unwrap(),
@@ -241,20 +482,16 @@ impl Entity {
             syn::parse_str("::derive_new::new").unwrap(),
             syn::parse_str("Holder").unwrap(),
         ];
-        if !self.supertypes.is_empty() {
-            derives.push(syn::parse_str("AsRef").unwrap());
-            derives.push(syn::parse_str("AsMut").unwrap());
-        }
-        if self.supertypes.len() == 1 {
-            derives.push(syn::parse_str("Deref").unwrap());
-            derives.push(syn::parse_str("DerefMut").unwrap());
+        for derive in self.derive_more_derives() {
+            derives.push(syn::parse_str(derive).unwrap());
         }
         derives
     }
 }
 
-impl ToTokens for Entity {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Entity {
+    /// Generate the struct, and `Any` enum if this entity is a supertype
+    pub(super) fn generate(&self, tokens: &mut TokenStream, derive_serde: bool) {
         let name = self.name_ident();
         let field_name = self.field_ident();
 
@@ -268,26 +505,175 @@ impl ToTokens for Entity {
             .map(|attr| Field::from(attr.clone()))
             .collect::<Vec<Field>>();
         let supertype_fields = self.supertype_fields();
+        let cache_fields = self.derived_cache_fields();
 
         let derive = self.derives();
+        let abstract_entity = if self.is_abstract {
+            Some(quote! { #[holder(abstract_entity)] })
+        } else {
+            None
+        };
+        let generate_serde = holder_serde(derive_serde);
 
         tokens.append_all(quote! {
             #( #[derive(#derive)] )*
             #[holder(table = Tables)]
             #[holder(field = #field_name)]
             #[holder(generate_deserialize)]
+            #[holder(generate_into_holder)]
+            #[holder(generate_to_record)]
+            #generate_serde
+            #abstract_entity
             pub struct #name {
                 #(#supertype_fields,)*
                 #(#fields,)*
+                #(#cache_fields,)*
             }
         });
 
+        self.generate_derived_accessors(tokens);
+
         // Generate `Any` enum if this entity is a supertype of other entities
         if !self.constraints.is_empty() {
-            self.generate_any_enum(tokens);
-            // Generate `impl Into<XxxAny> for Yyy` for self and all constraints
-            self.generate_into_any(tokens);
+            self.generate_any_enum(tokens, derive_serde);
+            // Generate `impl From<Yyy> for XxxAny` for self and all constraints
+            self.generate_from_for_any(tokens);
             self.generate_asref_from_any(tokens);
         }
     }
 }
+
+fn derived_cache_ident(derived: &DerivedAttribute) -> syn::Ident {
+    format_ident!("{}_cache", derived.name.to_lowercase())
+}
+
+// Precedence of Rust operators used in derived expressions
+const PREC_ADD: u8 = 1;
+const PREC_MUL: u8 = 2;
+const PREC_CAST: u8 = 3;
+const PREC_NEG: u8 = 4;
+const PREC_ATOM: u8 = 5;
+
+/// Rust expression for [DerivedExpression]
+struct Rendered {
+    tokens: TokenStream,
+    precedence: u8,
+    /// INTEGER operations are checked and yield `Option<i64>`,
+    /// i.e. overflow or division by zero makes the value indeterminate.
+    checked: bool,
+}
+
+impl Rendered {
+    fn new(tokens: TokenStream, precedence: u8) -> Self {
+        Rendered {
+            tokens,
+            precedence,
+            checked: false,
+        }
+    }
+
+    /// Body of accessor returning `Option<_>`
+    fn into_option(self) -> TokenStream {
+        let tokens = self.tokens;
+        if self.checked {
+            tokens
+        } else {
+            quote! { Some(#tokens) }
+        }
+    }
+
+    /// Use as an operand of an operator with `precedence`
+    fn operand(self, precedence: u8) -> TokenStream {
+        let tokens = self.tokens;
+        let (tokens, own) = if self.checked {
+            (quote! { #tokens? }, PREC_ATOM)
+        } else {
+            (tokens, self.precedence)
+        };
+        if own < precedence {
+            quote! { (#tokens) }
+        } else {
+            tokens
+        }
+    }
+}
+
+fn derived_expression(expr: &DerivedExpression, is_real: bool) -> Rendered {
+    let render = |expr: &DerivedExpression| derived_expression(expr, is_real);
+    match expr {
+        DerivedExpression::Real(x) if x.is_nan() => Rendered::new(quote! { f64::NAN }, PREC_ATOM),
+        DerivedExpression::Real(x) if x.is_infinite() && *x > 0.0 => {
+            Rendered::new(quote! { f64::INFINITY }, PREC_ATOM)
+        }
+        DerivedExpression::Real(x) if x.is_infinite() => {
+            Rendered::new(quote! { f64::NEG_INFINITY }, PREC_ATOM)
+        }
+        DerivedExpression::Real(x) if x.is_sign_negative() => {
+            let x = Literal::f64_unsuffixed(-x);
+            Rendered::new(quote! { -#x }, PREC_NEG)
+        }
+        DerivedExpression::Real(x) => {
+            Rendered::new(Literal::f64_unsuffixed(*x).into_token_stream(), PREC_ATOM)
+        }
+        DerivedExpression::Integer(i64::MIN) => Rendered::new(quote! { i64::MIN }, PREC_ATOM),
+        DerivedExpression::Integer(i) if *i < 0 => {
+            let i = Literal::i64_suffixed(-i);
+            Rendered::new(quote! { -#i }, PREC_NEG)
+        }
+        DerivedExpression::Integer(i) => {
+            Rendered::new(Literal::i64_suffixed(*i).into_token_stream(), PREC_ATOM)
+        }
+        DerivedExpression::Attribute {
+            name, is_integer, ..
+        } => {
+            let name = snake_ident(name);
+            if is_real && *is_integer {
+                Rendered::new(quote! { #name as f64 }, PREC_CAST)
+            } else {
+                Rendered::new(quote! { #name }, PREC_ATOM)
+            }
+        }
+        DerivedExpression::Neg(arg) if is_real => {
+            let arg = render(arg).operand(PREC_NEG);
+            Rendered::new(quote! { -#arg }, PREC_NEG)
+        }
+        DerivedExpression::Neg(arg) => {
+            let arg = render(arg).operand(PREC_ATOM);
+            Rendered {
+                tokens: quote! { #arg.checked_neg() },
+                precedence: PREC_ATOM,
+                checked: true,
+            }
+        }
+        DerivedExpression::Binary { op, lhs, rhs } if is_real => {
+            let (op, precedence) = match op {
+                ArithmeticOperator::Add => (quote! { + }, PREC_ADD),
+                ArithmeticOperator::Sub => (quote! { - }, PREC_ADD),
+                ArithmeticOperator::Mul => (quote! { * }, PREC_MUL),
+                ArithmeticOperator::RealDiv => (quote! { / }, PREC_MUL),
+                ArithmeticOperator::IntegerDiv => unreachable!("DIV is only for INTEGER"),
+            };
+            // Operators are left-associative
+            let lhs = render(lhs).operand(precedence);
+            let rhs = render(rhs).operand(precedence + 1);
+            Rendered::new(quote! { #lhs #op #rhs }, precedence)
+        }
+        DerivedExpression::Binary { op, lhs, rhs } => {
+            let method = match op {
+                ArithmeticOperator::Add => format_ident!("checked_add"),
+                ArithmeticOperator::Sub => format_ident!("checked_sub"),
+                ArithmeticOperator::Mul => format_ident!("checked_mul"),
+                ArithmeticOperator::IntegerDiv => format_ident!("checked_div"),
+                ArithmeticOperator::RealDiv => unreachable!("/ is only for REAL"),
+            };
+            let lhs = render(lhs).operand(PREC_ATOM);
+            let rhs = render(rhs).operand(0);
+            Rendered {
+                tokens: quote! { #lhs.#method(#rhs) },
+                precedence: PREC_ATOM,
+                checked: true,
+            }
+        }
+        DerivedExpression::Unevaluated => unreachable!(),
+    }
+}
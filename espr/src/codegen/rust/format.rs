@@ -1,37 +1,142 @@
+//! Formatting generated Rust code
+//!
+//! Generated code is formatted by a [Formatter]:
+//!
+//! - [RustfmtBinary] runs `rustfmt` as external process.
+//!   The output depends on the version of `rustfmt` installed on the host.
+//! - [PrettyPlease] formats in-process using `prettyplease` crate,
+//!   and thus works in hermetic build environments without `rustfmt`.
+//!
+//! [FormatterSelection::Auto], the default, uses `rustfmt` if available
+//! and falls back to `prettyplease` otherwise.
+
 use std::{
-    io::Write,
+    io::{self, Write},
     process::{Command, Stdio},
 };
 
-/// Format generated Rust code using `rustfmt` run as external process.
+/// Error while formatting generated code
+#[derive(Debug, thiserror::Error)]
+pub enum FormatError {
+    #[error("rustfmt is not found in PATH")]
+    RustfmtNotFound,
+
+    #[error("Failed to run rustfmt: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("rustfmt failed: {0}")]
+    RustfmtFailed(String),
+
+    #[error("Generated code is not valid Rust: {0}")]
+    Parse(#[from] syn::Error),
+}
+
+/// Formatter of generated Rust code
+pub trait Formatter {
+    fn format(&self, code: &str) -> Result<String, FormatError>;
+}
+
+/// Format using `rustfmt` run as external process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustfmtBinary;
+
+impl Formatter for RustfmtBinary {
+    fn format(&self, code: &str) -> Result<String, FormatError> {
+        let mut child = match Command::new("rustfmt")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(FormatError::RustfmtNotFound)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // Write input from another thread for avoiding deadlock.
+        // See https://doc.rust-lang.org/std/process/index.html#handling-io
+        let mut stdin = child.stdin.take().expect("Failed to open stdin");
+        let code = code.to_string();
+        let writer = std::thread::spawn(move || stdin.write_all(code.as_bytes()));
+        let output = child.wait_with_output()?;
+        writer.join().expect("Failed to join writer thread")?;
+
+        if !output.status.success() {
+            return Err(FormatError::RustfmtFailed(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+        // non-UTF8 comment should be handled in the tokenize phase,
+        // and not be included in IR.
+        Ok(String::from_utf8(output.stdout).expect("rustfmt output contains non-UTF8 input"))
+    }
+}
+
+/// Format in-process using `prettyplease`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrettyPlease;
+
+impl Formatter for PrettyPlease {
+    fn format(&self, code: &str) -> Result<String, FormatError> {
+        let file = syn::parse_file(code)?;
+        Ok(prettyplease::unparse(&file))
+    }
+}
+
+/// Which [Formatter] is used by [format()]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatterSelection {
+    /// [RustfmtBinary] if `rustfmt` is found in PATH, otherwise [PrettyPlease]
+    #[default]
+    Auto,
+    RustfmtBinary,
+    PrettyPlease,
+    /// Keep generated code as is
+    None,
+}
+
+impl std::str::FromStr for FormatterSelection {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "auto" => Ok(FormatterSelection::Auto),
+            "rustfmt" => Ok(FormatterSelection::RustfmtBinary),
+            "prettyplease" => Ok(FormatterSelection::PrettyPlease),
+            "none" => Ok(FormatterSelection::None),
+            _ => Err(format!(
+                "Unknown formatter '{}', expected one of auto, rustfmt, prettyplease, or none",
+                s
+            )),
+        }
+    }
+}
+
+/// Format generated Rust code by the selected formatter
+pub fn format(code: &str, selection: FormatterSelection) -> Result<String, FormatError> {
+    match selection {
+        FormatterSelection::Auto => match RustfmtBinary.format(code) {
+            Err(FormatError::RustfmtNotFound) => PrettyPlease.format(code),
+            result => result,
+        },
+        FormatterSelection::RustfmtBinary => RustfmtBinary.format(code),
+        FormatterSelection::PrettyPlease => PrettyPlease.format(code),
+        FormatterSelection::None => Ok(code.to_string()),
+    }
+}
+
+/// Format generated Rust code by [PrettyPlease]
+///
+/// Unlike [FormatterSelection::Auto], the output does not depend on `rustfmt` installed on the host,
+/// and thus this is used for snapshot tests of generated code.
 ///
 /// Panics
 /// -------
-/// - `rustfmt` is not found in PATH
-/// - `rustfmt` returns error
-///   - The input of rustfmt is completely generated by this crate,
+/// - Formatter returns error
+///   - The input of formatter is completely generated by this crate,
 ///     and thus it must be a bug, i.e. not a runtime error.
 ///
 pub fn rustfmt(tt: String) -> String {
-    let mut child = Command::new("rustfmt")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .spawn()
-        .expect("Failed to spawn rustfmt process");
-
-    // Write input from another thread for avoiding deadlock.
-    // See https://doc.rust-lang.org/std/process/index.html#handling-io
-    let mut stdin = child.stdin.take().expect("Failed to open stdin");
-    std::thread::spawn(move || {
-        stdin
-            .write_all(tt.as_bytes())
-            .expect("Failed to write to stdin");
-    });
-    let output = child
-        .wait_with_output()
-        .expect("Failed to wait output of rustfmt process");
-
-    // non-UTF8 comment should be handled in the tokenize phase,
-    // and not be included in IR.
-    String::from_utf8(output.stdout).expect("rustfmt output contains non-UTF8 input")
+    format(&tt, FormatterSelection::PrettyPlease).expect("Failed to format generated code")
 }
@@ -0,0 +1,40 @@
+use inflector::Inflector;
+use proc_macro2::TokenStream;
+use quote::*;
+
+use crate::ir::*;
+
+impl ToTokens for Interface {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // Interfaced declarations are copied into this schema by `IR::copy_interfaced_declarations`,
+        // and only the names given by `AS` are re-exported here.
+        for (ty, alias) in &self.resources {
+            let (name, has_holder, is_supertype) = match ty {
+                TypeRef::Entity {
+                    name, is_supertype, ..
+                } => (name, true, *is_supertype),
+                TypeRef::Named {
+                    name, is_enumerate, ..
+                } => (name, !is_enumerate, false),
+                _ => continue,
+            };
+            if name.as_str() == alias {
+                continue;
+            }
+            let mut names = vec![""];
+            if has_holder {
+                names.push("Holder");
+            }
+            if is_supertype {
+                names.extend(["Any", "AnyHolder"]);
+            }
+            for suffix in names {
+                let original = format_ident!("{}{}", name.to_pascal_case(), suffix);
+                let alias = format_ident!("{}{}", alias.to_pascal_case(), suffix);
+                tokens.append_all(quote! {
+                    pub use self::#original as #alias;
+                });
+            }
+        }
+    }
+}
@@ -1,11 +1,31 @@
 //! Generate Rust code using proc-macro utility crates
 
+mod constant;
 mod entity;
 mod format;
+mod interface;
 mod schema;
 mod simple_type;
 mod type_decl;
 mod type_ref;
 
-pub use format::rustfmt;
+pub use format::{
+    format, rustfmt, FormatError, Formatter, FormatterSelection, PrettyPlease, RustfmtBinary,
+};
 pub use schema::*;
+
+use check_keyword::CheckKeyword;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Identifier in snake case, e.g. a field of struct or table
+///
+/// EXPRESS identifiers are case-insensitive, and thus lower-cased.
+fn snake_ident(name: &str) -> syn::Ident {
+    format_ident!("{}", name.to_lowercase().into_safe())
+}
+
+/// `#[holder(generate_serde)]` of holders if [crate::ir::Schema::derive_serde] is enabled
+fn holder_serde(derive_serde: bool) -> Option<TokenStream> {
+    derive_serde.then(|| quote! { #[holder(generate_serde)] })
+}
@@ -1,9 +1,13 @@
-use crate::ir::*;
+use super::snake_ident;
+use crate::{
+    ast::{self, SyntaxTree},
+    ir::*,
+};
 
-use check_keyword::CheckKeyword;
 use inflector::Inflector;
 use proc_macro2::TokenStream;
 use quote::*;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CratePrefix {
@@ -20,6 +24,30 @@ impl CratePrefix {
     }
 }
 
+/// Code generated for the supported subset of schemas, see [generate_degraded]
+#[derive(Debug, Clone)]
+pub struct GenerateOutcome {
+    pub tokens: TokenStream,
+    /// Declarations not generated, with the reason
+    pub skipped: Vec<SkippedItem>,
+}
+
+/// Generate code for declarations which can be generated, and skip the others
+///
+/// Declarations are skipped as [IR::from_syntax_tree_degraded],
+/// and thus `Tables` and `Any` enums contain only the generated entities.
+pub fn generate_degraded(
+    st: &SyntaxTree,
+    options: &Options,
+    prefix: CratePrefix,
+) -> Result<GenerateOutcome, SemanticError> {
+    let (ir, skipped) = IR::from_syntax_tree_degraded(st, options)?;
+    Ok(GenerateOutcome {
+        tokens: ir.to_token_stream(prefix),
+        skipped,
+    })
+}
+
 impl IR {
     pub fn to_token_stream(&self, prefix: CratePrefix) -> TokenStream {
         let schemas: Vec<_> = self
@@ -33,13 +61,24 @@ impl IR {
 
 impl Schema {
     pub fn to_token_stream(&self, prefix: CratePrefix) -> TokenStream {
-        let name = format_ident!("{}", self.name);
-        let types = &self.types;
+        let name = snake_ident(&self.name);
+        let constants = &self.constants;
+        let interfaces = &self.interfaces;
         let entities = &self.entities;
+        let alias_keywords = self.alias_keywords();
+        let aliases_of = |id: &str| alias_keywords.get(id).map_or(&[][..], |a| a.as_slice());
+        let mut types = TokenStream::new();
+        for ty in &self.types {
+            ty.generate(&mut types, self.derive_serde, aliases_of(ty.id()));
+        }
+        let mut entity_defs = TokenStream::new();
+        for entity in entities {
+            entity.generate(&mut entity_defs, self.derive_serde);
+        }
         let type_decls = self
             .types
             .iter()
-            .filter(|e| !matches!(e, TypeDecl::Enumeration(_)));
+            .filter(|e| !matches!(e, TypeDecl::Enumeration(_) | TypeDecl::Alias(_)));
         let entity_types: Vec<_> = entities
             .iter()
             .map(|e| format_ident!("{}", e.name.to_pascal_case()))
@@ -51,31 +90,175 @@ impl Schema {
             .collect();
         let holder_name: Vec<_> = entities
             .iter()
-            .map(|e| format_ident!("{}", e.name.as_str().into_safe()))
-            .chain(
-                type_decls
-                    .clone()
-                    .map(|e| format_ident!("{}", e.id().into_safe())),
-            )
+            .map(|e| snake_ident(&e.name))
+            .chain(type_decls.clone().map(|e| snake_ident(e.id())))
+            .collect();
+        // Records of the aliases are placed in the table of their end of chain
+        let table_aliases: Vec<_> = entities
+            .iter()
+            .map(|_| quote! {})
+            .chain(type_decls.clone().map(|e| {
+                let aliases = aliases_of(e.id());
+                quote! { #( #[table_init(alias = #aliases)] )* }
+            }))
             .collect();
         let holders_name: Vec<_> = entities
             .iter()
-            .map(|e| format_ident!("{}_holders", e.name))
-            .chain(type_decls.map(|e| format_ident!("{}_holders", e.id())))
+            .map(|e| format_ident!("{}_holders", e.name.to_lowercase()))
+            .chain(type_decls.map(|e| format_ident!("{}_holders", e.id().to_lowercase())))
             .collect();
 
+        let entity_holder_name = &holder_name[..entities.len()];
+        let entity_holder_types = &entity_types[..entities.len()];
+        let insert_name: Vec<_> = entities
+            .iter()
+            .map(|e| format_ident!("insert_{}", e.name.to_lowercase()))
+            .collect();
+        let insert_with_id_name: Vec<_> = entities
+            .iter()
+            .map(|e| format_ident!("insert_{}_with_id", e.name.to_lowercase()))
+            .collect();
         let ruststep_path = prefix.as_path();
+        let enumerations = self.types.iter().filter_map(|ty| match ty {
+            TypeDecl::Enumeration(e) => Some(e.impl_to_parameter(&ruststep_path)),
+            _ => None,
+        });
+
+        let check_unique = entities
+            .iter()
+            .filter_map(|e| e.generate_check_unique(&ruststep_path));
+        let check_set = entities
+            .iter()
+            .filter_map(|e| e.generate_check_set(&ruststep_path));
+
+        let inverse_accessors = entities
+            .iter()
+            .map(|e| e.generate_inverse_accessors(entities, &ruststep_path));
+
+        let has_tables = !entity_types.is_empty();
+        let imports = self.imports(prefix, has_tables);
+        // Same as the keywords of fields in `#[derive(TableInit)]`
+        let keywords: Vec<_> = holder_name
+            .iter()
+            .map(|name| name.to_string().to_screaming_snake_case())
+            .collect();
+        let positions: Vec<_> = (0..holder_name.len()).collect();
+        let entity_positions = &positions[..entities.len()];
+        let descriptors =
+            keywords
+                .iter()
+                .zip(&entity_types)
+                .enumerate()
+                .map(|(i, (keyword, ty))| {
+                    entity_descriptor(&ruststep_path, keyword, ty, entities.get(i))
+                });
+        let any_entity = if has_tables {
+            quote! {
+                #[derive(Debug, Clone, PartialEq)]
+                pub enum AnyEntity {
+                    #( #entity_types(Box<#entity_types>), )*
+                }
+            }
+        } else {
+            quote! {}
+        };
+        // Constants are not persisted in `TablesCache`, and must be registered again
+        let skip_serde = self.derive_serde.then(|| quote! { #[serde(skip)] });
+        let keyword_index = if has_tables {
+            quote! {
+                #[table_init(index)]
+                keyword_index: HashMap<u64, usize>,
+                #[table_init(constants)]
+                #skip_serde
+                constants: #ruststep_path::tables::Constants,
+                #[table_init(ids)]
+                id_allocator: #ruststep_path::id::IdAllocator,
+            }
+        } else {
+            quote! {}
+        };
+        let any_accessors = if has_tables {
+            quote! {
+                pub fn constants_mut(&mut self) -> &mut #ruststep_path::tables::Constants {
+                    &mut self.constants
+                }
+
+                /// Reserve ranges of ids not to be assigned by `insert_*`
+                pub fn id_allocator_mut(&mut self) -> &mut #ruststep_path::id::IdAllocator {
+                    &mut self.id_allocator
+                }
+
+                pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                    const KEYWORDS: &[&str] = &[#(#keywords),*];
+                    self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+                }
+
+                pub fn get_any(&self, id: u64) -> #ruststep_path::error::Result<AnyEntity> {
+                    use #ruststep_path::tables::EntityTable;
+                    Ok(match self.keyword_index.get(&id) {
+                        #(
+                        Some(#positions) => AnyEntity::#entity_types(Box::new(
+                            EntityTable::<as_holder!(#entity_types)>::get_owned(self, id)?
+                        )),
+                        )*
+                        _ => return Err(#ruststep_path::error::Error::UnknownEntity(id)),
+                    })
+                }
+
+                pub fn any_iter(&self) -> Box<dyn Iterator<Item = #ruststep_path::error::Result<AnyEntity>> + '_> {
+                    #ruststep_path::tables::any_iter(self.keyword_index.keys().copied(), move |id| self.get_any(id))
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        let derive_serde = if self.derive_serde {
+            let serde_crate = format!("{}::serde", quote!(#ruststep_path)).replace(' ', "");
+            quote! {
+                #[derive(#ruststep_path::serde::Serialize, #ruststep_path::serde::Deserialize)]
+                #[serde(crate = #serde_crate)]
+            }
+        } else {
+            quote! {}
+        };
+        let cache = if self.derive_serde {
+            quote! {
+                pub fn to_cache(&self) -> #ruststep_path::cache::TablesCache<&Self> {
+                    #ruststep_path::cache::TablesCache::new(self)
+                }
+
+                pub fn from_cache(cache: #ruststep_path::cache::TablesCache<Self>) -> #ruststep_path::error::Result<Self> {
+                    cache.into_tables()
+                }
+            }
+        } else {
+            quote! {}
+        };
+        let impl_cached_tables = if self.derive_serde {
+            quote! {
+                impl #ruststep_path::cache::CachedTables for Tables {
+                    fn schema_fingerprint() -> u64 {
+                        #ruststep_path::cache::schema_fingerprint(SCHEMA_REGISTRY)
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        };
 
         quote! {
             pub mod #name {
-                use #ruststep_path::{as_holder, Holder, TableInit, primitive::*, derive_more::*};
-                use std::collections::HashMap;
+                #imports
 
                 #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+                #derive_serde
                 pub struct Tables {
                     #(
+                    #table_aliases
                     #holder_name: HashMap<u64, as_holder!(#entity_types)>,
                     )*
+                    #keyword_index
                 }
 
                 impl Tables {
@@ -84,11 +267,249 @@ impl Schema {
                         &self.#holder_name
                     }
                     )*
+
+                    #(
+                    pub fn #insert_name(
+                        &mut self,
+                        value: as_holder!(#entity_holder_types),
+                    ) -> #ruststep_path::error::Result<u64> {
+                        let id = #ruststep_path::tables::TableIds::next_id(self)?;
+                        self.#entity_holder_name.insert(id, value);
+                        self.keyword_index.insert(id, #entity_positions);
+                        Ok(id)
+                    }
+
+                    pub fn #insert_with_id_name(
+                        &mut self,
+                        id: u64,
+                        value: as_holder!(#entity_holder_types),
+                    ) -> #ruststep_path::error::Result<()> {
+                        if #ruststep_path::tables::TableIds::contains_id(self, id) {
+                            return Err(#ruststep_path::error::Error::DuplicatedEntity(id));
+                        }
+                        self.#entity_holder_name.insert(id, value);
+                        self.keyword_index.insert(id, #entity_positions);
+                        Ok(())
+                    }
+                    )*
+
+                    #(#check_unique)*
+                    #(#check_set)*
+                    #(#inverse_accessors)*
+
+                    #any_accessors
+
+                    /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+                    pub fn descriptor_for(
+                        keyword: &str,
+                    ) -> Option<&'static #ruststep_path::tables::EntityDescriptor<Tables>> {
+                        SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+                    }
+
+                    pub fn to_data_section(&self) -> #ruststep_path::ast::DataSection {
+                        #ruststep_path::tables::data_section(
+                            ::std::iter::empty()
+                                #( .chain(#ruststep_path::tables::instances(&self.#holder_name)) )*
+                        )
+                    }
+
+                    pub fn to_step_string(&self, header: &#ruststep_path::header::Header) -> String {
+                        #ruststep_path::tables::step_string(header, self.to_data_section())
+                    }
+
+                    /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+                    pub fn schema_fingerprint() -> u64 {
+                        #ruststep_path::cache::schema_fingerprint(SCHEMA_REGISTRY)
+                    }
+
+                    #cache
+
+                    pub fn check_all(&self) -> Vec<#ruststep_path::tables::EntityError> {
+                        #ruststep_path::tables::sorted_errors(
+                            ::std::iter::empty()
+                                #( .chain(#ruststep_path::tables::table_errors(self, &self.#holder_name)) )*
+                        )
+                    }
                 }
 
-                #(#types)*
-                #(#entities)*
+                #impl_cached_tables
+                #any_entity
+
+                /// Entities and types in [Tables] described at runtime
+                pub static SCHEMA_REGISTRY: &[#ruststep_path::tables::EntityDescriptor<Tables>] = &[
+                    #(#descriptors,)*
+                ];
+
+                #(#constants)*
+                #(#interfaces)*
+                #types
+                #(#enumerations)*
+                #entity_defs
+            }
+        }
+    }
+
+    /// Keywords of [Alias]es to each type declared in this schema, e.g. `C` for `b` of `TYPE c = b;`
+    ///
+    /// Only wrapper structs of [Simple] and [Rename] take them. A typed enumeration value is read
+    /// regardless of its keyword, and a [Select] is typed by the keyword of the selected type.
+    fn alias_keywords(&self) -> HashMap<&str, Vec<String>> {
+        let scope = Scope::root().pushed(ScopeType::Schema, &self.name);
+        let is_wrapper = |name: &str| {
+            self.types.iter().any(|ty| {
+                matches!(ty, TypeDecl::Simple(_) | TypeDecl::Rename(_)) && ty.id() == name
+            })
+        };
+        let mut keywords: HashMap<&str, Vec<String>> = HashMap::new();
+        for ty in &self.types {
+            if let TypeDecl::Alias(Alias {
+                id,
+                ty: TypeRef::Named {
+                    name, scope: end, ..
+                },
+            }) = ty
+            {
+                if *end == scope && is_wrapper(name) {
+                    keywords
+                        .entry(name)
+                        .or_default()
+                        .push(id.to_screaming_snake_case());
+                }
+            }
+        }
+        keywords
+    }
+
+    /// Generate `use` declarations only for items used in this schema
+    fn imports(&self, prefix: CratePrefix, has_tables: bool) -> TokenStream {
+        let ruststep_path = prefix.as_path();
+        let mut items = Vec::new();
+        if has_tables {
+            items.push(quote! { as_holder });
+        }
+
+        let derive_more: Vec<_> = ["AsMut", "AsRef", "Deref", "DerefMut", "From", "Into"]
+            .into_iter()
+            .filter(|derive| {
+                self.entities
+                    .iter()
+                    .any(|e| e.derive_more_derives().contains(derive))
+                    || self
+                        .types
+                        .iter()
+                        .any(|ty| ty.derive_more_derives().contains(derive))
+            })
+            .map(|derive| format_ident!("{}", derive))
+            .collect();
+        if !derive_more.is_empty() {
+            items.push(quote! { derive_more::{#(#derive_more),*} });
+        }
+
+        let uses_wrapped = self
+            .entities
+            .iter()
+            .any(|e| e.attributes.iter().any(|attr| attr.wrapped));
+        match (self.uses_logical(), uses_wrapped) {
+            (true, true) => items.push(quote! { primitive::{Logical, Wrapped} }),
+            (true, false) => items.push(quote! { primitive::Logical }),
+            (false, true) => items.push(quote! { primitive::Wrapped }),
+            (false, false) => {}
+        }
+
+        // Cache of derived attributes
+        if self
+            .entities
+            .iter()
+            .any(|e| !e.evaluated_derived().is_empty())
+        {
+            items.push(quote! { tables::Memo });
+        }
+
+        // `Holder` derive for ENTITY, SELECT, and `Any` enum
+        let has_select = self
+            .types
+            .iter()
+            .any(|ty| matches!(ty, TypeDecl::Select(_)));
+        if !self.entities.is_empty() || has_select {
+            items.push(quote! { Holder });
+        }
+        items.push(quote! { TableInit });
+
+        let mut imports = quote! {
+            use #ruststep_path::{#(#items),*};
+        };
+        if has_tables {
+            imports.append_all(quote! {
+                use std::collections::HashMap;
+            });
+        }
+        imports
+    }
+
+    fn uses_logical(&self) -> bool {
+        fn is_logical(ty: &TypeRef) -> bool {
+            match ty {
+                TypeRef::SimpleType(SimpleType(ty)) => matches!(ty, ast::SimpleType::Logical),
+                TypeRef::Set { base, .. }
+                | TypeRef::Bag { base, .. }
+                | TypeRef::List { base, .. }
+                | TypeRef::Array { base, .. } => is_logical(base),
+                _ => false,
             }
         }
+        self.types.iter().any(|ty| match ty {
+            TypeDecl::Simple(simple) => matches!(simple.ty, SimpleType(ast::SimpleType::Logical)),
+            TypeDecl::Rename(Rename { ty, .. }) | TypeDecl::Alias(Alias { ty, .. }) => {
+                is_logical(ty)
+            }
+            _ => false,
+        }) || self
+            .entities
+            .iter()
+            .any(|e| e.attributes.iter().any(|attr| is_logical(&attr.ty)))
+    }
+}
+
+/// `EntityDescriptor` of `SCHEMA_REGISTRY`, whose attributes are empty for types
+fn entity_descriptor(
+    ruststep_path: &syn::Path,
+    keyword: &str,
+    ty: &syn::Ident,
+    entity: Option<&Entity>,
+) -> TokenStream {
+    let (supertypes, attributes) = match entity {
+        Some(e) => (
+            e.supertypes
+                .iter()
+                .map(|ty| match ty {
+                    TypeRef::Entity { name, .. } => name.to_screaming_snake_case(),
+                    _ => unreachable!(),
+                })
+                .collect(),
+            e.attributes
+                .iter()
+                .map(|attr| {
+                    let name = &attr.name;
+                    let ty = attr.ty.express_name();
+                    let optional = attr.optional;
+                    quote! {
+                        #ruststep_path::tables::AttributeDescriptor {
+                            name: #name,
+                            ty: #ty,
+                            optional: #optional,
+                        }
+                    }
+                })
+                .collect(),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+    quote! {
+        #ruststep_path::tables::EntityDescriptor {
+            keyword: #keyword,
+            supertypes: &[#(#supertypes),*],
+            attributes: &[#(#attributes),*],
+            get: #ruststep_path::tables::get_debug::<Tables, as_holder!(#ty)>,
+        }
     }
 }
@@ -2,25 +2,49 @@ use inflector::Inflector;
 use proc_macro2::TokenStream;
 use quote::*;
 
+use super::{entity::use_place_holder, holder_serde, snake_ident};
 use crate::ir::*;
 
-impl ToTokens for TypeDecl {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+/// Derive macros of `derive_more` used for the wrapper struct of simple and rename types
+const WRAPPER_DERIVE_MORE: [&str; 5] = ["AsRef", "Deref", "DerefMut", "Into", "From"];
+
+impl TypeDecl {
+    /// Derive macros of `derive_more` used for this type
+    pub(super) fn derive_more_derives(&self) -> &'static [&'static str] {
         match self {
-            TypeDecl::Simple(simple) => simple.to_tokens(tokens),
-            TypeDecl::Rename(rename) => rename.to_tokens(tokens),
-            TypeDecl::Enumeration(e) => e.to_tokens(tokens),
-            TypeDecl::Select(select) => select.to_tokens(tokens),
+            TypeDecl::Simple(_) | TypeDecl::Rename(_) => &WRAPPER_DERIVE_MORE,
+            _ => &[],
         }
     }
 }
 
-impl ToTokens for Simple {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let field_name = format_ident!("{}", &self.id.to_snake_case());
+impl TypeDecl {
+    /// Generate the type, with serde derives if `derive_serde`
+    ///
+    /// `aliases` are keywords of [Alias]es to this type, e.g. `C` for `TYPE c = b;`,
+    /// which are also accepted as keywords of records and typed parameters of this type.
+    pub(super) fn generate(
+        &self,
+        tokens: &mut TokenStream,
+        derive_serde: bool,
+        aliases: &[String],
+    ) {
+        match self {
+            TypeDecl::Simple(simple) => simple.generate(tokens, derive_serde, aliases),
+            TypeDecl::Rename(rename) => rename.generate(tokens, derive_serde, aliases),
+            TypeDecl::Enumeration(e) => e.generate(tokens, derive_serde),
+            TypeDecl::Select(select) => select.generate(tokens, derive_serde),
+            TypeDecl::Alias(alias) => alias.to_tokens(tokens),
+        }
+    }
+}
+
+impl Simple {
+    fn generate(&self, tokens: &mut TokenStream, derive_serde: bool, aliases: &[String]) {
+        let field_name = snake_ident(&self.id);
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let ty = &self.ty;
-        let (derive, _) = simple_meta(&field_name);
+        let (derive, _) = simple_meta(&field_name, derive_serde, aliases);
         tokens.append_all(quote! {
             #derive
             pub struct #id(pub #ty);
@@ -28,22 +52,16 @@ impl ToTokens for Simple {
     }
 }
 
-impl ToTokens for Rename {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let field_name = format_ident!("{}", &self.id.to_snake_case());
+impl Rename {
+    fn generate(&self, tokens: &mut TokenStream, derive_serde: bool, aliases: &[String]) {
+        let field_name = snake_ident(&self.id);
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let ty = &self.ty;
-        let (derive, use_place_holder) = match ty {
-            TypeRef::SimpleType(_) => simple_meta(&field_name),
-            TypeRef::Named { is_enumerate, .. } => {
-                // Enumeration does not have Holder.
-                if *is_enumerate {
-                    simple_meta(&field_name)
-                } else {
-                    rename_meta(&field_name)
-                }
-            }
-            _ => rename_meta(&field_name),
+        // Enumeration and aggregates of simple types do not have Holder.
+        let (derive, use_place_holder) = if use_place_holder(ty) {
+            rename_meta(&field_name, derive_serde, aliases)
+        } else {
+            simple_meta(&field_name, derive_serde, aliases)
         };
 
         tokens.append_all(quote! {
@@ -53,40 +71,74 @@ impl ToTokens for Rename {
     }
 }
 
-fn simple_meta(field_name: &syn::Ident) -> (TokenStream, TokenStream) {
-    (
-        quote! {
-            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder)]
-            #[holder(table = Tables)]
-            #[holder(field = #field_name)]
-            #[holder(generate_deserialize)]
-        },
-        quote! {},
-    )
+impl ToTokens for Alias {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let id = format_ident!("{}", &self.id.to_pascal_case());
+        let ty = &self.ty;
+        tokens.append_all(quote! {
+            pub type #id = #ty;
+        });
+        // Enumeration does not have Holder.
+        if let TypeRef::Named {
+            name,
+            is_enumerate: false,
+            ..
+        } = ty
+        {
+            let holder = format_ident!("{}Holder", &self.id.to_pascal_case());
+            let ty_holder = format_ident!("{}Holder", name.to_pascal_case());
+            tokens.append_all(quote! {
+                pub type #holder = #ty_holder;
+            });
+        }
+    }
 }
 
-fn rename_meta(field_name: &syn::Ident) -> (TokenStream, TokenStream) {
+fn wrapper_derive(field_name: &syn::Ident, derive_serde: bool, aliases: &[String]) -> TokenStream {
+    let derive_more = WRAPPER_DERIVE_MORE.iter().map(|d| format_ident!("{}", d));
+    let generate_serde = holder_serde(derive_serde);
+    quote! {
+        #[derive(Clone, Debug, PartialEq, #(#derive_more,)* ::ruststep_derive::Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = #field_name)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #generate_serde
+        #( #[holder(alias = #aliases)] )*
+    }
+}
+
+fn simple_meta(
+    field_name: &syn::Ident,
+    derive_serde: bool,
+    aliases: &[String],
+) -> (TokenStream, TokenStream) {
+    (wrapper_derive(field_name, derive_serde, aliases), quote! {})
+}
+
+fn rename_meta(
+    field_name: &syn::Ident,
+    derive_serde: bool,
+    aliases: &[String],
+) -> (TokenStream, TokenStream) {
     (
-        quote! {
-            #[derive(Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, ::ruststep_derive::Holder)]
-            #[holder(table = Tables)]
-            #[holder(field = #field_name)]
-            #[holder(generate_deserialize)]
-        },
+        wrapper_derive(field_name, derive_serde, aliases),
         quote! {#[holder(use_place_holder)]},
     )
 }
 
-impl ToTokens for Enumeration {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Enumeration {
+    fn generate(&self, tokens: &mut TokenStream, derive_serde: bool) {
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let items: Vec<_> = self
             .items
             .iter()
             .map(|i| format_ident!("{}", i.to_pascal_case()))
             .collect();
+        let serialize = derive_serde.then(|| quote! { ::serde::Serialize, });
         tokens.append_all(quote! {
-            #[derive(Debug, Clone, PartialEq, ::serde::Deserialize)]
+            #[derive(Debug, Clone, PartialEq, #serialize ::serde::Deserialize)]
             pub enum #id {
                 #( #items ),*
             }
@@ -94,8 +146,31 @@ impl ToTokens for Enumeration {
     }
 }
 
-impl ToTokens for Select {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+impl Enumeration {
+    /// `impl ToParameter` writing each item as an enumeration value, e.g. `.ITEM.`
+    pub(super) fn impl_to_parameter(&self, ruststep_path: &syn::Path) -> TokenStream {
+        let id = format_ident!("{}", &self.id.to_pascal_case());
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|i| format_ident!("{}", i.to_pascal_case()))
+            .collect();
+        let values: Vec<_> = self.items.iter().map(|i| i.to_uppercase()).collect();
+        quote! {
+            impl #ruststep_path::tables::ToParameter for #id {
+                fn to_parameter(&self) -> #ruststep_path::ast::Parameter {
+                    let value = match self {
+                        #( #id::#items => #values, )*
+                    };
+                    #ruststep_path::ast::Parameter::Enumeration(value.to_string())
+                }
+            }
+        }
+    }
+}
+
+impl Select {
+    fn generate(&self, tokens: &mut TokenStream, derive_serde: bool) {
         let id = format_ident!("{}", &self.id.to_pascal_case());
         let mut entries = Vec::new();
         let mut entry_types = Vec::new();
@@ -128,10 +203,14 @@ impl ToTokens for Select {
                 _ => unimplemented!(),
             }
         }
+        let generate_serde = holder_serde(derive_serde);
         tokens.append_all(quote! {
             #[derive(Debug, Clone, PartialEq, Holder)]
             #[holder(table = Tables)]
             #[holder(generate_deserialize)]
+            #[holder(generate_into_holder)]
+            #[holder(generate_to_record)]
+            #generate_serde
             pub enum #id {
                 #(
                 #use_place_holder
@@ -1,5 +1,5 @@
 use inflector::Inflector;
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::*;
 
 use crate::ir::*;
@@ -23,9 +23,56 @@ impl ToTokens for TypeRef {
                 };
                 tokens.append_all(quote! { #name });
             }
-            Set { base, .. } | List { base, .. } => {
+            Set { base, .. } | Bag { base, .. } | List { base, .. } => {
                 tokens.append_all(quote! { Vec<#base> });
             }
+            Array { base, optional, .. } => {
+                let element = if *optional {
+                    quote! { Option<#base> }
+                } else {
+                    quote! { #base }
+                };
+                match self.array_size() {
+                    Some(size) => {
+                        let size = Literal::usize_unsuffixed(size);
+                        tokens.append_all(quote! { [#element; #size] })
+                    }
+                    None => tokens.append_all(quote! { Vec<#element> }),
+                }
+            }
+        }
+    }
+}
+
+impl TypeRef {
+    /// Type in EXPRESS, e.g. `LIST OF CARTESIAN_POINT`, used for `SCHEMA_REGISTRY`
+    pub(super) fn express_name(&self) -> String {
+        use TypeRef::*;
+        match self {
+            SimpleType(ty) => {
+                use crate::ast::SimpleType::*;
+                match ty.0 {
+                    Number => "NUMBER",
+                    Real => "REAL",
+                    Integer => "INTEGER",
+                    Logical => "LOGICAL",
+                    Boolen => "BOOLEAN",
+                    String_ { .. } => "STRING",
+                    Binary { .. } => "BINARY",
+                }
+                .to_string()
+            }
+            Named { name, .. } | Entity { name, .. } => name.to_screaming_snake_case(),
+            Set { base, .. } => format!("SET OF {}", base.express_name()),
+            Bag { base, .. } => format!("BAG OF {}", base.express_name()),
+            List { base, .. } => format!("LIST OF {}", base.express_name()),
+            Array { base, optional, .. } => {
+                if *optional {
+                    format!("ARRAY OF OPTIONAL {}", base.express_name())
+                } else {
+                    format!("ARRAY OF {}", base.express_name())
+                }
+            }
         }
     }
 }
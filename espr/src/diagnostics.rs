@@ -0,0 +1,552 @@
+//! Diagnostics of EXPRESS schema for editor tooling
+//!
+//! [check] parses and legalizes EXPRESS schema, and reports every problem found
+//! as [Diagnostic] in the manner of Language Server Protocol:
+//!
+//! ```
+//! use espr::diagnostics::*;
+//!
+//! let diagnostics = espr::check(
+//!     r#"
+//! SCHEMA s;
+//!   ENTITY a;
+//!     x: undefined_type;
+//!   END_ENTITY;
+//! END_SCHEMA;
+//! "#,
+//! );
+//! assert_eq!(diagnostics.len(), 1);
+//! assert_eq!(diagnostics[0].code, "type-not-found");
+//! assert_eq!(diagnostics[0].range.start, Position { line: 3, column: 7 });
+//! assert_eq!(diagnostics[0].range.end, Position { line: 3, column: 21 });
+//! ```
+//!
+//! AST does not keep positions in the source,
+//! and thus ranges are determined by looking up identifiers of declarations in the source.
+
+use crate::{ast::SyntaxTree, ir::*};
+use nom::error::{VerboseError, VerboseErrorKind};
+use std::{
+    collections::HashSet,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Position in the source. Both line and column start from zero, and column counts characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Range in the source, `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// Another location related to a [Diagnostic],
+/// e.g. the previous declaration of duplicated names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelatedInfo {
+    pub range: Range,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    /// Kind of the problem, e.g. `syntax-error` or `type-not-found`
+    pub code: &'static str,
+    pub message: String,
+    pub related: Vec<RelatedInfo>,
+}
+
+impl Diagnostic {
+    /// Convert an error of [SyntaxTree::parse] for `source`
+    pub fn from_parse_error(source: &str, err: &VerboseError<&str>) -> Self {
+        let map = SourceMap::new(source);
+        map.parse_error(err)
+    }
+
+    /// Convert an error while legalizing `st` parsed from `source`
+    pub fn from_semantic_error(source: &str, st: &SyntaxTree, err: &SemanticError) -> Self {
+        let map = SourceMap::new(source);
+        let ns = Namespace::new(st);
+        map.semantic_error(&ns, err)
+    }
+}
+
+/// Parse and legalize EXPRESS schema, and returns all diagnostics found
+///
+/// Legalization continues for each entity and type declaration after an error is found.
+/// Parsing stops at the first syntax error since the remaining input cannot be recovered.
+/// This never panics, and a panic in espr is reported as an `internal-error` diagnostic.
+pub fn check(source: &str) -> Vec<Diagnostic> {
+    match panic::catch_unwind(AssertUnwindSafe(|| check_unwind(source))) {
+        Ok(diagnostics) => diagnostics,
+        Err(payload) => {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            vec![Diagnostic {
+                range: Range::default(),
+                severity: Severity::Error,
+                code: "internal-error",
+                message: format!("espr cannot process this schema: {}", reason),
+                related: Vec::new(),
+            }]
+        }
+    }
+}
+
+fn check_unwind(source: &str) -> Vec<Diagnostic> {
+    let map = SourceMap::new(source);
+    let st = match SyntaxTree::parse(source) {
+        Ok(st) => st,
+        Err(err) => return vec![map.parse_error(&err)],
+    };
+    let ns = Namespace::new(&st);
+
+    let mut errors = Vec::new();
+    let mut declared = HashSet::new();
+    for (path, _ast) in &ns.ast {
+        if !declared.insert(path) {
+            errors.push(SemanticError::DuplicatedDeclaration(*path));
+        }
+    }
+
+    let ss = match Constraints::new(&ns, &st) {
+        Ok(ss) => ss,
+        Err(err) => {
+            errors.push(err);
+            return errors
+                .iter()
+                .map(|err| map.semantic_error(&ns, err))
+                .collect();
+        }
+    };
+
+    // Legalize each declaration separately to collect errors as many as possible.
+    // Types are resolved in the schema scope, and the scope of errors are narrowed into
+    // the declaration to locate the reference.
+    let narrow = |err: SemanticError, scope: Scope| match err {
+        SemanticError::TypeNotFound { name, .. } => SemanticError::TypeNotFound { name, scope },
+        SemanticError::Unsupported { construct, .. } => {
+            SemanticError::Unsupported { construct, scope }
+        }
+        err => err,
+    };
+    for schema in &st.schemas {
+        let here = Scope::root().schema(&schema.name);
+        for entity in &schema.entities {
+            let mut entity_errors: Vec<_> = entity
+                .attributes
+                .iter()
+                .filter_map(|attr| EntityAttribute::legalize(&ns, &ss, &here, attr).err())
+                .collect();
+            if entity_errors.is_empty() {
+                entity_errors.extend(Entity::legalize(&ns, &ss, &here, entity).err());
+            }
+            errors.extend(
+                entity_errors
+                    .into_iter()
+                    .map(|err| narrow(err, here.entity(&entity.name))),
+            );
+        }
+        for ty in &schema.types {
+            if let Err(err) = TypeDecl::legalize(&ns, &ss, &here, ty) {
+                errors.push(narrow(err, here.r#type(&ty.type_id)));
+            }
+        }
+    }
+    if errors.is_empty() {
+        if let Err(err) = IR::from_syntax_tree(&st) {
+            errors.push(err);
+        }
+    }
+
+    // A cycle is found from each type in it
+    let mut cycles = HashSet::new();
+    errors.retain(|err| match err {
+        SemanticError::CyclicTypeDefinition(paths) => {
+            let mut names: Vec<_> = paths.iter().map(|p| p.to_string()).collect();
+            names.sort();
+            names.dedup();
+            cycles.insert(names)
+        }
+        _ => true,
+    });
+
+    errors
+        .iter()
+        .map(|err| map.semantic_error(&ns, err))
+        .collect()
+}
+
+/// Declaration of SCHEMA, ENTITY, or TYPE found in the source
+struct Declaration {
+    schema: String,
+    ty: ScopeType,
+    /// Byte range of the declared identifier
+    name: (usize, usize),
+    /// Byte range from the keyword to the end keyword, e.g. `ENTITY` to `END_ENTITY`
+    body: (usize, usize),
+}
+
+struct SourceMap<'source> {
+    source: &'source str,
+    /// Byte ranges of identifiers and keywords, except in remarks and string literals
+    identifiers: Vec<(usize, usize)>,
+    declarations: Vec<Declaration>,
+}
+
+impl<'source> SourceMap<'source> {
+    fn new(source: &'source str) -> Self {
+        let identifiers = identifiers(source);
+        let text = |(start, end): (usize, usize)| &source[start..end];
+
+        let mut declarations: Vec<Declaration> = Vec::new();
+        let mut schema = String::new();
+        for (i, token) in identifiers.iter().enumerate() {
+            let keyword = text(*token).to_uppercase();
+            let ty = match keyword.as_str() {
+                "SCHEMA" => ScopeType::Schema,
+                "ENTITY" => ScopeType::Entity,
+                "TYPE" => ScopeType::Type,
+                "END_SCHEMA" | "END_ENTITY" | "END_TYPE" => {
+                    let ty = match keyword.as_str() {
+                        "END_SCHEMA" => ScopeType::Schema,
+                        "END_ENTITY" => ScopeType::Entity,
+                        _ => ScopeType::Type,
+                    };
+                    if let Some(decl) = declarations.iter_mut().rev().find(|d| d.ty == ty) {
+                        decl.body.1 = token.1;
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let name = match identifiers.get(i + 1) {
+                Some(name) => *name,
+                None => continue,
+            };
+            if ty == ScopeType::Schema {
+                schema = text(name).to_string();
+            }
+            declarations.push(Declaration {
+                schema: schema.clone(),
+                ty,
+                name,
+                body: (token.0, source.len()),
+            });
+        }
+
+        SourceMap {
+            source,
+            identifiers,
+            declarations,
+        }
+    }
+
+    fn text(&self, (start, end): (usize, usize)) -> &str {
+        &self.source[start..end]
+    }
+
+    fn position(&self, offset: usize) -> Position {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Position {
+            line: before.matches('\n').count(),
+            column: before[line_start..].chars().count(),
+        }
+    }
+
+    fn range(&self, (start, end): (usize, usize)) -> Range {
+        Range {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+
+    /// Declarations of the path in the order of the source
+    fn declarations(&self, path: &Path) -> Vec<&Declaration> {
+        let schema = match path.ty {
+            ScopeType::Schema => path.name.as_str(),
+            _ => match path.scope.iter().next() {
+                Some((ScopeType::Schema, schema)) => schema,
+                _ => return Vec::new(),
+            },
+        };
+        self.declarations
+            .iter()
+            .filter(|decl| {
+                decl.ty == path.ty
+                    && decl.schema.eq_ignore_ascii_case(schema)
+                    && self.text(decl.name).eq_ignore_ascii_case(&path.name)
+            })
+            .collect()
+    }
+
+    /// Declaration of the innermost scope
+    fn scope(&self, scope: &Scope) -> Option<&Declaration> {
+        let (ty, name) = scope.iter().last()?;
+        let path = Path::new(&scope.popped()?, ty, name);
+        self.declarations(&path).into_iter().next()
+    }
+
+    /// The first reference of `name` in `scope`, except for the declared identifiers
+    fn reference(&self, scope: &Scope, name: &str) -> Option<(usize, usize)> {
+        let body = self
+            .scope(scope)
+            .map(|decl| decl.body)
+            .unwrap_or((0, self.source.len()));
+        self.identifiers.iter().copied().find(|token| {
+            body.0 <= token.0
+                && token.1 <= body.1
+                && self.text(*token).eq_ignore_ascii_case(name)
+                && !self.declarations.iter().any(|decl| decl.name == *token)
+        })
+    }
+
+    fn path_range(&self, path: &Path) -> Range {
+        self.declarations(path)
+            .first()
+            .map(|decl| self.range(decl.name))
+            .unwrap_or_default()
+    }
+
+    fn parse_error(&self, err: &VerboseError<&str>) -> Diagnostic {
+        let (rest, kind) = match err.errors.first() {
+            Some(error) => error,
+            None => {
+                return Diagnostic {
+                    range: Range::default(),
+                    severity: Severity::Error,
+                    code: "syntax-error",
+                    message: "Syntax error".to_string(),
+                    related: Vec::new(),
+                }
+            }
+        };
+        // `rest` is a suffix of the source
+        let start = (rest.as_ptr() as usize)
+            .checked_sub(self.source.as_ptr() as usize)
+            .filter(|offset| *offset <= self.source.len())
+            .unwrap_or(0);
+        let end = match self.identifiers.iter().find(|token| token.0 == start) {
+            Some(token) => token.1,
+            None => self.source[start..]
+                .chars()
+                .next()
+                .map_or(start, |c| start + c.len_utf8()),
+        };
+        let found = match &self.source[start..end] {
+            "" => "end of input".to_string(),
+            token => format!("`{}`", token),
+        };
+        let message = match kind {
+            VerboseErrorKind::Char(c) => format!("Syntax error: expected `{}`, found {}", c, found),
+            VerboseErrorKind::Context(context) => {
+                format!("Syntax error: expected {}, found {}", context, found)
+            }
+            VerboseErrorKind::Nom(_) => format!("Syntax error: unexpected {}", found),
+        };
+        Diagnostic {
+            range: self.range((start, end)),
+            severity: Severity::Error,
+            code: "syntax-error",
+            message,
+            related: Vec::new(),
+        }
+    }
+
+    fn semantic_error(&self, ns: &Namespace, err: &SemanticError) -> Diagnostic {
+        let mut related = Vec::new();
+        let (code, range) = match err {
+            SemanticError::TypeNotFound { name, scope } => {
+                if let Some(candidate) = similar_name(ns, scope, name) {
+                    related.push(RelatedInfo {
+                        range: self.path_range(&candidate),
+                        message: format!("did you mean `{}`?", candidate.name),
+                    });
+                }
+                let range = match self.reference(scope, name) {
+                    Some(token) => self.range(token),
+                    None => self
+                        .scope(scope)
+                        .map(|decl| self.range(decl.name))
+                        .unwrap_or_default(),
+                };
+                ("type-not-found", range)
+            }
+            SemanticError::InvalidPath(path) => ("invalid-path", self.path_range(path)),
+            SemanticError::DuplicatedDeclaration(path) => {
+                let declarations = self.declarations(path);
+                let range = match declarations.as_slice() {
+                    [first, second, ..] => {
+                        related.push(RelatedInfo {
+                            range: self.range(first.name),
+                            message: format!("previous declaration of `{}`", path.name),
+                        });
+                        self.range(second.name)
+                    }
+                    [first] => self.range(first.name),
+                    [] => Range::default(),
+                };
+                ("duplicated-declaration", range)
+            }
+            SemanticError::CyclicTypeDefinition(paths) => {
+                // Report at the first declaration in the source
+                let mut paths: Vec<&Path> = paths.iter().collect();
+                paths.sort_by_key(|path| self.declarations(path).first().map(|decl| decl.name));
+                paths.dedup();
+                for path in paths.iter().skip(1) {
+                    related.push(RelatedInfo {
+                        range: self.path_range(path),
+                        message: format!("`{}` is a part of the cycle", path.name),
+                    });
+                }
+                let range = paths
+                    .first()
+                    .map(|path| self.path_range(path))
+                    .unwrap_or_default();
+                ("cyclic-type-definition", range)
+            }
+            SemanticError::Unsupported { scope, .. } => {
+                let range = self
+                    .scope(scope)
+                    .map(|decl| self.range(decl.name))
+                    .unwrap_or_default();
+                ("unsupported", range)
+            }
+        };
+        Diagnostic {
+            range,
+            severity: Severity::Error,
+            code,
+            message: err.to_string(),
+            related,
+        }
+    }
+}
+
+/// Byte ranges of identifiers and keywords, skipping remarks, strings, and numbers
+fn identifiers(source: &str) -> Vec<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let starts_with = |i: usize, pattern: &[u8]| bytes[i..].starts_with(pattern);
+    let mut identifiers = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // Embedded remark, which may be nested
+            b'(' if starts_with(i, b"(*") => {
+                let mut depth = 0;
+                while i < bytes.len() {
+                    if starts_with(i, b"(*") {
+                        depth += 1;
+                        i += 2;
+                    } else if starts_with(i, b"*)") {
+                        depth -= 1;
+                        i += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            // Tail remark
+            b'-' if starts_with(i, b"--") => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            // Simple string literal, where `''` is an escaped quote
+            b'\'' => {
+                i += 1;
+                while i < bytes.len() {
+                    if starts_with(i, b"''") {
+                        i += 2;
+                    } else if bytes[i] == b'\'' {
+                        i += 1;
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+            }
+            // Encoded string literal
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            c if c.is_ascii_alphanumeric() || c == b'_' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                if c.is_ascii_alphabetic() {
+                    identifiers.push((start, i));
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    identifiers
+}
+
+/// The most similar name visible from the scope, for suggestion of typo
+fn similar_name(ns: &Namespace, scope: &Scope, name: &str) -> Option<Path> {
+    let name = name.to_lowercase();
+    let threshold = (name.chars().count() / 3).max(1);
+    let mut scope = Some(*scope);
+    let mut best: Option<(usize, Path)> = None;
+    while let Some(current) = scope {
+        for (ty, candidate, _index) in ns.names.get(&current).into_iter().flatten() {
+            let distance = edit_distance(&name, &candidate.to_lowercase());
+            if distance <= threshold && best.as_ref().map_or(true, |(d, _)| distance < *d) {
+                best = Some((
+                    distance,
+                    Path {
+                        scope: current,
+                        ty: *ty,
+                        name: *candidate,
+                    },
+                ));
+            }
+        }
+        scope = current.popped();
+    }
+    best.map(|(_, path)| path)
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
@@ -0,0 +1,121 @@
+use super::*;
+use std::collections::HashMap;
+
+/// Defined type is identified by the scope where it is declared and its name
+type Key = (Scope, Symbol);
+
+impl IR {
+    /// Collapse chains of renames between defined types
+    ///
+    /// ```text
+    /// TYPE a = b; END_TYPE;
+    /// TYPE b = c; END_TYPE;
+    /// TYPE c = REAL; END_TYPE;
+    /// ```
+    ///
+    /// - A [Rename] to another defined type without `WHERE` rules is a hop of chain,
+    ///   and it is replaced by an [Alias] to the end of chain.
+    ///   In this example, both `a` and `b` become aliases of `c`.
+    /// - A [Rename] with `WHERE` rules is kept as is since it validates its value,
+    ///   and chains passing it end there.
+    /// - [TypeRef::Named] referring an alias keeps its name,
+    ///   but flags like `is_enumerate` are updated to follow the end of chain.
+    ///
+    pub fn collapse_alias_chains(&mut self) -> Result<(), SemanticError> {
+        let mut hops: HashMap<Key, &TypeRef> = HashMap::new();
+        for schema in &self.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            for ty in &schema.types {
+                if let TypeDecl::Rename(Rename {
+                    id,
+                    ty: next @ TypeRef::Named { .. },
+                    rules,
+                }) = ty
+                {
+                    if rules.is_empty() {
+                        hops.insert((scope, Symbol::new(id)), next);
+                    }
+                }
+            }
+        }
+
+        let mut ends: HashMap<Key, TypeRef> = HashMap::new();
+        for (key, next) in &hops {
+            let mut visited = vec![*key];
+            let mut current = *next;
+            while let TypeRef::Named { name, scope, .. } = current {
+                let key = (*scope, *name);
+                if let Some(start) = visited.iter().position(|v| v == &key) {
+                    let cycle = visited[start..]
+                        .iter()
+                        .chain(std::iter::once(&key))
+                        .map(|(scope, name)| Path::new(scope, ScopeType::Type, name))
+                        .collect();
+                    return Err(SemanticError::CyclicTypeDefinition(cycle));
+                }
+                match hops.get(&key) {
+                    Some(next) => {
+                        visited.push(key);
+                        current = next;
+                    }
+                    None => break,
+                }
+            }
+            ends.insert(*key, current.clone());
+        }
+
+        for schema in &mut self.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            for ty in &mut schema.types {
+                if let Some(end) = ends.get(&(scope, Symbol::new(ty.id()))) {
+                    *ty = TypeDecl::Alias(Alias {
+                        id: ty.id().to_string(),
+                        ty: end.clone(),
+                    });
+                    continue;
+                }
+                match ty {
+                    TypeDecl::Rename(rename) => follow_alias(&mut rename.ty, &ends),
+                    TypeDecl::Select(select) => {
+                        for ty in &mut select.types {
+                            follow_alias(ty, &ends);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            for entity in &mut schema.entities {
+                for attr in &mut entity.attributes {
+                    follow_alias(&mut attr.ty, &ends);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn follow_alias(ty: &mut TypeRef, ends: &HashMap<Key, TypeRef>) {
+    match ty {
+        TypeRef::Named {
+            name,
+            scope,
+            is_simple,
+            is_enumerate,
+        } => {
+            if let Some(TypeRef::Named {
+                is_simple: end_is_simple,
+                is_enumerate: end_is_enumerate,
+                ..
+            }) = ends.get(&(*scope, *name))
+            {
+                *is_simple = *end_is_simple;
+                *is_enumerate = *end_is_enumerate;
+            }
+        }
+        TypeRef::Set { base, .. }
+        | TypeRef::Bag { base, .. }
+        | TypeRef::List { base, .. }
+        | TypeRef::Array { base, .. } => follow_alias(base, ends),
+        _ => {}
+    }
+}
@@ -0,0 +1,183 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+
+/// How a generated struct contains another type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Edge {
+    /// Explicit attribute of an entity at the index
+    Attribute(usize),
+    /// Field of a supertype in a subtype
+    Supertype,
+    /// Underlying type of a rename or an alias
+    Underlying,
+}
+
+/// Types contained by value in the generated structs
+struct Graph {
+    /// Entities and types sorted by their paths
+    nodes: Vec<Path>,
+    edges: HashMap<Path, Vec<(Edge, Path)>>,
+}
+
+impl Graph {
+    fn new(ir: &IR) -> Self {
+        let mut edges = HashMap::new();
+        for schema in &ir.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            for entity in &schema.entities {
+                let supertypes = entity.supertypes.iter().filter_map(|ty| match ty {
+                    TypeRef::Entity { name, scope, .. } => {
+                        Some((Edge::Supertype, Path::entity(scope, name)))
+                    }
+                    _ => None,
+                });
+                let attributes = entity
+                    .attributes
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, attr)| Some((Edge::Attribute(i), contained(&attr.ty)?)));
+                edges.insert(
+                    Path::entity(&scope, &entity.name),
+                    supertypes.chain(attributes).collect(),
+                );
+            }
+            for ty in &schema.types {
+                let underlying = match ty {
+                    TypeDecl::Rename(Rename { ty, .. }) | TypeDecl::Alias(Alias { ty, .. }) => {
+                        contained(ty).map(|path| (Edge::Underlying, path))
+                    }
+                    _ => None,
+                };
+                edges.insert(
+                    Path::new(&scope, ScopeType::Type, ty.id()),
+                    underlying.into_iter().collect(),
+                );
+            }
+        }
+        let mut nodes: Vec<Path> = edges.keys().copied().collect();
+        nodes.sort_by_cached_key(|path| path.to_string());
+        Graph { nodes, edges }
+    }
+
+    /// Find a cycle not passing `boxed` attributes,
+    /// as the edges from each node in the cycle to the next one
+    fn find_cycle(&self, boxed: &HashSet<(Path, usize)>) -> Option<Vec<(Path, Edge)>> {
+        // `true` while the node is on the path from the root
+        let mut visiting = HashMap::new();
+        let mut trail = Vec::new();
+        for root in &self.nodes {
+            if !visiting.contains_key(root) {
+                if let Some(cycle) = self.visit(*root, boxed, &mut visiting, &mut trail) {
+                    return Some(cycle);
+                }
+            }
+        }
+        None
+    }
+
+    fn visit(
+        &self,
+        node: Path,
+        boxed: &HashSet<(Path, usize)>,
+        visiting: &mut HashMap<Path, bool>,
+        trail: &mut Vec<(Path, Edge)>,
+    ) -> Option<Vec<(Path, Edge)>> {
+        visiting.insert(node, true);
+        for (edge, next) in self.edges.get(&node).into_iter().flatten() {
+            if let Edge::Attribute(i) = edge {
+                if boxed.contains(&(node, *i)) {
+                    continue;
+                }
+            }
+            trail.push((node, *edge));
+            match visiting.get(next) {
+                Some(true) => {
+                    let start = trail.iter().position(|(path, _)| path == next).unwrap();
+                    return Some(trail[start..].to_vec());
+                }
+                Some(false) => {}
+                None => {
+                    if let Some(cycle) = self.visit(*next, boxed, visiting, trail) {
+                        return Some(cycle);
+                    }
+                }
+            }
+            trail.pop();
+        }
+        visiting.insert(node, false);
+        None
+    }
+}
+
+/// Entity or defined type whose value is contained in the field of this type
+fn contained(ty: &TypeRef) -> Option<Path> {
+    match ty {
+        TypeRef::Named {
+            name,
+            scope,
+            is_simple: false,
+            is_enumerate: false,
+        } => Some(Path::new(scope, ScopeType::Type, name)),
+        // Supertypes are referred through `Any` enums whose variants are boxed
+        TypeRef::Entity {
+            name,
+            scope,
+            is_supertype: false,
+        } => Some(Path::entity(scope, name)),
+        // Elements of a fixed-size array are contained by value, unlike `Vec`
+        TypeRef::Array { base, .. } if ty.array_size().is_some() => contained(base),
+        _ => None,
+    }
+}
+
+impl IR {
+    /// Box attributes to break reference cycles of entities
+    ///
+    /// ```text
+    /// ENTITY node;
+    ///   parent: OPTIONAL node;
+    /// END_ENTITY;
+    /// ```
+    ///
+    /// The struct `Node` cannot contain `Option<Node>` since its size would be infinite,
+    /// and `parent` becomes `Option<Box<Node>>`.
+    /// Aggregates except fixed-size arrays and `Any` enums already break cycles since they allocate their elements.
+    ///
+    /// Cycles are searched from the entity or type of the smallest name,
+    /// and the last attribute in each cycle is boxed until no cycle remains.
+    /// Thus the boxed attributes are determined only by the schema.
+    pub fn box_cyclic_references(&mut self) -> Result<(), SemanticError> {
+        let graph = Graph::new(self);
+        let mut boxed = HashSet::new();
+        let mut cycles = HashMap::new();
+        while let Some(cycle) = graph.find_cycle(&boxed) {
+            let Some((entity, index)) = cycle.iter().rev().find_map(|(path, edge)| match edge {
+                Edge::Attribute(i) => Some((*path, *i)),
+                _ => None,
+            }) else {
+                // Only supertypes form the cycle, i.e. an entity inherits itself
+                let mut paths: Vec<Path> = cycle.iter().map(|(path, _)| *path).collect();
+                paths.push(paths[0]);
+                return Err(SemanticError::CyclicTypeDefinition(paths));
+            };
+            let names: Vec<String> = cycle
+                .iter()
+                .chain(cycle.first())
+                .map(|(path, _)| path.name.to_string())
+                .collect();
+            boxed.insert((entity, index));
+            cycles.insert((entity, index), names);
+        }
+
+        for schema in &mut self.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            for entity in &mut schema.entities {
+                let path = Path::entity(&scope, &entity.name);
+                for (i, attr) in entity.attributes.iter_mut().enumerate() {
+                    attr.boxed = cycles.remove(&(path, i));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -75,7 +75,7 @@ impl PartialComplexEntity {
             .iter()
             .map(|index| {
                 let (path, _ast) = &ns[*index];
-                path.clone()
+                *path
             })
             .collect()
     }
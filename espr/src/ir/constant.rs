@@ -0,0 +1,33 @@
+use super::{namespace::*, scope::*, *};
+use crate::ast;
+
+/// Constant declared in `CONSTANT` block of schema,
+/// e.g. `CONSTANT dummy_gri : geometric_representation_item := ...; END_CONSTANT;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    /// Name of constant in snake_case
+    pub name: String,
+    pub ty: TypeRef,
+    /// Initializer of the constant, not evaluated
+    pub expr: ast::Expression,
+}
+
+// Literals never be NaN
+impl Eq for Constant {}
+
+impl Legalize for Constant {
+    type Input = ast::Constant;
+
+    fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        constant: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        Ok(Constant {
+            name: constant.name.clone(),
+            ty: TypeRef::legalize(ns, ss, scope, &constant.ty)?,
+            expr: constant.expr.clone(),
+        })
+    }
+}
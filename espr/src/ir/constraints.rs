@@ -91,12 +91,22 @@ impl ConstraintExpr {
     }
 }
 
+/// `ANDOR` of more subtypes than this is not expanded into [Constraints::instantiables]
+/// since it yields $2^n - 1$ combinations, see [Constraints::any_combination].
+pub const MAX_EXPANDED_ANDOR: usize = 12;
+
 /// Global constraints in EXPRESS components
 #[derive(Debug, PartialEq, Eq)]
 pub struct Constraints {
     /// Each super-type can be instantiable as its subtypes,
     /// but possible subtypes cannot be determined from local description in EXPRESS.
     pub instantiables: HashMap<Path, Vec<Vec<Path>>>,
+
+    /// Super-types instantiable as any non-empty combination of the subtypes,
+    /// i.e. `ANDOR` of more than [MAX_EXPANDED_ANDOR] subtypes, which is not expanded into `instantiables`.
+    /// This is typically the default constraint of a super-type with many subtypes,
+    /// e.g. `representation_item`.
+    pub any_combination: HashMap<Path, Vec<Path>>,
 }
 
 // Execute b), c), and d) steps of the algorithm described in the section B.3
@@ -125,11 +135,15 @@ pub fn gather_constraint_expr(
     // > in future editions. The use of the subtype constraint (see 9.7)
     // > is encouraged instead.
     //
+    // `ABSTRACT SUPERTYPE OF` is converted in the same way,
+    // since abstractness does not change the instantiable combinations of subtypes.
+    //
     for schema in &st.schemas {
         let scope = root.schema(&schema.name);
         for entity in &schema.entities {
             match &entity.constraint {
-                Some(ast::Constraint::SuperTypeRule(expr)) => {
+                Some(ast::Constraint::SuperTypeRule(expr))
+                | Some(ast::Constraint::AbstractSuperType(Some(expr))) => {
                     let result = exprs.insert(
                         Path::entity(&scope, &entity.name),
                         ConstraintExpr::from_ast_expr(ns, &scope, expr)?,
@@ -221,19 +235,100 @@ pub fn gather_constraint_expr(
     Ok(exprs)
 }
 
+/// Entities listed in `TOTAL_OVER` of `SUBTYPE_CONSTRAINT` for each supertype
+///
+/// Every instance of the supertype must be an instance of at least one of them (9.7.3).
+pub fn gather_total_over(
+    ns: &Namespace,
+    st: &SyntaxTree,
+) -> Result<HashMap<Path, Vec<Vec<Path>>>, SemanticError> {
+    let root = Scope::root();
+    let mut total_over: HashMap<Path, Vec<Vec<Path>>> = HashMap::new();
+    for schema in &st.schemas {
+        let scope = root.schema(&schema.name);
+        for constraint in &schema.subtype_constraints {
+            if let Some(entities) = &constraint.total_over {
+                let (path, _index) = ns.resolve(&scope, &constraint.entity)?;
+                let entities = entities
+                    .iter()
+                    .map(|name| Ok(ns.resolve(&scope, name)?.0))
+                    .collect::<Result<Vec<Path>, SemanticError>>()?;
+                total_over.entry(path).or_default().push(entities);
+            }
+        }
+    }
+    Ok(total_over)
+}
+
 impl Constraints {
     pub fn new(ns: &Namespace, st: &SyntaxTree) -> Result<Self, SemanticError> {
         let exprs = gather_constraint_expr(ns, st)?;
+        let mut instantiables = HashMap::new();
+        let mut any_combination = HashMap::new();
+        for (path, expr) in exprs {
+            match expr {
+                ConstraintExpr::AndOr(factors) if factors.len() > MAX_EXPANDED_ANDOR => {
+                    match factors
+                        .iter()
+                        .map(|f| match f {
+                            ConstraintExpr::Reference(sub) => Some(*sub),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<Path>>>()
+                    {
+                        Some(subs) => {
+                            any_combination.insert(path, subs);
+                        }
+                        None => {
+                            let expr = ConstraintExpr::AndOr(factors);
+                            instantiables.insert(path, expr.as_instantiables(ns)?);
+                        }
+                    }
+                }
+                expr => {
+                    instantiables.insert(path, expr.as_instantiables(ns)?);
+                }
+            }
+        }
+
+        // Drop combinations including none of `TOTAL_OVER` entities
+        for (path, covers) in gather_total_over(ns, st)? {
+            if let Some(instantiables) = instantiables.get_mut(&path) {
+                instantiables.retain(|pce| {
+                    covers
+                        .iter()
+                        .all(|cover| pce.iter().any(|path| cover.contains(path)))
+                });
+            }
+        }
+
         Ok(Constraints {
-            instantiables: exprs
-                .into_iter()
-                .map(|(path, expr)| Ok((path, expr.as_instantiables(ns)?)))
-                .collect::<Result<_, SemanticError>>()?,
+            instantiables,
+            any_combination,
         })
     }
 
     pub fn is_supertype(&self, path: &Path) -> bool {
-        self.instantiables.contains_key(path)
+        self.instantiables.contains_key(path) || self.any_combination.contains_key(path)
+    }
+
+    /// Subtypes which can be instantiated alone as the super-type `path`
+    pub fn single_subtypes(&self, path: &Path) -> Vec<Path> {
+        if let Some(subs) = self.any_combination.get(path) {
+            return subs.clone();
+        }
+        self.instantiables
+            .get(path)
+            .map(|instantiables| {
+                instantiables
+                    .iter()
+                    .filter_map(|pce| match pce.as_slice() {
+                        [sub] => Some(*sub),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 }
 
@@ -384,6 +479,65 @@ mod tests {
     END_SCHEMA;
     "#;
 
+    /// [PET] using `ABSTRACT SUPERTYPE OF` declaration
+    const PET_INLINE: &str = r#"
+    SCHEMA test_schema;
+      ENTITY pet ABSTRACT SUPERTYPE OF (ONEOF(cat, rabbit, dog));
+        name : pet_name;
+      END_ENTITY;
+
+      ENTITY cat SUBTYPE OF (pet);
+      END_ENTITY;
+
+      ENTITY rabbit SUBTYPE OF (pet);
+      END_ENTITY;
+
+      ENTITY dog SUBTYPE OF (pet);
+      END_ENTITY;
+    END_SCHEMA;
+    "#;
+
+    /// [SUPERTYPE_OF] using `SUBTYPE_CONSTRAINT`
+    const SUPERTYPE_OF_SEPARATE: &str = r#"
+    SCHEMA test_schema;
+      ENTITY base;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sub1 SUBTYPE OF (base);
+        y1: REAL;
+      END_ENTITY;
+
+      ENTITY sub2 SUBTYPE OF (base);
+        y2: REAL;
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT base_subtypes FOR base;
+        ONEOF(sub1, sub2);
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#;
+
+    /// Based on `TOTAL_OVER` example in ISO-10303-11
+    const PERSON_TOTAL_OVER: &str = r#"
+    SCHEMA test_schema;
+      ENTITY person;
+      END_ENTITY;
+
+      ENTITY employee SUBTYPE OF (person);
+      END_ENTITY;
+
+      ENTITY student SUBTYPE OF (person);
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT person_prop FOR person;
+        ABSTRACT SUPERTYPE;
+        TOTAL_OVER(employee);
+        employee ANDOR student;
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#;
+
     /// Example for using `SUPERTYPE OF` declaration
     const SUPERTYPE_OF: &str = r#"
     SCHEMA test_schema;
@@ -531,6 +685,7 @@ mod tests {
         assert_eq!(
             dbg!(c),
             Constraints {
+                any_combination: HashMap::new(),
                 instantiables: maplit::hashmap! {
                     Path::entity(&scope, "pet") => vec![
                         vec![Path::entity(&scope, "cat")],
@@ -551,6 +706,7 @@ mod tests {
         assert_eq!(
             dbg!(c),
             Constraints {
+                any_combination: HashMap::new(),
                 instantiables: maplit::hashmap! {
                     Path::entity(&scope, "base") => vec![
                         vec![Path::entity(&scope, "sub1")],
@@ -570,6 +726,7 @@ mod tests {
         assert_eq!(
             dbg!(c),
             Constraints {
+                any_combination: HashMap::new(),
                 instantiables: maplit::hashmap! {
                     Path::entity(&scope, "person") => vec![
                         vec![Path::entity(&scope, "employee")],
@@ -590,6 +747,7 @@ mod tests {
         assert_eq!(
             dbg!(c),
             Constraints {
+                any_combination: HashMap::new(),
                 instantiables: maplit::hashmap! {
                     Path::entity(&scope, "person") => vec![
                         vec![Path::entity(&scope, "male"), Path::entity(&scope, "citizen")],
@@ -611,6 +769,7 @@ mod tests {
         assert_eq!(
             dbg!(c),
             Constraints {
+                any_combination: HashMap::new(),
                 instantiables: maplit::hashmap! {
                     Path::entity(&scope, "person") => vec![
                         vec![Path::entity(&scope, "employee")],
@@ -621,4 +780,113 @@ mod tests {
             }
         );
     }
+
+    fn constraints(schema: &str) -> Constraints {
+        let st = ast::SyntaxTree::parse(schema).unwrap();
+        let ns = Namespace::new(&st);
+        Constraints::new(&ns, &st).unwrap()
+    }
+
+    #[test]
+    fn abstract_supertype_of_oneof() {
+        assert_eq!(constraints(PET_INLINE), constraints(PET));
+    }
+
+    #[test]
+    fn subtype_constraint_oneof() {
+        assert_eq!(
+            constraints(SUPERTYPE_OF_SEPARATE),
+            constraints(SUPERTYPE_OF)
+        );
+    }
+
+    #[test]
+    fn subtype_constraint_andor() {
+        assert_eq!(
+            constraints(PERSON_ANDOR_SEPARATE),
+            constraints(PERSON_ANDOR)
+        );
+    }
+
+    #[test]
+    fn subtype_constraint_and() {
+        assert_eq!(constraints(PERSON_AND_SEPARATE), constraints(PERSON_AND));
+    }
+
+    #[test]
+    fn subtype_constraint_total_over() {
+        let scope = Scope::root().schema("test_schema");
+        assert_eq!(
+            constraints(PERSON_TOTAL_OVER),
+            Constraints {
+                any_combination: HashMap::new(),
+                instantiables: maplit::hashmap! {
+                    Path::entity(&scope, "person") => vec![
+                        vec![Path::entity(&scope, "employee")],
+                        vec![Path::entity(&scope, "employee"), Path::entity(&scope, "student")],
+                    ]
+                }
+            }
+        );
+    }
+
+    /// Default constraint of many subtypes is kept without expanding 2^40 - 1 combinations
+    #[test]
+    fn default_constraint_many_subtypes() {
+        let mut schema = String::from("SCHEMA test_schema;\nENTITY base;\nEND_ENTITY;\n");
+        for i in 0..40 {
+            schema += &format!("ENTITY sub{} SUBTYPE OF (base);\nEND_ENTITY;\n", i);
+        }
+        schema += "END_SCHEMA;\n";
+
+        let st = ast::SyntaxTree::parse(&schema).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        let base = Path::entity(&scope, "base");
+        assert!(c.is_supertype(&base));
+        assert!(!c.instantiables.contains_key(&base));
+        assert_eq!(c.any_combination[&base].len(), 40);
+        assert_eq!(c.single_subtypes(&base).len(), 40);
+
+        // `BaseAny` covers all subtypes
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let base = &ir.schemas[0].entities[0];
+        assert_eq!(base.name, "base");
+        assert_eq!(base.constraints.len(), 40);
+    }
+
+    /// `AND` of six `ONEOF`s expands into 3^6 combinations
+    #[test]
+    fn and_of_many_oneofs() {
+        let groups = ["a", "b", "c", "d", "e", "f"];
+        let mut schema = String::from("SCHEMA test_schema;\nENTITY base;\nEND_ENTITY;\n");
+        for g in groups {
+            for i in 0..3 {
+                schema += &format!("ENTITY {}{} SUBTYPE OF (base);\nEND_ENTITY;\n", g, i);
+            }
+        }
+        let expr = groups
+            .iter()
+            .map(|g| format!("ONEOF({g}0, {g}1, {g}2)"))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        schema += &format!(
+            "SUBTYPE_CONSTRAINT c FOR base;\n{};\nEND_SUBTYPE_CONSTRAINT;\nEND_SCHEMA;\n",
+            expr
+        );
+
+        let st = ast::SyntaxTree::parse(&schema).unwrap();
+        let ns = Namespace::new(&st);
+        let c = Constraints::new(&ns, &st).unwrap();
+        let scope = Scope::root().schema("test_schema");
+        let instantiables = &c.instantiables[&Path::entity(&scope, "base")];
+        assert_eq!(instantiables.len(), 729);
+        assert!(instantiables.iter().all(|paths| paths.len() == 6));
+        assert!(instantiables.contains(
+            &groups
+                .map(|g| Path::entity(&scope, &format!("{}1", g)))
+                .to_vec()
+        ));
+    }
 }
@@ -0,0 +1,313 @@
+//! Legalize the supported subset of a schema, see [IR::from_syntax_tree_degraded]
+
+use super::*;
+use crate::ast;
+use std::{collections::HashMap, fmt};
+
+/// Why a declaration is skipped in [IR::from_syntax_tree_degraded]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Construct which espr cannot handle yet, e.g. `BAG OF` or `BINARY`
+    Unsupported(String),
+    /// Referred name is not declared in the schema
+    Unresolved(String),
+    /// Referred name is imported from another schema by `USE FROM` or `REFERENCE FROM`,
+    /// which espr does not resolve yet
+    UnresolvedImport { name: String, schema: String },
+    /// Another declaration has the same name in the schema
+    Duplicated,
+    /// A part of cyclic type definitions
+    Cyclic,
+    /// Requires another skipped declaration
+    DependsOn(Path),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::Unsupported(construct) => write!(f, "{} is not supported yet", construct),
+            SkipReason::Unresolved(name) => write!(f, "`{}` is not found", name),
+            SkipReason::UnresolvedImport { name, schema } => {
+                write!(f, "`{}` imported from `{}` is not resolved", name, schema)
+            }
+            SkipReason::Duplicated => write!(f, "declared multiple times"),
+            SkipReason::Cyclic => write!(f, "type definitions form a cycle"),
+            SkipReason::DependsOn(path) => write!(f, "depends on skipped `{}`", path),
+        }
+    }
+}
+
+/// Declaration skipped in [IR::from_syntax_tree_degraded]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedItem {
+    /// Name and location of the skipped ENTITY or TYPE declaration
+    pub path: Path,
+    pub reason: SkipReason,
+    /// Supertypes whose `Any` enums lost this entity as a variant
+    pub dropped_from: Vec<Path>,
+}
+
+impl fmt::Display for SkippedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.path.ty {
+            ScopeType::Entity => "ENTITY",
+            _ => "TYPE",
+        };
+        write!(f, "{} {} is skipped: {}", kind, self.path, self.reason)?;
+        for sup in &self.dropped_from {
+            write!(f, ", and dropped from `Any` of `{}`", sup.name)?;
+        }
+        Ok(())
+    }
+}
+
+impl IR {
+    /// Legalize declarations which can be legalized, and skip the others
+    ///
+    /// Different from [IR::from_syntax_tree_with_options] failing at the first error,
+    /// an ENTITY or TYPE declaration is skipped when it cannot be legalized,
+    /// e.g. it uses an unsupported construct or an unresolved name,
+    /// and everything requiring it transitively is also skipped.
+    /// A skipped subtype is removed from the constraints of its supertypes,
+    /// i.e. `Any` enums of the supertypes do not contain it.
+    ///
+    /// This still fails if the subtype constraints of the remaining entities are inconsistent.
+    pub fn from_syntax_tree_degraded(
+        st: &SyntaxTree,
+        options: &Options,
+    ) -> Result<(Self, Vec<SkippedItem>), SemanticError> {
+        let mut st = st.clone();
+        let mut skipped = Vec::new();
+        let mut supertypes = Vec::new();
+
+        for schema in &st.schemas {
+            let here = Scope::root().schema(&schema.name);
+            let mut count = HashMap::new();
+            for path in declarations(&here, schema) {
+                *count
+                    .entry(path.name.to_string().to_lowercase())
+                    .or_insert(0) += 1;
+            }
+            for path in declarations(&here, schema) {
+                if count[&path.name.to_string().to_lowercase()] > 1 {
+                    skipped.push(SkippedItem {
+                        path,
+                        reason: SkipReason::Duplicated,
+                        dropped_from: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        loop {
+            remove(&mut st, &skipped, &mut supertypes);
+            let found = {
+                let ns = Namespace::new(&st);
+                let mut found = unresolved_supertypes(&ns, &st);
+                if found.is_empty() {
+                    let ss = Constraints::new(&ns, &st)?;
+                    found = illegal_declarations(&ns, &ss, &st);
+                }
+                found
+            };
+            if found.is_empty() {
+                break;
+            }
+            for (path, err) in found {
+                let reason = skip_reason(&st, &skipped, &path, err);
+                skipped.push(SkippedItem {
+                    path,
+                    reason,
+                    dropped_from: Vec::new(),
+                });
+            }
+        }
+
+        let ir = Self::from_syntax_tree_with_options(&st, options)?;
+        let ns = Namespace::new(&st);
+        for (index, scope, names) in supertypes {
+            let item: &mut SkippedItem = &mut skipped[index];
+            for name in names {
+                if let Ok((sup, _)) = ns.resolve(&scope, &name) {
+                    item.dropped_from.push(sup);
+                }
+            }
+        }
+        Ok((ir, skipped))
+    }
+}
+
+fn declarations(scope: &Scope, schema: &ast::Schema) -> Vec<Path> {
+    schema
+        .types
+        .iter()
+        .map(|ty| Path::new(scope, ScopeType::Type, &ty.type_id))
+        .chain(
+            schema
+                .entities
+                .iter()
+                .map(|entity| Path::entity(scope, &entity.name)),
+        )
+        .collect()
+}
+
+/// Remove skipped declarations and the references to them in subtype constraints.
+///
+/// Supertypes of removed entities are recorded with the index in `skipped`
+/// to fill [SkippedItem::dropped_from] after legalization.
+fn remove(
+    st: &mut SyntaxTree,
+    skipped: &[SkippedItem],
+    supertypes: &mut Vec<(usize, Scope, Vec<String>)>,
+) {
+    for schema in &mut st.schemas {
+        let here = Scope::root().schema(&schema.name);
+        let index = |path: Path| skipped.iter().position(|item| item.path == path);
+        schema
+            .types
+            .retain(|ty| index(Path::new(&here, ScopeType::Type, &ty.type_id)).is_none());
+        schema.entities.retain(|entity| {
+            let Some(i) = index(Path::entity(&here, &entity.name)) else {
+                return true;
+            };
+            if let Some(subtype_of) = &entity.subtype_of {
+                supertypes.push((i, here, subtype_of.entity_references.clone()));
+            }
+            false
+        });
+
+        let entities: Vec<String> = schema
+            .entities
+            .iter()
+            .map(|entity| entity.name.to_lowercase())
+            .collect();
+        let declared = |name: &str| entities.contains(&name.to_lowercase());
+        for entity in &mut schema.entities {
+            entity.constraint = match entity.constraint.take() {
+                Some(ast::Constraint::SuperTypeRule(expr)) => {
+                    prune(expr, &declared).map(ast::Constraint::SuperTypeRule)
+                }
+                Some(ast::Constraint::AbstractSuperType(expr)) => Some(
+                    ast::Constraint::AbstractSuperType(expr.and_then(|e| prune(e, &declared))),
+                ),
+                constraint => constraint,
+            };
+        }
+        schema.subtype_constraints.retain_mut(|constraint| {
+            constraint.expr = constraint.expr.take().and_then(|e| prune(e, &declared));
+            declared(&constraint.entity)
+        });
+    }
+}
+
+/// Remove references to undeclared entities from the expression
+fn prune(
+    expr: ast::SuperTypeExpression,
+    declared: &impl Fn(&str) -> bool,
+) -> Option<ast::SuperTypeExpression> {
+    use ast::SuperTypeExpression::*;
+    let prune_all = |exprs: Vec<ast::SuperTypeExpression>| {
+        let exprs: Vec<_> = exprs
+            .into_iter()
+            .filter_map(|e| prune(e, declared))
+            .collect();
+        (!exprs.is_empty()).then_some(exprs)
+    };
+    match expr {
+        Reference(name) => declared(&name).then_some(Reference(name)),
+        AndOr { factors } => prune_all(factors).map(|factors| AndOr { factors }),
+        And { terms } => prune_all(terms).map(|terms| And { terms }),
+        OneOf { exprs } => prune_all(exprs).map(|exprs| OneOf { exprs }),
+    }
+}
+
+/// Entities whose `SUBTYPE OF` refers an undeclared name,
+/// which must be removed before gathering [Constraints]
+fn unresolved_supertypes(ns: &Namespace, st: &SyntaxTree) -> Vec<(Path, SemanticError)> {
+    let mut found = Vec::new();
+    for schema in &st.schemas {
+        let here = Scope::root().schema(&schema.name);
+        for entity in &schema.entities {
+            let Some(subtype_of) = &entity.subtype_of else {
+                continue;
+            };
+            for name in &subtype_of.entity_references {
+                if let Err(err) = ns.resolve(&here, name) {
+                    found.push((Path::entity(&here, &entity.name), err));
+                    break;
+                }
+            }
+        }
+    }
+    found
+}
+
+fn illegal_declarations(
+    ns: &Namespace,
+    ss: &Constraints,
+    st: &SyntaxTree,
+) -> Vec<(Path, SemanticError)> {
+    let mut found = Vec::new();
+    for schema in &st.schemas {
+        let here = Scope::root().schema(&schema.name);
+        for entity in &schema.entities {
+            if let Err(err) = Entity::legalize(ns, ss, &here, entity) {
+                found.push((Path::entity(&here, &entity.name), err));
+            }
+        }
+        for ty in &schema.types {
+            if let Err(err) = TypeDecl::legalize(ns, ss, &here, ty) {
+                found.push((Path::new(&here, ScopeType::Type, &ty.type_id), err));
+            }
+        }
+    }
+    found
+}
+
+fn skip_reason(
+    st: &SyntaxTree,
+    skipped: &[SkippedItem],
+    path: &Path,
+    err: SemanticError,
+) -> SkipReason {
+    match err {
+        // Declarations are legalized in the schema scope
+        SemanticError::TypeNotFound { name, .. } => {
+            if let Some(item) = skipped
+                .iter()
+                .find(|item| item.path.scope == path.scope && item.path.name == name.as_str())
+            {
+                return SkipReason::DependsOn(item.path);
+            }
+            let interfaces = st
+                .schemas
+                .iter()
+                .filter(|schema| Scope::root().schema(&schema.name) == path.scope)
+                .flat_map(|schema| &schema.interfaces);
+            for interface in interfaces {
+                let (schema, resources) = match interface {
+                    ast::InterfaceSpec::Reference { name, resources } => (name, resources),
+                    ast::InterfaceSpec::Use { name, types } => (name, types),
+                };
+                // `resources` are pairs of the original name and its alias
+                let imported = resources.iter().any(|(resource, alias)| {
+                    alias
+                        .as_ref()
+                        .unwrap_or(resource)
+                        .eq_ignore_ascii_case(&name)
+                });
+                if imported || resources.is_empty() {
+                    return SkipReason::UnresolvedImport {
+                        name,
+                        schema: schema.clone(),
+                    };
+                }
+            }
+            SkipReason::Unresolved(name)
+        }
+        SemanticError::InvalidPath(path) => SkipReason::Unresolved(path.name.to_string()),
+        SemanticError::DuplicatedDeclaration(_) => SkipReason::Duplicated,
+        SemanticError::CyclicTypeDefinition(_) => SkipReason::Cyclic,
+        SemanticError::Unsupported { construct, .. } => SkipReason::Unsupported(construct),
+    }
+}
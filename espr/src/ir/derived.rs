@@ -0,0 +1,234 @@
+use super::*;
+use crate::ast;
+
+/// Attribute declared in `DERIVE` clause,
+/// e.g. `DERIVE half : REAL := x / 2.0;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivedAttribute {
+    pub name: String,
+    pub ty: TypeRef,
+    pub expr: DerivedExpression,
+}
+
+/// Arithmetic operators supported in [DerivedExpression]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOperator {
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`, only for REAL
+    RealDiv,
+    /// `DIV`, only for INTEGER
+    IntegerDiv,
+}
+
+/// Expression of [DerivedAttribute] which can be evaluated only from the entity's own attributes
+///
+/// Constant sub-expressions are folded while legalizing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DerivedExpression {
+    Real(f64),
+    Integer(i64),
+    /// Explicit attribute of the entity itself
+    Attribute {
+        name: String,
+        /// The attribute is `OPTIONAL`, i.e. evaluation becomes indeterminate if it is not provided
+        optional: bool,
+        /// The attribute is INTEGER, and should be converted into REAL in REAL expression
+        is_integer: bool,
+    },
+    Neg(Box<DerivedExpression>),
+    Binary {
+        op: ArithmeticOperator,
+        lhs: Box<DerivedExpression>,
+        rhs: Box<DerivedExpression>,
+    },
+    /// Expression out of supported subset,
+    /// e.g. function calls or navigation into another entity
+    Unevaluated,
+}
+
+// Literals never be NaN, since folding keeps operations yielding non-finite values as is
+impl Eq for DerivedExpression {}
+
+impl DerivedExpression {
+    pub fn is_evaluated(&self) -> bool {
+        !matches!(self, DerivedExpression::Unevaluated)
+    }
+
+    /// Names of attributes referred in this expression without duplication
+    pub fn attributes(&self) -> Vec<(&str, bool)> {
+        let mut out = Vec::new();
+        self.collect_attributes(&mut out);
+        out
+    }
+
+    fn collect_attributes<'a>(&'a self, out: &mut Vec<(&'a str, bool)>) {
+        match self {
+            DerivedExpression::Attribute { name, optional, .. }
+                if !out.iter().any(|(n, _)| n == name) =>
+            {
+                out.push((name, *optional));
+            }
+            DerivedExpression::Neg(arg) => arg.collect_attributes(out),
+            DerivedExpression::Binary { lhs, rhs, .. } => {
+                lhs.collect_attributes(out);
+                rhs.collect_attributes(out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Numeric type of the derived attribute
+#[derive(Clone, Copy, PartialEq)]
+enum Numeric {
+    Real,
+    Integer,
+}
+
+impl DerivedAttribute {
+    /// Legalize a derived attribute of an entity whose explicit attributes are `attributes`
+    pub fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        attr: &ast::DerivedAttribute,
+        attributes: &[EntityAttribute],
+    ) -> Result<Option<Self>, SemanticError> {
+        // Re-declaration of attribute in supertype, e.g. `SELF\point.x : REAL := 1.0;`, is not supported
+        let name = match &attr.attr {
+            ast::AttributeDecl::Reference(name) => name.clone(),
+            _ => return Ok(None),
+        };
+        let ty = TypeRef::legalize(ns, ss, scope, &attr.ty)?;
+        let expr = match &ty {
+            TypeRef::SimpleType(SimpleType(ast::SimpleType::Real | ast::SimpleType::Number)) => {
+                evaluate(&attr.expr, attributes, Numeric::Real)
+            }
+            TypeRef::SimpleType(SimpleType(ast::SimpleType::Integer)) => {
+                evaluate(&attr.expr, attributes, Numeric::Integer)
+            }
+            _ => None,
+        }
+        .unwrap_or(DerivedExpression::Unevaluated);
+        Ok(Some(DerivedAttribute { name, ty, expr }))
+    }
+}
+
+// Returns `None` if the expression is out of supported subset
+fn evaluate(
+    expr: &ast::Expression,
+    attributes: &[EntityAttribute],
+    numeric: Numeric,
+) -> Option<DerivedExpression> {
+    use ast::{BinaryOperator, BuiltInConstant, Expression, Literal, QualifiableFactor};
+    Some(match expr {
+        Expression::Literal(Literal::Real(value)) => match numeric {
+            Numeric::Real => DerivedExpression::Real(*value),
+            Numeric::Integer if value.fract() == 0.0 => DerivedExpression::Integer(*value as i64),
+            Numeric::Integer => return None,
+        },
+        Expression::QualifiableFactor { factor, qualifiers } if qualifiers.is_empty() => {
+            match factor {
+                QualifiableFactor::Reference(name) => {
+                    let attr = attributes.iter().find(|attr| &attr.name == name)?;
+                    let is_integer = match &attr.ty {
+                        TypeRef::SimpleType(SimpleType(ast::SimpleType::Integer)) => true,
+                        TypeRef::SimpleType(SimpleType(
+                            ast::SimpleType::Real | ast::SimpleType::Number,
+                        )) if numeric == Numeric::Real => false,
+                        _ => return None,
+                    };
+                    DerivedExpression::Attribute {
+                        name: name.clone(),
+                        optional: attr.optional,
+                        is_integer,
+                    }
+                }
+                QualifiableFactor::BuiltInConstant(BuiltInConstant::Pi)
+                    if numeric == Numeric::Real =>
+                {
+                    DerivedExpression::Real(std::f64::consts::PI)
+                }
+                QualifiableFactor::BuiltInConstant(BuiltInConstant::Napier)
+                    if numeric == Numeric::Real =>
+                {
+                    DerivedExpression::Real(std::f64::consts::E)
+                }
+                _ => return None,
+            }
+        }
+        Expression::Unary { op, arg } => {
+            let arg = evaluate(arg, attributes, numeric)?;
+            match op {
+                ast::UnaryOperator::Plus => arg,
+                ast::UnaryOperator::Minus => fold(DerivedExpression::Neg(Box::new(arg))),
+                ast::UnaryOperator::Not => return None,
+            }
+        }
+        Expression::Binary { op, arg1, arg2 } => {
+            let op = match (op, numeric) {
+                (BinaryOperator::Add, _) => ArithmeticOperator::Add,
+                (BinaryOperator::Sub, _) => ArithmeticOperator::Sub,
+                (BinaryOperator::Mul, _) => ArithmeticOperator::Mul,
+                (BinaryOperator::RealDiv, Numeric::Real) => ArithmeticOperator::RealDiv,
+                (BinaryOperator::IntegerDiv, Numeric::Integer) => ArithmeticOperator::IntegerDiv,
+                _ => return None,
+            };
+            fold(DerivedExpression::Binary {
+                op,
+                lhs: Box::new(evaluate(arg1, attributes, numeric)?),
+                rhs: Box::new(evaluate(arg2, attributes, numeric)?),
+            })
+        }
+        _ => return None,
+    })
+}
+
+// Fold an operation whose operands are constants
+fn fold(expr: DerivedExpression) -> DerivedExpression {
+    use ArithmeticOperator::*;
+    use DerivedExpression::*;
+    match &expr {
+        Neg(arg) => match arg.as_ref() {
+            Real(x) => Real(-x),
+            Integer(x) => x.checked_neg().map(Integer).unwrap_or(expr),
+            _ => expr,
+        },
+        Binary { op, lhs, rhs } => match (op, lhs.as_ref(), rhs.as_ref()) {
+            (op, Real(x), Real(y)) => {
+                let folded = match op {
+                    Add => x + y,
+                    Sub => x - y,
+                    Mul => x * y,
+                    // Keep division by zero to be evaluated at runtime
+                    RealDiv if *y == 0.0 => return expr,
+                    RealDiv => x / y,
+                    IntegerDiv => return expr,
+                };
+                if folded.is_finite() {
+                    Real(folded)
+                } else {
+                    expr
+                }
+            }
+            (op, Integer(x), Integer(y)) => {
+                let folded = match op {
+                    Add => x.checked_add(*y),
+                    Sub => x.checked_sub(*y),
+                    Mul => x.checked_mul(*y),
+                    IntegerDiv => x.checked_div(*y),
+                    RealDiv => None,
+                };
+                // Keep as is to be evaluated as indeterminate at runtime, e.g. division by zero
+                folded.map(Integer).unwrap_or(expr)
+            }
+            _ => expr,
+        },
+        _ => expr,
+    }
+}
@@ -0,0 +1,33 @@
+use crate::ast;
+
+/// Rule in `WHERE` clause of entity or defined type,
+/// e.g. `wr1: SELF > 0.0;`
+///
+/// The expression is kept as AST since rules are not used in code generation yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainRule {
+    /// Label of the rule, e.g. `wr1`
+    pub label: Option<String>,
+    pub expr: ast::Expression,
+}
+
+// Literals never be NaN
+impl Eq for DomainRule {}
+
+impl DomainRule {
+    /// Rules in `WHERE` clause, or empty if the clause does not exist
+    pub fn from_where_clause(clause: Option<&ast::WhereClause>) -> Vec<Self> {
+        clause
+            .map(|clause| {
+                clause
+                    .rules
+                    .iter()
+                    .map(|rule| DomainRule {
+                        label: rule.label.clone(),
+                        expr: rule.expr.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
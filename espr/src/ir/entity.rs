@@ -23,6 +23,22 @@ pub struct Entity {
     /// then this `supertypes` is `[base]`.
     ///
     pub supertypes: Vec<TypeRef>,
+
+    /// Declared as `ABSTRACT SUPERTYPE`, or `ABSTRACT SUPERTYPE` in `SUBTYPE_CONSTRAINT`.
+    /// It cannot be instantiated except as a supertype of its subtypes.
+    pub is_abstract: bool,
+
+    /// Attributes declared in `DERIVE` clause
+    pub derived: Vec<DerivedAttribute>,
+
+    /// Attributes declared in `INVERSE` clause
+    pub inverse: Vec<InverseAttribute>,
+
+    /// Rules declared in `UNIQUE` clause
+    pub unique_rules: Vec<UniqueRule>,
+
+    /// Rules declared in `WHERE` clause
+    pub rules: Vec<DomainRule>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +46,11 @@ pub struct EntityAttribute {
     pub name: String,
     pub ty: TypeRef,
     pub optional: bool,
+    /// Keep the keyword of typed parameters, see [Options::preserve_type_wrappers]
+    pub wrapped: bool,
+    /// Names of entities and types in the reference cycle, e.g. `["a", "b", "a"]`,
+    /// if this attribute is boxed to break it, see [IR::box_cyclic_references]
+    pub boxed: Option<Vec<String>>,
 }
 
 impl Legalize for EntityAttribute {
@@ -44,13 +65,19 @@ impl Legalize for EntityAttribute {
         let ty = TypeRef::legalize(ns, ss, scope, &attr.ty)?;
         let name = match &attr.name {
             ast::AttributeDecl::Reference(name) => name.clone(),
-            ast::AttributeDecl::Qualified { group, attribute, rename} => {group.clone() + "_" + attribute}
+            ast::AttributeDecl::Qualified {
+                group,
+                attribute,
+                rename,
+            } => group.clone() + "_" + attribute,
             _ => "unimplemented".to_string(),
         };
         Ok(EntityAttribute {
             name,
             ty,
             optional: attr.optional,
+            wrapped: false,
+            boxed: None,
         })
     }
 }
@@ -82,17 +109,37 @@ impl Legalize for Entity {
         };
 
         let path = Path::entity(scope, &entity.name);
-        let constraints = if let Some(instantiables) = ss.instantiables.get(&path) {
-            instantiables
+        // FIXME ignore complex entity case
+        let constraints = ss
+            .single_subtypes(&path)
+            .iter()
+            .map(|sub| TypeRef::from_path(ns, ss, sub))
+            .collect::<Result<Vec<TypeRef>, SemanticError>>()?;
+
+        let mut derived = Vec::new();
+        if let Some(clause) = &entity.derive_clause {
+            for attr in &clause.attributes {
+                if let Some(attr) = DerivedAttribute::legalize(ns, ss, scope, attr, &attributes)? {
+                    derived.push(attr);
+                }
+            }
+        }
+
+        let inverse = match &entity.inverse_clause {
+            Some(clause) => clause
+                .attributes
                 .iter()
-                .filter_map(|pce| match pce.len() {
-                    // FIXME ignore complex entity case
-                    1 => Some(TypeRef::from_path(ns, ss, &pce[0])),
-                    _ => None,
-                })
-                .collect::<Result<Vec<TypeRef>, SemanticError>>()?
-        } else {
-            Vec::new()
+                .map(|attr| InverseAttribute::legalize(ns, ss, scope, attr))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        let unique_rules = match &entity.unique_clause {
+            Some(clause) => clause
+                .rules
+                .iter()
+                .map(|rule| UniqueRule::legalize(ns, ss, scope, rule))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
         };
 
         Ok(Entity {
@@ -100,6 +147,11 @@ impl Legalize for Entity {
             attributes,
             constraints,
             supertypes,
+            is_abstract: entity.is_abstract(),
+            derived,
+            inverse,
+            unique_rules,
+            rules: DomainRule::from_where_clause(entity.where_clause.as_ref()),
         })
     }
 }
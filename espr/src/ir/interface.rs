@@ -0,0 +1,223 @@
+use super::*;
+use crate::ast;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Kind of interface specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceKind {
+    /// `USE FROM`, interfaced entities can be instantiated independently
+    Use,
+    /// `REFERENCE FROM`, interfaced entities can be instantiated only as attribute values
+    Reference,
+}
+
+/// Declarations interfaced from another schema by `USE FROM` or `REFERENCE FROM`
+///
+/// ```text
+/// SCHEMA geometry;
+///   ENTITY point;
+///     x, y: REAL;
+///   END_ENTITY;
+/// END_SCHEMA;
+///
+/// SCHEMA drawing;
+///   USE FROM geometry (point AS pt);
+///   ENTITY marker;
+///     at: pt;  -- refers `point` in `geometry`
+///   END_ENTITY;
+/// END_SCHEMA;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub kind: InterfaceKind,
+    /// Name of the schema where declarations are interfaced from
+    pub schema: String,
+    /// Interfaced declarations and their names in this schema.
+    /// Every declaration visible in the schema is listed if no resource is specified,
+    /// e.g. `USE FROM geometry;`.
+    pub resources: Vec<(TypeRef, String)>,
+}
+
+impl Legalize for Interface {
+    type Input = ast::InterfaceSpec;
+
+    fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        _scope: &Scope,
+        interface: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        let (kind, schema, resources) = match interface {
+            ast::InterfaceSpec::Use { name, types } => (InterfaceKind::Use, name, types),
+            ast::InterfaceSpec::Reference { name, resources } => {
+                (InterfaceKind::Reference, name, resources)
+            }
+        };
+        let from = Scope::root().pushed(ScopeType::Schema, schema);
+        // Resources not found, e.g. in a schema out of the syntax tree, are not listed.
+        // They are reported when referred, see [Namespace::resolve].
+        let resources = if resources.is_empty() {
+            ns.names
+                .get(&from)
+                .into_iter()
+                .flatten()
+                .map(|(_ty, name, index)| {
+                    Ok((TypeRef::from_path(ns, ss, &ns[*index].0)?, name.to_string()))
+                })
+                .collect::<Result<Vec<_>, SemanticError>>()?
+        } else {
+            let mut found = Vec::new();
+            for (resource, alias) in resources {
+                if let Ok((path, _index)) = ns.resolve(&from, resource) {
+                    let name = alias.as_ref().unwrap_or(resource);
+                    found.push((TypeRef::from_path(ns, ss, &path)?, name.clone()));
+                }
+            }
+            found
+        };
+        Ok(Interface {
+            kind,
+            schema: schema.clone(),
+            resources,
+        })
+    }
+}
+
+/// Entity or type declaration in a schema
+enum Declaration<'ir> {
+    Entity(&'ir Entity),
+    Type(&'ir TypeDecl),
+}
+
+impl Declaration<'_> {
+    /// Declarations referred by this declaration
+    fn referred(&self) -> Vec<Path> {
+        let mut paths = Vec::new();
+        match self {
+            Declaration::Entity(entity) => {
+                let types = entity
+                    .attributes
+                    .iter()
+                    .map(|attr| &attr.ty)
+                    .chain(&entity.supertypes)
+                    .chain(&entity.constraints)
+                    .chain(entity.derived.iter().map(|attr| &attr.ty))
+                    .chain(entity.inverse.iter().map(|attr| &attr.dest))
+                    .chain(
+                        entity
+                            .unique_rules
+                            .iter()
+                            .flat_map(|rule| &rule.attributes)
+                            .filter_map(|attr| attr.group.as_ref()),
+                    );
+                for ty in types {
+                    referred(ty, &mut paths);
+                }
+            }
+            Declaration::Type(TypeDecl::Rename(Rename { ty, .. }))
+            | Declaration::Type(TypeDecl::Alias(Alias { ty, .. })) => referred(ty, &mut paths),
+            Declaration::Type(TypeDecl::Select(select)) => {
+                for ty in &select.types {
+                    referred(ty, &mut paths);
+                }
+            }
+            Declaration::Type(TypeDecl::Simple(_) | TypeDecl::Enumeration(_)) => {}
+        }
+        paths
+    }
+}
+
+/// Declaration referred by the type, or by its base type for aggregates
+fn referred(ty: &TypeRef, paths: &mut Vec<Path>) {
+    match ty {
+        TypeRef::SimpleType(_) => {}
+        TypeRef::Named { name, scope, .. } => paths.push(Path::new(scope, ScopeType::Type, name)),
+        TypeRef::Entity { name, scope, .. } => paths.push(Path::entity(scope, name)),
+        TypeRef::Set { base, .. }
+        | TypeRef::Bag { base, .. }
+        | TypeRef::List { base, .. }
+        | TypeRef::Array { base, .. } => referred(base, paths),
+    }
+}
+
+impl IR {
+    /// Copy declarations interfaced from other schemas into each schema
+    ///
+    /// A generated schema module is self-contained,
+    /// since holders of entities are bound to the `Tables` of the module.
+    /// Every declaration in another schema referred from a schema,
+    /// directly by interfaces and declarations or transitively by the copied declarations,
+    /// is copied into the schema with its original name.
+    /// Names given by `AS` are re-exported as aliases in code generation.
+    ///
+    /// Error
+    /// ------
+    /// - [SemanticError::DuplicatedDeclaration] if a copied declaration has the same name as a declaration in the schema
+    ///
+    pub fn copy_interfaced_declarations(&mut self) -> Result<(), SemanticError> {
+        let mut declarations = HashMap::new();
+        for schema in &self.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            for entity in &schema.entities {
+                declarations.insert(
+                    Path::entity(&scope, &entity.name),
+                    Declaration::Entity(entity),
+                );
+            }
+            for ty in &schema.types {
+                declarations.insert(
+                    Path::new(&scope, ScopeType::Type, ty.id()),
+                    Declaration::Type(ty),
+                );
+            }
+        }
+
+        let mut copies = Vec::new();
+        for schema in &self.schemas {
+            let scope = Scope::root().pushed(ScopeType::Schema, &schema.name);
+            let mut paths = Vec::new();
+            for (ty, _name) in schema.interfaces.iter().flat_map(|i| &i.resources) {
+                referred(ty, &mut paths);
+            }
+            paths.extend(
+                schema
+                    .entities
+                    .iter()
+                    .flat_map(|e| Declaration::Entity(e).referred()),
+            );
+            paths.extend(
+                schema
+                    .types
+                    .iter()
+                    .flat_map(|ty| Declaration::Type(ty).referred()),
+            );
+            let mut queue = VecDeque::from(paths);
+
+            let mut visited = HashSet::new();
+            let mut entities = Vec::new();
+            let mut types = Vec::new();
+            while let Some(path) = queue.pop_front() {
+                if path.scope == scope || !visited.insert(path) {
+                    continue;
+                }
+                let Some(declaration) = declarations.get(&path) else {
+                    continue;
+                };
+                if declarations.contains_key(&Path { scope, ..path }) {
+                    return Err(SemanticError::DuplicatedDeclaration(Path { scope, ..path }));
+                }
+                queue.extend(declaration.referred());
+                match declaration {
+                    Declaration::Entity(entity) => entities.push((*entity).clone()),
+                    Declaration::Type(ty) => types.push((*ty).clone()),
+                }
+            }
+            copies.push((entities, types));
+        }
+        for (schema, (entities, types)) in self.schemas.iter_mut().zip(copies) {
+            schema.entities.extend(entities);
+            schema.types.extend(types);
+        }
+        Ok(())
+    }
+}
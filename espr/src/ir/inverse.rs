@@ -0,0 +1,68 @@
+use super::{namespace::*, scope::*, *};
+use crate::ast;
+
+/// Attribute in `INVERSE` clause of entity,
+/// e.g. `bounds : SET [1:?] OF face_bound FOR face;` in `face`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InverseAttribute {
+    /// Name of inverse attribute, e.g. `bounds`
+    pub name: String,
+    /// Entity referring this entity, e.g. `face_bound`
+    pub dest: TypeRef,
+    /// Attribute of `dest` referring this entity, e.g. `face`
+    pub attribute: String,
+    pub aggregation: InverseAggregation,
+}
+
+/// How many entities refer the entity by [InverseAttribute]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InverseAggregation {
+    /// Exactly one entity, e.g. `opens : door FOR handle;`
+    None,
+    Set {
+        bound: Option<Bound>,
+    },
+    Bag {
+        bound: Option<Bound>,
+    },
+}
+
+impl Legalize for InverseAttribute {
+    type Input = ast::InverseAttribute;
+
+    fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        attr: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        let name = match &attr.name {
+            ast::AttributeDecl::Reference(name) => name.clone(),
+            ast::AttributeDecl::Qualified {
+                attribute, rename, ..
+            } => rename.as_ref().unwrap_or(attribute).clone(),
+        };
+        let dest = TypeRef::from_path(ns, ss, &ns.resolve(scope, &attr.dest)?.0)?;
+        let bound = |bound: &Option<ast::Bound>| -> Result<Option<Bound>, SemanticError> {
+            bound
+                .as_ref()
+                .map(|bound| Bound::legalize(ns, ss, scope, bound))
+                .transpose()
+        };
+        let aggregation = match &attr.dest_aggregation {
+            ast::AggregationOption::None => InverseAggregation::None,
+            ast::AggregationOption::Set { bound: b } => {
+                InverseAggregation::Set { bound: bound(b)? }
+            }
+            ast::AggregationOption::Bag { bound: b } => {
+                InverseAggregation::Bag { bound: bound(b)? }
+            }
+        };
+        Ok(InverseAttribute {
+            name,
+            dest,
+            attribute: attr.attribute.clone(),
+            aggregation,
+        })
+    }
+}
@@ -165,23 +165,43 @@
 //! e.g. Python code or HTML reference.
 //!
 
+mod alias;
+mod boxing;
 mod complex_entity;
+mod constant;
 mod constraints;
+mod degrade;
+mod derived;
+mod domain;
 mod entity;
+mod interface;
+mod inverse;
 mod namespace;
+mod rule;
 mod schema;
 mod scope;
+mod symbol;
 mod type_decl;
 mod type_ref;
+mod unique;
 
 pub use complex_entity::*;
+pub use constant::*;
 pub use constraints::*;
+pub use degrade::*;
+pub use derived::*;
+pub use domain::*;
 pub use entity::*;
+pub use interface::*;
+pub use inverse::*;
 pub use namespace::*;
+pub use rule::*;
 pub use schema::*;
 pub use scope::*;
+pub use symbol::*;
 pub use type_decl::*;
 pub use type_ref::*;
+pub use unique::*;
 
 use crate::ast::SyntaxTree;
 use thiserror::Error;
@@ -197,6 +217,12 @@ pub enum SemanticError {
 
     #[error("Same item ({0}) is declared multiple times")]
     DuplicatedDeclaration(Path),
+
+    #[error("Type definitions form a cycle: {}", .0.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" -> "))]
+    CyclicTypeDefinition(Vec<Path>),
+
+    #[error("{construct} referred in scope {scope} is not supported yet")]
+    Unsupported { construct: String, scope: Scope },
 }
 
 /// Legalize partial AST input into corresponding intermediate representation
@@ -218,11 +244,70 @@ pub struct IR {
     pub schemas: Vec<Schema>,
 }
 
+/// Options for passes applied after legalization
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Run [IR::collapse_alias_chains], enabled by default.
+    /// Keywords of the collapsed types, e.g. `C('KORE')` for `TYPE c = a;`, are read as the end of chain.
+    pub collapse_alias_chains: bool,
+    /// Keep [DerivedExpression]s to generate accessors of derived attributes, enabled by default.
+    /// If disabled, every derived attribute is regarded as [DerivedExpression::Unevaluated].
+    pub evaluate_derived_attributes: bool,
+    /// Generate `Wrapped<T>` for attributes of simple types, e.g. `STRING`,
+    /// to keep keywords of typed parameters like `LABEL('bolt')`. Disabled by default.
+    ///
+    /// Attributes of defined types are not wrapped, since their own types imply the keyword.
+    pub preserve_type_wrappers: bool,
+    /// Derive `Serialize` and `Deserialize` for `Tables` and holders in binary formats,
+    /// to persist populated tables by `ruststep::cache::TablesCache`. Disabled by default.
+    pub derive_serde: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            collapse_alias_chains: true,
+            evaluate_derived_attributes: true,
+            preserve_type_wrappers: false,
+            derive_serde: false,
+        }
+    }
+}
+
 impl IR {
     pub fn from_syntax_tree(st: &SyntaxTree) -> Result<Self, SemanticError> {
+        Self::from_syntax_tree_with_options(st, &Options::default())
+    }
+
+    pub fn from_syntax_tree_with_options(
+        st: &SyntaxTree,
+        options: &Options,
+    ) -> Result<Self, SemanticError> {
         let ns = Namespace::new(st);
         let ss = Constraints::new(&ns, st)?;
-        let ir = Self::legalize(&ns, &ss, &Scope::root(), st)?;
+        let mut ir = Self::legalize(&ns, &ss, &Scope::root(), st)?;
+        if options.collapse_alias_chains {
+            ir.collapse_alias_chains()?;
+        }
+        ir.box_cyclic_references()?;
+        if !options.evaluate_derived_attributes {
+            for entity in ir.schemas.iter_mut().flat_map(|s| s.entities.iter_mut()) {
+                for attr in &mut entity.derived {
+                    attr.expr = DerivedExpression::Unevaluated;
+                }
+            }
+        }
+        if options.preserve_type_wrappers {
+            for entity in ir.schemas.iter_mut().flat_map(|s| s.entities.iter_mut()) {
+                for attr in &mut entity.attributes {
+                    attr.wrapped = matches!(attr.ty, TypeRef::SimpleType(_));
+                }
+            }
+        }
+        for schema in &mut ir.schemas {
+            schema.derive_serde = options.derive_serde;
+        }
+        ir.copy_interfaced_declarations()?;
         Ok(ir)
     }
 }
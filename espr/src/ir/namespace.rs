@@ -1,4 +1,4 @@
-use super::{scope::*, SemanticError};
+use super::{scope::*, SemanticError, Symbol};
 use crate::ast::{self, SyntaxTree};
 
 use std::collections::HashMap;
@@ -20,7 +20,7 @@ pub enum Named<'st> {
 ///
 #[derive(Debug, Clone)]
 pub struct Namespace<'st> {
-    pub names: HashMap<Scope, Vec<(ScopeType, String, usize)>>,
+    pub names: HashMap<Scope, Vec<(ScopeType, Symbol, usize)>>,
     /// Indexed AST portion
     pub ast: Vec<(Path, Named<'st>)>,
 }
@@ -33,7 +33,7 @@ impl<'st> std::ops::Index<usize> for Namespace<'st> {
 }
 
 impl<'st> std::ops::Index<&Scope> for Namespace<'st> {
-    type Output = [(ScopeType, String, usize)];
+    type Output = [(ScopeType, Symbol, usize)];
     fn index(&self, id: &Scope) -> &Self::Output {
         &self.names[id]
     }
@@ -53,18 +53,50 @@ impl<'st> Namespace<'st> {
                 let path = Path::new(&here, ScopeType::Type, name);
                 let index = ast.len();
                 ast.push((path, Named::Type(ty)));
-                current_names.push((ScopeType::Type, name.to_string(), index));
+                current_names.push((ScopeType::Type, path.name, index));
             }
             for entity in &schema.entities {
                 let name = &entity.name;
                 let path = Path::new(&here, ScopeType::Entity, name);
                 let index = ast.len();
                 ast.push((path, Named::Entity(entity)));
-                current_names.push((ScopeType::Entity, name.to_string(), index));
+                current_names.push((ScopeType::Entity, path.name, index));
             }
             names.insert(here, current_names);
         }
 
+        // Declarations interfaced by `USE FROM` or `REFERENCE FROM` are visible in the schema
+        // by their names, or aliases given by `AS`.
+        // Interfaces from a schema not in the syntax tree are ignored.
+        for schema in &st.schemas {
+            let here = root.pushed(ScopeType::Schema, &schema.name);
+            let mut interfaced = Vec::new();
+            for interface in &schema.interfaces {
+                let (from, resources) = match interface {
+                    ast::InterfaceSpec::Use { name, types } => (name, types),
+                    ast::InterfaceSpec::Reference { name, resources } => (name, resources),
+                };
+                let declared = match names.get(&root.pushed(ScopeType::Schema, from)) {
+                    Some(declared) => declared,
+                    None => continue,
+                };
+                if resources.is_empty() {
+                    interfaced.extend(declared.iter().copied());
+                    continue;
+                }
+                for (resource, alias) in resources {
+                    if let Some((ty, _, index)) = declared
+                        .iter()
+                        .find(|(_, name, _)| *name == resource.as_str())
+                    {
+                        let name = alias.as_ref().unwrap_or(resource);
+                        interfaced.push((*ty, Symbol::new(name), *index));
+                    }
+                }
+            }
+            names.get_mut(&here).unwrap().extend(interfaced);
+        }
+
         Namespace { names, ast }
     }
 
@@ -79,22 +111,25 @@ impl<'st> Namespace<'st> {
 
     /// Resolve a `name` referred in a `scope` into the full path.
     ///
+    /// A name interfaced from another schema is resolved into the path in that schema,
+    /// e.g. `pt` in `USE FROM geometry (point AS pt);` is resolved into `geometry[schema].point[entity]`.
+    ///
     /// Error
     /// ------
     /// - If no corresponding definition found.
     ///
     pub fn resolve(&self, scope: &Scope, name: &str) -> Result<(Path, usize), SemanticError> {
-        let mut scope = scope.clone();
+        let mut scope = *scope;
         loop {
             if let Some(names) = self.names.get(&scope) {
-                for (ty, n, index) in names {
-                    if name == n {
-                        return Ok((Path::new(&scope, *ty, n), *index));
+                for (_ty, n, index) in names {
+                    if *n == name {
+                        return Ok((self.ast[*index].0, *index));
                     }
                 }
             }
             scope = scope.popped().ok_or_else(|| SemanticError::TypeNotFound {
-                scope: scope.clone(),
+                scope,
                 name: name.to_string(),
             })?;
         }
@@ -112,7 +147,7 @@ impl<'st> Namespace<'st> {
                 return Ok((*ast, index));
             }
         }
-        Err(SemanticError::InvalidPath(path.clone()))
+        Err(SemanticError::InvalidPath(*path))
     }
 }
 
@@ -157,6 +192,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolve_interfaced() {
+        let st = SyntaxTree::parse(
+            r#"
+            SCHEMA geometry;
+              ENTITY point;
+                x, y: REAL;
+              END_ENTITY;
+              TYPE label = STRING;
+              END_TYPE;
+            END_SCHEMA;
+
+            SCHEMA drawing;
+              USE FROM geometry (point AS pt);
+              REFERENCE FROM geometry;
+              USE FROM unknown_schema (line);
+            END_SCHEMA;
+            "#
+            .trim(),
+        )
+        .unwrap();
+        let ns = Namespace::new(&st);
+        let root = Scope::root();
+        let geometry = root.pushed(ScopeType::Schema, "geometry");
+        let drawing = root.pushed(ScopeType::Schema, "drawing");
+
+        let (path, _) = ns.resolve(&drawing, "pt").unwrap();
+        assert_eq!(path, Path::entity(&geometry, "point"));
+        // Every declaration is interfaced by `REFERENCE FROM geometry;`
+        let (path, _) = ns.resolve(&drawing, "label").unwrap();
+        assert_eq!(path, Path::new(&geometry, ScopeType::Type, "label"));
+        assert!(ns.resolve(&drawing, "line").is_err());
+        assert!(ns.resolve(&geometry, "pt").is_err());
+    }
+
     #[test]
     fn namespace_debug() {
         let st = ast::SyntaxTree::parse(
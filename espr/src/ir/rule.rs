@@ -0,0 +1,38 @@
+use super::{namespace::*, scope::*, *};
+use crate::ast;
+
+/// Global rule,
+/// e.g. `RULE compatible_dimension FOR (cartesian_point, direction); ... END_RULE;`
+///
+/// Local declarations and statements are not kept, since rules are not used in code generation yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// Name of rule in snake_case
+    pub name: String,
+    /// Entities listed in `FOR`
+    pub references: Vec<Path>,
+    /// Rules declared in `WHERE` clause
+    pub rules: Vec<DomainRule>,
+}
+
+impl Legalize for Rule {
+    type Input = ast::Rule;
+
+    fn legalize(
+        ns: &Namespace,
+        _ss: &Constraints,
+        scope: &Scope,
+        rule: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        let references = rule
+            .references
+            .iter()
+            .map(|name| Ok(ns.resolve(scope, name)?.0))
+            .collect::<Result<Vec<Path>, SemanticError>>()?;
+        Ok(Rule {
+            name: rule.name.clone(),
+            references,
+            rules: DomainRule::from_where_clause(Some(&rule.where_clause)),
+        })
+    }
+}
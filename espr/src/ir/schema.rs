@@ -1,4 +1,4 @@
-use super::{entity::*, namespace::*, scope::*, type_decl::*, *};
+use super::{constant::*, entity::*, namespace::*, rule::*, scope::*, type_decl::*, *};
 use crate::ast;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -6,6 +6,14 @@ pub struct Schema {
     pub name: String,
     pub entities: Vec<Entity>,
     pub types: Vec<TypeDecl>,
+    /// Global rules declared by `RULE`
+    pub rules: Vec<Rule>,
+    /// Constants declared in `CONSTANT` block
+    pub constants: Vec<Constant>,
+    /// Declarations interfaced by `USE FROM` or `REFERENCE FROM`
+    pub interfaces: Vec<Interface>,
+    /// Derive serde for `Tables` and holders, see [Options::derive_serde]
+    pub derive_serde: bool,
 }
 
 impl Legalize for Schema {
@@ -18,20 +26,47 @@ impl Legalize for Schema {
     ) -> Result<Self, SemanticError> {
         let name = schema.name.clone();
         let here = scope.pushed(ScopeType::Schema, &name);
-        let entities = schema
+        let mut entities = schema
             .entities
             .iter()
             .map(|entity| Entity::legalize(ns, ss, &here, entity))
             .collect::<Result<Vec<Entity>, _>>()?;
+        for constraint in &schema.subtype_constraints {
+            if !constraint.is_abstract {
+                continue;
+            }
+            if let Some(entity) = entities.iter_mut().find(|e| e.name == constraint.entity) {
+                entity.is_abstract = true;
+            }
+        }
         let types = schema
             .types
             .iter()
             .map(|entity| TypeDecl::legalize(ns, ss, &here, entity))
             .collect::<Result<Vec<TypeDecl>, _>>()?;
+        let rules = schema
+            .rules
+            .iter()
+            .map(|rule| Rule::legalize(ns, ss, &here, rule))
+            .collect::<Result<Vec<Rule>, _>>()?;
+        let constants = schema
+            .constants
+            .iter()
+            .map(|constant| Constant::legalize(ns, ss, &here, constant))
+            .collect::<Result<Vec<Constant>, _>>()?;
+        let interfaces = schema
+            .interfaces
+            .iter()
+            .map(|interface| Interface::legalize(ns, ss, &here, interface))
+            .collect::<Result<Vec<Interface>, _>>()?;
         Ok(Schema {
             name,
             entities,
             types,
+            rules,
+            constants,
+            interfaces,
+            derive_serde: false,
         })
     }
 }
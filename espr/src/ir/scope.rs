@@ -1,5 +1,11 @@
+use super::Symbol;
 use itertools::*;
-use std::{cmp, fmt};
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt, hash,
+    sync::{Mutex, OnceLock},
+};
 
 /// Identifier in EXPRESS language must be one of scopes described in
 /// "Table 9 – Scope and identifier defining items"
@@ -46,13 +52,56 @@ pub enum ScopeType {
 /// assert!(!(schema1 <= schema2));
 /// assert!(!(schema1 >= schema2));
 /// ```
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct Scope(Vec<(ScopeType, String)>);
+///
+/// Interning
+/// ----------
+/// Scopes are interned as a tree of nodes shared in the whole process,
+/// and [Scope] is a copyable reference to a node.
+/// Two scopes are equal iff they refer the same node:
+///
+/// ```
+/// # use espr::ir::*;
+/// let a = Scope::root().schema("schema").entity("entity");
+/// let b = Scope::root().schema("schema").entity("entity");
+/// assert_eq!(a, b);
+/// assert_eq!(a.popped(), Some(Scope::root().schema("schema")));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Scope(Option<&'static ScopeNode>);
+
+#[derive(Debug)]
+struct ScopeNode {
+    parent: Scope,
+    ty: ScopeType,
+    name: Symbol,
+    depth: usize,
+}
+
+type ScopeKey = (Option<usize> /* parent address */, ScopeType, Symbol);
+
+fn scope_interner() -> &'static Mutex<HashMap<ScopeKey, &'static ScopeNode>> {
+    static INTERNER: OnceLock<Mutex<HashMap<ScopeKey, &'static ScopeNode>>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl PartialEq for Scope {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl Eq for Scope {}
+
+impl hash::Hash for Scope {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.address().hash(state)
+    }
+}
 
 // Custom debug output like: `schema.entity`
 impl fmt::Display for Scope {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0.iter().map(|(_ty, name)| name).join("."))
+        write!(f, "{}", self.iter().map(|(_ty, name)| name).join("."))
     }
 }
 
@@ -60,7 +109,7 @@ impl fmt::Display for Scope {
 impl fmt::Debug for Scope {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Scope(")?;
-        for (i, (ty, name)) in self.0.iter().enumerate() {
+        for (i, (ty, name)) in self.iter().enumerate() {
             if i != 0 {
                 write!(f, ".")?;
             }
@@ -73,12 +122,12 @@ impl fmt::Debug for Scope {
 
 impl PartialOrd for Scope {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
-        for (lhs, rhs) in self.0.iter().zip(other.0.iter()) {
-            if lhs != rhs {
-                return None;
-            }
+        let (lhs, rhs) = (self.depth(), other.depth());
+        let common = cmp::min(lhs, rhs);
+        if self.ancestor(common) != other.ancestor(common) {
+            return None;
         }
-        Some(other.0.len().cmp(&self.0.len()))
+        Some(rhs.cmp(&lhs))
     }
 }
 
@@ -93,13 +142,23 @@ macro_rules! add_scope {
 
 impl Scope {
     pub fn root() -> Self {
-        Self(Vec::new())
+        Self(None)
     }
 
     pub fn pushed(&self, ty: ScopeType, name: &str) -> Self {
-        let mut new = self.clone();
-        new.0.push((ty, name.to_string()));
-        new
+        let name = Symbol::new(name);
+        let mut interner = scope_interner().lock().unwrap();
+        let node = *interner
+            .entry((self.address(), ty, name))
+            .or_insert_with(|| {
+                Box::leak(Box::new(ScopeNode {
+                    parent: *self,
+                    ty,
+                    name,
+                    depth: self.depth() + 1,
+                }))
+            });
+        Self(Some(node))
     }
 
     add_scope!(entity, Entity);
@@ -113,21 +172,48 @@ impl Scope {
     add_scope!(subtype, SubType);
     add_scope!(r#type, Type);
 
+    /// Scope types and names from the outermost one
+    pub fn iter(&self) -> impl Iterator<Item = (ScopeType, &'static str)> {
+        let mut nodes = Vec::with_capacity(self.depth());
+        let mut current = self.0;
+        while let Some(node) = current {
+            nodes.push((node.ty, node.name.as_str()));
+            current = node.parent.0;
+        }
+        nodes.into_iter().rev()
+    }
+
     /// Pop the last scope
     ///
     /// Returns `None` when `self` is root.
     pub fn popped(&self) -> Option<Self> {
-        let mut new = self.clone();
-        let _current = new.0.pop()?;
-        Some(new)
+        self.0.map(|node| node.parent)
+    }
+
+    /// Number of scopes from the root
+    pub fn depth(&self) -> usize {
+        self.0.map_or(0, |node| node.depth)
+    }
+
+    /// Ancestor scope at `depth`, which must be smaller than or equal to `self.depth()`
+    fn ancestor(&self, depth: usize) -> Scope {
+        let mut current = *self;
+        while current.depth() > depth {
+            current = current.popped().unwrap();
+        }
+        current
+    }
+
+    fn address(&self) -> Option<usize> {
+        self.0.map(|node| node as *const ScopeNode as usize)
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Path {
     pub scope: Scope,
     pub ty: ScopeType,
-    pub name: String,
+    pub name: Symbol,
 }
 
 impl fmt::Display for Path {
@@ -154,9 +240,9 @@ macro_rules! new_path {
 impl Path {
     pub fn new(scope: &Scope, ty: ScopeType, name: &str) -> Self {
         Path {
-            scope: scope.clone(),
+            scope: *scope,
             ty,
-            name: name.to_string(),
+            name: Symbol::new(name),
         }
     }
 
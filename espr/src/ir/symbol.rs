@@ -0,0 +1,118 @@
+use std::{
+    collections::HashSet,
+    fmt, hash,
+    ops::Deref,
+    sync::{Mutex, OnceLock},
+};
+
+/// Interned identifier
+///
+/// Each distinct string is stored only once for the whole process,
+/// and [Symbol] is a copyable reference to it.
+/// Equality and hashing compare the address of the stored string instead of its content:
+///
+/// ```
+/// # use espr::ir::*;
+/// let a = Symbol::new("cartesian_point");
+/// let b = Symbol::new(&String::from("cartesian_point"));
+/// assert_eq!(a, b);
+/// assert_eq!(a.as_str().as_ptr(), b.as_str().as_ptr());
+/// assert_eq!(a, "cartesian_point");
+/// ```
+///
+/// Interned strings are never freed, as identifiers in schemas are bounded
+/// and they live until code generation finishes.
+#[derive(Clone, Copy)]
+pub struct Symbol(&'static str);
+
+fn interner() -> &'static Mutex<HashSet<&'static str>> {
+    static INTERNER: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    INTERNER.get_or_init(Default::default)
+}
+
+impl Symbol {
+    pub fn new(s: &str) -> Self {
+        let mut interner = interner().lock().unwrap();
+        if let Some(interned) = interner.get(s) {
+            return Symbol(interned);
+        }
+        let interned: &'static str = Box::leak(s.to_string().into_boxed_str());
+        interner.insert(interned);
+        Symbol(interned)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl hash::Hash for Symbol {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().hash(state)
+    }
+}
+
+// Ordered by content for deterministic output
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(other.0)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        Symbol::new(s)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.0, f)
+    }
+}
+
+// Same as `String` to keep debug output of IR
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
@@ -7,6 +7,7 @@ use crate::ast;
 pub struct Simple {
     pub id: String,
     pub ty: SimpleType,
+    pub rules: Vec<DomainRule>,
 }
 
 /// Rename of user defined type,
@@ -15,6 +16,19 @@ pub struct Simple {
 pub struct Rename {
     pub id: String,
     pub ty: TypeRef,
+    /// Rules in `WHERE` clause. This type is not a mere alias of `ty` if they exist.
+    pub rules: Vec<DomainRule>,
+}
+
+/// Alias of a defined type collapsed from a chain of [Rename]s,
+/// e.g. `TYPE a = b; END_TYPE; TYPE b = c; END_TYPE;` where `c` is not a rename.
+///
+/// This is created by [IR::collapse_alias_chains], never by legalizing AST directly.
+/// `ty` is a [TypeRef::Named] referring the end of the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alias {
+    pub id: String,
+    pub ty: TypeRef,
 }
 
 /// Enumeration of values,
@@ -22,7 +36,11 @@ pub struct Rename {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Enumeration {
     pub id: String,
+    /// Items including ones extended by `BASED_ON`, see [extended_items]
     pub items: Vec<String>,
+    /// Extended type by `ENUMERATION BASED_ON`
+    pub based_on: Option<TypeRef>,
+    pub rules: Vec<DomainRule>,
 }
 
 /// Select of user defined types,
@@ -30,7 +48,11 @@ pub struct Enumeration {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Select {
     pub id: String,
+    /// Types including ones extended by `BASED_ON`, see [extended_items]
     pub types: Vec<TypeRef>,
+    /// Extended type by `SELECT BASED_ON`
+    pub based_on: Option<TypeRef>,
+    pub rules: Vec<DomainRule>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -39,6 +61,7 @@ pub enum TypeDecl {
     Rename(Rename),
     Enumeration(Enumeration),
     Select(Select),
+    Alias(Alias),
 }
 
 impl TypeDecl {
@@ -48,6 +71,7 @@ impl TypeDecl {
             TypeDecl::Rename(e) => &e.id,
             TypeDecl::Enumeration(e) => &e.id,
             TypeDecl::Select(e) => &e.id,
+            TypeDecl::Alias(e) => &e.id,
         }
     }
 }
@@ -62,37 +86,64 @@ impl Legalize for TypeDecl {
     ) -> Result<Self, SemanticError> {
         use ast::Type;
         let id = type_decl.type_id.clone();
+        let rules = DomainRule::from_where_clause(type_decl.where_clause.as_ref());
         Ok(match &type_decl.underlying_type {
             Type::Simple(ty) => TypeDecl::Simple(Simple {
                 id,
-                ty: SimpleType(*ty),
+                ty: SimpleType::legalize(ns, ss, scope, ty)?,
+                rules,
             }),
             Type::Named(name) => {
                 let (path, _index) = ns.resolve(scope, name)?;
                 TypeDecl::Rename(Rename {
                     id,
                     ty: TypeRef::from_path(ns, ss, &path)?,
+                    rules,
                 })
             }
-            Type::Enumeration {
-                items,
-                extensibility: _,
-            } => TypeDecl::Enumeration(Enumeration {
-                id,
-                items: items.clone(),
-            }),
-            Type::Select {
-                types,
-                extensibility: _,
-            } => {
-                let types = types
-                    .iter()
-                    .map(|ty| {
-                        let (path, _index) = ns.resolve(scope, ty)?;
-                        TypeRef::from_path(ns, ss, &path)
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
-                TypeDecl::Select(Select { id, types })
+            Type::Enumeration { based_on, .. } => {
+                let path = Path::new(scope, ScopeType::Type, &id);
+                let mut items: Vec<String> = Vec::new();
+                for (_scope, item) in extended_items(ns, &path, false)? {
+                    if !items.contains(&item) {
+                        items.push(item);
+                    }
+                }
+                if items.is_empty() {
+                    return Err(SemanticError::Unsupported {
+                        construct: "ENUMERATION without items even in its extensions".to_string(),
+                        scope: *scope,
+                    });
+                }
+                TypeDecl::Enumeration(Enumeration {
+                    id,
+                    items,
+                    based_on: legalize_based_on(ns, ss, scope, based_on.as_ref())?,
+                    rules,
+                })
+            }
+            Type::Select { based_on, .. } => {
+                let path = Path::new(scope, ScopeType::Type, &id);
+                let mut types = Vec::new();
+                for (scope, ty) in extended_items(ns, &path, true)? {
+                    let (path, _index) = ns.resolve(&scope, &ty)?;
+                    let ty = TypeRef::from_path(ns, ss, &path)?;
+                    if !types.contains(&ty) {
+                        types.push(ty);
+                    }
+                }
+                if types.is_empty() {
+                    return Err(SemanticError::Unsupported {
+                        construct: "SELECT without items even in its extensions".to_string(),
+                        scope: *scope,
+                    });
+                }
+                TypeDecl::Select(Select {
+                    id,
+                    types,
+                    based_on: legalize_based_on(ns, ss, scope, based_on.as_ref())?,
+                    rules,
+                })
             }
             Type::Set { base, bound } => {
                 let base = TypeRef::legalize(ns, ss, scope, base.as_ref())?;
@@ -107,6 +158,7 @@ impl Legalize for TypeDecl {
                         base: Box::new(base),
                         bound,
                     },
+                    rules,
                 })
             }
             Type::List {
@@ -127,9 +179,135 @@ impl Legalize for TypeDecl {
                         bound,
                         unique: *unique,
                     },
+                    rules,
+                })
+            }
+            Type::Bag { .. } | Type::Array { .. } => TypeDecl::Rename(Rename {
+                id,
+                ty: TypeRef::legalize(ns, ss, scope, &type_decl.underlying_type)?,
+                rules,
+            }),
+            ty => {
+                return Err(SemanticError::Unsupported {
+                    construct: format!("{} in TYPE declaration", unsupported_construct(ty)),
+                    scope: *scope,
                 })
             }
-            _ => panic!(),
         })
     }
 }
+
+fn legalize_based_on(
+    ns: &Namespace,
+    ss: &Constraints,
+    scope: &Scope,
+    based_on: Option<&String>,
+) -> Result<Option<TypeRef>, SemanticError> {
+    based_on
+        .map(|name| TypeRef::from_path(ns, ss, &ns.resolve(scope, name)?.0))
+        .transpose()
+}
+
+/// Own items and `BASED_ON` type of the `SELECT` (if `select`) or `ENUMERATION` type at `path`
+fn extension<'ns>(
+    ns: &'ns Namespace,
+    path: &Path,
+    select: bool,
+) -> Result<(&'ns [String], Option<&'ns String>), SemanticError> {
+    let (named, _index) = ns.get(path)?;
+    match (named, select) {
+        (
+            Named::Type(ast::TypeDecl {
+                underlying_type:
+                    ast::Type::Select {
+                        types, based_on, ..
+                    },
+                ..
+            }),
+            true,
+        ) => Ok((types, based_on.as_ref())),
+        (
+            Named::Type(ast::TypeDecl {
+                underlying_type:
+                    ast::Type::Enumeration {
+                        items, based_on, ..
+                    },
+                ..
+            }),
+            false,
+        ) => Ok((items, based_on.as_ref())),
+        _ => Err(SemanticError::Unsupported {
+            construct: format!(
+                "BASED_ON {} which is not {}",
+                path.name,
+                if select { "SELECT" } else { "ENUMERATION" }
+            ),
+            scope: path.scope,
+        }),
+    }
+}
+
+/// Types in the `BASED_ON` chain starting from `path` itself
+fn based_on_chain(ns: &Namespace, path: &Path, select: bool) -> Result<Vec<Path>, SemanticError> {
+    let mut chain = vec![*path];
+    let mut current = *path;
+    while let (_, Some(based_on)) = extension(ns, &current, select)? {
+        current = ns.resolve(&current.scope, based_on)?.0;
+        if let Some(start) = chain.iter().position(|p| p == &current) {
+            let mut cycle = chain.split_off(start);
+            cycle.push(current);
+            return Err(SemanticError::CyclicTypeDefinition(cycle));
+        }
+        chain.push(current);
+    }
+    Ok(chain)
+}
+
+/// Items of the `SELECT` (if `select`) or `ENUMERATION` type at `path` extended by `BASED_ON`,
+/// with the scopes where they are declared
+///
+/// ```text
+/// TYPE a = EXTENSIBLE SELECT (x); END_TYPE;
+/// TYPE b = SELECT BASED_ON a WITH (y); END_TYPE;
+/// ```
+///
+/// - Items of the types in the `BASED_ON` chain precede its own items, i.e. `b` has `x` and `y`.
+/// - Items added by the types based on it are also its values, i.e. `a` also has `y`.
+///
+/// Error
+/// ------
+/// - [SemanticError::CyclicTypeDefinition] if the `BASED_ON` chain forms a cycle
+///
+pub fn extended_items(
+    ns: &Namespace,
+    path: &Path,
+    select: bool,
+) -> Result<Vec<(Scope, String)>, SemanticError> {
+    let mut items = Vec::new();
+    for p in based_on_chain(ns, path, select)?.iter().rev() {
+        let (own, _) = extension(ns, p, select)?;
+        items.extend(own.iter().map(|item| (p.scope, item.clone())));
+    }
+    for (p, named) in &ns.ast {
+        let based_on = match named {
+            Named::Type(ast::TypeDecl {
+                underlying_type:
+                    ast::Type::Select { based_on, .. } | ast::Type::Enumeration { based_on, .. },
+                ..
+            }) => based_on,
+            _ => continue,
+        };
+        if based_on.is_none() || p == path {
+            continue;
+        }
+        // Broken chains are reported while legalizing the type itself
+        let Ok(chain) = based_on_chain(ns, p, select) else {
+            continue;
+        };
+        if chain[1..].contains(path) {
+            let (own, _) = extension(ns, p, select)?;
+            items.extend(own.iter().map(|item| (p.scope, item.clone())));
+        }
+    }
+    Ok(items)
+}
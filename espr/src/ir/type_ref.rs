@@ -9,15 +9,35 @@ impl Legalize for SimpleType {
     fn legalize(
         _ns: &Namespace,
         _ss: &Constraints,
-        _scope: &Scope,
+        scope: &Scope,
         input: &Self::Input,
     ) -> Result<Self, SemanticError> {
-        Ok(SimpleType(*input))
+        match input {
+            ast::SimpleType::Binary { .. } => Err(SemanticError::Unsupported {
+                construct: "BINARY".to_string(),
+                scope: *scope,
+            }),
+            _ => Ok(SimpleType(*input)),
+        }
     }
 }
 
+/// Bound of an aggregation type, e.g. `[1:3]` of `ARRAY [1:3] OF REAL`
+///
+/// Only integer literals are evaluated, and other expressions, e.g. constants or `?`, are `None`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Bound {}
+pub struct Bound {
+    pub lower: Option<i64>,
+    pub upper: Option<i64>,
+}
+
+impl Bound {
+    /// Number of elements of an `ARRAY` if both bounds are literals
+    pub fn size(&self) -> Option<usize> {
+        let (lower, upper) = (self.lower?, self.upper?);
+        usize::try_from(upper - lower + 1).ok()
+    }
+}
 
 impl Legalize for Bound {
     type Input = ast::Bound;
@@ -25,10 +45,26 @@ impl Legalize for Bound {
         _ns: &Namespace,
         _ss: &Constraints,
         _scope: &Scope,
-        _input: &Self::Input,
+        input: &Self::Input,
     ) -> Result<Self, SemanticError> {
-        // FIXME
-        Ok(Bound {})
+        Ok(Bound {
+            lower: integer_literal(&input.lower),
+            upper: integer_literal(&input.upper),
+        })
+    }
+}
+
+/// Value of an integer literal, e.g. `3` or `-1`, which is parsed as a real literal
+fn integer_literal(expr: &ast::Expression) -> Option<i64> {
+    match expr {
+        ast::Expression::Literal(ast::Literal::Real(value)) if value.fract() == 0.0 => {
+            Some(*value as i64)
+        }
+        ast::Expression::Unary {
+            op: ast::UnaryOperator::Minus,
+            arg,
+        } => integer_literal(arg).map(|value| -value),
+        _ => None,
     }
 }
 
@@ -36,7 +72,7 @@ impl Legalize for Bound {
 pub enum TypeRef {
     SimpleType(SimpleType),
     Named {
-        name: String,
+        name: Symbol,
 
         /// Scope where the named type is declared
         scope: Scope,
@@ -57,7 +93,7 @@ pub enum TypeRef {
 
     /* Declared as `ENTITY` */
     Entity {
-        name: String,
+        name: Symbol,
         scope: Scope,
         is_supertype: bool,
     },
@@ -67,40 +103,74 @@ pub enum TypeRef {
         base: Box<TypeRef>,
         bound: Option<Bound>,
     },
+    Bag {
+        base: Box<TypeRef>,
+        bound: Option<Bound>,
+    },
     List {
         base: Box<TypeRef>,
         bound: Option<Bound>,
         unique: bool,
     },
+    /// `ARRAY OF OPTIONAL` has `$` for missing elements
+    Array {
+        base: Box<TypeRef>,
+        bound: Option<Bound>,
+        unique: bool,
+        optional: bool,
+    },
 }
 
 impl TypeRef {
     /// Returns `true` iff `self` is:
     /// - a simple type,
     /// - a named type whose underlying type is simple, or,
-    /// - a set or list of a type `x` such that `x.is_simple() == true`.
+    /// - a set, bag, list, or array of a type `x` such that `x.is_simple() == true`.
     pub fn is_simple(&self) -> bool {
         match self {
             TypeRef::SimpleType(..) => true,
             TypeRef::Named { is_simple, .. } => *is_simple,
-            TypeRef::Set { base, .. } | TypeRef::List { base, .. } => base.is_simple(),
+            TypeRef::Set { base, .. }
+            | TypeRef::Bag { base, .. }
+            | TypeRef::List { base, .. }
+            | TypeRef::Array { base, .. } => base.is_simple(),
             _ => false,
         }
     }
 
+    /// Number of elements if this is an `ARRAY` generated as a fixed-size array `[T; N]`
+    ///
+    /// `None` for other types, and for an `ARRAY` whose bound is not literal
+    /// or whose size exceeds the arrays supported by serde, which is generated as `Vec<T>`.
+    pub fn array_size(&self) -> Option<usize> {
+        match self {
+            TypeRef::Array {
+                bound: Some(bound), ..
+            } => bound.size().filter(|size| (1..=32).contains(size)),
+            _ => None,
+        }
+    }
+
     pub fn from_path(ns: &Namespace, ss: &Constraints, path: &Path) -> Result<Self, SemanticError> {
         match path.ty {
             ScopeType::Entity => {
                 let is_supertype = ss.is_supertype(path);
                 Ok(TypeRef::Entity {
-                    name: path.name.clone(),
-                    scope: path.scope.clone(),
+                    name: path.name,
+                    scope: path.scope,
                     is_supertype,
                 })
             }
             ScopeType::Type => {
-                let mut p = path.clone();
+                let mut p = *path;
+                let mut visited = Vec::new();
                 let is_simple = loop {
+                    if let Some(start) = visited.iter().position(|v| v == &p) {
+                        let mut cycle = visited.split_off(start);
+                        cycle.push(p);
+                        return Err(SemanticError::CyclicTypeDefinition(cycle));
+                    }
+                    visited.push(p);
                     match ns.get(&p)?.0 {
                         Named::Type(ast::TypeDecl {
                             underlying_type, ..
@@ -140,13 +210,17 @@ impl TypeRef {
                     Named::Entity(_) => false,
                 };
                 Ok(TypeRef::Named {
-                    scope: path.scope.clone(),
-                    name: path.name.clone(),
+                    scope: path.scope,
+                    name: path.name,
                     is_simple,
                     is_enumerate,
                 })
             }
-            _ => unimplemented!("Path to TypeRef conversion only supports Entity and Types yet."),
+            // Path to TypeRef conversion only supports Entity and Types yet
+            _ => Err(SemanticError::Unsupported {
+                construct: format!("Reference to {}", path),
+                scope: path.scope,
+            }),
         }
     }
 }
@@ -162,7 +236,7 @@ impl Legalize for TypeRef {
     ) -> Result<Self, SemanticError> {
         use ast::Type::*;
         Ok(match ty {
-            Simple(ty) => Self::SimpleType(SimpleType(*ty)),
+            Simple(ty) => Self::SimpleType(SimpleType::legalize(ns, ss, scope, ty)?),
             Named(name) => {
                 let (path, _index) = ns.resolve(scope, name)?;
                 Self::from_path(ns, ss, &path)?
@@ -179,6 +253,18 @@ impl Legalize for TypeRef {
                     bound,
                 }
             }
+            Bag { base, bound } => {
+                let base = TypeRef::legalize(ns, ss, scope, base.as_ref())?;
+                let bound = if let Some(bound) = bound {
+                    Some(Legalize::legalize(ns, ss, scope, bound)?)
+                } else {
+                    None
+                };
+                Self::Bag {
+                    base: Box::new(base),
+                    bound,
+                }
+            }
             List {
                 base,
                 bound,
@@ -196,7 +282,49 @@ impl Legalize for TypeRef {
                     unique: *unique,
                 }
             }
-            _ => todo!(),
+            Array {
+                base,
+                bound,
+                unique,
+                optional,
+            } => {
+                let base = TypeRef::legalize(ns, ss, scope, base.as_ref())?;
+                let bound = if let Some(bound) = bound {
+                    Some(Legalize::legalize(ns, ss, scope, bound)?)
+                } else {
+                    None
+                };
+                Self::Array {
+                    base: Box::new(base),
+                    bound,
+                    unique: *unique,
+                    optional: *optional,
+                }
+            }
+            _ => {
+                return Err(SemanticError::Unsupported {
+                    construct: unsupported_construct(ty).to_string(),
+                    scope: *scope,
+                })
+            }
         })
     }
 }
+
+/// Name of the type construct which cannot be legalized yet
+pub(super) fn unsupported_construct(ty: &ast::Type) -> &'static str {
+    use ast::Type::*;
+    match ty {
+        Simple(_) => "Simple type",
+        Named(_) => "Named type",
+        Set { .. } => "SET",
+        Bag { .. } => "BAG",
+        List { .. } => "LIST",
+        Array { .. } => "ARRAY",
+        Enumeration { .. } => "ENUMERATION",
+        Select { .. } => "SELECT",
+        Aggregate { .. } => "AGGREGATE",
+        GenericEntity(_) => "GENERIC_ENTITY",
+        Generic(_) => "GENERIC",
+    }
+}
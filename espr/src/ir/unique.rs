@@ -0,0 +1,55 @@
+use super::{namespace::*, scope::*, *};
+use crate::ast;
+
+/// Rule in `UNIQUE` clause of entity,
+/// e.g. `ur1: revision_identifier, drawing_identifier;`
+///
+/// The values of these attributes must be jointly unique among all instances of the entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueRule {
+    /// Label of the rule, e.g. `ur1`
+    pub label: Option<String>,
+    pub attributes: Vec<UniqueAttribute>,
+}
+
+/// Attribute referred in [UniqueRule]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueAttribute {
+    pub name: String,
+    /// Supertype declaring the attribute, e.g. `named_unit` of `SELF\named_unit.name`
+    pub group: Option<TypeRef>,
+}
+
+impl Legalize for UniqueRule {
+    type Input = ast::UniqueRule;
+
+    fn legalize(
+        ns: &Namespace,
+        ss: &Constraints,
+        scope: &Scope,
+        rule: &Self::Input,
+    ) -> Result<Self, SemanticError> {
+        let attributes = rule
+            .attributes
+            .iter()
+            .map(|attr| {
+                Ok(match attr {
+                    ast::AttributeDecl::Reference(name) => UniqueAttribute {
+                        name: name.clone(),
+                        group: None,
+                    },
+                    ast::AttributeDecl::Qualified {
+                        group, attribute, ..
+                    } => UniqueAttribute {
+                        name: attribute.clone(),
+                        group: Some(TypeRef::from_path(ns, ss, &ns.resolve(scope, group)?.0)?),
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, SemanticError>>()?;
+        Ok(UniqueRule {
+            label: rule.name.clone(),
+            attributes,
+        })
+    }
+}
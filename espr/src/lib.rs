@@ -35,6 +35,9 @@
 //! - Code Generation
 //!   - [codegen::rust] module generates Rust code from IR
 //!
+//! [check] runs tokenize and legalize steps to report problems in schema as [diagnostics::Diagnostic]s
+//! for editor tooling.
+//!
 //! Introduction to STEP
 //! ---------------------
 //!
@@ -153,5 +156,8 @@
 
 pub mod ast;
 pub mod codegen;
+pub mod diagnostics;
 pub mod ir;
 pub mod parser;
+
+pub use diagnostics::check;
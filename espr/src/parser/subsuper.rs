@@ -163,7 +163,7 @@ pub fn total_over(input: &str) -> ParseResult<Vec<String>> {
     tuple((
         tag("TOTAL_OVER"),
         char('('),
-        many1(entity_ref),
+        comma_separated(entity_ref),
         char(')'),
         char(';'),
     ))
@@ -197,4 +197,25 @@ mod tests {
         dbg!(&entity);
         assert_eq!(residual, "");
     }
+
+    #[test]
+    fn subtype_constraint_total_over() {
+        let exp_str = r#"
+        SUBTYPE_CONSTRAINT person_sex FOR person;
+          TOTAL_OVER(male, female);
+          ONEOF(male, female);
+        END_SUBTYPE_CONSTRAINT;
+        "#
+        .trim();
+
+        let (residual, (constraint, _remark)) =
+            super::subtype_constraint_decl(exp_str).finish().unwrap();
+        assert_eq!(residual, "");
+        assert!(!constraint.is_abstract);
+        assert_eq!(
+            constraint.total_over,
+            Some(vec!["male".to_string(), "female".to_string()])
+        );
+        assert!(constraint.expr.is_some());
+    }
 }
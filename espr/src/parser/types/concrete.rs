@@ -96,3 +96,58 @@ pub fn bound_spec(input: &str) -> ParseResult<Bound> {
 pub fn instantiable_type(input: &str) -> ParseResult<Type> {
     alt((concrete_types, entity_ref.map(Type::Named))).parse(input)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::Finish;
+
+    #[test]
+    fn nested_aggregation() {
+        let (res, (ty, _remarks)) =
+            super::aggregation_types("LIST [2:?] OF LIST [2:?] OF cartesian_point")
+                .finish()
+                .unwrap();
+        assert_eq!(res, "");
+        let bound = Some(Bound {
+            lower: Expression::real(2.0),
+            upper: Expression::indeterminate(),
+        });
+        assert_eq!(
+            ty,
+            Type::List {
+                base: Box::new(Type::List {
+                    base: Box::new(Type::Named("cartesian_point".to_string())),
+                    bound: bound.clone(),
+                    unique: false,
+                }),
+                bound,
+                unique: false,
+            }
+        );
+
+        let (res, (ty, _remarks)) = super::aggregation_types("LIST OF SET OF ARRAY [1:2] OF REAL")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            ty,
+            Type::List {
+                base: Box::new(Type::Set {
+                    base: Box::new(Type::Array {
+                        base: Box::new(Type::Simple(SimpleType::Real)),
+                        bound: Some(Bound {
+                            lower: Expression::real(1.0),
+                            upper: Expression::real(2.0),
+                        }),
+                        unique: false,
+                        optional: false,
+                    }),
+                    bound: None,
+                }),
+                bound: None,
+                unique: false,
+            }
+        );
+    }
+}
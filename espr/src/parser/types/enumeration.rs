@@ -8,24 +8,35 @@ pub fn enumeration_items(input: &str) -> ParseResult<Vec<String>> {
         .parse(input)
 }
 
-/// 213 enumeration_type = \[ EXTENSIBLE \] ENUMERATION \[ ( OF [enumeration_items] ) | enumeration_extension \] .
+/// 213 enumeration_type = \[ EXTENSIBLE \] ENUMERATION \[ ( OF [enumeration_items] ) | [enumeration_extension] \] .
 pub fn enumeration_type(input: &str) -> ParseResult<Type> {
-    // FIXME enumeration_extension
-    tuple((
-        opt(tag("EXTENSIBLE")),
-        tag("ENUMERATION"),
-        tag("OF"),
-        enumeration_items,
-    ))
-    .map(|(extensiblility, _start, _of, items)| Type::Enumeration {
-        extensibility: if extensiblility.is_some() {
-            Extensibility::Extensible
-        } else {
-            Extensibility::None
-        },
-        items,
-    })
-    .parse(input)
+    // `ENUMERATION;` without items is allowed for extensible enumeration
+    let items = alt((
+        tuple((tag("OF"), enumeration_items)).map(|(_of, items)| (items, None)),
+        enumeration_extension.map(|(based_on, items)| (items, Some(based_on))),
+    ));
+    tuple((opt(tag("EXTENSIBLE")), tag("ENUMERATION"), opt(items)))
+        .map(|(extensiblility, _start, items)| {
+            let (items, based_on) = items.unwrap_or_default();
+            Type::Enumeration {
+                extensibility: if extensiblility.is_some() {
+                    Extensibility::Extensible
+                } else {
+                    Extensibility::None
+                },
+                items,
+                based_on,
+            }
+        })
+        .parse(input)
+}
+
+/// 214 enumeration_extension = BASED_ON [type_ref] \[ WITH [enumeration_items] \] .
+pub fn enumeration_extension(input: &str) -> ParseResult<(String, Vec<String>)> {
+    let with = tuple((tag("WITH"), enumeration_items)).map(|(_with, items)| items);
+    tuple((tag("BASED_ON"), type_ref, opt(with)))
+        .map(|(_based_on, id, opt)| (id, opt.unwrap_or_default()))
+        .parse(input)
 }
 
 #[cfg(test)]
@@ -48,7 +59,8 @@ mod tests {
                     "down".to_string(),
                     "left".to_string(),
                     "right".to_string()
-                ]
+                ],
+                based_on: None,
             }
         );
     }
@@ -69,7 +81,8 @@ mod tests {
                     "down".to_string(),
                     "left".to_string(),
                     "right".to_string()
-                ]
+                ],
+                based_on: None,
             }
         );
 
@@ -80,4 +93,34 @@ mod tests {
         .finish()
         .is_err());
     }
+
+    #[test]
+    fn enumeration_based_on() {
+        let (residual, (e, _remark)) =
+            super::enumeration_type("ENUMERATION BASED_ON text_path WITH (forward, backward)")
+                .finish()
+                .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(
+            e,
+            super::Type::Enumeration {
+                extensibility: super::Extensibility::None,
+                items: vec!["forward".to_string(), "backward".to_string()],
+                based_on: Some("text_path".to_string()),
+            }
+        );
+
+        let (residual, (e, _remark)) = super::enumeration_type("EXTENSIBLE ENUMERATION")
+            .finish()
+            .unwrap();
+        assert_eq!(residual, "");
+        assert_eq!(
+            e,
+            super::Type::Enumeration {
+                extensibility: super::Extensibility::Extensible,
+                items: Vec::new(),
+                based_on: None,
+            }
+        );
+    }
 }
@@ -18,8 +18,6 @@ pub fn select_extension(input: &str) -> ParseResult<(String, Vec<String>)> {
 
 /// 302 select_type = \[ EXTENSIBLE \[ GENERIC_ENTITY \] \] SELECT \[ [select_list] | [select_extension] \] .
 pub fn select_type(input: &str) -> ParseResult<Type> {
-    // FIXME support select_extension
-
     // `GENERIC_ENTITY` only appears in `select_type` declaration.
     let extensibility = tuple((
         tag("EXTENSIBLE"),
@@ -32,23 +30,27 @@ pub fn select_type(input: &str) -> ParseResult<Type> {
             Extensibility::Extensible
         }
     });
+    // `SELECT;` without items is allowed for extensible select
+    let items = alt((
+        select_list.map(|types| (types, None)),
+        select_extension.map(|(based_on, types)| (types, Some(based_on))),
+    ));
 
     tuple((
         opt(tuple((extensibility, spaces))),
         tag("SELECT"),
-        select_list,
+        opt(items),
     ))
-    .map(|(opt, _select, types)| {
-        if let Some((extensibility, _spaces)) = opt {
-            Type::Select {
-                extensibility,
-                types,
-            }
-        } else {
-            Type::Select {
-                extensibility: Extensibility::None,
-                types,
-            }
+    .map(|(opt, _select, items)| {
+        let extensibility = match opt {
+            Some((extensibility, _spaces)) => extensibility,
+            None => Extensibility::None,
+        };
+        let (types, based_on) = items.unwrap_or_default();
+        Type::Select {
+            extensibility,
+            types,
+            based_on,
         }
     })
     .parse(input)
@@ -66,6 +68,7 @@ mod tests {
         if let Type::Select {
             extensibility,
             types,
+            based_on: None,
         } = s
         {
             assert_eq!(extensibility, Extensibility::None);
@@ -75,4 +78,50 @@ mod tests {
             panic!()
         }
     }
+
+    #[test]
+    fn extensible_select() {
+        let (res, (s, _remarks)) = super::select_type("EXTENSIBLE GENERIC_ENTITY SELECT")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            s,
+            Type::Select {
+                extensibility: Extensibility::GenericEntity,
+                types: Vec::new(),
+                based_on: None,
+            }
+        );
+    }
+
+    #[test]
+    fn select_based_on() {
+        let (res, (s, _remarks)) = super::select_type("SELECT BASED_ON a WITH (x, y)")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            s,
+            Type::Select {
+                extensibility: Extensibility::None,
+                types: vec!["x".to_string(), "y".to_string()],
+                based_on: Some("a".to_string()),
+            }
+        );
+
+        // `WITH` clause is optional
+        let (res, (s, _remarks)) = super::select_type("EXTENSIBLE SELECT BASED_ON a")
+            .finish()
+            .unwrap();
+        assert_eq!(res, "");
+        assert_eq!(
+            s,
+            Type::Select {
+                extensibility: Extensibility::Extensible,
+                types: Vec::new(),
+                based_on: Some("a".to_string()),
+            }
+        );
+    }
 }
@@ -0,0 +1,483 @@
+use espr::{
+    ast::SyntaxTree,
+    codegen::rust::*,
+    ir::{SemanticError, IR},
+};
+
+const CHAIN: &str = r#"
+SCHEMA test_schema;
+  TYPE a = b;
+  END_TYPE;
+
+  TYPE b = c;
+  END_TYPE;
+
+  TYPE c = d;
+  END_TYPE;
+
+  TYPE d = REAL;
+  END_TYPE;
+
+  ENTITY e;
+    a: a;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn alias_chain() {
+    let st = SyntaxTree::parse(CHAIN).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{
+            as_holder, derive_more::{AsRef, Deref, DerefMut, From, Into},
+            Holder, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            e: HashMap<u64, as_holder!(E)>,
+            #[table_init(alias = "A")]
+            #[table_init(alias = "B")]
+            #[table_init(alias = "C")]
+            d: HashMap<u64, as_holder!(D)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn e_holders(&self) -> &HashMap<u64, as_holder!(E)> {
+                &self.e
+            }
+            pub fn d_holders(&self) -> &HashMap<u64, as_holder!(D)> {
+                &self.d
+            }
+            pub fn insert_e(
+                &mut self,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_e_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["E", "D"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::E(
+                                Box::new(EntityTable::<as_holder!(E)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::D(
+                                Box::new(EntityTable::<as_holder!(D)>::get_owned(self, id)?),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.e))
+                        .chain(::ruststep::tables::instances(&self.d)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.e))
+                        .chain(::ruststep::tables::table_errors(self, &self.d)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            E(Box<E>),
+            D(Box<D>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "E",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "a",
+                        ty: "A",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(E)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "D",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(D)>,
+            },
+        ];
+        pub type A = D;
+        pub type AHolder = DHolder;
+        pub type B = D;
+        pub type BHolder = DHolder;
+        pub type C = D;
+        pub type CHolder = DHolder;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = d)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(alias = "A")]
+        #[holder(alias = "B")]
+        #[holder(alias = "C")]
+        pub struct D(pub f64);
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = e)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct E {
+            #[holder(use_place_holder)]
+            pub a: A,
+        }
+    }
+    "###);
+}
+
+const CHAIN_WITH_RULE: &str = r#"
+SCHEMA test_schema;
+  TYPE a = b;
+  END_TYPE;
+
+  TYPE b = c;
+  WHERE
+    wr1: SELF > 0.0;
+  END_TYPE;
+
+  TYPE c = d;
+  END_TYPE;
+
+  TYPE d = REAL;
+  END_TYPE;
+
+  ENTITY e;
+    a: a;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn alias_chain_with_rule() {
+    let st = SyntaxTree::parse(CHAIN_WITH_RULE).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{
+            as_holder, derive_more::{AsRef, Deref, DerefMut, From, Into},
+            Holder, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            e: HashMap<u64, as_holder!(E)>,
+            #[table_init(alias = "A")]
+            b: HashMap<u64, as_holder!(B)>,
+            #[table_init(alias = "C")]
+            d: HashMap<u64, as_holder!(D)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn e_holders(&self) -> &HashMap<u64, as_holder!(E)> {
+                &self.e
+            }
+            pub fn b_holders(&self) -> &HashMap<u64, as_holder!(B)> {
+                &self.b
+            }
+            pub fn d_holders(&self) -> &HashMap<u64, as_holder!(D)> {
+                &self.d
+            }
+            pub fn insert_e(
+                &mut self,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_e_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["E", "B", "D"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::E(
+                                Box::new(EntityTable::<as_holder!(E)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::B(
+                                Box::new(EntityTable::<as_holder!(B)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::D(
+                                Box::new(EntityTable::<as_holder!(D)>::get_owned(self, id)?),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.e))
+                        .chain(::ruststep::tables::instances(&self.b))
+                        .chain(::ruststep::tables::instances(&self.d)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.e))
+                        .chain(::ruststep::tables::table_errors(self, &self.b))
+                        .chain(::ruststep::tables::table_errors(self, &self.d)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            E(Box<E>),
+            B(Box<B>),
+            D(Box<D>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "E",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "a",
+                        ty: "A",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(E)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "B",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(B)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "D",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(D)>,
+            },
+        ];
+        pub type A = B;
+        pub type AHolder = BHolder;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = b)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(alias = "A")]
+        pub struct B(#[holder(use_place_holder)] pub C);
+        pub type C = D;
+        pub type CHolder = DHolder;
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = d)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(alias = "C")]
+        pub struct D(pub f64);
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = e)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct E {
+            #[holder(use_place_holder)]
+            pub a: A,
+        }
+    }
+    "###);
+}
+
+const CYCLE: &str = r#"
+SCHEMA test_schema;
+  TYPE a = b;
+  END_TYPE;
+
+  TYPE b = c;
+  END_TYPE;
+
+  TYPE c = a;
+  END_TYPE;
+END_SCHEMA;
+"#;
+
+#[test]
+fn alias_cycle() {
+    let st = SyntaxTree::parse(CYCLE).unwrap();
+    let err = IR::from_syntax_tree(&st).unwrap_err();
+    assert!(matches!(err, SemanticError::CyclicTypeDefinition(_)));
+    insta::assert_snapshot!(err.to_string(), @"Type definitions form a cycle: test_schema.b -> test_schema.c -> test_schema.a -> test_schema.b");
+}
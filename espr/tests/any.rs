@@ -26,13 +26,22 @@ fn any() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ::ruststep::{
+            as_holder, derive_more::{AsMut, AsRef, Deref, DerefMut},
+            Holder, TableInit,
+        };
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
         pub struct Tables {
             base: HashMap<u64, as_holder!(Base)>,
             sub1: HashMap<u64, as_holder!(Sub1)>,
             sub2: HashMap<u64, as_holder!(Sub2)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
         }
         impl Tables {
             pub fn base_holders(&self) -> &HashMap<u64, as_holder!(Base)> {
@@ -44,17 +53,210 @@ fn any() {
             pub fn sub2_holders(&self) -> &HashMap<u64, as_holder!(Sub2)> {
                 &self.sub2
             }
+            pub fn insert_base(
+                &mut self,
+                value: as_holder!(Base),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.base.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_base_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Base),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.base.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_sub1(
+                &mut self,
+                value: as_holder!(Sub1),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.sub1.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_sub1_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Sub1),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.sub1.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn insert_sub2(
+                &mut self,
+                value: as_holder!(Sub2),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.sub2.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(id)
+            }
+            pub fn insert_sub2_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Sub2),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.sub2.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["BASE", "SUB_1", "SUB_2"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Base(
+                                Box::new(
+                                    EntityTable::<as_holder!(Base)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::Sub1(
+                                Box::new(
+                                    EntityTable::<as_holder!(Sub1)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::Sub2(
+                                Box::new(
+                                    EntityTable::<as_holder!(Sub2)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.base))
+                        .chain(::ruststep::tables::instances(&self.sub1))
+                        .chain(::ruststep::tables::instances(&self.sub2)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.base))
+                        .chain(::ruststep::tables::table_errors(self, &self.sub1))
+                        .chain(::ruststep::tables::table_errors(self, &self.sub2)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Base(Box<Base>),
+            Sub1(Box<Sub1>),
+            Sub2(Box<Sub2>),
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = base)]
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "BASE",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "x",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Base)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SUB_1",
+                supertypes: &["BASE"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y1",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Sub1)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SUB_2",
+                supertypes: &["BASE"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y2",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Sub2)>,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = base)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Base {
             pub x: f64,
         }
         #[derive(Debug, Clone, PartialEq, Holder)]
-        # [holder (table = Tables)]
+        #[holder(table = Tables)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub enum BaseAny {
             #[holder(use_place_holder)]
             Base(Box<Base>),
@@ -63,19 +265,19 @@ fn any() {
             #[holder(use_place_holder)]
             Sub2(Box<Sub2>),
         }
-        impl Into<BaseAny> for Base {
-            fn into(self) -> BaseAny {
-                BaseAny::Base(Box::new(self))
+        impl From<Base> for BaseAny {
+            fn from(value: Base) -> Self {
+                BaseAny::Base(Box::new(value))
             }
         }
-        impl Into<BaseAny> for Sub1 {
-            fn into(self) -> BaseAny {
-                BaseAny::Sub1(Box::new(self.into()))
+        impl From<Sub1> for BaseAny {
+            fn from(value: Sub1) -> Self {
+                BaseAny::Sub1(Box::new(value))
             }
         }
-        impl Into<BaseAny> for Sub2 {
-            fn into(self) -> BaseAny {
-                BaseAny::Sub2(Box::new(self.into()))
+        impl From<Sub2> for BaseAny {
+            fn from(value: Sub2) -> Self {
+                BaseAny::Sub2(Box::new(value))
             }
         }
         impl AsRef<Base> for BaseAny {
@@ -87,12 +289,20 @@ fn any() {
                 }
             }
         }
-        #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = sub1)]
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = sub1)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Sub1 {
             #[as_ref]
             #[as_mut]
@@ -102,12 +312,20 @@ fn any() {
             pub base: Base,
             pub y1: f64,
         }
-        #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = sub2)]
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = sub2)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Sub2 {
             #[as_ref]
             #[as_mut]
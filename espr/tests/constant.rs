@@ -0,0 +1,47 @@
+//! Constants in `CONSTANT` block kept in IR, using `dummy_gri` in ISO 10303-42
+//!
+//! Generated code is checked by `tests/corpus/constant.exp`.
+
+use espr::{ast, ast::SyntaxTree, ir::*};
+
+const EXPRESS: &str = r#"
+SCHEMA geometry_schema;
+  CONSTANT
+    dummy_gri : geometric_representation_item := representation_item('') ||
+                geometric_representation_item();
+    max_degree : INTEGER := 25;
+  END_CONSTANT;
+
+  ENTITY representation_item;
+    name : STRING;
+  END_ENTITY;
+
+  ENTITY geometric_representation_item SUBTYPE OF (representation_item);
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn constants() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+
+    let constants = &ir.schemas[0].constants;
+    assert_eq!(constants.len(), 2);
+
+    assert_eq!(constants[0].name, "dummy_gri");
+    assert!(matches!(
+        &constants[0].ty,
+        TypeRef::Entity { name, .. } if name.as_str() == "geometric_representation_item"
+    ));
+
+    assert_eq!(constants[1].name, "max_degree");
+    assert_eq!(
+        constants[1].ty,
+        TypeRef::SimpleType(SimpleType(ast::SimpleType::Integer))
+    );
+    assert_eq!(
+        constants[1].expr,
+        ast::Expression::Literal(ast::Literal::Real(25.0))
+    );
+}
@@ -0,0 +1,45 @@
+//! Snapshots of the code generated for schemas in `tests/corpus/`
+//!
+//! Add a schema as `tests/corpus/{name}.exp`, and its snapshot is created as
+//! `tests/corpus/snapshots/{name}.snap` by `cargo insta review`.
+//! When a change of the generated code is intended, regenerate the snapshots by
+//!
+//! ```text
+//! INSTA_UPDATE=always cargo test -p espr --test corpus
+//! ```
+//!
+//! and review the diff of the snapshots.
+//! The generated code is compiled by `ruststep/tests/corpus.rs`.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+use std::{fs, path::Path};
+
+#[test]
+fn corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut schemas: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "exp"))
+        .collect();
+    schemas.sort();
+    assert!(!schemas.is_empty());
+
+    for path in schemas {
+        let name = path.file_stem().unwrap().to_str().unwrap().to_string();
+        let express = fs::read_to_string(&path).unwrap();
+        let st = SyntaxTree::parse(&express).unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+        let tt = rustfmt(tt);
+
+        insta::with_settings!({
+            snapshot_path => dir.join("snapshots"),
+            prepend_module_to_snapshot => false,
+            input_file => &path,
+        }, {
+            insta::assert_snapshot!(name, tt);
+        });
+    }
+}
@@ -0,0 +1,326 @@
+//! Test for generating code of the supported subset of a schema
+//!
+//! The generated code is compiled by `ruststep/tests/degraded.rs`.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::*};
+
+const MIXED: &str = include_str!("degraded/mixed.exp");
+
+#[test]
+fn strict_generation_fails() {
+    let st = SyntaxTree::parse(MIXED).unwrap();
+    assert!(IR::from_syntax_tree(&st).is_err());
+}
+
+#[test]
+fn skipped() {
+    let st = SyntaxTree::parse(MIXED).unwrap();
+    let outcome = generate_degraded(&st, &Options::default(), CratePrefix::External).unwrap();
+    let skipped: Vec<_> = outcome
+        .skipped
+        .iter()
+        .map(|item| item.to_string())
+        .collect();
+    insta::assert_debug_snapshot!(skipped, @r###"
+    [
+        "TYPE mixed.marker is skipped: declared multiple times",
+        "ENTITY mixed.marker is skipped: declared multiple times",
+        "ENTITY mixed.measured is skipped: `length_unit` imported from `measure_schema` is not resolved",
+        "TYPE mixed.blob is skipped: BINARY is not supported yet",
+        "ENTITY mixed.bitmap is skipped: depends on skipped `mixed.blob`, and dropped from `Any` of `shape`",
+        "TYPE mixed.labels is skipped: depends on skipped `mixed.blob`",
+        "ENTITY mixed.tagged is skipped: depends on skipped `mixed.labels`",
+        "TYPE mixed.shape_select is skipped: depends on skipped `mixed.bitmap`",
+        "ENTITY mixed.drawing is skipped: depends on skipped `mixed.tagged`",
+    ]
+    "###);
+
+    let bitmap = outcome
+        .skipped
+        .iter()
+        .find(|item| item.path.name == "bitmap")
+        .unwrap();
+    assert!(matches!(bitmap.reason, SkipReason::DependsOn(blob) if blob.name == "blob"));
+    assert_eq!(bitmap.dropped_from.len(), 1);
+    assert_eq!(bitmap.dropped_from[0].name, "shape");
+}
+
+#[test]
+fn generated() {
+    let st = SyntaxTree::parse(MIXED).unwrap();
+    let outcome = generate_degraded(&st, &Options::default(), CratePrefix::External).unwrap();
+    let tt = rustfmt(outcome.tokens.to_string());
+    insta::assert_snapshot!(tt, @r###"
+    pub mod mixed {
+        use ::ruststep::{
+            as_holder, derive_more::{AsMut, AsRef, Deref, DerefMut, From, Into},
+            Holder, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            shape: HashMap<u64, as_holder!(Shape)>,
+            circle: HashMap<u64, as_holder!(Circle)>,
+            label: HashMap<u64, as_holder!(Label)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn shape_holders(&self) -> &HashMap<u64, as_holder!(Shape)> {
+                &self.shape
+            }
+            pub fn circle_holders(&self) -> &HashMap<u64, as_holder!(Circle)> {
+                &self.circle
+            }
+            pub fn label_holders(&self) -> &HashMap<u64, as_holder!(Label)> {
+                &self.label
+            }
+            pub fn insert_shape(
+                &mut self,
+                value: as_holder!(Shape),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.shape.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_shape_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Shape),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.shape.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_circle(
+                &mut self,
+                value: as_holder!(Circle),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.circle.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_circle_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Circle),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.circle.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["SHAPE", "CIRCLE", "LABEL"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Shape(
+                                Box::new(
+                                    EntityTable::<as_holder!(Shape)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::Circle(
+                                Box::new(
+                                    EntityTable::<as_holder!(Circle)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::Label(
+                                Box::new(
+                                    EntityTable::<as_holder!(Label)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.shape))
+                        .chain(::ruststep::tables::instances(&self.circle))
+                        .chain(::ruststep::tables::instances(&self.label)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.shape))
+                        .chain(::ruststep::tables::table_errors(self, &self.circle))
+                        .chain(::ruststep::tables::table_errors(self, &self.label)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Shape(Box<Shape>),
+            Circle(Box<Circle>),
+            Label(Box<Label>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SHAPE",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "name",
+                        ty: "LABEL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Shape)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "CIRCLE",
+                supertypes: &["SHAPE"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "radius",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Circle)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "LABEL",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Label)>,
+            },
+        ];
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = label)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Label(pub String);
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = shape)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Shape {
+            #[holder(use_place_holder)]
+            pub name: Label,
+        }
+        #[derive(Debug, Clone, PartialEq, Holder)]
+        #[holder(table = Tables)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub enum ShapeAny {
+            #[holder(use_place_holder)]
+            Shape(Box<Shape>),
+            #[holder(use_place_holder)]
+            Circle(Box<Circle>),
+        }
+        impl From<Shape> for ShapeAny {
+            fn from(value: Shape) -> Self {
+                ShapeAny::Shape(Box::new(value))
+            }
+        }
+        impl From<Circle> for ShapeAny {
+            fn from(value: Circle) -> Self {
+                ShapeAny::Circle(Box::new(value))
+            }
+        }
+        impl AsRef<Shape> for ShapeAny {
+            fn as_ref(&self) -> &Shape {
+                match self {
+                    ShapeAny::Shape(x) => x.as_ref(),
+                    ShapeAny::Circle(x) => (**x).as_ref(),
+                }
+            }
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = circle)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Circle {
+            #[as_ref]
+            #[as_mut]
+            #[deref]
+            #[deref_mut]
+            #[holder(use_place_holder)]
+            pub shape: Shape,
+            pub radius: f64,
+        }
+    }
+    "###);
+}
@@ -0,0 +1,229 @@
+use espr::{
+    ast::SyntaxTree,
+    codegen::rust::*,
+    ir::{DerivedExpression, IR},
+};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY a;
+    x: REAL;
+    y: OPTIONAL REAL;
+    n: INTEGER;
+  DERIVE
+    half: REAL := x / 2.0;
+    shifted: REAL := -y + n * (1.0 / 4.0);
+    twice: INTEGER := n * 2 - 1;
+    root: REAL := SQRT(x);
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn derive() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{as_holder, tables::Memo, Holder, TableInit};
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            a: HashMap<u64, as_holder!(A)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn a_holders(&self) -> &HashMap<u64, as_holder!(A)> {
+                &self.a
+            }
+            pub fn insert_a(
+                &mut self,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_a_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["A"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::A(
+                                Box::new(EntityTable::<as_holder!(A)>::get_owned(self, id)?),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty().chain(::ruststep::tables::instances(&self.a)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.a)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            A(Box<A>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "A",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "x",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y",
+                        ty: "REAL",
+                        optional: true,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "n",
+                        ty: "INTEGER",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(A)>,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = a)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct A {
+            pub x: f64,
+            pub y: Option<f64>,
+            pub n: i64,
+            #[holder(cache)]
+            #[new(default)]
+            pub half_cache: Memo<Option<f64>>,
+            #[holder(cache)]
+            #[new(default)]
+            pub shifted_cache: Memo<Option<f64>>,
+            #[holder(cache)]
+            #[new(default)]
+            pub twice_cache: Memo<Option<i64>>,
+        }
+        impl A {
+            pub fn half(&self) -> Option<f64> {
+                *self.half_cache.get_or_init(|| self.evaluate_half())
+            }
+            fn evaluate_half(&self) -> Option<f64> {
+                let x = self.x;
+                Some(x / 2.0)
+            }
+            pub fn shifted(&self) -> Option<f64> {
+                *self.shifted_cache.get_or_init(|| self.evaluate_shifted())
+            }
+            fn evaluate_shifted(&self) -> Option<f64> {
+                let y = self.y?;
+                let n = self.n;
+                Some(-y + n as f64 * 0.25)
+            }
+            pub fn twice(&self) -> Option<i64> {
+                *self.twice_cache.get_or_init(|| self.evaluate_twice())
+            }
+            fn evaluate_twice(&self) -> Option<i64> {
+                let n = self.n;
+                n.checked_mul(2i64)?.checked_sub(1i64)
+            }
+        }
+    }
+    "###);
+}
+
+#[test]
+fn fold_finite_only() {
+    let st = SyntaxTree::parse(
+        r#"
+        SCHEMA test_schema;
+          ENTITY a;
+            x: REAL;
+          DERIVE
+            third: REAL := 1.0 / 4.0;
+            infinite: REAL := 1.0 / 0.0;
+            overflow: REAL := 1.0E300 * 1.0E300;
+          END_ENTITY;
+        END_SCHEMA;
+        "#,
+    )
+    .unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let derived = &ir.schemas[0].entities[0].derived;
+    assert_eq!(derived[0].expr, DerivedExpression::Real(0.25));
+    // Operations yielding infinity are kept to be evaluated at runtime
+    assert!(matches!(derived[1].expr, DerivedExpression::Binary { .. }));
+    assert!(matches!(derived[2].expr, DerivedExpression::Binary { .. }));
+}
@@ -0,0 +1,466 @@
+use espr::{
+    ast::SyntaxTree,
+    codegen::rust::*,
+    ir::{Options, IR},
+};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  TYPE label = STRING;
+  END_TYPE;
+
+  TYPE side = ENUMERATION OF (left, right);
+  END_TYPE;
+
+  TYPE shape = SELECT (circle, point);
+  END_TYPE;
+
+  ENTITY point SUPERTYPE OF (ONEOF (circle));
+    x: REAL;
+    y: REAL;
+  END_ENTITY;
+
+  ENTITY circle SUBTYPE OF (point);
+    r: REAL;
+    s: side;
+  END_ENTITY;
+
+  ENTITY tag;
+    name: label;
+    target: shape;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn derive_serde() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let options = Options {
+        derive_serde: true,
+        ..Default::default()
+    };
+    let ir = IR::from_syntax_tree_with_options(&st, &options).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{
+            as_holder, derive_more::{AsMut, AsRef, Deref, DerefMut, From, Into},
+            Holder, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        #[derive(::ruststep::serde::Serialize, ::ruststep::serde::Deserialize)]
+        #[serde(crate = "::ruststep::serde")]
+        pub struct Tables {
+            point: HashMap<u64, as_holder!(Point)>,
+            circle: HashMap<u64, as_holder!(Circle)>,
+            tag: HashMap<u64, as_holder!(Tag)>,
+            label: HashMap<u64, as_holder!(Label)>,
+            shape: HashMap<u64, as_holder!(Shape)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            #[serde(skip)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn point_holders(&self) -> &HashMap<u64, as_holder!(Point)> {
+                &self.point
+            }
+            pub fn circle_holders(&self) -> &HashMap<u64, as_holder!(Circle)> {
+                &self.circle
+            }
+            pub fn tag_holders(&self) -> &HashMap<u64, as_holder!(Tag)> {
+                &self.tag
+            }
+            pub fn label_holders(&self) -> &HashMap<u64, as_holder!(Label)> {
+                &self.label
+            }
+            pub fn shape_holders(&self) -> &HashMap<u64, as_holder!(Shape)> {
+                &self.shape
+            }
+            pub fn insert_point(
+                &mut self,
+                value: as_holder!(Point),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.point.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_point_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Point),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.point.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_circle(
+                &mut self,
+                value: as_holder!(Circle),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.circle.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_circle_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Circle),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.circle.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn insert_tag(
+                &mut self,
+                value: as_holder!(Tag),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.tag.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(id)
+            }
+            pub fn insert_tag_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Tag),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.tag.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["POINT", "CIRCLE", "TAG", "LABEL", "SHAPE"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Point(
+                                Box::new(
+                                    EntityTable::<as_holder!(Point)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::Circle(
+                                Box::new(
+                                    EntityTable::<as_holder!(Circle)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::Tag(
+                                Box::new(
+                                    EntityTable::<as_holder!(Tag)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(3usize) => {
+                            AnyEntity::Label(
+                                Box::new(
+                                    EntityTable::<as_holder!(Label)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(4usize) => {
+                            AnyEntity::Shape(
+                                Box::new(
+                                    EntityTable::<as_holder!(Shape)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.point))
+                        .chain(::ruststep::tables::instances(&self.circle))
+                        .chain(::ruststep::tables::instances(&self.tag))
+                        .chain(::ruststep::tables::instances(&self.label))
+                        .chain(::ruststep::tables::instances(&self.shape)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn to_cache(&self) -> ::ruststep::cache::TablesCache<&Self> {
+                ::ruststep::cache::TablesCache::new(self)
+            }
+            pub fn from_cache(
+                cache: ::ruststep::cache::TablesCache<Self>,
+            ) -> ::ruststep::error::Result<Self> {
+                cache.into_tables()
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.point))
+                        .chain(::ruststep::tables::table_errors(self, &self.circle))
+                        .chain(::ruststep::tables::table_errors(self, &self.tag))
+                        .chain(::ruststep::tables::table_errors(self, &self.label))
+                        .chain(::ruststep::tables::table_errors(self, &self.shape)),
+                )
+            }
+        }
+        impl ::ruststep::cache::CachedTables for Tables {
+            fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Point(Box<Point>),
+            Circle(Box<Circle>),
+            Tag(Box<Tag>),
+            Label(Box<Label>),
+            Shape(Box<Shape>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "POINT",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "x",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Point)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "CIRCLE",
+                supertypes: &["POINT"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "r",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "s",
+                        ty: "SIDE",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Circle)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "TAG",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "name",
+                        ty: "LABEL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "target",
+                        ty: "SHAPE",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Tag)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "LABEL",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Label)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SHAPE",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Shape)>,
+            },
+        ];
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = label)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub struct Label(pub String);
+        #[derive(Debug, Clone, PartialEq, ::serde::Serialize, ::serde::Deserialize)]
+        pub enum Side {
+            Left,
+            Right,
+        }
+        #[derive(Debug, Clone, PartialEq, Holder)]
+        #[holder(table = Tables)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub enum Shape {
+            #[holder(use_place_holder)]
+            Circle(Box<Circle>),
+            #[holder(use_place_holder)]
+            Point(PointAny),
+        }
+        impl ::ruststep::tables::ToParameter for Side {
+            fn to_parameter(&self) -> ::ruststep::ast::Parameter {
+                let value = match self {
+                    Side::Left => "LEFT",
+                    Side::Right => "RIGHT",
+                };
+                ::ruststep::ast::Parameter::Enumeration(value.to_string())
+            }
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = point)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub struct Point {
+            pub x: f64,
+            pub y: f64,
+        }
+        #[derive(Debug, Clone, PartialEq, Holder)]
+        #[holder(table = Tables)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub enum PointAny {
+            #[holder(use_place_holder)]
+            Point(Box<Point>),
+            #[holder(use_place_holder)]
+            Circle(Box<Circle>),
+        }
+        impl From<Point> for PointAny {
+            fn from(value: Point) -> Self {
+                PointAny::Point(Box::new(value))
+            }
+        }
+        impl From<Circle> for PointAny {
+            fn from(value: Circle) -> Self {
+                PointAny::Circle(Box::new(value))
+            }
+        }
+        impl AsRef<Point> for PointAny {
+            fn as_ref(&self) -> &Point {
+                match self {
+                    PointAny::Point(x) => x.as_ref(),
+                    PointAny::Circle(x) => (**x).as_ref(),
+                }
+            }
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = circle)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub struct Circle {
+            #[as_ref]
+            #[as_mut]
+            #[deref]
+            #[deref_mut]
+            #[holder(use_place_holder)]
+            pub point: Point,
+            pub r: f64,
+            pub s: Side,
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = tag)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(generate_serde)]
+        pub struct Tag {
+            #[holder(use_place_holder)]
+            pub name: Label,
+            #[holder(use_place_holder)]
+            pub target: Shape,
+        }
+    }
+    "###);
+}
@@ -0,0 +1,101 @@
+use espr::diagnostics::*;
+
+const THREE_PROBLEMS: &str = r#"SCHEMA test_schema;
+  ENTITY point;
+    x: REAL;
+  END_ENTITY;
+
+  ENTITY segment;
+    start: point;
+    span: distnce;
+  END_ENTITY;
+
+  ENTITY point;
+    y: REAL;
+  END_ENTITY;
+
+  TYPE distance = REAL;
+  END_TYPE;
+
+  TYPE a = b;
+  END_TYPE;
+
+  TYPE b = a;
+  END_TYPE;
+END_SCHEMA;
+"#;
+
+fn range(start: (usize, usize), end: (usize, usize)) -> Range {
+    Range {
+        start: Position {
+            line: start.0,
+            column: start.1,
+        },
+        end: Position {
+            line: end.0,
+            column: end.1,
+        },
+    }
+}
+
+#[test]
+fn three_problems() {
+    let diagnostics = espr::check(THREE_PROBLEMS);
+    let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(
+        codes,
+        [
+            "duplicated-declaration",
+            "type-not-found",
+            "cyclic-type-definition"
+        ]
+    );
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+
+    // The second declaration is reported with the first one
+    let duplicated = &diagnostics[0];
+    assert_eq!(duplicated.range, range((10, 9), (10, 14)));
+    assert_eq!(duplicated.related.len(), 1);
+    assert_eq!(duplicated.related[0].range, range((1, 9), (1, 14)));
+
+    // Suggest the similar name
+    let not_found = &diagnostics[1];
+    assert_eq!(not_found.range, range((7, 10), (7, 17)));
+    assert_eq!(not_found.related.len(), 1);
+    assert_eq!(not_found.related[0].range, range((14, 7), (14, 15)));
+    assert_eq!(not_found.related[0].message, "did you mean `distance`?");
+
+    // Reported once for the cycle
+    let cycle = &diagnostics[2];
+    assert_eq!(cycle.range, range((17, 7), (17, 8)));
+    assert_eq!(cycle.related.len(), 1);
+    assert_eq!(cycle.related[0].range, range((20, 7), (20, 8)));
+}
+
+#[test]
+fn syntax_error() {
+    let diagnostics = espr::check(
+        r#"SCHEMA test_schema;
+  ENTITY point;
+    x: REAL
+  END_ENTITY;
+END_SCHEMA;
+"#,
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].code, "syntax-error");
+    assert_eq!(diagnostics[0].range.start.line, 1);
+}
+
+#[test]
+fn valid() {
+    let diagnostics = espr::check(
+        r#"SCHEMA test_schema;
+  ENTITY point;
+    x: REAL;
+  END_ENTITY;
+END_SCHEMA;
+"#,
+    );
+    assert!(diagnostics.is_empty());
+}
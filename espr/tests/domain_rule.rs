@@ -0,0 +1,128 @@
+//! `WHERE` rules kept in IR, using fragments of `measure_schema` in ISO 10303-41
+
+use espr::{ast::SyntaxTree, ir::*};
+
+const EXPRESS: &str = r#"
+SCHEMA measure_schema;
+  TYPE length_measure = REAL;
+  END_TYPE;
+
+  TYPE positive_length_measure = length_measure;
+  WHERE
+    WR1: SELF > 0.0;
+  END_TYPE;
+
+  TYPE day_in_month_number = INTEGER;
+  WHERE
+    WR1: {1 <= SELF <= 31};
+  END_TYPE;
+
+  TYPE second_in_minute = REAL;
+  WHERE
+    WR1: {0 <= SELF < 60.0};
+  END_TYPE;
+
+  TYPE si_prefix = ENUMERATION OF (mega, kilo, milli, micro);
+  END_TYPE;
+
+  TYPE unit = SELECT (named_unit, derived_unit);
+  END_TYPE;
+
+  ENTITY dimensional_exponents;
+    length_exponent : REAL;
+    mass_exponent : REAL;
+    time_exponent : REAL;
+  END_ENTITY;
+
+  ENTITY named_unit;
+    dimensions : dimensional_exponents;
+  END_ENTITY;
+
+  ENTITY length_unit SUBTYPE OF (named_unit);
+  WHERE
+    WR1: (SELF\named_unit.dimensions.length_exponent = 1.0) AND
+         (SELF\named_unit.dimensions.mass_exponent = 0.0) AND
+         (SELF\named_unit.dimensions.time_exponent = 0.0);
+  END_ENTITY;
+
+  ENTITY si_unit SUBTYPE OF (named_unit);
+    prefix : OPTIONAL si_prefix;
+  WHERE
+    WR1: NOT(('MEASURE_SCHEMA.MASS_UNIT' IN TYPEOF(SELF)) AND
+         (SIZEOF(USEDIN(SELF, 'MEASURE_SCHEMA.DERIVED_UNIT_ELEMENT.UNIT')) > 0)) OR
+         (prefix <> si_prefix.kilo);
+    NOT(EXISTS(prefix)) OR (prefix <> si_prefix.micro);
+  END_ENTITY;
+
+  ENTITY derived_unit_element;
+    unit : named_unit;
+    exponent : REAL;
+  END_ENTITY;
+
+  ENTITY derived_unit;
+    elements : SET [1:?] OF derived_unit_element;
+  WHERE
+    WR1: (SIZEOF(elements) > 1) OR
+         ((SIZEOF(elements) = 1) AND (elements[1].exponent <> 1.0));
+    WR2: SIZEOF(QUERY(e <* elements | e.exponent = 0.0)) = 0;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+fn ir() -> IR {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    IR::from_syntax_tree(&st).unwrap()
+}
+
+fn labels(rules: &[DomainRule]) -> Vec<Option<&str>> {
+    rules.iter().map(|rule| rule.label.as_deref()).collect()
+}
+
+#[test]
+fn type_rules() {
+    let ir = ir();
+    let rules = |id: &str| -> Vec<DomainRule> {
+        match ir.schemas[0].types.iter().find(|ty| ty.id() == id).unwrap() {
+            TypeDecl::Simple(Simple { rules, .. })
+            | TypeDecl::Rename(Rename { rules, .. })
+            | TypeDecl::Enumeration(Enumeration { rules, .. })
+            | TypeDecl::Select(Select { rules, .. }) => rules.clone(),
+            TypeDecl::Alias(_) => Vec::new(),
+        }
+    };
+    assert_eq!(labels(&rules("positive_length_measure")), [Some("WR1")]);
+    assert_eq!(labels(&rules("day_in_month_number")), [Some("WR1")]);
+    assert_eq!(labels(&rules("second_in_minute")), [Some("WR1")]);
+    assert!(rules("length_measure").is_empty());
+    assert!(rules("si_prefix").is_empty());
+    assert!(rules("unit").is_empty());
+}
+
+#[test]
+fn rename_with_rules_is_not_alias() {
+    let ir = ir();
+    let ty = ir.schemas[0]
+        .types
+        .iter()
+        .find(|ty| ty.id() == "positive_length_measure")
+        .unwrap();
+    assert!(matches!(ty, TypeDecl::Rename(_)));
+}
+
+#[test]
+fn entity_rules() {
+    let ir = ir();
+    let rules = |name: &str| {
+        ir.schemas[0]
+            .entities
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap()
+            .rules
+            .clone()
+    };
+    assert_eq!(labels(&rules("length_unit")), [Some("WR1")]);
+    assert_eq!(labels(&rules("si_unit")), [Some("WR1"), None]);
+    assert_eq!(labels(&rules("derived_unit")), [Some("WR1"), Some("WR2")]);
+    assert!(rules("named_unit").is_empty());
+}
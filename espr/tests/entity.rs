@@ -24,12 +24,18 @@ fn entity() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ::ruststep::{as_holder, Holder, TableInit};
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
         pub struct Tables {
             a: HashMap<u64, as_holder!(A)>,
             b: HashMap<u64, as_holder!(B)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
         }
         impl Tables {
             pub fn a_holders(&self) -> &HashMap<u64, as_holder!(A)> {
@@ -38,19 +44,179 @@ fn entity() {
             pub fn b_holders(&self) -> &HashMap<u64, as_holder!(B)> {
                 &self.b
             }
+            pub fn insert_a(
+                &mut self,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_a_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_b(
+                &mut self,
+                value: as_holder!(B),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.b.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_b_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(B),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.b.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["A", "B"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::A(
+                                Box::new(EntityTable::<as_holder!(A)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::B(
+                                Box::new(EntityTable::<as_holder!(B)>::get_owned(self, id)?),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.a))
+                        .chain(::ruststep::tables::instances(&self.b)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.a))
+                        .chain(::ruststep::tables::table_errors(self, &self.b)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            A(Box<A>),
+            B(Box<B>),
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = a)]
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "A",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "x",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(A)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "B",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "z",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "a",
+                        ty: "A",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(B)>,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = a)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct A {
             pub x: f64,
             pub y: f64,
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = b)]
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = b)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct B {
             pub z: f64,
             #[holder(use_place_holder)]
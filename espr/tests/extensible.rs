@@ -0,0 +1,57 @@
+use espr::{
+    ast::SyntaxTree,
+    ir::{SemanticError, TypeDecl, IR},
+};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  TYPE a = EXTENSIBLE ENUMERATION OF (x);
+  END_TYPE;
+
+  TYPE b = EXTENSIBLE ENUMERATION BASED_ON a WITH (y);
+  END_TYPE;
+
+  TYPE c = ENUMERATION BASED_ON b WITH (z);
+  END_TYPE;
+END_SCHEMA;
+"#;
+
+fn items(ir: &IR, id: &str) -> Vec<String> {
+    ir.schemas[0]
+        .types
+        .iter()
+        .find_map(|ty| match ty {
+            TypeDecl::Enumeration(e) if e.id == id => Some(e.items.clone()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn flatten_based_on() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    assert_eq!(items(&ir, "c"), ["x", "y", "z"]);
+    assert_eq!(items(&ir, "b"), ["x", "y", "z"]);
+    assert_eq!(items(&ir, "a"), ["x", "y", "z"]);
+}
+
+const CYCLE: &str = r#"
+SCHEMA test_schema;
+  TYPE a = EXTENSIBLE SELECT BASED_ON b WITH (e);
+  END_TYPE;
+
+  TYPE b = EXTENSIBLE SELECT BASED_ON a WITH (e);
+  END_TYPE;
+
+  ENTITY e;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn based_on_cycle() {
+    let st = SyntaxTree::parse(CYCLE).unwrap();
+    let err = IR::from_syntax_tree(&st).unwrap_err();
+    assert!(matches!(err, SemanticError::CyclicTypeDefinition(_)));
+}
@@ -0,0 +1,42 @@
+// Formatting generated code without rustfmt binary.
+// This test modifies PATH, and thus must be a separate test binary.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY a;
+    x: REAL;
+    y: REAL;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn without_rustfmt() {
+    std::env::set_var("PATH", "");
+    assert!(matches!(
+        RustfmtBinary.format("fn main() {}"),
+        Err(FormatError::RustfmtNotFound)
+    ));
+
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    // Fallback to prettyplease
+    let code = format(&tt, FormatterSelection::Auto).unwrap();
+    assert_eq!(code, PrettyPlease.format(&tt).unwrap());
+    assert_eq!(code, rustfmt(tt.clone()));
+    assert!(syn::parse_file(&code).is_ok());
+    assert!(code.contains("test_schema"));
+
+    assert!(matches!(
+        format(&tt, FormatterSelection::RustfmtBinary),
+        Err(FormatError::RustfmtNotFound)
+    ));
+    assert!(matches!(
+        format("fn {", FormatterSelection::PrettyPlease),
+        Err(FormatError::Parse(_))
+    ));
+}
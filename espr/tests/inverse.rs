@@ -0,0 +1,56 @@
+//! `INVERSE` attributes kept in IR
+//!
+//! Generated code is checked by `tests/corpus/inverse.exp`.
+
+use espr::{ast::SyntaxTree, ir::*};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY face;
+  INVERSE
+    bounds : SET [1:?] OF face_bound FOR face;
+    owners : BAG OF shell FOR faces;
+    outer : face_bound FOR face;
+  END_ENTITY;
+
+  ENTITY face_bound;
+    face : face;
+  END_ENTITY;
+
+  ENTITY shell;
+    faces : SET [1:?] OF face;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn inverse_attributes() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+
+    let entities = &ir.schemas[0].entities;
+    assert!(entities[1].inverse.is_empty());
+
+    let inverse = &entities[0].inverse;
+    let names: Vec<_> = inverse
+        .iter()
+        .map(|attr| (attr.name.as_str(), attr.attribute.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [("bounds", "face"), ("owners", "faces"), ("outer", "face")]
+    );
+    assert!(matches!(
+        &inverse[0].dest,
+        TypeRef::Entity { name, .. } if name.as_str() == "face_bound"
+    ));
+    assert!(matches!(
+        inverse[0].aggregation,
+        InverseAggregation::Set { bound: Some(_) }
+    ));
+    assert!(matches!(
+        inverse[1].aggregation,
+        InverseAggregation::Bag { bound: None }
+    ));
+    assert_eq!(inverse[2].aggregation, InverseAggregation::None);
+}
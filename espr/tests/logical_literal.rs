@@ -20,27 +20,154 @@ fn logical_literal() {
     let tt = rustfmt(tt);
 
     insta::assert_snapshot!(tt, @r###"
-    pub mod IFC4X3_DEV_6a23ae8 {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+    pub mod ifc4x3_dev_6a23ae8 {
+        use ::ruststep::{as_holder, Holder, TableInit};
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
         pub struct Tables {
-            IfcGeometricRepresentationContext:
-                HashMap<u64, as_holder!(IfcGeometricRepresentationContext)>,
+            ifcgeometricrepresentationcontext: HashMap<
+                u64,
+                as_holder!(IfcGeometricRepresentationContext),
+            >,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
         }
         impl Tables {
-            pub fn IfcGeometricRepresentationContext_holders(
+            pub fn ifcgeometricrepresentationcontext_holders(
                 &self,
             ) -> &HashMap<u64, as_holder!(IfcGeometricRepresentationContext)> {
-                &self.IfcGeometricRepresentationContext
+                &self.ifcgeometricrepresentationcontext
             }
+            pub fn insert_ifcgeometricrepresentationcontext(
+                &mut self,
+                value: as_holder!(IfcGeometricRepresentationContext),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.ifcgeometricrepresentationcontext.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_ifcgeometricrepresentationcontext_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(IfcGeometricRepresentationContext),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.ifcgeometricrepresentationcontext.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["IFCGEOMETRICREPRESENTATIONCONTEXT"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::IfcGeometricRepresentationContext(
+                                Box::new(
+                                    EntityTable::<
+                                        as_holder!(IfcGeometricRepresentationContext),
+                                    >::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(
+                            ::ruststep::tables::instances(
+                                &self.ifcgeometricrepresentationcontext,
+                            ),
+                        ),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(
+                            ::ruststep::tables::table_errors(
+                                self,
+                                &self.ifcgeometricrepresentationcontext,
+                            ),
+                        ),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            IfcGeometricRepresentationContext(Box<IfcGeometricRepresentationContext>),
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = IfcGeometricRepresentationContext)]
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "IFCGEOMETRICREPRESENTATIONCONTEXT",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "TrueNorth",
+                        ty: "BOOLEAN",
+                        optional: true,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<
+                    Tables,
+                    as_holder!(IfcGeometricRepresentationContext),
+                >,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = ifcgeometricrepresentationcontext)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct IfcGeometricRepresentationContext {
-            pub TrueNorth: Option<bool>,
+            pub truenorth: Option<bool>,
         }
     }
     "###);
@@ -0,0 +1,57 @@
+//! Global `RULE` kept in IR, using `compatible_dimension` rule in ISO 10303-42
+
+use espr::{ast::SyntaxTree, ir::*};
+
+const EXPRESS: &str = r#"
+SCHEMA geometry_schema;
+  ENTITY representation_context;
+    context_identifier : STRING;
+  END_ENTITY;
+
+  ENTITY geometric_representation_context SUBTYPE OF (representation_context);
+    coordinate_space_dimension : INTEGER;
+  END_ENTITY;
+
+  ENTITY cartesian_point;
+    coordinates : LIST [1:3] OF REAL;
+  END_ENTITY;
+
+  ENTITY direction;
+    direction_ratios : LIST [2:3] OF REAL;
+  END_ENTITY;
+
+  RULE compatible_dimension FOR
+    (cartesian_point, direction, representation_context, geometric_representation_context);
+  WHERE
+    WR1: SIZEOF(QUERY(x <* cartesian_point | SIZEOF(QUERY(y <* geometric_representation_context |
+           item_in_context(x, y) AND (HIINDEX(x.coordinates) <> y.coordinate_space_dimension))) > 0)) = 0;
+    WR2: SIZEOF(QUERY(x <* direction | SIZEOF(QUERY(y <* geometric_representation_context |
+           item_in_context(x, y) AND (HIINDEX(x.direction_ratios) <> y.coordinate_space_dimension))) > 0)) = 0;
+  END_RULE;
+END_SCHEMA;
+"#;
+
+#[test]
+fn compatible_dimension() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+
+    let schema = &ir.schemas[0];
+    assert_eq!(schema.rules.len(), 1);
+    let rule = &schema.rules[0];
+    assert_eq!(rule.name, "compatible_dimension");
+
+    let scope = Scope::root().pushed(ScopeType::Schema, "geometry_schema");
+    assert_eq!(
+        rule.references,
+        [
+            Path::entity(&scope, "cartesian_point"),
+            Path::entity(&scope, "direction"),
+            Path::entity(&scope, "representation_context"),
+            Path::entity(&scope, "geometric_representation_context"),
+        ]
+    );
+
+    let labels: Vec<_> = rule.rules.iter().map(|r| r.label.as_deref()).collect();
+    assert_eq!(labels, [Some("WR1"), Some("WR2")]);
+}
@@ -0,0 +1,346 @@
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY node;
+    parent: OPTIONAL node;
+  END_ENTITY;
+
+  ENTITY a;
+    b: OPTIONAL b;
+  END_ENTITY;
+
+  ENTITY b;
+    a: a;
+  END_ENTITY;
+
+  ENTITY tree;
+    children: LIST OF tree;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn self_reference() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{as_holder, Holder, TableInit};
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            node: HashMap<u64, as_holder!(Node)>,
+            a: HashMap<u64, as_holder!(A)>,
+            b: HashMap<u64, as_holder!(B)>,
+            tree: HashMap<u64, as_holder!(Tree)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn node_holders(&self) -> &HashMap<u64, as_holder!(Node)> {
+                &self.node
+            }
+            pub fn a_holders(&self) -> &HashMap<u64, as_holder!(A)> {
+                &self.a
+            }
+            pub fn b_holders(&self) -> &HashMap<u64, as_holder!(B)> {
+                &self.b
+            }
+            pub fn tree_holders(&self) -> &HashMap<u64, as_holder!(Tree)> {
+                &self.tree
+            }
+            pub fn insert_node(
+                &mut self,
+                value: as_holder!(Node),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.node.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_node_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Node),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.node.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_a(
+                &mut self,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_a_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(A),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.a.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn insert_b(
+                &mut self,
+                value: as_holder!(B),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.b.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(id)
+            }
+            pub fn insert_b_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(B),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.b.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(())
+            }
+            pub fn insert_tree(
+                &mut self,
+                value: as_holder!(Tree),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.tree.insert(id, value);
+                self.keyword_index.insert(id, 3usize);
+                Ok(id)
+            }
+            pub fn insert_tree_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Tree),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.tree.insert(id, value);
+                self.keyword_index.insert(id, 3usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["NODE", "A", "B", "TREE"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Node(
+                                Box::new(
+                                    EntityTable::<as_holder!(Node)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::A(
+                                Box::new(EntityTable::<as_holder!(A)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::B(
+                                Box::new(EntityTable::<as_holder!(B)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(3usize) => {
+                            AnyEntity::Tree(
+                                Box::new(
+                                    EntityTable::<as_holder!(Tree)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.node))
+                        .chain(::ruststep::tables::instances(&self.a))
+                        .chain(::ruststep::tables::instances(&self.b))
+                        .chain(::ruststep::tables::instances(&self.tree)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.node))
+                        .chain(::ruststep::tables::table_errors(self, &self.a))
+                        .chain(::ruststep::tables::table_errors(self, &self.b))
+                        .chain(::ruststep::tables::table_errors(self, &self.tree)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Node(Box<Node>),
+            A(Box<A>),
+            B(Box<B>),
+            Tree(Box<Tree>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "NODE",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "parent",
+                        ty: "NODE",
+                        optional: true,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Node)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "A",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "b",
+                        ty: "B",
+                        optional: true,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(A)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "B",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "a",
+                        ty: "A",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(B)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "TREE",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "children",
+                        ty: "LIST OF TREE",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Tree)>,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = node)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Node {
+            /// Boxed to break the reference cycle `node -> node`
+            #[holder(use_place_holder)]
+            pub parent: Option<Box<Node>>,
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = a)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct A {
+            #[holder(use_place_holder)]
+            pub b: Option<B>,
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = b)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct B {
+            /// Boxed to break the reference cycle `a -> b -> a`
+            #[holder(use_place_holder)]
+            pub a: Box<A>,
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = tree)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Tree {
+            #[holder(use_place_holder)]
+            pub children: Vec<Tree>,
+        }
+    }
+    "###);
+}
@@ -28,13 +28,22 @@ fn subsuper() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ::ruststep::{
+            as_holder, derive_more::{AsMut, AsRef, Deref, DerefMut},
+            Holder, TableInit,
+        };
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
         pub struct Tables {
             base: HashMap<u64, as_holder!(Base)>,
             sub: HashMap<u64, as_holder!(Sub)>,
             subsub: HashMap<u64, as_holder!(Subsub)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
         }
         impl Tables {
             pub fn base_holders(&self) -> &HashMap<u64, as_holder!(Base)> {
@@ -46,31 +55,224 @@ fn subsuper() {
             pub fn subsub_holders(&self) -> &HashMap<u64, as_holder!(Subsub)> {
                 &self.subsub
             }
+            pub fn insert_base(
+                &mut self,
+                value: as_holder!(Base),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.base.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_base_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Base),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.base.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn insert_sub(
+                &mut self,
+                value: as_holder!(Sub),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.sub.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(id)
+            }
+            pub fn insert_sub_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Sub),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.sub.insert(id, value);
+                self.keyword_index.insert(id, 1usize);
+                Ok(())
+            }
+            pub fn insert_subsub(
+                &mut self,
+                value: as_holder!(Subsub),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.subsub.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(id)
+            }
+            pub fn insert_subsub_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Subsub),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.subsub.insert(id, value);
+                self.keyword_index.insert(id, 2usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["BASE", "SUB", "SUBSUB"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Base(
+                                Box::new(
+                                    EntityTable::<as_holder!(Base)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::Sub(
+                                Box::new(
+                                    EntityTable::<as_holder!(Sub)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(2usize) => {
+                            AnyEntity::Subsub(
+                                Box::new(
+                                    EntityTable::<as_holder!(Subsub)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.base))
+                        .chain(::ruststep::tables::instances(&self.sub))
+                        .chain(::ruststep::tables::instances(&self.subsub)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.base))
+                        .chain(::ruststep::tables::table_errors(self, &self.sub))
+                        .chain(::ruststep::tables::table_errors(self, &self.subsub)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Base(Box<Base>),
+            Sub(Box<Sub>),
+            Subsub(Box<Subsub>),
         }
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = base)]
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "BASE",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "x",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Base)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SUB",
+                supertypes: &["BASE"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "y",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Sub)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "SUBSUB",
+                supertypes: &["SUB"],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "z",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Subsub)>,
+            },
+        ];
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = base)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Base {
             pub x: f64,
         }
         #[derive(Debug, Clone, PartialEq, Holder)]
-        # [holder (table = Tables)]
+        #[holder(table = Tables)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub enum BaseAny {
             #[holder(use_place_holder)]
             Base(Box<Base>),
             #[holder(use_place_holder)]
             Sub(Box<SubAny>),
         }
-        impl Into<BaseAny> for Base {
-            fn into(self) -> BaseAny {
-                BaseAny::Base(Box::new(self))
+        impl From<Base> for BaseAny {
+            fn from(value: Base) -> Self {
+                BaseAny::Base(Box::new(value))
             }
         }
-        impl Into<BaseAny> for Sub {
-            fn into(self) -> BaseAny {
-                BaseAny::Sub(Box::new(self.into()))
+        impl From<Sub> for BaseAny {
+            fn from(value: Sub) -> Self {
+                BaseAny::Sub(Box::new(value.into()))
             }
         }
         impl AsRef<Base> for BaseAny {
@@ -81,12 +283,20 @@ fn subsuper() {
                 }
             }
         }
-        #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = sub)]
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = sub)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Sub {
             #[as_ref]
             #[as_mut]
@@ -97,22 +307,24 @@ fn subsuper() {
             pub y: f64,
         }
         #[derive(Debug, Clone, PartialEq, Holder)]
-        # [holder (table = Tables)]
+        #[holder(table = Tables)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub enum SubAny {
             #[holder(use_place_holder)]
             Sub(Box<Sub>),
             #[holder(use_place_holder)]
             Subsub(Box<Subsub>),
         }
-        impl Into<SubAny> for Sub {
-            fn into(self) -> SubAny {
-                SubAny::Sub(Box::new(self))
+        impl From<Sub> for SubAny {
+            fn from(value: Sub) -> Self {
+                SubAny::Sub(Box::new(value))
             }
         }
-        impl Into<SubAny> for Subsub {
-            fn into(self) -> SubAny {
-                SubAny::Subsub(Box::new(self.into()))
+        impl From<Subsub> for SubAny {
+            fn from(value: Subsub) -> Self {
+                SubAny::Subsub(Box::new(value))
             }
         }
         impl AsRef<Sub> for SubAny {
@@ -131,12 +343,20 @@ fn subsuper() {
                 }
             }
         }
-        #[derive(
-            Debug, Clone, PartialEq, :: derive_new :: new, Holder, AsRef, AsMut, Deref, DerefMut,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = subsub)]
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[derive(AsRef)]
+        #[derive(AsMut)]
+        #[derive(Deref)]
+        #[derive(DerefMut)]
+        #[holder(table = Tables)]
+        #[holder(field = subsub)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct Subsub {
             #[as_ref]
             #[as_mut]
@@ -37,14 +37,22 @@ fn type_decl() {
 
     insta::assert_snapshot!(tt, @r###"
     pub mod test_schema {
-        use ruststep::{as_holder, derive_more::*, primitive::*, Holder, TableInit};
+        use ::ruststep::{
+            as_holder, derive_more::{AsRef, Deref, DerefMut, From, Into},
+            Holder, TableInit,
+        };
         use std::collections::HashMap;
         #[derive(Debug, Clone, PartialEq, Default, TableInit)]
         pub struct Tables {
             e: HashMap<u64, as_holder!(E)>,
+            #[table_init(alias = "C")]
             a: HashMap<u64, as_holder!(A)>,
-            c: HashMap<u64, as_holder!(C)>,
-            d: HashMap<u64, as_holder!(D)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
         }
         impl Tables {
             pub fn e_holders(&self) -> &HashMap<u64, as_holder!(E)> {
@@ -53,51 +61,186 @@ fn type_decl() {
             pub fn a_holders(&self) -> &HashMap<u64, as_holder!(A)> {
                 &self.a
             }
-            pub fn c_holders(&self) -> &HashMap<u64, as_holder!(C)> {
-                &self.c
+            pub fn insert_e(
+                &mut self,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
             }
-            pub fn d_holders(&self) -> &HashMap<u64, as_holder!(D)> {
-                &self.d
+            pub fn insert_e_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(E),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.e.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["E", "A"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::E(
+                                Box::new(EntityTable::<as_holder!(E)>::get_owned(self, id)?),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::A(
+                                Box::new(EntityTable::<as_holder!(A)>::get_owned(self, id)?),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.e))
+                        .chain(::ruststep::tables::instances(&self.a)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.e))
+                        .chain(::ruststep::tables::table_errors(self, &self.a)),
+                )
             }
         }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            E(Box<E>),
+            A(Box<A>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "E",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "a",
+                        ty: "A",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "b",
+                        ty: "B",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "c",
+                        ty: "C",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "d",
+                        ty: "D",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(E)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "A",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(A)>,
+            },
+        ];
         #[derive(
-            Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, :: ruststep_derive :: Holder,
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
         )]
-        # [holder (table = Tables)]
-        # [holder (field = a)]
+        #[holder(table = Tables)]
+        #[holder(field = a)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        #[holder(alias = "C")]
         pub struct A(pub String);
-        #[derive(Debug, Clone, PartialEq, :: serde :: Deserialize)]
+        #[derive(Debug, Clone, PartialEq, ::serde::Deserialize)]
         pub enum B {
             Are,
             Sore,
             Dore,
         }
-        #[derive(
-            Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, :: ruststep_derive :: Holder,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = c)]
-        #[holder(generate_deserialize)]
-        pub struct C(#[holder(use_place_holder)] pub A);
-        #[derive(
-            Clone, Debug, PartialEq, AsRef, Deref, DerefMut, Into, From, :: ruststep_derive :: Holder,
-        )]
-        # [holder (table = Tables)]
-        # [holder (field = d)]
-        #[holder(generate_deserialize)]
-        pub struct D(pub B);
-        #[derive(Debug, Clone, PartialEq, :: derive_new :: new, Holder)]
-        # [holder (table = Tables)]
-        # [holder (field = e)]
+        pub type C = A;
+        pub type CHolder = AHolder;
+        pub type D = B;
+        impl ::ruststep::tables::ToParameter for B {
+            fn to_parameter(&self) -> ::ruststep::ast::Parameter {
+                let value = match self {
+                    B::Are => "ARE",
+                    B::Sore => "SORE",
+                    B::Dore => "DORE",
+                };
+                ::ruststep::ast::Parameter::Enumeration(value.to_string())
+            }
+        }
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = e)]
         #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
         pub struct E {
             #[holder(use_place_holder)]
             pub a: A,
             pub b: B,
             #[holder(use_place_holder)]
             pub c: C,
-            #[holder(use_place_holder)]
             pub d: D,
         }
     }
@@ -0,0 +1,239 @@
+use espr::{
+    ast::SyntaxTree,
+    codegen::rust::*,
+    ir::{Options, IR},
+};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  TYPE label = STRING;
+  END_TYPE;
+
+  ENTITY product;
+    id: STRING;
+    name: label;
+    description: OPTIONAL STRING;
+    mass: REAL;
+  DERIVE
+    twice: REAL := mass * 2.0;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn type_wrappers() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let options = Options {
+        preserve_type_wrappers: true,
+        ..Default::default()
+    };
+    let ir = IR::from_syntax_tree_with_options(&st, &options).unwrap();
+    let tt = ir.to_token_stream(CratePrefix::External).to_string();
+
+    let tt = rustfmt(tt);
+
+    insta::assert_snapshot!(tt, @r###"
+    pub mod test_schema {
+        use ::ruststep::{
+            as_holder, derive_more::{AsRef, Deref, DerefMut, From, Into},
+            primitive::Wrapped, tables::Memo, Holder, TableInit,
+        };
+        use std::collections::HashMap;
+        #[derive(Debug, Clone, PartialEq, Default, TableInit)]
+        pub struct Tables {
+            product: HashMap<u64, as_holder!(Product)>,
+            label: HashMap<u64, as_holder!(Label)>,
+            #[table_init(index)]
+            keyword_index: HashMap<u64, usize>,
+            #[table_init(constants)]
+            constants: ::ruststep::tables::Constants,
+            #[table_init(ids)]
+            id_allocator: ::ruststep::id::IdAllocator,
+        }
+        impl Tables {
+            pub fn product_holders(&self) -> &HashMap<u64, as_holder!(Product)> {
+                &self.product
+            }
+            pub fn label_holders(&self) -> &HashMap<u64, as_holder!(Label)> {
+                &self.label
+            }
+            pub fn insert_product(
+                &mut self,
+                value: as_holder!(Product),
+            ) -> ::ruststep::error::Result<u64> {
+                let id = ::ruststep::tables::TableIds::next_id(self)?;
+                self.product.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(id)
+            }
+            pub fn insert_product_with_id(
+                &mut self,
+                id: u64,
+                value: as_holder!(Product),
+            ) -> ::ruststep::error::Result<()> {
+                if ::ruststep::tables::TableIds::contains_id(self, id) {
+                    return Err(::ruststep::error::Error::DuplicatedEntity(id));
+                }
+                self.product.insert(id, value);
+                self.keyword_index.insert(id, 0usize);
+                Ok(())
+            }
+            pub fn constants_mut(&mut self) -> &mut ::ruststep::tables::Constants {
+                &mut self.constants
+            }
+            /// Reserve ranges of ids not to be assigned by `insert_*`
+            pub fn id_allocator_mut(&mut self) -> &mut ::ruststep::id::IdAllocator {
+                &mut self.id_allocator
+            }
+            pub fn keyword_of(&self, id: u64) -> Option<&'static str> {
+                const KEYWORDS: &[&str] = &["PRODUCT", "LABEL"];
+                self.keyword_index.get(&id).map(|position| KEYWORDS[*position])
+            }
+            pub fn get_any(&self, id: u64) -> ::ruststep::error::Result<AnyEntity> {
+                use ::ruststep::tables::EntityTable;
+                Ok(
+                    match self.keyword_index.get(&id) {
+                        Some(0usize) => {
+                            AnyEntity::Product(
+                                Box::new(
+                                    EntityTable::<as_holder!(Product)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        Some(1usize) => {
+                            AnyEntity::Label(
+                                Box::new(
+                                    EntityTable::<as_holder!(Label)>::get_owned(self, id)?,
+                                ),
+                            )
+                        }
+                        _ => return Err(::ruststep::error::Error::UnknownEntity(id)),
+                    },
+                )
+            }
+            pub fn any_iter(
+                &self,
+            ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<AnyEntity>> + '_> {
+                ::ruststep::tables::any_iter(
+                    self.keyword_index.keys().copied(),
+                    move |id| self.get_any(id),
+                )
+            }
+            /// Look up [SCHEMA_REGISTRY] by the keyword of a record, e.g. `POINT`
+            pub fn descriptor_for(
+                keyword: &str,
+            ) -> Option<&'static ::ruststep::tables::EntityDescriptor<Tables>> {
+                SCHEMA_REGISTRY.iter().find(|descriptor| descriptor.keyword == keyword)
+            }
+            pub fn to_data_section(&self) -> ::ruststep::ast::DataSection {
+                ::ruststep::tables::data_section(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::instances(&self.product))
+                        .chain(::ruststep::tables::instances(&self.label)),
+                )
+            }
+            pub fn to_step_string(&self, header: &::ruststep::header::Header) -> String {
+                ::ruststep::tables::step_string(header, self.to_data_section())
+            }
+            /// Fingerprint of [SCHEMA_REGISTRY] to detect a stale cache
+            pub fn schema_fingerprint() -> u64 {
+                ::ruststep::cache::schema_fingerprint(SCHEMA_REGISTRY)
+            }
+            pub fn check_all(&self) -> Vec<::ruststep::tables::EntityError> {
+                ::ruststep::tables::sorted_errors(
+                    ::std::iter::empty()
+                        .chain(::ruststep::tables::table_errors(self, &self.product))
+                        .chain(::ruststep::tables::table_errors(self, &self.label)),
+                )
+            }
+        }
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyEntity {
+            Product(Box<Product>),
+            Label(Box<Label>),
+        }
+        /// Entities and types in [Tables] described at runtime
+        pub static SCHEMA_REGISTRY: &[::ruststep::tables::EntityDescriptor<Tables>] = &[
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "PRODUCT",
+                supertypes: &[],
+                attributes: &[
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "id",
+                        ty: "STRING",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "name",
+                        ty: "LABEL",
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "description",
+                        ty: "STRING",
+                        optional: true,
+                    },
+                    ::ruststep::tables::AttributeDescriptor {
+                        name: "mass",
+                        ty: "REAL",
+                        optional: false,
+                    },
+                ],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Product)>,
+            },
+            ::ruststep::tables::EntityDescriptor {
+                keyword: "LABEL",
+                supertypes: &[],
+                attributes: &[],
+                get: ::ruststep::tables::get_debug::<Tables, as_holder!(Label)>,
+            },
+        ];
+        #[derive(
+            Clone,
+            Debug,
+            PartialEq,
+            AsRef,
+            Deref,
+            DerefMut,
+            Into,
+            From,
+            ::ruststep_derive::Holder
+        )]
+        #[holder(table = Tables)]
+        #[holder(field = label)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Label(pub String);
+        #[derive(Debug)]
+        #[derive(Clone)]
+        #[derive(PartialEq)]
+        #[derive(::derive_new::new)]
+        #[derive(Holder)]
+        #[holder(table = Tables)]
+        #[holder(field = product)]
+        #[holder(generate_deserialize)]
+        #[holder(generate_into_holder)]
+        #[holder(generate_to_record)]
+        pub struct Product {
+            pub id: Wrapped<String>,
+            #[holder(use_place_holder)]
+            pub name: Label,
+            pub description: Option<Wrapped<String>>,
+            pub mass: Wrapped<f64>,
+            #[holder(cache)]
+            #[new(default)]
+            pub twice_cache: Memo<Option<f64>>,
+        }
+        impl Product {
+            pub fn twice(&self) -> Option<f64> {
+                *self.twice_cache.get_or_init(|| self.evaluate_twice())
+            }
+            fn evaluate_twice(&self) -> Option<f64> {
+                let mass = self.mass.value;
+                Some(mass * 2.0)
+            }
+        }
+    }
+    "###);
+}
@@ -0,0 +1,48 @@
+//! `UNIQUE` rules kept in IR
+//!
+//! Generated code is checked by `tests/corpus/unique.exp`.
+
+use espr::{ast::SyntaxTree, ir::*};
+
+const EXPRESS: &str = r#"
+SCHEMA test_schema;
+  ENTITY named_unit;
+    name : STRING;
+  END_ENTITY;
+
+  ENTITY si_unit SUBTYPE OF (named_unit);
+    prefix : OPTIONAL STRING;
+  UNIQUE
+    ur1 : SELF\named_unit.name, prefix;
+    prefix;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+#[test]
+fn unique_rules() {
+    let st = SyntaxTree::parse(EXPRESS).unwrap();
+    let ir = IR::from_syntax_tree(&st).unwrap();
+
+    let entities = &ir.schemas[0].entities;
+    assert!(entities[0].unique_rules.is_empty());
+
+    let rules = &entities[1].unique_rules;
+    assert_eq!(rules.len(), 2);
+
+    assert_eq!(rules[0].label.as_deref(), Some("ur1"));
+    let names: Vec<_> = rules[0]
+        .attributes
+        .iter()
+        .map(|a| a.name.as_str())
+        .collect();
+    assert_eq!(names, ["name", "prefix"]);
+    assert!(matches!(
+        &rules[0].attributes[0].group,
+        Some(TypeRef::Entity { name, .. }) if name.as_str() == "named_unit"
+    ));
+    assert!(rules[0].attributes[1].group.is_none());
+
+    assert_eq!(rules[1].label, None);
+    assert_eq!(rules[1].attributes.len(), 1);
+}
@@ -1,7 +1,7 @@
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use proc_macro_crate::{crate_name, FoundCrate};
 use proc_macro_error::ResultExt;
-use quote::format_ident;
+use quote::{format_ident, quote};
 use std::convert::*;
 
 use super::field_type::*;
@@ -26,23 +26,83 @@ pub fn as_visitor_ident(input: &syn::Ident) -> syn::Ident {
     format_ident!("{}Visitor", input)
 }
 
-pub fn serde_crate() -> syn::Path {
-    let ruststep = ruststep_crate();
-    syn::parse_quote!( #ruststep::serde )
+pub fn as_serde_mirror_ident(input: &syn::Ident) -> syn::Ident {
+    format_ident!("{}Serde", input)
+}
+
+/// Mirror of a holder deriving serde by `#[serde(remote)]` for `#[holder(generate_serde)]`,
+/// and `impl Serialize` of the holder through it
+///
+/// `body` is the definition of the holder after its name, e.g. `{ pub x: f64 }`.
+/// The mirror is also used by `impl Deserialize` of the holder for binary formats,
+/// see [deserialize_binary].
+pub fn def_serde_mirror(
+    holder_ident: &syn::Ident,
+    keyword: TokenStream2,
+    body: &TokenStream2,
+) -> TokenStream2 {
+    let mirror_ident = as_serde_mirror_ident(holder_ident);
+    let serde = serde_crate();
+    let serde_str = quote!(#serde).to_string().replace(' ', "");
+    let remote = holder_ident.to_string();
+    quote! {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #[derive(#serde::Serialize, #serde::Deserialize)]
+        #[serde(crate = #serde_str)]
+        #[serde(remote = #remote)]
+        #keyword #mirror_ident #body
+
+        #[automatically_derived]
+        impl #serde::ser::Serialize for #holder_ident {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: #serde::ser::Serializer,
+            {
+                #mirror_ident::serialize(self, serializer)
+            }
+        }
+    } // quote!
 }
 
-pub fn itertools_crate() -> syn::Path {
+/// Statement in `impl Deserialize` of a holder with `#[holder(generate_serde)]`
+/// reading a binary format, e.g. bincode, through the mirror of [def_serde_mirror]
+///
+/// Human-readable formats are regarded as the exchange structure.
+pub fn deserialize_binary(holder_ident: &syn::Ident, generate_serde: bool) -> Option<TokenStream2> {
+    if !generate_serde {
+        return None;
+    }
+    let mirror_ident = as_serde_mirror_ident(holder_ident);
+    let serde = serde_crate();
+    Some(quote! {
+        if !#serde::de::Deserializer::is_human_readable(&deserializer) {
+            return #mirror_ident::deserialize(deserializer);
+        }
+    })
+}
+
+pub fn serde_crate() -> syn::Path {
     let ruststep = ruststep_crate();
-    syn::parse_quote!( #ruststep::itertools )
+    syn::parse_quote!( #ruststep::serde )
 }
 
 /// Returns `crate` or `::ruststep` as in ruststep crate or not
 pub fn ruststep_crate() -> syn::Path {
     let path = crate_name("ruststep").unwrap();
     match path {
-        FoundCrate::Itself => match std::env::var("CARGO_TARGET_TMPDIR") {
+        FoundCrate::Itself => match std::env::var("CARGO_TARGET_TMPDIR").or_else(|_| {
+            // Examples are also separate crates
+            std::env::var("CARGO_CRATE_NAME").and_then(|name| {
+                if name == "ruststep" {
+                    Err(std::env::VarError::NotPresent)
+                } else {
+                    Ok(name)
+                }
+            })
+        }) {
             Ok(_) => {
-                // For tests and benches in ruststep crate
+                // For tests, benches, and examples in ruststep crate
                 //
                 // https://doc.rust-lang.org/cargo/reference/environment-variables.html
                 // > CARGO_TARGET_TMPDIR — Only set when building integration test or benchmark code.
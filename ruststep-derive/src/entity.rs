@@ -1,15 +1,16 @@
 use inflector::Inflector;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use proc_macro_error::OptionExt;
-use quote::quote;
+use proc_macro_error::{abort, OptionExt};
+use quote::{quote, quote_spanned};
 use std::convert::*;
+use syn::{ext::IdentExt, spanned::Spanned};
 
 use super::*;
 
 pub fn derive_deserialize(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
     let name = ident.to_string().to_screaming_snake_case();
-    let def_visitor_tt = def_visitor(ident, &name, st);
-    let impl_deserialize_tt = impl_deserialize(ident, &name, st);
+    let def_visitor_tt = def_visitor(ident, &name, st, false);
+    let impl_deserialize_tt = impl_deserialize(ident, &name, st, false);
     quote! {
         #def_visitor_tt
         #impl_deserialize_tt
@@ -19,26 +20,42 @@ pub fn derive_deserialize(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStre
 pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
     let name = ident.to_string().to_screaming_snake_case();
     let holder_ident = as_holder_ident(ident);
-    let def_holder_tt = def_holder(ident, st);
+    let def_holder_tt = def_holder(ident, st, attr);
     let impl_holder_tt = impl_holder(ident, attr, st);
     let impl_entity_table_tt = impl_entity_table(ident, attr);
+    let impl_into_holder_tt = if attr.generate_into_holder {
+        Some(impl_into_holder(ident, attr, st))
+    } else {
+        None
+    };
+    let impl_to_record_tt = if attr.generate_to_record {
+        Some(impl_to_record(ident, st))
+    } else {
+        None
+    };
     if attr.generate_deserialize {
-        let def_visitor_tt = def_visitor(&holder_ident, &name, st);
-        let impl_deserialize_tt = impl_deserialize(&holder_ident, &name, st);
+        let def_visitor_tt = def_visitor(&holder_ident, &name, st, true);
+        let impl_deserialize_tt = impl_deserialize(&holder_ident, &name, st, attr.generate_serde);
         let impl_with_visitor_tt = impl_with_visitor(ident);
+        let impl_parse_record_tt = impl_parse_record(ident);
         quote! {
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
             #def_visitor_tt
             #impl_deserialize_tt
             #impl_with_visitor_tt
+            #impl_parse_record_tt
         }
     } else {
         quote! {
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
         }
     }
 }
@@ -49,69 +66,382 @@ fn table_arg() -> syn::Ident {
 }
 
 struct FieldEntries {
+    /// Fields of the holder struct, i.e. fields except `#[holder(skip)]`
     attributes: Vec<syn::Ident>,
+    /// Name of each field in `attributes`, given by `#[holder(rename = "...")]`
+    names: Vec<String>,
+    /// Span of the type of each field in `attributes` to report errors on the field
+    spans: Vec<Span>,
     holder_types: Vec<syn::Type>,
     into_owned: Vec<TokenStream2>,
+    /// Conversion of each field in `attributes` from the owned struct into the holder
+    into_holder: Vec<TokenStream2>,
+    /// `ruststep::tables::AttributeValue` of each field in `attributes`
+    attr_values: Vec<TokenStream2>,
+    /// Statements calling `f` with entity references in each place holder field
+    references: Vec<TokenStream2>,
+    /// Fields with `#[holder(skip)]`, which are `Default::default()` in the owned struct
+    skipped: Vec<syn::Ident>,
+    layout: Layout,
+}
+
+/// Parameter position of a record bound to a field
+#[derive(Clone)]
+enum Slot {
+    Field {
+        ident: syn::Ident,
+        name: String,
+        span: Span,
+        kind: TokenStream2,
+    },
+    /// Consumed and discarded
+    Skip,
+}
+
+/// Positions of fields in a record,
+/// given by `#[holder(index = ..)]`, `#[holder(skip)]`, `#[holder(skip_params = ..)]`, and `#[holder(rest)]`
+struct Layout {
+    slots: Vec<Slot>,
+    /// Field capturing parameters after `slots`
+    rest: Option<syn::Ident>,
+    /// Parameters after `slots` are discarded by `#[holder(skip, rest)]`
+    ignore_rest: bool,
+    /// Any position attribute is used. Fields are in the declaration order otherwise.
+    custom: bool,
+}
+
+impl Layout {
+    fn attr_len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Parameters after `slots` are accepted
+    fn has_rest(&self) -> bool {
+        self.rest.is_some() || self.ignore_rest
+    }
+
+    fn attr_kinds(&self) -> Vec<TokenStream2> {
+        let ruststep = ruststep_crate();
+        self.slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Field { kind, .. } => kind.clone(),
+                Slot::Skip => quote! {
+                    #ruststep::tables::AttributeKind {
+                        kind: #ruststep::tables::ValueKind::Other,
+                        optional: true,
+                    }
+                },
+            })
+            .collect()
+    }
+}
+
+/// `ruststep::tables::AttributeKind` expected for the field type
+fn attr_kind(ft: &FieldType) -> TokenStream2 {
+    fn value_kind(ft: &FieldType) -> &'static str {
+        match ft {
+            FieldType::Path(path) => {
+                let last = path.segments.last().unwrap().ident.to_string();
+                match last.as_str() {
+                    "f64" => "Real",
+                    "i64" => "Integer",
+                    "String" => "String",
+                    "bool" | "Logical" => "Logical",
+                    _ => "Other",
+                }
+            }
+            FieldType::Optional(ft) | FieldType::Boxed(ft) => value_kind(ft),
+            FieldType::List(_) | FieldType::Array(..) => "List",
+        }
+    }
+    let ruststep = ruststep_crate();
+    let kind = syn::Ident::new(value_kind(ft), Span::call_site());
+    let optional = matches!(ft, FieldType::Optional(_));
+    quote! {
+        #ruststep::tables::AttributeKind {
+            kind: #ruststep::tables::ValueKind::#kind,
+            optional: #optional,
+        }
+    }
+}
+
+/// Expression of `ruststep::tables::AttributeValue` for the holder field `expr`,
+/// which is a reference to the field if `by_ref`
+fn attr_value(
+    ft: &FieldType,
+    expr: TokenStream2,
+    by_ref: bool,
+    place_holder: bool,
+) -> TokenStream2 {
+    let ruststep = ruststep_crate();
+    let (value, reference) = if by_ref {
+        (quote! { *#expr }, expr.clone())
+    } else {
+        (expr.clone(), quote! { &#expr })
+    };
+    match ft {
+        FieldType::Path(_) if place_holder => {
+            quote! { #ruststep::tables::AttributeValue::Reference }
+        }
+        FieldType::Path(path) => {
+            let last = path.segments.last().unwrap();
+            let inner = match &last.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(syn::GenericArgument::Type(ty)) => FieldType::try_from(ty.clone()).ok(),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match (last.ident.to_string().as_str(), inner) {
+                ("f64", _) => quote! { #ruststep::tables::AttributeValue::Real(#value) },
+                ("i64", _) => quote! { #ruststep::tables::AttributeValue::Integer(#value) },
+                ("String", _) => quote! { #ruststep::tables::AttributeValue::String(#reference) },
+                ("bool" | "Logical", _) => {
+                    quote! { #ruststep::tables::AttributeValue::Logical(#ruststep::primitive::Logical::from(#value)) }
+                }
+                ("Wrapped", Some(inner)) => {
+                    attr_value(&inner, quote! { #expr.value }, false, false)
+                }
+                _ => {
+                    quote! { #ruststep::tables::AttributeValue::Other(::std::format!("{:?}", #expr)) }
+                }
+            }
+        }
+        FieldType::Optional(ft) => {
+            let some = attr_value(ft, quote! { value }, true, place_holder);
+            quote! {
+                match #reference {
+                    Some(value) => #some,
+                    None => #ruststep::tables::AttributeValue::Null,
+                }
+            }
+        }
+        FieldType::List(_) | FieldType::Array(..) => {
+            quote! { #ruststep::tables::AttributeValue::List(#expr.len()) }
+        }
+        FieldType::Boxed(ft) => attr_value(ft, quote! { #expr.as_ref() }, true, place_holder),
+    }
 }
 
 impl FieldEntries {
     fn parse(st: &syn::DataStruct) -> Self {
         let table_arg = table_arg();
+        let ruststep = ruststep_crate();
 
         let mut attributes = Vec::new();
+        let mut names = Vec::new();
+        let mut spans = Vec::new();
         let mut holder_types = Vec::new();
         let mut into_owned = Vec::new();
+        let mut into_holder = Vec::new();
+        let mut attr_values = Vec::new();
+        let mut references = Vec::new();
+        let mut skipped = Vec::new();
+
+        let mut slots: Vec<Option<Slot>> = Vec::new();
+        let mut rest = None;
+        let mut ignore_rest = false;
+        let mut custom = false;
+        let mut next = 0;
 
-        for field in &st.fields {
+        let n_fields = st.fields.len();
+        for (i, field) in st.fields.iter().enumerate() {
             let ident = field.ident.as_ref().expect_or_abort("st is not struct");
-            attributes.push(ident.clone());
+            let HolderAttr {
+                place_holder,
+                index,
+                skip,
+                skip_params,
+                rest: is_rest,
+                rename,
+                cache,
+                ..
+            } = HolderAttr::parse(&field.attrs);
+            // Raw identifier like `r#type` is named `type`
+            let name = match &rename {
+                Some(name) => name.value(),
+                None => ident.unraw().to_string(),
+            };
+            if skip && (place_holder || rename.is_some()) {
+                abort!(
+                    ident.span(),
+                    "`#[holder(skip)]` cannot be used with `use_place_holder` or `rename`";
+                    help = "Skipped field is not in the holder, and is `Default::default()` in the owned struct"
+                );
+            }
+
+            if cache {
+                if place_holder
+                    || skip
+                    || is_rest
+                    || index.is_some()
+                    || skip_params.is_some()
+                    || rename.is_some()
+                {
+                    abort!(
+                        ident.span(),
+                        "`#[holder(cache)]` cannot be used with other field attributes";
+                        help = "Cache field is not bound to any parameter, and is `Default::default()` in the owned struct"
+                    );
+                }
+                skipped.push(ident.clone());
+                continue;
+            }
+
+            if is_rest {
+                if i + 1 != n_fields {
+                    abort!(ident.span(), "`#[holder(rest)]` must be the last field");
+                }
+                if index.is_some() || skip_params.is_some() {
+                    abort!(
+                        ident.span(),
+                        "`#[holder(rest)]` cannot be used with `index` or `skip_params`"
+                    );
+                }
+                custom = true;
+                if skip {
+                    // Trailing parameters are discarded
+                    ignore_rest = true;
+                    skipped.push(ident.clone());
+                    continue;
+                }
+                rest = Some(ident.clone());
+                attributes.push(ident.clone());
+                names.push(name);
+                spans.push(field.ty.span());
+                into_owned.push(quote! { #ident });
+                into_holder.push(quote! { #ident });
+                attr_values
+                    .push(quote! { #ruststep::tables::AttributeValue::List(self.#ident.len()) });
+                holder_types.push(field.ty.clone());
+                continue;
+            }
+
+            if index.is_some() && skip_params.is_some() {
+                abort!(
+                    ident.span(),
+                    "`#[holder(index)]` cannot be used with `skip_params`"
+                );
+            }
+            let position = match (&index, &skip_params) {
+                (Some(index), _) => {
+                    custom = true;
+                    index.base10_parse::<usize>().unwrap()
+                }
+                (None, Some(count)) => {
+                    custom = true;
+                    next + count.base10_parse::<usize>().unwrap()
+                }
+                (None, None) => next,
+            };
+            next = position + 1;
+            if slots.len() <= position {
+                slots.resize(position + 1, None);
+            }
+            if let Some(slot) = &slots[position] {
+                let used_by = match slot {
+                    Slot::Field { ident, .. } => format!("field `{}`", ident),
+                    Slot::Skip => "a skipped field".to_string(),
+                };
+                let span = index.as_ref().map(|i| i.span()).unwrap_or(ident.span());
+                abort!(span, "position {} is already used by {}", position, used_by);
+            }
+
+            if skip {
+                custom = true;
+                slots[position] = Some(Slot::Skip);
+                skipped.push(ident.clone());
+                continue;
+            }
 
             let ft: FieldType = field.ty.clone().try_into().unwrap();
+            slots[position] = Some(Slot::Field {
+                ident: ident.clone(),
+                name: name.clone(),
+                span: field.ty.span(),
+                kind: attr_kind(&ft),
+            });
+            attributes.push(ident.clone());
+            names.push(name);
+            spans.push(field.ty.span());
+            attr_values.push(attr_value(&ft, quote! { self.#ident }, false, place_holder));
 
-            let HolderAttr { place_holder, .. } = HolderAttr::parse(&field.attrs);
             if place_holder {
+                into_holder.push(ft.place_holder_expr(quote! { #ident }, &table_arg));
+                let index = attributes.len() - 1;
+                references.push(quote! {
+                    #ruststep::tables::References::references(&self.#ident, &mut |id| f(#index, id));
+                });
                 match &ft {
-                    FieldType::Path(_) => {
+                    // `Option` is resolved through `IntoOwned for Option<T>` to accept dangling references leniently
+                    FieldType::Path(_)
+                    | FieldType::Boxed(_)
+                    | FieldType::Optional(_)
+                    | FieldType::Array(..) => {
                         into_owned.push(quote! { #ident.into_owned(#table_arg)? });
                     }
-                    FieldType::Optional(_) => {
-                        into_owned.push(quote! { #ident.map(|holder| holder.into_owned(#table_arg)).transpose()? });
-                    }
                     FieldType::List(_) => into_owned.push(quote! {
                         #ident
                             .into_iter()
                             .map(|v| v.into_owned(#table_arg))
                             .collect::<::std::result::Result<Vec<_>, _>>()?
                     }),
-                    FieldType::Boxed(_) => abort_call_site!("Unexpected Box<T>"),
                 }
                 holder_types.push(ft.into_holder().into_place_holder().into());
             } else {
                 into_owned.push(quote! { #ident });
+                into_holder.push(quote! { #ident });
                 holder_types.push(ft.into());
             }
         }
+        // Positions not bound to any field are skipped
+        let slots = slots
+            .into_iter()
+            .map(|slot| slot.unwrap_or(Slot::Skip))
+            .collect();
         FieldEntries {
             attributes,
+            names,
+            spans,
             holder_types,
             into_owned,
+            into_holder,
+            attr_values,
+            references,
+            skipped,
+            layout: Layout {
+                slots,
+                rest,
+                ignore_rest,
+                custom,
+            },
         }
     }
 }
 
-pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
     let holder_ident = as_holder_ident(ident);
     let FieldEntries {
         attributes,
         holder_types,
         ..
     } = FieldEntries::parse(st);
+    let body = quote! {
+        {
+            #( pub #attributes: #holder_types ),*
+        }
+    };
+    let def_serde_mirror_tt = if attr.generate_serde {
+        Some(def_serde_mirror(&holder_ident, quote! { struct }, &body))
+    } else {
+        None
+    };
     quote! {
         /// Auto-generated by `#[derive(Holder)]`
         #[derive(Debug, Clone, PartialEq)]
-        pub struct #holder_ident {
-            #( pub #attributes: #holder_types ),*
-        }
+        pub struct #holder_ident #body
+        #def_serde_mirror_tt
     }
 }
 
@@ -120,10 +450,58 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
     let holder_ident = as_holder_ident(ident);
     let FieldEntries {
         attributes,
+        names,
         into_owned,
+        attr_values,
+        references,
+        skipped,
+        layout,
         ..
     } = FieldEntries::parse(st);
-    let attr_len = attributes.len();
+    let impl_for_each_reference = if references.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                #(#references)*
+            }
+        })
+    };
+    let attr_indices = 0..attributes.len();
+    // `match` without arms except `_ => return None` is warned as unreachable
+    let attr_value_body = if attributes.is_empty() {
+        quote! {
+            let _ = index;
+            None
+        }
+    } else {
+        quote! {
+            Some(match index {
+                #(#attr_indices => #attr_values,)*
+                _ => return None,
+            })
+        }
+    };
+    let attr_len = layout.attr_len();
+    let attr_kinds = layout.attr_kinds();
+    let impl_has_rest = if layout.has_rest() {
+        Some(quote! {
+            fn has_rest() -> bool {
+                true
+            }
+        })
+    } else {
+        None
+    };
+    let impl_is_abstract = if table.abstract_entity {
+        Some(quote! {
+            fn is_abstract() -> bool {
+                true
+            }
+        })
+    } else {
+        None
+    };
     let HolderAttr { table, .. } = table;
     let table_arg = table_arg();
     let ruststep = ruststep_crate();
@@ -135,7 +513,10 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
             type Owned = #ident;
             fn into_owned(self, #table_arg: &Self::Table) -> #ruststep::error::Result<Self::Owned> {
                 let #holder_ident { #(#attributes),* } = self;
-                Ok(#ident { #(#attributes: #into_owned),* })
+                Ok(#ident {
+                    #(#attributes: #into_owned,)*
+                    #(#skipped: ::std::default::Default::default(),)*
+                })
             }
         }
         #[automatically_derived]
@@ -146,6 +527,18 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
             fn attr_len() -> usize {
                 #attr_len
             }
+            fn attr_kinds() -> &'static [#ruststep::tables::AttributeKind] {
+                &[#(#attr_kinds),*]
+            }
+            #impl_has_rest
+            #impl_is_abstract
+            fn attr_names() -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+            fn attr_value(&self, index: usize) -> Option<#ruststep::tables::AttributeValue<'_>> {
+                #attr_value_body
+            }
+            #impl_for_each_reference
         }
     } // quote!
 }
@@ -161,6 +554,9 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
             fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
                 #ruststep::tables::get_owned(self, &self.#field, entity_id)
             }
+            fn entity_ids(&self) -> Vec<u64> {
+                #ruststep::tables::entity_ids(&self.#field)
+            }
             fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
                 #ruststep::tables::owned_iter(self, &self.#field)
             }
@@ -168,13 +564,193 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
     }
 }
 
+/// Convert into the holder, and insert it into `table.#field` for a place holder
+fn impl_into_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct) -> TokenStream2 {
+    let holder_ident = as_holder_ident(ident);
+    let FieldEntries {
+        attributes,
+        into_holder,
+        skipped,
+        ..
+    } = FieldEntries::parse(st);
+    let rest = if skipped.is_empty() {
+        None
+    } else {
+        Some(quote! { .. })
+    };
+    let field = table.require_field(ident);
+    let HolderAttr { table, .. } = table;
+    // Same as the keyword of the field in `#[derive(TableInit)]`
+    let keyword = field.to_string().to_screaming_snake_case();
+    let table_arg = table_arg();
+    let ruststep = ruststep_crate();
+
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::tables::IntoHolder for #ident {
+            type Holder = #holder_ident;
+            #[allow(clippy::needless_question_mark)]
+            fn into_holder(self, #table_arg: &mut #table) -> #ruststep::error::Result<Self::Holder> {
+                let #ident { #(#attributes,)* #rest } = self;
+                Ok(#holder_ident {
+                    #(#attributes: #into_holder,)*
+                })
+            }
+            fn into_place_holder(self, #table_arg: &mut #table) -> #ruststep::error::Result<#ruststep::tables::PlaceHolder<Self::Holder>> {
+                let holder = self.into_holder(#table_arg)?;
+                let id = #ruststep::tables::TableIds::next_id(#table_arg)?;
+                #table_arg.#field.insert(id, holder);
+                #ruststep::tables::TableIds::register_id(#table_arg, id, #keyword);
+                Ok(#ruststep::tables::PlaceHolder::Ref(#ruststep::ast::Name::Entity(id)))
+            }
+        }
+    }
+}
+
+/// Write the holder as a record whose parameters are in the positions of the fields,
+/// and as a typed parameter when it is a value of another record
+fn impl_to_record(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+    let name = ident.to_string().to_screaming_snake_case();
+    let holder_ident = as_holder_ident(ident);
+    let FieldEntries { layout, .. } = FieldEntries::parse(st);
+    let ruststep = ruststep_crate();
+    let parameters = layout.slots.iter().map(|slot| match slot {
+        Slot::Field { ident, span, .. } => quote_spanned! {*span=>
+            #ruststep::tables::ToParameter::to_parameter(&self.#ident)
+        },
+        Slot::Skip => quote! { #ruststep::ast::Parameter::NotProvided },
+    });
+    let extend_rest = layout.rest.iter().map(|rest| {
+        quote! { parameters.extend(self.#rest.iter().cloned()); }
+    });
+
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::tables::ToRecord for #holder_ident {
+            fn to_record(&self) -> #ruststep::ast::Record {
+                #[allow(unused_mut)]
+                let mut parameters = vec![#(#parameters),*];
+                #(#extend_rest)*
+                #ruststep::ast::Record {
+                    name: #name.to_string(),
+                    user_defined: false,
+                    parameter: #ruststep::ast::Parameter::List(parameters),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::ToParameter for #holder_ident {
+            fn to_parameter(&self) -> #ruststep::ast::Parameter {
+                let #ruststep::ast::Record { name, parameter, .. } =
+                    #ruststep::tables::ToRecord::to_record(self);
+                #ruststep::ast::Parameter::Typed {
+                    keyword: name,
+                    parameter: Box::new(parameter),
+                }
+            }
+        }
+    }
+}
+
 // `name` may be different from `ident`
 // because this will be used for both Entity struct and its `*Holder` struct.
-fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStream2 {
+//
+// Parameters are read in the order of `ruststep::tables::attribute_order` if `holder`
+// and positions of fields are not given by attributes.
+// Fields with `#[holder(skip)]` are filled by `Default::default()` unless `holder`.
+fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct, holder: bool) -> TokenStream2 {
     let visitor_ident = as_visitor_ident(ident);
-    let FieldEntries { attributes, .. } = FieldEntries::parse(st);
-    let attr_len = attributes.len();
+    let FieldEntries {
+        attributes,
+        names,
+        spans,
+        skipped,
+        layout,
+        ..
+    } = FieldEntries::parse(st);
+    // Read the next parameter, reporting unsatisfied trait bounds of the field type on the field
+    let next_elements: Vec<_> = spans
+        .iter()
+        .map(|span| quote_spanned! {*span=> seq.next_element() })
+        .collect();
+    let attr_len = layout.attr_len();
     let serde = serde_crate();
+    let read_attributes = if layout.custom {
+        let ruststep = ruststep_crate();
+        let read_slots = layout.slots.iter().map(|slot| match slot {
+            Slot::Field {
+                ident, name, span, ..
+            } => {
+                let next_element = quote_spanned! {*span=> seq.next_element() };
+                quote! {
+                    let #ident = {
+                        let _attribute = #ruststep::error::enter_attribute(#name);
+                        #next_element?.unwrap()
+                    };
+                }
+            }
+            Slot::Skip => quote! {
+                seq.next_element::<#ruststep::ast::Parameter>()?;
+            },
+        });
+        let read_rest = layout.rest.iter().map(|rest| {
+            quote! {
+                let mut #rest = Vec::new();
+                while let Some(parameter) = seq.next_element()? {
+                    #rest.push(parameter);
+                }
+            }
+        });
+        let ignore_rest = if layout.ignore_rest {
+            Some(quote! {
+                while seq.next_element::<#ruststep::ast::Parameter>()?.is_some() {}
+            })
+        } else {
+            None
+        };
+        quote! {
+            #( #read_slots )*
+            #( #read_rest )*
+            #ignore_rest
+        }
+    } else if holder && attr_len > 0 {
+        let ruststep = ruststep_crate();
+        let indices = 0..attr_len;
+        quote! {
+            #( let mut #attributes = None; )*
+            let order = #ruststep::tables::attribute_order(#name, #attr_len)
+                .map_err(<A::Error as #serde::de::Error>::custom)?;
+            for index in order {
+                match index {
+                    #(
+                    #indices => {
+                        let _attribute = #ruststep::error::enter_attribute(#names);
+                        #attributes = #next_elements?;
+                    }
+                    )*
+                    _ => unreachable!(),
+                }
+            }
+            #( let #attributes = #attributes.unwrap(); )*
+        }
+    } else {
+        let ruststep = ruststep_crate();
+        quote! {
+            #(
+            let #attributes = {
+                let _attribute = #ruststep::error::enter_attribute(#names);
+                #next_elements?.unwrap()
+            };
+            )*
+        }
+    };
+    let check_size = if layout.has_rest() {
+        quote! { size < #attr_len }
+    } else {
+        quote! { size != #attr_len }
+    };
+    let skipped = if holder { Vec::new() } else { skipped };
     quote! {
         #[doc(hidden)]
         pub struct #visitor_ident;
@@ -191,13 +767,16 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
                 A: #serde::de::SeqAccess<'de>,
             {
                 if let Some(size) = seq.size_hint() {
-                    if size != #attr_len {
+                    if #check_size {
                         use #serde::de::Error;
                         return Err(A::Error::invalid_length(size, &self));
                     }
                 }
-                #( let #attributes = seq.next_element()?.unwrap(); )*
-                Ok(#ident { #(#attributes),* })
+                #read_attributes
+                Ok(#ident {
+                    #(#attributes,)*
+                    #(#skipped: ::std::default::Default::default(),)*
+                })
             }
 
             // Entry point for Record or Parameter::Typed
@@ -221,11 +800,17 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
 
 // `name` may be different from `ident`
 // because this will be used for both Entity struct and its `*Holder` struct.
-fn impl_deserialize(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStream2 {
+fn impl_deserialize(
+    ident: &syn::Ident,
+    name: &str,
+    st: &syn::DataStruct,
+    generate_serde: bool,
+) -> TokenStream2 {
     let visitor_ident = as_visitor_ident(ident);
-    let FieldEntries { attributes, .. } = FieldEntries::parse(st);
-    let attr_len = attributes.len();
+    let FieldEntries { layout, .. } = FieldEntries::parse(st);
+    let attr_len = layout.attr_len();
     let serde = serde_crate();
+    let deserialize_binary = deserialize_binary(ident, generate_serde);
     quote! {
         #[automatically_derived]
         impl<'de> #serde::de::Deserialize<'de> for #ident {
@@ -233,6 +818,7 @@ fn impl_deserialize(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> Tok
             where
                 D: #serde::de::Deserializer<'de>,
             {
+                #deserialize_binary
                 deserializer.deserialize_tuple_struct(#name, #attr_len, #visitor_ident {})
             }
         }
@@ -255,3 +841,33 @@ fn impl_with_visitor(ident: &syn::Ident) -> TokenStream2 {
         }
     } // quote!
 }
+
+fn impl_parse_record(ident: &syn::Ident) -> TokenStream2 {
+    let ruststep = ruststep_crate();
+    let holder_ident = as_holder_ident(ident);
+
+    quote! {
+        #[automatically_derived]
+        impl #holder_ident {
+            /// Deserialize from a record without tables. References are kept as place holders.
+            pub fn parse_record(record: &#ruststep::ast::Record) -> #ruststep::error::Result<Self> {
+                #ruststep::tables::parse_record(record)
+            }
+
+            /// Deserialize from a simple entity instance, or a partial record in a complex entity instance.
+            pub fn try_from_instance(
+                instance: &#ruststep::ast::EntityInstance,
+            ) -> #ruststep::error::Result<Self> {
+                #ruststep::tables::parse_instance(instance)
+            }
+        }
+
+        #[automatically_derived]
+        impl ::std::convert::TryFrom<&#ruststep::ast::Record> for #holder_ident {
+            type Error = #ruststep::error::Error;
+            fn try_from(record: &#ruststep::ast::Record) -> #ruststep::error::Result<Self> {
+                Self::parse_record(record)
+            }
+        }
+    } // quote!
+}
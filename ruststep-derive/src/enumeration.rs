@@ -0,0 +1,149 @@
+//! `#[derive(Holder)]` for an enum only with unit variants, i.e. `ENUMERATION` type
+//!
+//! Same as espr-generated `ENUMERATION`, the enum itself is used in holders
+//! since it does not refer any entity. Each variant is read from and written as
+//! the enumeration value of its keyword, e.g. `.MILLI.` for `Milli`.
+
+use super::*;
+use inflector::Inflector;
+use proc_macro2::TokenStream as TokenStream2;
+use proc_macro_error::abort;
+use quote::quote;
+
+struct Input {
+    ident: syn::Ident,
+    variants: Vec<syn::Ident>,
+    /// Keyword of each variant, e.g. `MILLI`, or given by `#[holder(rename = "...")]`
+    keywords: Vec<String>,
+}
+
+impl Input {
+    fn parse(ident: &syn::Ident, e: &syn::DataEnum) -> Self {
+        let mut variants = Vec::new();
+        let mut keywords = Vec::new();
+        for var in &e.variants {
+            let HolderAttr {
+                rename,
+                place_holder,
+                ..
+            } = HolderAttr::parse(&var.attrs);
+            if place_holder {
+                abort!(var.ident, "unit variant cannot use `use_place_holder`");
+            }
+            keywords.push(match rename {
+                Some(keyword) => keyword.value(),
+                None => var.ident.to_string().to_screaming_snake_case(),
+            });
+            variants.push(var.ident.clone());
+        }
+        Input {
+            ident: ident.clone(),
+            variants,
+            keywords,
+        }
+    }
+
+    fn impl_deserialize(&self, generate_serde: bool) -> TokenStream2 {
+        let Input {
+            ident,
+            variants,
+            keywords,
+        } = self;
+        let name = ident.to_string().to_screaming_snake_case();
+        let visitor_ident = as_visitor_ident(ident);
+        let serde = serde_crate();
+        let deserialize_binary = deserialize_binary(ident, generate_serde);
+
+        quote! {
+            #[doc(hidden)]
+            pub struct #visitor_ident;
+
+            #[automatically_derived]
+            impl<'de> #serde::de::Visitor<'de> for #visitor_ident {
+                type Value = #ident;
+                fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                    write!(formatter, #name)
+                }
+
+                // Entry point for Parameter::Enumeration
+                fn visit_enum<A>(self, data: A) -> ::std::result::Result<Self::Value, A::Error>
+                where
+                    A: #serde::de::EnumAccess<'de>,
+                {
+                    use #serde::de::{Error, VariantAccess};
+                    const KEYWORDS: &[&str] = &[#(#keywords),*];
+                    // The content of the variant is the original value, e.g. `MILLI` for `.MILLI.`
+                    let (_, variant): (String, _) = data.variant()?;
+                    let keyword: String = variant.newtype_variant()?;
+                    match keyword.as_str() {
+                        #( #keywords => Ok(#ident::#variants), )*
+                        _ => Err(A::Error::unknown_variant(&keyword, KEYWORDS)),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl<'de> #serde::de::Deserialize<'de> for #ident {
+                fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                where
+                    D: #serde::de::Deserializer<'de>,
+                {
+                    #deserialize_binary
+                    deserializer.deserialize_enum(#name, &[#(#keywords),*], #visitor_ident {})
+                }
+            }
+        } // quote!
+    }
+
+    fn def_serde_mirror(&self) -> TokenStream2 {
+        let Input {
+            ident, variants, ..
+        } = self;
+        def_serde_mirror(ident, quote! { enum }, &quote! { { #(#variants),* } })
+    }
+
+    fn impl_to_parameter(&self) -> TokenStream2 {
+        let Input {
+            ident,
+            variants,
+            keywords,
+        } = self;
+        let ruststep = ruststep_crate();
+
+        quote! {
+            #[automatically_derived]
+            impl #ruststep::tables::ToParameter for #ident {
+                fn to_parameter(&self) -> #ruststep::ast::Parameter {
+                    let keyword = match self {
+                        #( #ident::#variants => #keywords, )*
+                    };
+                    #ruststep::ast::Parameter::Enumeration(keyword.to_string())
+                }
+            }
+        } // quote!
+    }
+}
+
+pub fn derive_holder(ident: &syn::Ident, e: &syn::DataEnum, attr: &HolderAttr) -> TokenStream2 {
+    let input = Input::parse(ident, e);
+    let impl_deserialize_tt = if attr.generate_deserialize {
+        Some(input.impl_deserialize(attr.generate_serde))
+    } else {
+        None
+    };
+    let def_serde_mirror_tt = if attr.generate_serde {
+        Some(input.def_serde_mirror())
+    } else {
+        None
+    };
+    let impl_to_parameter_tt = if attr.generate_to_record {
+        Some(input.impl_to_parameter())
+    } else {
+        None
+    };
+    quote! {
+        #impl_deserialize_tt
+        #def_serde_mirror_tt
+        #impl_to_parameter_tt
+    }
+}
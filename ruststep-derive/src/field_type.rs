@@ -12,6 +12,8 @@ pub enum FieldType {
     Optional(Box<FieldType>),
     /// Like `Vec<T>`
     List(Box<FieldType>),
+    /// Like `[T; N]`
+    Array(Box<FieldType>, syn::Expr),
     /// Like `Box<T>`
     Boxed(Box<FieldType>),
 }
@@ -44,6 +46,10 @@ impl FieldType {
                 let holder = ty.into_holder();
                 FieldType::List(Box::new(holder))
             }
+            FieldType::Array(ty, len) => {
+                let holder = ty.into_holder();
+                FieldType::Array(Box::new(holder), len)
+            }
             FieldType::Boxed(ty) => {
                 let holder = ty.into_holder();
                 FieldType::Boxed(Box::new(holder))
@@ -66,12 +72,54 @@ impl FieldType {
                 let place_holder = ty.into_place_holder();
                 FieldType::List(Box::new(place_holder))
             }
+            FieldType::Array(ty, len) => {
+                let place_holder = ty.into_place_holder();
+                FieldType::Array(Box::new(place_holder), len)
+            }
             FieldType::Boxed(ty) => {
                 let place_holder = ty.into_place_holder();
                 FieldType::Boxed(Box::new(place_holder))
             }
         }
     }
+
+    /// Expression converting the owned value `expr` of a place holder field into its holder value
+    /// by `ruststep::tables::IntoHolder::into_place_holder`, which is evaluated in a function returning
+    /// `ruststep::error::Result` to propagate errors by `?`
+    pub fn place_holder_expr(&self, expr: TokenStream2, table: &syn::Ident) -> TokenStream2 {
+        let ruststep = ruststep_crate();
+        match self {
+            FieldType::Path(_) => {
+                quote! { #ruststep::tables::IntoHolder::into_place_holder(#expr, #table)? }
+            }
+            FieldType::Optional(ty) => {
+                let inner = ty.place_holder_expr(quote! { v }, table);
+                quote! {
+                    match #expr {
+                        Some(v) => Some(#inner),
+                        None => None,
+                    }
+                }
+            }
+            FieldType::List(ty) => {
+                let inner = ty.place_holder_expr(quote! { v }, table);
+                quote! {
+                    #expr
+                        .into_iter()
+                        .map(|v| Ok(#inner))
+                        .collect::<#ruststep::error::Result<Vec<_>>>()?
+                }
+            }
+            FieldType::Array(ty, _len) => {
+                let inner = ty.place_holder_expr(quote! { v }, table);
+                quote! { #ruststep::tables::try_map_array(#expr, |v| Ok(#inner))? }
+            }
+            FieldType::Boxed(ty) => {
+                let inner = ty.place_holder_expr(quote! { (*#expr) }, table);
+                quote! { Box::new(#inner) }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -98,6 +146,10 @@ impl From<FieldType> for syn::Type {
                 let ty: syn::Type = (*ty).into();
                 syn::parse_quote! { Vec<#ty> }
             }
+            FieldType::Array(ty, len) => {
+                let ty: syn::Type = (*ty).into();
+                return syn::parse_quote! { [#ty; #len] };
+            }
             FieldType::Boxed(ty) => {
                 let ty: syn::Type = (*ty).into();
                 syn::parse_quote! { Box<#ty> }
@@ -111,10 +163,12 @@ impl TryFrom<syn::Type> for FieldType {
     type Error = UnsupportedTypeError;
 
     fn try_from(ty: syn::Type) -> Result<Self, Self::Error> {
-        let path = if let syn::Type::Path(syn::TypePath { path, .. }) = ty {
-            path
-        } else {
-            return Err(UnsupportedTypeError {});
+        let path = match ty {
+            syn::Type::Path(syn::TypePath { path, .. }) => path,
+            syn::Type::Array(syn::TypeArray { elem, len, .. }) => {
+                return Ok(FieldType::Array(Box::new((*elem).try_into()?), len));
+            }
+            _ => return Err(UnsupportedTypeError {}),
         };
 
         let syn::Path { segments, .. } = &path;
@@ -126,6 +180,13 @@ impl TryFrom<syn::Type> for FieldType {
                 args,
                 ..
             }) => {
+                let is_container = ["Option", "Vec", "Box"]
+                    .iter()
+                    .any(|container| last_seg.ident == container);
+                if !is_container {
+                    // Other generic types, e.g. `Wrapped<String>`, are used as is
+                    return Ok(FieldType::Path(path.clone()));
+                }
                 assert_eq!(args.len(), 1);
                 if let syn::GenericArgument::Type(ty) = &args[0] {
                     let ty = Box::new(ty.clone().try_into()?);
@@ -175,6 +236,24 @@ mod tests {
             panic!()
         }
         assert_eq!(<FieldType as Into<syn::Type>>::into(f), ty);
+
+        let ty: syn::Type = syn::parse_str("[Option<T>; 3]").unwrap();
+        let f = ty.clone().try_into().unwrap();
+        if let FieldType::Array(ty, _) = &f {
+            assert!(matches!(**ty, FieldType::Optional(_)));
+        } else {
+            panic!()
+        }
+        assert_eq!(<FieldType as Into<syn::Type>>::into(f), ty);
+
+        let ty: syn::Type = syn::parse_str("Option<Wrapped<String>>").unwrap();
+        let f = ty.clone().try_into().unwrap();
+        if let FieldType::Optional(ty) = &f {
+            assert!(matches!(**ty, FieldType::Path(_)));
+        } else {
+            panic!()
+        }
+        assert_eq!(<FieldType as Into<syn::Type>>::into(f), ty);
     }
 
     #[test]
@@ -232,5 +311,19 @@ mod tests {
         let ans: syn::Type =
             syn::parse_str("Option<Vec<::ruststep::tables::PlaceHolder<THolder>>>").unwrap();
         assert_eq!(<FieldType as Into<syn::Type>>::into(place_holder), ans);
+
+        let ty: syn::Type = syn::parse_str("Vec<Vec<T>>").unwrap();
+        let f: FieldType = ty.try_into().unwrap();
+        let place_holder = f.into_holder().into_place_holder();
+        let ans: syn::Type =
+            syn::parse_str("Vec<Vec<::ruststep::tables::PlaceHolder<THolder>>>").unwrap();
+        assert_eq!(<FieldType as Into<syn::Type>>::into(place_holder), ans);
+
+        let ty: syn::Type = syn::parse_str("[T; 3]").unwrap();
+        let f: FieldType = ty.try_into().unwrap();
+        let place_holder = f.into_holder().into_place_holder();
+        let ans: syn::Type =
+            syn::parse_str("[::ruststep::tables::PlaceHolder<THolder>; 3]").unwrap();
+        assert_eq!(<FieldType as Into<syn::Type>>::into(place_holder), ans);
     }
 }
@@ -1,19 +1,44 @@
 //! Parse the associated attribute `#[holder(...)]` with `#[derive(Holder)]`
 //!
-//! There are three options:
+//! There are following options:
 //!
 //! - `#[holder(table = {path::to::table::struct})]`
 //! - `#[holder(field = {field_ident})]`
 //! - `#[holder(use_place_holder)]`
 //! - `#[holder(generate_deserialize)]`
+//! - `#[holder(generate_into_holder)]`
+//! - `#[holder(generate_to_record)]`
+//! - `#[holder(generate_serde)]`
+//! - `#[holder(index = {position})]`
+//! - `#[holder(skip)]`
+//! - `#[holder(rest)]`
+//! - `#[holder(rename = "{KEYWORD}")]`
+//! - `#[holder(skip_params = {count})]`
+//! - `#[holder(abstract_entity)]`
+//! - `#[holder(cache)]`
+//! - `#[holder(alias = "{KEYWORD}")]`
 //!
 
+use inflector::Inflector;
+use proc_macro_error::abort;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct HolderAttr {
     pub table: Option<syn::Path>,
     pub field: Option<syn::Ident>,
     pub place_holder: bool,
     pub generate_deserialize: bool,
+    pub generate_into_holder: bool,
+    pub generate_to_record: bool,
+    pub generate_serde: bool,
+    pub index: Option<syn::LitInt>,
+    pub skip: bool,
+    pub rest: bool,
+    pub rename: Option<syn::LitStr>,
+    pub skip_params: Option<syn::LitInt>,
+    pub abstract_entity: bool,
+    pub cache: bool,
+    pub aliases: Vec<syn::LitStr>,
 }
 
 impl HolderAttr {
@@ -22,6 +47,17 @@ impl HolderAttr {
         let mut field = None;
         let mut place_holder = false;
         let mut generate_deserialize = false;
+        let mut generate_into_holder = false;
+        let mut generate_to_record = false;
+        let mut generate_serde = false;
+        let mut index = None;
+        let mut skip = false;
+        let mut rest = false;
+        let mut rename = None;
+        let mut skip_params = None;
+        let mut abstract_entity = false;
+        let mut cache = false;
+        let mut aliases = Vec::new();
 
         for attr in attrs {
             // Only read `#[holder(...)]`
@@ -33,7 +69,11 @@ impl HolderAttr {
                 continue;
             }
 
-            match attr.parse_args().unwrap() {
+            let attr = match attr.parse_args() {
+                Ok(attr) => attr,
+                Err(e) => abort!(e.span(), "{}", e),
+            };
+            match attr {
                 Attr::Table(path) => {
                     table = Some(path);
                 }
@@ -46,6 +86,39 @@ impl HolderAttr {
                 Attr::GenerateDeserialize => {
                     generate_deserialize = true;
                 }
+                Attr::GenerateIntoHolder => {
+                    generate_into_holder = true;
+                }
+                Attr::GenerateToRecord => {
+                    generate_to_record = true;
+                }
+                Attr::GenerateSerde => {
+                    generate_serde = true;
+                }
+                Attr::Index(position) => {
+                    index = Some(position);
+                }
+                Attr::Skip => {
+                    skip = true;
+                }
+                Attr::Rest => {
+                    rest = true;
+                }
+                Attr::Rename(keyword) => {
+                    rename = Some(keyword);
+                }
+                Attr::SkipParams(count) => {
+                    skip_params = Some(count);
+                }
+                Attr::AbstractEntity => {
+                    abstract_entity = true;
+                }
+                Attr::Cache => {
+                    cache = true;
+                }
+                Attr::Alias(keyword) => {
+                    aliases.push(keyword);
+                }
             }
         }
         HolderAttr {
@@ -53,6 +126,44 @@ impl HolderAttr {
             field,
             place_holder,
             generate_deserialize,
+            generate_into_holder,
+            generate_to_record,
+            generate_serde,
+            index,
+            skip,
+            rest,
+            rename,
+            skip_params,
+            abstract_entity,
+            cache,
+            aliases,
+        }
+    }
+}
+
+impl HolderAttr {
+    /// `#[holder(table = ...)]` of the container `ident`
+    pub fn require_table(&self, ident: &syn::Ident) -> &syn::Path {
+        match &self.table {
+            Some(table) => table,
+            None => abort!(
+                ident,
+                "`#[holder(table = ...)]` is required for `{}`", ident;
+                help = "Specify the struct containing the tables, e.g. `#[holder(table = Tables)]`"
+            ),
+        }
+    }
+
+    /// `#[holder(field = ...)]` of the container `ident`
+    pub fn require_field(&self, ident: &syn::Ident) -> &syn::Ident {
+        match &self.field {
+            Some(field) => field,
+            None => abort!(
+                ident,
+                "`#[holder(field = ...)]` is required for `{}`", ident;
+                help = "Specify the field of the table for this holder, e.g. `#[holder(field = {})]`",
+                    ident.to_string().to_snake_case()
+            ),
         }
     }
 }
@@ -63,6 +174,17 @@ enum Attr {
     Field(syn::Ident),
     PlaceHolder,
     GenerateDeserialize,
+    GenerateIntoHolder,
+    GenerateToRecord,
+    GenerateSerde,
+    Index(syn::LitInt),
+    Skip,
+    Rest,
+    Rename(syn::LitStr),
+    SkipParams(syn::LitInt),
+    AbstractEntity,
+    Cache,
+    Alias(syn::LitStr),
 }
 
 impl syn::parse::Parse for Attr {
@@ -81,9 +203,38 @@ impl syn::parse::Parse for Attr {
             }
             "use_place_holder" => Ok(Attr::PlaceHolder),
             "generate_deserialize" => Ok(Attr::GenerateDeserialize),
+            "generate_into_holder" => Ok(Attr::GenerateIntoHolder),
+            "generate_to_record" => Ok(Attr::GenerateToRecord),
+            "generate_serde" => Ok(Attr::GenerateSerde),
+            "index" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let position: syn::LitInt = input.parse()?;
+                position.base10_parse::<usize>()?;
+                Ok(Attr::Index(position))
+            }
+            "skip" => Ok(Attr::Skip),
+            "rest" => Ok(Attr::Rest),
+            "rename" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let keyword = input.parse()?;
+                Ok(Attr::Rename(keyword))
+            }
+            "skip_params" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let count: syn::LitInt = input.parse()?;
+                count.base10_parse::<usize>()?;
+                Ok(Attr::SkipParams(count))
+            }
+            "abstract_entity" => Ok(Attr::AbstractEntity),
+            "cache" => Ok(Attr::Cache),
+            "alias" => {
+                let _eq: syn::Token![=] = input.parse()?;
+                let keyword = input.parse()?;
+                Ok(Attr::Alias(keyword))
+            }
             _ => Err(syn::parse::Error::new(
                 ident.span(),
-                "expected `table`, `field`, or `use_place_holder`",
+                format!("unknown holder attribute `{}`, expected `table`, `field`, `use_place_holder`, `generate_deserialize`, `generate_into_holder`, `generate_to_record`, `generate_serde`, `index`, `skip`, `skip_params`, `rest`, `rename`, `abstract_entity`, `cache`, or `alias`", ident),
             )),
         }
     }
@@ -132,4 +283,48 @@ mod tests {
         // typo
         assert!(syn::parse_str::<Attr>("use_place_helder").is_err());
     }
+
+    #[test]
+    fn parse_attr_position() {
+        let attr: Attr = syn::parse_str("index = 3").unwrap();
+        assert_eq!(attr, Attr::Index(syn::parse_str("3").unwrap()));
+        assert_eq!(syn::parse_str::<Attr>("skip").unwrap(), Attr::Skip);
+        assert_eq!(syn::parse_str::<Attr>("rest").unwrap(), Attr::Rest);
+        let attr: Attr = syn::parse_str("skip_params = 2").unwrap();
+        assert_eq!(attr, Attr::SkipParams(syn::parse_str("2").unwrap()));
+
+        // index must be non-negative integer
+        assert!(syn::parse_str::<Attr>("index").is_err());
+        assert!(syn::parse_str::<Attr>("index = -1").is_err());
+        assert!(syn::parse_str::<Attr>("index = a").is_err());
+        assert!(syn::parse_str::<Attr>("skip_params").is_err());
+        assert!(syn::parse_str::<Attr>("skip_params = -1").is_err());
+    }
+
+    #[test]
+    fn parse_attr_rename() {
+        let attr: Attr = syn::parse_str(r#"rename = "SI_UNIT""#).unwrap();
+        assert_eq!(attr, Attr::Rename(syn::parse_str(r#""SI_UNIT""#).unwrap()));
+
+        // keyword must be a string literal
+        assert!(syn::parse_str::<Attr>("rename = SI_UNIT").is_err());
+        assert!(syn::parse_str::<Attr>("rename").is_err());
+    }
+
+    #[test]
+    fn parse_attr_alias() {
+        let attr: Attr = syn::parse_str(r#"alias = "LENGTH""#).unwrap();
+        assert_eq!(attr, Attr::Alias(syn::parse_str(r#""LENGTH""#).unwrap()));
+
+        // keyword must be a string literal
+        assert!(syn::parse_str::<Attr>("alias = LENGTH").is_err());
+        assert!(syn::parse_str::<Attr>("alias").is_err());
+    }
+
+    #[test]
+    fn parse_attr_abstract_entity() {
+        let attr: Attr = syn::parse_str("abstract_entity").unwrap();
+        assert_eq!(attr, Attr::AbstractEntity);
+        assert!(syn::parse_str::<Attr>("abstract_entity = true").is_err());
+    }
 }
@@ -23,12 +23,13 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use proc_macro_error::{abort_call_site, proc_macro_error};
+use proc_macro_error::{abort, abort_call_site, proc_macro_error};
 use quote::quote;
 use std::convert::*;
 
 mod common;
 mod entity;
+mod enumeration;
 mod field_type;
 mod holder_attr;
 mod select;
@@ -71,8 +72,21 @@ use table_init::*;
 ///     pub a: A,
 /// }
 /// ```
+///
+/// A field `HashMap<u64, usize>` with `#[table_init(index)]` is not a table,
+/// but keeps the position of the table field for each id, e.g. `0` for `a` above.
+/// It is updated when records are appended and by `ruststep::tables::TableIds::register_id`.
+///
+/// A field `ruststep::tables::Constants` with `#[table_init(constants)]` is not a table either,
+/// but returned by `ruststep::tables::TableConstants::constants` to resolve constant names, e.g. `#ORIGIN`.
+///
+/// A field `ruststep::id::IdAllocator` with `#[table_init(ids)]` is returned by `ruststep::tables::TableIds::id_allocator`,
+/// and ids in its reserved ranges are not assigned by `ruststep::tables::TableIds::next_id`.
+///
+/// A table field with `#[table_init(alias = "C")]` also takes records of the keyword `C`,
+/// e.g. for a type collapsed into an alias of the type of the table.
 #[proc_macro_error]
-#[proc_macro_derive(TableInit)]
+#[proc_macro_derive(TableInit, attributes(table_init))]
 pub fn derive_table_init_entry(input: TokenStream) -> TokenStream {
     derive_table_init(&syn::parse(input).unwrap()).into()
 }
@@ -122,15 +136,137 @@ fn derive_deserialize(ast: &syn::DeriveInput) -> TokenStream2 {
 /// - `#[holder(table = {path::to::table::struct})]`
 ///   - This must be a container attribute
 ///   - Specify a struct path which contains a table for this Holder
+///   - The table must implement `ruststep::tables::TableConstants` to resolve place holders,
+///     e.g. by `#[derive(TableInit)]`
 /// - `#[holder(field = {field_ident})]`
 ///   - This can be both in container or variant attribute
 ///   - Identifier of table field
 /// - `#[holder(generate_deserialize)]`
 ///   - This must be a container attribute
 ///   - Flag for generating `impl Deserialize for XxxHolder`
+/// - `#[holder(generate_into_holder)]`
+///   - This must be a container attribute
+///   - Flag for generating `impl IntoHolder for Xxx`.
+///     The table must implement `ruststep::tables::TableIds`, e.g. by `#[derive(TableInit)]`
+/// - `#[holder(generate_to_record)]`
+///   - This must be a container attribute
+///   - Flag for generating `impl ToRecord for XxxHolder` and `impl ToParameter for XxxHolder`.
+///     Parameters are written in the positions of the fields, and skipped positions are `$`
+/// - `#[holder(generate_serde)]`
+///   - This must be a container attribute with `generate_deserialize`
+///   - Flag for generating `impl Serialize for XxxHolder`, and reading binary formats,
+///     e.g. bincode, by `impl Deserialize for XxxHolder` in the same form.
+///     Human-readable formats are still read as the exchange structure.
+/// - `#[holder(abstract_entity)]`
+///   - This must be a container attribute
+///   - The entity is `ABSTRACT SUPERTYPE`. A simple record of it, e.g. `#1 = SHAPE(...)`,
+///     is rejected while loading tables, but it is still accepted as the supertype part of its subtypes
 /// - `#[holder(use_place_holder)]`
 ///   - This can be both in field or variant attribute
 ///   - Specify the field is not a simple type
+/// - `#[holder(index = {position})]`
+///   - This must be a field attribute
+///   - Bind the field to the parameter at `position` in the record.
+///     A field without `index` is bound to the position next to the previous field.
+///   - Positions not bound to any field are consumed and discarded
+/// - `#[holder(skip)]`
+///   - This must be a field attribute
+///   - Consume and discard the parameter at the position of the field.
+///     The field is not in the holder, and is `Default::default()` in the owned struct.
+///   - This cannot be used with `use_place_holder` since the field is not in the holder
+/// - `#[holder(skip_params = {count})]`
+///   - This must be a field attribute
+///   - Consume and discard `count` parameters before the field, i.e. the field is bound to
+///     the position `count` after the previous field. This cannot be used with `index`.
+/// - `#[holder(rest)]`
+///   - This must be an attribute of the last field whose type is `Vec<ruststep::ast::Parameter>`
+///   - Capture the parameters after the other positions
+///   - With `#[holder(skip)]`, the parameters are discarded instead,
+///     and the field of any `Default` type is `Default::default()` in the owned struct.
+/// - `#[holder(rename = "{name}")]`
+///   - This can be both in field or variant attribute
+///   - Name of the field used in `attr_names` and error messages, e.g. `"type"` for `r#type`.
+///     A raw identifier is named without `r#` by default.
+///     A renamed field with `use_place_holder` is reported by this name in `for_each_reference`.
+///   - Keyword of the variant, e.g. `.MICRO.` of a unit variant or `RECTANGLE_2D(...)` of a struct variant,
+///     instead of the variant name in screaming snake case
+/// - `#[holder(cache)]`
+///   - This must be a field attribute
+///   - The field is not bound to any parameter, e.g. `ruststep::tables::Memo` of a derived attribute.
+///     It is not in the holder, and is `Default::default()` in the owned struct.
+/// - `#[holder(alias = "{KEYWORD}")]`
+///   - This must be a container attribute of a tuple struct, and can be used more than once
+///   - Another keyword of records and typed parameters of this type, e.g. `LENGTH('...')`
+///     for a type aliased as `length`
+///
+/// ```ignore
+/// #[derive(Holder)]
+/// #[holder(table = Table)]
+/// #[holder(field = c)]
+/// #[holder(generate_deserialize)]
+/// pub struct C {
+///     #[holder(index = 2)]
+///     pub x: f64,     // <- 3rd parameter
+///     #[holder(index = 0)]
+///     pub name: String, // <- 1st parameter
+///     #[holder(skip)]
+///     pub label: String, // <- 2nd parameter is discarded
+///     #[holder(rest)]
+///     pub rest: Vec<Parameter>, // <- 4th and later parameters
+/// }
+///
+/// #[derive(Holder)]
+/// #[holder(table = Table)]
+/// #[holder(field = d)]
+/// #[holder(generate_deserialize)]
+/// pub struct D {
+///     #[holder(rename = "ref")]
+///     #[holder(use_place_holder)]
+///     pub reference: C, // <- 1st parameter named `ref`
+///     #[holder(skip_params = 2)]
+///     pub x: f64,       // <- 4th parameter, 2nd and 3rd are discarded
+///     #[holder(skip)]
+///     #[holder(rest)]
+///     pub ignored: (),  // <- 5th and later parameters are discarded
+/// }
+/// ```
+///
+/// Enums
+/// ------
+///
+/// An enum only with unit variants is an `ENUMERATION` type. It is used as is in holders like espr-generated one,
+/// and `generate_deserialize` and `generate_to_record` generate `Deserialize` and `ToParameter` for the enum itself
+/// reading and writing enumeration values, e.g. `.MILLI.`. `table` is not needed.
+///
+/// Other enums are `SELECT` types whose variants are
+///
+/// - tuple variants with exactly one field, which is an entity with `use_place_holder`, or a simple type
+/// - struct variants, which are values of records whose parameters are the fields in order,
+///   e.g. `CIRCLE((#1, 3.0))` for `Circle { center, radius }`.
+///   `use_place_holder` is given to each field.
+///
+/// ```ignore
+/// #[derive(Holder)]
+/// #[holder(generate_deserialize)]
+/// pub enum Prefix {
+///     Kilo,  // <- .KILO.
+///     #[holder(rename = "MICRO")]
+///     Mu,    // <- .MICRO.
+/// }
+///
+/// #[derive(Holder)]
+/// #[holder(table = Table)]
+/// #[holder(generate_deserialize)]
+/// pub enum Shape {
+///     #[holder(use_place_holder)]
+///     Point(Box<Point>),
+///     Circle {
+///         #[holder(use_place_holder)]
+///         center: Point,
+///         radius: f64,
+///     },
+/// }
+/// ```
 ///
 #[proc_macro_error]
 #[proc_macro_derive(Holder, attributes(holder))]
@@ -141,13 +277,38 @@ pub fn derive_holder_entry(input: TokenStream) -> TokenStream {
 fn derive_holder(ast: &syn::DeriveInput) -> TokenStream2 {
     let attr = HolderAttr::parse(&ast.attrs);
     let ident = &ast.ident;
+    if attr.generate_serde && !attr.generate_deserialize {
+        abort!(
+            ident,
+            "`#[holder(generate_serde)]` requires `#[holder(generate_deserialize)]`";
+            help = "Binary formats are read by `impl Deserialize` generated by `generate_deserialize`"
+        );
+    }
     match &ast.data {
-        syn::Data::Struct(st) => match st.fields {
-            syn::Fields::Named(_) => entity::derive_holder(ident, st, &attr),
-            syn::Fields::Unnamed(_) => type_decl::derive_holder(ident, st, &attr),
-            syn::Fields::Unit => abort_call_site!("Unit struct is not supported."),
-        },
-        syn::Data::Enum(e) => select::derive_holder(ident, e, &attr),
+        syn::Data::Struct(st) => {
+            if let syn::Fields::Unit = st.fields {
+                abort_call_site!("Unit struct is not supported.");
+            }
+            attr.require_table(ident);
+            attr.require_field(ident);
+            match st.fields {
+                syn::Fields::Named(_) => entity::derive_holder(ident, st, &attr),
+                _ => type_decl::derive_holder(ident, st, &attr),
+            }
+        }
+        syn::Data::Enum(e) => {
+            if e.variants.is_empty() {
+                abort_call_site!("Enum without variants is not supported");
+            }
+            if e.variants
+                .iter()
+                .all(|var| matches!(var.fields, syn::Fields::Unit))
+            {
+                enumeration::derive_holder(ident, e, &attr)
+            } else {
+                select::derive_holder(ident, e, &attr)
+            }
+        }
         _ => abort_call_site!("Only struct is supprted currently"),
     }
 }
@@ -188,7 +349,7 @@ mod snapshot_tests {
         let out = espr::codegen::rust::rustfmt(tt.to_string());
 
         insta::assert_snapshot!(out, @r###"
-        #[doc = r" Auto-generated by `#[derive(Holder)]`"]
+        /// Auto-generated by `#[derive(Holder)]`
         #[derive(Clone, Debug, PartialEq)]
         pub enum S1Holder {
             A(Box<AHolder>),
@@ -198,10 +359,12 @@ mod snapshot_tests {
             type Owned = S1;
             type Table = Table;
             fn into_owned(self, table: &Self::Table) -> ::ruststep::error::Result<Self::Owned> {
-                Ok(match self {
-                    S1Holder::A(sub) => S1::A(Box::new(sub.into_owned(table)?)),
-                    S1Holder::B(sub) => S1::B(Box::new(sub.into_owned(table)?)),
-                })
+                Ok(
+                    match self {
+                        S1Holder::A(sub) => S1::A(sub.into_owned(table)?),
+                        S1Holder::B(sub) => S1::B(sub.into_owned(table)?),
+                    },
+                )
             }
         }
         impl ::ruststep::tables::Holder for S1Holder {
@@ -211,6 +374,16 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 0
             }
+            fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                match self {
+                    S1Holder::A(sub) => {
+                        ::ruststep::tables::Holder::for_each_reference(sub.as_ref(), f)
+                    }
+                    S1Holder::B(sub) => {
+                        ::ruststep::tables::Holder::for_each_reference(sub.as_ref(), f)
+                    }
+                }
+            }
         }
         impl<'de> ::ruststep::serde::de::Deserialize<'de> for S1Holder {
             fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
@@ -244,7 +417,7 @@ mod snapshot_tests {
                         return Ok(S1Holder::B(Box::new(owned)));
                     }
                     _ => {
-                        use ruststep::serde::de::{Error, Unexpected};
+                        use ::ruststep::serde::de::{Error, Unexpected};
                         return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
                     }
                 }
@@ -258,23 +431,32 @@ mod snapshot_tests {
         }
         impl ::ruststep::tables::EntityTable<S1Holder> for Table {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<S1> {
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<AHolder>::get_owned(self, entity_id) {
-                    return Ok(S1::A(Box::new(owned.into())));
+                match ::ruststep::tables::EntityTable::<AHolder>::get_owned(self, entity_id) {
+                    Ok(owned) => return Ok(S1::A(Box::new(owned.into()))),
+                    Err(::ruststep::error::Error::UnknownEntity(_)) => {}
+                    Err(e) => return Err(e),
                 }
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<BHolder>::get_owned(self, entity_id) {
-                    return Ok(S1::B(Box::new(owned.into())));
+                match ::ruststep::tables::EntityTable::<BHolder>::get_owned(self, entity_id) {
+                    Ok(owned) => return Ok(S1::B(Box::new(owned.into()))),
+                    Err(::ruststep::error::Error::UnknownEntity(_)) => {}
+                    Err(e) => return Err(e),
                 }
                 Err(::ruststep::error::Error::UnknownEntity(entity_id))
             }
+            fn entity_ids(&self) -> Vec<u64> {
+                let mut ids = Vec::new();
+                ids.extend(::ruststep::tables::EntityTable::<AHolder>::entity_ids(self));
+                ids.extend(::ruststep::tables::EntityTable::<BHolder>::entity_ids(self));
+                ids.sort_unstable();
+                ids
+            }
             fn owned_iter<'table>(
                 &'table self,
             ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<S1>> + 'table> {
-                Box::new(::ruststep::itertools::chain![
-                    ::ruststep::tables::EntityTable::<AHolder>::owned_iter(self)
-                        .map(|owned| owned.map(|owned| S1::A(Box::new(owned.into())))),
-                    ::ruststep::tables::EntityTable::<BHolder>::owned_iter(self)
-                        .map(|owned| owned.map(|owned| S1::B(Box::new(owned.into()))))
-                ])
+                ::ruststep::tables::any_iter(
+                    ::ruststep::tables::EntityTable::<S1Holder>::entity_ids(self).into_iter(),
+                    move |id| ::ruststep::tables::EntityTable::<S1Holder>::get_owned(self, id),
+                )
             }
         }
         "###);
@@ -302,7 +484,7 @@ mod snapshot_tests {
         let out = espr::codegen::rust::rustfmt(tt.to_string());
 
         insta::assert_snapshot!(out, @r###"
-        #[doc = r" Auto-generated by `#[derive(Holder)]`"]
+        /// Auto-generated by `#[derive(Holder)]`
         #[derive(Clone, Debug, PartialEq)]
         pub enum BaseAnyHolder {
             Base(Box<BaseHolder>),
@@ -312,10 +494,12 @@ mod snapshot_tests {
             type Owned = BaseAny;
             type Table = Tables;
             fn into_owned(self, table: &Self::Table) -> ::ruststep::error::Result<Self::Owned> {
-                Ok(match self {
-                    BaseAnyHolder::Base(sub) => BaseAny::Base(Box::new(sub.into_owned(table)?)),
-                    BaseAnyHolder::Sub(sub) => BaseAny::Sub(Box::new(sub.into_owned(table)?)),
-                })
+                Ok(
+                    match self {
+                        BaseAnyHolder::Base(sub) => BaseAny::Base(sub.into_owned(table)?),
+                        BaseAnyHolder::Sub(sub) => BaseAny::Sub(sub.into_owned(table)?),
+                    },
+                )
             }
         }
         impl ::ruststep::tables::Holder for BaseAnyHolder {
@@ -325,6 +509,16 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 0
             }
+            fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                match self {
+                    BaseAnyHolder::Base(sub) => {
+                        ::ruststep::tables::Holder::for_each_reference(sub.as_ref(), f)
+                    }
+                    BaseAnyHolder::Sub(sub) => {
+                        ::ruststep::tables::Holder::for_each_reference(sub.as_ref(), f)
+                    }
+                }
+            }
         }
         impl<'de> ::ruststep::serde::de::Deserialize<'de> for BaseAnyHolder {
             fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
@@ -358,7 +552,7 @@ mod snapshot_tests {
                         return Ok(BaseAnyHolder::Sub(Box::new(owned)));
                     }
                     _ => {
-                        use ruststep::serde::de::{Error, Unexpected};
+                        use ::ruststep::serde::de::{Error, Unexpected};
                         return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
                     }
                 }
@@ -372,26 +566,37 @@ mod snapshot_tests {
         }
         impl ::ruststep::tables::EntityTable<BaseAnyHolder> for Tables {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<BaseAny> {
-                if let Ok(owned) = ::ruststep::tables::EntityTable::<BaseHolder>::get_owned(self, entity_id)
-                {
-                    return Ok(BaseAny::Base(Box::new(owned.into())));
+                match ::ruststep::tables::EntityTable::<BaseHolder>::get_owned(self, entity_id) {
+                    Ok(owned) => return Ok(BaseAny::Base(Box::new(owned.into()))),
+                    Err(::ruststep::error::Error::UnknownEntity(_)) => {}
+                    Err(e) => return Err(e),
                 }
-                if let Ok(owned) =
-                    ::ruststep::tables::EntityTable::<SubAnyHolder>::get_owned(self, entity_id)
-                {
-                    return Ok(BaseAny::Sub(Box::new(owned.into())));
+                match ::ruststep::tables::EntityTable::<
+                    SubAnyHolder,
+                >::get_owned(self, entity_id) {
+                    Ok(owned) => return Ok(BaseAny::Sub(Box::new(owned.into()))),
+                    Err(::ruststep::error::Error::UnknownEntity(_)) => {}
+                    Err(e) => return Err(e),
                 }
                 Err(::ruststep::error::Error::UnknownEntity(entity_id))
             }
+            fn entity_ids(&self) -> Vec<u64> {
+                let mut ids = Vec::new();
+                ids.extend(::ruststep::tables::EntityTable::<BaseHolder>::entity_ids(self));
+                ids.extend(::ruststep::tables::EntityTable::<SubAnyHolder>::entity_ids(self));
+                ids.sort_unstable();
+                ids
+            }
             fn owned_iter<'table>(
                 &'table self,
             ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<BaseAny>> + 'table> {
-                Box::new(::ruststep::itertools::chain![
-                    ::ruststep::tables::EntityTable::<BaseHolder>::owned_iter(self)
-                        .map(|owned| owned.map(|owned| BaseAny::Base(Box::new(owned.into())))),
-                    ::ruststep::tables::EntityTable::<SubAnyHolder>::owned_iter(self)
-                        .map(|owned| owned.map(|owned| BaseAny::Sub(Box::new(owned.into()))))
-                ])
+                ::ruststep::tables::any_iter(
+                    ::ruststep::tables::EntityTable::<BaseAnyHolder>::entity_ids(self)
+                        .into_iter(),
+                    move |id| ::ruststep::tables::EntityTable::<
+                        BaseAnyHolder,
+                    >::get_owned(self, id),
+                )
             }
         }
         "###);
@@ -424,7 +629,7 @@ mod snapshot_tests {
         let out = espr::codegen::rust::rustfmt(tt.to_string());
 
         insta::assert_snapshot!(out, @r###"
-        #[doc = r" Auto-generated by `#[derive(Holder)]`"]
+        /// Auto-generated by `#[derive(Holder)]`
         #[derive(Debug, Clone, PartialEq)]
         pub struct Sub1Holder {
             pub base: ::ruststep::tables::PlaceHolder<BaseHolder>,
@@ -450,12 +655,45 @@ mod snapshot_tests {
             fn attr_len() -> usize {
                 2usize
             }
+            fn attr_kinds() -> &'static [::ruststep::tables::AttributeKind] {
+                &[
+                    ::ruststep::tables::AttributeKind {
+                        kind: ::ruststep::tables::ValueKind::Other,
+                        optional: false,
+                    },
+                    ::ruststep::tables::AttributeKind {
+                        kind: ::ruststep::tables::ValueKind::Real,
+                        optional: false,
+                    },
+                ]
+            }
+            fn attr_names() -> &'static [&'static str] {
+                &["base", "y1"]
+            }
+            fn attr_value(
+                &self,
+                index: usize,
+            ) -> Option<::ruststep::tables::AttributeValue<'_>> {
+                Some(
+                    match index {
+                        0usize => ::ruststep::tables::AttributeValue::Reference,
+                        1usize => ::ruststep::tables::AttributeValue::Real(self.y1),
+                        _ => return None,
+                    },
+                )
+            }
+            fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                ::ruststep::tables::References::references(&self.base, &mut |id| f(0usize, id));
+            }
         }
         #[automatically_derived]
         impl ::ruststep::tables::EntityTable<Sub1Holder> for Tables {
             fn get_owned(&self, entity_id: u64) -> ::ruststep::error::Result<Sub1> {
                 ::ruststep::tables::get_owned(self, &self.sub1, entity_id)
             }
+            fn entity_ids(&self) -> Vec<u64> {
+                ::ruststep::tables::entity_ids(&self.sub1)
+            }
             fn owned_iter<'table>(
                 &'table self,
             ) -> Box<dyn Iterator<Item = ::ruststep::error::Result<Sub1>> + 'table> {
@@ -476,12 +714,29 @@ mod snapshot_tests {
             {
                 if let Some(size) = seq.size_hint() {
                     if size != 2usize {
-                        use ruststep::serde::de::Error;
+                        use ::ruststep::serde::de::Error;
                         return Err(A::Error::invalid_length(size, &self));
                     }
                 }
-                let base = seq.next_element()?.unwrap();
-                let y1 = seq.next_element()?.unwrap();
+                let mut base = None;
+                let mut y1 = None;
+                let order = ::ruststep::tables::attribute_order("SUB_1", 2usize)
+                    .map_err(<A::Error as ::ruststep::serde::de::Error>::custom)?;
+                for index in order {
+                    match index {
+                        0usize => {
+                            let _attribute = ::ruststep::error::enter_attribute("base");
+                            base = seq.next_element()?;
+                        }
+                        1usize => {
+                            let _attribute = ::ruststep::error::enter_attribute("y1");
+                            y1 = seq.next_element()?;
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                let base = base.unwrap();
+                let y1 = y1.unwrap();
                 Ok(Sub1Holder { base, y1 })
             }
             fn visit_map<A>(self, mut map: A) -> ::std::result::Result<Self::Value, A::Error>
@@ -492,7 +747,7 @@ mod snapshot_tests {
                     .next_key()?
                     .expect("Empty map cannot be accepted as ruststep Holder");
                 if key != "SUB_1" {
-                    use ruststep::serde::de::{Error, Unexpected};
+                    use ::ruststep::serde::de::{Error, Unexpected};
                     return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
                 }
                 let value = map.next_value()?;
@@ -515,6 +770,28 @@ mod snapshot_tests {
                 Sub1HolderVisitor {}
             }
         }
+        #[automatically_derived]
+        impl Sub1Holder {
+            /// Deserialize from a record without tables. References are kept as place holders.
+            pub fn parse_record(
+                record: &::ruststep::ast::Record,
+            ) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::parse_record(record)
+            }
+            /// Deserialize from a simple entity instance, or a partial record in a complex entity instance.
+            pub fn try_from_instance(
+                instance: &::ruststep::ast::EntityInstance,
+            ) -> ::ruststep::error::Result<Self> {
+                ::ruststep::tables::parse_instance(instance)
+            }
+        }
+        #[automatically_derived]
+        impl ::std::convert::TryFrom<&::ruststep::ast::Record> for Sub1Holder {
+            type Error = ::ruststep::error::Error;
+            fn try_from(record: &::ruststep::ast::Record) -> ::ruststep::error::Result<Self> {
+                Self::parse_record(record)
+            }
+        }
         "###);
     }
 }
@@ -1,6 +1,6 @@
 use super::*;
 use inflector::Inflector;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use proc_macro_error::*;
 use quote::quote;
 
@@ -16,7 +16,84 @@ struct Input {
     variant_into_exprs: Vec<TokenStream2>,
     holder_types: Vec<syn::Type>,
     holder_exprs: Vec<TokenStream2>,
+    /// Conversion of the owned value of each variant into the holder
+    into_holder_exprs: Vec<TokenStream2>,
+    /// Conversion of each variant into a place holder of this holder
+    place_holder_exprs: Vec<TokenStream2>,
+    /// Match arm calling `f` with entity references in each variant
+    reference_arms: Vec<TokenStream2>,
     place_holders: Vec<bool>,
+    /// Struct variants, which are not included in the fields above
+    struct_variants: Vec<StructVariant>,
+}
+
+/// Struct variant like `Circle { radius: f64 }`,
+/// which is a value of the record `CIRCLE(1.0)` whose parameters are the fields in order
+struct StructVariant {
+    ident: syn::Ident,
+    name: String,
+    fields: Vec<syn::Ident>,
+    holder_types: Vec<syn::Type>,
+    /// Conversion of each holder field into the owned field
+    into_owned: Vec<TokenStream2>,
+    /// Conversion of each owned field into the holder field
+    into_holder: Vec<TokenStream2>,
+    /// Fields with `#[holder(use_place_holder)]` and their indices in the variant
+    place_holder_fields: Vec<(syn::Ident, usize)>,
+}
+
+impl StructVariant {
+    fn parse(var: &syn::Variant, name: String) -> Self {
+        let ruststep = ruststep_crate();
+        let table = syn::Ident::new("table", Span::call_site());
+        if var.fields.is_empty() {
+            abort!(var.ident, "struct variant must have fields");
+        }
+        let mut fields = Vec::new();
+        let mut holder_types = Vec::new();
+        let mut into_owned = Vec::new();
+        let mut into_holder = Vec::new();
+        let mut place_holder_fields = Vec::new();
+        for (i, f) in var.fields.iter().enumerate() {
+            let ident = f.ident.clone().expect_or_abort("unreachable!");
+            let HolderAttr {
+                place_holder,
+                index,
+                skip,
+                rest,
+                ..
+            } = HolderAttr::parse(&f.attrs);
+            if index.is_some() || skip || rest {
+                abort!(
+                    ident,
+                    "`index`, `skip`, and `rest` are not supported in a struct variant"
+                );
+            }
+            let ft = FieldType::try_from(f.ty.clone()).unwrap_or_abort();
+            if place_holder {
+                into_holder.push(ft.place_holder_expr(quote! { #ident }, &table));
+                into_owned.push(quote! {
+                    #ruststep::tables::IntoOwned::into_owned(#ident, #table)?
+                });
+                holder_types.push(ft.into_holder().into_place_holder().into());
+                place_holder_fields.push((ident.clone(), i));
+            } else {
+                into_holder.push(quote! { #ident });
+                into_owned.push(quote! { #ident });
+                holder_types.push(ft.into());
+            }
+            fields.push(ident);
+        }
+        StructVariant {
+            ident: var.ident.clone(),
+            name,
+            fields,
+            holder_types,
+            into_owned,
+            into_holder,
+            place_holder_fields,
+        }
+    }
 }
 
 impl Input {
@@ -24,35 +101,78 @@ impl Input {
         let name = ident.to_string().to_screaming_snake_case();
         let holder_ident = as_holder_ident(ident);
         let holder_visitor_ident = as_visitor_ident(&holder_ident);
-        let variants: Vec<syn::Ident> = e.variants.iter().map(|var| var.ident.clone()).collect();
-        let variant_names: Vec<_> = variants
-            .iter()
-            .map(|id| id.to_string().to_screaming_snake_case())
-            .collect();
-        let table = attr
-            .table
-            .clone()
-            .expect_or_abort("table attribute is lacked");
+        let table = attr.require_table(ident).clone();
 
+        let mut variants = Vec::new();
+        let mut variant_names = Vec::new();
         let mut holder_exprs = Vec::new();
         let mut holder_types = Vec::new();
         let mut variant_exprs = Vec::new();
         let mut variant_into_exprs = Vec::new();
+        let mut into_holder_exprs = Vec::new();
+        let mut place_holder_exprs = Vec::new();
+        let mut reference_arms = Vec::new();
         let mut place_holders = Vec::new();
+        let mut struct_variants = Vec::new();
+        let ruststep = ruststep_crate();
         for var in &e.variants {
-            let HolderAttr { place_holder, .. } = HolderAttr::parse(&var.attrs);
+            let var_ident = &var.ident;
+            let HolderAttr {
+                place_holder,
+                rename,
+                ..
+            } = HolderAttr::parse(&var.attrs);
+            let var_name = match rename {
+                Some(keyword) => keyword.value(),
+                None => var_ident.to_string().to_screaming_snake_case(),
+            };
+
+            match &var.fields {
+                syn::Fields::Named(_) => {
+                    if place_holder {
+                        abort!(
+                            var_ident,
+                            "`use_place_holder` of a struct variant must be given to its fields"
+                        );
+                    }
+                    struct_variants.push(StructVariant::parse(var, var_name));
+                    continue;
+                }
+                syn::Fields::Unit => abort!(
+                    var_ident,
+                    "unit variant cannot be mixed with other kinds of variants";
+                    help = "An enum only with unit variants is supported as ENUMERATION"
+                ),
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() != 1 => abort!(
+                    var_ident,
+                    "tuple variant must have exactly one field";
+                    help = "Use a struct variant for a record with multiple parameters"
+                ),
+                syn::Fields::Unnamed(_) => {}
+            }
+            variants.push(var_ident.clone());
+            variant_names.push(var_name);
             place_holders.push(place_holder);
 
-            assert_eq!(var.fields.len(), 1);
             for f in &var.fields {
                 let ty = FieldType::try_from(f.ty.clone()).unwrap();
                 if let FieldType::Boxed(_) = ty {
                     if place_holder {
                         // ENTITY case
                         holder_types.push(as_holder_path(&f.ty));
-                        holder_exprs.push(quote! { Box::new(sub.into_owned(table)?) });
+                        holder_exprs.push(quote! { sub.into_owned(table)? });
                         variant_exprs.push(quote! { Box::new(owned) });
                         variant_into_exprs.push(quote! { Box::new(owned.into()) });
+                        into_holder_exprs.push(quote! {
+                            Box::new(#ruststep::tables::IntoHolder::into_holder(*owned, table)?)
+                        });
+                        place_holder_exprs.push(quote! {
+                            #ruststep::tables::IntoHolder::into_place_holder(*owned, table)?
+                                .map(|holder| #holder_ident::#var_ident(Box::new(holder)))
+                        });
+                        reference_arms.push(quote! {
+                            #holder_ident::#var_ident(sub) => #ruststep::tables::Holder::for_each_reference(sub.as_ref(), f)
+                        });
                     } else {
                         abort_call_site!("Simple type should not be Boxed")
                     }
@@ -63,10 +183,25 @@ impl Input {
                         // *Any case
                         holder_types.push(as_holder_path(&f.ty));
                         holder_exprs.push(quote! { sub.into_owned(table)? });
+                        into_holder_exprs.push(quote! {
+                            #ruststep::tables::IntoHolder::into_holder(owned, table)?
+                        });
+                        place_holder_exprs.push(quote! {
+                            #ruststep::tables::IntoHolder::into_place_holder(owned, table)?
+                                .map(#holder_ident::#var_ident)
+                        });
+                        reference_arms.push(quote! {
+                            #holder_ident::#var_ident(sub) => #ruststep::tables::Holder::for_each_reference(sub, f)
+                        });
                     } else {
                         // SimpleType case
                         holder_types.push(f.ty.clone());
                         holder_exprs.push(quote! { sub });
+                        into_holder_exprs.push(quote! { owned });
+                        place_holder_exprs.push(quote! {
+                            #ruststep::tables::PlaceHolder::Owned(#holder_ident::#var_ident(owned))
+                        });
+                        reference_arms.push(quote! { #holder_ident::#var_ident(_) => {} });
                     }
                 }
             }
@@ -84,23 +219,47 @@ impl Input {
             variant_into_exprs,
             holder_types,
             holder_exprs,
+            into_holder_exprs,
+            place_holder_exprs,
+            reference_arms,
             place_holders,
+            struct_variants,
         }
     }
 
-    fn def_holder(&self) -> TokenStream2 {
+    fn def_holder(&self, generate_serde: bool) -> TokenStream2 {
         let Input {
             holder_ident,
             variants,
             holder_types,
+            struct_variants,
             ..
         } = self;
+        let struct_defs = struct_variants.iter().map(|var| {
+            let StructVariant {
+                ident,
+                fields,
+                holder_types,
+                ..
+            } = var;
+            quote! { #ident { #(#fields: #holder_types),* } }
+        });
+        let body = quote! {
+            {
+                #(#variants(#holder_types),)*
+                #(#struct_defs,)*
+            }
+        };
+        let def_serde_mirror_tt = if generate_serde {
+            Some(def_serde_mirror(holder_ident, quote! { enum }, &body))
+        } else {
+            None
+        };
         quote! {
             /// Auto-generated by `#[derive(Holder)]`
             #[derive(Clone, Debug, PartialEq)]
-            pub enum #holder_ident {
-                #(#variants(#holder_types)),*
-            }
+            pub enum #holder_ident #body
+            #def_serde_mirror_tt
         } // quote!
     }
 
@@ -112,9 +271,48 @@ impl Input {
             variants,
             table,
             holder_exprs,
+            reference_arms,
+            place_holders,
+            struct_variants,
             ..
         } = self;
         let ruststep = ruststep_crate();
+        let into_owned_arms = struct_variants.iter().map(|var| {
+            let StructVariant {
+                ident: var_ident,
+                fields,
+                into_owned,
+                ..
+            } = var;
+            quote! {
+                #holder_ident::#var_ident { #(#fields),* } => #ident::#var_ident { #(#fields: #into_owned),* }
+            }
+        });
+        let struct_reference_arms = struct_variants.iter().map(|var| {
+            let var_ident = &var.ident;
+            let (fields, indices): (Vec<_>, Vec<_>) = var.place_holder_fields.iter().cloned().unzip();
+            quote! {
+                #holder_ident::#var_ident { #(#fields,)* .. } => {
+                    #( #ruststep::tables::References::references(#fields, &mut |id| f(#indices, id)); )*
+                }
+            }
+        });
+        let has_references = place_holders.iter().any(|p| *p)
+            || struct_variants
+                .iter()
+                .any(|var| !var.place_holder_fields.is_empty());
+        let impl_for_each_reference = if has_references {
+            Some(quote! {
+                fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                    match self {
+                        #(#reference_arms,)*
+                        #(#struct_reference_arms,)*
+                    }
+                }
+            })
+        } else {
+            None
+        };
 
         quote! {
             impl #ruststep::tables::IntoOwned for #holder_ident {
@@ -122,7 +320,8 @@ impl Input {
                 type Table = #table;
                 fn into_owned(self, table: &Self::Table) -> #ruststep::error::Result<Self::Owned> {
                     Ok(match self {
-                        #(#holder_ident::#variants(sub) => #ident::#variants(#holder_exprs)),*
+                        #(#holder_ident::#variants(sub) => #ident::#variants(#holder_exprs),)*
+                        #(#into_owned_arms,)*
                     })
                 }
             }
@@ -133,11 +332,149 @@ impl Input {
                 fn attr_len() -> usize {
                     0
                 }
+                #impl_for_each_reference
+            }
+        } // quote!
+    }
+
+    fn impl_into_holder(&self) -> TokenStream2 {
+        let Input {
+            ident,
+            holder_ident,
+            variants,
+            table,
+            into_holder_exprs,
+            place_holder_exprs,
+            struct_variants,
+            ..
+        } = self;
+        let ruststep = ruststep_crate();
+        let into_holder_arms = struct_variants.iter().map(|var| {
+            let StructVariant {
+                ident: var_ident,
+                fields,
+                into_holder,
+                ..
+            } = var;
+            quote! {
+                #ident::#var_ident { #(#fields),* } => #holder_ident::#var_ident { #(#fields: #into_holder),* }
+            }
+        });
+        // Struct variants are values, not inserted into the table
+        let place_holder_arms = struct_variants.iter().map(|var| {
+            let var_ident = &var.ident;
+            quote! {
+                owned @ #ident::#var_ident { .. } => #ruststep::tables::PlaceHolder::Owned(
+                    #ruststep::tables::IntoHolder::into_holder(owned, table)?
+                )
+            }
+        });
+
+        quote! {
+            impl #ruststep::tables::IntoHolder for #ident {
+                type Holder = #holder_ident;
+                fn into_holder(self, table: &mut #table) -> #ruststep::error::Result<Self::Holder> {
+                    Ok(match self {
+                        #(#ident::#variants(owned) => #holder_ident::#variants(#into_holder_exprs),)*
+                        #(#into_holder_arms,)*
+                    })
+                }
+                fn into_place_holder(self, table: &mut #table) -> #ruststep::error::Result<#ruststep::tables::PlaceHolder<Self::Holder>> {
+                    Ok(match self {
+                        #(#ident::#variants(owned) => #place_holder_exprs,)*
+                        #(#place_holder_arms,)*
+                    })
+                }
             }
         } // quote!
     }
 
-    fn impl_deserialize(&self) -> TokenStream2 {
+    fn impl_to_record(&self) -> TokenStream2 {
+        let Input {
+            holder_ident,
+            variants,
+            variant_names,
+            place_holders,
+            struct_variants,
+            ..
+        } = self;
+        let ruststep = ruststep_crate();
+        let mut to_parameter_arms = Vec::new();
+        let mut to_record_arms = Vec::new();
+        for var in struct_variants {
+            let StructVariant {
+                ident: var_ident,
+                name,
+                fields,
+                ..
+            } = var;
+            let parameter = quote! {
+                #ruststep::ast::Parameter::List(vec![
+                    #( #ruststep::tables::ToParameter::to_parameter(#fields) ),*
+                ])
+            };
+            to_parameter_arms.push(quote! {
+                #holder_ident::#var_ident { #(#fields),* } => #ruststep::ast::Parameter::Typed {
+                    keyword: #name.to_string(),
+                    parameter: Box::new(#parameter),
+                }
+            });
+            to_record_arms.push(quote! {
+                #holder_ident::#var_ident { #(#fields),* } => #ruststep::ast::Record {
+                    name: #name.to_string(),
+                    user_defined: false,
+                    parameter: #parameter,
+                }
+            });
+        }
+        let mut to_parameter_exprs = Vec::new();
+        let mut to_record_exprs = Vec::new();
+        for (name, place_holder) in variant_names.iter().zip(place_holders) {
+            if *place_holder {
+                to_parameter_exprs
+                    .push(quote! { #ruststep::tables::ToParameter::to_parameter(sub) });
+                to_record_exprs.push(quote! { #ruststep::tables::ToRecord::to_record(sub) });
+            } else {
+                // Simple types are distinguished by the keyword of the variant
+                to_parameter_exprs.push(quote! {
+                    #ruststep::ast::Parameter::Typed {
+                        keyword: #name.to_string(),
+                        parameter: Box::new(#ruststep::tables::ToParameter::to_parameter(sub)),
+                    }
+                });
+                to_record_exprs.push(quote! {
+                    #ruststep::ast::Record {
+                        name: #name.to_string(),
+                        user_defined: false,
+                        parameter: #ruststep::ast::Parameter::List(vec![
+                            #ruststep::tables::ToParameter::to_parameter(sub)
+                        ]),
+                    }
+                });
+            }
+        }
+
+        quote! {
+            impl #ruststep::tables::ToParameter for #holder_ident {
+                fn to_parameter(&self) -> #ruststep::ast::Parameter {
+                    match self {
+                        #(#holder_ident::#variants(sub) => #to_parameter_exprs,)*
+                        #(#to_parameter_arms,)*
+                    }
+                }
+            }
+            impl #ruststep::tables::ToRecord for #holder_ident {
+                fn to_record(&self) -> #ruststep::ast::Record {
+                    match self {
+                        #(#holder_ident::#variants(sub) => #to_record_exprs,)*
+                        #(#to_record_arms,)*
+                    }
+                }
+            }
+        } // quote!
+    }
+
+    fn impl_deserialize(&self, generate_serde: bool) -> TokenStream2 {
         let Input {
             name,
             holder_ident,
@@ -145,12 +482,14 @@ impl Input {
             ..
         } = self;
         let serde = serde_crate();
+        let deserialize_binary = deserialize_binary(holder_ident, generate_serde);
         quote! {
             impl<'de> #serde::de::Deserialize<'de> for #holder_ident {
                 fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
                 where
                     D: #serde::de::Deserializer<'de>,
                 {
+                    #deserialize_binary
                     deserializer.deserialize_tuple_struct(#name, 0, #holder_visitor_ident {})
                 }
             }
@@ -165,10 +504,27 @@ impl Input {
             variants,
             variant_names,
             variant_exprs,
+            struct_variants,
             ..
         } = self;
         let ruststep = ruststep_crate();
         let serde = serde_crate();
+        // Parameters of the record are read as a tuple of the fields
+        let struct_arms = struct_variants.iter().map(|var| {
+            let StructVariant {
+                ident: var_ident,
+                name,
+                fields,
+                holder_types,
+                ..
+            } = var;
+            quote! {
+                #name => {
+                    let (#(#fields,)*): (#(#holder_types,)*) = map.next_value()?;
+                    return Ok(#holder_ident::#var_ident { #(#fields),* });
+                }
+            }
+        });
 
         quote! {
             #[doc(hidden)]
@@ -195,6 +551,7 @@ impl Input {
                             return Ok(#holder_ident::#variants(#variant_exprs));
                         }
                         )*
+                        #(#struct_arms)*
                         _ => {
                             use #serde::de::{Error, Unexpected};
                             return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
@@ -224,7 +581,6 @@ impl Input {
             ..
         } = self;
         let ruststep = ruststep_crate();
-        let itertools = itertools_crate();
         let mut vars = Vec::new();
         let mut holders = Vec::<syn::Type>::new();
         let mut exprs = Vec::new();
@@ -249,19 +605,28 @@ impl Input {
             impl #ruststep::tables::EntityTable<#holder_ident> for #table {
                 fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
                     #(
-                    if let Ok(owned) = #ruststep::tables::EntityTable::<#holders>::get_owned(self, entity_id) {
-                        return Ok(#ident::#vars(#exprs));
+                    match #ruststep::tables::EntityTable::<#holders>::get_owned(self, entity_id) {
+                        Ok(owned) => return Ok(#ident::#vars(#exprs)),
+                        // Not in this table
+                        Err(#ruststep::error::Error::UnknownEntity(_)) => {}
+                        Err(e) => return Err(e),
                     }
                     )*
                     Err(#ruststep::error::Error::UnknownEntity(entity_id))
                 }
+                fn entity_ids(&self) -> Vec<u64> {
+                    let mut ids = Vec::new();
+                    #(
+                    ids.extend(#ruststep::tables::EntityTable::<#holders>::entity_ids(self));
+                    )*
+                    ids.sort_unstable();
+                    ids
+                }
                 fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
-                    Box::new(#itertools::chain![
-                        #(
-                        #ruststep::tables::EntityTable::<#holders>::owned_iter(self)
-                            .map(|owned| owned.map(|owned| #ident::#vars(#exprs)))
-                        ),*
-                    ])
+                    #ruststep::tables::any_iter(
+                        #ruststep::tables::EntityTable::<#holder_ident>::entity_ids(self).into_iter(),
+                        move |id| #ruststep::tables::EntityTable::<#holder_ident>::get_owned(self, id),
+                    )
                 }
             }
         } // quote!
@@ -270,11 +635,21 @@ impl Input {
 
 pub fn derive_holder(ident: &syn::Ident, e: &syn::DataEnum, attr: &HolderAttr) -> TokenStream2 {
     let input = Input::parse(ident, e, attr);
-    let def_holder_tt = input.def_holder();
+    let def_holder_tt = input.def_holder(attr.generate_serde);
     let impl_holder_tt = input.impl_holder();
+    let impl_into_holder_tt = if attr.generate_into_holder {
+        Some(input.impl_into_holder())
+    } else {
+        None
+    };
+    let impl_to_record_tt = if attr.generate_to_record {
+        Some(input.impl_to_record())
+    } else {
+        None
+    };
 
     if attr.generate_deserialize {
-        let impl_deserialize_tt = input.impl_deserialize();
+        let impl_deserialize_tt = input.impl_deserialize(attr.generate_serde);
         let def_visitor_tt = input.def_visitor();
         let impl_entity_table_tt = input.impl_entity_table();
         quote! {
@@ -283,11 +658,15 @@ pub fn derive_holder(ident: &syn::Ident, e: &syn::DataEnum, attr: &HolderAttr) -
             #impl_deserialize_tt
             #def_visitor_tt
             #impl_entity_table_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
         } // quote!
     } else {
         quote! {
             #def_holder_tt
             #impl_holder_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
         } // quote!
     }
 }
@@ -1,6 +1,6 @@
 use inflector::Inflector;
 use proc_macro2::TokenStream as TokenStream2;
-use proc_macro_error::{abort_call_site, OptionExt};
+use proc_macro_error::{abort, abort_call_site, OptionExt};
 use quote::quote;
 
 use crate::common::ruststep_crate;
@@ -17,46 +17,193 @@ pub fn derive_table_init(ast: &syn::DeriveInput) -> TokenStream2 {
     }
 }
 
+/// Role of a field which is not a table
+#[derive(Clone, Copy, PartialEq)]
+enum FieldRole {
+    /// `#[table_init(index)]`
+    Index,
+    /// `#[table_init(constants)]`
+    Constants,
+    /// `#[table_init(ids)]`
+    Ids,
+}
+
+/// Attributes of a field given by `#[table_init(..)]`
+#[derive(Default)]
+struct FieldAttr {
+    /// `None` for tables
+    role: Option<FieldRole>,
+    /// Other keywords of records in the table, given by `#[table_init(alias = "...")]`
+    aliases: Vec<String>,
+}
+
+fn field_attr(field: &syn::Field) -> FieldAttr {
+    let mut field_attr = FieldAttr::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("table_init"))
+    {
+        match attr.parse_args::<syn::Meta>() {
+            Ok(syn::Meta::Path(arg)) if arg.is_ident("index") => {
+                field_attr.role = Some(FieldRole::Index)
+            }
+            Ok(syn::Meta::Path(arg)) if arg.is_ident("constants") => {
+                field_attr.role = Some(FieldRole::Constants)
+            }
+            Ok(syn::Meta::Path(arg)) if arg.is_ident("ids") => field_attr.role = Some(FieldRole::Ids),
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                path,
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(keyword),
+                        ..
+                    }),
+                ..
+            })) if path.is_ident("alias") => field_attr.aliases.push(keyword.value()),
+            _ => abort!(
+                attr,
+                "Unknown table_init attribute, only `index`, `constants`, `ids`, and `alias = \"...\"` are supported"
+            ),
+        }
+    }
+    field_attr
+}
+
 fn entity_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
     let mut table_names = Vec::new();
     let mut entity_names = Vec::new();
+    let mut index = None;
+    let mut constants = None;
+    let mut ids = None;
+    // Tables and positions of keywords given by `#[table_init(alias = "...")]`
+    let mut alias_tables = Vec::new();
+    let mut alias_positions = Vec::new();
+    let mut aliases = Vec::new();
     for field in &st.fields {
         let ident = field.ident.as_ref().expect_or_abort("unreachable!");
+        let FieldAttr {
+            role,
+            aliases: field_aliases,
+        } = field_attr(field);
+        match role {
+            Some(FieldRole::Index) => {
+                if index.is_some() {
+                    abort!(ident, "`#[table_init(index)]` is used more than once");
+                }
+                index = Some(ident);
+                continue;
+            }
+            Some(FieldRole::Constants) => {
+                if constants.is_some() {
+                    abort!(ident, "`#[table_init(constants)]` is used more than once");
+                }
+                constants = Some(ident);
+                continue;
+            }
+            Some(FieldRole::Ids) => {
+                if ids.is_some() {
+                    abort!(ident, "`#[table_init(ids)]` is used more than once");
+                }
+                ids = Some(ident);
+                continue;
+            }
+            None => {}
+        }
+        for alias in field_aliases {
+            alias_tables.push(ident);
+            alias_positions.push(table_names.len());
+            aliases.push(alias);
+        }
         let name = ident.to_string().to_screaming_snake_case();
         table_names.push(ident);
         entity_names.push(name);
     }
     assert_eq!(table_names.len(), entity_names.len());
+    let positions: Vec<usize> = (0..table_names.len()).collect();
 
     let ruststep = ruststep_crate();
 
+    // Positions of fields for ids are kept in the index
+    let (insert_arms, register_id) = match index {
+        Some(index) => (
+            quote! {
+                #(
+                #entity_names if !record.user_defined => {
+                    insert_record(&mut self.#table_names, *id, record)?;
+                    self.#index.insert(*id, #positions);
+                    Ok(())
+                }
+                )*
+                #(
+                #aliases if !record.user_defined => {
+                    insert_record(&mut self.#alias_tables, *id, record)?;
+                    self.#index.insert(*id, #alias_positions);
+                    Ok(())
+                }
+                )*
+            },
+            quote! {
+                fn register_id(&mut self, id: u64, keyword: &str) {
+                    let position = match keyword {
+                        #( #entity_names => #positions, )*
+                        #( #aliases => #alias_positions, )*
+                        _ => return,
+                    };
+                    self.#index.insert(id, position);
+                }
+            },
+        ),
+        None => (
+            quote! {
+                #(
+                #entity_names if !record.user_defined => insert_record(&mut self.#table_names, *id, record),
+                )*
+                #(
+                #aliases if !record.user_defined => insert_record(&mut self.#alias_tables, *id, record),
+                )*
+            },
+            quote! {},
+        ),
+    };
+
+    let constants = constants.map(|constants| {
+        quote! {
+            fn constants(&self) -> Option<&#ruststep::tables::Constants> {
+                Some(&self.#constants)
+            }
+        }
+    });
+
+    let id_allocator = ids.map(|ids| {
+        quote! {
+            fn id_allocator(&self) -> Option<&#ruststep::id::IdAllocator> {
+                Some(&self.#ids)
+            }
+        }
+    });
+
     quote! {
         #[automatically_derived]
         impl #ruststep::tables::TableInit for #ident {
-            fn append_data_section(
+            fn append_entity_instance(
                 &mut self,
-                data_sec: &#ruststep::ast::DataSection
+                instance: &#ruststep::ast::EntityInstance
             ) -> #ruststep::error::Result<()> {
                 use #ruststep::{error::Error, tables::insert_record, ast::EntityInstance};
-                for entity in &data_sec.entities {
-                    match entity {
-                        EntityInstance::Simple { id, record } => match record.name.as_str() {
-                            #(
-                            #entity_names => insert_record(&mut self.#table_names, *id, record)?,
-                            )*
-                            _ => {
-                                return Err(Error::UnknownEntityName {
-                                    entity_name: record.name.clone(),
-                                    schema: "".to_string(),
-                                });
-                            }
-                        },
-                        EntityInstance::Complex { .. } => {
-                            unimplemented!("Complex entity is not supported")
-                        }
-                    }
+                match instance {
+                    EntityInstance::Simple { id, record } => match record.name.as_str() {
+                        #insert_arms
+                        _ => Err(Error::UnknownEntityName {
+                            entity_name: record.keyword(),
+                            schema: "".to_string(),
+                        }),
+                    },
+                    EntityInstance::Complex { id, subsuper } => Err(Error::UnplacedComplexEntity {
+                        id: *id,
+                        keywords: subsuper.into_iter().map(|record| record.keyword()).collect(),
+                    }),
                 }
-                Ok(())
             }
         }
 
@@ -66,7 +213,53 @@ fn entity_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStre
             fn from_str(input: &str) -> #ruststep::error::Result<Self> {
                 use #ruststep::{tables::TableInit, ast::DataSection};
                 let data_sec = DataSection::from_str(input)?;
-                Ok(Self::from_data_section(&data_sec)?)
+                Self::from_data_section(&data_sec)
+            }
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::TableConstants for #ident {
+            #constants
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::AnyTables for #ident {
+            fn attr_names(&self, entity: &str) -> Option<&'static [&'static str]> {
+                match entity {
+                    #(
+                    #entity_names => Some(#ruststep::tables::table_attr_names(&self.#table_names)),
+                    )*
+                    _ => None,
+                }
+            }
+            fn for_each_holder(
+                &self,
+                entity: &str,
+                f: &mut dyn FnMut(u64, &dyn #ruststep::tables::ReadAttribute),
+            ) {
+                match entity {
+                    #(
+                    #entity_names => #ruststep::tables::visit_table(&self.#table_names, f),
+                    )*
+                    _ => {}
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::TableIds for #ident {
+            fn contains_id(&self, id: u64) -> bool {
+                false #( || self.#table_names.contains_key(&id) )*
+            }
+            fn max_id(&self) -> Option<u64> {
+                let max = None;
+                #( let max = max.max(self.#table_names.keys().max().copied()); )*
+                max
+            }
+            #id_allocator
+            #register_id
+            fn for_each_reference(&self, f: &mut dyn FnMut(u64, &'static str, u64)) {
+                #( #ruststep::tables::table_references(&self.#table_names, f); )*
             }
         }
     }
@@ -88,30 +281,26 @@ fn tuple_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStrea
     quote! {
         #[automatically_derived]
         impl #ruststep::tables::TableInit for #ident {
-            fn append_data_section(
+            fn append_entity_instance(
                 &mut self,
-                data_sec: &#ruststep::ast::DataSection
+                instance: &#ruststep::ast::EntityInstance
             ) -> #ruststep::error::Result<()> {
                 use #ruststep::{error::Error, tables::insert_record, ast::EntityInstance};
-                for entity in &data_sec.entities {
-                    match entity {
-                        EntityInstance::Simple { id, record } => match record.name.as_str() {
-                            #(
-                            #entity_names => insert_record(&mut self.#table_names, *id, record)?,
-                            )*
-                            _ => {
-                                return Err(Error::UnknownEntityName {
-                                    entity_name: record.name.clone(),
-                                    schema: "".to_string(),
-                                });
-                            }
-                        },
-                        EntityInstance::Complex { .. } => {
-                            unimplemented!("Complex entity is not supported")
-                        }
-                    }
+                match instance {
+                    EntityInstance::Simple { id, record } => match record.name.as_str() {
+                        #(
+                        #entity_names if !record.user_defined => insert_record(&mut self.#table_names, *id, record),
+                        )*
+                        _ => Err(Error::UnknownEntityName {
+                            entity_name: record.keyword(),
+                            schema: "".to_string(),
+                        }),
+                    },
+                    EntityInstance::Complex { id, subsuper } => Err(Error::UnplacedComplexEntity {
+                        id: *id,
+                        keywords: subsuper.into_iter().map(|record| record.keyword()).collect(),
+                    }),
                 }
-                Ok(())
             }
         }
 
@@ -121,8 +310,11 @@ fn tuple_impl_table_init(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStrea
             fn from_str(input: &str) -> #ruststep::error::Result<Self> {
                 use #ruststep::{tables::TableInit, ast::DataSection};
                 let data_sec = DataSection::from_str(input)?;
-                Ok(Self::from_data_section(&data_sec)?)
+                Self::from_data_section(&data_sec)
             }
         }
+
+        #[automatically_derived]
+        impl #ruststep::tables::TableConstants for #ident {}
     }
 }
@@ -1,24 +1,37 @@
 use inflector::Inflector;
 use proc_macro2::{Span, TokenStream as TokenStream2};
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use std::convert::*;
+use syn::spanned::Spanned;
 
 use super::*;
 
 pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
     let name = ident.to_string().to_screaming_snake_case();
     let holder_ident = as_holder_ident(ident);
-    let def_holder_tt = def_holder(ident, st);
+    let def_holder_tt = def_holder(ident, st, attr);
     let impl_holder_tt = impl_holder(ident, attr, st);
     let impl_entity_table_tt = impl_entity_table(ident, attr);
+    let impl_into_holder_tt = if attr.generate_into_holder {
+        Some(impl_into_holder(ident, attr, st))
+    } else {
+        None
+    };
+    let impl_to_record_tt = if attr.generate_to_record {
+        Some(impl_to_record(ident, st))
+    } else {
+        None
+    };
     if attr.generate_deserialize {
-        let def_visitor_tt = def_visitor(&holder_ident, &name, st);
-        let impl_deserialize_tt = impl_deserialize(&holder_ident, &name, st);
+        let def_visitor_tt = def_visitor(&holder_ident, &name, &attr.aliases, st);
+        let impl_deserialize_tt = impl_deserialize(&holder_ident, &name, st, attr.generate_serde);
         let impl_with_visitor_tt = impl_with_visitor(ident);
         quote! {
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
             #def_visitor_tt
             #impl_deserialize_tt
             #impl_with_visitor_tt
@@ -28,17 +41,26 @@ pub fn derive_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr
             #def_holder_tt
             #impl_holder_tt
             #impl_entity_table_tt
+            #impl_into_holder_tt
+            #impl_to_record_tt
         }
     }
 }
 
-pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+pub fn def_holder(ident: &syn::Ident, st: &syn::DataStruct, attr: &HolderAttr) -> TokenStream2 {
     let holder_ident = as_holder_ident(ident);
     let FieldEntries { holder_types, .. } = FieldEntries::parse(st);
+    let body = quote! { (#(pub #holder_types),*); };
+    let def_serde_mirror_tt = if attr.generate_serde {
+        Some(def_serde_mirror(&holder_ident, quote! { struct }, &body))
+    } else {
+        None
+    };
     quote! {
         /// Auto-generated by `#[derive(Holder)]`
         #[derive(Debug, Clone, PartialEq)]
-        pub struct #holder_ident(#(pub #holder_types),*);
+        pub struct #holder_ident #body
+        #def_serde_mirror_tt
     }
 }
 
@@ -48,7 +70,18 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
     let FieldEntries {
         holder_types,
         into_owned,
+        references,
+        ..
     } = FieldEntries::parse(st);
+    let impl_for_each_reference = if references.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            fn for_each_reference(&self, f: &mut dyn FnMut(usize, u64)) {
+                #(#references)*
+            }
+        })
+    };
     let HolderAttr { table, .. } = table;
     let tuple_len = holder_types.len();
     let table_arg = table_arg();
@@ -71,6 +104,7 @@ pub fn impl_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct)
             fn attr_len() -> usize {
                 #tuple_len
             }
+            #impl_for_each_reference
         }
     } // quote!
 }
@@ -86,6 +120,9 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
             fn get_owned(&self, entity_id: u64) -> #ruststep::error::Result<#ident> {
                 #ruststep::tables::get_owned(self, &self.#field, entity_id)
             }
+            fn entity_ids(&self) -> Vec<u64> {
+                #ruststep::tables::entity_ids(&self.#field)
+            }
             fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = #ruststep::error::Result<#ident>> + 'table> {
                 #ruststep::tables::owned_iter(self, &self.#field)
             }
@@ -93,16 +130,110 @@ pub fn impl_entity_table(ident: &syn::Ident, table: &HolderAttr) -> TokenStream2
     }
 }
 
+/// Convert into the holder, which is kept in a place holder as is
+fn impl_into_holder(ident: &syn::Ident, table: &HolderAttr, st: &syn::DataStruct) -> TokenStream2 {
+    let holder_ident = as_holder_ident(ident);
+    let FieldEntries { into_holder, .. } = FieldEntries::parse(st);
+    let HolderAttr { table, .. } = table;
+    let table_arg = table_arg();
+    let ruststep = ruststep_crate();
+
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::tables::IntoHolder for #ident {
+            type Holder = #holder_ident;
+            #[allow(clippy::needless_question_mark)]
+            fn into_holder(self, #table_arg: &mut #table) -> #ruststep::error::Result<Self::Holder> {
+                Ok(#holder_ident ( #(#into_holder),* ))
+            }
+        }
+    }
+}
+
+/// Write the holder as a record, and as a typed parameter when it is a value of another record
+fn impl_to_record(ident: &syn::Ident, st: &syn::DataStruct) -> TokenStream2 {
+    let name = ident.to_string().to_screaming_snake_case();
+    let holder_ident = as_holder_ident(ident);
+    let ruststep = ruststep_crate();
+    let indices = (0..st.fields.len()).map(syn::Index::from);
+
+    quote! {
+        #[automatically_derived]
+        impl #ruststep::tables::ToRecord for #holder_ident {
+            fn to_record(&self) -> #ruststep::ast::Record {
+                #ruststep::ast::Record {
+                    name: #name.to_string(),
+                    user_defined: false,
+                    parameter: #ruststep::ast::Parameter::List(vec![
+                        #( #ruststep::tables::ToParameter::to_parameter(&self.#indices) ),*
+                    ]),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #ruststep::tables::ToParameter for #holder_ident {
+            fn to_parameter(&self) -> #ruststep::ast::Parameter {
+                let #ruststep::ast::Record { name, parameter, .. } =
+                    #ruststep::tables::ToRecord::to_record(self);
+                #ruststep::ast::Parameter::Typed {
+                    keyword: name,
+                    parameter: Box::new(parameter),
+                }
+            }
+        }
+    }
+}
+
 // `name` may be different from `ident`
 // because this will be used for both Entity struct and its `*Holder` struct.
-fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStream2 {
+// A record or typed parameter of `aliases`, given by `#[holder(alias = "...")]`, is also accepted.
+fn def_visitor(
+    ident: &syn::Ident,
+    name: &str,
+    aliases: &[syn::LitStr],
+    st: &syn::DataStruct,
+) -> TokenStream2 {
     let visitor_ident = as_visitor_ident(ident);
     let FieldEntries { holder_types, .. } = FieldEntries::parse(st);
     let attr_len = holder_types.len();
     let attributes = (0..attr_len)
         .map(|i| format_ident!("a_{}", i))
         .collect::<Vec<_>>();
+    // Report unsatisfied trait bounds of the field type on the field
+    let next_elements = holder_types
+        .iter()
+        .map(|ty| quote_spanned! {ty.span()=> seq.next_element() });
     let serde = serde_crate();
+    // A typed parameter, e.g. `LABEL('name')`, has a single value instead of a list of parameters
+    let visit_single_value = if attr_len == 1 {
+        Some(quote! {
+            fn visit_i64<E: #serde::de::Error>(self, v: i64) -> ::std::result::Result<Self::Value, E> {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::Deserialize::deserialize(v.into_deserializer())?))
+            }
+
+            fn visit_f64<E: #serde::de::Error>(self, v: f64) -> ::std::result::Result<Self::Value, E> {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::Deserialize::deserialize(v.into_deserializer())?))
+            }
+
+            fn visit_str<E: #serde::de::Error>(self, v: &str) -> ::std::result::Result<Self::Value, E> {
+                use #serde::de::IntoDeserializer;
+                Ok(#ident(#serde::Deserialize::deserialize(v.into_deserializer())?))
+            }
+
+            fn visit_enum<A>(self, data: A) -> ::std::result::Result<Self::Value, A::Error>
+            where
+                A: #serde::de::EnumAccess<'de>,
+            {
+                let value = #serde::de::value::EnumAccessDeserializer::new(data);
+                Ok(#ident(#serde::Deserialize::deserialize(value)?))
+            }
+        })
+    } else {
+        None
+    };
     quote! {
         #[doc(hidden)]
         pub struct #visitor_ident;
@@ -124,7 +255,7 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
                         return Err(A::Error::invalid_length(size, &self));
                     }
                 }
-                #( let #attributes = seq.next_element()?.unwrap(); )*
+                #( let #attributes = #next_elements?.unwrap(); )*
                 Ok(#ident ( #(#attributes),* ))
             }
 
@@ -136,24 +267,32 @@ fn def_visitor(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStr
                 let key: String = map
                     .next_key()?
                     .expect("Empty map cannot be accepted as ruststep Holder"); // this must be a bug, not runtime error
-                if key != #name {
+                if key != #name #( && key != #aliases )* {
                     use #serde::de::{Error, Unexpected};
                     return Err(A::Error::invalid_value(Unexpected::Other(&key), &self));
                 }
                 let value = map.next_value()?; // send to Self::visit_seq
                 Ok(value)
             }
+
+            #visit_single_value
         }
     } // quote!
 }
 
 // `name` may be different from `ident`
 // because this will be used for both Entity struct and its `*Holder` struct.
-fn impl_deserialize(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> TokenStream2 {
+fn impl_deserialize(
+    ident: &syn::Ident,
+    name: &str,
+    st: &syn::DataStruct,
+    generate_serde: bool,
+) -> TokenStream2 {
     let visitor_ident = as_visitor_ident(ident);
     let FieldEntries { holder_types, .. } = FieldEntries::parse(st);
     let attr_len = holder_types.len();
     let serde = serde_crate();
+    let deserialize_binary = deserialize_binary(ident, generate_serde);
     quote! {
         #[automatically_derived]
         impl<'de> #serde::de::Deserialize<'de> for #ident {
@@ -161,6 +300,7 @@ fn impl_deserialize(ident: &syn::Ident, name: &str, st: &syn::DataStruct) -> Tok
             where
                 D: #serde::de::Deserializer<'de>,
             {
+                #deserialize_binary
                 deserializer.deserialize_tuple_struct(#name, #attr_len, #visitor_ident {})
             }
         }
@@ -187,14 +327,20 @@ fn impl_with_visitor(ident: &syn::Ident) -> TokenStream2 {
 struct FieldEntries {
     holder_types: Vec<syn::Type>,
     into_owned: Vec<TokenStream2>,
+    into_holder: Vec<TokenStream2>,
+    /// Statements calling `f` with entity references in each place holder field
+    references: Vec<TokenStream2>,
 }
 
 impl FieldEntries {
     fn parse(st: &syn::DataStruct) -> Self {
         let table_arg = table_arg();
+        let ruststep = ruststep_crate();
 
         let mut holder_types = Vec::new();
         let mut into_owned = Vec::new();
+        let mut into_holder = Vec::new();
+        let mut references = Vec::new();
 
         for (i, field) in st.fields.iter().enumerate() {
             let ft: FieldType = field.ty.clone().try_into().unwrap();
@@ -202,13 +348,14 @@ impl FieldEntries {
 
             let HolderAttr { place_holder, .. } = HolderAttr::parse(&field.attrs);
             if place_holder {
+                into_holder.push(ft.place_holder_expr(quote! { self.#index }, &table_arg));
+                references.push(quote! {
+                    #ruststep::tables::References::references(&self.#index, &mut |id| f(#i, id));
+                });
                 match &ft {
-                    FieldType::Path(_) => {
+                    FieldType::Path(_) | FieldType::Optional(_) | FieldType::Array(..) => {
                         into_owned.push(quote! { self.#index.into_owned(#table_arg)? });
                     }
-                    FieldType::Optional(_) => {
-                        into_owned.push(quote! { self.#index.map(|holder| holder.into_owned(#table_arg)).transpose()? },);
-                    }
                     FieldType::List(_) => into_owned.push(quote! {
                         self.#index
                             .into_iter()
@@ -220,12 +367,15 @@ impl FieldEntries {
                 holder_types.push(ft.into_holder().into_place_holder().into());
             } else {
                 into_owned.push(quote! { self.#index });
+                into_holder.push(quote! { self.#index });
                 holder_types.push(ft.into());
             }
         }
         FieldEntries {
             holder_types,
             into_owned,
+            into_holder,
+            references,
         }
     }
 }
@@ -0,0 +1,13 @@
+use ruststep_derive::Holder;
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(generate_deserialize)]
+#[holder(generate_to_record)]
+pub enum SiPrefix {
+    Kilo,
+    Milli,
+    #[holder(rename = "MICRO")]
+    Mu,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use ruststep::tables::Constants;
+use ruststep_derive::{as_holder, Holder, TableInit};
+use std::collections::HashMap;
+
+#[derive(Default, TableInit)]
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+    #[table_init(constants)]
+    constants: Constants,
+    #[table_init(constants)]
+    more_constants: Constants,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    pub x: f64,
+}
+
+fn main() {}
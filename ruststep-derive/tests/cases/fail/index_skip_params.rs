@@ -0,0 +1,18 @@
+use ruststep_derive::{as_holder, Holder};
+use std::collections::HashMap;
+
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    #[holder(index = 1)]
+    #[holder(skip_params = 1)]
+    pub x: f64,
+}
+
+fn main() {}
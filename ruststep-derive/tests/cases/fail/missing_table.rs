@@ -0,0 +1,10 @@
+use ruststep_derive::Holder;
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    pub x: f64,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use ruststep_derive::Holder;
+
+pub struct Table;
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(generate_deserialize)]
+pub enum S {
+    P(f64),
+    Unknown,
+}
+
+fn main() {}
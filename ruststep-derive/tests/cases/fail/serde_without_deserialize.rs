@@ -0,0 +1,13 @@
+use ruststep_derive::Holder;
+
+pub struct Tables;
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = a)]
+#[holder(generate_serde)]
+pub struct A {
+    pub x: f64,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use ruststep_derive::{as_holder, Holder};
+use std::collections::HashMap;
+
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    pub x: f64,
+    #[holder(skip)]
+    pub y: f64,
+    #[holder(index = 1)]
+    pub z: f64,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use ruststep_derive::Holder;
+
+pub struct Table;
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(generate_deserialize)]
+pub enum S {
+    Point(f64, f64),
+}
+
+fn main() {}
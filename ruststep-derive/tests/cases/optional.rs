@@ -7,6 +7,8 @@ pub struct Table {
     c: HashMap<u64, as_holder!(C)>,
 }
 
+impl ruststep::tables::TableConstants for Table {}
+
 #[derive(Debug, Clone, PartialEq, Holder)]
 #[holder(table = Table)]
 #[holder(field = a)]
@@ -0,0 +1,24 @@
+use ruststep::ast::Parameter;
+use ruststep_derive::{as_holder, Holder};
+use std::collections::HashMap;
+
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    #[holder(index = 2)]
+    pub x: f64,
+    #[holder(index = 0)]
+    pub y: f64,
+    #[holder(skip)]
+    pub z: Option<f64>,
+    #[holder(rest)]
+    pub rest: Vec<Parameter>,
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+use ruststep_derive::{as_holder, Holder};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+    b: HashMap<u64, as_holder!(B)>,
+}
+
+impl ruststep::tables::TableConstants for Table {}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+pub struct A {
+    pub r#type: String,
+    #[holder(rename = "ref")]
+    pub reference: String,
+    #[holder(skip_params = 2)]
+    pub x: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = b)]
+#[holder(generate_deserialize)]
+pub struct B {
+    #[holder(use_place_holder)]
+    #[holder(rename = "base")]
+    pub a: A,
+    #[holder(skip)]
+    #[holder(rest)]
+    pub ignored: (),
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use ruststep_derive::{as_holder, Holder};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    a: HashMap<u64, as_holder!(A)>,
+}
+
+impl ruststep::tables::TableConstants for Table {}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(field = a)]
+#[holder(generate_deserialize)]
+#[holder(generate_to_record)]
+pub struct A {
+    pub x: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Table)]
+#[holder(generate_deserialize)]
+#[holder(generate_to_record)]
+pub enum S {
+    #[holder(use_place_holder)]
+    A(Box<A>),
+    Circle {
+        #[holder(use_place_holder)]
+        center: A,
+        radius: f64,
+    },
+    #[holder(rename = "RECTANGLE_2D")]
+    Rectangle {
+        width: f64,
+        height: Option<f64>,
+    },
+}
+
+fn main() {}
@@ -7,6 +7,8 @@ pub struct Table {
     b: HashMap<u64, as_holder!(B)>,
 }
 
+impl ruststep::tables::TableConstants for Table {}
+
 #[derive(Debug, Clone, PartialEq, ::serde::Deserialize)]
 pub struct Simple(pub f64);
 
@@ -6,6 +6,8 @@ pub struct Table {
     b: HashMap<u64, as_holder!(B)>,
 }
 
+impl ruststep::tables::TableConstants for Table {}
+
 #[derive(Debug, Clone, PartialEq, Holder)]
 #[holder(table = Table)]
 #[holder(field = a)]
@@ -6,4 +6,9 @@ fn try_build() {
     t.pass("tests/cases/vec.rs");
     t.pass("tests/cases/select.rs");
     t.pass("tests/cases/tuple.rs");
+    t.pass("tests/cases/positions.rs");
+    t.pass("tests/cases/enumeration.rs");
+    t.pass("tests/cases/struct_variant.rs");
+    t.pass("tests/cases/rename.rs");
+    t.compile_fail("tests/cases/fail/*.rs");
 }
@@ -0,0 +1,34 @@
+//! Parsing into the borrowed AST by `parse_borrowed` and the owned AST by `parse`
+//!
+//! Run by `cargo bench -p ruststep --bench borrowed`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruststep::parser;
+use std::{fs, path::PathBuf};
+
+const FILES: [&str; 2] = [
+    "00000050_80d90bfdd2e74e709956122a_step_000.step",
+    "database.p21",
+];
+
+fn read(name: &str) -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps")
+        .join(name);
+    fs::read_to_string(step_file).unwrap()
+}
+
+fn borrowed(c: &mut Criterion) {
+    for name in FILES {
+        let step = read(name);
+        let mut group = c.benchmark_group(name);
+        group.bench_function("owned", |b| b.iter(|| parser::parse(&step).unwrap()));
+        group.bench_function("borrowed", |b| {
+            b.iter(|| parser::parse_borrowed(&step).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, borrowed);
+criterion_main!(benches);
@@ -0,0 +1,152 @@
+//! Resolving a graph whose entities are shared by many others, with and without cache,
+//! and a long chain of references
+//!
+//! Run by `cargo bench -p ruststep --bench resolve`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+
+      ENTITY edge;
+        edge_start: vertex_point;
+        edge_end: vertex_point;
+      END_ENTITY;
+
+      ENTITY face;
+        bounds: LIST [1:?] OF edge;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA chain_schema;
+      ENTITY node SUPERTYPE OF (link);
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY link SUBTYPE OF (node);
+        next: node;
+        other: node;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const POINTS: u64 = 10;
+const VERTICES: u64 = 1_000;
+const EDGES: u64 = 5_000;
+const FACES: u64 = 500;
+const EDGES_PER_FACE: u64 = 20;
+
+/// Each layer refers entities of the previous layer in a round-robin manner
+fn shared_graph() -> Tables {
+    let mut step = String::from("DATA;\n");
+    let point = |k: u64| 1 + k % POINTS;
+    let vertex = |k: u64| 1 + POINTS + k % VERTICES;
+    let edge = |k: u64| 1 + POINTS + VERTICES + k % EDGES;
+    for k in 0..POINTS {
+        step.push_str(&format!(
+            "#{} = CARTESIAN_POINT(({k}.0, 0.0, 0.0));\n",
+            point(k)
+        ));
+    }
+    for k in 0..VERTICES {
+        step.push_str(&format!("#{} = VERTEX_POINT(#{});\n", vertex(k), point(k)));
+    }
+    for k in 0..EDGES {
+        step.push_str(&format!(
+            "#{} = EDGE(#{}, #{});\n",
+            edge(k),
+            vertex(k),
+            vertex(k + 1)
+        ));
+    }
+    for k in 0..FACES {
+        let bounds: Vec<String> = (0..EDGES_PER_FACE)
+            .map(|i| format!("#{}", edge(k * 7 + i)))
+            .collect();
+        step.push_str(&format!(
+            "#{} = FACE(({}));\n",
+            1 + POINTS + VERTICES + EDGES + k,
+            bounds.join(", ")
+        ));
+    }
+    step.push_str("ENDSEC;\n");
+    Tables::from_data_section(&DataSection::from_str(&step).unwrap()).unwrap()
+}
+
+fn resolve(c: &mut Criterion) {
+    let tables = shared_graph();
+    let ids: Vec<u64> = tables.face_holders().keys().copied().collect();
+
+    let mut group = c.benchmark_group("resolve_shared_graph");
+    group.bench_function("cold", |b| {
+        b.iter(|| {
+            for id in &ids {
+                EntityTable::<FaceHolder>::get_owned(&tables, *id).unwrap();
+            }
+        })
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            for face in EntityTable::<FaceHolder>::owned_iter(&tables) {
+                face.unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+/// `#1` is a node, and `#k` is a link to `#k-1` and `#1`
+fn chain(len: u64) -> chain_schema::Tables {
+    let mut step = String::from("DATA;\n#1 = NODE('0');\n");
+    for k in 2..=len {
+        step.push_str(&format!("#{k} = LINK(NODE(('{k}')), #{}, #1);\n", k - 1));
+    }
+    step.push_str("ENDSEC;\n");
+    chain_schema::Tables::from_data_section(&DataSection::from_str(&step).unwrap()).unwrap()
+}
+
+/// Number of links following `next`. Consumes the value to drop it without recursion.
+fn length(mut node: chain_schema::NodeAny) -> usize {
+    let mut len = 0;
+    while let chain_schema::NodeAny::Link(link) = node {
+        len += 1;
+        node = link.next;
+    }
+    len
+}
+
+fn resolve_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resolve_chain");
+    group.sample_size(10);
+    for len in [12_500, 25_000, 50_000] {
+        let tables = chain(len);
+        group.bench_function(len.to_string(), |b| {
+            b.iter(|| {
+                let head =
+                    EntityTable::<chain_schema::NodeAnyHolder>::get_owned(&tables, len).unwrap();
+                length(head)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, resolve, resolve_chain);
+criterion_main!(benches);
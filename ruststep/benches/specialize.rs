@@ -0,0 +1,43 @@
+//! Parsing a large exchange structure by the generic parser and the specialized parsers,
+//! see `ParseOptions::specialize_keywords`
+//!
+//! Run by `cargo bench -p ruststep --bench specialize`
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ruststep::parser::{parse_with, ParseOptions, SpecializedKeywords};
+use std::{fs, path::PathBuf};
+
+/// Geometry example with its DATA section repeated to make the measurement stable
+fn large_geometry() -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    let step = fs::read_to_string(step_file).unwrap();
+    let begin = step.find("DATA;").unwrap() + "DATA;".len();
+    let end = step.find("ENDSEC;\nEND-ISO").unwrap();
+    let data = &step[begin..end];
+    let mut large = step[..begin].to_string();
+    for k in 0..100 {
+        large.push_str(&data.replace('#', &format!("#{}", k + 1)));
+    }
+    large.push_str(&step[end..]);
+    large
+}
+
+fn specialize(c: &mut Criterion) {
+    let step = large_geometry();
+    let mut group = c.benchmark_group("parse_geometry");
+    group.sample_size(10);
+    for (label, options) in [
+        ("generic", ParseOptions::strict()),
+        (
+            "specialized",
+            ParseOptions::strict().specialize_keywords(&SpecializedKeywords::SUPPORTED),
+        ),
+    ] {
+        group.bench_function(label, |b| b.iter(|| parse_with(&step, &options).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, specialize);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+//! Recipes for common tasks using ruststep
+//!
+//! Each recipe is a small function built on the public API, and tested by `cargo test --example cookbook`.
+//! `cargo run --example cookbook` runs all recipes for [EXAMPLE].
+
+use ruststep::{
+    ast::{Exchange, Parameter},
+    error::Result,
+    load::{load, LoadOptions},
+    parser::{self, read_instances},
+    tables::EntityTable,
+};
+use std::{collections::BTreeMap, io::BufRead, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA cookbook;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        name: STRING;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+
+      ENTITY edge;
+        name: STRING;
+        edge_start: vertex_point;
+        edge_end: vertex_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use cookbook::*;
+
+pub const EXAMPLE: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('cookbook example'),'2;1');
+FILE_NAME('example.stp','2024-01-01T00:00:00',(''),(''),'','','');
+FILE_SCHEMA(('COOKBOOK'));
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('origin',(0.0,0.0,0.0));
+#2 = CARTESIAN_POINT('x',(1.0,0.0,0.0));
+#3 = CARTESIAN_POINT('y',(0.0,1.0,0.0));
+#11 = VERTEX_POINT('v1',#1);
+#12 = VERTEX_POINT('v2',#2);
+#13 = VERTEX_POINT('v3',#3);
+#21 = EDGE('e1',#11,#12);
+#22 = EDGE('e2',#11,#13);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+/// 1. Read a file and print an attribute of every instance of a keyword
+pub fn print_attribute(step: &str, keyword: &str, index: usize) -> Result<Vec<String>> {
+    let exchange = parser::parse(step)?;
+    let mut lines = Vec::new();
+    for instance in exchange.data.iter().flat_map(|section| &section.entities) {
+        for record in instance.records().iter().filter(|r| r.name == keyword) {
+            if let Some(value) = record.attribute(index) {
+                lines.push(format!("#{} {}", instance.id(), value));
+            }
+        }
+    }
+    for line in &lines {
+        println!("{}", line);
+    }
+    Ok(lines)
+}
+
+/// 2. Load into generated `Tables`, and iterate owned entities
+pub fn edge_lengths(step: &str) -> Result<Vec<(String, f64)>> {
+    let tables: Tables = load(step, LoadOptions::new())?;
+    EntityTable::<EdgeHolder>::owned_iter(&tables)
+        .map(|edge| {
+            let edge = edge?;
+            let start = &edge.edge_start.vertex_geometry.coordinates;
+            let end = &edge.edge_end.vertex_geometry.coordinates;
+            let length = start
+                .iter()
+                .zip(end)
+                .map(|(s, e)| (e - s).powi(2))
+                .sum::<f64>()
+                .sqrt();
+            Ok((edge.name, length))
+        })
+        .collect()
+}
+
+/// 3. Modify an attribute, and write the file back
+pub fn rename(step: &str, id: u64, name: &str) -> Result<String> {
+    let mut exchange = Exchange::from_str(step)?;
+    for section in &mut exchange.data {
+        if let Some(record) = section.instance_mut(id).and_then(|i| i.record_mut()) {
+            if let Some(attribute) = record.attribute_mut(0) {
+                *attribute = Parameter::String(name.to_string());
+            }
+        }
+    }
+    Ok(exchange.to_string())
+}
+
+/// 4. Extract instances referred from `root` into a new file
+pub fn extract(step: &str, root: u64) -> Result<String> {
+    let mut exchange = Exchange::from_str(step)?;
+    for section in &mut exchange.data {
+        *section = section.extract(&[root]);
+    }
+    Ok(exchange.to_string())
+}
+
+/// 5. Count entity instances for each keyword without loading the entire file
+pub fn count_keywords(reader: impl BufRead) -> Result<BTreeMap<String, usize>> {
+    let mut counts = BTreeMap::new();
+    for instance in read_instances(reader) {
+        for record in instance?.records() {
+            *counts.entry(record.name.clone()).or_default() += 1;
+        }
+    }
+    Ok(counts)
+}
+
+fn main() -> Result<()> {
+    print_attribute(EXAMPLE, "CARTESIAN_POINT", 1)?;
+    for (name, length) in edge_lengths(EXAMPLE)? {
+        println!("{}: {}", name, length);
+    }
+    print!("{}", rename(EXAMPLE, 1, "O")?);
+    print!("{}", extract(EXAMPLE, 21)?);
+    println!("{:?}", count_keywords(EXAMPLE.as_bytes())?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruststep::ast::DataSection;
+    use std::io::Cursor;
+
+    #[test]
+    fn print_attribute() {
+        let lines = super::print_attribute(EXAMPLE, "VERTEX_POINT", 1).unwrap();
+        assert_eq!(lines, ["#11 #1", "#12 #2", "#13 #3"]);
+    }
+
+    #[test]
+    fn edge_lengths() {
+        let mut lengths = super::edge_lengths(EXAMPLE).unwrap();
+        lengths.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(lengths, [("e1".to_string(), 1.0), ("e2".to_string(), 1.0)]);
+    }
+
+    #[test]
+    fn rename() {
+        let output = super::rename(EXAMPLE, 1, "O").unwrap();
+        assert!(output.contains("#1 = CARTESIAN_POINT('O',(0.0,0.0,0.0));"));
+        // Other lines are kept as is
+        assert_eq!(output, EXAMPLE.replace("'origin'", "'O'"));
+    }
+
+    #[test]
+    fn extract() {
+        let output = super::extract(EXAMPLE, 21).unwrap();
+        let exchange = Exchange::from_str(&output).unwrap();
+        let ids: Vec<u64> = exchange.data[0].entities.iter().map(|e| e.id()).collect();
+        assert_eq!(ids, [1, 2, 11, 12, 21]);
+        // Extracted file can be loaded
+        let tables: Tables = load(&output, LoadOptions::new()).unwrap();
+        assert_eq!(EntityTable::<EdgeHolder>::owned_iter(&tables).count(), 1);
+    }
+
+    #[test]
+    fn count_keywords() {
+        let counts = super::count_keywords(Cursor::new(EXAMPLE)).unwrap();
+        let expected = DataSection::from_str(
+            &EXAMPLE[EXAMPLE.find("DATA;").unwrap()..EXAMPLE.find("END-ISO").unwrap()],
+        )
+        .unwrap()
+        .summary()
+        .keyword_counts;
+        assert_eq!(counts, expected);
+        assert_eq!(counts["CARTESIAN_POINT"], 3);
+    }
+}
@@ -0,0 +1,695 @@
+//! Product structure of assemblies
+//!
+//! Assemblies in AP203, AP214, and AP242 are described by following entities:
+//!
+//! ```text
+//! PRODUCT <- PRODUCT_DEFINITION_FORMATION <- PRODUCT_DEFINITION
+//!
+//! PRODUCT_DEFINITION (parent) <- NEXT_ASSEMBLY_USAGE_OCCURRENCE -> PRODUCT_DEFINITION (child)
+//!                                  ^
+//!                                  PRODUCT_DEFINITION_SHAPE
+//!                                  ^
+//!                                  CONTEXT_DEPENDENT_SHAPE_REPRESENTATION
+//!                                  |
+//!                                  v
+//! (REPRESENTATION_RELATIONSHIP REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION ...)
+//!                                  |
+//!                                  v
+//! ITEM_DEFINED_TRANSFORMATION -> AXIS2_PLACEMENT_3D x 2
+//! ```
+//!
+//! [build_tree] walks them to build an [AssemblyTree] with the placement of each child.
+//! The relationship with transformation is usually written as a complex entity instance,
+//! which cannot be stored in `Tables` generated by espr.
+//! Thus this module reads [DataSection]s directly,
+//! and works for any schema sharing these entities.
+//!
+//! ```
+//! use ruststep::{assembly, ast::DataSection};
+//! use std::str::FromStr;
+//!
+//! let data = DataSection::from_str(r#"
+//! DATA;
+//!   #1 = PRODUCT('A', 'assembly', '', ());
+//!   #2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+//!   #3 = PRODUCT_DEFINITION('design', '', #2, #100);
+//!   #4 = PRODUCT('P', 'part', '', ());
+//!   #5 = PRODUCT_DEFINITION_FORMATION('', '', #4);
+//!   #6 = PRODUCT_DEFINITION('design', '', #5, #100);
+//!   #7 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO1', 'P-1', '', #3, #6, $);
+//!   #100 = PRODUCT_DEFINITION_CONTEXT('part definition', #101, 'design');
+//!   #101 = APPLICATION_CONTEXT('');
+//! ENDSEC;
+//! "#).unwrap();
+//!
+//! let tree = assembly::build_tree(&[data]).unwrap();
+//! assert_eq!(tree.roots.len(), 1);
+//! assert_eq!(tree.roots[0].product_name, "assembly");
+//!
+//! let part = tree.find(&["P-1"]).unwrap();
+//! assert_eq!(part.product_id, "P");
+//! ```
+
+use crate::{
+    ast::*,
+    error::{Error, Result},
+};
+use std::collections::{HashMap, HashSet};
+
+/// Rigid transformation mapping coordinates of a child into its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// Rotation matrix, whose columns are the axes of the child in the parent
+    pub rotation: [[f64; 3]; 3],
+    pub translation: [f64; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            translation: [0.0; 3],
+        }
+    }
+
+    /// Apply this transformation to a point
+    pub fn apply(&self, p: [f64; 3]) -> [f64; 3] {
+        let r = &self.rotation;
+        let t = &self.translation;
+        [
+            r[0][0] * p[0] + r[0][1] * p[1] + r[0][2] * p[2] + t[0],
+            r[1][0] * p[0] + r[1][1] * p[1] + r[1][2] * p[2] + t[1],
+            r[2][0] * p[0] + r[2][1] * p[1] + r[2][2] * p[2] + t[2],
+        ]
+    }
+
+    /// `self * other`, i.e. apply `other` first, and then `self`
+    pub fn compose(&self, other: &Transform) -> Transform {
+        let mut rotation = [[0.0; 3]; 3];
+        for (i, row) in rotation.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3)
+                    .map(|k| self.rotation[i][k] * other.rotation[k][j])
+                    .sum();
+            }
+        }
+        let t = self.apply(other.translation);
+        Transform {
+            rotation,
+            translation: t,
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        let r = &self.rotation;
+        // Rotation matrix is orthogonal
+        let rotation = [
+            [r[0][0], r[1][0], r[2][0]],
+            [r[0][1], r[1][1], r[2][1]],
+            [r[0][2], r[1][2], r[2][2]],
+        ];
+        let t = &self.translation;
+        let translation = [
+            -(rotation[0][0] * t[0] + rotation[0][1] * t[1] + rotation[0][2] * t[2]),
+            -(rotation[1][0] * t[0] + rotation[1][1] * t[1] + rotation[1][2] * t[2]),
+            -(rotation[2][0] * t[0] + rotation[2][1] * t[1] + rotation[2][2] * t[2]),
+        ];
+        Transform {
+            rotation,
+            translation,
+        }
+    }
+}
+
+/// Node of [AssemblyTree] corresponding to a `PRODUCT_DEFINITION`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyNode {
+    /// `id` attribute of `PRODUCT`
+    pub product_id: String,
+    /// `name` attribute of `PRODUCT`
+    pub product_name: String,
+    /// Entity instance name of `PRODUCT_DEFINITION`
+    pub definition: u64,
+    pub children: Vec<AssemblyChild>,
+}
+
+/// Usage of a child node in its parent, i.e. `NEXT_ASSEMBLY_USAGE_OCCURRENCE`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyChild {
+    /// Entity instance name of `NEXT_ASSEMBLY_USAGE_OCCURRENCE`
+    pub occurrence: u64,
+    /// `id` attribute of the occurrence
+    pub occurrence_id: String,
+    /// `name` attribute of the occurrence
+    pub occurrence_name: String,
+    /// Placement of the child in the parent.
+    /// `None` if the occurrence does not have a shape representation with transformation.
+    pub transform: Option<Transform>,
+    pub node: AssemblyNode,
+}
+
+/// Product structure built by [build_tree]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AssemblyTree {
+    /// Products not used in any other products
+    pub roots: Vec<AssemblyNode>,
+}
+
+/// Node of flattened [AssemblyTree] with accumulated transformation
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyInstance<'tree> {
+    /// Occurrence names from a root to this node
+    pub path: Vec<&'tree str>,
+    pub node: &'tree AssemblyNode,
+    /// Placement of this node in the root.
+    /// Missing transformations are regarded as identity.
+    pub transform: Transform,
+}
+
+impl AssemblyTree {
+    /// Iterate all nodes in depth-first order
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            stack: self.roots.iter().rev().collect(),
+        }
+    }
+
+    /// Lookup a node by occurrence names from a root,
+    /// where each name matches `name` or `id` of [AssemblyChild]
+    pub fn find(&self, path: &[&str]) -> Option<&AssemblyNode> {
+        self.roots.iter().find_map(|root| {
+            path.iter().try_fold(root, |node, name| {
+                node.children
+                    .iter()
+                    .find(|child| child.occurrence_name == *name || child.occurrence_id == *name)
+                    .map(|child| &child.node)
+            })
+        })
+    }
+
+    /// All nodes with placements accumulated from their roots, in depth-first order
+    pub fn flatten(&self) -> Vec<AssemblyInstance<'_>> {
+        fn visit<'tree>(
+            node: &'tree AssemblyNode,
+            path: Vec<&'tree str>,
+            transform: Transform,
+            out: &mut Vec<AssemblyInstance<'tree>>,
+        ) {
+            out.push(AssemblyInstance {
+                path: path.clone(),
+                node,
+                transform,
+            });
+            for child in &node.children {
+                let mut path = path.clone();
+                path.push(&child.occurrence_name);
+                let transform = transform.compose(&child.transform.unwrap_or_default());
+                visit(&child.node, path, transform, out);
+            }
+        }
+        let mut out = Vec::new();
+        for root in &self.roots {
+            visit(root, Vec::new(), Transform::identity(), &mut out);
+        }
+        out
+    }
+}
+
+/// Depth-first iterator over [AssemblyNode]s
+pub struct Iter<'tree> {
+    stack: Vec<&'tree AssemblyNode>,
+}
+
+impl<'tree> Iterator for Iter<'tree> {
+    type Item = &'tree AssemblyNode;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack
+            .extend(node.children.iter().rev().map(|child| &child.node));
+        Some(node)
+    }
+}
+
+/// Policy for products having multiple `PRODUCT_DEFINITION`s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DefinitionSelection {
+    /// Use the definition referred by each occurrence,
+    /// and the first one in entity instance name for roots
+    #[default]
+    AsReferenced,
+    /// Use the definition having most child occurrences among definitions of the same product,
+    /// since some exporters write children into a definition different from the referred one
+    MostChildren,
+}
+
+/// Options for [build_tree_with]
+#[derive(Debug, Clone, Default)]
+pub struct AssemblyOptions {
+    pub definition_selection: DefinitionSelection,
+}
+
+/// Build [AssemblyTree] from data sections with default options
+pub fn build_tree(data: &[DataSection]) -> Result<AssemblyTree> {
+    build_tree_with(data, &AssemblyOptions::default())
+}
+
+/// Build [AssemblyTree] from data sections
+///
+/// Error
+/// ------
+/// - [Error::UnknownEntity] if an entity referred from the product structure is not found
+/// - [Error::DeserializeFailed] if a record does not have expected parameters
+/// - [Error::CyclicAssembly] if a product contains itself
+///
+pub fn build_tree_with(data: &[DataSection], options: &AssemblyOptions) -> Result<AssemblyTree> {
+    let index = Index::new(data);
+    let structure = Structure::new(&index)?;
+    structure.build(options)
+}
+
+const PRODUCT_DEFINITION_FORMATION: &[&str] = &[
+    "PRODUCT_DEFINITION_FORMATION",
+    "PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE",
+];
+const PRODUCT_DEFINITION: &[&str] = &[
+    "PRODUCT_DEFINITION",
+    "PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS",
+];
+
+// Records of entity instances, where a complex instance has multiple partial records
+struct Index<'data> {
+    records: HashMap<u64, Vec<&'data Record>>,
+}
+
+impl<'data> Index<'data> {
+    fn new(data: &'data [DataSection]) -> Self {
+        let mut records = HashMap::new();
+        for instance in data.iter().flat_map(|section| &section.entities) {
+            let partial = match instance {
+                EntityInstance::Simple { record, .. } => vec![record],
+                EntityInstance::Complex { subsuper, .. } => subsuper.0.iter().collect(),
+            };
+            records.insert(instance.id(), partial);
+        }
+        Index { records }
+    }
+
+    fn iter<'a>(&'a self, keywords: &'a [&str]) -> impl Iterator<Item = (u64, &'data Record)> + 'a {
+        self.records.iter().flat_map(move |(id, records)| {
+            records
+                .iter()
+                .filter(move |record| keywords.contains(&record.name.as_str()))
+                .map(move |record| (*id, *record))
+        })
+    }
+
+    /// Partial record of the entity instance, `Ok(None)` if the instance does not have `keywords`
+    fn get(&self, id: u64, keywords: &[&str]) -> Result<Option<&'data Record>> {
+        let records = self.records.get(&id).ok_or(Error::UnknownEntity(id))?;
+        Ok(records
+            .iter()
+            .find(|record| keywords.contains(&record.name.as_str()))
+            .copied())
+    }
+
+    /// Like [Index::get], but the instance must have `keywords`
+    fn expect(&self, id: u64, keywords: &[&str]) -> Result<&'data Record> {
+        self.get(id, keywords)?.ok_or_else(|| {
+            Error::DeserializeFailed(format!("#{} is not {}", id, keywords.join(" or ")))
+        })
+    }
+}
+
+fn parameter(record: &Record, index: usize) -> Result<&Parameter> {
+    record.attribute(index).ok_or_else(|| {
+        Error::DeserializeFailed(format!(
+            "{} does not have parameter at {}",
+            record.name, index
+        ))
+    })
+}
+
+// Treat `$` as an empty string
+fn string(record: &Record, index: usize) -> Result<String> {
+    match parameter(record, index)? {
+        Parameter::String(s) => Ok(s.clone()),
+        Parameter::NotProvided => Ok(String::new()),
+        p => Err(Error::DeserializeFailed(format!(
+            "Expected string for {} at {}, but found {:?}",
+            record.name, index, p
+        ))),
+    }
+}
+
+fn reference(record: &Record, index: usize) -> Result<Option<u64>> {
+    match parameter(record, index)? {
+        Parameter::Ref(Name::Entity(id)) => Ok(Some(*id)),
+        Parameter::NotProvided | Parameter::Omitted => Ok(None),
+        p => Err(Error::DeserializeFailed(format!(
+            "Expected reference for {} at {}, but found {:?}",
+            record.name, index, p
+        ))),
+    }
+}
+
+fn required_reference(record: &Record, index: usize) -> Result<u64> {
+    reference(record, index)?.ok_or_else(|| {
+        Error::DeserializeFailed(format!("{} at {} is not provided", record.name, index))
+    })
+}
+
+fn real(p: &Parameter) -> Result<f64> {
+    match p {
+        Parameter::Real { value, .. } => Ok(*value),
+        Parameter::Integer(i) => Ok(*i as f64),
+        Parameter::Typed { parameter, .. } => real(parameter),
+        p => Err(Error::DeserializeFailed(format!(
+            "Expected real, but found {:?}",
+            p
+        ))),
+    }
+}
+
+fn vector(record: &Record, index: usize) -> Result<[f64; 3]> {
+    let mut v = [0.0; 3];
+    match parameter(record, index)? {
+        Parameter::List(components) if (2..=3).contains(&components.len()) => {
+            for (v, p) in v.iter_mut().zip(components) {
+                *v = real(p)?;
+            }
+            Ok(v)
+        }
+        p => Err(Error::DeserializeFailed(format!(
+            "Expected 2D or 3D coordinates for {} at {}, but found {:?}",
+            record.name, index, p
+        ))),
+    }
+}
+
+fn normalize(v: [f64; 3]) -> Option<[f64; 3]> {
+    let norm = v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if norm > f64::EPSILON {
+        Some([v[0] / norm, v[1] / norm, v[2] / norm])
+    } else {
+        None
+    }
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+// Parent-child relations and placements read from data sections
+struct Structure<'data> {
+    index: &'data Index<'data>,
+    /// product definition -> product
+    definitions: HashMap<u64, Product>,
+    /// entity instance name of product -> product definitions,
+    /// where the `id` attribute of `PRODUCT` is not unique in files of some exporters
+    definitions_of_product: HashMap<u64, Vec<u64>>,
+    /// parent product definition -> occurrences
+    occurrences: HashMap<u64, Vec<Occurrence>>,
+    /// product definition or occurrence -> shape representation
+    shape_representations: HashMap<u64, u64>,
+    /// occurrence -> representation relationship
+    relationships: HashMap<u64, u64>,
+}
+
+struct Product {
+    /// Entity instance name of `PRODUCT`
+    instance: u64,
+    id: String,
+    name: String,
+}
+
+struct Occurrence {
+    id: u64,
+    occurrence_id: String,
+    name: String,
+    child: u64,
+}
+
+impl<'data> Structure<'data> {
+    fn new(index: &'data Index<'data>) -> Result<Self> {
+        let mut definitions = HashMap::new();
+        let mut definitions_of_product: HashMap<u64, Vec<u64>> = HashMap::new();
+        for (id, record) in index.iter(PRODUCT_DEFINITION) {
+            let formation =
+                index.expect(required_reference(record, 2)?, PRODUCT_DEFINITION_FORMATION)?;
+            let instance = required_reference(formation, 2)?;
+            let product = index.expect(instance, &["PRODUCT"])?;
+            definitions_of_product.entry(instance).or_default().push(id);
+            definitions.insert(
+                id,
+                Product {
+                    instance,
+                    id: string(product, 0)?,
+                    name: string(product, 1)?,
+                },
+            );
+        }
+        for ids in definitions_of_product.values_mut() {
+            ids.sort_unstable();
+        }
+
+        let mut occurrences: HashMap<u64, Vec<Occurrence>> = HashMap::new();
+        for (id, record) in index.iter(&["NEXT_ASSEMBLY_USAGE_OCCURRENCE"]) {
+            let parent = required_reference(record, 3)?;
+            let child = required_reference(record, 4)?;
+            for definition in [parent, child] {
+                if !definitions.contains_key(&definition) {
+                    index.expect(definition, PRODUCT_DEFINITION)?;
+                }
+            }
+            occurrences.entry(parent).or_default().push(Occurrence {
+                id,
+                occurrence_id: string(record, 0)?,
+                name: string(record, 1)?,
+                child,
+            });
+        }
+        for children in occurrences.values_mut() {
+            children.sort_unstable_by_key(|occurrence| occurrence.id);
+        }
+
+        // PRODUCT_DEFINITION_SHAPE -> product definition or occurrence
+        let mut shape_definitions = HashMap::new();
+        for (id, record) in index.iter(&["PRODUCT_DEFINITION_SHAPE"]) {
+            if let Some(definition) = reference(record, 2)? {
+                shape_definitions.insert(id, definition);
+            }
+        }
+
+        let mut shape_representations = HashMap::new();
+        for (_id, record) in index.iter(&["SHAPE_DEFINITION_REPRESENTATION"]) {
+            let shape = required_reference(record, 0)?;
+            if let Some(definition) = shape_definitions.get(&shape) {
+                shape_representations.insert(*definition, required_reference(record, 1)?);
+            }
+        }
+
+        let mut relationships = HashMap::new();
+        for (_id, record) in index.iter(&["CONTEXT_DEPENDENT_SHAPE_REPRESENTATION"]) {
+            let shape = required_reference(record, 1)?;
+            if let Some(occurrence) = shape_definitions.get(&shape) {
+                relationships.insert(*occurrence, required_reference(record, 0)?);
+            }
+        }
+
+        Ok(Structure {
+            index,
+            definitions,
+            definitions_of_product,
+            occurrences,
+            shape_representations,
+            relationships,
+        })
+    }
+
+    fn num_children(&self, definition: u64) -> usize {
+        self.occurrences.get(&definition).map_or(0, Vec::len)
+    }
+
+    fn select(&self, definition: u64, options: &AssemblyOptions) -> u64 {
+        match options.definition_selection {
+            DefinitionSelection::AsReferenced => definition,
+            DefinitionSelection::MostChildren => {
+                let product = self.definitions[&definition].instance;
+                self.definitions_of_product[&product]
+                    .iter()
+                    .copied()
+                    // Prefer the smaller instance name for ties
+                    .max_by_key(|id| (self.num_children(*id), std::cmp::Reverse(*id)))
+                    .unwrap_or(definition)
+            }
+        }
+    }
+
+    fn build(&self, options: &AssemblyOptions) -> Result<AssemblyTree> {
+        let used_products: HashSet<u64> = self
+            .occurrences
+            .values()
+            .flatten()
+            .map(|occurrence| self.definitions[&occurrence.child].instance)
+            .collect();
+        let mut root_products: Vec<u64> = self
+            .definitions_of_product
+            .keys()
+            .copied()
+            .filter(|product| !used_products.contains(product))
+            .collect();
+        root_products.sort_unstable_by_key(|product| self.definitions_of_product[product][0]);
+
+        let mut roots = Vec::new();
+        for product in root_products {
+            let first = self.definitions_of_product[&product][0];
+            let definition = self.select(first, options);
+            roots.push(self.node(definition, options, &mut Vec::new())?);
+        }
+        Ok(AssemblyTree { roots })
+    }
+
+    fn node(
+        &self,
+        definition: u64,
+        options: &AssemblyOptions,
+        ancestors: &mut Vec<u64>,
+    ) -> Result<AssemblyNode> {
+        if ancestors.contains(&definition) {
+            return Err(Error::CyclicAssembly(definition));
+        }
+        ancestors.push(definition);
+        let product = &self.definitions[&definition];
+        let (product_id, product_name) = (product.id.clone(), product.name.clone());
+        let mut children = Vec::new();
+        for occurrence in self.occurrences.get(&definition).into_iter().flatten() {
+            let child = self.select(occurrence.child, options);
+            children.push(AssemblyChild {
+                occurrence: occurrence.id,
+                occurrence_id: occurrence.occurrence_id.clone(),
+                occurrence_name: occurrence.name.clone(),
+                transform: self.transform(occurrence, definition, child)?,
+                node: self.node(child, options, ancestors)?,
+            });
+        }
+        ancestors.pop();
+        Ok(AssemblyNode {
+            product_id,
+            product_name,
+            definition,
+            children,
+        })
+    }
+
+    /// Placement of the child of the occurrence in the parent
+    ///
+    /// As recommended by CAx-IF, `rep_1` of the relationship is the representation of the child,
+    /// and `transform_item_1` is mapped onto `transform_item_2` of the parent.
+    /// The reversed order is detected if the shape representations of products are known.
+    /// Unit conversions between representation contexts are not considered.
+    fn transform(
+        &self,
+        occurrence: &Occurrence,
+        parent: u64,
+        child: u64,
+    ) -> Result<Option<Transform>> {
+        let index = self.index;
+        let relationship = match self.relationships.get(&occurrence.id) {
+            Some(relationship) => *relationship,
+            None => return Ok(None),
+        };
+        // Simple instance of a subtype has all attributes,
+        // whereas a partial record in complex instance has only its own attributes
+        let (rep_1, rep_2, operator) = match index.get(
+            relationship,
+            &["REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION"],
+        )? {
+            Some(record) if matches!(record.parameter, Parameter::List(ref p) if p.len() == 1) => {
+                let base = index.expect(relationship, &["REPRESENTATION_RELATIONSHIP"])?;
+                (
+                    reference(base, 2)?,
+                    reference(base, 3)?,
+                    required_reference(record, 0)?,
+                )
+            }
+            Some(record) => (
+                reference(record, 2)?,
+                reference(record, 3)?,
+                required_reference(record, 4)?,
+            ),
+            None => return Ok(None),
+        };
+        let operator = match index.get(operator, &["ITEM_DEFINED_TRANSFORMATION"])? {
+            Some(operator) => operator,
+            None => return Ok(None),
+        };
+        let (item_1, item_2) = match (
+            self.placement(required_reference(operator, 2)?)?,
+            self.placement(required_reference(operator, 3)?)?,
+        ) {
+            (Some(item_1), Some(item_2)) => (item_1, item_2),
+            _ => return Ok(None),
+        };
+
+        let reversed = rep_1.is_some()
+            && (rep_1 == self.shape_representations.get(&parent).copied()
+                || rep_2 == self.shape_representations.get(&child).copied());
+        if reversed {
+            Ok(Some(item_1.compose(&item_2.inverse())))
+        } else {
+            Ok(Some(item_2.compose(&item_1.inverse())))
+        }
+    }
+
+    /// Coordinate system of `AXIS2_PLACEMENT_3D` as defined in ISO-10303-42,
+    /// `None` for other placements or degenerated axes
+    fn placement(&self, id: u64) -> Result<Option<Transform>> {
+        let index = self.index;
+        let placement = match index.get(id, &["AXIS2_PLACEMENT_3D"])? {
+            Some(placement) => placement,
+            None => return Ok(None),
+        };
+        let location = index.expect(required_reference(placement, 1)?, &["CARTESIAN_POINT"])?;
+        let translation = vector(location, 1)?;
+        let direction = |index_: usize| -> Result<Option<[f64; 3]>> {
+            match reference(placement, index_)? {
+                Some(id) => Ok(Some(vector(index.expect(id, &["DIRECTION"])?, 1)?)),
+                None => Ok(None),
+            }
+        };
+
+        let z = match normalize(direction(2)?.unwrap_or([0.0, 0.0, 1.0])) {
+            Some(z) => z,
+            None => return Ok(None),
+        };
+        let v = match direction(3)? {
+            Some(v) => v,
+            None if (dot(z, [1.0, 0.0, 0.0]).abs() - 1.0).abs() > 1e-12 => [1.0, 0.0, 0.0],
+            None => [0.0, 0.0, 1.0],
+        };
+        let proj = dot(v, z);
+        let x = match normalize([v[0] - proj * z[0], v[1] - proj * z[1], v[2] - proj * z[2]]) {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+        let y = cross(z, x);
+        Ok(Some(Transform {
+            rotation: [[x[0], y[0], z[0]], [x[1], y[1], z[1]], [x[2], y[2], z[2]]],
+            translation,
+        }))
+    }
+}
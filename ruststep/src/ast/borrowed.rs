@@ -0,0 +1,197 @@
+//! AST borrowing keywords and strings from the input
+//!
+//! The types in this module correspond to the types in [crate::ast] of the same name,
+//! but keep keywords, enumerations and strings as [Cow] of the input.
+//! They are [Cow::Owned] only when a string contains an escaped apostrophe `''`,
+//! and [Cow::Borrowed] otherwise, so that parsing by [crate::parser::parse_borrowed]
+//! does not allocate for each token.
+//!
+//! `into_owned` converts them into the owned AST,
+//! which is the same as the one [crate::parser::parse] returns:
+//!
+//! ```
+//! use ruststep::{ast::borrowed, parser};
+//! use std::borrow::Cow;
+//!
+//! let step = r#"ISO-10303-21;
+//! HEADER;
+//! FILE_DESCRIPTION(('it''s'), '2;1');
+//! ENDSEC;
+//! DATA;
+//! #1 = A('x', .T., B(1.0));
+//! ENDSEC;
+//! END-ISO-10303-21;
+//! "#;
+//!
+//! let exchange = parser::parse_borrowed(step).unwrap();
+//! let record = exchange.data[0].entities[0].records()[0].clone();
+//! assert!(matches!(record.name, Cow::Borrowed("A")));
+//! assert!(matches!(exchange.header[0].parameter, borrowed::Parameter::List(_)));
+//!
+//! assert_eq!(exchange.into_owned(), parser::parse(step).unwrap());
+//! ```
+
+use super::{Anchor, Name, ReferenceEntry};
+use std::borrow::Cow;
+
+/// Borrowed version of [super::Parameter]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parameter<'a> {
+    Typed {
+        keyword: Cow<'a, str>,
+        parameter: Box<Parameter<'a>>,
+    },
+    Integer(i64),
+    Real(f64),
+    String(Cow<'a, str>),
+    Binary(crate::primitive::Binary),
+    Enumeration(Cow<'a, str>),
+    List(Vec<Parameter<'a>>),
+    Ref(Name),
+    NotProvided,
+    Omitted,
+}
+
+impl Parameter<'_> {
+    pub fn into_owned(self) -> super::Parameter {
+        match self {
+            Parameter::Typed { keyword, parameter } => super::Parameter::Typed {
+                keyword: keyword.into_owned(),
+                parameter: Box::new(parameter.into_owned()),
+            },
+            Parameter::Integer(value) => super::Parameter::Integer(value),
+            Parameter::Real(value) => super::Parameter::real(value),
+            Parameter::String(value) => super::Parameter::String(value.into_owned()),
+            Parameter::Binary(value) => super::Parameter::Binary(value),
+            Parameter::Enumeration(value) => super::Parameter::Enumeration(value.into_owned()),
+            Parameter::List(parameters) => {
+                super::Parameter::List(parameters.into_iter().map(Parameter::into_owned).collect())
+            }
+            Parameter::Ref(name) => super::Parameter::Ref(name),
+            Parameter::NotProvided => super::Parameter::NotProvided,
+            Parameter::Omitted => super::Parameter::Omitted,
+        }
+    }
+}
+
+/// Borrowed version of [super::Record]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<'a> {
+    pub name: Cow<'a, str>,
+    pub user_defined: bool,
+    pub parameter: Parameter<'a>,
+}
+
+impl Record<'_> {
+    pub fn into_owned(self) -> super::Record {
+        super::Record {
+            name: self.name.into_owned(),
+            user_defined: self.user_defined,
+            parameter: self.parameter.into_owned(),
+        }
+    }
+}
+
+/// Borrowed version of [super::SubSuperRecord]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubSuperRecord<'a>(pub Vec<Record<'a>>);
+
+impl SubSuperRecord<'_> {
+    pub fn into_owned(self) -> super::SubSuperRecord {
+        super::SubSuperRecord(self.0.into_iter().map(Record::into_owned).collect())
+    }
+}
+
+/// Borrowed version of [super::EntityInstance]
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityInstance<'a> {
+    Simple {
+        id: u64,
+        record: Record<'a>,
+    },
+    Complex {
+        id: u64,
+        subsuper: SubSuperRecord<'a>,
+    },
+}
+
+impl<'a> EntityInstance<'a> {
+    pub fn id(&self) -> u64 {
+        match self {
+            EntityInstance::Simple { id, .. } | EntityInstance::Complex { id, .. } => *id,
+        }
+    }
+
+    /// The record of a simple entity instance, or partial records of a complex entity instance
+    pub fn records(&self) -> &[Record<'a>] {
+        match self {
+            EntityInstance::Simple { record, .. } => std::slice::from_ref(record),
+            EntityInstance::Complex { subsuper, .. } => &subsuper.0,
+        }
+    }
+
+    pub fn into_owned(self) -> super::EntityInstance {
+        match self {
+            EntityInstance::Simple { id, record } => super::EntityInstance::Simple {
+                id,
+                record: record.into_owned(),
+            },
+            EntityInstance::Complex { id, subsuper } => super::EntityInstance::Complex {
+                id,
+                subsuper: subsuper.into_owned(),
+            },
+        }
+    }
+}
+
+/// Borrowed version of [super::DataSection]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataSection<'a> {
+    /// Metadata
+    pub meta: Vec<Parameter<'a>>,
+    /// Each lines in data section
+    pub entities: Vec<EntityInstance<'a>>,
+}
+
+impl DataSection<'_> {
+    pub fn into_owned(self) -> super::DataSection {
+        super::DataSection {
+            meta: self.meta.into_iter().map(Parameter::into_owned).collect(),
+            entities: self
+                .entities
+                .into_iter()
+                .map(EntityInstance::into_owned)
+                .collect(),
+        }
+    }
+}
+
+/// Borrowed version of [super::Exchange]
+///
+/// `ANCHOR`, `REFERENCE` and `SIGNATURE` sections are kept as owned AST
+/// since they are rare and small compared to `DATA` sections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange<'a> {
+    /// `HEADER` section
+    pub header: Vec<Record<'a>>,
+    /// `ANCHOR` section
+    pub anchor: Vec<Anchor>,
+    /// `REFERENCE` section
+    pub reference: Vec<ReferenceEntry>,
+    /// `DATA` section
+    pub data: Vec<DataSection<'a>>,
+    /// `SIGNATURE` section
+    pub signature: Vec<String>,
+}
+
+impl Exchange<'_> {
+    pub fn into_owned(self) -> super::Exchange {
+        super::Exchange {
+            header: self.header.into_iter().map(Record::into_owned).collect(),
+            anchor: self.anchor,
+            reference: self.reference,
+            data: self.data.into_iter().map(DataSection::into_owned).collect(),
+            signature: self.signature,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Builders of [DataSection] and [Exchange] assigning entity instance ids
+
+use super::*;
+use crate::{
+    error::Result,
+    header::{FileDescription, FileName, FileSchema, Header},
+    id::IdAllocator,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Build a [DataSection] from records, assigning ids sequentially from `#1`
+///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let mut builder = DataSectionBuilder::new();
+/// let origin = builder.add(Record::from_str("CARTESIAN_POINT('', (0.0, 0.0, 0.0))").unwrap());
+/// builder.add_with_id(10, Record::from_str("DIRECTION('', (0.0, 0.0, 1.0))").unwrap()).unwrap();
+/// let placement = builder.add(
+///     Record::from_str(&format!("AXIS2_PLACEMENT_3D('', #{}, #10, $)", origin)).unwrap(),
+/// );
+/// assert_eq!((origin, placement), (1, 2));
+///
+/// // Ids given explicitly are never assigned again
+/// assert!(builder.add_with_id(1, Record::from_str("A()").unwrap()).is_err());
+///
+/// let section = builder.build();
+/// assert_eq!(section.entities[2].to_string(), "#2 = AXIS2_PLACEMENT_3D('',#1,#10,$);");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DataSectionBuilder {
+    section: DataSection,
+    ids: IdAllocator,
+}
+
+impl DataSectionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parameters of `DATA(...);`, e.g. the name and schemas of the section
+    pub fn meta(mut self, meta: Vec<Parameter>) -> Self {
+        self.section.meta = meta;
+        self
+    }
+
+    /// Append `record` as a simple entity instance, and returns its id
+    ///
+    /// Panics
+    /// -------
+    /// - if every id below [u64::MAX] is used
+    ///
+    pub fn add(&mut self, record: Record) -> u64 {
+        let id = self.ids.allocate().expect("Entity ids are exhausted");
+        self.section
+            .entities
+            .push(EntityInstance::Simple { id, record });
+        id
+    }
+
+    /// Append `record` as a simple entity instance of `id`
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DuplicatedEntity](crate::error::Error::DuplicatedEntity) if `id` is already used
+    ///
+    pub fn add_with_id(&mut self, id: u64, record: Record) -> Result<()> {
+        self.section.insert(EntityInstance::Simple { id, record })?;
+        self.ids.reserve_range(id..id.saturating_add(1));
+        Ok(())
+    }
+
+    pub fn build(self) -> DataSection {
+        self.section
+    }
+}
+
+/// Build an [Exchange] with the mandatory header entities
+///
+/// The time stamp is the current time in UTC, and the preprocessor version is this crate,
+/// unless they are given explicitly.
+///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let mut data = DataSectionBuilder::new();
+/// data.add(Record::from_str("A(1.0)").unwrap());
+///
+/// let exchange = ExchangeBuilder::new()
+///     .name("a.stp")
+///     .schema("CONFIG_CONTROL_DESIGN")
+///     .data_section(data.build())
+///     .build();
+/// let header = exchange.header_typed().unwrap();
+/// assert_eq!(header.file_name.name, "a.stp");
+/// assert!(header.file_name.preprocessor_version.starts_with("ruststep "));
+/// assert_eq!(Exchange::from_str(&exchange.to_string()).unwrap(), exchange);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExchangeBuilder {
+    header: Header,
+    data: Vec<DataSection>,
+}
+
+impl Default for ExchangeBuilder {
+    fn default() -> Self {
+        ExchangeBuilder {
+            header: Header {
+                file_description: FileDescription {
+                    description: vec![String::new()],
+                    implementation_level: "2;1".to_string(),
+                },
+                file_name: FileName {
+                    name: String::new(),
+                    time_stamp: time_stamp(SystemTime::now()),
+                    author: vec![String::new()],
+                    organization: vec![String::new()],
+                    preprocessor_version: format!("ruststep {}", env!("CARGO_PKG_VERSION")),
+                    originating_system: String::new(),
+                    authorization: String::new(),
+                },
+                file_schema: FileSchema { schema: Vec::new() },
+                extra: Vec::new(),
+            },
+            data: Vec::new(),
+        }
+    }
+}
+
+impl ExchangeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Paragraphs of `FILE_DESCRIPTION`
+    pub fn description<I, S>(mut self, paragraphs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.header
+            .file_description
+            .set_description_paragraphs(paragraphs);
+        self
+    }
+
+    /// Implementation level of `FILE_DESCRIPTION`. The default is `2;1`.
+    pub fn implementation_level(mut self, level: &str) -> Self {
+        self.header.file_description.implementation_level = level.to_string();
+        self
+    }
+
+    /// Name of `FILE_NAME`
+    pub fn name(mut self, name: &str) -> Self {
+        self.header.file_name.name = name.to_string();
+        self
+    }
+
+    /// Time stamp of `FILE_NAME` in ISO 8601, e.g. `2024-01-01T00:00:00`
+    pub fn time_stamp(mut self, time_stamp: &str) -> Self {
+        self.header.file_name.time_stamp = time_stamp.to_string();
+        self
+    }
+
+    /// Add an author of `FILE_NAME`
+    pub fn author(mut self, author: &str) -> Self {
+        push_or_replace_empty(&mut self.header.file_name.author, author);
+        self
+    }
+
+    /// Add an organization of `FILE_NAME`
+    pub fn organization(mut self, organization: &str) -> Self {
+        push_or_replace_empty(&mut self.header.file_name.organization, organization);
+        self
+    }
+
+    /// Preprocessor version of `FILE_NAME`. The default is `ruststep` and its version.
+    pub fn preprocessor_version(mut self, version: &str) -> Self {
+        self.header.file_name.preprocessor_version = version.to_string();
+        self
+    }
+
+    /// Originating system of `FILE_NAME`
+    pub fn originating_system(mut self, system: &str) -> Self {
+        self.header.file_name.originating_system = system.to_string();
+        self
+    }
+
+    /// Authorization of `FILE_NAME`
+    pub fn authorization(mut self, authorization: &str) -> Self {
+        self.header.file_name.authorization = authorization.to_string();
+        self
+    }
+
+    /// Add a schema of `FILE_SCHEMA`
+    pub fn schema(mut self, schema: &str) -> Self {
+        self.header.file_schema.schema.push(schema.to_string());
+        self
+    }
+
+    /// Add a header record after `FILE_SCHEMA`, e.g. `SECTION_LANGUAGE('en')`
+    pub fn header_record(mut self, record: Record) -> Self {
+        self.header.extra.push(record);
+        self
+    }
+
+    /// Add a `DATA` section
+    pub fn data_section(mut self, section: DataSection) -> Self {
+        self.data.push(section);
+        self
+    }
+
+    pub fn build(self) -> Exchange {
+        Exchange {
+            header: self.header.to_records(),
+            anchor: Vec::new(),
+            reference: Vec::new(),
+            data: self.data,
+            signature: Vec::new(),
+        }
+    }
+}
+
+/// `LIST [1:?] OF STRING` is `('')` until a value is given
+fn push_or_replace_empty(values: &mut Vec<String>, value: &str) {
+    if values.len() == 1 && values[0].is_empty() {
+        values.clear();
+    }
+    values.push(value.to_string());
+}
+
+/// `time` in UTC formatted as ISO 8601, e.g. `2024-01-01T00:00:00`
+fn time_stamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = (secs / 86400, secs % 86400);
+    // Civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn time_stamp() {
+        for (secs, expected) in [
+            (0, "1970-01-01T00:00:00"),
+            (951782400, "2000-02-29T00:00:00"),
+            (1704067199, "2023-12-31T23:59:59"),
+            (1718454645, "2024-06-15T12:30:45"),
+        ] {
+            let time = UNIX_EPOCH + Duration::from_secs(secs);
+            assert_eq!(super::time_stamp(time), expected);
+        }
+    }
+}
@@ -18,14 +18,14 @@ impl<'de, 'param> de::Deserializer<'de> for &'param Parameter {
                 visitor.visit_map(RecordDeserializer::new(keyword, parameter))
             }
             Parameter::Integer(val) => visitor.visit_i64(*val),
-            Parameter::Real(val) => visitor.visit_f64(*val),
+            Parameter::Real { value, .. } => visitor.visit_f64(*value),
             Parameter::String(val) => visitor.visit_str(val),
+            Parameter::Binary(val) => visitor.visit_bytes(val.as_bytes()),
             Parameter::List(params) => visitor.visit_seq(SeqDeserializer::new(params)),
             Parameter::Ref(name) => visitor.visit_enum(name),
-            Parameter::NotProvided | Parameter::Omitted => visitor.visit_none(),
-            Parameter::Enumeration(variant) => {
-                visitor.visit_enum(variant.to_pascal_case().into_deserializer())
-            }
+            Parameter::NotProvided => visitor.visit_none(),
+            Parameter::Omitted => visitor.visit_unit(),
+            Parameter::Enumeration(variant) => visitor.visit_enum(EnumerationDeserializer(variant)),
         }
     }
 
@@ -39,7 +39,7 @@ impl<'de, 'param> de::Deserializer<'de> for &'param Parameter {
                 "TRUE" => visitor.visit_bool(true),
                 "F" => visitor.visit_bool(false),
                 "FALSE" => visitor.visit_bool(false),
-                _ => visitor.visit_enum(variant.to_pascal_case().into_deserializer()),
+                _ => visitor.visit_enum(EnumerationDeserializer(variant)),
             }
         } else {
             self.deserialize_any(visitor)
@@ -57,10 +57,107 @@ impl<'de, 'param> de::Deserializer<'de> for &'param Parameter {
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == crate::primitive::BINARY {
+            // [crate::primitive::Binary] is read from the content of the binary token to keep the number of bits
+            if let Parameter::Binary(val) = self {
+                return visitor.visit_str(&val.to_string());
+            }
+        }
+        if name != crate::primitive::WRAPPED {
+            return self.deserialize_any(visitor);
+        }
+        // [crate::primitive::Wrapped] is read as a pair of the keyword and the value
+        match self {
+            Parameter::Typed { keyword, parameter } => {
+                let keyword = if crate::tables::preserve_type_wrappers() {
+                    Parameter::String(keyword.clone())
+                } else {
+                    Parameter::NotProvided
+                };
+                visitor.visit_seq(WrappedAccess {
+                    keyword: Some(keyword),
+                    value: Some(parameter),
+                })
+            }
+            _ => visitor.visit_seq(WrappedAccess {
+                keyword: Some(Parameter::NotProvided),
+                value: Some(self),
+            }),
+        }
+    }
+
+    /// Fixed-size arrays, e.g. `[f64; 3]` for `ARRAY [1:3] OF REAL`, are read as tuples
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Parameter::List(params) = self {
+            if params.len() != len {
+                return Err(crate::error::Error::ArrayLengthMismatch {
+                    expected: len,
+                    found: params.len(),
+                }
+                .in_attribute());
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    /// An enumeration value typed by the keyword of its type, e.g. `UNIT_NAME(.METRE.)`, is read as the value.
+    /// The keyword is not checked since the type may be read by its alias.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self {
+            Parameter::Typed { parameter, .. }
+                if matches!(parameter.as_ref(), Parameter::Enumeration(_)) =>
+            {
+                parameter.as_ref().deserialize_enum(name, variants, visitor)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
     forward_to_deserialize_any! {
         i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf unit unit_struct newtype_struct seq tuple
-        struct tuple_struct map enum identifier ignored_any
+        bytes byte_buf unit unit_struct seq
+        struct tuple_struct map identifier ignored_any
+    }
+}
+
+struct WrappedAccess<'param> {
+    keyword: Option<Parameter>,
+    value: Option<&'param Parameter>,
+}
+
+impl<'de, 'param> de::SeqAccess<'de> for WrappedAccess<'param> {
+    type Error = crate::error::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if let Some(keyword) = self.keyword.take() {
+            return seed.deserialize(&keyword).map(Some);
+        }
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
     }
 }
 
@@ -108,6 +205,7 @@ impl<'de, 'p> de::SeqAccess<'de> for SeqDeserializer<'p> {
         T: de::DeserializeSeed<'de>,
     {
         if self.cursor < self.parameters.len() {
+            let _element = crate::error::enter_element(self.cursor);
             let value = seed.deserialize(&self.parameters[self.cursor])?;
             self.cursor += 1;
             Ok(Some(value))
@@ -116,3 +214,237 @@ impl<'de, 'p> de::SeqAccess<'de> for SeqDeserializer<'p> {
         }
     }
 }
+
+/// Enumeration, e.g. `.MILLI.`, as an enum in serde data model
+///
+/// The variant is in pascal case, e.g. `Milli`, and it is usually a unit variant.
+/// It can also be read as a newtype variant whose content is the original string, e.g. `MILLI`.
+struct EnumerationDeserializer<'param>(&'param str);
+
+impl<'de, 'param> de::EnumAccess<'de> for EnumerationDeserializer<'param> {
+    type Error = crate::error::Error;
+    type Variant = Self;
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let variant: de::value::StringDeserializer<Self::Error> =
+            self.0.to_pascal_case().into_deserializer();
+        Ok((seed.deserialize(variant)?, self))
+    }
+}
+
+impl<'de, 'param> de::VariantAccess<'de> for EnumerationDeserializer<'param> {
+    type Error = crate::error::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<D>(self, seed: D) -> Result<D::Value, Self::Error>
+    where
+        D: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.0.into_deserializer())
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+/// Rebuild [Parameter] through serde data model, e.g. for `#[holder(rest)]` fields
+///
+/// [Parameter::Omitted] is rebuilt from unit, and thus it is kept distinct from [Parameter::NotProvided].
+/// [Parameter::Binary] is rebuilt from its bytes, i.e. the bits are padded to a multiple of 8.
+///
+/// ```
+/// use ruststep::ast::Parameter;
+/// use serde::Deserialize;
+/// use std::str::FromStr;
+///
+/// let p = Parameter::from_str("(1, 2.0, 'a', .T., #3, A((4, $, *)))").unwrap();
+/// assert_eq!(Parameter::deserialize(&p).unwrap(), p);
+/// ```
+impl<'de> de::Deserialize<'de> for Parameter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ParameterVisitor)
+    }
+}
+
+struct ParameterVisitor;
+
+impl<'de> de::Visitor<'de> for ParameterVisitor {
+    type Value = Parameter;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "parameter")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Parameter, E> {
+        Ok(Parameter::Integer(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Parameter, E> {
+        i64::try_from(value)
+            .map(Parameter::Integer)
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Parameter, E> {
+        Ok(Parameter::real(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Parameter, E> {
+        Ok(Parameter::String(value.to_string()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Parameter, E> {
+        Ok(Parameter::Binary(value.to_vec().into()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Parameter, E> {
+        Ok(Parameter::NotProvided)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Parameter, E> {
+        Ok(Parameter::Omitted)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Parameter, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        de::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Parameter, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut parameters = Vec::new();
+        while let Some(parameter) = seq.next_element()? {
+            parameters.push(parameter);
+        }
+        Ok(Parameter::List(parameters))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Parameter, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let (keyword, parameter) = map
+            .next_entry::<String, Parameter>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        Ok(Parameter::Typed {
+            keyword,
+            parameter: Box::new(parameter),
+        })
+    }
+
+    // Both of [Name] and enumeration are enum in serde data model,
+    // and their newtype variants contain the id, the constant name, or the original enumeration.
+    // `.CONSTANT_ENTITY.` and `#CONSTANT_ENTITY` cannot be distinguished, and the latter is assumed.
+    fn visit_enum<A>(self, data: A) -> Result<Parameter, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        use de::VariantAccess;
+        let (variant, access): (String, _) = data.variant()?;
+        Ok(match (variant.as_str(), access.newtype_variant()?) {
+            ("Entity", NameValue::Id(id)) => Parameter::Ref(Name::Entity(id)),
+            ("Value", NameValue::Id(id)) => Parameter::Ref(Name::Value(id)),
+            ("ConstantEntity", NameValue::Name(name)) => Parameter::Ref(Name::ConstantEntity(name)),
+            ("ConstantValue", NameValue::Name(name)) => Parameter::Ref(Name::ConstantValue(name)),
+            (_, NameValue::Name(name)) => Parameter::Enumeration(name),
+            (_, NameValue::Id(id)) => {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Unsigned(id),
+                    &self,
+                ))
+            }
+        })
+    }
+}
+
+/// Content of newtype variants of [Name] and enumeration
+enum NameValue {
+    Id(u64),
+    Name(String),
+}
+
+impl<'de> de::Deserialize<'de> for NameValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct NameValueVisitor;
+        impl de::Visitor<'_> for NameValueVisitor {
+            type Value = NameValue;
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "id or name")
+            }
+            fn visit_u64<E: de::Error>(self, id: u64) -> Result<NameValue, E> {
+                Ok(NameValue::Id(id))
+            }
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<NameValue, E> {
+                Ok(NameValue::Name(name.to_string()))
+            }
+        }
+        deserializer.deserialize_any(NameValueVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::str::FromStr;
+
+    #[test]
+    fn deserialize_parameter() {
+        for input in [
+            "1",
+            "-2.5",
+            "'it''s'",
+            "$",
+            "()",
+            ".T.",
+            ".ENTITY.",
+            ".MY_ENUM2.",
+            "#12",
+            "@3",
+            "#ORIGIN",
+            "A(B((1, #2)))",
+            "((1, 2), ($, .F.))",
+            "*",
+            "(*, $, A(*))",
+        ] {
+            let p = Parameter::from_str(input).unwrap();
+            assert_eq!(Parameter::deserialize(&p).unwrap(), p, "{}", input);
+        }
+    }
+}
@@ -11,6 +11,11 @@ impl<'de, 'record> de::Deserializer<'de> for &'record Record {
     where
         V: de::Visitor<'de>,
     {
+        if self.user_defined {
+            // Keep `!` so that user-defined keyword does not match to standard ones
+            let key = format!("!{}", self.name);
+            return visitor.visit_map(RecordDeserializer::new(&key, &self.parameter));
+        }
         visitor.visit_map(RecordDeserializer::new(&self.name, &self.parameter))
     }
 
@@ -23,7 +28,7 @@ impl<'de, 'record> de::Deserializer<'de> for &'record Record {
     where
         V: de::Visitor<'de>,
     {
-        if name == self.name {
+        if name == self.name && !self.user_defined {
             if let Parameter::List(ref parameters) = self.parameter {
                 return visitor.visit_map(RecordStructDeserializer::new(fields, parameters));
             }
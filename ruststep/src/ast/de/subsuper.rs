@@ -3,6 +3,7 @@ use serde::{
     de::{self, IntoDeserializer},
     forward_to_deserialize_any,
 };
+use std::borrow::Cow;
 
 impl<'de, 'record> de::Deserializer<'de> for &'record SubSuperRecord {
     type Error = crate::error::Error;
@@ -23,14 +24,20 @@ impl<'de, 'record> de::Deserializer<'de> for &'record SubSuperRecord {
 
 #[derive(Debug)]
 pub struct SubSuperRecordDeserializer<'record> {
-    keywords: Vec<&'record str>,
+    keywords: Vec<Cow<'record, str>>,
     parameters: Vec<&'record Parameter>,
 }
 
 impl<'record> SubSuperRecordDeserializer<'record> {
     pub fn new(records: &'record [Record]) -> Self {
         Self {
-            keywords: records.iter().map(|x| x.name.as_str()).collect(),
+            keywords: records
+                .iter()
+                .map(|x| match x.user_defined {
+                    true => Cow::Owned(x.keyword()),
+                    false => Cow::Borrowed(x.name.as_str()),
+                })
+                .collect(),
             parameters: records.iter().map(|x| &x.parameter).collect(),
         }
     }
@@ -45,7 +52,7 @@ impl<'de, 'record> de::MapAccess<'de> for SubSuperRecordDeserializer<'record> {
         K: de::DeserializeSeed<'de>,
     {
         if let Some(key) = self.keywords.pop() {
-            let key: de::value::StrDeserializer<Self::Error> = key.into_deserializer();
+            let key: de::value::StrDeserializer<Self::Error> = key.as_ref().into_deserializer();
             let key: K::Value = seed.deserialize(key)?;
             Ok(Some(key))
         } else {
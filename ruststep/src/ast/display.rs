@@ -0,0 +1,321 @@
+//! Write AST in the syntax of exchange structure
+//!
+//! The output is parsed back into the same AST by [std::str::FromStr],
+//! e.g. `$` is written as `$` wherever it appears.
+//! Comments in the input are not kept.
+//!
+//! ```
+//! use ruststep::ast::Record;
+//! use std::str::FromStr;
+//!
+//! let record = Record::from_str("A($, (1.0, $), B($), 'it''s')").unwrap();
+//! assert_eq!(record.to_string(), "A($,(1.0,$),B($),'it''s')");
+//! assert_eq!(Record::from_str(&record.to_string()).unwrap(), record);
+//! ```
+
+use super::*;
+use std::fmt;
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Name::Entity(id) => write!(f, "#{}", id),
+            Name::Value(id) => write!(f, "@{}", id),
+            Name::ConstantEntity(name) => write!(f, "#{}", name),
+            Name::ConstantValue(name) => write!(f, "@{}", name),
+        }
+    }
+}
+
+/// Real must have a decimal point, and exponent is written as `E`, e.g. `1.E-7`
+fn write_real(f: &mut fmt::Formatter, value: f64) -> fmt::Result {
+    let debug = format!("{:?}", value);
+    let (mantissa, exponent) = match debug.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (debug.as_str(), None),
+    };
+    write!(f, "{}", mantissa)?;
+    if !mantissa.contains('.') {
+        write!(f, ".")?;
+    }
+    if let Some(exponent) = exponent {
+        write!(f, "E{}", exponent)?;
+    }
+    Ok(())
+}
+
+/// `raw` is a real in ISO-10303-21 equal to `value`
+fn represents(raw: &str, value: f64) -> bool {
+    use crate::parser::token;
+    use nom::Finish;
+    match token::real(raw).finish() {
+        Ok((rest, parsed)) => rest.is_empty() && parsed.to_bits() == value.to_bits(),
+        Err(_) => false,
+    }
+}
+
+/// Write a string with `'` and `\` escaped, and characters out of printable ASCII
+/// encoded by `\X2\` or `\X4\` control directives, e.g. `café` as `'caf\X2\00E9\X0\'`
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    let printable = |c: char| matches!(c, ' '..='~');
+    write!(f, "'")?;
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        if c == '\'' {
+            write!(f, "''")?;
+            rest = &rest[1..];
+        } else if c == '\\' {
+            write!(f, "\\\\")?;
+            rest = &rest[1..];
+        } else if printable(c) {
+            write!(f, "{}", c)?;
+            rest = &rest[1..];
+        } else {
+            let end = rest.find(printable).unwrap_or(rest.len());
+            let run = &rest[..end];
+            if run.chars().all(|c| (c as u32) < 0x10000) {
+                write!(f, "\\X2\\")?;
+                for c in run.chars() {
+                    write!(f, "{:04X}", c as u32)?;
+                }
+            } else {
+                write!(f, "\\X4\\")?;
+                for c in run.chars() {
+                    write!(f, "{:08X}", c as u32)?;
+                }
+            }
+            write!(f, "\\X0\\")?;
+            rest = &rest[end..];
+        }
+    }
+    write!(f, "'")
+}
+
+/// Write comma-separated parameters without parentheses
+fn write_list(f: &mut fmt::Formatter, parameters: &[Parameter]) -> fmt::Result {
+    for (i, parameter) in parameters.iter().enumerate() {
+        if i > 0 {
+            write!(f, ",")?;
+        }
+        write!(f, "{}", parameter)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Parameter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Parameter::Typed { keyword, parameter } => write!(f, "{}({})", keyword, parameter),
+            Parameter::Integer(value) => write!(f, "{}", value),
+            Parameter::Real {
+                value,
+                raw: Some(raw),
+            } if represents(raw, *value) => write!(f, "{}", raw),
+            Parameter::Real { value, .. } => write_real(f, *value),
+            Parameter::String(value) => write_string(f, value),
+            Parameter::Binary(value) => write!(f, "\"{}\"", value),
+            Parameter::Enumeration(value) => write!(f, ".{}.", value),
+            Parameter::List(parameters) => {
+                write!(f, "(")?;
+                write_list(f, parameters)?;
+                write!(f, ")")
+            }
+            Parameter::Ref(name) => write!(f, "{}", name),
+            Parameter::NotProvided => write!(f, "$"),
+            Parameter::Omitted => write!(f, "*"),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.user_defined {
+            write!(f, "!")?;
+        }
+        write!(f, "{}(", self.name)?;
+        match &self.parameter {
+            Parameter::List(parameters) => write_list(f, parameters)?,
+            parameter => write!(f, "{}", parameter)?,
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for SubSuperRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for record in &self.0 {
+            write!(f, "{}", record)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for EntityInstance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntityInstance::Simple { id, record } => write!(f, "#{} = {};", id, record),
+            EntityInstance::Complex { id, subsuper } => write!(f, "#{} = {};", id, subsuper),
+        }
+    }
+}
+
+impl fmt::Display for DataSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DATA")?;
+        if !self.meta.is_empty() {
+            write!(f, "(")?;
+            write_list(f, &self.meta)?;
+            write!(f, ")")?;
+        }
+        writeln!(f, ";")?;
+        for instance in &self.entities {
+            writeln!(f, "{}", instance)?;
+        }
+        write!(f, "ENDSEC;")
+    }
+}
+
+impl fmt::Display for AnchorItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AnchorItem::Integer(value) => write!(f, "{}", value),
+            AnchorItem::Real(value) => write_real(f, *value),
+            AnchorItem::String(value) => write_string(f, value),
+            AnchorItem::Enumeration(value) => write!(f, ".{}.", value),
+            AnchorItem::Binary(value) => write!(f, "\"{}\"", value),
+            AnchorItem::NotProvided => write!(f, "$"),
+            AnchorItem::Name(name) => write!(f, "{}", name),
+            AnchorItem::Resource(uri) => write!(f, "<{}>", uri.0),
+            AnchorItem::List(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}> = {}", self.name, self.item)?;
+        for (name, item) in &self.tags {
+            write!(f, "{{{}:{}}}", name, item)?;
+        }
+        write!(f, ";")
+    }
+}
+
+impl fmt::Display for ReferenceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} = <{}>;", self.name, self.resource.0)
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ISO-10303-21;")?;
+        writeln!(f, "HEADER;")?;
+        for record in &self.header {
+            writeln!(f, "{};", record)?;
+        }
+        writeln!(f, "ENDSEC;")?;
+        if !self.anchor.is_empty() {
+            writeln!(f, "ANCHOR;")?;
+            for anchor in &self.anchor {
+                writeln!(f, "{}", anchor)?;
+            }
+            writeln!(f, "ENDSEC;")?;
+        }
+        if !self.reference.is_empty() {
+            writeln!(f, "REFERENCE;")?;
+            for entry in &self.reference {
+                writeln!(f, "{}", entry)?;
+            }
+            writeln!(f, "ENDSEC;")?;
+        }
+        for section in &self.data {
+            writeln!(f, "{}", section)?;
+        }
+        writeln!(f, "END-ISO-10303-21;")?;
+        for signature in &self.signature {
+            writeln!(f, "SIGNATURE {} ENDSEC;", signature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real() {
+        for (value, expected) in [
+            (1.0, "1.0"),
+            (-0.5, "-0.5"),
+            (1e-7, "1.E-7"),
+            (1.5e20, "1.5E20"),
+        ] {
+            let p = Parameter::real(value);
+            assert_eq!(p.to_string(), expected);
+            assert_eq!(Parameter::from_str(expected).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn string() {
+        for (value, expected) in [
+            ("it's", "'it''s'"),
+            (r"C:\temp", r"'C:\\temp'"),
+            ("café shop", r"'caf\X2\00E9\X0\ shop'"),
+            ("© 'A'", r"'\X2\00A9\X0\ ''A'''"),
+            ("ステップ", r"'\X2\30B930C630C330D7\X0\'"),
+            ("😀!", r"'\X4\0001F600\X0\!'"),
+        ] {
+            let p = Parameter::String(value.to_string());
+            assert_eq!(p.to_string(), expected);
+            assert_eq!(Parameter::from_str(expected).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn entity_instance() {
+        for input in ["#1 = A(#2,@3,.T.,*);", "#10 = (A(1)B('b'));"] {
+            let instance = EntityInstance::from_str(input).unwrap();
+            assert_eq!(instance.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn exchange() {
+        let input = r#"
+            ISO-10303-21;
+            HEADER;
+            FILE_DESCRIPTION(('a'),'2;1');
+            ENDSEC;
+            ANCHOR;
+            <origin> = #1{tag:(1,$)};
+            ENDSEC;
+            REFERENCE;
+            #2 = <other.stp#3>;
+            ENDSEC;
+            DATA('geometry',('SCHEMA'));
+            #1 = A(#2);
+            ENDSEC;
+            DATA;
+            ENDSEC;
+            END-ISO-10303-21;
+        "#;
+        let exchange = Exchange::from_str(input).unwrap();
+        let output = exchange.to_string();
+        assert_eq!(Exchange::from_str(&output).unwrap(), exchange);
+        let expected: Vec<_> = input.trim().lines().map(str::trim).collect();
+        assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+    }
+}
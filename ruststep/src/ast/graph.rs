@@ -0,0 +1,131 @@
+//! Graph of references between entity instances in a [DataSection]
+
+use super::*;
+use crate::error::CycleError;
+
+impl DataSection {
+    /// Ids of instances in `roots` and those referred from them transitively
+    ///
+    /// References to instances not in this section are not followed.
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #1 = A(1.0);
+    ///   #2 = B(#1, #9);
+    ///   #3 = C(#2);
+    ///   #4 = C(#1);
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let reached = section.reachable_from(&[3]);
+    /// assert_eq!(reached, [1, 2, 3].into_iter().collect());
+    /// ```
+    pub fn reachable_from(&self, roots: &[u64]) -> HashSet<u64> {
+        let instances = self.references_by_id();
+        let mut reached = HashSet::new();
+        let mut stack = roots.to_vec();
+        while let Some(id) = stack.pop() {
+            if let Some(references) = instances.get(&id) {
+                if reached.insert(id) {
+                    stack.extend(references.iter().copied());
+                }
+            }
+        }
+        reached
+    }
+
+    /// Ids of all instances ordered so that referred instances appear before referring ones
+    ///
+    /// Instances keep the order of this section as far as possible.
+    /// References to instances not in this section are ignored.
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #1 = A(#3, #2);
+    ///   #2 = B(#3);
+    ///   #3 = C(1.0);
+    ///   #4 = D(#5);
+    /// ENDSEC;
+    /// "#).unwrap();
+    /// assert_eq!(section.topological_order().unwrap(), [3, 2, 1, 4]);
+    ///
+    /// let section = DataSection::from_str("DATA; #1 = A(#2); #2 = B(#1); ENDSEC;").unwrap();
+    /// let err = section.topological_order().unwrap_err();
+    /// assert_eq!(err.cycle, [1, 2]);
+    /// assert_eq!(err.to_string(), "Entity references are cyclic: #1 -> #2 -> #1");
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [CycleError] with one of the cycles if instances refer themselves transitively
+    ///
+    pub fn topological_order(&self) -> Result<Vec<u64>, CycleError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            Visiting,
+            Done,
+        }
+
+        let instances = self.references_by_id();
+        let mut states: HashMap<u64, State> = HashMap::with_capacity(instances.len());
+        let mut order = Vec::with_capacity(instances.len());
+        for instance in &self.entities {
+            if states.contains_key(&instance.id()) {
+                continue;
+            }
+            // Depth-first search without recursion, since a chain of references can be very long.
+            // Each frame is an id and the number of its references already visited.
+            let mut path: Vec<(u64, usize)> = vec![(instance.id(), 0)];
+            states.insert(instance.id(), State::Visiting);
+            while let Some((id, next)) = path.last_mut() {
+                let references = &instances[id];
+                match references.get(*next) {
+                    Some(&child) => {
+                        *next += 1;
+                        if !instances.contains_key(&child) {
+                            continue;
+                        }
+                        match states.get(&child) {
+                            Some(State::Done) => {}
+                            Some(State::Visiting) => {
+                                let start = path.iter().position(|(id, _)| *id == child).unwrap();
+                                return Err(CycleError {
+                                    cycle: path[start..].iter().map(|(id, _)| *id).collect(),
+                                });
+                            }
+                            None => {
+                                states.insert(child, State::Visiting);
+                                path.push((child, 0));
+                            }
+                        }
+                    }
+                    None => {
+                        states.insert(*id, State::Done);
+                        order.push(*id);
+                        path.pop();
+                    }
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Referred ids of each instance. The first one is used if ids are duplicated.
+    fn references_by_id(&self) -> HashMap<u64, Vec<u64>> {
+        let mut instances = HashMap::with_capacity(self.entities.len());
+        for instance in &self.entities {
+            instances
+                .entry(instance.id())
+                .or_insert_with(|| instance.references());
+        }
+        instances
+    }
+}
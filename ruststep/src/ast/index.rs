@@ -0,0 +1,61 @@
+//! Lookup of entity instances by id in a [DataSection]
+
+use super::*;
+use crate::error::{Error, Result};
+
+/// Map from id to entity instance of a [DataSection], created by [DataSection::index]
+///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let section = DataSection::from_str(r#"
+/// DATA;
+///   #1 = A(1.0);
+///   #5 = (B(2.0) C(#1));
+/// ENDSEC;
+/// "#).unwrap();
+///
+/// let index = section.index().unwrap();
+/// assert_eq!(index.len(), 2);
+/// assert_eq!(index.get(5).unwrap().records().len(), 2);
+/// assert!(index.get(2).is_none());
+///
+/// // Each id appears only once
+/// let section = DataSection::from_str("DATA; #1 = A(1.0); #1 = B(2.0); ENDSEC;").unwrap();
+/// assert!(section.index().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct EntityIndex<'a> {
+    instances: HashMap<u64, &'a EntityInstance>,
+}
+
+impl<'a> EntityIndex<'a> {
+    pub(super) fn new(section: &'a DataSection) -> Result<Self> {
+        let mut instances = HashMap::with_capacity(section.entities.len());
+        for instance in &section.entities {
+            if instances.insert(instance.id(), instance).is_some() {
+                return Err(Error::DuplicatedEntity(instance.id()));
+            }
+        }
+        Ok(EntityIndex { instances })
+    }
+
+    /// Instance of `id`
+    pub fn get(&self, id: u64) -> Option<&'a EntityInstance> {
+        self.instances.get(&id).copied()
+    }
+
+    pub fn contains(&self, id: u64) -> bool {
+        self.instances.contains_key(&id)
+    }
+
+    /// Number of instances
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+}
@@ -0,0 +1,147 @@
+//! Merge of two [Exchange]s into one
+
+use super::*;
+use crate::{error::MergeError, id::IdAllocator};
+
+/// Options of [Exchange::merge_with]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeOptions {
+    /// Put all instances into the first data section instead of keeping the data sections of both exchanges
+    pub single_section: bool,
+    /// Ids reserved in this allocator are not assigned to the instances of the other exchange.
+    /// The allocation starts above the largest id of this exchange regardless of the allocator state.
+    pub ids: IdAllocator,
+}
+
+impl Exchange {
+    /// Merge `other` into this exchange keeping the data sections of both, see [Exchange::merge_with]
+    pub fn merge(self, other: Exchange) -> Result<Exchange, MergeError> {
+        self.merge_with(other, &MergeOptions::default())
+    }
+
+    /// Merge `other` into this exchange
+    ///
+    /// - Instances of `other` are renumbered by [Exchange::renumber_with] above the largest id of this exchange,
+    ///   skipping ids reserved in [MergeOptions::ids]
+    /// - `FILE_DESCRIPTION` has the paragraphs of both, and the implementation levels must be the same
+    /// - `FILE_NAME` of this exchange is kept
+    /// - `FILE_SCHEMA` has the schemas of both without duplicates
+    /// - Other header records, `ANCHOR`, and `REFERENCE` entries of `other` follow those of this exchange
+    /// - `SIGNATURE` sections are dropped since they do not sign the new content
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use std::str::FromStr;
+    ///
+    /// let file = |description: &str, schema: &str, data: &str| {
+    ///     Exchange::from_str(&format!(r#"ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION(('{}'), '2;1');
+    /// FILE_NAME('{}.stp', '2024-01-01T00:00:00', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('{}'));
+    /// ENDSEC;
+    /// DATA;
+    /// {}
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// "#, description, description, schema, data)).unwrap()
+    /// };
+    /// let dictionary = file("dictionary", "ISO13584_IEC61360_DICTIONARY_SCHEMA", "#1 = A(1.0); #2 = B(#1);");
+    /// let data = file("data", "ISO13584_IEC61360_DICTIONARY_SCHEMA", "#1 = C(#2); #2 = D(2.0);");
+    ///
+    /// let merged = dictionary
+    ///     .merge_with(data, &MergeOptions { single_section: true, ..Default::default() })
+    ///     .unwrap();
+    /// let header = merged.header_typed().unwrap();
+    /// assert_eq!(header.file_description.description, ["dictionary", "data"]);
+    /// assert_eq!(header.file_name.name, "dictionary.stp");
+    /// assert_eq!(header.file_schema.schema, ["ISO13584_IEC61360_DICTIONARY_SCHEMA"]);
+    /// assert_eq!(
+    ///     merged.data[0].to_string(),
+    ///     "DATA;\n#1 = A(1.0);\n#2 = B(#1);\n#3 = D(2.0);\n#4 = C(#3);\nENDSEC;"
+    /// );
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [MergeError::ImplementationLevelMismatch] if the implementation levels are different
+    /// - [MergeError::DuplicatedAnchor] if both exchanges have an anchor of the same name
+    /// - [MergeError::Invalid] if either header cannot be read by [Exchange::header_typed],
+    ///   or `other` cannot be renumbered
+    ///
+    pub fn merge_with(
+        self,
+        mut other: Exchange,
+        options: &MergeOptions,
+    ) -> Result<Exchange, MergeError> {
+        let mut header = self.header_typed()?;
+        let other_header = other.header_typed()?;
+
+        let first = &header.file_description.implementation_level;
+        let second = &other_header.file_description.implementation_level;
+        if first != second {
+            return Err(MergeError::ImplementationLevelMismatch {
+                first: first.clone(),
+                second: second.clone(),
+            });
+        }
+        if let Some(anchor) = other
+            .anchor
+            .iter()
+            .find(|a| self.anchor.iter().any(|b| a.name == b.name))
+        {
+            return Err(MergeError::DuplicatedAnchor(anchor.name.clone()));
+        }
+
+        let max = self
+            .data
+            .iter()
+            .flat_map(|section| &section.entities)
+            .map(|instance| instance.id())
+            .chain(self.reference.iter().filter_map(|e| e.name.entity_id()))
+            .max()
+            .unwrap_or(0);
+        let start = max
+            .checked_add(1)
+            .ok_or(crate::error::Error::IdExhausted { ceiling: u64::MAX })?;
+        other.renumber_with(&mut options.ids.clone().start(start))?;
+
+        let description = &mut header.file_description.description;
+        description.extend(other_header.file_description.description);
+        description.retain(|paragraph| !paragraph.is_empty());
+        if description.is_empty() {
+            description.push(String::new());
+        }
+        for schema in other_header.file_schema.schema {
+            let schemas = &mut header.file_schema.schema;
+            if !schemas.iter().any(|s| s.eq_ignore_ascii_case(&schema)) {
+                schemas.push(schema);
+            }
+        }
+        for record in other_header.extra {
+            if !header.extra.contains(&record) {
+                header.extra.push(record);
+            }
+        }
+
+        let mut data = self.data;
+        if options.single_section {
+            let mut sections = data.into_iter().chain(other.data);
+            let mut merged = sections.next().unwrap_or_default();
+            for section in sections {
+                merged.entities.extend(section.entities);
+            }
+            data = vec![merged];
+        } else {
+            data.extend(other.data);
+        }
+
+        Ok(Exchange {
+            header: header.to_records(),
+            anchor: self.anchor.into_iter().chain(other.anchor).collect(),
+            reference: self.reference.into_iter().chain(other.reference).collect(),
+            data,
+            signature: Vec::new(),
+        })
+    }
+}
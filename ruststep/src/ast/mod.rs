@@ -33,12 +33,62 @@
 //! │ Other Rust struct │
 //! └───────────────────┘
 //! ```
+//!
+//! Serialize
+//! ----------
+//!
+//! [serde::Serialize] is implemented for [Exchange], [DataSection], [EntityInstance], [Record], and [Parameter]
+//! to inspect parsed files in other formats, e.g. JSON.
+//! This representation is kept stable, but it is not read back by [serde::Deserialize] implementations.
+//!
+//! | AST                  | JSON                                                     |
+//! |:---------------------|:---------------------------------------------------------|
+//! | Integer, Real        | number                                                   |
+//! | String               | string                                                   |
+//! | List                 | array                                                    |
+//! | NotProvided (`$`)    | `null`                                                   |
+//! | Omitted (`*`)        | `{"omitted": true}`                                      |
+//! | Enumeration          | `{"enumeration": "T"}`                                   |
+//! | Binary               | `{"binary": "0F"}`                                       |
+//! | Typed                | `{"keyword": "A", "parameter": ...}`                     |
+//! | Ref                  | `{"entity": 1}`, `{"value": 1}`, `{"constant_entity": "X"}`, or `{"constant_value": "X"}` |
+//! | Record               | `{"keyword": "A", "parameters": [...]}`, with `!` for user-defined keyword |
+//! | EntityInstance       | `{"id": 1, "record": ...}`, or `{"id": 1, "records": [...]}` if complex |
+//! | DataSection          | `{"meta": [...], "entities": [...]}`                     |
+//! | Exchange             | `{"header": [...], "anchor": [...], "reference": [...], "data": [...], "signature": [...]}` |
+//!
+//! ```
+//! use ruststep::ast::EntityInstance;
+//! use std::str::FromStr;
+//!
+//! let instance = EntityInstance::from_str("#1 = A(#2, $, .T., B(1.0));").unwrap();
+//! assert_eq!(
+//!     serde_json::to_string(&instance).unwrap(),
+//!     r#"{"id":1,"record":{"keyword":"A","parameters":[{"entity":2},null,{"enumeration":"T"},{"keyword":"B","parameter":1.0}]}}"#
+//! );
+//! ```
 
+pub mod borrowed;
 pub mod de;
 pub mod ser;
 
-use crate::parser;
-use std::str::FromStr;
+mod builder;
+mod display;
+mod graph;
+mod index;
+mod merge;
+mod serialize;
+
+pub use builder::{DataSectionBuilder, ExchangeBuilder};
+pub use index::EntityIndex;
+pub use merge::MergeOptions;
+
+use crate::{id::IdAllocator, parser};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    ops::RangeInclusive,
+    str::FromStr,
+};
 
 /// AST portion
 pub trait AST: FromStr<Err = crate::error::Error> {
@@ -86,6 +136,50 @@ pub enum Name {
 }
 derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 
+impl Name {
+    /// Id of an entity instance name `#id`
+    pub fn entity_id(&self) -> Option<u64> {
+        match self {
+            Name::Entity(id) => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+/// Iterator over [Name]s in parameters in the document order, see [Parameter::names]
+///
+/// Nested lists and typed parameters are traversed without recursion,
+/// and thus deeply nested parameters do not overflow the stack.
+#[derive(Debug, Clone)]
+pub struct Names<'a> {
+    /// Parameters to be visited, the next one at the end
+    stack: Vec<&'a Parameter>,
+}
+
+impl<'a> Names<'a> {
+    fn new(parameters: impl DoubleEndedIterator<Item = &'a Parameter>) -> Self {
+        Names {
+            stack: parameters.rev().collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for Names<'a> {
+    type Item = &'a Name;
+
+    fn next(&mut self) -> Option<&'a Name> {
+        while let Some(parameter) = self.stack.pop() {
+            match parameter {
+                Parameter::Ref(name) => return Some(name),
+                Parameter::Typed { parameter, .. } => self.stack.push(parameter),
+                Parameter::List(parameters) => self.stack.extend(parameters.iter().rev()),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
 /// A struct typed in EXPRESS schema, e.g. `A(1.0, 2.0)`
 ///
 /// FromStr
@@ -100,9 +194,16 @@ derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 ///     record,
 ///     Record {
 ///         name: "A".to_string(),
+///         user_defined: false,
 ///         parameter: vec![Parameter::Integer(1), Parameter::Integer(2)].into(),
 ///     }
-/// )
+/// );
+///
+/// // `!` of user-defined keyword is kept as a flag, and written back by `Display`
+/// let record = Record::from_str("!MY_EXTENSION(1)").unwrap();
+/// assert_eq!(record.name, "MY_EXTENSION");
+/// assert!(record.user_defined);
+/// assert_eq!(record.to_string(), "!MY_EXTENSION(1)");
 /// ```
 ///
 /// Deserialize as a map
@@ -173,6 +274,10 @@ derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 ///     y: i32,
 /// }
 /// assert!(B::deserialize(&p).is_err());
+///
+/// // User-defined keyword never matches to a struct
+/// let p = Record::from_str("!DATA_KEYWORD(1, 2)").unwrap();
+/// assert!(A::deserialize(&p).is_err());
 /// ```
 ///
 /// Internal mapping to complex entity instance
@@ -230,11 +335,71 @@ derive_ast_from_str!(Name, parser::token::rhs_occurrence_name);
 ///
 #[derive(Debug, Clone, PartialEq)]
 pub struct Record {
+    /// Keyword without the leading `!` of user-defined keyword
     pub name: String,
+    /// Whether the keyword is user-defined, e.g. `!MY_EXTENSION`
+    pub user_defined: bool,
     pub parameter: Parameter,
 }
 derive_ast_from_str!(Record, parser::exchange::simple_record);
 
+impl Record {
+    /// Keyword as written in the exchange structure, i.e. with `!` if user-defined
+    ///
+    /// ```
+    /// use ruststep::ast::Record;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Record::from_str("A(1)").unwrap().keyword(), "A");
+    /// assert_eq!(Record::from_str("!A(1)").unwrap().keyword(), "!A");
+    /// ```
+    pub fn keyword(&self) -> String {
+        if self.user_defined {
+            format!("!{}", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Parameter at `index` in the declaration order of attributes
+    ///
+    /// ```
+    /// use ruststep::ast::{Parameter, Record};
+    /// use std::str::FromStr;
+    ///
+    /// let mut record = Record::from_str("A(1, 'x')").unwrap();
+    /// assert_eq!(record.attribute(1), Some(&Parameter::String("x".to_string())));
+    /// assert_eq!(record.attribute(2), None);
+    ///
+    /// *record.attribute_mut(0).unwrap() = Parameter::NotProvided;
+    /// assert_eq!(record.to_string(), "A($,'x')");
+    /// ```
+    pub fn attribute(&self, index: usize) -> Option<&Parameter> {
+        match &self.parameter {
+            Parameter::List(parameters) => parameters.get(index),
+            _ => None,
+        }
+    }
+
+    /// Names referred from the parameters in the document order, see [Parameter::names]
+    pub fn names(&self) -> Names<'_> {
+        self.parameter.names()
+    }
+
+    /// Ids of entity instances referred from the parameters in the document order
+    pub fn entity_refs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.parameter.entity_refs()
+    }
+
+    /// Mutable version of [Record::attribute]
+    pub fn attribute_mut(&mut self, index: usize) -> Option<&mut Parameter> {
+        match &mut self.parameter {
+            Parameter::List(parameters) => parameters.get_mut(index),
+            _ => None,
+        }
+    }
+}
+
 /// A set of [Record] mapping to complex entity instance,
 /// e.g. `(A(1) B(2.0) C("3"))`
 ///
@@ -251,6 +416,7 @@ derive_ast_from_str!(Record, parser::exchange::simple_record);
 ///     SubSuperRecord(vec![
 ///         Record {
 ///             name: "A".to_string(),
+///             user_defined: false,
 ///             parameter: vec![
 ///                 Parameter::Integer(1),
 ///                 Parameter::Integer(2)
@@ -258,6 +424,7 @@ derive_ast_from_str!(Record, parser::exchange::simple_record);
 ///         },
 ///         Record {
 ///             name: "B".to_string(),
+///             user_defined: false,
 ///             parameter: vec![
 ///                 Parameter::Integer(3),
 ///                 Parameter::Integer(4)
@@ -398,7 +565,7 @@ impl<'a> FromIterator<&'a Record> for SubSuperRecord {
 /// let data_section = DataSection::from_str(input).unwrap();
 /// dbg!(data_section);
 /// ```
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct DataSection {
     /// Metadata
     pub meta: Vec<Parameter>,
@@ -407,6 +574,285 @@ pub struct DataSection {
 }
 derive_ast_from_str!(DataSection, parser::exchange::data_section);
 
+impl DataSection {
+    /// Name of section, i.e. the first parameter of `DATA('name', ('SCHEMA'));`
+    pub fn name(&self) -> Option<&str> {
+        match self.meta.first() {
+            Some(Parameter::String(name)) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Instance of `id`
+    pub fn instance(&self, id: u64) -> Option<&EntityInstance> {
+        self.entities.iter().find(|e| e.id() == id)
+    }
+
+    /// Mutable version of [DataSection::instance]
+    pub fn instance_mut(&mut self, id: u64) -> Option<&mut EntityInstance> {
+        self.entities.iter_mut().find(|e| e.id() == id)
+    }
+
+    /// Index of instances by id for repeated lookups, unlike [DataSection::instance] scanning all instances
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DuplicatedEntity](crate::error::Error::DuplicatedEntity) if two instances share an id
+    ///
+    pub fn index(&self) -> crate::error::Result<EntityIndex<'_>> {
+        EntityIndex::new(self)
+    }
+
+    /// Records of `keyword` with the id of their instances in the order of this section
+    ///
+    /// Each part of a complex entity instance is matched separately.
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #1 = A(1.0);
+    ///   #2 = B(#1);
+    ///   #3 = (A(2.0) C(#2));
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let a: Vec<_> = section
+    ///     .entities_by_keyword("A")
+    ///     .map(|(id, record)| (id, record.parameter.to_string()))
+    ///     .collect();
+    /// assert_eq!(a, [(1, "(1.0)".to_string()), (3, "(2.0)".to_string())]);
+    /// ```
+    pub fn entities_by_keyword<'a>(
+        &'a self,
+        keyword: &'a str,
+    ) -> impl Iterator<Item = (u64, &'a Record)> + 'a {
+        self.entities.iter().flat_map(move |instance| {
+            instance
+                .records()
+                .iter()
+                .filter(move |record| !record.user_defined && record.name == keyword)
+                .map(move |record| (instance.id(), record))
+        })
+    }
+
+    /// Instances in `roots` and those referred from them transitively, keeping the order in this section
+    ///
+    /// References to instances not in this section are kept as is.
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #1 = A(1.0);
+    ///   #2 = A(2.0);
+    ///   #3 = B(#1, #4);
+    ///   #4 = C(#3);
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let sub = section.extract(&[4]);
+    /// let ids: Vec<u64> = sub.entities.iter().map(|e| e.id()).collect();
+    /// assert_eq!(ids, vec![1, 3, 4]);
+    /// ```
+    pub fn extract(&self, roots: &[u64]) -> DataSection {
+        let reached = self.reachable_from(roots);
+        DataSection {
+            meta: self.meta.clone(),
+            entities: self
+                .entities
+                .iter()
+                .filter(|e| reached.contains(&e.id()))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Append `instance` at the end of this section
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DuplicatedEntity](crate::error::Error::DuplicatedEntity) if an instance of the same id exists
+    ///
+    pub fn insert(&mut self, instance: EntityInstance) -> crate::error::Result<()> {
+        if self.instance(instance.id()).is_some() {
+            return Err(crate::error::Error::DuplicatedEntity(instance.id()));
+        }
+        self.entities.push(instance);
+        Ok(())
+    }
+
+    /// Remove the instance of `id`, keeping the order of the others
+    pub fn remove(&mut self, id: u64) -> Option<EntityInstance> {
+        let position = self.entities.iter().position(|e| e.id() == id)?;
+        Some(self.entities.remove(position))
+    }
+
+    /// Replace references to `#from` by `#to`, and returns the number of replaced references
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let mut section = DataSection::from_str("DATA; #1 = A(#2, (#2, B(#2)), #3); ENDSEC;").unwrap();
+    /// assert_eq!(section.rewrite_references(2, 4), 3);
+    /// assert_eq!(section.entities[0].to_string(), "#1 = A(#4,(#4,B(#4)),#3);");
+    /// ```
+    pub fn rewrite_references(&mut self, from: u64, to: u64) -> usize {
+        let mut count = 0;
+        for instance in &mut self.entities {
+            count += instance.rewrite_references(from, to);
+        }
+        count
+    }
+
+    /// Assign consecutive ids from `start` to all instances, and returns the map from old ids to new ids
+    ///
+    /// Ids are assigned in [DataSection::topological_order], or in the order of this section if references are cyclic,
+    /// and the instances are sorted by the new ids.
+    /// References to instances not in this section are kept as is.
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let mut section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #4 = A(#90211, #90374);
+    ///   #90211 = B(#90374);
+    ///   #90374 = C((#1000, 1.0));
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let ids = section.renumber(1).unwrap();
+    /// assert_eq!(ids, [(90374, 1), (90211, 2), (4, 3)].into_iter().collect());
+    /// assert_eq!(
+    ///     section.to_string(),
+    ///     "DATA;\n#1 = C((#1000,1.0));\n#2 = B(#1);\n#3 = A(#2,#1);\nENDSEC;"
+    /// );
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DuplicatedEntity](crate::error::Error::DuplicatedEntity) if two instances share an id
+    /// - [Error::IdExhausted](crate::error::Error::IdExhausted) if new ids exceed [u64::MAX]
+    ///
+    /// The section is unchanged in error cases.
+    pub fn renumber(&mut self, start: u64) -> crate::error::Result<HashMap<u64, u64>> {
+        let ids = self.renumbering(&mut IdAllocator::new().start(start))?;
+        self.apply_renumbering(&ids);
+        Ok(ids)
+    }
+
+    /// Map from old ids to new ids allocated by `ids`, see [DataSection::renumber]
+    fn renumbering(&self, ids: &mut IdAllocator) -> crate::error::Result<HashMap<u64, u64>> {
+        self.index()?;
+        let order = self
+            .topological_order()
+            .unwrap_or_else(|_| self.entities.iter().map(|e| e.id()).collect());
+        order
+            .into_iter()
+            .map(|old| Ok((old, ids.allocate()?)))
+            .collect()
+    }
+
+    fn apply_renumbering(&mut self, ids: &HashMap<u64, u64>) {
+        for instance in &mut self.entities {
+            instance.renumber(ids);
+        }
+        self.entities.sort_by_key(|e| e.id());
+    }
+
+    /// Remove instances not reachable from `roots`, and returns the removed instances in the order of this section
+    ///
+    /// ```
+    /// use ruststep::ast::DataSection;
+    /// use std::str::FromStr;
+    ///
+    /// let mut section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #1 = A(1.0);
+    ///   #2 = A(2.0);
+    ///   #3 = B(#1);
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let removed = section.gc(&[3]);
+    /// assert_eq!(removed.len(), 1);
+    /// assert_eq!(removed[0].id(), 2);
+    /// assert_eq!(section.entities.len(), 2);
+    /// ```
+    pub fn gc(&mut self, roots: &[u64]) -> Vec<EntityInstance> {
+        let reached = self.reachable_from(roots);
+        let (kept, removed) = std::mem::take(&mut self.entities)
+            .into_iter()
+            .partition(|e| reached.contains(&e.id()));
+        self.entities = kept;
+        removed
+    }
+
+    /// Statistics of this section without creating tables
+    pub fn summary(&self) -> SectionSummary {
+        let mut summary = SectionSummary {
+            name: self.name().map(str::to_string),
+            ..Default::default()
+        };
+        for instance in &self.entities {
+            // Count each partial entity
+            for record in instance.records() {
+                *summary
+                    .keyword_counts
+                    .entry(record.name.clone())
+                    .or_default() += 1;
+            }
+            let id = instance.id();
+            summary.id_range = Some(match summary.id_range {
+                Some(range) => (*range.start()).min(id)..=(*range.end()).max(id),
+                None => id..=id,
+            });
+            summary.num_instances += 1;
+        }
+        summary
+    }
+}
+
+/// Statistics of a [DataSection]
+///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let section = DataSection::from_str(r#"
+/// DATA('geometry', ('SCHEMA'));
+///   #3 = A(1.0, 2.0);
+///   #5 = A(3.0, 4.0);
+///   #8 = B(#3);
+/// ENDSEC;
+/// "#).unwrap();
+///
+/// let summary = section.summary();
+/// assert_eq!(summary.name.as_deref(), Some("geometry"));
+/// assert_eq!(summary.num_instances, 3);
+/// assert_eq!(summary.keyword_counts["A"], 2);
+/// assert_eq!(summary.id_range, Some(3..=8));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SectionSummary {
+    /// Name of section, see [DataSection::name]
+    pub name: Option<String>,
+    /// Number of entity instances
+    pub num_instances: usize,
+    /// Number of records for each keyword.
+    /// Each part of a complex entity instance is counted separately.
+    pub keyword_counts: BTreeMap<String, usize>,
+    /// Smallest and largest entity instance ids. `None` for an empty section.
+    pub id_range: Option<RangeInclusive<u64>>,
+}
+
 /// Primitive value type in STEP data
 ///
 /// Inline struct or list can be nested, i.e. `Parameter` can be a tree.
@@ -456,13 +902,29 @@ derive_ast_from_str!(DataSection, parser::exchange::data_section);
 /// | Integer     | i64              |
 /// | Real        | f64              |
 /// | String      | string           |
+/// | Binary      | bytes, or [Binary](crate::primitive::Binary) through newtype_struct |
 /// | List        | seq              |
 /// | NotProvided | option (always none)|
-/// | Omitted     | option (always none)|
+/// | Omitted     | unit, or none through option |
 /// | Enumeration | unit_variant (through [serde::de::value::StringDeserializer])|
 /// | Typed       | map (through [de::RecordDeserializer])|
 /// | Ref         | newtype_variant  |
 ///
+/// `$` is always [Parameter::NotProvided], and its meaning is determined by the type it is deserialized into:
+///
+/// | `$` appears as                  | Deserialized into            |
+/// |:--------------------------------|:-----------------------------|
+/// | an `OPTIONAL` attribute         | `None` of `Option<T>` field  |
+/// | an element of `ARRAY OF OPTIONAL` | `None` of `Vec<Option<T>>` element |
+/// | a typed parameter, e.g. `A($)`  | `None` of the value of the map |
+/// | otherwise                       | error, since `T` does not accept none |
+///
+/// [ser::to_record] and [std::fmt::Display] write `None` and [Parameter::NotProvided] back as `$`.
+///
+/// `*` is always [Parameter::Omitted], which is used for an attribute redeclared as `DERIVE` in a subtype.
+/// It is none for `Option<T>` as `$`, and unit otherwise, e.g. for `()` or [Parameter] itself.
+/// [std::fmt::Display] writes it back as `*`, and thus `$` and `*` are written back as they are read.
+///
 #[derive(Debug, Clone, PartialEq, derive_more::From)]
 pub enum Parameter {
     /// Corresponding to `TYPED_PARAMETER` in WSN:
@@ -577,17 +1039,35 @@ pub enum Parameter {
 
     /// Real number
     ///
+    /// `raw` is the text of the number in the input if [ParseOptions::keep_real_text](crate::parser::ParseOptions::keep_real_text) is enabled.
+    /// [std::fmt::Display] writes `raw` as it is if `raw` still represents `value` in ISO-10303-21,
+    /// i.e. unless `value` is modified after parsing.
+    ///
     /// FromStr
     /// --------
     /// ```
     /// use std::str::FromStr;
-    /// use ruststep::ast::Parameter;
+    /// use nom::{Finish, Parser};
+    /// use ruststep::{ast::Parameter, parser::{exchange, ParseOptions}};
     ///
     /// let p = Parameter::from_str("1.0").unwrap();
-    /// assert_eq!(p, Parameter::Real(1.0));
+    /// assert_eq!(p, Parameter::real(1.0));
+    ///
+    /// let options = ParseOptions { keep_real_text: true, ..Default::default() };
+    /// let (_, mut p) = exchange::parameter_with(&options)
+    ///     .parse("0.000000000001")
+    ///     .finish()
+    ///     .unwrap();
+    /// assert_eq!(p, Parameter::Real { value: 1e-12, raw: Some("0.000000000001".to_string()) });
+    /// assert_eq!(p.to_string(), "0.000000000001");
+    ///
+    /// // Written by the value if modified
+    /// if let Parameter::Real { value, .. } = &mut p {
+    ///     *value *= 2.0;
+    /// }
+    /// assert_eq!(p.to_string(), "2.E-12");
     /// ```
-    #[from]
-    Real(f64),
+    Real { value: f64, raw: Option<String> },
 
     /// string literal
     ///
@@ -603,6 +1083,35 @@ pub enum Parameter {
     #[from]
     String(String),
 
+    /// Binary, a sequence of bits
+    ///
+    /// FromStr
+    /// --------
+    /// ```
+    /// use std::str::FromStr;
+    /// use ruststep::{ast::Parameter, primitive::Binary};
+    ///
+    /// // `3` unused bits of the first hex digit `F`, and `0F` for the remaining 8 bits
+    /// let p = Parameter::from_str("\"3F0F\"").unwrap();
+    /// assert_eq!(p, Parameter::Binary(Binary::new(vec![0x01, 0x0F], 9)));
+    ///
+    /// let p = Parameter::from_str("\"\"").unwrap();
+    /// assert_eq!(p, Parameter::Binary(Binary::default()));
+    /// ```
+    ///
+    /// Deserialize
+    /// ------------
+    /// ```
+    /// use std::str::FromStr;
+    /// use ruststep::{ast::Parameter, primitive::Binary};
+    /// use serde::Deserialize;
+    ///
+    /// let p = Parameter::from_str("\"0ABCD\"").unwrap();
+    /// assert_eq!(Binary::deserialize(&p).unwrap(), Binary::from(vec![0xAB, 0xCD]));
+    /// ```
+    #[from]
+    Binary(crate::primitive::Binary),
+
     /// Enumeration defined in EXPRESS schema, like `.TRUE.`
     ///
     /// FromStr
@@ -643,7 +1152,7 @@ pub enum Parameter {
     ///
     /// let p = Parameter::from_str("(1.0, 2, 'STRING')").unwrap();
     /// assert_eq!(p, Parameter::List(vec![
-    ///   Parameter::Real(1.0),
+    ///   Parameter::real(1.0),
     ///   Parameter::Integer(2),
     ///   Parameter::String("STRING".to_string()),
     /// ]));
@@ -722,6 +1231,8 @@ pub enum Parameter {
     ///
     /// let p = Parameter::Omitted;
     /// assert_eq!(Option::<i64>::deserialize(&p).unwrap(), None);
+    /// assert_eq!(<()>::deserialize(&p).unwrap(), ());
+    /// assert!(i64::deserialize(&p).is_err());
     /// ```
     Omitted,
 }
@@ -732,12 +1243,144 @@ impl Parameter {
     }
 
     pub fn real(x: f64) -> Self {
-        Parameter::Real(x)
+        Parameter::Real {
+            value: x,
+            raw: None,
+        }
     }
 
     pub fn string(s: &str) -> Self {
         Parameter::String(s.to_string())
     }
+
+    /// References of entity instances, value instances, and constants in the document order,
+    /// traversing nested lists and typed parameters
+    ///
+    /// ```
+    /// use ruststep::ast::{Name, Parameter};
+    /// use std::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("(#1, A((@2, #ORIGIN)), ((#3)))").unwrap();
+    /// assert_eq!(
+    ///     p.names().collect::<Vec<_>>(),
+    ///     [
+    ///         &Name::Entity(1),
+    ///         &Name::Value(2),
+    ///         &Name::ConstantEntity("ORIGIN".to_string()),
+    ///         &Name::Entity(3)
+    ///     ]
+    /// );
+    /// assert_eq!(p.entity_refs().collect::<Vec<_>>(), [1, 3]);
+    /// ```
+    pub fn names(&self) -> Names<'_> {
+        Names::new(std::iter::once(self))
+    }
+
+    /// Ids of entity instances referred in the document order, i.e. `#id` in [Parameter::names]
+    pub fn entity_refs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.names().filter_map(Name::entity_id)
+    }
+
+    /// Strip [Parameter::Typed] wrappers, e.g. `'x'` of `LABEL(TEXT('x'))`
+    ///
+    /// ```
+    /// use ruststep::ast::Parameter;
+    /// use std::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("LABEL(TEXT('x'))").unwrap();
+    /// assert_eq!(p.unwrap_typed(), &Parameter::string("x"));
+    /// assert_eq!(p.as_string(), Some("x"));
+    /// ```
+    pub fn unwrap_typed(&self) -> &Parameter {
+        let mut current = self;
+        while let Parameter::Typed { parameter, .. } = current {
+            current = parameter;
+        }
+        current
+    }
+
+    /// String value, through [Parameter::unwrap_typed]
+    pub fn as_string(&self) -> Option<&str> {
+        match self.unwrap_typed() {
+            Parameter::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Real value, also accepting an integer, through [Parameter::unwrap_typed]
+    ///
+    /// ```
+    /// use ruststep::ast::Parameter;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Parameter::from_str("1.5").unwrap().as_real(), Some(1.5));
+    /// assert_eq!(Parameter::from_str("LENGTH_MEASURE(2)").unwrap().as_real(), Some(2.0));
+    /// assert_eq!(Parameter::from_str("'2'").unwrap().as_real(), None);
+    /// ```
+    pub fn as_real(&self) -> Option<f64> {
+        match self.unwrap_typed() {
+            Parameter::Real { value, .. } => Some(*value),
+            Parameter::Integer(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// Integer value, through [Parameter::unwrap_typed]
+    pub fn as_integer(&self) -> Option<i64> {
+        match self.unwrap_typed() {
+            Parameter::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Elements of a list, through [Parameter::unwrap_typed]
+    pub fn as_list(&self) -> Option<&[Parameter]> {
+        match self.unwrap_typed() {
+            Parameter::List(parameters) => Some(parameters),
+            _ => None,
+        }
+    }
+
+    /// Id of an entity instance reference `#id`, through [Parameter::unwrap_typed]
+    ///
+    /// ```
+    /// use ruststep::ast::Parameter;
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Parameter::from_str("#12").unwrap().as_entity_ref(), Some(12));
+    /// assert_eq!(Parameter::from_str("@12").unwrap().as_entity_ref(), None);
+    /// ```
+    pub fn as_entity_ref(&self) -> Option<u64> {
+        match self.unwrap_typed() {
+            Parameter::Ref(Name::Entity(id)) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Element at `path` in nested lists, e.g. `[1, 0]` for `#2` in `(#1, (#2, #3))`
+    ///
+    /// Typed parameters are unwrapped on the way, and an empty path returns `self`.
+    ///
+    /// ```
+    /// use ruststep::ast::Parameter;
+    /// use std::str::FromStr;
+    ///
+    /// let p = Parameter::from_str("(#1, (#2, #3), A(('x')))").unwrap();
+    /// assert_eq!(p.get(&[1, 0]).and_then(Parameter::as_entity_ref), Some(2));
+    /// assert_eq!(p.get(&[2, 0]).and_then(Parameter::as_string), Some("x"));
+    /// assert_eq!(p.get(&[1, 2]), None);
+    /// assert_eq!(p.get(&[0, 0]), None);
+    /// ```
+    pub fn get(&self, path: &[usize]) -> Option<&Parameter> {
+        path.iter()
+            .try_fold(self, |parameter, index| parameter.as_list()?.get(*index))
+    }
+}
+
+impl From<f64> for Parameter {
+    fn from(value: f64) -> Self {
+        Parameter::real(value)
+    }
 }
 
 impl std::iter::FromIterator<Parameter> for Parameter {
@@ -770,6 +1413,287 @@ pub struct Exchange {
 }
 derive_ast_from_str!(Exchange, parser::exchange::exchange_file);
 
+impl Exchange {
+    /// [SectionSummary] of each data section in the order of [Exchange::data]
+    pub fn section_summaries(&self) -> Vec<SectionSummary> {
+        self.data.iter().map(DataSection::summary).collect()
+    }
+
+    /// Read [Exchange::header] into [crate::header::Header]
+    pub fn header_typed(&self) -> crate::error::Result<crate::header::Header> {
+        crate::header::Header::from_records(&self.header)
+    }
+
+    /// Assign consecutive ids from `start` to instances in all data sections,
+    /// and returns the map from old ids to new ids
+    ///
+    /// Each data section is renumbered as [DataSection::renumber] one after another,
+    /// and then entity names in the `REFERENCE` section follow.
+    /// References across data sections and from the `ANCHOR` section are rewritten.
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use std::str::FromStr;
+    ///
+    /// let mut exchange = Exchange::from_str(r#"ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION((''), '3;1');
+    /// ENDSEC;
+    /// ANCHOR;
+    /// <origin> = #20;
+    /// ENDSEC;
+    /// REFERENCE;
+    /// #1 = <other.stp#bolt>;
+    /// ENDSEC;
+    /// DATA;
+    /// #20 = POINT(0.0);
+    /// ENDSEC;
+    /// DATA;
+    /// #30 = ASSEMBLY(#20, #1);
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// "#).unwrap();
+    ///
+    /// let ids = exchange.renumber(1).unwrap();
+    /// assert_eq!(ids, [(20, 1), (30, 2), (1, 3)].into_iter().collect());
+    /// assert_eq!(exchange.anchor[0].to_string(), "<origin> = #1;");
+    /// assert_eq!(exchange.reference[0].to_string(), "#3 = <other.stp#bolt>;");
+    /// assert_eq!(exchange.data[1].entities[0].to_string(), "#2 = ASSEMBLY(#1,#3);");
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DuplicatedEntity](crate::error::Error::DuplicatedEntity) if two instances share an id
+    /// - [Error::IdExhausted](crate::error::Error::IdExhausted) if new ids exceed [u64::MAX]
+    ///
+    /// The exchange is unchanged in error cases.
+    pub fn renumber(&mut self, start: u64) -> crate::error::Result<HashMap<u64, u64>> {
+        self.renumber_with(&mut IdAllocator::new().start(start))
+    }
+
+    /// Assign ids allocated by `allocator` in the same order as [Exchange::renumber],
+    /// e.g. to skip ranges reserved by [IdAllocator::reserve_range]
+    ///
+    /// ```
+    /// use ruststep::{ast::*, id::IdAllocator};
+    /// use std::str::FromStr;
+    ///
+    /// let mut exchange = Exchange::from_str(
+    ///     "ISO-10303-21; HEADER; A(); ENDSEC; DATA; #1 = A(1.0); #2 = B(#1); ENDSEC; END-ISO-10303-21;",
+    /// )
+    /// .unwrap();
+    /// let mut allocator = IdAllocator::new().start(5);
+    /// allocator.reserve_range(6..10);
+    /// exchange.renumber_with(&mut allocator).unwrap();
+    /// assert_eq!(exchange.data[0].entities[1].to_string(), "#10 = B(#5);");
+    /// assert_eq!(allocator.allocate().unwrap(), 11);
+    /// ```
+    ///
+    /// Errors are the same as [Exchange::renumber], and neither the exchange nor `allocator` is changed in error cases.
+    pub fn renumber_with(
+        &mut self,
+        allocator: &mut IdAllocator,
+    ) -> crate::error::Result<HashMap<u64, u64>> {
+        let mut next = allocator.clone();
+        let mut ids = HashMap::new();
+        let mut insert = |old: u64, new: u64| match ids.insert(old, new) {
+            Some(_) => Err(crate::error::Error::DuplicatedEntity(old)),
+            None => Ok(()),
+        };
+        for section in &self.data {
+            for (old, new) in section.renumbering(&mut next)? {
+                insert(old, new)?;
+            }
+        }
+        for entry in &self.reference {
+            if let Name::Entity(id) = entry.name {
+                insert(id, next.allocate()?)?;
+            }
+        }
+        *allocator = next;
+
+        for section in &mut self.data {
+            section.apply_renumbering(&ids);
+        }
+        for entry in &mut self.reference {
+            if let Name::Entity(id) = &mut entry.name {
+                *id = ids[id];
+            }
+        }
+        for anchor in &mut self.anchor {
+            anchor.item.renumber(&ids);
+            for (_, item) in &mut anchor.tags {
+                item.renumber(&ids);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// New exchange of instances in `roots` and those referred from them transitively
+    ///
+    /// - Each data section keeps the extracted instances in its order, see [DataSection::extract]
+    /// - `REFERENCE` entries and `ANCHOR` entries are kept if they refer only extracted instances
+    /// - `FILE_DESCRIPTION` in the header gets a paragraph noting the extraction
+    /// - `SIGNATURE` sections are dropped since they do not sign the new content
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use std::str::FromStr;
+    ///
+    /// let exchange = Exchange::from_str(r#"ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION(('assembly'), '2;1');
+    /// FILE_NAME('assembly.stp', '2024-01-01T00:00:00', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('SCHEMA'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = POINT(0.0);
+    /// #2 = POINT(1.0);
+    /// #3 = PRODUCT('bolt', #1);
+    /// #4 = PRODUCT('nut', #2);
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// "#).unwrap();
+    ///
+    /// let bolt = exchange.extract(&[3]).unwrap();
+    /// let ids: Vec<u64> = bolt.data[0].entities.iter().map(|e| e.id()).collect();
+    /// assert_eq!(ids, [1, 3]);
+    /// assert_eq!(
+    ///     bolt.header_typed().unwrap().file_description.description,
+    ///     ["assembly", "Extracted #3 from assembly.stp"]
+    /// );
+    ///
+    /// assert_eq!(
+    ///     exchange.extract(&[3, 5]).unwrap_err().to_string(),
+    ///     "References to undefined entity instances: #5"
+    /// );
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DanglingReferences](crate::error::Error::DanglingReferences) with the ids in ascending order
+    ///   if `roots` or the extracted instances refer instances not defined in this exchange
+    /// - [Error::InvalidHeader](crate::error::Error::InvalidHeader) if the header cannot be read by [Exchange::header_typed]
+    ///
+    pub fn extract(&self, roots: &[u64]) -> crate::error::Result<Exchange> {
+        let instances: HashMap<u64, &EntityInstance> = self
+            .data
+            .iter()
+            .flat_map(|section| &section.entities)
+            .map(|instance| (instance.id(), instance))
+            .collect();
+        let external: HashSet<u64> = self
+            .reference
+            .iter()
+            .filter_map(|entry| entry.name.entity_id())
+            .collect();
+
+        let mut reached = HashSet::new();
+        let mut dangling = BTreeSet::new();
+        let mut stack = roots.to_vec();
+        while let Some(id) = stack.pop() {
+            if !reached.insert(id) {
+                continue;
+            }
+            match instances.get(&id) {
+                Some(instance) => stack.extend(instance.entity_refs()),
+                None if external.contains(&id) => {}
+                None => {
+                    dangling.insert(id);
+                }
+            }
+        }
+        if !dangling.is_empty() {
+            return Err(crate::error::Error::DanglingReferences(
+                dangling.into_iter().collect(),
+            ));
+        }
+
+        let mut header = self.header_typed()?;
+        let roots = roots
+            .iter()
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let note = match header.file_name.name.as_str() {
+            "" => format!("Extracted {}", roots),
+            name => format!("Extracted {} from {}", roots, name),
+        };
+        let description = &mut header.file_description.description;
+        if description.len() == 1 && description[0].is_empty() {
+            description.clear();
+        }
+        description.push(note);
+
+        let extracted = |name: &Name| name.entity_id().map_or(true, |id| reached.contains(&id));
+        Ok(Exchange {
+            header: header.to_records(),
+            anchor: self
+                .anchor
+                .iter()
+                .filter(|anchor| {
+                    anchor.item.names().all(extracted)
+                        && anchor
+                            .tags
+                            .iter()
+                            .all(|(_, item)| item.names().all(extracted))
+                })
+                .cloned()
+                .collect(),
+            reference: self
+                .reference
+                .iter()
+                .filter(|entry| extracted(&entry.name))
+                .cloned()
+                .collect(),
+            data: self
+                .data
+                .iter()
+                .map(|section| DataSection {
+                    meta: section.meta.clone(),
+                    entities: section
+                        .entities
+                        .iter()
+                        .filter(|e| reached.contains(&e.id()))
+                        .cloned()
+                        .collect(),
+                })
+                .collect(),
+            signature: Vec::new(),
+        })
+    }
+
+    /// Resources of [Exchange::reference] in the order of the entries
+    ///
+    /// [URI::document] is the companion file to be loaded to resolve the reference:
+    ///
+    /// ```
+    /// use ruststep::ast::*;
+    /// use std::str::FromStr;
+    ///
+    /// let exchange = Exchange::from_str(r#"ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION((''), '3;1');
+    /// ENDSEC;
+    /// REFERENCE;
+    /// #10 = <file2.stp#bolt>;
+    /// @11 = <file2.stp#steel>;
+    /// #12 = <#nut>;
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = ASSEMBLY((#10, #12));
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// "#).unwrap();
+    ///
+    /// let documents: Vec<_> = exchange.external_references().map(URI::document).collect();
+    /// assert_eq!(documents, ["file2.stp", "file2.stp", ""]);
+    /// ```
+    pub fn external_references(&self) -> impl Iterator<Item = &URI> {
+        self.reference.iter().map(|entry| &entry.resource)
+    }
+}
+
 /// Each line of data section
 #[derive(Debug, Clone, PartialEq)]
 pub enum EntityInstance {
@@ -778,6 +1702,122 @@ pub enum EntityInstance {
 }
 derive_ast_from_str!(EntityInstance, parser::exchange::entity_instance);
 
+impl EntityInstance {
+    pub fn id(&self) -> u64 {
+        match self {
+            EntityInstance::Simple { id, .. } | EntityInstance::Complex { id, .. } => *id,
+        }
+    }
+
+    /// The record of a simple entity instance, or partial records of a complex entity instance
+    pub fn records(&self) -> &[Record] {
+        match self {
+            EntityInstance::Simple { record, .. } => std::slice::from_ref(record),
+            EntityInstance::Complex { subsuper, .. } => &subsuper.0,
+        }
+    }
+
+    /// The record of a simple entity instance. `None` for a complex entity instance.
+    pub fn record_mut(&mut self) -> Option<&mut Record> {
+        match self {
+            EntityInstance::Simple { record, .. } => Some(record),
+            EntityInstance::Complex { .. } => None,
+        }
+    }
+
+    /// Mutable version of [EntityInstance::records]
+    pub fn records_mut(&mut self) -> &mut [Record] {
+        match self {
+            EntityInstance::Simple { record, .. } => std::slice::from_mut(record),
+            EntityInstance::Complex { subsuper, .. } => &mut subsuper.0,
+        }
+    }
+
+    /// Replace references to `#from` by `#to`, see [DataSection::rewrite_references]
+    pub fn rewrite_references(&mut self, from: u64, to: u64) -> usize {
+        self.rewrite_with(&|id| (id == from).then_some(to))
+    }
+
+    /// Replace the id of this instance and references by `ids`.
+    /// Ids not in `ids` are kept as is.
+    ///
+    /// ```
+    /// use ruststep::ast::EntityInstance;
+    /// use std::str::FromStr;
+    ///
+    /// let mut instance = EntityInstance::from_str("#4 = A(#1, (#4, B(#2)));").unwrap();
+    /// instance.renumber(&[(4, 2), (2, 1), (1, 4)].into_iter().collect());
+    /// assert_eq!(instance.to_string(), "#2 = A(#4,(#2,B(#1)));");
+    /// ```
+    pub fn renumber(&mut self, ids: &HashMap<u64, u64>) {
+        match self {
+            EntityInstance::Simple { id, .. } | EntityInstance::Complex { id, .. } => {
+                if let Some(new) = ids.get(id) {
+                    *id = *new;
+                }
+            }
+        }
+        self.rewrite_with(&|id| ids.get(&id).copied());
+    }
+
+    /// Replace references for which `f` returns `Some`, and returns the number of replaced references
+    fn rewrite_with(&mut self, f: &dyn Fn(u64) -> Option<u64>) -> usize {
+        fn rewrite(parameter: &mut Parameter, f: &dyn Fn(u64) -> Option<u64>) -> usize {
+            match parameter {
+                Parameter::Ref(Name::Entity(id)) => match f(*id) {
+                    Some(new) => {
+                        *id = new;
+                        1
+                    }
+                    None => 0,
+                },
+                Parameter::Typed { parameter, .. } => rewrite(parameter, f),
+                Parameter::List(parameters) => parameters.iter_mut().map(|p| rewrite(p, f)).sum(),
+                _ => 0,
+            }
+        }
+        let records = match self {
+            EntityInstance::Simple { record, .. } => std::slice::from_mut(record),
+            EntityInstance::Complex { subsuper, .. } => &mut subsuper.0,
+        };
+        records
+            .iter_mut()
+            .map(|record| rewrite(&mut record.parameter, f))
+            .sum()
+    }
+
+    /// Ids of entity instances referred from this instance in the order of appearance
+    ///
+    /// ```
+    /// use ruststep::ast::EntityInstance;
+    /// use std::str::FromStr;
+    ///
+    /// let instance = EntityInstance::from_str("#4 = A(#1, (#3, B(#2)), @5);").unwrap();
+    /// assert_eq!(instance.references(), vec![1, 3, 2]);
+    /// ```
+    pub fn references(&self) -> Vec<u64> {
+        self.entity_refs().collect()
+    }
+
+    /// Names referred from the records of this instance in the document order, see [Parameter::names]
+    pub fn names(&self) -> Names<'_> {
+        Names::new(self.records().iter().map(|record| &record.parameter))
+    }
+
+    /// Ids of entity instances referred from the records of this instance in the document order
+    ///
+    /// ```
+    /// use ruststep::ast::EntityInstance;
+    /// use std::str::FromStr;
+    ///
+    /// let instance = EntityInstance::from_str("#4 = (A(#1, @5) B((#3, C(#2))));").unwrap();
+    /// assert_eq!(instance.entity_refs().collect::<Vec<_>>(), [1, 3, 2]);
+    /// ```
+    pub fn entity_refs(&self) -> impl Iterator<Item = u64> + '_ {
+        self.names().filter_map(Name::entity_id)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReferenceEntry {
     pub name: Name,
@@ -788,8 +1828,35 @@ derive_ast_from_str!(ReferenceEntry, parser::exchange::reference);
 #[derive(Debug, Clone, PartialEq)]
 pub struct URI(pub String);
 
+impl URI {
+    /// Part before the fragment identifier, e.g. `file2.stp` of `file2.stp#bolt`.
+    /// Empty for a reference in the same file, e.g. `#bolt`.
+    pub fn document(&self) -> &str {
+        self.0
+            .split_once('#')
+            .map_or(&self.0, |(document, _)| document)
+    }
+
+    /// Fragment identifier after `#`, e.g. `bolt` of `file2.stp#bolt`
+    pub fn fragment(&self) -> Option<&str> {
+        self.0.split_once('#').map(|(_, fragment)| fragment)
+    }
+}
+
+/// An entry of `ANCHOR` section, which exports an instance or a value by the name of URI fragment
+///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let anchor = Anchor::from_str("<bolt> = #10 {revision:'B'};").unwrap();
+/// assert_eq!(anchor.name, "bolt");
+/// assert_eq!(anchor.item, AnchorItem::Name(Name::Entity(10)));
+/// assert_eq!(anchor.tags, [("revision".to_string(), AnchorItem::String("B".to_string()))]);
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Anchor {
+    /// URI fragment identifier without `<` and `>`
     pub name: String,
     pub item: AnchorItem,
     pub tags: Vec<(String, AnchorItem)>,
@@ -802,11 +1869,42 @@ pub enum AnchorItem {
     Real(f64),
     String(String),
     Enumeration(String),
+    Binary(crate::primitive::Binary),
     /// The special token dollar sign (`$`) is used to represent an object whose value is not provided in the exchange structure.
     NotProvided,
     /// A reference to entity or value
     Name(Name),
+    /// A reference to an external resource, e.g. `<other.stp#bolt>`
+    Resource(URI),
     /// List of other parameters
     List(Vec<AnchorItem>),
 }
 derive_ast_from_str!(AnchorItem, parser::exchange::anchor_item);
+
+impl AnchorItem {
+    /// Names in this item in the document order
+    fn names(&self) -> Box<dyn Iterator<Item = &Name> + '_> {
+        match self {
+            AnchorItem::Name(name) => Box::new(std::iter::once(name)),
+            AnchorItem::List(items) => Box::new(items.iter().flat_map(AnchorItem::names)),
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Replace references to entity instances by `ids`. Ids not in `ids` are kept as is.
+    fn renumber(&mut self, ids: &HashMap<u64, u64>) {
+        match self {
+            AnchorItem::Name(Name::Entity(id)) => {
+                if let Some(new) = ids.get(id) {
+                    *id = *new;
+                }
+            }
+            AnchorItem::List(items) => {
+                for item in items {
+                    item.renumber(ids);
+                }
+            }
+            _ => {}
+        }
+    }
+}
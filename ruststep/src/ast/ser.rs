@@ -3,12 +3,34 @@ use serde::ser;
 use std::convert::TryFrom;
 
 /// Serialize struct into STEP [Record]
+///
+/// `None` is serialized as [Parameter::NotProvided] both for a field and for an element of a sequence,
+/// and a sequence, e.g. `Vec<Option<f64>>`, is serialized as [Parameter::List]:
+///
+/// ```
+/// use ruststep::ast::{ser::to_record, Record};
+/// use serde::Serialize;
+/// use std::str::FromStr;
+///
+/// #[derive(Serialize)]
+/// #[allow(non_camel_case_types)]
+/// struct A {
+///     x: Option<f64>,
+///     y: Vec<Option<f64>>,
+/// }
+///
+/// let record = to_record(&A { x: None, y: vec![Some(1.0), None] }).unwrap();
+/// assert_eq!(record, Record::from_str("A($, (1.0, $))").unwrap());
+/// ```
 pub fn to_record(obj: &impl ser::Serialize) -> Result<Record> {
     let mut ser = RecordSerializer::default();
     obj.serialize(&mut ser)?;
-    assert!(ser.stack.is_empty()); // should panic because this must be bug, not a valid input
+    // should panic because this must be bug, not a valid input
+    assert!(ser.stack.is_empty());
+    assert!(ser.lists.is_empty());
     Ok(Record {
         name: ser.name,
+        user_defined: false,
         parameter: ser.parameters.iter().collect(),
     })
 }
@@ -19,6 +41,17 @@ struct RecordSerializer {
     parameters: Vec<Parameter>,
     // For supporting nested record e.g. `B(3.0, A((1.0, 2.0)))`
     stack: Vec<(String, Vec<Parameter>)>,
+    // For supporting nested list e.g. `A((1.0, $))`
+    lists: Vec<Vec<Parameter>>,
+}
+
+impl RecordSerializer {
+    /// Called by `end` of sequences started by [ser::Serializer::serialize_seq]
+    fn end_list(&mut self) {
+        let outer = self.lists.pop().expect("list is not started");
+        let elements = std::mem::replace(&mut self.parameters, outer);
+        self.parameters.push(Parameter::List(elements));
+    }
 }
 
 impl<'se> ser::Serializer for &'se mut RecordSerializer {
@@ -74,7 +107,7 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
         self.serialize_f64(f64::from(v))
     }
     fn serialize_f64(self, v: f64) -> Result<()> {
-        self.parameters.push(Parameter::Real(v));
+        self.parameters.push(Parameter::real(v));
         Ok(())
     }
 
@@ -85,8 +118,12 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
         self.parameters.push(Parameter::String(v.to_string()));
         Ok(())
     }
-    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
-        unimplemented!("Bytes is not supported yet")
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.parameters
+            .push(Parameter::Binary(crate::primitive::Binary::from(
+                v.to_vec(),
+            )));
+        Ok(())
     }
 
     fn serialize_none(self) -> Result<()> {
@@ -118,11 +155,35 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
         self.serialize_str(variant)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(self)
+        value.serialize(&mut *self)?;
+        if name == crate::primitive::BINARY {
+            // [crate::primitive::Binary] is serialized as the content of the binary token
+            if let Some(Parameter::String(content)) = self.parameters.pop() {
+                self.parameters.push(Parameter::Binary(content.parse()?));
+                return Ok(());
+            }
+            unreachable!("Binary must be serialized as a string");
+        }
+        if name == crate::primitive::WRAPPED {
+            // [crate::primitive::Wrapped] is serialized as a pair of the keyword and the value
+            if let Some(Parameter::List(mut pair)) = self.parameters.pop() {
+                if let (Some(parameter), Some(Parameter::String(keyword))) =
+                    (pair.pop(), pair.pop())
+                {
+                    self.parameters.push(Parameter::Typed {
+                        keyword,
+                        parameter: Box::new(parameter),
+                    });
+                    return Ok(());
+                }
+            }
+            unreachable!("Wrapped must be serialized as a pair of keyword and value");
+        }
+        Ok(())
     }
 
     fn serialize_newtype_variant<T>(
@@ -139,6 +200,7 @@ impl<'se> ser::Serializer for &'se mut RecordSerializer {
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.lists.push(std::mem::take(&mut self.parameters));
         Ok(self)
     }
 
@@ -211,6 +273,7 @@ impl<'se> ser::SerializeSeq for &'se mut RecordSerializer {
         value.serialize(&mut **self)
     }
     fn end(self) -> Result<()> {
+        self.end_list();
         Ok(())
     }
 }
@@ -226,6 +289,7 @@ impl<'se> ser::SerializeTuple for &'se mut RecordSerializer {
     }
 
     fn end(self) -> Result<()> {
+        self.end_list();
         Ok(())
     }
 }
@@ -242,6 +306,7 @@ impl<'se> ser::SerializeTupleStruct for &'se mut RecordSerializer {
     }
 
     fn end(self) -> Result<()> {
+        self.end_list();
         Ok(())
     }
 }
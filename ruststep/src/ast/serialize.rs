@@ -0,0 +1,130 @@
+//! [serde::Serialize] for AST, see [Serialize](super#serialize) section
+
+use super::*;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Map of a single entry, e.g. `{"entity": 42}`
+fn single_entry<S, V>(serializer: S, key: &str, value: &V) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    V: Serialize + ?Sized,
+{
+    let mut map = serializer.serialize_map(Some(1))?;
+    map.serialize_entry(key, value)?;
+    map.end()
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Name::Entity(id) => single_entry(serializer, "entity", id),
+            Name::Value(id) => single_entry(serializer, "value", id),
+            Name::ConstantEntity(name) => single_entry(serializer, "constant_entity", name),
+            Name::ConstantValue(name) => single_entry(serializer, "constant_value", name),
+        }
+    }
+}
+
+impl Serialize for Parameter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Parameter::Typed { keyword, parameter } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                map.serialize_entry("keyword", keyword)?;
+                map.serialize_entry("parameter", parameter)?;
+                map.end()
+            }
+            Parameter::Integer(value) => serializer.serialize_i64(*value),
+            Parameter::Real { value, .. } => serializer.serialize_f64(*value),
+            Parameter::String(value) => serializer.serialize_str(value),
+            Parameter::Binary(value) => single_entry(serializer, "binary", value),
+            Parameter::Enumeration(value) => single_entry(serializer, "enumeration", value),
+            Parameter::List(parameters) => parameters.serialize(serializer),
+            Parameter::Ref(name) => name.serialize(serializer),
+            Parameter::NotProvided => serializer.serialize_none(),
+            Parameter::Omitted => single_entry(serializer, "omitted", &true),
+        }
+    }
+}
+
+impl Serialize for Record {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("keyword", &self.keyword())?;
+        map.serialize_entry("parameters", &self.parameter)?;
+        map.end()
+    }
+}
+
+impl Serialize for EntityInstance {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        match self {
+            EntityInstance::Simple { id, record } => {
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("record", record)?;
+            }
+            EntityInstance::Complex { id, subsuper } => {
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("records", &subsuper.0)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl Serialize for DataSection {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("meta", &self.meta)?;
+        map.serialize_entry("entities", &self.entities)?;
+        map.end()
+    }
+}
+
+impl Serialize for AnchorItem {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            AnchorItem::Integer(value) => serializer.serialize_i64(*value),
+            AnchorItem::Real(value) => serializer.serialize_f64(*value),
+            AnchorItem::String(value) => serializer.serialize_str(value),
+            AnchorItem::Enumeration(value) => single_entry(serializer, "enumeration", value),
+            AnchorItem::Binary(value) => single_entry(serializer, "binary", value),
+            AnchorItem::NotProvided => serializer.serialize_none(),
+            AnchorItem::Name(name) => name.serialize(serializer),
+            AnchorItem::Resource(uri) => single_entry(serializer, "resource", &uri.0),
+            AnchorItem::List(items) => items.serialize(serializer),
+        }
+    }
+}
+
+impl Serialize for Anchor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("name", &self.name)?;
+        map.serialize_entry("item", &self.item)?;
+        map.serialize_entry("tags", &self.tags)?;
+        map.end()
+    }
+}
+
+impl Serialize for ReferenceEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("name", &self.name)?;
+        map.serialize_entry("resource", &self.resource.0)?;
+        map.end()
+    }
+}
+
+impl Serialize for Exchange {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(5))?;
+        map.serialize_entry("header", &self.header)?;
+        map.serialize_entry("anchor", &self.anchor)?;
+        map.serialize_entry("reference", &self.reference)?;
+        map.serialize_entry("data", &self.data)?;
+        map.serialize_entry("signature", &self.signature)?;
+        map.end()
+    }
+}
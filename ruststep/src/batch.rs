@@ -0,0 +1,347 @@
+//! Batch processing of exchange structure files in a directory tree
+//!
+//! [process_dir] discovers files by their extensions under an input directory,
+//! runs a [PassManager] on each of them using a bounded number of worker threads,
+//! and writes the results into an output directory keeping the relative directory structure.
+//!
+//! ```no_run
+//! use ruststep::batch::*;
+//! use std::path::Path;
+//!
+//! let mut passes = PassManager::new();
+//! passes.add_pass(|input: &str| {
+//!     // Reject files which cannot be parsed
+//!     ruststep::parser::parse(input)?;
+//!     Ok(input.to_string())
+//! });
+//!
+//! let report = process_dir(
+//!     Path::new("input"),
+//!     Path::new("output"),
+//!     &passes,
+//!     BatchOptions::default(),
+//! );
+//! for file in report.failed() {
+//!     eprintln!("{}: {:?}", file.input.display(), file.status);
+//! }
+//! ```
+//!
+//! A pass registered by [PassManager::add_checked_pass] may also report findings,
+//! e.g. warnings of a lenient parser, which are collected into [FileReport::findings]:
+//!
+//! ```no_run
+//! use ruststep::{batch::*, parser::*};
+//!
+//! let mut passes = PassManager::new();
+//! passes.add_checked_pass(|input: &str, findings: &mut Vec<String>| {
+//!     let (_exchange, warnings) = parse_with_warnings(input, &ParseOptions::lenient())?;
+//!     findings.extend(warnings.iter().map(|w| format!("{:?}", w)));
+//!     Ok(input.to_string())
+//! });
+//! ```
+//!
+//! Each file is processed in isolation.
+//! An error or a panic while processing a file is recorded in [BatchReport]
+//! and does not abort the other files.
+
+use crate::error::Result;
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A pass takes the content of a file and returns the processed content
+///
+/// Findings of the pass are pushed into the second argument.
+pub type Pass = Box<dyn Fn(&str, &mut Vec<String>) -> Result<String> + Send + Sync>;
+
+/// Finding reported by a pass, see [PassManager::add_checked_pass]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Index of the pass in the registered order
+    pub pass: usize,
+    pub message: String,
+}
+
+/// Sequence of [Pass]es applied to each file
+#[derive(Default)]
+pub struct PassManager {
+    passes: Vec<Pass>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a pass to the end of the pipeline
+    pub fn add_pass(
+        &mut self,
+        pass: impl Fn(&str) -> Result<String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.add_checked_pass(move |input: &str, _: &mut Vec<String>| pass(input))
+    }
+
+    /// Append a pass reporting findings to the end of the pipeline
+    pub fn add_checked_pass(
+        &mut self,
+        pass: impl Fn(&str, &mut Vec<String>) -> Result<String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Run all passes in the registered order
+    ///
+    /// The input is returned as is if no pass is registered.
+    pub fn run(&self, input: &str) -> Result<String> {
+        self.run_with_findings(input, &mut Vec::new())
+    }
+
+    /// Run all passes in the registered order, and push their findings into `findings`
+    ///
+    /// Findings reported before a failing pass are kept.
+    pub fn run_with_findings(&self, input: &str, findings: &mut Vec<Finding>) -> Result<String> {
+        let mut current = input.to_string();
+        for (index, pass) in self.passes.iter().enumerate() {
+            let mut messages = Vec::new();
+            let result = pass(&current, &mut messages);
+            findings.extend(messages.into_iter().map(|message| Finding {
+                pass: index,
+                message,
+            }));
+            current = result?;
+        }
+        Ok(current)
+    }
+}
+
+/// Options for [process_dir]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchOptions {
+    /// File extensions to be processed, compared case-insensitively
+    pub extensions: Vec<String>,
+    /// Number of worker threads
+    pub parallelism: usize,
+    /// Skip files whose output is newer than the input
+    pub incremental: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            extensions: vec!["step".to_string(), "stp".to_string(), "p21".to_string()],
+            parallelism: thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+            incremental: false,
+        }
+    }
+}
+
+/// Result of processing a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileStatus {
+    /// Processed and written into output
+    Processed,
+    /// Skipped since the output is up to date
+    Skipped,
+    /// The pipeline returns an error, or I/O error occurs
+    Failed(String),
+    /// The pipeline panics
+    Panicked(String),
+}
+
+/// Report of each file in [BatchReport]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub status: FileStatus,
+    /// Findings of the passes, empty if skipped
+    pub findings: Vec<Finding>,
+    pub elapsed: Duration,
+}
+
+/// Report of [process_dir], sorted by the input path
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BatchReport {
+    pub files: Vec<FileReport>,
+}
+
+impl BatchReport {
+    pub fn processed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files
+            .iter()
+            .filter(|f| f.status == FileStatus::Processed)
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &FileReport> {
+        self.files
+            .iter()
+            .filter(|f| f.status == FileStatus::Skipped)
+    }
+
+    /// Files failed by error or panic
+    pub fn failed(&self) -> impl Iterator<Item = &FileReport> {
+        self.files
+            .iter()
+            .filter(|f| matches!(f.status, FileStatus::Failed(_) | FileStatus::Panicked(_)))
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// Process all files under `input` directory, and write them into `output` directory
+///
+/// - Files are discovered recursively by [BatchOptions::extensions].
+/// - The relative path from `input` is kept in `output`.
+/// - Errors, including panics in the pipeline, are recorded per file in [BatchReport].
+///
+pub fn process_dir(
+    input: &Path,
+    output: &Path,
+    pipeline: &PassManager,
+    options: BatchOptions,
+) -> BatchReport {
+    let mut reports = Vec::new();
+    let mut files = Vec::new();
+    discover(input, &options.extensions, &mut files, &mut reports);
+
+    let queue = Mutex::new(files.into_iter().collect::<VecDeque<_>>());
+    let reports = Mutex::new(reports);
+    thread::scope(|s| {
+        for _ in 0..options.parallelism.max(1) {
+            s.spawn(|| loop {
+                let next = queue.lock().unwrap().pop_front();
+                let Some(path) = next else {
+                    break;
+                };
+                let report = process_file(pipeline, input, output, &path, options.incremental);
+                reports.lock().unwrap().push(report);
+            });
+        }
+    });
+
+    let mut files = reports.into_inner().unwrap();
+    files.sort_by(|a, b| a.input.cmp(&b.input));
+    BatchReport { files }
+}
+
+fn discover(
+    dir: &Path,
+    extensions: &[String],
+    files: &mut Vec<PathBuf>,
+    reports: &mut Vec<FileReport>,
+) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            reports.push(FileReport {
+                input: dir.to_path_buf(),
+                output: PathBuf::new(),
+                status: FileStatus::Failed(e.to_string()),
+                findings: Vec::new(),
+                elapsed: Duration::ZERO,
+            });
+            return;
+        }
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            discover(&path, extensions, files, reports);
+            continue;
+        }
+        let matched = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+            .unwrap_or(false);
+        if matched {
+            files.push(path);
+        }
+    }
+}
+
+fn process_file(
+    pipeline: &PassManager,
+    input_root: &Path,
+    output_root: &Path,
+    input: &Path,
+    incremental: bool,
+) -> FileReport {
+    let start = Instant::now();
+    let relative = input.strip_prefix(input_root).unwrap_or(input);
+    let output = output_root.join(relative);
+
+    let mut findings = Vec::new();
+    let status = if incremental && is_up_to_date(input, &output) {
+        FileStatus::Skipped
+    } else {
+        run(pipeline, input, &output, &mut findings)
+    };
+
+    FileReport {
+        input: input.to_path_buf(),
+        output,
+        status,
+        findings,
+        elapsed: start.elapsed(),
+    }
+}
+
+fn run(
+    pipeline: &PassManager,
+    input: &Path,
+    output: &Path,
+    findings: &mut Vec<Finding>,
+) -> FileStatus {
+    let content = match fs::read_to_string(input) {
+        Ok(content) => content,
+        Err(e) => return FileStatus::Failed(e.to_string()),
+    };
+    let processed = match panic::catch_unwind(AssertUnwindSafe(|| {
+        pipeline.run_with_findings(&content, findings)
+    })) {
+        Ok(Ok(processed)) => processed,
+        Ok(Err(e)) => return FileStatus::Failed(e.to_string()),
+        Err(payload) => return FileStatus::Panicked(panic_message(payload.as_ref())),
+    };
+    if let Some(parent) = output.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            return FileStatus::Failed(e.to_string());
+        }
+    }
+    match fs::write(output, processed) {
+        Ok(_) => FileStatus::Processed,
+        Err(e) => FileStatus::Failed(e.to_string()),
+    }
+}
+
+fn is_up_to_date(input: &Path, output: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    match (modified(input), modified(output)) {
+        (Some(input), Some(output)) => output >= input,
+        _ => false,
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
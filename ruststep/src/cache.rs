@@ -0,0 +1,182 @@
+//! Snapshot of tables persisted in a binary format and reloaded without parsing
+//!
+//! Tokenizing a large exchange structure takes long even if the file is not changed.
+//! `Tables` generated by espr with `derive_serde` option, e.g. `esprc --derive-serde`,
+//! and holders with `#[holder(generate_serde)]` implement [serde::Serialize] and [serde::Deserialize]
+//! for binary formats, e.g. bincode or postcard. [TablesCache] keeps the typed tables with
+//! the version of ruststep and the schema, and is written by `Tables::to_cache`
+//! and read by `Tables::from_cache`:
+//!
+//! ```ignore
+//! std::fs::write("model.cache", bincode::serialize(&tables.to_cache())?)?;
+//!
+//! let cache: TablesCache<Tables> = bincode::deserialize(&std::fs::read("model.cache")?)?;
+//! let tables = Tables::from_cache(cache)?;
+//! ```
+//!
+//! Human-readable formats, e.g. JSON, are not supported, since `Deserialize` of holders
+//! reads them as the exchange structure.
+//! [Constants](crate::tables::Constants) registered in the tables are not persisted,
+//! and must be registered again to the reloaded tables.
+//!
+//! Stale cache
+//! ------------
+//! A cache keeps the version of ruststep and the [schema_fingerprint] of the tables which wrote it,
+//! and deserializing it fails with [Error::StaleCache] before reading the tables if they do not match,
+//! instead of misreading tables written for another schema.
+//! The fingerprint is computed from the keywords, supertypes and attributes in `SCHEMA_REGISTRY`,
+//! and does not depend on the platform or [std::hash::Hash].
+
+use crate::{
+    error::{Error, Result},
+    tables::EntityDescriptor,
+};
+use serde::{de, Deserialize, Serialize};
+use std::{fmt, marker::PhantomData};
+
+/// Tables with the schema fingerprint, implemented by `Tables` generated with `derive_serde`
+pub trait CachedTables {
+    /// [schema_fingerprint] of `SCHEMA_REGISTRY`
+    fn schema_fingerprint() -> u64;
+}
+
+impl<T: CachedTables> CachedTables for &T {
+    fn schema_fingerprint() -> u64 {
+        T::schema_fingerprint()
+    }
+}
+
+/// Tables with the version and the schema fingerprint which wrote them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TablesCache<T> {
+    /// Version of ruststep, e.g. `0.4.0`
+    pub version: String,
+    /// [schema_fingerprint] of the tables
+    pub fingerprint: u64,
+    tables: T,
+}
+
+impl<T: CachedTables> TablesCache<T> {
+    /// Snapshot of tables written by this version of ruststep
+    pub fn new(tables: T) -> Self {
+        TablesCache {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            fingerprint: T::schema_fingerprint(),
+            tables,
+        }
+    }
+
+    /// Take the tables out
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::StaleCache] if the cache is written by another version of ruststep or another schema
+    ///
+    pub fn into_tables(self) -> Result<T> {
+        check(&self.version, self.fingerprint, T::schema_fingerprint())?;
+        Ok(self.tables)
+    }
+}
+
+fn check(version: &str, fingerprint: u64, expected: u64) -> Result<()> {
+    if version != env!("CARGO_PKG_VERSION") || fingerprint != expected {
+        return Err(Error::StaleCache {
+            version: version.to_string(),
+            fingerprint,
+            expected,
+        });
+    }
+    Ok(())
+}
+
+const FIELDS: &[&str] = &["version", "fingerprint", "tables"];
+
+impl<'de, T: Deserialize<'de> + CachedTables> Deserialize<'de> for TablesCache<T> {
+    /// Read the version and the fingerprint, and then the tables only if they match,
+    /// i.e. a stale cache fails with the message of [Error::StaleCache].
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("TablesCache", FIELDS, CacheVisitor(PhantomData))
+    }
+}
+
+struct CacheVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de> + CachedTables> de::Visitor<'de> for CacheVisitor<T> {
+    type Value = TablesCache<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "struct TablesCache")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let version: String = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let fingerprint: u64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        check(&version, fingerprint, T::schema_fingerprint()).map_err(de::Error::custom)?;
+        let tables = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        Ok(TablesCache {
+            version,
+            fingerprint,
+            tables,
+        })
+    }
+}
+
+/// 64-bit FNV-1a over the keywords, supertypes and attributes of a schema
+///
+/// The order of descriptors is significant, since espr generates them in the declaration order.
+pub fn schema_fingerprint<T>(registry: &[EntityDescriptor<T>]) -> u64 {
+    let mut hasher = Hasher::new();
+    for descriptor in registry {
+        hasher.str(descriptor.keyword);
+        hasher.u64(descriptor.supertypes.len() as u64);
+        for supertype in descriptor.supertypes {
+            hasher.str(supertype);
+        }
+        hasher.u64(descriptor.attributes.len() as u64);
+        for attribute in descriptor.attributes {
+            hasher.str(attribute.name);
+            hasher.str(attribute.ty);
+            hasher.bytes(&[attribute.optional as u8]);
+        }
+    }
+    hasher.0
+}
+
+struct Hasher(u64);
+
+impl Hasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x00000100000001b3;
+
+    fn new() -> Self {
+        Hasher(Self::OFFSET_BASIS)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+}
@@ -0,0 +1,383 @@
+//! Detect features of exchange structure used in a file, and whether this crate supports them
+//!
+//! [analyze] scans the input once without parsing it,
+//! and thus it works even for files which the parser rejects:
+//!
+//! ```
+//! use ruststep::capabilities::*;
+//!
+//! let report = analyze("DATA; #1 = (A() B(\"0F\")) &SCOPE #2 = C(); ENDSCOPE; #3 = d(); ENDSEC;");
+//! assert_eq!(report.get(Feature::ComplexInstance).unwrap().count, 1);
+//! assert_eq!(report.get(Feature::Binary).unwrap().support, Support::Supported);
+//! assert_eq!(report.get(Feature::ScopeBlock).unwrap().support, Support::Supported);
+//! assert_eq!(
+//!     report.get(Feature::LowercaseKeyword).unwrap().support,
+//!     Support::SupportedWithOption("ParseOptions::case_insensitive_keywords")
+//! );
+//! assert!(report.is_supported());
+//! ```
+//!
+//! Verdicts come from the registry of features each module of this crate supports,
+//! and features not registered are [Support::Unsupported].
+
+use crate::{header, parser, tables};
+use std::fmt;
+
+/// Feature of exchange structure which some readers do not support
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Feature {
+    /// Complex entity instance in external mapping, e.g. `#1 = (A() B());`
+    ComplexInstance,
+    /// Value instance name, e.g. `@1`
+    ValueInstance,
+    /// `ANCHOR` section
+    AnchorSection,
+    /// `REFERENCE` section
+    ReferenceSection,
+    /// `SIGNATURE` section
+    SignatureSection,
+    /// Binary literal, e.g. `"0F"`
+    Binary,
+    /// Control directive in string, e.g. `\X2\30B9\X0\` or `\S\`
+    ControlDirective,
+    /// User defined keyword, e.g. `!MY_ENTITY()`
+    UserDefinedKeyword,
+    /// Two or more `DATA` sections
+    MultipleDataSections,
+    /// `&SCOPE` block of the first edition
+    ScopeBlock,
+    /// Implementation level `3;1` of ISO-10303-21 edition 3 in `FILE_DESCRIPTION`
+    Edition3,
+    /// Full-width punctuation outside strings, e.g. `；`
+    FullwidthPunctuation,
+    /// Space between a sign and digits, e.g. `- 5`
+    SpaceAfterSign,
+    /// Real without digits before the decimal point, e.g. `.5`, or without the decimal point, e.g. `5E-2`
+    LooseReal,
+    /// Lower case letters in a keyword or enumeration, e.g. `Cartesian_Point(...)` or `.true.`
+    LowercaseKeyword,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 15] = [
+        Feature::ComplexInstance,
+        Feature::ValueInstance,
+        Feature::AnchorSection,
+        Feature::ReferenceSection,
+        Feature::SignatureSection,
+        Feature::Binary,
+        Feature::ControlDirective,
+        Feature::UserDefinedKeyword,
+        Feature::MultipleDataSections,
+        Feature::ScopeBlock,
+        Feature::Edition3,
+        Feature::FullwidthPunctuation,
+        Feature::SpaceAfterSign,
+        Feature::LooseReal,
+        Feature::LowercaseKeyword,
+    ];
+
+    /// Whether this crate supports the feature
+    pub fn support(self) -> Support {
+        REGISTRY
+            .iter()
+            .flat_map(|features| features.iter())
+            .find(|(feature, _)| *feature == self)
+            .map(|(_, support)| *support)
+            .unwrap_or(Support::Unsupported)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Support {
+    Supported,
+    /// Supported if the option is enabled
+    SupportedWithOption(&'static str),
+    Unsupported,
+}
+
+/// Features supported by each module
+const REGISTRY: &[&[(Feature, Support)]] = &[
+    parser::CAPABILITIES,
+    header::CAPABILITIES,
+    tables::CAPABILITIES,
+];
+
+/// Usage of a [Feature] in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureUsage {
+    pub feature: Feature,
+    pub count: usize,
+    /// Byte offset of the first occurrence
+    pub first_offset: usize,
+    pub support: Support,
+}
+
+/// Features used in the input, see [analyze]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CapabilityReport {
+    /// Used features in the order of [Feature::ALL]
+    pub features: Vec<FeatureUsage>,
+}
+
+impl CapabilityReport {
+    pub fn get(&self, feature: Feature) -> Option<&FeatureUsage> {
+        self.features.iter().find(|usage| usage.feature == feature)
+    }
+
+    /// Used features which are not supported even with options
+    pub fn unsupported(&self) -> impl Iterator<Item = &FeatureUsage> {
+        self.features
+            .iter()
+            .filter(|usage| usage.support == Support::Unsupported)
+    }
+
+    /// Options required to process the input
+    pub fn required_options(&self) -> Vec<&'static str> {
+        self.features
+            .iter()
+            .filter_map(|usage| match usage.support {
+                Support::SupportedWithOption(option) => Some(option),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every used feature is supported, possibly with options
+    pub fn is_supported(&self) -> bool {
+        self.unsupported().next().is_none()
+    }
+
+    fn record(&mut self, feature: Feature, offset: usize) {
+        match self
+            .features
+            .iter_mut()
+            .find(|usage| usage.feature == feature)
+        {
+            Some(usage) => usage.count += 1,
+            None => self.features.push(FeatureUsage {
+                feature,
+                count: 1,
+                first_offset: offset,
+                support: feature.support(),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for CapabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for usage in &self.features {
+            write!(
+                f,
+                "{:?}: {} time(s), first at byte {}: ",
+                usage.feature, usage.count, usage.first_offset
+            )?;
+            match usage.support {
+                Support::Supported => writeln!(f, "supported")?,
+                Support::SupportedWithOption(option) => writeln!(f, "supported with {}", option)?,
+                Support::Unsupported => writeln!(f, "unsupported")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scan {
+    Normal,
+    String,
+    Binary,
+    Comment,
+}
+
+/// Scan `input` and report features used in it
+///
+/// Statements are not validated, and a broken statement is scanned as well as possible.
+pub fn analyze(input: &str) -> CapabilityReport {
+    let mut report = CapabilityReport::default();
+    let bytes = input.as_bytes();
+    let mut state = Scan::Normal;
+    let mut statement: Option<usize> = None;
+    let mut data_sections = 0;
+    let mut comment_start = 0;
+    // Position of `\\` escaping the next backslash in a string
+    let mut escape = None;
+
+    for (i, c) in input.char_indices() {
+        let next = bytes.get(i + 1).copied().unwrap_or(0);
+        match state {
+            Scan::Comment => {
+                if c == '/' && i >= comment_start + 3 && bytes[i - 1] == b'*' {
+                    state = Scan::Normal;
+                }
+                continue;
+            }
+            Scan::String => {
+                if c == '\'' {
+                    state = Scan::Normal;
+                } else if c == '\\' && escape != Some(i - 1) {
+                    if next == b'\\' {
+                        escape = Some(i);
+                    } else if is_control_directive(&input[i..]) {
+                        report.record(Feature::ControlDirective, i);
+                    }
+                }
+                continue;
+            }
+            Scan::Binary => {
+                if c == '"' {
+                    state = Scan::Normal;
+                }
+                continue;
+            }
+            Scan::Normal => {}
+        }
+
+        match c {
+            '/' if next == b'*' => {
+                state = Scan::Comment;
+                comment_start = i;
+                continue;
+            }
+            '\'' => state = Scan::String,
+            ';' | '；' => {
+                if c == '；' {
+                    report.record(Feature::FullwidthPunctuation, i);
+                }
+                if let Some(start) = statement.take() {
+                    let text = &input[start..i];
+                    statement_features(&mut report, text, start, &mut data_sections);
+                }
+                continue;
+            }
+            '"' => {
+                report.record(Feature::Binary, i);
+                state = Scan::Binary;
+            }
+            '@' if next.is_ascii_alphanumeric() => report.record(Feature::ValueInstance, i),
+            '!' if next.is_ascii_uppercase() => report.record(Feature::UserDefinedKeyword, i),
+            '&' if input[i + 1..].starts_with("SCOPE") => report.record(Feature::ScopeBlock, i),
+            '\u{3000}' | '（' | '）' | '＃' | '＝' => {
+                report.record(Feature::FullwidthPunctuation, i)
+            }
+            '-' | '+' if is_space_after_sign(input, i) => report.record(Feature::SpaceAfterSign, i),
+            '.' | 'E' if is_loose_real(input, i) => report.record(Feature::LooseReal, i),
+            'A'..='Z' | 'a'..='z' | '_' if is_lowercase_keyword(input, i) => {
+                report.record(Feature::LowercaseKeyword, i)
+            }
+            _ => {}
+        }
+        if statement.is_none() && !c.is_whitespace() {
+            statement = Some(i);
+        }
+    }
+
+    report
+        .features
+        .sort_by_key(|usage| Feature::ALL.iter().position(|f| *f == usage.feature));
+    report
+}
+
+/// Features determined by a whole statement, without the terminating `;`
+fn statement_features(
+    report: &mut CapabilityReport,
+    text: &str,
+    offset: usize,
+    data_sections: &mut usize,
+) {
+    let keyword: String = text
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect();
+    match keyword.as_str() {
+        "DATA" => {
+            *data_sections += 1;
+            if *data_sections >= 2 {
+                report.record(Feature::MultipleDataSections, offset);
+            }
+        }
+        "ANCHOR" => report.record(Feature::AnchorSection, offset),
+        "REFERENCE" => report.record(Feature::ReferenceSection, offset),
+        "SIGNATURE" => report.record(Feature::SignatureSection, offset),
+        "FILE_DESCRIPTION" => {
+            // The implementation level is the last string
+            let mut strings = text.rsplit('\'');
+            if let (Some(_), Some(level)) = (strings.next(), strings.next()) {
+                if level.starts_with("3;") {
+                    report.record(Feature::Edition3, offset);
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // `#1 = (A() B());`
+    if let Some(rest) = text.strip_prefix('#') {
+        let rest = rest
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start();
+        if let Some(rest) = rest.strip_prefix('=') {
+            if rest.trim_start().starts_with('(') {
+                report.record(Feature::ComplexInstance, offset);
+            }
+        }
+    }
+}
+
+/// `\X\`, `\X2\`, `\X4\`, `\S\`, or `\P?\` at the head of `s` in a string
+fn is_control_directive(s: &str) -> bool {
+    ["\\X\\", "\\X2\\", "\\X4\\", "\\S\\"]
+        .iter()
+        .any(|directive| s.starts_with(directive))
+        || (s.starts_with("\\P") && s.as_bytes().get(3) == Some(&b'\\'))
+}
+
+/// Sign at `i` is followed by spaces and a digit, and the sign follows `(`, `,`, or `=`
+fn is_space_after_sign(input: &str, i: usize) -> bool {
+    let after = &input[i + 1..];
+    let digits = after.trim_start_matches(' ');
+    let before = input[..i].trim_end();
+    digits.len() < after.len()
+        && digits.starts_with(|c: char| c.is_ascii_digit())
+        && before.ends_with(['(', ',', '='])
+}
+
+/// Word starting at `i` contains lower case letters,
+/// and is a keyword followed by `(` or an enumeration parameter between `.`
+fn is_lowercase_keyword(input: &str, i: usize) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let before = input[..i].chars().next_back();
+    if before.is_some_and(|c| is_word(c) || c == '#' || c == '@') {
+        return false;
+    }
+    let rest = input[i..].trim_start_matches(is_word);
+    let word = &input[i..input.len() - rest.len()];
+    let is_enumeration = before == Some('.')
+        && rest.starts_with('.')
+        && input[..i - 1].trim_end().ends_with(['(', ',']);
+    word.bytes().any(|b| b.is_ascii_lowercase())
+        && (rest.trim_start().starts_with('(') || is_enumeration)
+}
+
+/// `.` at `i` starts the fractional digits of a real without integral digits, e.g. `.5`,
+/// or `E` at `i` follows the digits of a real without `.`, e.g. `5E-2`
+fn is_loose_real(input: &str, i: usize) -> bool {
+    // Number starting at `start` is a parameter, i.e. follows `(` or `,` with an optional sign
+    let is_parameter = |start: usize| {
+        input[..start]
+            .trim_end_matches(['+', '-', ' '])
+            .trim_end()
+            .ends_with(['(', ','])
+    };
+    let after = &input[i + 1..];
+    if input.as_bytes()[i] == b'.' {
+        return after.starts_with(|c: char| c.is_ascii_digit()) && is_parameter(i);
+    }
+    let integral = input[..i].trim_end_matches(|c: char| c.is_ascii_digit());
+    integral.len() < i
+        && is_parameter(integral.len())
+        && after
+            .trim_start_matches(['+', '-'])
+            .starts_with(|c: char| c.is_ascii_digit())
+}
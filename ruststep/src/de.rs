@@ -0,0 +1,77 @@
+//! Deserialize values directly from a record or a parameter without tables
+//!
+//! References to entity instances, e.g. `#1`, need tables to be resolved,
+//! and they are reported as [Error::UnresolvedReferences].
+//! Read a data section into tables by [TableInit](crate::tables::TableInit) for such values.
+
+use crate::{
+    ast::{Parameter, Record},
+    error::{Error, Result},
+    tables::{parse_record, Holder, IntoHolder, IntoOwned},
+};
+use serde::de::DeserializeOwned;
+use std::str::FromStr;
+
+/// Deserialize a value from a parameter
+///
+/// ```
+/// use ruststep::{ast::Parameter, de::from_parameter, error::Error};
+/// use std::str::FromStr;
+///
+/// let parameter = Parameter::from_str("(1.0, 2.0, 3.0)").unwrap();
+/// let coordinates: Vec<f64> = from_parameter(&parameter).unwrap();
+/// assert_eq!(coordinates, [1.0, 2.0, 3.0]);
+///
+/// let parameter = Parameter::from_str("(#2, #1)").unwrap();
+/// assert!(matches!(
+///     from_parameter::<Vec<f64>>(&parameter),
+///     Err(Error::UnresolvedReferences(ids)) if ids == [1, 2]
+/// ));
+/// ```
+pub fn from_parameter<T: DeserializeOwned>(parameter: &Parameter) -> Result<T> {
+    check_references(parameter.entity_refs())?;
+    T::deserialize(parameter)
+}
+
+/// Deserialize an owned entity, e.g. generated by espr, from a record
+///
+/// Values in the record, e.g. typed parameters of a `SELECT` attribute, are converted
+/// with the default tables, and the keyword of the record is compared with [Holder::name].
+pub fn from_record<T>(record: &Record) -> Result<T>
+where
+    T: IntoHolder,
+    T::Holder: Holder + DeserializeOwned,
+    <T::Holder as IntoOwned>::Table: Default,
+{
+    check_references(record.entity_refs())?;
+    let holder: T::Holder = parse_record(record)?;
+    holder.into_owned(&Default::default())
+}
+
+/// Deserialize an owned entity from a string of a record, see [from_record]
+///
+/// ```ignore
+/// // Generated by espr for `ENTITY a; x: LIST [0:?] OF REAL; END_ENTITY;`
+/// use test_schema::A;
+///
+/// let a: A = ruststep::de::from_record_str("A((1.0, 2.0))").unwrap();
+/// assert_eq!(a.x, [1.0, 2.0]);
+/// ```
+pub fn from_record_str<T>(input: &str) -> Result<T>
+where
+    T: IntoHolder,
+    T::Holder: Holder + DeserializeOwned,
+    <T::Holder as IntoOwned>::Table: Default,
+{
+    from_record(&Record::from_str(input)?)
+}
+
+fn check_references(ids: impl Iterator<Item = u64>) -> Result<()> {
+    let mut ids: Vec<u64> = ids.collect();
+    if ids.is_empty() {
+        return Ok(());
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    Err(Error::UnresolvedReferences(ids))
+}
@@ -0,0 +1,525 @@
+//! Typed access to properties declared in ISO 13399 dictionaries
+//!
+//! An ISO 13399 dictionary, e.g. `database.p21` of the maintenance agency, is an exchange structure
+//! of the ISO 13584-25 library schema declaring properties of cutting tools identified by `PROPERTY_BSU` codes.
+//! [Dictionary::from_data_section] reads them, and [generate_properties] writes a Rust module
+//! with a zero-sized marker type implementing [PropertyDef] for each property:
+//!
+//! ```ignore
+//! /// tool cutting edge angle
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+//! pub struct ToolCuttingEdgeAngle;
+//!
+//! impl ::ruststep::dictionary::PropertyDef for ToolCuttingEdgeAngle {
+//!     const CODE: &'static str = "71D078F683C9B";
+//!     const VERSION: &'static str = "003";
+//!     type Value = ::ruststep::dictionary::Measure<::ruststep::dictionary::units::Degree>;
+//! }
+//! ```
+//!
+//! The value type follows the data type of the property, i.e. [Measure] in the unit of `REAL_MEASURE_TYPE`,
+//! and `f64`, `i64`, `bool`, or `String` for others. Values of items are read by the markers from [Catalogue]:
+//!
+//! ```ignore
+//! let angle = catalogue.value_of::<ToolCuttingEdgeAngle>("T1")?; // Option<Measure<Degree>>
+//! ```
+//!
+//! The generated module also has `REGISTRY` of [PropertyInfo]s sorted by codes,
+//! and `property(code)` to look up one of them at runtime.
+
+use crate::{
+    ast::*,
+    error::{Error, Result},
+};
+use inflector::Inflector;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write},
+    marker::PhantomData,
+};
+
+/// Units of [Measure] used in ISO 13399 dictionaries
+pub mod units {
+    /// Marker type of a unit
+    pub trait Unit {
+        /// Symbol written in `MATHEMATICAL_STRING` of the dictionary, e.g. `mm`
+        const SYMBOL: &'static str;
+    }
+
+    macro_rules! def_units {
+        ($($(#[$meta:meta])* $unit:ident = $symbol:literal,)*) => {
+            $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+            pub struct $unit;
+
+            impl Unit for $unit {
+                const SYMBOL: &'static str = $symbol;
+            }
+            )*
+
+            /// Name of the unit marker for a symbol, e.g. `Millimetre` for `mm`
+            pub(super) fn marker_name(symbol: &str) -> Option<&'static str> {
+                match symbol {
+                    $( $symbol => Some(stringify!($unit)), )*
+                    _ => None,
+                }
+            }
+        };
+    }
+
+    def_units! {
+        Millimetre = "mm",
+        Micrometre = "microns",
+        Kilogram = "kg",
+        Newton = "N",
+        NewtonMetre = "Nm",
+        /// Degree of angle
+        Degree = "deg",
+    }
+}
+
+/// Real value in the unit `U`
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Measure<U> {
+    pub value: f64,
+    unit: PhantomData<U>,
+}
+
+impl<U> Measure<U> {
+    pub fn new(value: f64) -> Self {
+        Measure {
+            value,
+            unit: PhantomData,
+        }
+    }
+}
+
+impl<U: units::Unit> fmt::Display for Measure<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, U::SYMBOL)
+    }
+}
+
+/// Value of a property read from a [Parameter]
+pub trait PropertyValue: Sized {
+    /// Read the value. Typed parameters, e.g. `LENGTH_MEASURE(1.0)`, are read by their contents.
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DeserializeFailed] if the parameter is not acceptable for this type
+    ///
+    fn from_parameter(parameter: &Parameter) -> Result<Self>;
+}
+
+fn untyped(parameter: &Parameter) -> &Parameter {
+    match parameter {
+        Parameter::Typed { parameter, .. } => untyped(parameter),
+        _ => parameter,
+    }
+}
+
+fn not_acceptable(parameter: &Parameter, ty: &str) -> Error {
+    Error::DeserializeFailed(format!("{:?} is not acceptable as {}", parameter, ty))
+}
+
+impl PropertyValue for f64 {
+    fn from_parameter(parameter: &Parameter) -> Result<Self> {
+        match untyped(parameter) {
+            Parameter::Real { value, .. } => Ok(*value),
+            Parameter::Integer(value) => Ok(*value as f64),
+            _ => Err(not_acceptable(parameter, "REAL")),
+        }
+    }
+}
+
+impl PropertyValue for i64 {
+    fn from_parameter(parameter: &Parameter) -> Result<Self> {
+        match untyped(parameter) {
+            Parameter::Integer(value) => Ok(*value),
+            _ => Err(not_acceptable(parameter, "INTEGER")),
+        }
+    }
+}
+
+impl PropertyValue for bool {
+    fn from_parameter(parameter: &Parameter) -> Result<Self> {
+        match untyped(parameter) {
+            Parameter::Enumeration(value) if value == "T" => Ok(true),
+            Parameter::Enumeration(value) if value == "F" => Ok(false),
+            _ => Err(not_acceptable(parameter, "BOOLEAN")),
+        }
+    }
+}
+
+impl PropertyValue for String {
+    fn from_parameter(parameter: &Parameter) -> Result<Self> {
+        match untyped(parameter) {
+            Parameter::String(value) => Ok(value.clone()),
+            _ => Err(not_acceptable(parameter, "STRING")),
+        }
+    }
+}
+
+impl<U> PropertyValue for Measure<U> {
+    fn from_parameter(parameter: &Parameter) -> Result<Self> {
+        f64::from_parameter(parameter).map(Measure::new)
+    }
+}
+
+/// Property identified by its code, implemented by marker types of [generate_properties]
+pub trait PropertyDef {
+    /// Code of `PROPERTY_BSU`, e.g. `71D078F683C9B`
+    const CODE: &'static str;
+    /// Version of `PROPERTY_BSU`, e.g. `003`
+    const VERSION: &'static str;
+    /// Type of values determined by the data type of the property
+    type Value: PropertyValue;
+}
+
+/// Property described at runtime, listed in `REGISTRY` of the generated module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyInfo {
+    pub code: &'static str,
+    pub version: &'static str,
+    /// Preferred name, e.g. `tool cutting edge angle`
+    pub name: &'static str,
+    /// Symbol of the unit, e.g. `deg`, for a measure
+    pub unit: Option<&'static str>,
+}
+
+/// Values of properties for items, e.g. tools in a catalogue, keyed by the item and the property code
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Catalogue {
+    items: HashMap<String, HashMap<String, Parameter>>,
+}
+
+impl Catalogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of the property `code` of `item`. Returns the previous value.
+    pub fn insert(&mut self, item: &str, code: &str, value: Parameter) -> Option<Parameter> {
+        self.items
+            .entry(item.to_string())
+            .or_default()
+            .insert(code.to_string(), value)
+    }
+
+    /// Value of the property `code` of `item` as written
+    pub fn get(&self, item: &str, code: &str) -> Option<&Parameter> {
+        self.items.get(item)?.get(code)
+    }
+
+    /// Value of the property `P` of `item`, `None` if it is not set
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::DeserializeFailed] if the value is not acceptable as [PropertyDef::Value]
+    ///
+    pub fn value_of<P: PropertyDef>(&self, item: &str) -> Result<Option<P::Value>> {
+        self.get(item, P::CODE)
+            .map(P::Value::from_parameter)
+            .transpose()
+    }
+}
+
+/// Data type of a property, i.e. the value domain of `NON_DEPENDENT_P_DET`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataType {
+    /// `REAL_MEASURE_TYPE` with the symbol of its unit, or `REAL_TYPE` without unit
+    Real {
+        unit: Option<String>,
+    },
+    /// `INT_TYPE` or `NON_QUANTITATIVE_INT_TYPE`
+    Integer,
+    Boolean,
+    /// `STRING_TYPE` or `NON_QUANTITATIVE_CODE_TYPE`
+    String,
+    /// Keyword of other data types, whose values are not typed
+    Unsupported(String),
+}
+
+/// Property declared by `NON_DEPENDENT_P_DET` or `DEPENDENT_P_DET`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// Code of `PROPERTY_BSU`
+    pub code: String,
+    /// Version of `PROPERTY_BSU`
+    pub version: String,
+    pub revision: String,
+    /// Preferred name, e.g. `tool cutting edge angle`
+    pub name: String,
+    pub short_name: Option<String>,
+    /// Preferred symbol, e.g. `KAPR`
+    pub symbol: Option<String>,
+    pub definition: String,
+    pub data_type: DataType,
+}
+
+/// Properties declared in an ISO 13399 dictionary
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dictionary {
+    /// Properties sorted by codes
+    pub properties: Vec<Property>,
+}
+
+/// Records of a data section keyed by their ids
+struct Records<'a>(HashMap<u64, &'a Record>);
+
+impl<'a> Records<'a> {
+    fn get(&self, parameter: Option<&Parameter>) -> Result<&'a Record> {
+        match parameter {
+            Some(Parameter::Ref(Name::Entity(id))) => {
+                self.0.get(id).copied().ok_or(Error::UnknownEntity(*id))
+            }
+            _ => Err(Error::DeserializeFailed(format!(
+                "expected entity reference, but found {}",
+                display(parameter)
+            ))),
+        }
+    }
+}
+
+fn display(parameter: Option<&Parameter>) -> String {
+    parameter.map_or("nothing".to_string(), |p| format!("{:?}", p))
+}
+
+/// Parameter at `index` of a record with parameters in a list
+fn attribute(record: &Record, index: usize) -> Option<&Parameter> {
+    match &record.parameter {
+        Parameter::List(parameters) => parameters.get(index),
+        _ => None,
+    }
+}
+
+/// String of a parameter, e.g. `'x'`, or `LABEL('x')`
+fn string(parameter: Option<&Parameter>) -> Result<String> {
+    match parameter.map(untyped) {
+        Some(Parameter::String(value)) => Ok(value.clone()),
+        _ => Err(Error::DeserializeFailed(format!(
+            "expected string, but found {}",
+            display(parameter)
+        ))),
+    }
+}
+
+/// Non-empty string, and `None` for `$` or `''`
+fn optional_string(parameter: Option<&Parameter>) -> Result<Option<String>> {
+    match parameter {
+        None | Some(Parameter::NotProvided) => Ok(None),
+        _ => string(parameter).map(|value| Some(value).filter(|value| !value.is_empty())),
+    }
+}
+
+impl Dictionary {
+    /// Read properties from the data section of a dictionary
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::UnknownEntity] if a property refers an undefined instance
+    /// - [Error::DeserializeFailed] if attributes of a property are not in the form of ISO 13584-25
+    ///
+    pub fn from_data_section(section: &DataSection) -> Result<Self> {
+        let records = Records(
+            section
+                .entities
+                .iter()
+                .filter_map(|instance| match instance {
+                    EntityInstance::Simple { id, record } => Some((*id, record)),
+                    EntityInstance::Complex { .. } => None,
+                })
+                .collect(),
+        );
+        let mut properties = Vec::new();
+        for (id, record) in &records.0 {
+            if !matches!(
+                record.name.as_str(),
+                "NON_DEPENDENT_P_DET" | "DEPENDENT_P_DET"
+            ) {
+                continue;
+            }
+            let property = read_property(&records, record).map_err(|e| match e {
+                Error::DeserializeFailed(message) => {
+                    Error::DeserializeFailed(format!("#{}: {}", id, message))
+                }
+                e => e,
+            })?;
+            properties.push(property);
+        }
+        properties.sort_by(|a, b| a.code.cmp(&b.code));
+        Ok(Dictionary { properties })
+    }
+
+    /// Property of the code
+    pub fn property(&self, code: &str) -> Option<&Property> {
+        self.properties
+            .binary_search_by(|property| property.code.as_str().cmp(code))
+            .ok()
+            .map(|index| &self.properties[index])
+    }
+}
+
+// #669=NON_DEPENDENT_P_DET(#670, #998, '002', #671, TEXT('...'), #676, TEXT(''), TEXT(''), #672, (), #5758, $, #673, $);
+fn read_property(records: &Records, record: &Record) -> Result<Property> {
+    let bsu = records.get(attribute(record, 0))?;
+    let names = records.get(attribute(record, 3))?;
+    let symbol = match attribute(record, 8) {
+        Some(Parameter::NotProvided) => None,
+        parameter => optional_string(attribute(records.get(parameter)?, 0))?,
+    };
+    Ok(Property {
+        code: string(attribute(bsu, 0))?,
+        version: string(attribute(bsu, 1))?,
+        revision: string(attribute(record, 2))?,
+        name: string(attribute(names, 0))?,
+        short_name: optional_string(attribute(names, 2))?,
+        symbol,
+        definition: string(attribute(record, 4))?,
+        data_type: read_data_type(records, records.get(attribute(record, 12))?)?,
+    })
+}
+
+fn read_data_type(records: &Records, record: &Record) -> Result<DataType> {
+    Ok(match record.name.as_str() {
+        // #673=REAL_MEASURE_TYPE('NR2 S..3.6', #674);
+        // #674=DIC_UNIT(#675, #12475);
+        // #12475=MATHEMATICAL_STRING('deg', $);
+        "REAL_MEASURE_TYPE" => {
+            let unit = records.get(attribute(record, 1))?;
+            let unit = match attribute(unit, 1) {
+                Some(Parameter::NotProvided) => None,
+                parameter => optional_string(attribute(records.get(parameter)?, 0))?,
+            };
+            DataType::Real { unit }
+        }
+        "REAL_TYPE" => DataType::Real { unit: None },
+        "INT_TYPE" | "NON_QUANTITATIVE_INT_TYPE" => DataType::Integer,
+        "BOOLEAN_TYPE" => DataType::Boolean,
+        "STRING_TYPE" | "NON_QUANTITATIVE_CODE_TYPE" => DataType::String,
+        other => DataType::Unsupported(other.to_string()),
+    })
+}
+
+/// Write a Rust module of marker types implementing [PropertyDef] for properties of the dictionary
+///
+/// The marker is named by the preferred name in upper camel case, e.g. `ToolCuttingEdgeAngle`,
+/// and the code is appended if the name is shared by several properties.
+/// Properties of [DataType::Unsupported] are skipped.
+/// The module refers ruststep as `::ruststep`.
+pub fn generate_properties(dictionary: &Dictionary) -> String {
+    let properties: Vec<&Property> = dictionary
+        .properties
+        .iter()
+        .filter(|property| !matches!(property.data_type, DataType::Unsupported(_)))
+        .collect();
+
+    let mut count = HashMap::new();
+    for property in &properties {
+        *count.entry(marker_name(&property.name)).or_insert(0) += 1;
+    }
+    let mut used = HashSet::new();
+    let markers: Vec<String> = properties
+        .iter()
+        .map(|property| {
+            let name = marker_name(&property.name);
+            let name = if count[&name] > 1 {
+                format!("{}{}", name, property.code.to_pascal_case())
+            } else {
+                name
+            };
+            assert!(used.insert(name.clone()), "Duplicated marker {}", name);
+            name
+        })
+        .collect();
+
+    let mut out = String::new();
+    for (property, marker) in properties.iter().zip(&markers) {
+        let value = match &property.data_type {
+            DataType::Real { unit } => match unit.as_deref().and_then(units::marker_name) {
+                Some(unit) => format!(
+                    "::ruststep::dictionary::Measure<::ruststep::dictionary::units::{}>",
+                    unit
+                ),
+                None => "f64".to_string(),
+            },
+            DataType::Integer => "i64".to_string(),
+            DataType::Boolean => "bool".to_string(),
+            DataType::String => "String".to_string(),
+            DataType::Unsupported(_) => unreachable!(),
+        };
+        writeln!(out, "#[doc = {:?}]", property.name).unwrap();
+        writeln!(out, "#[doc = \"\"]").unwrap();
+        writeln!(out, "#[doc = {:?}]", property.definition).unwrap();
+        writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]\npub struct {};\n",
+            marker
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "impl ::ruststep::dictionary::PropertyDef for {} {{\n    const CODE: &'static str = {:?};\n    const VERSION: &'static str = {:?};\n    type Value = {};\n}}\n",
+            marker, property.code, property.version, value
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "/// Properties sorted by codes\npub static REGISTRY: &[::ruststep::dictionary::PropertyInfo] = &["
+    )
+    .unwrap();
+    for property in &properties {
+        let unit = match &property.data_type {
+            DataType::Real { unit: Some(unit) } => format!("Some({:?})", unit),
+            _ => "None".to_string(),
+        };
+        writeln!(
+            out,
+            "    ::ruststep::dictionary::PropertyInfo {{ code: {:?}, version: {:?}, name: {:?}, unit: {} }},",
+            property.code, property.version, property.name, unit
+        )
+        .unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+    writeln!(
+        out,
+        "/// Property of the code in [REGISTRY]\npub fn property(code: &str) -> Option<&'static ::ruststep::dictionary::PropertyInfo> {{\n    REGISTRY\n        .binary_search_by(|info| info.code.cmp(code))\n        .ok()\n        .map(|index| &REGISTRY[index])\n}}"
+    )
+    .unwrap();
+    out
+}
+
+/// Upper camel case identifier of a name, e.g. `ToolCuttingEdgeAngle` for `tool cutting edge angle`
+fn marker_name(name: &str) -> String {
+    let words: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect();
+    let name = words.to_pascal_case();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) || name == "Self" {
+        format!("Property{}", name)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_names() {
+        assert_eq!(
+            marker_name("tool cutting edge angle"),
+            "ToolCuttingEdgeAngle"
+        );
+        assert_eq!(
+            marker_name("insert/holder clamping"),
+            "InsertHolderClamping"
+        );
+        assert_eq!(marker_name("2nd cutting edge"), "Property2NdCuttingEdge");
+    }
+}
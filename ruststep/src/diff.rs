@@ -0,0 +1,527 @@
+//! Structural diff of two exchanges regardless of entity ids
+//!
+//! Exporters renumber and reorder entity instances on every save,
+//! and thus [diff] matches instances of two exchanges by their contents instead of their ids:
+//!
+//! ```
+//! use ruststep::{ast::Exchange, diff::*};
+//! use std::str::FromStr;
+//!
+//! let file = |data: &str| Exchange::from_str(&format!(r#"ISO-10303-21;
+//! HEADER;
+//! FILE_DESCRIPTION((''), '2;1');
+//! ENDSEC;
+//! DATA;
+//! {}
+//! ENDSEC;
+//! END-ISO-10303-21;
+//! "#, data)).unwrap();
+//!
+//! let a = file("#1 = POINT('a', (0.0, 0.0)); #2 = VERTEX(#1); #3 = POINT('b', (1.0, 0.0));");
+//! let b = file("#10 = POINT('b', (1.0, 0.0)); #11 = POINT('a', (0.0, 0.000001)); #12 = VERTEX(#11);");
+//!
+//! // Renumbered, reordered, and jittered
+//! let report = diff(&a, &b, &DiffOptions { tolerance: 1e-5 });
+//! assert!(report.is_empty());
+//! assert_eq!(report.matched, [(1, 11), (2, 12), (3, 10)]);
+//!
+//! let report = diff(&a, &b, &DiffOptions::default());
+//! assert_eq!(report.modified.len(), 1);
+//! assert_eq!(report.to_string(), "modified #1 -> #11\n  POINT[1][1]: 0.0 -> 1.E-6\n");
+//! ```
+//!
+//! Matching
+//! ---------
+//! Instances in all data sections are matched as follows. Headers are not compared.
+//!
+//! 1. Instances of the same [deep hash](crate::hash#deep-mode) are matched,
+//!    i.e. the instances and all instances referred from them transitively are identical.
+//!    Reals are rounded to a multiple of [DiffOptions::tolerance] before hashing.
+//! 2. The other instances are matched if they are unique in both exchanges with the same keywords and parameters,
+//!    where references to matched instances are compared by the matching,
+//!    and references to unmatched instances are regarded as equal.
+//!    This matches instances modified only in the instances they refer.
+//! 3. Unmatched instances referred at the same position from matched instances are matched
+//!    if their keywords are the same.
+//! 4. If nothing is matched by 2 and 3, instances unique in both exchanges
+//!    with the same keywords and parameters except reals are matched.
+//! 5. If nothing is matched still, instances of the same keywords and parameters as 2 are matched in the order of ids.
+//! 6. If nothing is matched still, instances unique in both exchanges with the same keywords are matched.
+//!
+//! Steps 2 to 6 are repeated until no instance is matched,
+//! and the remaining instances are reported as [DiffReport::removed] and [DiffReport::added].
+//! Matched instances except those matched in step 1 are compared parameter by parameter,
+//! and reported as [DiffReport::modified] if some parameters differ.
+
+use crate::{
+    ast::*,
+    hash::{entity_hashes, HashMode, HashOptions},
+};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    fmt,
+};
+
+/// Options for [diff]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiffOptions {
+    /// Reals are regarded as equal if their difference is within this value
+    pub tolerance: f64,
+}
+
+/// Result of [diff]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiffReport {
+    /// Pairs of ids in `a` and `b` of matched instances in ascending order of ids in `a`,
+    /// including modified instances
+    pub matched: Vec<(u64, u64)>,
+    /// Ids of instances only in `a` in ascending order
+    pub removed: Vec<u64>,
+    /// Ids of instances only in `b` in ascending order
+    pub added: Vec<u64>,
+    /// Matched instances with different parameters in ascending order of ids in `a`
+    pub modified: Vec<Modified>,
+}
+
+impl DiffReport {
+    /// No instance is added, removed, or modified
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.modified.is_empty()
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for id in &self.removed {
+            writeln!(f, "removed #{}", id)?;
+        }
+        for id in &self.added {
+            writeln!(f, "added #{}", id)?;
+        }
+        for modified in &self.modified {
+            writeln!(f, "modified #{} -> #{}", modified.old, modified.new)?;
+            for change in &modified.changes {
+                write!(f, "  {}", change.keyword)?;
+                for index in &change.path {
+                    write!(f, "[{}]", index)?;
+                }
+                writeln!(f, ": {} -> {}", change.old, change.new)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Instance matched by [diff] with different parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modified {
+    /// Id in `a`
+    pub old: u64,
+    /// Id in `b`
+    pub new: u64,
+    pub changes: Vec<ParameterChange>,
+}
+
+/// Parameter differing between matched instances
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterChange {
+    /// Index of the partial record in a complex entity instance, `0` for a simple entity instance
+    pub record: usize,
+    /// Keyword of the record
+    pub keyword: String,
+    /// Position in the parameters of the record, see [Parameter::get]
+    pub path: Vec<usize>,
+    /// Parameter in `a`. References are the ids in `a`.
+    pub old: Parameter,
+    /// Parameter in `b`. References are the ids in `b`.
+    pub new: Parameter,
+}
+
+/// Compare entity instances of `a` and `b` regardless of their ids, see [Matching](self#matching)
+pub fn diff(a: &Exchange, b: &Exchange, options: &DiffOptions) -> DiffReport {
+    let mut matcher = Matcher {
+        a: Side::new(a, options.tolerance),
+        b: Side::new(b, options.tolerance),
+        tolerance: options.tolerance,
+        forward: HashMap::new(),
+        backward: HashMap::new(),
+    };
+    let identical = matcher.match_identical();
+    loop {
+        let matched = matcher.forward.len();
+        let new = matcher.match_by_key(Compare::Parameters, true);
+        matcher.match_referred(new);
+        if matcher.forward.len() == matched {
+            let new = matcher.match_by_key(Compare::IgnoreReals, true);
+            matcher.match_referred(new);
+        }
+        if matcher.forward.len() == matched {
+            let new = matcher.match_by_key(Compare::Parameters, false);
+            matcher.match_referred(new);
+        }
+        if matcher.forward.len() == matched {
+            let new = matcher.match_by_key(Compare::KeywordsOnly, true);
+            matcher.match_referred(new);
+        }
+        if matcher.forward.len() == matched {
+            break;
+        }
+    }
+    matcher.report(&identical)
+}
+
+/// Instances of all data sections in an exchange
+struct Side<'a> {
+    /// The first instance is used if an id is duplicated
+    instances: BTreeMap<u64, &'a EntityInstance>,
+    deep_hashes: HashMap<u64, u128>,
+}
+
+impl<'a> Side<'a> {
+    fn new(exchange: &'a Exchange, tolerance: f64) -> Self {
+        let mut instances = BTreeMap::new();
+        for instance in exchange.data.iter().flat_map(|section| &section.entities) {
+            instances.entry(instance.id()).or_insert(instance);
+        }
+        let rounded = DataSection {
+            meta: Vec::new(),
+            entities: instances
+                .values()
+                .map(|instance| {
+                    let mut instance = (*instance).clone();
+                    for record in instance.records_mut() {
+                        round_reals(&mut record.parameter, tolerance);
+                    }
+                    instance
+                })
+                .collect(),
+        };
+        let deep_hashes = entity_hashes(
+            &rounded,
+            &HashOptions {
+                mode: HashMode::Deep,
+            },
+        );
+        Side {
+            instances,
+            deep_hashes,
+        }
+    }
+
+    fn keywords(&self, id: u64) -> Option<Vec<String>> {
+        let instance = self.instances.get(&id)?;
+        Some(instance.records().iter().map(Record::keyword).collect())
+    }
+}
+
+/// What is compared by [Matcher::key]
+#[derive(Clone, Copy)]
+enum Compare {
+    /// Keywords and parameters with rounded reals
+    Parameters,
+    /// Keywords and parameters except reals
+    IgnoreReals,
+    KeywordsOnly,
+}
+
+struct Matcher<'a> {
+    a: Side<'a>,
+    b: Side<'a>,
+    tolerance: f64,
+    /// Matched ids from `a` to `b`
+    forward: HashMap<u64, u64>,
+    /// Matched ids from `b` to `a`
+    backward: HashMap<u64, u64>,
+}
+
+impl<'a> Matcher<'a> {
+    fn insert(&mut self, a: u64, b: u64) {
+        self.forward.insert(a, b);
+        self.backward.insert(b, a);
+    }
+
+    /// Match instances of the same deep hash, and returns the matched ids in `a`
+    fn match_identical(&mut self) -> Vec<u64> {
+        let mut candidates: HashMap<u128, VecDeque<u64>> = HashMap::new();
+        for id in self.a.instances.keys() {
+            candidates
+                .entry(self.a.deep_hashes[id])
+                .or_default()
+                .push_back(*id);
+        }
+        let mut matched = Vec::new();
+        let ids: Vec<u64> = self.b.instances.keys().copied().collect();
+        for b in ids {
+            if let Some(a) = candidates
+                .get_mut(&self.b.deep_hashes[&b])
+                .and_then(VecDeque::pop_front)
+            {
+                self.insert(a, b);
+                matched.push(a);
+            }
+        }
+        matched
+    }
+
+    /// Match unmatched instances of the same [Matcher::key], and returns the matched pairs
+    fn match_by_key(&mut self, compare: Compare, unique: bool) -> Vec<(u64, u64)> {
+        let mut candidates: HashMap<String, Vec<u64>> = HashMap::new();
+        for (id, instance) in &self.a.instances {
+            if !self.forward.contains_key(id) {
+                let key = self.key(instance, compare, &|id| {
+                    self.forward.contains_key(&id).then_some(id)
+                });
+                candidates.entry(key).or_default().push(*id);
+            }
+        }
+        let mut keys: HashMap<String, Vec<u64>> = HashMap::new();
+        for (id, instance) in &self.b.instances {
+            if !self.backward.contains_key(id) {
+                let key = self.key(instance, compare, &|id| self.backward.get(&id).copied());
+                keys.entry(key).or_default().push(*id);
+            }
+        }
+
+        let mut matched = Vec::new();
+        for (key, ids) in keys {
+            let Some(a) = candidates.get(&key) else {
+                continue;
+            };
+            if unique && (a.len() != 1 || ids.len() != 1) {
+                continue;
+            }
+            for (a, b) in a.iter().zip(ids) {
+                matched.push((*a, b));
+            }
+        }
+        matched.sort_unstable();
+        for (a, b) in &matched {
+            self.insert(*a, *b);
+        }
+        matched
+    }
+
+    /// Keywords and parameters where references are replaced by the ids in `a` given by `matched`
+    fn key(
+        &self,
+        instance: &EntityInstance,
+        compare: Compare,
+        matched: &dyn Fn(u64) -> Option<u64>,
+    ) -> String {
+        fn normalize(
+            parameter: &Parameter,
+            compare: Compare,
+            tolerance: f64,
+            matched: &dyn Fn(u64) -> Option<u64>,
+        ) -> Parameter {
+            match parameter {
+                Parameter::Real { .. } if matches!(compare, Compare::IgnoreReals) => {
+                    Parameter::Omitted
+                }
+                Parameter::Real { value, .. } => {
+                    let mut value = Parameter::real(*value);
+                    round_reals(&mut value, tolerance);
+                    value
+                }
+                Parameter::Ref(Name::Entity(id)) => match matched(*id) {
+                    Some(id) => Parameter::Ref(Name::Entity(id)),
+                    None => Parameter::Ref(Name::ConstantEntity("?".to_string())),
+                },
+                Parameter::Typed { keyword, parameter } => Parameter::Typed {
+                    keyword: keyword.clone(),
+                    parameter: Box::new(normalize(parameter, compare, tolerance, matched)),
+                },
+                Parameter::List(parameters) => parameters
+                    .iter()
+                    .map(|p| normalize(p, compare, tolerance, matched))
+                    .collect(),
+                _ => parameter.clone(),
+            }
+        }
+        instance
+            .records()
+            .iter()
+            .map(|record| match compare {
+                Compare::KeywordsOnly => record.keyword(),
+                _ => {
+                    let parameter = normalize(&record.parameter, compare, self.tolerance, matched);
+                    format!("{}{}", record.keyword(), parameter)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Match unmatched instances referred at the same position from `matched` pairs and newly matched pairs
+    fn match_referred(&mut self, matched: Vec<(u64, u64)>) {
+        fn walk(a: &Parameter, b: &Parameter, refs: &mut Vec<(u64, u64)>) {
+            match (a, b) {
+                (Parameter::Ref(Name::Entity(a)), Parameter::Ref(Name::Entity(b))) => {
+                    refs.push((*a, *b))
+                }
+                (Parameter::List(a), Parameter::List(b)) if a.len() == b.len() => {
+                    for (a, b) in a.iter().zip(b) {
+                        walk(a, b, refs);
+                    }
+                }
+                (
+                    Parameter::Typed {
+                        keyword: ka,
+                        parameter: a,
+                    },
+                    Parameter::Typed {
+                        keyword: kb,
+                        parameter: b,
+                    },
+                ) if ka == kb => walk(a, b, refs),
+                _ => {}
+            }
+        }
+
+        let mut queue: VecDeque<(u64, u64)> = matched.into();
+        while let Some((a, b)) = queue.pop_front() {
+            let mut refs = Vec::new();
+            let records = self.a.instances[&a].records();
+            for (ra, rb) in records.iter().zip(self.b.instances[&b].records()) {
+                walk(&ra.parameter, &rb.parameter, &mut refs);
+            }
+            for (a, b) in refs {
+                if self.forward.contains_key(&a) || self.backward.contains_key(&b) {
+                    continue;
+                }
+                match (self.a.keywords(a), self.b.keywords(b)) {
+                    (Some(ka), Some(kb)) if ka == kb => {
+                        self.insert(a, b);
+                        queue.push_back((a, b));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn report(&self, identical: &[u64]) -> DiffReport {
+        let mut matched: Vec<(u64, u64)> = self.forward.iter().map(|(a, b)| (*a, *b)).collect();
+        matched.sort_unstable();
+        let identical: std::collections::HashSet<u64> = identical.iter().copied().collect();
+        let modified = matched
+            .iter()
+            .filter(|(a, _)| !identical.contains(a))
+            .filter_map(|(a, b)| {
+                let mut changes = Vec::new();
+                let records = self.a.instances[a].records();
+                for (index, (ra, rb)) in records
+                    .iter()
+                    .zip(self.b.instances[b].records())
+                    .enumerate()
+                {
+                    let mut path = Vec::new();
+                    let mut push = |path: &[usize], old: &Parameter, new: &Parameter| {
+                        changes.push(ParameterChange {
+                            record: index,
+                            keyword: ra.keyword(),
+                            path: path.to_vec(),
+                            old: old.clone(),
+                            new: new.clone(),
+                        })
+                    };
+                    self.compare(&ra.parameter, &rb.parameter, &mut path, &mut push);
+                }
+                (!changes.is_empty()).then_some(Modified {
+                    old: *a,
+                    new: *b,
+                    changes,
+                })
+            })
+            .collect();
+        DiffReport {
+            removed: self
+                .a
+                .instances
+                .keys()
+                .filter(|id| !self.forward.contains_key(id))
+                .copied()
+                .collect(),
+            added: self
+                .b
+                .instances
+                .keys()
+                .filter(|id| !self.backward.contains_key(id))
+                .copied()
+                .collect(),
+            matched,
+            modified,
+        }
+    }
+
+    fn compare(
+        &self,
+        a: &Parameter,
+        b: &Parameter,
+        path: &mut Vec<usize>,
+        push: &mut dyn FnMut(&[usize], &Parameter, &Parameter),
+    ) {
+        match (a, b) {
+            (Parameter::Real { value: x, .. }, Parameter::Real { value: y, .. })
+                if (x - y).abs() <= self.tolerance => {}
+            (Parameter::Ref(Name::Entity(x)), Parameter::Ref(Name::Entity(y))) => {
+                let same = match self.forward.get(x) {
+                    Some(matched) => matched == y,
+                    // Both refer an instance not in the exchange, e.g. in the REFERENCE section
+                    None => {
+                        x == y
+                            && !self.a.instances.contains_key(x)
+                            && !self.b.instances.contains_key(y)
+                    }
+                };
+                if !same {
+                    push(path, a, b);
+                }
+            }
+            (Parameter::List(xs), Parameter::List(ys)) if xs.len() == ys.len() => {
+                for (i, (x, y)) in xs.iter().zip(ys).enumerate() {
+                    path.push(i);
+                    self.compare(x, y, path, push);
+                    path.pop();
+                }
+            }
+            (
+                Parameter::Typed {
+                    keyword: kx,
+                    parameter: x,
+                },
+                Parameter::Typed {
+                    keyword: ky,
+                    parameter: y,
+                },
+            ) if kx == ky => self.compare(x, y, path, push),
+            _ if a == b => {}
+            _ => push(path, a, b),
+        }
+    }
+}
+
+/// Round reals to a multiple of `tolerance`, and regard `-0.0` as `0.0`
+fn round_reals(parameter: &mut Parameter, tolerance: f64) {
+    match parameter {
+        Parameter::Real { value, raw } => {
+            *raw = None;
+            if tolerance > 0.0 {
+                *value = (*value / tolerance).round() * tolerance;
+            }
+            if *value == 0.0 {
+                *value = 0.0;
+            }
+        }
+        Parameter::Typed { parameter, .. } => round_reals(parameter, tolerance),
+        Parameter::List(parameters) => {
+            for parameter in parameters {
+                round_reals(parameter, tolerance);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,352 @@
+//! Undo and redo edits of [DataSection]
+//!
+//! [EditSession] applies mutations to a [DataSection] and records them in a journal,
+//! so that they can be reverted by [EditSession::undo] and re-applied by [EditSession::redo].
+//!
+//! ```
+//! use ruststep::{ast::*, edit::EditSession};
+//! use std::str::FromStr;
+//!
+//! let mut section = DataSection::from_str(r#"
+//! DATA;
+//!   #1 = A(1.0);
+//!   #2 = B(#1);
+//! ENDSEC;
+//! "#).unwrap();
+//! let original = section.clone();
+//!
+//! let mut session = EditSession::new(&mut section);
+//! session.checkpoint("loaded");
+//! session.set_attribute(1, 0, Parameter::real(2.0)).unwrap();
+//! session.insert(EntityInstance::from_str("#3 = A(3.0);").unwrap()).unwrap();
+//! session.rewrite_references(1, 3);
+//! assert_eq!(session.gc(&[2]), 1); // #1 is not referred anymore
+//!
+//! session.undo();
+//! assert!(session.section().instance(1).is_some());
+//!
+//! session.revert_to("loaded").unwrap();
+//! assert_eq!(session.section(), &original);
+//! ```
+//!
+//! Each operation is validated before modifying the section,
+//! and thus the section and the journal are unchanged if it returns an error.
+
+use crate::{
+    ast::{DataSection, EntityInstance, Parameter},
+    error::{Error, Result},
+};
+use std::collections::{HashMap, VecDeque};
+
+/// Primitive modification of [DataSection::entities]
+#[derive(Debug, Clone)]
+enum Edit {
+    Insert {
+        position: usize,
+        instance: EntityInstance,
+    },
+    Remove {
+        position: usize,
+        instance: EntityInstance,
+    },
+    Replace {
+        position: usize,
+        before: EntityInstance,
+        after: EntityInstance,
+    },
+}
+
+impl Edit {
+    fn apply(&self, section: &mut DataSection) {
+        match self {
+            Edit::Insert { position, instance } => {
+                section.entities.insert(*position, instance.clone())
+            }
+            Edit::Remove { position, .. } => {
+                section.entities.remove(*position);
+            }
+            Edit::Replace {
+                position, after, ..
+            } => section.entities[*position] = after.clone(),
+        }
+    }
+
+    fn revert(&self, section: &mut DataSection) {
+        match self {
+            Edit::Insert { position, .. } => {
+                section.entities.remove(*position);
+            }
+            Edit::Remove { position, instance } => {
+                section.entities.insert(*position, instance.clone())
+            }
+            Edit::Replace {
+                position, before, ..
+            } => section.entities[*position] = before.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    /// Edits of an operation in the order of application
+    Change(Vec<Edit>),
+    Checkpoint(String),
+}
+
+/// Mutable access to a [DataSection] recording a journal for undo and redo
+#[derive(Debug)]
+pub struct EditSession<'a> {
+    section: &'a mut DataSection,
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    journal_size: usize,
+}
+
+impl<'a> EditSession<'a> {
+    /// Start a session with an unbounded journal
+    pub fn new(section: &'a mut DataSection) -> Self {
+        EditSession {
+            section,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            journal_size: usize::MAX,
+        }
+    }
+
+    /// Keep at most `size` operations for undo
+    ///
+    /// When the journal exceeds, the operations before the oldest checkpoint are evicted.
+    /// If there is no checkpoint after the oldest operation, only the oldest operation is evicted
+    /// with the checkpoints before it since they cannot be reverted to anymore.
+    ///
+    /// ```
+    /// use ruststep::{ast::*, edit::EditSession};
+    /// use std::str::FromStr;
+    ///
+    /// let mut section = DataSection::from_str("DATA; #1 = A(0); ENDSEC;").unwrap();
+    /// let mut session = EditSession::new(&mut section).journal_size(2);
+    /// session.checkpoint("start");
+    /// for i in 1..=3 {
+    ///     session.set_attribute(1, 0, Parameter::Integer(i)).unwrap();
+    /// }
+    /// // The first operation and the checkpoint before it are evicted
+    /// assert!(session.revert_to("start").is_err());
+    /// assert!(session.undo());
+    /// assert!(session.undo());
+    /// assert!(!session.undo());
+    /// assert_eq!(session.section().entities[0].to_string(), "#1 = A(1);");
+    /// ```
+    pub fn journal_size(mut self, size: usize) -> Self {
+        self.journal_size = size;
+        self.evict();
+        self
+    }
+
+    /// Current state of the section
+    pub fn section(&self) -> &DataSection {
+        self.section
+    }
+
+    /// Append `instance` at the end of the section, see [DataSection::insert]
+    pub fn insert(&mut self, instance: EntityInstance) -> Result<()> {
+        let position = self.section.entities.len();
+        self.section.insert(instance.clone())?;
+        self.record(vec![Edit::Insert { position, instance }]);
+        Ok(())
+    }
+
+    /// Remove the instance of `id`, see [DataSection::remove]
+    pub fn remove(&mut self, id: u64) -> Result<EntityInstance> {
+        let position = self.position(id)?;
+        let instance = self.section.remove(id).expect("position is checked");
+        self.record(vec![Edit::Remove {
+            position,
+            instance: instance.clone(),
+        }]);
+        Ok(instance)
+    }
+
+    /// Set the attribute at `index` of a simple entity instance `#id`, and returns the previous value
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::UnknownEntity] if `#id` is not in the section
+    /// - [Error::UnknownAttribute] if `#id` is a complex entity instance, or it does not have the attribute
+    ///
+    pub fn set_attribute(&mut self, id: u64, index: usize, value: Parameter) -> Result<Parameter> {
+        let position = self.position(id)?;
+        let before = self.section.entities[position].clone();
+        let mut after = before.clone();
+        let attribute = after
+            .record_mut()
+            .and_then(|record| record.attribute_mut(index))
+            .ok_or(Error::UnknownAttribute { id, index })?;
+        let previous = std::mem::replace(attribute, value);
+        self.section.entities[position] = after.clone();
+        self.record(vec![Edit::Replace {
+            position,
+            before,
+            after,
+        }]);
+        Ok(previous)
+    }
+
+    /// Replace references to `#from` by `#to`, see [DataSection::rewrite_references]
+    pub fn rewrite_references(&mut self, from: u64, to: u64) -> usize {
+        let before: Vec<(usize, EntityInstance)> = self
+            .section
+            .entities
+            .iter()
+            .enumerate()
+            .filter(|(_, instance)| instance.references().contains(&from))
+            .map(|(position, instance)| (position, instance.clone()))
+            .collect();
+        let count = self.section.rewrite_references(from, to);
+        let edits = before
+            .into_iter()
+            .map(|(position, before)| Edit::Replace {
+                position,
+                after: self.section.entities[position].clone(),
+                before,
+            })
+            .collect();
+        self.record(edits);
+        count
+    }
+
+    /// Remove instances not reachable from `roots`, see [DataSection::gc]
+    ///
+    /// The removed instances are kept in the journal to restore them.
+    /// Returns the number of removed instances.
+    pub fn gc(&mut self, roots: &[u64]) -> usize {
+        let positions: HashMap<u64, usize> = self
+            .section
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(position, instance)| (instance.id(), position))
+            .collect();
+        let removed = self.section.gc(roots);
+        let count = removed.len();
+        // Remove from the last one not to shift positions of the others
+        let edits = removed
+            .into_iter()
+            .rev()
+            .map(|instance| Edit::Remove {
+                position: positions[&instance.id()],
+                instance,
+            })
+            .collect();
+        self.record(edits);
+        count
+    }
+
+    /// Mark the current state as `label` for [EditSession::revert_to]
+    pub fn checkpoint(&mut self, label: &str) {
+        self.undo.push_back(Entry::Checkpoint(label.to_string()));
+    }
+
+    /// Revert the last operation. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let mut checkpoints = Vec::new();
+        while let Some(entry) = self.undo.pop_back() {
+            match entry {
+                Entry::Checkpoint(_) => checkpoints.push(entry),
+                Entry::Change(edits) => {
+                    for edit in edits.iter().rev() {
+                        edit.revert(self.section);
+                    }
+                    self.redo.extend(checkpoints);
+                    self.redo.push(Entry::Change(edits));
+                    return true;
+                }
+            }
+        }
+        // Only checkpoints remain
+        self.undo.extend(checkpoints.into_iter().rev());
+        false
+    }
+
+    /// Re-apply the last operation reverted by [EditSession::undo].
+    /// Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let edits = match self.redo.pop() {
+            Some(Entry::Change(edits)) => edits,
+            Some(Entry::Checkpoint(_)) => unreachable!("checkpoints are pushed before a change"),
+            None => return false,
+        };
+        for edit in &edits {
+            edit.apply(self.section);
+        }
+        self.undo.push_back(Entry::Change(edits));
+        while let Some(Entry::Checkpoint(_)) = self.redo.last() {
+            let checkpoint = self.redo.pop().unwrap();
+            self.undo.push_back(checkpoint);
+        }
+        self.evict();
+        true
+    }
+
+    /// Undo operations until the state marked by [EditSession::checkpoint]
+    ///
+    /// The latest checkpoint is used if there are checkpoints of the same `label`.
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::UnknownCheckpoint] if `label` is not in the journal, e.g. evicted.
+    ///   The section is unchanged in this case.
+    ///
+    pub fn revert_to(&mut self, label: &str) -> Result<()> {
+        let target = self
+            .undo
+            .iter()
+            .rposition(|entry| matches!(entry, Entry::Checkpoint(l) if l == label))
+            .ok_or_else(|| Error::UnknownCheckpoint(label.to_string()))?;
+        while self.undo.len() > target + 1 && self.undo() {}
+        Ok(())
+    }
+
+    fn position(&self, id: u64) -> Result<usize> {
+        self.section
+            .entities
+            .iter()
+            .position(|instance| instance.id() == id)
+            .ok_or(Error::UnknownEntity(id))
+    }
+
+    fn record(&mut self, edits: Vec<Edit>) {
+        self.redo.clear();
+        self.undo.push_back(Entry::Change(edits));
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        let changes = |undo: &VecDeque<Entry>| {
+            undo.iter()
+                .filter(|entry| matches!(entry, Entry::Change(_)))
+                .count()
+        };
+        while changes(&self.undo) > self.journal_size {
+            let first = self
+                .undo
+                .iter()
+                .position(|entry| matches!(entry, Entry::Change(_)))
+                .expect("journal has changes");
+            let checkpoint = self
+                .undo
+                .iter()
+                .skip(first)
+                .position(|entry| matches!(entry, Entry::Checkpoint(_)));
+            match checkpoint {
+                // Evict operations until the oldest checkpoint, which is still reachable
+                Some(offset) => {
+                    self.undo.drain(..first + offset);
+                }
+                // Checkpoints before the evicted operation are no longer reachable
+                None => {
+                    self.undo.drain(..=first);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
+use crate::parser::combinator::ParserError;
 use serde::{de, ser};
-use std::fmt;
+use std::{cell::RefCell, fmt};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -8,28 +9,333 @@ pub enum Error {
     #[error(transparent)]
     TokenizeFailed(#[from] TokenizeFailed),
 
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
     #[error("Extra input string remains behind: {0}")]
     ExtraInputRemaining(String),
 
     #[error("Error while deserialize STEP struct: {0}")]
     DeserializeFailed(String),
 
+    #[error("Invalid binary: \"{0}\"")]
+    InvalidBinary(String),
+
     #[error("Lookup failed for #{0}")]
     UnknownEntity(u64),
 
+    #[error("Lookup failed for #{id}: target is in unloaded section {section}")]
+    EntityInUnloadedSection { id: u64, section: usize },
+
     #[error("Entity ID #{0} is duplicated")]
     DuplicatedEntity(u64),
 
     #[error("Entity '{entity_name}' is not a member of the schema '{schema}'")]
     UnknownEntityName { entity_name: String, schema: String },
+
+    #[error("Assembly structure is cyclic at product definition #{0}")]
+    CyclicAssembly(u64),
+
+    #[error("Reference to #{0} is cyclic")]
+    CyclicReference(u64),
+
+    #[error("Constant {0} is not defined")]
+    UnknownConstant(String),
+
+    #[error("Value instance @{0} is not supported")]
+    UnsupportedValueInstance(u64),
+
+    #[error("References to undefined entity instances: {}", display_ids(.0))]
+    DanglingReferences(Vec<u64>),
+
+    #[error("References {} cannot be resolved without tables", display_ids(.0))]
+    UnresolvedReferences(Vec<u64>),
+
+    #[error(
+        "Cache written by ruststep {version} for schema {fingerprint:016x} is stale for ruststep {} and schema {expected:016x}",
+        env!("CARGO_PKG_VERSION")
+    )]
+    StaleCache {
+        version: String,
+        fingerprint: u64,
+        expected: u64,
+    },
+
+    #[error("Complex entity instance #{id} of ({}) cannot be placed in tables", keywords.join(" "))]
+    UnplacedComplexEntity { id: u64, keywords: Vec<String> },
+
+    #[error("{0} is ABSTRACT SUPERTYPE, and cannot be instantiated as a simple record")]
+    AbstractEntityInstance(String),
+
+    #[error(
+        "ARRAY of {expected} elements is expected by its bound, but {found} elements are given"
+    )]
+    ArrayLengthMismatch { expected: usize, found: usize },
+
+    /// Used internally by [crate::tables::get_owned], and never returned to users
+    #[doc(hidden)]
+    #[error("Resolution is deferred")]
+    ResolutionDeferred,
+
+    #[error("Loading is cancelled")]
+    Cancelled,
+
+    #[error("Parameter {position} of {entity} is not acceptable as {expected:?}: {found:?}")]
+    AttributeKindMismatch {
+        entity: String,
+        position: usize,
+        expected: crate::tables::AttributeKind,
+        found: crate::ast::Parameter,
+    },
+
+    #[error("{record} in HEADER section is invalid: {message}")]
+    InvalidHeader { record: String, message: String },
+
+    #[error("Keyword mismatch: expected '{expected}', but found '{found}'")]
+    KeywordMismatch { expected: String, found: String },
+
+    #[error("#{id} does not have attribute at {index}")]
+    UnknownAttribute { id: u64, index: usize },
+
+    #[error("Checkpoint '{0}' is not found in the journal")]
+    UnknownCheckpoint(String),
+
+    #[error("No free entity id below {ceiling}")]
+    IdExhausted { ceiling: u64 },
+
+    #[error("Statement exceeds {limit} bytes")]
+    StatementTooLarge { limit: usize },
+
+    /// [ParseOptions](crate::parser::ParseOptions) field which cannot be applied by the parser
+    #[error("ParseOptions::{option} is not supported by {parser}")]
+    UnsupportedOption {
+        option: &'static str,
+        parser: &'static str,
+    },
+
+    #[error("#{id}: {source}")]
+    InstanceFailed { id: u64, source: Box<Error> },
+
+    #[error("{}: {source}", path.display())]
+    FileFailed {
+        path: std::path::PathBuf,
+        source: Box<Error>,
+    },
+
+    #[error("Repair failed after {} repairs: {source}", log.repairs.len())]
+    RepairFailed {
+        source: Box<Error>,
+        log: crate::repair::RepairLog,
+    },
+
+    /// Error in deserializing or resolving an entity instance, see [ErrorContext]
+    #[error("{context}: {}", display_source(source))]
+    InContext {
+        context: ErrorContext,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Instance and attribute where this error occurs
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::InContext { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// This error without [Error::InContext]
+    ///
+    /// ```
+    /// use ruststep::error::*;
+    ///
+    /// let err = Error::InContext {
+    ///     context: ErrorContext {
+    ///         id: Some(4521),
+    ///         keyword: Some("ADVANCED_FACE".to_string()),
+    ///         attribute: Some("bounds[2].bound".to_string()),
+    ///     },
+    ///     source: Box::new(Error::DeserializeFailed("expected entity reference".to_string())),
+    /// };
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "in #4521 (ADVANCED_FACE), attribute 'bounds[2].bound': expected entity reference"
+    /// );
+    /// assert!(matches!(err.without_context(), Error::DeserializeFailed(_)));
+    /// ```
+    pub fn without_context(&self) -> &Error {
+        match self {
+            Error::InContext { source, .. } => source.without_context(),
+            _ => self,
+        }
+    }
+
+    /// Add the instance where this error occurs, unless an inner instance is already known
+    pub(crate) fn in_instance(self, id: u64, keyword: &str) -> Self {
+        match self {
+            // Not an error, see [crate::tables::get_owned]
+            Error::ResolutionDeferred => self,
+            Error::InContext {
+                mut context,
+                source,
+            } => {
+                if context.id.is_none() {
+                    context.id = Some(id);
+                    context.keyword = Some(keyword.to_string());
+                }
+                Error::InContext { context, source }
+            }
+            source => Error::InContext {
+                context: ErrorContext {
+                    id: Some(id),
+                    keyword: Some(keyword.to_string()),
+                    attribute: None,
+                },
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Add the attribute being deserialized where this error occurs
+    pub(crate) fn in_attribute(self) -> Self {
+        match attribute_path() {
+            Some(attribute) => Error::InContext {
+                context: ErrorContext {
+                    id: None,
+                    keyword: None,
+                    attribute: Some(attribute),
+                },
+                source: Box::new(self),
+            },
+            None => self,
+        }
+    }
+}
+
+/// Message of [Error::DeserializeFailed] is shown without its prefix after the context
+fn display_source(source: &Error) -> String {
+    match source {
+        Error::DeserializeFailed(message) => message.clone(),
+        _ => source.to_string(),
+    }
+}
+
+/// Where an error occurs, e.g. `in #4521 (ADVANCED_FACE), attribute 'bounds[2].bound'`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Id of the entity instance, the innermost one when references are resolved
+    pub id: Option<u64>,
+    /// Keyword of the entity instance
+    pub keyword: Option<String>,
+    /// Path of the attribute from the record,
+    /// where `[i]` is the `i`-th element from zero of an aggregate
+    pub attribute: Option<String>,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        match (self.id, &self.keyword) {
+            (Some(id), Some(keyword)) => parts.push(format!("in #{} ({})", id, keyword)),
+            (Some(id), None) => parts.push(format!("in #{}", id)),
+            (None, Some(keyword)) => parts.push(format!("in {}", keyword)),
+            (None, None) => {}
+        }
+        if let Some(attribute) = &self.attribute {
+            parts.push(format!("attribute '{}'", attribute));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+enum PathSegment {
+    /// Attribute of a record, which is `entered` when its parameter is being read
+    Attribute { name: &'static str, entered: bool },
+    /// Element of an aggregate
+    Index(usize),
+}
+
+thread_local! {
+    /// Attributes and elements being deserialized, see [enter_attribute]
+    static ATTRIBUTE_PATH: RefCell<Vec<PathSegment>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Segment of [ErrorContext::attribute] until dropped
+#[doc(hidden)]
+pub struct PathGuard {
+    pushed: bool,
+}
+
+impl Drop for PathGuard {
+    fn drop(&mut self) {
+        if self.pushed {
+            ATTRIBUTE_PATH.with(|path| path.borrow_mut().pop());
+        }
+    }
+}
+
+/// Called by the visitors generated by [ruststep_derive::Holder] before reading the parameter of attribute `name`
+#[doc(hidden)]
+pub fn enter_attribute(name: &'static str) -> PathGuard {
+    ATTRIBUTE_PATH.with(|path| {
+        path.borrow_mut().push(PathSegment::Attribute {
+            name,
+            entered: false,
+        })
+    });
+    PathGuard { pushed: true }
+}
+
+/// Called before reading the `index`-th element of a list.
+/// The parameter of an attribute entered by [enter_attribute] is not an element.
+pub(crate) fn enter_element(index: usize) -> PathGuard {
+    ATTRIBUTE_PATH.with(|path| {
+        let mut path = path.borrow_mut();
+        if let Some(PathSegment::Attribute { entered, .. }) = path.last_mut() {
+            if !*entered {
+                *entered = true;
+                return PathGuard { pushed: false };
+            }
+        }
+        path.push(PathSegment::Index(index));
+        PathGuard { pushed: true }
+    })
+}
+
+/// Current path, `None` if no attribute is being read
+fn attribute_path() -> Option<String> {
+    ATTRIBUTE_PATH.with(|path| {
+        let path = path.borrow();
+        if !path
+            .iter()
+            .any(|segment| matches!(segment, PathSegment::Attribute { .. }))
+        {
+            return None;
+        }
+        let mut rendered = String::new();
+        for segment in path.iter() {
+            match segment {
+                PathSegment::Attribute { name, .. } => {
+                    if !rendered.is_empty() {
+                        rendered.push('.');
+                    }
+                    rendered.push_str(name);
+                }
+                PathSegment::Index(index) => rendered.push_str(&format!("[{}]", index)),
+            }
+        }
+        Some(rendered)
+    })
 }
 
 impl de::Error for Error {
+    /// The attribute being deserialized is kept as [Error::InContext]
     fn custom<T>(msg: T) -> Self
     where
         T: fmt::Display,
     {
-        Error::DeserializeFailed(msg.to_string())
+        Error::DeserializeFailed(msg.to_string()).in_attribute()
     }
 }
 
@@ -42,17 +348,75 @@ impl ser::Error for Error {
     }
 }
 
+/// Conflict while merging exchanges, see [Exchange::merge_with](crate::ast::Exchange::merge_with)
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    #[error("Implementation levels are different: '{first}' and '{second}'")]
+    ImplementationLevelMismatch { first: String, second: String },
+
+    #[error("Anchor <{0}> exists in both exchanges")]
+    DuplicatedAnchor(String),
+
+    #[error(transparent)]
+    Invalid(#[from] Error),
+}
+
+/// Entity instances referring themselves transitively, see [DataSection::topological_order](crate::ast::DataSection::topological_order)
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Entity references are cyclic: {}", display_cycle(cycle))]
+pub struct CycleError {
+    /// Ids in a cycle, where each instance refers the next one, and the last one refers the first one
+    pub cycle: Vec<u64>,
+}
+
+fn display_cycle(cycle: &[u64]) -> String {
+    cycle
+        .iter()
+        .chain(cycle.first())
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn display_ids(ids: &[u64]) -> String {
+    ids.iter()
+        .map(|id| format!("#{}", id))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Token which is well-formed but cannot be represented, see [TokenizeFailed::token_error]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TokenError {
+    /// Entity or value instance name larger than [u64::MAX], e.g. `#18446744073709551616`
+    #[error("Instance name {text} is larger than u64::MAX")]
+    IdOverflow { text: String },
+    /// Integer out of the range of [i64], e.g. `9223372036854775808`
+    #[error("Integer {text} is out of the range of i64")]
+    IntegerOverflow { text: String },
+    /// Parameters are nested deeper than [ParseOptions::max_depth](crate::parser::ParseOptions::max_depth)
+    #[error("Parameters are nested to depth {depth} at byte {offset}")]
+    DepthLimitExceeded {
+        /// Nesting level of the parameter exceeding the limit, i.e. `max_depth + 1`
+        depth: usize,
+        /// Byte offset of the parameter in the input
+        offset: usize,
+    },
+}
+
 /// Error while tokenizing STEP input
 pub struct TokenizeFailed {
     rendered_error: String,
+    offset: usize,
+    token_error: Option<TokenError>,
 }
 
 impl fmt::Debug for TokenizeFailed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<(), fmt::Error> {
         write!(
             f,
-            "Error while tokenizing STEP input\n{}",
-            self.rendered_error
+            "Error while tokenizing STEP input at byte {}\n{}",
+            self.offset, self.rendered_error
         )?;
         Ok(())
     }
@@ -68,9 +432,56 @@ impl fmt::Display for TokenizeFailed {
 impl std::error::Error for TokenizeFailed {}
 
 impl TokenizeFailed {
-    pub fn new(input: &str, err: nom::error::VerboseError<&str>) -> Self {
+    pub fn new(input: &str, err: ParserError) -> Self {
+        use nom::Offset;
+        let offset = err
+            .verbose
+            .errors
+            .first()
+            .map(|(rest, _kind)| input.offset(rest))
+            .unwrap_or_default();
+        let token_error = err.token.map(|(rest, token)| match token {
+            TokenError::DepthLimitExceeded { depth, .. } => TokenError::DepthLimitExceeded {
+                depth,
+                offset: input.offset(rest),
+            },
+            token => token,
+        });
         TokenizeFailed {
-            rendered_error: nom::error::convert_error(input, err),
+            rendered_error: nom::error::convert_error(input, err.verbose),
+            offset,
+            token_error,
+        }
+    }
+
+    /// Structured cause of the error if a token cannot be represented,
+    /// `None` for other syntax errors
+    ///
+    /// ```
+    /// use ruststep::{error::{Error, TokenError}, parser::parse_data_section};
+    ///
+    /// let err = parse_data_section("DATA; #18446744073709551616 = A(); ENDSEC;").unwrap_err();
+    /// let Error::TokenizeFailed(err) = err else { panic!() };
+    /// assert_eq!(
+    ///     err.token_error(),
+    ///     Some(&TokenError::IdOverflow { text: "#18446744073709551616".to_string() })
+    /// );
+    /// ```
+    pub fn token_error(&self) -> Option<&TokenError> {
+        self.token_error.as_ref()
+    }
+
+    /// Byte offset in the input where the innermost error occurs
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Same error for the input where the tokenized part begins at `base`
+    pub(crate) fn shift(mut self, base: usize) -> Self {
+        self.offset += base;
+        if let Some(TokenError::DepthLimitExceeded { offset, .. }) = &mut self.token_error {
+            *offset += base;
         }
+        self
     }
 }
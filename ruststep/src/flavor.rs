@@ -0,0 +1,119 @@
+//! Adapt records between flavors of application protocols
+//!
+//! AP203 and AP214 share most of entities for product structures and contexts,
+//! but AP203 files often use subtypes of them which differ only in keywords or additional attributes,
+//! e.g. `PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE` with `make_or_buy` attribute
+//! instead of `PRODUCT_DEFINITION_FORMATION`.
+//! A [RecordAdapter] rewrites such records before they are deserialized,
+//! so that a schema consisting of the shared entities reads both flavors.
+//! Adapters are registered by [LoadOptions::flavor_adapter](crate::load::LoadOptions::flavor_adapter):
+//!
+//! ```
+//! use ruststep::{ast::Record, flavor::*};
+//! use std::str::FromStr;
+//!
+//! let mut record = Record::from_str("PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE('1', '', #1, .NOT_KNOWN.)").unwrap();
+//! assert!(ProductStructureAdapter.adapt(&mut record));
+//! assert_eq!(record, Record::from_str("PRODUCT_DEFINITION_FORMATION('1', '', #1)").unwrap());
+//!
+//! // Records already in the shared form are kept
+//! assert!(!ProductStructureAdapter.adapt(&mut record));
+//! ```
+
+use crate::ast::{Parameter, Record};
+
+/// Rewrite a record before it is deserialized into tables
+pub trait RecordAdapter {
+    /// Name of this adapter in [LoadReport::adapted](crate::load::LoadReport::adapted)
+    fn name(&self) -> &str;
+
+    /// Rewrite keyword and parameters of `record`, and return `true` if it is rewritten
+    fn adapt(&self, record: &mut Record) -> bool;
+}
+
+/// Replace the keyword `from` by `to`, and drop parameters after `len`
+struct Rule {
+    from: &'static str,
+    to: &'static str,
+    len: usize,
+}
+
+fn apply(rules: &[Rule], record: &mut Record) -> bool {
+    let Some(rule) = rules
+        .iter()
+        .find(|rule| record.name.eq_ignore_ascii_case(rule.from))
+    else {
+        return false;
+    };
+    record.name = rule.to.to_string();
+    if let Parameter::List(parameters) = &mut record.parameter {
+        parameters.truncate(rule.len);
+    }
+    true
+}
+
+/// Read AP203 subtypes of product structure entities as their AP214 supertypes
+///
+/// | AP203                                              | AP214                          |
+/// |:---------------------------------------------------|:-------------------------------|
+/// | `PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE` | `PRODUCT_DEFINITION_FORMATION`, without `make_or_buy` |
+/// | `PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS`     | `PRODUCT_DEFINITION`, without `documentation_ids` |
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProductStructureAdapter;
+
+impl RecordAdapter for ProductStructureAdapter {
+    fn name(&self) -> &str {
+        "product_structure"
+    }
+
+    fn adapt(&self, record: &mut Record) -> bool {
+        apply(
+            &[
+                Rule {
+                    from: "PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE",
+                    to: "PRODUCT_DEFINITION_FORMATION",
+                    len: 3,
+                },
+                Rule {
+                    from: "PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS",
+                    to: "PRODUCT_DEFINITION",
+                    len: 4,
+                },
+            ],
+            record,
+        )
+    }
+}
+
+/// Read AP203 contexts as their AP214 supertypes
+///
+/// | AP203                | AP214                        |
+/// |:---------------------|:-----------------------------|
+/// | `MECHANICAL_CONTEXT` | `PRODUCT_CONTEXT`            |
+/// | `DESIGN_CONTEXT`     | `PRODUCT_DEFINITION_CONTEXT` |
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContextAdapter;
+
+impl RecordAdapter for ContextAdapter {
+    fn name(&self) -> &str {
+        "context"
+    }
+
+    fn adapt(&self, record: &mut Record) -> bool {
+        apply(
+            &[
+                Rule {
+                    from: "MECHANICAL_CONTEXT",
+                    to: "PRODUCT_CONTEXT",
+                    len: 3,
+                },
+                Rule {
+                    from: "DESIGN_CONTEXT",
+                    to: "PRODUCT_DEFINITION_CONTEXT",
+                    len: 3,
+                },
+            ],
+            record,
+        )
+    }
+}
@@ -0,0 +1,447 @@
+//! Content hashes of entity instances for change detection
+//!
+//! [entity_hashes] computes a structural hash for each entity instance in a [DataSection],
+//! and [changed_since] compares hashes of two revisions of a file:
+//!
+//! ```
+//! use ruststep::{ast::DataSection, hash::*};
+//! use std::str::FromStr;
+//!
+//! let old = DataSection::from_str(r#"
+//! DATA;
+//!   #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+//!   #2 = VERTEX_POINT('', #1);
+//! ENDSEC;
+//! "#).unwrap();
+//! let new = DataSection::from_str(r#"
+//! DATA;
+//!   #1 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+//!   #2 = VERTEX_POINT('', #1);
+//! ENDSEC;
+//! "#).unwrap();
+//!
+//! // Shallow mode only detects the modified instance
+//! let options = HashOptions { mode: HashMode::Shallow };
+//! let changes = changed_since(&entity_hashes(&old, &options), &entity_hashes(&new, &options));
+//! assert_eq!(changes.modified, [1]);
+//!
+//! // Deep mode also detects the instances referring it
+//! let options = HashOptions { mode: HashMode::Deep };
+//! let changes = changed_since(&entity_hashes(&old, &options), &entity_hashes(&new, &options));
+//! assert_eq!(changes.modified, [1, 2]);
+//! ```
+//!
+//! Stability
+//! ----------
+//! Hashes are computed by 128-bit FNV-1a over a byte encoding of the keyword and parameters
+//! defined in this module, and do not depend on the platform, [std::hash::Hash],
+//! or the order of entity instances.
+//! The encoding is identified by [ALGORITHM_VERSION], which is mixed into every hash.
+//! It is incremented when the encoding changes,
+//! and hashes computed by different versions must not be compared.
+//!
+//! Deep mode
+//! ----------
+//! In [HashMode::Deep], a reference `#n` is hashed by the deep hash of `#n` itself
+//! instead of the raw id `n`, and thus a modification propagates to all instances
+//! referring the modified one transitively. References may be cyclic,
+//! and deep hashes are computed as follows:
+//!
+//! 1. Decompose the reference graph into strongly connected components.
+//!    Components are processed in reverse topological order,
+//!    i.e. all instances referred from a component are hashed before it.
+//! 2. For a component without internal references, the hash is computed directly.
+//! 3. For a cyclic component of `n` instances, every instance is hashed
+//!    with internal references replaced by a fixed marker.
+//!    Then `n` rounds are iterated, where each round hashes every instance again
+//!    with internal references replaced by the hashes of the previous round.
+//!    Since any instance in the component is reachable within `n - 1` references,
+//!    the final hash of each instance covers the contents of all instances in the component.
+//!
+//! Each round depends only on the previous round, not on the order of instances in the round,
+//! and thus the result is deterministic and independent of entity ids.
+//! References to entity instances not in the [DataSection] are hashed by the raw id.
+
+use crate::ast::*;
+use std::collections::{HashMap, HashSet};
+
+/// Version of the hash algorithm, see [Stability](self#stability)
+pub const ALGORITHM_VERSION: u32 = 1;
+
+/// How references are hashed, see [HashOptions]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HashMode {
+    /// Hash references by the raw entity id
+    #[default]
+    Shallow,
+    /// Hash references by the deep hash of the referred instance, see [Deep mode](self#deep-mode)
+    Deep,
+}
+
+/// Options for [entity_hashes]
+#[derive(Debug, Clone, Default)]
+pub struct HashOptions {
+    pub mode: HashMode,
+}
+
+/// Difference of entity hashes computed by [changed_since]. Each list is sorted by entity id.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ChangeSet {
+    /// Ids only in the new hashes
+    pub added: Vec<u64>,
+    /// Ids only in the old hashes
+    pub removed: Vec<u64>,
+    /// Ids in both hashes with different values
+    pub modified: Vec<u64>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Compute structural hashes of entity instances in the data section
+///
+/// If an entity id is duplicated, the first instance is used.
+pub fn entity_hashes(data: &DataSection, options: &HashOptions) -> HashMap<u64, u128> {
+    let mut instances: HashMap<u64, &EntityInstance> = HashMap::new();
+    for instance in &data.entities {
+        instances.entry(instance.id()).or_insert(instance);
+    }
+    match options.mode {
+        HashMode::Shallow => instances
+            .iter()
+            .map(|(&id, instance)| {
+                let mut hasher = Hasher::new(HashMode::Shallow);
+                hasher.instance(instance, &|_| None);
+                (id, hasher.finish())
+            })
+            .collect(),
+        HashMode::Deep => deep_hashes(&instances),
+    }
+}
+
+/// Compare hashes computed by [entity_hashes] for two revisions
+pub fn changed_since(old: &HashMap<u64, u128>, new: &HashMap<u64, u128>) -> ChangeSet {
+    let mut changes = ChangeSet::default();
+    for (id, hash) in new {
+        match old.get(id) {
+            Some(old_hash) if old_hash != hash => changes.modified.push(*id),
+            Some(_) => {}
+            None => changes.added.push(*id),
+        }
+    }
+    changes.removed = old
+        .keys()
+        .filter(|id| !new.contains_key(id))
+        .copied()
+        .collect();
+    changes.added.sort_unstable();
+    changes.removed.sort_unstable();
+    changes.modified.sort_unstable();
+    changes
+}
+
+/// Hash of a referred entity instance used in deep mode
+#[derive(Clone, Copy)]
+enum Target {
+    Hash(u128),
+    /// Internal reference of a cyclic component before the first round
+    Cycle,
+}
+
+/// 128-bit FNV-1a over the encoding of entity instances
+struct Hasher(u128);
+
+impl Hasher {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new(mode: HashMode) -> Self {
+        let mut hasher = Hasher(Self::OFFSET_BASIS);
+        hasher.bytes(&ALGORITHM_VERSION.to_le_bytes());
+        hasher.tag(match mode {
+            HashMode::Shallow => 0,
+            HashMode::Deep => 1,
+        });
+        hasher
+    }
+
+    fn finish(&self) -> u128 {
+        self.0
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.bytes(&[tag]);
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u64(value.len() as u64);
+        self.bytes(value.as_bytes());
+    }
+
+    fn instance(&mut self, instance: &EntityInstance, target: &dyn Fn(u64) -> Option<Target>) {
+        match instance {
+            EntityInstance::Simple { record, .. } => {
+                self.tag(0x10);
+                self.record(record, target);
+            }
+            EntityInstance::Complex { subsuper, .. } => {
+                self.tag(0x11);
+                self.u64(subsuper.0.len() as u64);
+                for record in &subsuper.0 {
+                    self.record(record, target);
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, record: &Record, target: &dyn Fn(u64) -> Option<Target>) {
+        if record.user_defined {
+            self.tag(0x12);
+        }
+        self.str(&record.name);
+        self.parameter(&record.parameter, target);
+    }
+
+    fn parameter(&mut self, parameter: &Parameter, target: &dyn Fn(u64) -> Option<Target>) {
+        match parameter {
+            Parameter::Typed { keyword, parameter } => {
+                self.tag(0x01);
+                self.str(keyword);
+                self.parameter(parameter, target);
+            }
+            Parameter::Integer(value) => {
+                self.tag(0x02);
+                self.bytes(&value.to_le_bytes());
+            }
+            Parameter::Real { value, .. } => {
+                self.tag(0x03);
+                // Regard -0.0 as 0.0
+                let value = if *value == 0.0 { 0.0 } else { *value };
+                self.u64(value.to_bits());
+            }
+            Parameter::String(value) => {
+                self.tag(0x04);
+                self.str(value);
+            }
+            Parameter::Enumeration(value) => {
+                self.tag(0x05);
+                self.str(value);
+            }
+            Parameter::List(parameters) => {
+                self.tag(0x06);
+                self.u64(parameters.len() as u64);
+                for parameter in parameters {
+                    self.parameter(parameter, target);
+                }
+            }
+            Parameter::Ref(name) => {
+                self.tag(0x07);
+                self.name(name, target);
+            }
+            Parameter::NotProvided => self.tag(0x08),
+            Parameter::Omitted => self.tag(0x09),
+            Parameter::Binary(value) => {
+                self.tag(0x0a);
+                self.u64(value.len() as u64);
+                self.bytes(value.as_bytes());
+            }
+        }
+    }
+
+    fn name(&mut self, name: &Name, target: &dyn Fn(u64) -> Option<Target>) {
+        match name {
+            Name::Entity(id) => match target(*id) {
+                Some(Target::Hash(hash)) => {
+                    self.tag(0x21);
+                    self.bytes(&hash.to_le_bytes());
+                }
+                Some(Target::Cycle) => self.tag(0x22),
+                None => {
+                    self.tag(0x20);
+                    self.u64(*id);
+                }
+            },
+            Name::Value(id) => {
+                self.tag(0x23);
+                self.u64(*id);
+            }
+            Name::ConstantEntity(name) => {
+                self.tag(0x24);
+                self.str(name);
+            }
+            Name::ConstantValue(name) => {
+                self.tag(0x25);
+                self.str(name);
+            }
+        }
+    }
+}
+
+fn deep_hashes(instances: &HashMap<u64, &EntityInstance>) -> HashMap<u64, u128> {
+    let mut ids: Vec<u64> = instances.keys().copied().collect();
+    ids.sort_unstable();
+    let edges: HashMap<u64, Vec<u64>> = ids
+        .iter()
+        .map(|&id| {
+            let mut refs = instances[&id].references();
+            refs.retain(|id| instances.contains_key(id));
+            (id, refs)
+        })
+        .collect();
+
+    let hash = |id: u64, target: &dyn Fn(u64) -> Option<Target>| {
+        let mut hasher = Hasher::new(HashMode::Deep);
+        hasher.instance(instances[&id], target);
+        hasher.finish()
+    };
+
+    let mut hashes: HashMap<u64, u128> = HashMap::new();
+    for component in strongly_connected_components(&ids, &edges) {
+        let members: HashSet<u64> = component.iter().copied().collect();
+        let cyclic = component
+            .iter()
+            .any(|id| edges[id].iter().any(|to| members.contains(to)));
+        if !cyclic {
+            let id = component[0];
+            let value = hash(id, &|to| hashes.get(&to).map(|h| Target::Hash(*h)));
+            hashes.insert(id, value);
+            continue;
+        }
+
+        let outer = |to: u64| hashes.get(&to).map(|h| Target::Hash(*h));
+        let mut round: HashMap<u64, u128> = component
+            .iter()
+            .map(|&id| {
+                let target = |to: u64| {
+                    if members.contains(&to) {
+                        Some(Target::Cycle)
+                    } else {
+                        outer(to)
+                    }
+                };
+                (id, hash(id, &target))
+            })
+            .collect();
+        for _ in 0..component.len() {
+            let previous = round;
+            round = component
+                .iter()
+                .map(|&id| {
+                    let target = |to: u64| match previous.get(&to) {
+                        Some(h) => Some(Target::Hash(*h)),
+                        None => outer(to),
+                    };
+                    (id, hash(id, &target))
+                })
+                .collect();
+        }
+        hashes.extend(round);
+    }
+    hashes
+}
+
+/// Tarjan's algorithm without recursion.
+/// Components are returned in reverse topological order.
+fn strongly_connected_components(ids: &[u64], edges: &HashMap<u64, Vec<u64>>) -> Vec<Vec<u64>> {
+    let mut index: HashMap<u64, usize> = HashMap::new();
+    let mut lowlink: HashMap<u64, usize> = HashMap::new();
+    let mut on_stack: HashSet<u64> = HashSet::new();
+    let mut stack: Vec<u64> = Vec::new();
+    let mut components = Vec::new();
+
+    for &root in ids {
+        if index.contains_key(&root) {
+            continue;
+        }
+        // (node, position of the next edge to visit)
+        let mut call_stack = vec![(root, 0)];
+        while let Some((node, position)) = call_stack.pop() {
+            if position == 0 {
+                let n = index.len();
+                index.insert(node, n);
+                lowlink.insert(node, n);
+                stack.push(node);
+                on_stack.insert(node);
+            }
+            if let Some(&to) = edges[&node].get(position) {
+                call_stack.push((node, position + 1));
+                if !index.contains_key(&to) {
+                    call_stack.push((to, 0));
+                } else if on_stack.contains(&to) {
+                    let low = lowlink[&node].min(index[&to]);
+                    lowlink.insert(node, low);
+                }
+                continue;
+            }
+            // All edges are visited
+            if lowlink[&node] == index[&node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack.remove(&member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                components.push(component);
+            }
+            if let Some(&(parent, _)) = call_stack.last() {
+                let low = lowlink[&parent].min(lowlink[&node]);
+                lowlink.insert(parent, low);
+            }
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn components() {
+        let ids = [1, 2, 3, 4];
+        let edges: HashMap<u64, Vec<u64>> =
+            [(1, vec![2]), (2, vec![3]), (3, vec![2, 4]), (4, vec![])]
+                .into_iter()
+                .collect();
+        assert_eq!(
+            strongly_connected_components(&ids, &edges),
+            [vec![4], vec![2, 3], vec![1]]
+        );
+    }
+
+    #[test]
+    fn independent_of_ids() {
+        let a = DataSection::from_str("DATA; #1 = A(#2); #2 = B(#1, 1.0); #3 = C(#1); ENDSEC;")
+            .unwrap();
+        let b = DataSection::from_str("DATA; #7 = A(#5); #5 = B(#7, 1.0); #6 = C(#7); ENDSEC;")
+            .unwrap();
+        let options = HashOptions {
+            mode: HashMode::Deep,
+        };
+        let a = entity_hashes(&a, &options);
+        let b = entity_hashes(&b, &options);
+        assert_eq!(a[&1], b[&7]);
+        assert_eq!(a[&2], b[&5]);
+        assert_eq!(a[&3], b[&6]);
+        assert_ne!(a[&1], a[&2]);
+    }
+}
@@ -20,9 +20,16 @@
 //! we write these definitions manually to keep development process simple.
 //!
 
-use crate::{ast::*, error::Result};
+use crate::{
+    ast::*,
+    capabilities::{Feature, Support},
+    error::{Error, Result},
+};
 use serde::Deserialize;
 
+/// Header entities are common through editions, see [crate::capabilities]
+pub(crate) const CAPABILITIES: &[(Feature, Support)] = &[(Feature::Edition3, Support::Supported)];
+
 /// File description
 ///
 /// Following EXPRESS schema is an exerpt from
@@ -40,6 +47,137 @@ pub struct FileDescription {
     pub implementation_level: String,
 }
 
+impl FileDescription {
+    /// Paragraphs in `description` joined by `sep`
+    ///
+    /// ```
+    /// use ruststep::header::FileDescription;
+    ///
+    /// let mut desc = FileDescription {
+    ///     description: Vec::new(),
+    ///     implementation_level: "2;1".to_string(),
+    /// };
+    /// desc.set_description_paragraphs(["First paragraph.", "", "Third one."]);
+    /// assert_eq!(desc.description.len(), 3);
+    /// assert_eq!(desc.description_text("\n"), "First paragraph.\n\nThird one.");
+    /// ```
+    pub fn description_text(&self, sep: &str) -> String {
+        self.description.join(sep)
+    }
+
+    /// Replace `description` by `paragraphs`, one string element for each paragraph
+    pub fn set_description_paragraphs<I, S>(&mut self, paragraphs: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.description = paragraphs.into_iter().map(Into::into).collect();
+    }
+
+    /// Header entity record `FILE_DESCRIPTION((...), '...')`
+    pub fn to_record(&self) -> Record {
+        Record {
+            name: "FILE_DESCRIPTION".to_string(),
+            user_defined: false,
+            parameter: Parameter::List(vec![
+                strings(&self.description),
+                Parameter::String(self.implementation_level.clone()),
+            ]),
+        }
+    }
+
+    /// Write as a header entity `FILE_DESCRIPTION((...), '...');`
+    ///
+    /// Lines are wrapped at `width` characters if given.
+    /// Line breaks may be inserted in strings since they are not a part of strings,
+    /// but never in `''`, control directives like `\X2\30B9\X0\`, or keywords.
+    ///
+    /// ```
+    /// use ruststep::{header::FileDescription, parser::exchange};
+    /// use nom::Finish;
+    ///
+    /// let desc = FileDescription {
+    ///     description: vec!["It's a long ス paragraph".to_string()],
+    ///     implementation_level: "2;1".to_string(),
+    /// };
+    /// let written = desc.write(Some(16));
+    /// assert!(written.contains("\\X2\\30B9\\X0\\"));
+    /// assert!(written.lines().all(|line| line.chars().count() <= 16));
+    /// assert!(written.lines().count() > 1);
+    ///
+    /// let (_, record) = exchange::header_entity(&written).finish().unwrap();
+    /// assert_eq!(record, desc.to_record());
+    /// ```
+    pub fn write(&self, width: Option<usize>) -> String {
+        let line = format!("{};", self.to_record());
+        match width {
+            Some(width) => wrap(&line, width),
+            None => line,
+        }
+    }
+}
+
+/// Split a line of exchange structure into pieces where a line break can be inserted between them
+fn atoms(line: &str) -> Vec<&str> {
+    let mut atoms = Vec::new();
+    let mut in_string = false;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let len = if in_string {
+            if rest.starts_with("''") {
+                2
+            } else if c == '\'' {
+                in_string = false;
+                1
+            } else if rest.starts_with("\\X2\\") || rest.starts_with("\\X4\\") {
+                rest.find("\\X0\\").map_or(rest.len(), |end| end + 4)
+            } else if rest.starts_with("\\X\\") {
+                5.min(rest.len())
+            } else if rest.starts_with("\\S\\")
+                || (rest.starts_with("\\P") && rest.as_bytes().get(3) == Some(&b'\\'))
+            {
+                4.min(rest.len())
+            } else if rest.starts_with("\\\\") {
+                2
+            } else {
+                c.len_utf8()
+            }
+        } else if c == '\'' {
+            in_string = true;
+            1
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len())
+        } else {
+            c.len_utf8()
+        };
+        // Malformed directives may end in a multi-byte character
+        let len = if rest.is_char_boundary(len) {
+            len
+        } else {
+            c.len_utf8()
+        };
+        atoms.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    atoms
+}
+
+fn wrap(line: &str, width: usize) -> String {
+    let mut wrapped = String::with_capacity(line.len());
+    let mut column = 0;
+    for atom in atoms(line) {
+        let len = atom.chars().count();
+        if column > 0 && column + len > width {
+            wrapped.push('\n');
+            column = 0;
+        }
+        wrapped.push_str(atom);
+        column += len;
+    }
+    wrapped
+}
+
 /// File name
 ///
 /// Following EXPRESS schema is an exerpt from
@@ -71,6 +209,25 @@ pub struct FileName {
     pub authorization: String,
 }
 
+impl FileName {
+    /// Header entity record `FILE_NAME('...', '...', (...), (...), '...', '...', '...')`
+    pub fn to_record(&self) -> Record {
+        Record {
+            name: "FILE_NAME".to_string(),
+            user_defined: false,
+            parameter: Parameter::List(vec![
+                Parameter::String(self.name.clone()),
+                Parameter::String(self.time_stamp.clone()),
+                strings(&self.author),
+                strings(&self.organization),
+                Parameter::String(self.preprocessor_version.clone()),
+                Parameter::String(self.originating_system.clone()),
+                Parameter::String(self.authorization.clone()),
+            ]),
+        }
+    }
+}
+
 /// File schema
 ///
 /// Following EXPRESS schema is an exerpt from
@@ -89,30 +246,104 @@ pub struct FileSchema {
     pub schema: Vec<String>,
 }
 
+impl FileSchema {
+    /// Header entity record `FILE_SCHEMA((...))`
+    pub fn to_record(&self) -> Record {
+        Record {
+            name: "FILE_SCHEMA".to_string(),
+            user_defined: false,
+            parameter: Parameter::List(vec![strings(&self.schema)]),
+        }
+    }
+}
+
+/// List of strings, e.g. `('a', 'b')`
+fn strings(values: &[String]) -> Parameter {
+    values
+        .iter()
+        .map(|s| Parameter::String(s.clone()))
+        .collect()
+}
+
 /// STEP-file HEADER section
 ///
 /// There is a schema for HEADER section,
 /// but we do not generate this structure from it to simplify build process.
 ///
+/// ```
+/// use ruststep::ast::*;
+/// use std::str::FromStr;
+///
+/// let exchange = Exchange::from_str(r#"ISO-10303-21;
+/// HEADER;
+/// FILE_DESCRIPTION(('bolt'), '2;1');
+/// FILE_NAME('bolt.stp', '2024-01-01T00:00:00', ('author'), (''), '', 'CAD 1.0', '');
+/// FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+/// SECTION_LANGUAGE('en');
+/// ENDSEC;
+/// DATA;
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#).unwrap();
+///
+/// let header = exchange.header_typed().unwrap();
+/// assert_eq!(header.file_name.originating_system, "CAD 1.0");
+/// assert_eq!(header.file_schema.schema, ["CONFIG_CONTROL_DESIGN"]);
+/// assert_eq!(header.extra[0].name, "SECTION_LANGUAGE");
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct Header {
     pub file_description: FileDescription,
     pub file_name: FileName,
     pub file_schema: FileSchema,
+    /// Records after `FILE_SCHEMA`, e.g. `FILE_POPULATION` or `SECTION_LANGUAGE`
+    pub extra: Vec<Record>,
 }
 
 impl Header {
+    /// Read the mandatory records `FILE_DESCRIPTION`, `FILE_NAME`, and `FILE_SCHEMA` in this order,
+    /// and keep the others in [Header::extra]
+    ///
+    /// Error
+    /// ------
+    /// - [Error::InvalidHeader] if a mandatory record is missing, out of order,
+    ///   or does not have the parameters of its entity
     pub fn from_records(records: &[Record]) -> Result<Self> {
-        assert!(records.len() >= 3);
-        let file_description = FileDescription::deserialize(&records[0])?;
-        let file_name = FileName::deserialize(&records[1])?;
-        let file_schema = FileSchema::deserialize(&records[2])?;
         Ok(Header {
-            file_description,
-            file_name,
-            file_schema,
+            file_description: mandatory(records, 0, "FILE_DESCRIPTION")?,
+            file_name: mandatory(records, 1, "FILE_NAME")?,
+            file_schema: mandatory(records, 2, "FILE_SCHEMA")?,
+            extra: records.iter().skip(3).cloned().collect(),
         })
     }
+
+    /// Records of the header section, the inverse of [Header::from_records]
+    pub fn to_records(&self) -> Vec<Record> {
+        let mut records = vec![
+            self.file_description.to_record(),
+            self.file_name.to_record(),
+            self.file_schema.to_record(),
+        ];
+        records.extend(self.extra.iter().cloned());
+        records
+    }
+}
+
+fn mandatory<'de, T: Deserialize<'de>>(
+    records: &'de [Record],
+    index: usize,
+    name: &str,
+) -> Result<T> {
+    let invalid = |message: String| Error::InvalidHeader {
+        record: name.to_string(),
+        message,
+    };
+    let record = match records.get(index) {
+        Some(record) if record.name.eq_ignore_ascii_case(name) => record,
+        Some(record) => return Err(invalid(format!("found {} instead", record.name))),
+        None => return Err(invalid("missing".to_string())),
+    };
+    T::deserialize(record).map_err(|e| invalid(e.to_string()))
 }
 
 #[cfg(test)]
@@ -133,6 +364,6 @@ mod tests {
             .finish()
             .unwrap();
         let header = super::Header::from_records(&records).unwrap();
-        dbg!(header);
+        assert_eq!(header.to_records(), records);
     }
 }
@@ -0,0 +1,196 @@
+//! Allocation of entity instance ids
+//!
+//! [IdAllocator] hands out ids sequentially, skipping ranges registered by [IdAllocator::reserve_range],
+//! e.g. ids of instances round-tripped from other systems which must be kept as they are.
+//!
+//! ```
+//! use ruststep::{ast::*, id::IdAllocator};
+//! use std::str::FromStr;
+//!
+//! let section = DataSection::from_str("DATA; #1 = A(1.0); #2 = B(#1); ENDSEC;").unwrap();
+//! let mut ids = IdAllocator::for_section(&section);
+//! ids.reserve_range(3..10);
+//! assert_eq!(ids.allocate().unwrap(), 10);
+//! assert_eq!(ids.allocate().unwrap(), 11);
+//! ```
+//!
+//! Allocation never wraps around. When no id is left below [IdAllocator::ceiling],
+//! [IdAllocator::allocate] returns [Error::IdExhausted].
+
+use crate::{
+    ast::DataSection,
+    error::{Error, Result},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, ops::Range};
+
+/// Sequential allocator of entity instance ids
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdAllocator {
+    next: u64,
+    ceiling: u64,
+    /// Sorted and non-overlapping ranges
+    reserved: Vec<Range<u64>>,
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        IdAllocator {
+            next: 1,
+            ceiling: u64::MAX,
+            reserved: Vec::new(),
+        }
+    }
+}
+
+impl IdAllocator {
+    /// Allocator starting from `#1`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocator starting after the largest id in `section`
+    pub fn for_section(section: &DataSection) -> Self {
+        let max = section.entities.iter().map(|e| e.id()).max().unwrap_or(0);
+        IdAllocator {
+            next: max.saturating_add(1),
+            ..Default::default()
+        }
+    }
+
+    /// Allocate ids from `start`
+    pub fn start(mut self, start: u64) -> Self {
+        self.next = start;
+        self
+    }
+
+    /// Allocate only ids smaller than `ceiling`. The default is [u64::MAX].
+    pub fn ceiling(mut self, ceiling: u64) -> Self {
+        self.ceiling = ceiling;
+        self
+    }
+
+    /// Do not allocate ids in `range`
+    ///
+    /// Overlapping or adjacent ranges are merged.
+    pub fn reserve_range(&mut self, range: Range<u64>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut merged = range;
+        let mut reserved = Vec::with_capacity(self.reserved.len() + 1);
+        for r in self.reserved.drain(..) {
+            if r.end < merged.start || merged.end < r.start {
+                reserved.push(r);
+            } else {
+                merged = merged.start.min(r.start)..merged.end.max(r.end);
+            }
+        }
+        let position = reserved
+            .iter()
+            .position(|r| merged.end < r.start)
+            .unwrap_or(reserved.len());
+        reserved.insert(position, merged);
+        self.reserved = reserved;
+    }
+
+    /// Reserved ranges in ascending order
+    pub fn reserved(&self) -> &[Range<u64>] {
+        &self.reserved
+    }
+
+    pub fn is_reserved(&self, id: u64) -> bool {
+        self.reserved.iter().any(|r| r.contains(&id))
+    }
+
+    /// Allocate a new id
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::IdExhausted] if there is no free id below the ceiling
+    ///
+    pub fn allocate(&mut self) -> Result<u64> {
+        let id = self.first_free(self.next)?;
+        self.next = id + 1;
+        Ok(id)
+    }
+
+    /// Smallest id not less than `from` which is neither reserved nor above the ceiling,
+    /// without allocating it
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::IdExhausted] if there is no such id
+    ///
+    pub fn first_free(&self, from: u64) -> Result<u64> {
+        let mut id = from;
+        loop {
+            if id >= self.ceiling {
+                return Err(Error::IdExhausted {
+                    ceiling: self.ceiling,
+                });
+            }
+            match self.reserved.iter().find(|r| r.contains(&id)) {
+                Some(r) => id = r.end,
+                None => return Ok(id),
+            }
+        }
+    }
+
+    /// Renumber instances not in reserved ranges downward from `#1`, keeping their order by id
+    ///
+    /// Instances in reserved ranges keep their ids, and references to renumbered instances are rewritten.
+    /// Returns the map from old ids to new ids of renumbered instances,
+    /// and the allocation restarts after the largest renumbered id.
+    ///
+    /// ```
+    /// use ruststep::{ast::*, id::IdAllocator};
+    /// use std::str::FromStr;
+    ///
+    /// let mut section = DataSection::from_str(r#"
+    /// DATA;
+    ///   #5 = A(1.0);
+    ///   #2 = B(#5, #8);
+    ///   #8 = C(#5);
+    /// ENDSEC;
+    /// "#).unwrap();
+    ///
+    /// let mut ids = IdAllocator::for_section(&section);
+    /// ids.reserve_range(8..9);
+    /// ids.compact_ids(&mut section).unwrap();
+    /// assert_eq!(section.to_string(), "DATA;\n#2 = A(1.0);\n#1 = B(#2,#8);\n#8 = C(#2);\nENDSEC;");
+    /// assert_eq!(ids.allocate().unwrap(), 3);
+    /// ```
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::IdExhausted] if an instance cannot be renumbered below the ceiling.
+    ///   The section is unchanged in this case.
+    ///
+    pub fn compact_ids(&mut self, section: &mut DataSection) -> Result<HashMap<u64, u64>> {
+        let mut ids: Vec<u64> = section
+            .entities
+            .iter()
+            .map(|e| e.id())
+            .filter(|id| !self.is_reserved(*id))
+            .collect();
+        ids.sort_unstable();
+
+        let mut allocator = IdAllocator {
+            next: 1,
+            ..self.clone()
+        };
+        let mut renumbered = HashMap::new();
+        for id in ids {
+            let new = allocator.allocate()?;
+            if new != id {
+                renumbered.insert(id, new);
+            }
+        }
+        for instance in &mut section.entities {
+            instance.renumber(&renumbered);
+        }
+        self.next = allocator.next;
+        Ok(renumbered)
+    }
+}
@@ -108,16 +108,38 @@
 
 #![deny(rustdoc::broken_intra_doc_links)]
 
+pub mod assembly;
 pub mod ast;
+pub mod batch;
+pub mod cache;
+pub mod capabilities;
+pub mod de;
+pub mod dictionary;
+pub mod diff;
+pub mod edit;
 pub mod error;
+pub mod flavor;
+pub mod hash;
 pub mod header;
+pub mod id;
+pub mod load;
 pub mod parser;
+pub mod prelude;
 pub mod primitive;
+pub mod repair;
+pub mod stats;
 pub mod tables;
+pub mod writer;
+
+#[cfg(feature = "testing")]
+pub mod testing;
 
 // To work generated code by ruststep-derive only with ruststep
+#[doc(hidden)]
 pub use derive_more;
+#[doc(hidden)]
 pub use itertools;
+#[doc(hidden)]
 pub use serde;
 
 pub use ruststep_derive::*;
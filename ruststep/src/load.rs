@@ -0,0 +1,379 @@
+//! Load tables from exchange structure with progress reports and cancellation
+//!
+//! Loading a large exchange structure consists of following phases, see [LoadPhase]:
+//!
+//! 1. Parse the string into [DataSection]s
+//! 2. Populate tables by deserializing each entity instance
+//! 3. Check every reference between entity instances is defined
+//!
+//! [load] reports [LoadProgress] through the callback of [LoadOptions::progress]
+//! every [LoadOptions::interval] entity instances and at the end of each phase.
+//! The callback can cancel loading by returning [ControlFlow::Break]:
+//!
+//! ```
+//! use ruststep::{error::Error, load::*};
+//! use std::ops::ControlFlow;
+//!
+//! # #[derive(Debug, Default)]
+//! # struct Tables;
+//! # impl ruststep::tables::TableInit for Tables {
+//! #     fn append_entity_instance(&mut self, _: &ruststep::ast::EntityInstance) -> ruststep::error::Result<()> { Ok(()) }
+//! # }
+//! // `Tables` is generated by espr
+//! let step_str = r#"ISO-10303-21;
+//! HEADER;
+//! FILE_DESCRIPTION((''), '2;1');
+//! FILE_NAME('', '', (''), (''), '', '', '');
+//! FILE_SCHEMA(('TEST_SCHEMA'));
+//! ENDSEC;
+//! DATA;
+//! #1 = A(1.0);
+//! #2 = A(2.0);
+//! ENDSEC;
+//! END-ISO-10303-21;
+//! "#;
+//!
+//! let mut phases = Vec::new();
+//! let options = LoadOptions::new().progress(|progress| {
+//!     phases.push(progress.phase);
+//!     ControlFlow::Continue(())
+//! });
+//! let tables: Tables = load(step_str, options).unwrap();
+//! assert_eq!(
+//!     phases,
+//!     [LoadPhase::Parsing, LoadPhase::Populating, LoadPhase::Checking]
+//! );
+//!
+//! // Cancel at the beginning of population
+//! let options = LoadOptions::new().interval(1).progress(|progress| {
+//!     if progress.phase == LoadPhase::Populating {
+//!         ControlFlow::Break(())
+//!     } else {
+//!         ControlFlow::Continue(())
+//!     }
+//! });
+//! let result: Result<Tables, _> = load(step_str, options);
+//! assert!(matches!(result, Err(Error::Cancelled)));
+//! ```
+
+use crate::{
+    ast::*,
+    error::*,
+    flavor::RecordAdapter,
+    parser::{self, ParseOptions},
+    tables::{with_deserialize_options, DeserializeOptions, TableInit, UnplacedComplex},
+};
+use std::{
+    collections::HashSet,
+    fmt,
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
+
+/// Phase of [load]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadPhase {
+    /// Parsing exchange structure string
+    Parsing,
+    /// Deserializing entity instances into tables
+    Populating,
+    /// Checking every reference between entity instances is defined
+    Checking,
+}
+
+/// Progress of [load] passed to the callback of [LoadOptions::progress]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    pub phase: LoadPhase,
+    /// Number of entity instances processed in this phase
+    pub processed: usize,
+    /// Number of entity instances to be processed in this phase.
+    /// This is `None` while parsing, since it is unknown until parsing completes.
+    pub total: Option<usize>,
+    /// Elapsed time since [load] starts
+    pub elapsed: Duration,
+}
+
+type ProgressCallback<'a> = Box<dyn FnMut(LoadProgress) -> ControlFlow<()> + 'a>;
+
+/// Options for [load]
+pub struct LoadOptions<'a> {
+    parse_options: ParseOptions,
+    deserialize_options: DeserializeOptions,
+    interval: usize,
+    progress: Option<ProgressCallback<'a>>,
+    flavor_adapters: Vec<Box<dyn RecordAdapter + 'a>>,
+}
+
+impl Default for LoadOptions<'_> {
+    fn default() -> Self {
+        LoadOptions {
+            parse_options: ParseOptions::default(),
+            deserialize_options: DeserializeOptions::default(),
+            interval: 1000,
+            progress: None,
+            flavor_adapters: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for LoadOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoadOptions")
+            .field("parse_options", &self.parse_options)
+            .field("deserialize_options", &self.deserialize_options)
+            .field("interval", &self.interval)
+            .field("progress", &self.progress.is_some())
+            .field(
+                "flavor_adapters",
+                &self
+                    .flavor_adapters
+                    .iter()
+                    .map(|adapter| adapter.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> LoadOptions<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Options for parsing phase
+    pub fn parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse_options = options;
+        self
+    }
+
+    /// Reject entity instances whose parameters do not match the kinds of attributes,
+    /// see [DeserializeOptions::check_attribute_kinds]
+    pub fn check_attribute_kinds(mut self, check: bool) -> Self {
+        self.deserialize_options.check_attribute_kinds = check;
+        self
+    }
+
+    /// Keep keywords of typed parameters read into [Wrapped](crate::primitive::Wrapped),
+    /// see [DeserializeOptions::preserve_type_wrappers]
+    pub fn preserve_type_wrappers(mut self, preserve: bool) -> Self {
+        self.deserialize_options.preserve_type_wrappers = preserve;
+        self
+    }
+
+    /// Read parameters of `entity` in the given order,
+    /// i.e. `order[i]` is the position in records of the `i`-th attribute in the schema.
+    /// See [DeserializeOptions::attribute_orders].
+    ///
+    /// Panics
+    /// -------
+    /// - if `order` is not a permutation of `0..order.len()`
+    ///
+    pub fn attribute_order(mut self, entity: &str, order: Vec<usize>) -> Self {
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert!(
+            sorted.iter().copied().eq(0..order.len()),
+            "Attribute order of {} must be a permutation: {:?}",
+            entity,
+            order
+        );
+        self.deserialize_options
+            .attribute_orders
+            .insert(entity.to_uppercase(), order);
+        self
+    }
+
+    /// Report progress every `interval` entity instances. The default is 1000.
+    ///
+    /// Panics
+    /// -------
+    /// - if `interval` is zero
+    ///
+    pub fn interval(mut self, interval: usize) -> Self {
+        assert!(interval > 0, "Interval of progress report must be positive");
+        self.interval = interval;
+        self
+    }
+
+    /// Set callback to receive [LoadProgress].
+    /// Loading is cancelled with [Error::Cancelled] if it returns [ControlFlow::Break].
+    pub fn progress(mut self, callback: impl FnMut(LoadProgress) -> ControlFlow<()> + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Rewrite each record by `adapter` before deserializing it, see [crate::flavor].
+    /// Adapters are applied in the order of registration.
+    pub fn flavor_adapter(mut self, adapter: impl RecordAdapter + 'a) -> Self {
+        self.flavor_adapters.push(Box::new(adapter));
+        self
+    }
+}
+
+/// Summary of [load_with_report]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Number of entity instances loaded
+    pub instances: usize,
+    /// Name of each adapter in [LoadOptions::flavor_adapter] and the number of records it rewrites
+    pub adapted: Vec<(String, usize)>,
+    /// Complex entity instances skipped since they cannot be placed in tables
+    pub unplaced: Vec<UnplacedComplex>,
+}
+
+struct Reporter<'a> {
+    start: Instant,
+    interval: usize,
+    progress: Option<ProgressCallback<'a>>,
+}
+
+impl Reporter<'_> {
+    /// Report if `processed` reaches the interval, or `force` is true
+    fn report(
+        &mut self,
+        phase: LoadPhase,
+        processed: usize,
+        total: Option<usize>,
+        force: bool,
+    ) -> Result<()> {
+        let progress = match &mut self.progress {
+            Some(progress) => progress,
+            None => return Ok(()),
+        };
+        if !force && (processed == 0 || processed % self.interval != 0) {
+            return Ok(());
+        }
+        let flow = progress(LoadProgress {
+            phase,
+            processed,
+            total,
+            elapsed: self.start.elapsed(),
+        });
+        match flow {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(Error::Cancelled),
+        }
+    }
+
+    fn finish(&mut self, phase: LoadPhase, total: usize) -> Result<()> {
+        // Skip if already reported by the interval
+        let reported = total > 0 && total % self.interval == 0 && phase != LoadPhase::Parsing;
+        if reported {
+            return Ok(());
+        }
+        self.report(phase, total, Some(total), true)
+    }
+}
+
+/// Load tables from exchange structure string
+///
+/// Tables are returned only when all phases complete,
+/// i.e. partially populated tables are dropped when an error occurs or loading is cancelled.
+/// Complex entity instances which cannot be placed in tables are skipped,
+/// and listed in [LoadReport::unplaced] of [load_with_report].
+///
+/// Errors
+/// -------
+/// - [Error::Cancelled] if the callback of [LoadOptions::progress] returns [ControlFlow::Break]
+/// - [Error::UnknownEntity] if an entity instance refers an undefined entity instance
+/// - [Error::AttributeKindMismatch] if [LoadOptions::check_attribute_kinds] is enabled
+///   and a parameter does not match the kind of attribute
+/// - Errors in parsing or deserializing entity instances
+///
+pub fn load<T: TableInit>(input: &str, options: LoadOptions) -> Result<T> {
+    load_with_report(input, options).map(|(tables, _report)| tables)
+}
+
+/// [load] with [LoadReport]
+pub fn load_with_report<T: TableInit>(
+    input: &str,
+    options: LoadOptions,
+) -> Result<(T, LoadReport)> {
+    let LoadOptions {
+        parse_options,
+        deserialize_options,
+        interval,
+        progress,
+        flavor_adapters,
+    } = options;
+    let mut reporter = Reporter {
+        start: Instant::now(),
+        interval,
+        progress,
+    };
+
+    // Parsing
+    let mut parsed = 0;
+    let mut exchange = parser::parse_each(input, &parse_options, |_instance| {
+        parsed += 1;
+        // Only cancellation is reported as an error
+        match reporter.report(LoadPhase::Parsing, parsed, None, false) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        }
+    })?;
+    let total: usize = exchange.data.iter().map(|sec| sec.entities.len()).sum();
+    reporter.finish(LoadPhase::Parsing, total)?;
+
+    // Populating
+    let mut tables = T::default();
+    let mut adapted = vec![0; flavor_adapters.len()];
+    let mut unplaced = Vec::new();
+    let instances = exchange.data.iter_mut().flat_map(|sec| &mut sec.entities);
+    with_deserialize_options(&deserialize_options, || -> Result<()> {
+        for (processed, instance) in instances.enumerate() {
+            for record in instance.records_mut() {
+                for (adapter, count) in flavor_adapters.iter().zip(&mut adapted) {
+                    if adapter.adapt(record) {
+                        *count += 1;
+                    }
+                }
+            }
+            match tables.append_entity_instance(instance) {
+                Err(Error::UnplacedComplexEntity { id, keywords }) => {
+                    unplaced.push(UnplacedComplex { id, keywords })
+                }
+                result => result?,
+            }
+            reporter.report(LoadPhase::Populating, processed + 1, Some(total), false)?;
+        }
+        Ok(())
+    })?;
+    reporter.finish(LoadPhase::Populating, total)?;
+
+    // Checking
+    let instances = exchange.data.iter().flat_map(|sec| &sec.entities);
+    let mut defined: HashSet<u64> = instances.clone().map(EntityInstance::id).collect();
+    defined.extend(
+        exchange
+            .reference
+            .iter()
+            .filter_map(|entry| match entry.name {
+                Name::Entity(id) => Some(id),
+                _ => None,
+            }),
+    );
+    for (processed, instance) in instances.enumerate() {
+        if let Some(id) = instance
+            .references()
+            .into_iter()
+            .find(|id| !defined.contains(id))
+        {
+            return Err(Error::UnknownEntity(id));
+        }
+        reporter.report(LoadPhase::Checking, processed + 1, Some(total), false)?;
+    }
+    reporter.finish(LoadPhase::Checking, total)?;
+
+    let report = LoadReport {
+        instances: total,
+        adapted: flavor_adapters
+            .iter()
+            .map(|adapter| adapter.name().to_string())
+            .zip(adapted)
+            .collect(),
+        unplaced,
+    };
+    Ok((tables, report))
+}
@@ -33,6 +33,11 @@ pub fn digit(input: &str) -> ParseResult<char> {
     satisfy(|c| matches!(c, '0'..='9')).parse(input)
 }
 
+/// hex = [digit] | `A` | `B` | `C` | `D` | `E` | `F` .
+pub fn hex(input: &str) -> ParseResult<char> {
+    satisfy(|c| matches!(c, '0'..='9' | 'A'..='F')).parse(input)
+}
+
 /// lower = `a` | `b` | `c` | `d` | `e` | `f` | `g` | `h`
 ///       | `i` | `j` | `k` | `l` | `m` | `n` | `o` | `p`
 ///       | `q` | `r` | `s` | `t` | `u` | `v` | `w` | `x`
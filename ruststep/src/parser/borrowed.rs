@@ -0,0 +1,596 @@
+//! Parsers into [crate::ast::borrowed], see [parse_borrowed](super::parse_borrowed)
+//!
+//! Each parser corresponds to the parser of the same name in [exchange] and [token],
+//! and only keywords, enumerations and strings are parsed differently.
+//! Numbers and names are parsed by the owned parsers, and thus [ParseOptions] given to `_with` variants are respected.
+//! Keywords and enumerations are borrowed unless they are converted into upper case
+//! by [ParseOptions::case_insensitive_keywords].
+//! [SpecializedKeywords](super::SpecializedKeywords) are not used in this mode.
+
+use crate::{
+    ast::borrowed::*,
+    parser::{
+        basic::{lower, upper},
+        combinator::*,
+        exchange, options,
+        token::{self, entity_instance_name, rhs_occurrence_name_with},
+        ParseOptions,
+    },
+};
+use nom::{
+    branch::alt,
+    character::complete::char,
+    combinator::{recognize, value},
+    sequence::{preceded, tuple},
+    Parser,
+};
+use std::borrow::Cow;
+
+/// Borrowed version of [token::string]
+///
+/// [Cow::Owned] only when the string contains `''`, line breaks, or backslashes.
+pub fn string(input: &str) -> ParseResult<Cow<str>> {
+    let (rest, content) = token::string_literal(input)?;
+    let s = token::decode_string(content)?;
+    Ok((rest, s))
+}
+
+/// Borrowed version of [token::standard_keyword]
+pub fn standard_keyword(input: &str) -> ParseResult<&str> {
+    standard_keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [token::standard_keyword_with]
+///
+/// Lower case letters are accepted as they are if [ParseOptions::case_insensitive_keywords] is set.
+pub fn standard_keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, &'a str> {
+    let case_insensitive = options.case_insensitive_keywords;
+    move |input: &'a str| {
+        if case_insensitive {
+            return recognize(tuple((
+                alt((upper, lower)),
+                nom::bytes::complete::take_while(|c: char| c.is_ascii_alphanumeric() || c == '_'),
+            )))
+            .parse(input);
+        }
+        recognize(tuple((
+            upper,
+            nom::bytes::complete::take_while(|c: char| matches!(c, 'A'..='Z' | '_' | '0'..='9')),
+        )))
+        .parse(input)
+    }
+}
+
+/// Keyword in upper case, borrowed if it is already
+fn upper_case(name: &str) -> Cow<str> {
+    if name.bytes().any(|b| b.is_ascii_lowercase()) {
+        Cow::Owned(name.to_ascii_uppercase())
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Borrowed version of [token::keyword]
+pub fn keyword(input: &str) -> ParseResult<Cow<str>> {
+    keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [token::keyword_with]
+pub fn keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Cow<'a, str>> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            preceded(char('!'), standard_keyword_with(&options)),
+            standard_keyword_with(&options),
+        ))
+        .map(upper_case)
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [token::marked_keyword]
+pub fn marked_keyword(input: &str) -> ParseResult<(Cow<str>, bool)> {
+    marked_keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [token::marked_keyword_with]
+pub fn marked_keyword_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, (Cow<'a, str>, bool)> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            preceded(char('!'), standard_keyword_with(&options))
+                .map(|name| (upper_case(name), true)),
+            standard_keyword_with(&options).map(|name| (upper_case(name), false)),
+        ))
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [token::enumeration]
+pub fn enumeration(input: &str) -> ParseResult<Cow<str>> {
+    enumeration_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [token::enumeration_with]
+pub fn enumeration_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Cow<'a, str>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('.'), standard_keyword_with(&options), char('.')))
+            .map(|(_head, name, _tail)| upper_case(name))
+            .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::list]
+pub fn list(input: &str) -> ParseResult<Parameter> {
+    list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::list_with]
+pub fn list_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            char_('('),
+            opt_(comma_separated(parameter_with(&options))),
+            char_(')'),
+        ))
+        .map(|(_open, params, _close)| Parameter::List(params.unwrap_or_default()))
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::parameter]
+pub fn parameter(input: &str) -> ParseResult<Parameter> {
+    parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::parameter_with]
+pub fn parameter_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        options::nested(&options, input, |input| {
+            alt((
+                typed_parameter_with(&options),
+                untyped_parameter_with(&options),
+                omitted_parameter,
+            ))
+            .parse(input)
+        })
+    }
+}
+
+/// Borrowed version of [exchange::typed_parameter]
+pub fn typed_parameter(input: &str) -> ParseResult<Parameter> {
+    typed_parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::typed_parameter_with]
+pub fn typed_parameter_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            keyword_with(&options),
+            char_('('),
+            parameter_with(&options),
+            char_(')'),
+        ))
+        .map(|(keyword, _open, parameter, _close)| Parameter::Typed {
+            keyword,
+            parameter: Box::new(parameter),
+        })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::untyped_parameter]
+pub fn untyped_parameter(input: &str) -> ParseResult<Parameter> {
+    untyped_parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::untyped_parameter_with]
+pub fn untyped_parameter_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Parameter<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            char_('$').map(|_| Parameter::NotProvided),
+            token::real_with(&options).map(Parameter::Real),
+            token::integer_with(&options).map(Parameter::Integer),
+            string.map(Parameter::String),
+            rhs_occurrence_name_with(&options).map(Parameter::Ref),
+            enumeration_with(&options).map(Parameter::Enumeration),
+            token::binary.map(Parameter::Binary),
+            list_with(&options),
+        ))
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::omitted_parameter]
+pub fn omitted_parameter(input: &str) -> ParseResult<Parameter> {
+    value(Parameter::Omitted, char_('*')).parse(input)
+}
+
+/// Borrowed version of [exchange::parameter_list]
+pub fn parameter_list(input: &str) -> ParseResult<Vec<Parameter>> {
+    parameter_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::parameter_list_with]
+pub fn parameter_list_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<Parameter<'a>>> {
+    let options = *options;
+    move |input: &'a str| comma_separated(parameter_with(&options)).parse(input)
+}
+
+/// Borrowed version of [exchange::simple_record]
+pub fn simple_record(input: &str) -> ParseResult<Record> {
+    simple_record_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::simple_record_with]
+pub fn simple_record_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Record<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            marked_keyword_with(&options),
+            char_('('),
+            opt_(parameter_list_with(&options)),
+            char_(')'),
+        ))
+        .map(|((name, user_defined), _open, parameter, _close)| Record {
+            name,
+            user_defined,
+            parameter: Parameter::List(parameter.unwrap_or_default()),
+        })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::subsuper_record]
+pub fn subsuper_record(input: &str) -> ParseResult<SubSuperRecord> {
+    subsuper_record_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::subsuper_record_with]
+pub fn subsuper_record_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, SubSuperRecord<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((char_('('), many0_(simple_record_with(&options)), char_(')')))
+            .map(|(_open, records, _close)| SubSuperRecord(records))
+            .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::entity_instance]
+pub fn entity_instance(input: &str) -> ParseResult<EntityInstance> {
+    entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::entity_instance_with]
+pub fn entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            simple_entity_instance_with(&options),
+            complex_entity_instance_with(&options),
+        ))
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::simple_entity_instance]
+pub fn simple_entity_instance(input: &str) -> ParseResult<EntityInstance> {
+    simple_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::simple_entity_instance_with]
+pub fn simple_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            simple_record_with(&options),
+            char_(';'),
+        ))
+        .map(|(id, _eq, record, _semicolon)| EntityInstance::Simple { id, record })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::complex_entity_instance]
+pub fn complex_entity_instance(input: &str) -> ParseResult<EntityInstance> {
+    complex_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::complex_entity_instance_with]
+pub fn complex_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            subsuper_record_with(&options),
+            char_(';'),
+        ))
+        .map(|(id, _eq, subsuper, _semicolon)| EntityInstance::Complex { id, subsuper })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::entity_instance_list]
+pub fn entity_instance_list(input: &str) -> ParseResult<Vec<EntityInstance>> {
+    entity_instance_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::entity_instance_list_with]
+pub fn entity_instance_list_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<EntityInstance<'a>>> {
+    let options = *options;
+    move |input: &'a str| {
+        many0_(scoped_entity_instance_with(&options))
+            .map(|instances| instances.into_iter().flatten().collect())
+            .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::scoped_entity_instance]
+pub fn scoped_entity_instance(input: &str) -> ParseResult<Vec<EntityInstance>> {
+    scoped_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::scoped_entity_instance_with]
+pub fn scoped_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<EntityInstance<'a>>> {
+    let options = *options;
+    move |input: &'a str| {
+        let (input, (id, _eq, before)) =
+            tuple_((entity_instance_name, char_('='), opt_(scope_with(&options)))).parse(input)?;
+        let (input, (_, (instance, after, _semicolon))) = tuple((
+            ignorable,
+            tuple_((
+                move |input| {
+                    alt((
+                        simple_record_with(&options)
+                            .map(|record| EntityInstance::Simple { id, record }),
+                        subsuper_record_with(&options)
+                            .map(|subsuper| EntityInstance::Complex { id, subsuper }),
+                    ))
+                    .parse(input)
+                },
+                opt_(scope_with(&options)),
+                char_(';'),
+            )),
+        ))
+        .parse(input)?;
+        let instances = before
+            .into_iter()
+            .flatten()
+            .chain(std::iter::once(instance))
+            .chain(after.into_iter().flatten())
+            .collect();
+        Ok((input, instances))
+    }
+}
+
+/// Borrowed version of [exchange::scope]
+pub fn scope(input: &str) -> ParseResult<Vec<EntityInstance>> {
+    scope_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::scope_with]
+pub fn scope_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<EntityInstance<'a>>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("&SCOPE"),
+            entity_instance_list_with(&options),
+            tag_("ENDSCOPE"),
+            opt_(exchange::export_list),
+        ))
+        .map(|(_start, instances, _end, _export)| instances)
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::data_section]
+pub fn data_section(input: &str) -> ParseResult<DataSection> {
+    data_section_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::data_section_with]
+pub fn data_section_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, DataSection<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            data_section_header_with(&options),
+            entity_instance_list_with(&options),
+            tag_("ENDSEC;"),
+        ))
+        .map(|(meta, entities, _end)| DataSection { meta, entities })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::data_section_header]
+pub fn data_section_header(input: &str) -> ParseResult<Vec<Parameter>> {
+    data_section_header_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::data_section_header_with]
+pub fn data_section_header_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<Parameter<'a>>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("DATA"),
+            opt_(tuple_((
+                char_('('),
+                parameter_list_with(&options),
+                char_(')'),
+            ))),
+            char_(';'),
+        ))
+        .map(|(_start, meta, _semicolon)| {
+            meta.map(|(_open, params, _close)| params)
+                .unwrap_or_default()
+        })
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::header_section]
+pub fn header_section(input: &str) -> ParseResult<Vec<Record>> {
+    header_section_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::header_section_with]
+pub fn header_section_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<Record<'a>>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("HEADER;"),
+            many1_(header_entity_with(&options)),
+            tag_("ENDSEC;"),
+        ))
+        .map(|(_start, entities, _close)| entities)
+        .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::header_entity]
+pub fn header_entity(input: &str) -> ParseResult<Record> {
+    header_entity_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::header_entity_with]
+pub fn header_entity_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Record<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((simple_record_with(&options), char_(';')))
+            .map(|(record, _semicolon)| record)
+            .parse(input)
+    }
+}
+
+/// Borrowed version of [exchange::exchange_file]
+pub fn exchange_file(input: &str) -> ParseResult<Exchange> {
+    exchange_file_with(&ParseOptions::strict()).parse(input)
+}
+
+/// Borrowed version of [exchange::exchange_file_with]
+///
+/// [ParseOptions::normalize_fullwidth_punctuation] and [ParseOptions::recover_header]
+/// are not applied, since the AST borrows from the input as it is.
+/// [parse_borrowed_with](crate::parser::parse_borrowed_with) rejects them.
+pub fn exchange_file_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Exchange<'a>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            ignorable,
+            tag_("ISO-10303-21;"),
+            header_section_with(&options),
+            opt_(exchange::anchor_section_with(&options)),
+            opt_(exchange::reference_section),
+            many0_(data_section_with(&options)),
+            tag_("END-ISO-10303-21;"),
+            many0_(exchange::signature_section),
+            ignorable,
+        ))
+        .map(
+            |(_, _start, header, anchor, reference, data, _end, signature, _)| Exchange {
+                header,
+                anchor: anchor.unwrap_or_default(),
+                reference: reference.unwrap_or_default(),
+                data,
+                signature,
+            },
+        )
+        .parse(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ParseOptions;
+    use nom::Finish;
+
+    #[test]
+    fn string() {
+        for (input, expected, borrowed) in [
+            ("''", "", true),
+            ("'abc'", "abc", true),
+            ("'it''s'", "it's", false),
+            ("''''", "'", false),
+            ("'a''b''c'", "a'b'c", false),
+            ("'ab\ncd'", "abcd", false),
+            ("'a''\r\nb'", "a'b", false),
+            ("'caf\\X2\\00E9\\X0\\'", "café", false),
+            ("'C:\\\\temp'", "C:\\temp", false),
+        ] {
+            let (residual, s) = super::string(input).finish().unwrap();
+            assert_eq!(residual, "");
+            assert_eq!(s, expected);
+            assert_eq!(matches!(s, Cow::Borrowed(_)), borrowed, "{}", input);
+            assert_eq!(token::string(input).finish().unwrap().1, expected);
+        }
+        assert!(super::string("'abc").is_err());
+        assert!(super::string("'it''").is_err());
+    }
+
+    #[test]
+    fn keyword() {
+        for input in ["A", "A_1", "!USER", "_A2B"] {
+            let (residual, k) = super::keyword(input).finish().unwrap();
+            assert_eq!(residual, "");
+            assert_eq!(k, token::keyword(input).finish().unwrap().1);
+        }
+        assert!(super::keyword("a").is_err());
+
+        // Borrowed unless converted into upper case
+        let options = ParseOptions {
+            case_insensitive_keywords: true,
+            ..Default::default()
+        };
+        for (input, expected, borrowed) in [
+            ("A_1", "A_1", true),
+            ("Cartesian_Point", "CARTESIAN_POINT", false),
+            ("!user_2", "USER_2", false),
+        ] {
+            let k = super::keyword_with(&options).parse(input).finish();
+            let (residual, k) = k.unwrap();
+            assert_eq!(residual, "");
+            assert_eq!(k, expected);
+            assert_eq!(matches!(k, Cow::Borrowed(_)), borrowed, "{}", input);
+            let owned = token::keyword_with(&options).parse(input).finish();
+            assert_eq!(owned.unwrap().1, expected);
+        }
+    }
+
+    #[test]
+    fn record() {
+        for input in ["A(1, 2.0, 'x''y', .T., B((#1, $)), *)", "!USER(1)"] {
+            let (_, borrowed) = super::simple_record(input).finish().unwrap();
+            let (_, owned) = exchange::simple_record(input).finish().unwrap();
+            assert_eq!(borrowed.into_owned(), owned);
+        }
+    }
+}
@@ -10,26 +10,132 @@
 //!
 //! and combinators in this submodule responsible for handling them.
 
+use crate::error::TokenError;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{char, multispace0, multispace1, none_of},
-    combinator::{not, opt, peek, value},
-    error::VerboseError,
+    character::complete::{char, multispace0, multispace1},
+    combinator::{opt, value},
+    error::{
+        ContextError, ErrorKind, FromExternalError, ParseError, VerboseError, VerboseErrorKind,
+    },
     multi::{many0, many1},
     sequence::tuple,
     IResult, Parser,
 };
 
 /// Parse result
-pub type ParseResult<'a, X> = IResult<&'a str, X, VerboseError<&'a str>>;
+pub type ParseResult<'a, X> = IResult<&'a str, X, ParserError<'a>>;
 
 /// Alias of `nom::Parser`
-pub trait ExchangeParser<'a, X>: Clone + nom::Parser<&'a str, X, VerboseError<&'a str>> {}
+pub trait ExchangeParser<'a, X>: Clone + nom::Parser<&'a str, X, ParserError<'a>> {}
 
-impl<'a, X, T> ExchangeParser<'a, X> for T where
-    T: Clone + nom::Parser<&'a str, X, VerboseError<&'a str>>
-{
+impl<'a, X, T> ExchangeParser<'a, X> for T where T: Clone + nom::Parser<&'a str, X, ParserError<'a>> {}
+
+/// Error of the parsers in this module
+///
+/// This is [VerboseError] with a [TokenError] if the parsing fails
+/// since a token is well-formed but cannot be represented,
+/// see [TokenizeFailed::token_error](crate::error::TokenizeFailed::token_error).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserError<'a> {
+    /// Stack of the errors from the innermost one
+    pub verbose: VerboseError<&'a str>,
+    /// The input beginning with the token which cannot be represented, and the cause
+    pub token: Option<(&'a str, TokenError)>,
+}
+
+impl<'a> ParserError<'a> {
+    /// Error for the token at the head of `input`
+    pub fn token(input: &'a str, token: TokenError) -> Self {
+        ParserError {
+            verbose: VerboseError::from_error_kind(input, ErrorKind::Verify),
+            token: Some((input, token)),
+        }
+    }
+
+    /// Error with a static description, e.g. `"unterminated comment"`
+    pub fn context(input: &'a str, context: &'static str) -> Self {
+        ParserError {
+            verbose: VerboseError {
+                errors: vec![(input, VerboseErrorKind::Context(context))],
+            },
+            token: None,
+        }
+    }
+}
+
+impl ParserError<'_> {
+    /// Same error in `input` of the same length as the parsed one,
+    /// where positions in a multi-byte character of `input` are moved to its beginning
+    pub(crate) fn rebase(self, input: &str) -> ParserError<'_> {
+        let at = |rest: &str| {
+            let mut offset = input.len() - rest.len();
+            while !input.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            &input[offset..]
+        };
+        ParserError {
+            verbose: VerboseError {
+                errors: self
+                    .verbose
+                    .errors
+                    .into_iter()
+                    .map(|(rest, kind)| (at(rest), kind))
+                    .collect(),
+            },
+            token: self.token.map(|(rest, token)| (at(rest), token)),
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for ParserError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        ParserError {
+            verbose: VerboseError::from_error_kind(input, kind),
+            token: None,
+        }
+    }
+
+    fn append(input: &'a str, kind: ErrorKind, other: Self) -> Self {
+        ParserError {
+            verbose: VerboseError::append(input, kind, other.verbose),
+            token: other.token,
+        }
+    }
+
+    fn from_char(input: &'a str, c: char) -> Self {
+        ParserError {
+            verbose: VerboseError::from_char(input, c),
+            token: None,
+        }
+    }
+
+    fn or(self, other: Self) -> Self {
+        ParserError {
+            token: other.token.or(self.token),
+            verbose: self.verbose.or(other.verbose),
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for ParserError<'a> {
+    fn add_context(input: &'a str, context: &'static str, other: Self) -> Self {
+        ParserError {
+            verbose: VerboseError::add_context(input, context, other.verbose),
+            token: other.token,
+        }
+    }
+}
+
+impl<'a, E> FromExternalError<&'a str, E> for ParserError<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, e: E) -> Self {
+        ParserError {
+            verbose: VerboseError::from_external_error(input, kind, e),
+            token: None,
+        }
+    }
 }
 
 pub fn char_<'a>(c: char) -> impl ExchangeParser<'a, char> {
@@ -60,15 +166,22 @@ pub fn opt_<'a, O>(f: impl ExchangeParser<'a, O>) -> impl ExchangeParser<'a, Opt
 /// and terminated by an asterisk solidus `*/`
 ///
 /// These comments are dropped while parsing. Do not passed to following convert step.
+/// Comments are not nested, i.e. `/*` in a comment is a part of the comment.
+///
+/// Error
+/// ------
+/// - Failure if `*/` does not appear after `/*`,
+///   and [TokenizeFailed::offset](crate::error::TokenizeFailed::offset) points to the `/*`
 ///
 pub fn comment(input: &str) -> ParseResult<String> {
-    let internal = alt((
-        none_of("*"),
-        tuple((char('*'), peek(not(char('/'))))).map(|(star, _not_slash)| star),
-    ));
-    tuple((tag("/*"), many0(internal), tag("*/")))
-        .map(|(_start, c, _end)| c.into_iter().collect())
-        .parse(input)
+    let (rest, _start) = tag("/*")(input)?;
+    match rest.find("*/") {
+        Some(end) => Ok((&rest[end + 2..], rest[..end].to_string())),
+        None => Err(nom::Err::Failure(ParserError::context(
+            input,
+            "unterminated comment",
+        ))),
+    }
 }
 
 /// Comments with front/back spaces, or multi-space at least 1 char
@@ -247,6 +360,20 @@ mod tests {
         let (res, c) = super::comment("/* vim * vim */").finish().unwrap();
         assert_eq!(res, "");
         assert_eq!(c, " vim * vim ");
+
+        // Not nested
+        let (res, c) = super::comment("/* /* vim */ */").finish().unwrap();
+        assert_eq!(res, " */");
+        assert_eq!(c, " /* vim ");
+
+        assert!(matches!(
+            super::comment("/* vim *"),
+            Err(nom::Err::Failure(_))
+        ));
+        assert!(matches!(
+            super::ignorable(" /* vim"),
+            Err(nom::Err::Failure(_))
+        ));
     }
 
     #[test]
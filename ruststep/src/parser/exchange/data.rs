@@ -1,81 +1,311 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, exchange::*, token::*},
+    parser::{combinator::*, exchange::*, token::*, ParseOptions},
 };
-use nom::{branch::alt, Parser};
+use nom::{branch::alt, sequence::tuple, Parser};
 
-/// data_section = `DATA` \[ `(` [parameter_list] `)` \] `;` [entity_instance_list] `ENDSEC;` .
+/// data_section = [data_section_header] [entity_instance_list] `ENDSEC;` .
 pub fn data_section(input: &str) -> ParseResult<DataSection> {
-    tuple_((
-        tag_("DATA"),
-        opt_(tuple_((char_('('), parameter_list, char_(')')))),
-        char_(';'),
-        entity_instance_list,
-        tag_("ENDSEC;"),
-    ))
-    .map(|(_start, meta, _semicolon, entities, _end)| DataSection {
-        meta: meta
-            .map(|(_open, params, _close)| params)
-            .unwrap_or_default(),
-        entities,
-    })
-    .parse(input)
+    data_section_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [data_section] with [ParseOptions]
+pub fn data_section_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, DataSection> {
+    data_section_by(options, scoped_entity_instance_with(options))
+}
+
+/// [data_section_with] whose entity instances are parsed by `instance` instead of [scoped_entity_instance_with]
+pub(crate) fn data_section_by<'a>(
+    options: &ParseOptions,
+    instance: impl ExchangeParser<'a, Vec<EntityInstance>>,
+) -> impl ExchangeParser<'a, DataSection> {
+    let options = *options;
+    move |input| {
+        tuple_((
+            data_section_header_with(&options),
+            many0_(instance.clone()),
+            tag_("ENDSEC;"),
+        ))
+        .map(|(meta, instances, _end)| DataSection {
+            meta,
+            entities: instances.into_iter().flatten().collect(),
+        })
+        .parse(input)
+    }
+}
+
+/// data_section_header = `DATA` \[ `(` [parameter_list] `)` \] `;` .
+///
+/// This is not a rule in ISO-10303-21, but the beginning of [data_section].
+pub fn data_section_header(input: &str) -> ParseResult<Vec<Parameter>> {
+    data_section_header_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [data_section_header] with [ParseOptions]
+pub fn data_section_header_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<Parameter>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("DATA"),
+            opt_(tuple_((
+                char_('('),
+                parameter_list_with(&options),
+                char_(')'),
+            ))),
+            char_(';'),
+        ))
+        .map(|(_start, meta, _semicolon)| {
+            meta.map(|(_open, params, _close)| params)
+                .unwrap_or_default()
+        })
+        .parse(input)
+    }
 }
 
 /// entity_instance_list = { [entity_instance] } .
+///
+/// Entity instances in `&SCOPE` blocks are included, see [scoped_entity_instance].
 pub fn entity_instance_list(input: &str) -> ParseResult<Vec<EntityInstance>> {
-    many0_(entity_instance).parse(input)
+    entity_instance_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [entity_instance_list] with [ParseOptions]
+pub fn entity_instance_list_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<EntityInstance>> {
+    let options = *options;
+    move |input: &'a str| {
+        many0_(scoped_entity_instance_with(&options))
+            .map(|instances| instances.into_iter().flatten().collect())
+            .parse(input)
+    }
+}
+
+/// [entity_instance] with `&SCOPE` blocks of the first edition of ISO-10303-21
+///
+/// ```text
+/// simple_entity_instance  = entity_instance_name `=` [ scope ] simple_record `;` .
+/// complex_entity_instance = entity_instance_name `=` [ scope ] subsuper_record `;` .
+/// ```
+///
+/// Scopes are not kept, i.e. entity instances in the scope are returned with this instance
+/// in the order of the input, and the export list is ignored.
+/// A scope after the record, e.g. `#1 = A() &SCOPE #2 = B(); ENDSCOPE;`, is also accepted.
+///
+/// ```
+/// use nom::Finish;
+/// use ruststep::parser::exchange::scoped_entity_instance;
+///
+/// let input = "#1 = &SCOPE #2 = B(); #3 = C(#2); ENDSCOPE /#3/ A(#3);";
+/// let (_, instances) = scoped_entity_instance(input).finish().unwrap();
+/// let ids: Vec<u64> = instances.iter().map(|instance| instance.id()).collect();
+/// assert_eq!(ids, [2, 3, 1]);
+/// ```
+pub fn scoped_entity_instance(input: &str) -> ParseResult<Vec<EntityInstance>> {
+    scoped_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [scoped_entity_instance] with [ParseOptions]
+pub fn scoped_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Vec<EntityInstance>> {
+    let options = *options;
+    move |input: &'a str| {
+        let (input, (id, _eq, before)) =
+            tuple_((entity_instance_name, char_('='), opt_(scope_with(&options)))).parse(input)?;
+        let (input, (_, (instance, after, _semicolon))) = tuple((
+            ignorable,
+            tuple_((
+                move |input| {
+                    alt((
+                        simple_record_with(&options)
+                            .map(|record| EntityInstance::Simple { id, record }),
+                        subsuper_record_with(&options)
+                            .map(|subsuper| EntityInstance::Complex { id, subsuper }),
+                    ))
+                    .parse(input)
+                },
+                opt_(scope_with(&options)),
+                char_(';'),
+            )),
+        ))
+        .parse(input)?;
+        let instances = before
+            .into_iter()
+            .flatten()
+            .chain(std::iter::once(instance))
+            .chain(after.into_iter().flatten())
+            .collect();
+        Ok((input, instances))
+    }
+}
+
+/// scope = `&SCOPE` [entity_instance_list] `ENDSCOPE` \[ [export_list] \] .
+///
+/// This is a rule of the first edition of ISO-10303-21, see [scoped_entity_instance].
+pub fn scope(input: &str) -> ParseResult<Vec<EntityInstance>> {
+    scope_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [scope] with [ParseOptions]
+pub fn scope_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<EntityInstance>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("&SCOPE"),
+            entity_instance_list_with(&options),
+            tag_("ENDSCOPE"),
+            opt_(export_list),
+        ))
+        .map(|(_start, instances, _end, _export)| instances)
+        .parse(input)
+    }
+}
+
+/// export_list = `/` [entity_instance_name] { `,` [entity_instance_name] } `/` .
+///
+/// This is a rule of the first edition of ISO-10303-21, see [scoped_entity_instance].
+pub fn export_list(input: &str) -> ParseResult<Vec<u64>> {
+    tuple_((
+        char_('/'),
+        comma_separated(entity_instance_name),
+        char_('/'),
+    ))
+    .map(|(_open, names, _close)| names)
+    .parse(input)
 }
 
 /// entity_instance = [simple_entity_instance] | [complex_entity_instance] .
 pub fn entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    alt((simple_entity_instance, complex_entity_instance)).parse(input)
+    entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [entity_instance] with [ParseOptions]
+pub fn entity_instance_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, EntityInstance> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            simple_entity_instance_with(&options),
+            complex_entity_instance_with(&options),
+        ))
+        .parse(input)
+    }
 }
 
 /// simple_entity_instance = [entity_instance_name] `=` [simple_record] `;` .
 pub fn simple_entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    tuple_((entity_instance_name, char_('='), simple_record, char_(';')))
+    simple_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [simple_entity_instance] with [ParseOptions]
+pub fn simple_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            simple_record_with(&options),
+            char_(';'),
+        ))
         .map(|(id, _eq, record, _semicolon)| EntityInstance::Simple { id, record })
         .parse(input)
+    }
 }
 
 /// complex_entity_instance = [entity_instance_name] `=` [subsuper_record] `;` .
 pub fn complex_entity_instance(input: &str) -> ParseResult<EntityInstance> {
-    tuple_((
-        entity_instance_name,
-        char_('='),
-        subsuper_record,
-        char_(';'),
-    ))
-    .map(|(id, _eq, subsuper, _semicolon)| EntityInstance::Complex { id, subsuper })
-    .parse(input)
+    complex_entity_instance_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [complex_entity_instance] with [ParseOptions]
+pub fn complex_entity_instance_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, EntityInstance> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            entity_instance_name,
+            char_('='),
+            subsuper_record_with(&options),
+            char_(';'),
+        ))
+        .map(|(id, _eq, subsuper, _semicolon)| EntityInstance::Complex { id, subsuper })
+        .parse(input)
+    }
 }
 
 /// simple_record = [keyword] `(` \[ [parameter_list] \] `)` .
+///
+/// The leading `!` of [user_defined_keyword] is stripped, and kept as [Record::user_defined].
 pub fn simple_record(input: &str) -> ParseResult<Record> {
-    tuple_((keyword, char_('('), opt_(parameter_list), char_(')')))
-        .map(|(name, _open, parameter, _close)| Record {
+    simple_record_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [simple_record] with [ParseOptions]
+///
+/// Records of keywords enabled by [ParseOptions::specialize_keywords]
+/// are parsed by specialized parsers if possible.
+pub fn simple_record_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Record> {
+    let options = *options;
+    move |input: &'a str| {
+        if !options.specialized_keywords.is_empty() {
+            if let Some(parsed) = super::specialized::simple_record(input, &options) {
+                return Ok(parsed);
+            }
+        }
+        tuple_((
+            marked_keyword_with(&options),
+            char_('('),
+            opt_(parameter_list_with(&options)),
+            char_(')'),
+        ))
+        .map(|((name, user_defined), _open, parameter, _close)| Record {
             name,
+            user_defined,
             parameter: parameter.unwrap_or_default().into_iter().collect(),
         })
         .parse(input)
+    }
 }
 
 /// simple_record_list = [simple_record] { [simple_record] } .
 pub fn simple_record_list(input: &str) -> ParseResult<Vec<Record>> {
-    many0_(simple_record).parse(input)
+    simple_record_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [simple_record_list] with [ParseOptions]
+pub fn simple_record_list_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<Record>> {
+    let options = *options;
+    move |input: &'a str| many0_(simple_record_with(&options)).parse(input)
 }
 
 /// subsuper_record = `(` [simple_record_list] `)` .
 pub fn subsuper_record(input: &str) -> ParseResult<SubSuperRecord> {
-    tuple_((char_('('), simple_record_list, char_(')')))
-        .map(|(_open, records, _close)| SubSuperRecord(records))
-        .parse(input)
+    subsuper_record_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [subsuper_record] with [ParseOptions]
+pub fn subsuper_record_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, SubSuperRecord> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((char_('('), simple_record_list_with(&options), char_(')')))
+            .map(|(_open, records, _close)| SubSuperRecord(records))
+            .parse(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use nom::Finish;
+    use crate::{
+        ast::{Parameter, Record},
+        error::{TokenError, TokenizeFailed},
+        parser::ParseOptions,
+    };
+    use nom::{Finish, Parser};
 
     #[test]
     fn simple_recode1() {
@@ -106,4 +336,177 @@ mod tests {
         dbg!(record);
         assert_eq!(res, "");
     }
+
+    // Numeric forms and their values, which must be accepted in both strict and lenient modes
+    fn numeric_forms() -> Vec<(&'static str, Parameter)> {
+        vec![
+            ("5", Parameter::Integer(5)),
+            ("+5", Parameter::Integer(5)),
+            ("-5", Parameter::Integer(-5)),
+            ("05", Parameter::Integer(5)),
+            ("+05", Parameter::Integer(5)),
+            ("-007", Parameter::Integer(-7)),
+            ("0.5", Parameter::real(0.5)),
+            ("+0.500", Parameter::real(0.5)),
+            ("-000.5", Parameter::real(-0.5)),
+            ("+05.", Parameter::real(5.0)),
+            ("1.5E+02", Parameter::real(150.0)),
+            ("-01.5E-02", Parameter::real(-0.015)),
+            ("2.E003", Parameter::real(2000.0)),
+        ]
+    }
+
+    // Numeric forms with spaces after sign, which are accepted only in lenient mode
+    fn spaced_numeric_forms() -> Vec<(&'static str, Parameter)> {
+        vec![
+            ("- 5", Parameter::Integer(-5)),
+            ("+ 05", Parameter::Integer(5)),
+            ("- 0.5", Parameter::real(-0.5)),
+            ("1.0E- 3", Parameter::real(0.001)),
+        ]
+    }
+
+    // Reals without integral digits or decimal point, which are accepted only in lenient mode
+    fn loose_numeric_forms() -> Vec<(&'static str, Parameter)> {
+        vec![
+            (".5", Parameter::real(0.5)),
+            ("-.5", Parameter::real(-0.5)),
+            ("+.25E1", Parameter::real(2.5)),
+            ("5E-2", Parameter::real(0.05)),
+            ("-5E2", Parameter::real(-500.0)),
+        ]
+    }
+
+    // Records placing a numeric form at `{}`, and the expected parameters of the record
+    #[allow(clippy::type_complexity)]
+    fn positions() -> Vec<(&'static str, fn(Parameter) -> Vec<Parameter>)> {
+        vec![
+            ("A({})", |p| vec![p]),
+            ("A('s', {}, $)", |p| {
+                vec![
+                    Parameter::String("s".to_string()),
+                    p,
+                    Parameter::NotProvided,
+                ]
+            }),
+            ("A(({}))", |p| vec![Parameter::List(vec![p])]),
+            ("A((1, ({})))", |p| {
+                vec![Parameter::List(vec![
+                    Parameter::Integer(1),
+                    Parameter::List(vec![p]),
+                ])]
+            }),
+            ("A(B({}))", |p| {
+                vec![Parameter::Typed {
+                    keyword: "B".to_string(),
+                    parameter: Box::new(p),
+                }]
+            }),
+        ]
+    }
+
+    fn parse_record(input: &str, options: &ParseOptions) -> Option<Record> {
+        super::simple_record_with(options)
+            .parse(input)
+            .finish()
+            .ok()
+            .filter(|(res, _)| res.is_empty())
+            .map(|(_, record)| record)
+    }
+
+    #[test]
+    fn numeric_forms_in_positions() {
+        for (template, expected) in positions() {
+            for (form, value) in numeric_forms() {
+                let input = template.replace("{}", form);
+                let answer = Record {
+                    name: "A".to_string(),
+                    user_defined: false,
+                    parameter: Parameter::List(expected(value)),
+                };
+                for options in [ParseOptions::strict(), ParseOptions::lenient()] {
+                    assert_eq!(
+                        parse_record(&input, &options).as_ref(),
+                        Some(&answer),
+                        "{} with {:?}",
+                        input,
+                        options
+                    );
+                }
+            }
+            for (form, value) in spaced_numeric_forms()
+                .into_iter()
+                .chain(loose_numeric_forms())
+            {
+                let input = template.replace("{}", form);
+                let answer = Record {
+                    name: "A".to_string(),
+                    user_defined: false,
+                    parameter: Parameter::List(expected(value)),
+                };
+                assert_eq!(
+                    parse_record(&input, &ParseOptions::strict()),
+                    None,
+                    "{}",
+                    input
+                );
+                assert_eq!(
+                    parse_record(&input, &ParseOptions::lenient()),
+                    Some(answer),
+                    "{}",
+                    input
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let max = format!("A({})", i64::MAX);
+        let record = parse_record(&max, &ParseOptions::strict()).unwrap();
+        assert_eq!(
+            record.parameter,
+            Parameter::List(vec![Parameter::Integer(i64::MAX)])
+        );
+        let min = format!("A({})", i64::MIN);
+        let record = parse_record(&min, &ParseOptions::strict()).unwrap();
+        assert_eq!(
+            record.parameter,
+            Parameter::List(vec![Parameter::Integer(i64::MIN)])
+        );
+
+        // Error instead of panic
+        let input = "A(9223372036854775808)";
+        let err = super::simple_record(input).finish().unwrap_err();
+        assert_eq!(
+            TokenizeFailed::new(input, err).token_error(),
+            Some(&TokenError::IntegerOverflow {
+                text: "9223372036854775808".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn case_insensitive_keywords() {
+        let input = "Cartesian_Point('Name', .true., length_measure(1.0))";
+        assert_eq!(parse_record(input, &ParseOptions::strict()), None);
+
+        let options = ParseOptions {
+            case_insensitive_keywords: true,
+            ..Default::default()
+        };
+        let record = parse_record(input, &options).unwrap();
+        assert_eq!(record.name, "CARTESIAN_POINT");
+        assert_eq!(
+            record.parameter,
+            Parameter::List(vec![
+                Parameter::String("Name".to_string()),
+                Parameter::Enumeration("TRUE".to_string()),
+                Parameter::Typed {
+                    keyword: "LENGTH_MEASURE".to_string(),
+                    parameter: Box::new(Parameter::real(1.0)),
+                },
+            ])
+        );
+    }
 }
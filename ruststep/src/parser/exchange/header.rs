@@ -1,19 +1,37 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, exchange::*},
+    parser::{combinator::*, exchange::*, ParseOptions},
 };
 use nom::Parser;
 
 /// header_section = `HEADER;` [header_entity] [header_entity] [header_entity] \[ [header_entity_list] \] `ENDSEC;` .
 pub fn header_section(input: &str) -> ParseResult<Vec<Record>> {
-    tuple_((tag_("HEADER;"), header_entity_list, tag_("ENDSEC;")))
+    header_section_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [header_section] with [ParseOptions]
+pub fn header_section_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<Record>> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            tag_("HEADER;"),
+            header_entity_list_with(&options),
+            tag_("ENDSEC;"),
+        ))
         .map(|(_start, entities, _close)| entities)
         .parse(input)
+    }
 }
 
 /// header_entity_list = [header_entity] { [header_entity] } .
 pub fn header_entity_list(input: &str) -> ParseResult<Vec<Record>> {
-    many1_(header_entity).parse(input)
+    header_entity_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [header_entity_list] with [ParseOptions]
+pub fn header_entity_list_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<Record>> {
+    let options = *options;
+    move |input: &'a str| many1_(header_entity_with(&options)).parse(input)
 }
 
 /// header_entity = [simple_record] `;` .
@@ -24,7 +42,15 @@ pub fn header_entity_list(input: &str) -> ParseResult<Vec<Record>> {
 /// header_entity = keyword ( [ parameter_list ] ) ; .
 /// ```
 pub fn header_entity(input: &str) -> ParseResult<Record> {
-    tuple_((simple_record, char_(';')))
-        .map(|(record, _semicolon)| record)
-        .parse(input)
+    header_entity_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [header_entity] with [ParseOptions]
+pub fn header_entity_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Record> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((simple_record_with(&options), char_(';')))
+            .map(|(record, _semicolon)| record)
+            .parse(input)
+    }
 }
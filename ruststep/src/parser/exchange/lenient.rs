@@ -0,0 +1,167 @@
+use crate::{
+    ast::*,
+    parser::{combinator::*, exchange::*, normalize::normalized, token::*, ParseOptions},
+};
+use nom::{error::VerboseErrorKind, Parser};
+
+/// Entity instance skipped by [exchange_file_lenient]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIssue {
+    /// Entity instance name, e.g. `12` for `#12 = ...`, if it can be read
+    pub id: Option<u64>,
+    /// Byte offset of the skipped entity instance in the input
+    pub offset: usize,
+    /// Description of the syntax error
+    pub message: String,
+}
+
+/// Same as [exchange_file], but entity instances in DATA sections which cannot be parsed are skipped
+///
+/// A malformed entity instance is skipped up to `;` followed by the next entity instance or `ENDSEC;`,
+/// and reported as [ParseIssue].
+/// Errors in other parts of the exchange structure are not recovered,
+/// except the header with [ParseOptions::recover_header] by [exchange_file_lenient_with].
+///
+/// ```
+/// use nom::Finish;
+/// use ruststep::parser::exchange::exchange_file_lenient;
+///
+/// let step = r#"
+/// ISO-10303-21;
+/// HEADER;
+///   FILE_DESCRIPTION((''), '2;1');
+///   FILE_NAME('', '', (''), (''), '', '', '');
+///   FILE_SCHEMA(('TEST_SCHEMA'));
+/// ENDSEC;
+/// DATA;
+///   #1 = A('O'Brien');
+///   #2 = B(1.0, 2.0);
+///   #3 = C(1.0,);
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#;
+///
+/// let (_, (exchange, issues)) = exchange_file_lenient(step).finish().unwrap();
+/// assert_eq!(exchange.data[0].entities.len(), 1);
+/// assert_eq!(exchange.data[0].entities[0].id(), 2);
+/// assert_eq!(issues.len(), 2);
+/// assert_eq!(issues[0].id, Some(1));
+/// assert_eq!(issues[0].offset, step.find("#1").unwrap());
+/// assert_eq!(issues[1].id, Some(3));
+/// ```
+pub fn exchange_file_lenient(input: &str) -> ParseResult<(Exchange, Vec<ParseIssue>)> {
+    exchange_file_lenient_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [exchange_file_lenient] with [ParseOptions]
+pub fn exchange_file_lenient_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, (Exchange, Vec<ParseIssue>)> {
+    let options = *options;
+    move |input: &'a str| normalized(&options, input, lenient_exchange_file)
+}
+
+fn lenient_exchange_file<'a>(
+    options: &ParseOptions,
+    input: &'a str,
+) -> ParseResult<'a, (Exchange, Vec<ParseIssue>)> {
+    let (mut rest, prelude) = prelude(options, input)?;
+
+    let mut data = Vec::new();
+    let mut issues = Vec::new();
+    loop {
+        let (next, _) = ignorable(rest)?;
+        let Ok((next, meta)) = data_section_header_with(options).parse(next) else {
+            break;
+        };
+        rest = next;
+        let mut entities = Vec::new();
+        loop {
+            (rest, _) = ignorable(rest)?;
+            if let Ok((next, _end)) = tag_("ENDSEC;").parse(rest) {
+                rest = next;
+                break;
+            }
+            if rest.is_empty() || rest.starts_with("END-ISO-10303-21;") {
+                // Report missing `ENDSEC;` instead of skipping the rest
+                tag_("ENDSEC;").parse(rest)?;
+            }
+            match scoped_entity_instance_with(options).parse(rest) {
+                Ok((next, instances)) => {
+                    entities.extend(instances);
+                    rest = next;
+                }
+                Err(nom::Err::Error(e) | nom::Err::Failure(e)) => {
+                    issues.push(ParseIssue {
+                        id: entity_instance_name(rest).ok().map(|(_, id)| id),
+                        offset: input.len() - rest.len(),
+                        message: describe(&e),
+                    });
+                    rest = &rest[skip_instance(rest)..];
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        data.push(DataSection { meta, entities });
+    }
+
+    let (rest, (_, _end, signature, _)) = tuple_((
+        ignorable,
+        tag_("END-ISO-10303-21;"),
+        many0_(signature_section),
+        ignorable,
+    ))
+    .parse(rest)?;
+
+    let (exchange, _warnings) = prelude.into_exchange(data, signature);
+    Ok((rest, (exchange, issues)))
+}
+
+/// Length of the malformed entity instance at the beginning of `input`,
+/// i.e. up to `;` followed by `#` or `ENDSEC;`, or the entire `input` if not found.
+///
+/// Strings and comments are not taken into account,
+/// since a malformed instance may contain an unescaped apostrophe.
+fn skip_instance(input: &str) -> usize {
+    let mut start = 0;
+    while let Some(i) = input[start..].find(';') {
+        let end = start + i + 1;
+        let next = ignorable(&input[end..]).map_or(&input[end..], |(next, _)| next);
+        if next.starts_with('#') || next.starts_with("ENDSEC;") {
+            return end;
+        }
+        start = end;
+    }
+    input.len()
+}
+
+/// One-line description of the innermost error
+fn describe(e: &ParserError) -> String {
+    if let Some((_, token)) = &e.token {
+        return token.to_string();
+    }
+    let Some((rest, kind)) = e.verbose.errors.first() else {
+        return "syntax error".to_string();
+    };
+    let found = match rest.chars().next() {
+        Some(c) => format!("'{}'", c),
+        None => "end of input".to_string(),
+    };
+    match kind {
+        VerboseErrorKind::Char(c) => format!("expected '{}', found {}", c, found),
+        VerboseErrorKind::Context(context) => format!("{}, found {}", context, found),
+        VerboseErrorKind::Nom(kind) => format!("{:?} failed at {}", kind, found),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn skip_instance() {
+        let input = "#1 = A('a;b'); #2 = B();";
+        assert_eq!(super::skip_instance(input), "#1 = A('a;b');".len());
+        let input = "#1 = A(); /* comment */ ENDSEC;";
+        assert_eq!(super::skip_instance(input), "#1 = A();".len());
+        assert_eq!(super::skip_instance("#1 = A("), 7);
+    }
+}
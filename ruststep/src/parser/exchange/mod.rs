@@ -3,18 +3,25 @@
 mod anchor;
 mod data;
 mod header;
+mod lenient;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod parameter;
 mod reference;
+mod specialized;
 
 pub use anchor::*;
 pub use data::*;
 pub use header::*;
+pub use lenient::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
 pub use parameter::*;
 pub use reference::*;
 
 use crate::{
     ast::*,
-    parser::{combinator::*, token::*},
+    parser::{combinator::*, normalize::normalized, recover, token::*, ParseOptions, Warning},
 };
 use nom::Parser;
 
@@ -25,26 +32,111 @@ use nom::Parser;
 ///               { [data_section] }
 ///                 `END-ISO-10303-21;`
 ///               { signature_section } .
+///
+/// Comments and spaces are allowed before `ISO-10303-21;` and after the last section
+/// as well as between tokens.
+/// See [exchange_file_lenient] to skip malformed entity instances,
+/// and `exchange_file_parallel` with `rayon` feature to parse DATA sections on multiple threads.
 pub fn exchange_file(input: &str) -> ParseResult<Exchange> {
-    tuple_((
-        tag_("ISO-10303-21;"),
-        header_section,
-        opt_(anchor_section),
-        opt_(reference_section),
-        many0_(data_section),
-        tag_("END-ISO-10303-21;"),
-        many0_(signature_section),
-    ))
-    .map(
-        |(_start, header, anchor, reference, data, _end, signature)| Exchange {
-            header,
-            anchor: anchor.unwrap_or_default(),
-            reference: reference.unwrap_or_default(),
+    exchange_file_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [exchange_file] with [ParseOptions]
+///
+/// Full-width punctuation is normalized if [ParseOptions::normalize_fullwidth_punctuation] is set.
+pub fn exchange_file_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Exchange> {
+    let options = *options;
+    move |input: &'a str| {
+        normalized(&options, input, |options, input| {
+            exchange_file_with_warnings(options)
+                .map(|(exchange, _warnings)| exchange)
+                .parse(input)
+        })
+    }
+}
+
+/// [exchange_file_with] without normalization, reporting the header entities recovered by [prelude]
+pub(crate) fn exchange_file_with_warnings<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, (Exchange, Vec<Warning>)> {
+    exchange_file_by(options, scoped_entity_instance_with(options))
+}
+
+/// [exchange_file_with_warnings] whose entity instances are parsed by `instance`, see [data_section_by]
+pub(crate) fn exchange_file_by<'a>(
+    options: &ParseOptions,
+    instance: impl ExchangeParser<'a, Vec<EntityInstance>>,
+) -> impl ExchangeParser<'a, (Exchange, Vec<Warning>)> {
+    let options = *options;
+    move |input| {
+        let (rest, prelude) = prelude(&options, input)?;
+        let (rest, (_, data, _end, signature, _)) = tuple_((
+            ignorable,
+            many0_(data_section_by(&options, instance.clone())),
+            tag_("END-ISO-10303-21;"),
+            many0_(signature_section),
+            ignorable,
+        ))
+        .parse(rest)?;
+        Ok((rest, prelude.into_exchange(data, signature)))
+    }
+}
+
+/// `ISO-10303-21;` and the sections before DATA sections
+pub(crate) struct Prelude {
+    pub header: Vec<Record>,
+    pub anchor: Vec<Anchor>,
+    pub reference: Vec<ReferenceEntry>,
+    /// Header entities skipped by [ParseOptions::recover_header]
+    pub warnings: Vec<Warning>,
+}
+
+impl Prelude {
+    pub(crate) fn into_exchange(
+        self,
+        data: Vec<DataSection>,
+        signature: Vec<String>,
+    ) -> (Exchange, Vec<Warning>) {
+        let exchange = Exchange {
+            header: self.header,
+            anchor: self.anchor,
+            reference: self.reference,
             data,
             signature,
-        },
-    )
-    .parse(input)
+        };
+        (exchange, self.warnings)
+    }
+}
+
+/// `ISO-10303-21;` [header_section] \[ [anchor_section] \] \[ [reference_section] \] .
+///
+/// This is the common part of the parsers of entire exchange structures.
+/// If [ParseOptions::recover_header] is set,
+/// the header is parsed by [recover::header_section],
+/// and positions of warnings are byte offsets in `input`.
+pub(crate) fn prelude<'a>(options: &ParseOptions, input: &'a str) -> ParseResult<'a, Prelude> {
+    let (rest, (_, _start, _)) =
+        tuple_((ignorable, tag_("ISO-10303-21;"), ignorable)).parse(input)?;
+    let (rest, (header, warnings)) = if options.recover_header {
+        recover::header_section(options, input, rest)?
+    } else {
+        header_section_with(options)
+            .map(|header| (header, Vec::new()))
+            .parse(rest)?
+    };
+    let (rest, (_, anchor, reference)) = tuple_((
+        ignorable,
+        opt_(anchor_section_with(options)),
+        opt_(reference_section),
+    ))
+    .parse(rest)?;
+    let prelude = Prelude {
+        header,
+        anchor: anchor.unwrap_or_default(),
+        reference: reference.unwrap_or_default(),
+        warnings,
+    };
+    Ok((rest, prelude))
 }
 
 /// signature_section  = `SIGNATURE` signature_content `ENDSEC;`.
@@ -0,0 +1,212 @@
+use crate::{
+    ast::*,
+    parser::{combinator::*, exchange::*, normalize::normalized, ParseOptions},
+};
+use nom::{sequence::tuple, Parser};
+use rayon::prelude::*;
+use std::iter;
+
+/// Same as [exchange_file], but entity instances in each DATA section are parsed on threads of rayon,
+/// enabled by `rayon` feature
+///
+/// A DATA section is split into chunks at `;` followed by `#`, i.e. possible ends of entity instances,
+/// and the chunks are parsed in parallel.
+/// If a chunk cannot be parsed, e.g. it is split in a string or a `&SCOPE` block,
+/// the section is parsed again by [data_section],
+/// and thus the result including errors is always same as [exchange_file].
+///
+/// ```
+/// use nom::Finish;
+/// use ruststep::parser::exchange::{exchange_file, exchange_file_parallel};
+///
+/// let step = std::fs::read_to_string(concat!(
+///     env!("CARGO_MANIFEST_DIR"),
+///     "/tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step"
+/// ))
+/// .unwrap();
+/// let (_, parallel) = exchange_file_parallel(&step).finish().unwrap();
+/// let (_, sequential) = exchange_file(&step).finish().unwrap();
+/// assert_eq!(parallel, sequential);
+/// ```
+pub fn exchange_file_parallel(input: &str) -> ParseResult<Exchange> {
+    exchange_file_parallel_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [exchange_file_parallel] with [ParseOptions], which are used in every thread
+pub fn exchange_file_parallel_with<'a>(
+    options: &ParseOptions,
+) -> impl ExchangeParser<'a, Exchange> {
+    let options = *options;
+    move |input: &'a str| normalized(&options, input, parallel_exchange_file)
+}
+
+fn parallel_exchange_file<'a>(options: &ParseOptions, input: &'a str) -> ParseResult<'a, Exchange> {
+    let (mut rest, prelude) = prelude(options, input)?;
+
+    let mut data = Vec::new();
+    loop {
+        let (start, _) = ignorable(rest)?;
+        let Ok((body, meta)) = data_section_header_with(options).parse(start) else {
+            break;
+        };
+        let chunks = rayon::current_num_threads();
+        let (next, section) = match parallel_entity_instances(options, body, chunks) {
+            Some((next, entities)) => (next, DataSection { meta, entities }),
+            None => match data_section_with(options).parse(start) {
+                Ok(parsed) => parsed,
+                // Report the error at the same position as `many0_(data_section)` in [exchange_file]
+                Err(nom::Err::Error(_)) => break,
+                Err(e) => return Err(e),
+            },
+        };
+        data.push(section);
+        rest = next;
+    }
+
+    let (rest, (_, _end, signature, _)) = tuple_((
+        ignorable,
+        tag_("END-ISO-10303-21;"),
+        many0_(signature_section),
+        ignorable,
+    ))
+    .parse(rest)?;
+
+    let (exchange, _warnings) = prelude.into_exchange(data, signature);
+    Ok((rest, exchange))
+}
+
+/// Minimum length of a chunk in bytes, not to split small sections
+const MIN_CHUNK_LEN: usize = 1 << 16;
+
+/// Parse entity instances and `ENDSEC;` after [data_section_header] in at most `chunks` chunks.
+/// `None` if the input cannot be split or parsed.
+fn parallel_entity_instances<'a>(
+    options: &ParseOptions,
+    input: &'a str,
+    chunks: usize,
+) -> Option<(&'a str, Vec<EntityInstance>)> {
+    // This is the end of section unless it is in a string or a comment,
+    // and then the last chunk cannot be parsed.
+    let end = input.find("ENDSEC;")?;
+    let body = &input[..end];
+
+    let chunks = chunks.min(body.len() / MIN_CHUNK_LEN).max(1);
+    let mut bounds: Vec<usize> = (1..chunks)
+        .into_par_iter()
+        .filter_map(|i| boundary(body, i * body.len() / chunks))
+        .collect();
+    bounds.dedup();
+    let ranges: Vec<_> = iter::once(0)
+        .chain(bounds.iter().copied())
+        .zip(bounds.iter().copied().chain(iter::once(end)))
+        .collect();
+
+    let parsed: Option<Vec<Vec<EntityInstance>>> = ranges
+        .into_par_iter()
+        .map(|(start, end)| {
+            let (rest, (_, entities, _)) =
+                tuple((ignorable, entity_instance_list_with(options), ignorable))
+                    .parse(&body[start..end])
+                    .ok()?;
+            rest.is_empty().then_some(entities)
+        })
+        .collect();
+    let entities = parsed?.concat();
+
+    let (rest, _) = tag_("ENDSEC;").parse(&input[end..]).ok()?;
+    Some((rest, entities))
+}
+
+/// Position next to the first `;` at or after `from` followed by spaces and `#`
+fn boundary(body: &str, from: usize) -> Option<usize> {
+    let bytes = body.as_bytes();
+    (from..bytes.len())
+        .find(|&i| {
+            bytes[i] == b';'
+                && bytes[i + 1..]
+                    .iter()
+                    .find(|b| !b.is_ascii_whitespace())
+                    .is_some_and(|&b| b == b'#')
+        })
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::Finish;
+
+    fn lines(len: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut total = 0;
+        while total < len {
+            let id = lines.len() + 1;
+            let line = format!("#{} = A({}.0, 'x;{}') /* ; */;\n", id, id, id);
+            total += line.len();
+            lines.push(line);
+        }
+        lines
+    }
+
+    /// Index of the line at the middle of the input in bytes
+    fn middle(lines: &[String]) -> usize {
+        let half = lines.concat().len() / 2;
+        let mut len = 0;
+        lines
+            .iter()
+            .position(|line| {
+                len += line.len();
+                len > half
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn chunks() {
+        let input = lines(10 * MIN_CHUNK_LEN).concat() + "ENDSEC;";
+        let (_, (expected, _)) = tuple_((entity_instance_list, tag_("ENDSEC;")))
+            .parse(&input)
+            .finish()
+            .unwrap();
+        for chunks in [1, 3, 4, 16] {
+            let (rest, entities) =
+                parallel_entity_instances(&ParseOptions::strict(), &input, chunks).unwrap();
+            assert_eq!(rest, "");
+            assert_eq!(entities, expected);
+        }
+    }
+
+    #[test]
+    fn split_scope() {
+        // Scope block over the boundary of chunks
+        let mut lines = lines(2 * MIN_CHUNK_LEN);
+        let middle = middle(&lines);
+        lines[middle - 2].insert_str(0, "#0 = &SCOPE ");
+        lines[middle + 2].insert_str(0, "ENDSCOPE B(); ");
+        let input = lines.concat() + "ENDSEC;";
+        assert!(parallel_entity_instances(&ParseOptions::strict(), &input, 2).is_none());
+        assert!(parallel_entity_instances(&ParseOptions::strict(), &input, 1).is_some());
+    }
+
+    #[test]
+    fn split_string() {
+        // `;` followed by `#` in a string at the boundary of chunks
+        let mut lines = lines(2 * MIN_CHUNK_LEN);
+        let middle = middle(&lines);
+        for line in &mut lines[middle - 2..middle + 2] {
+            *line = line.replace("'x;", "'x; #");
+        }
+        let input = lines.concat() + "ENDSEC;";
+        let (_, (expected, _)) = tuple_((entity_instance_list, tag_("ENDSEC;")))
+            .parse(&input)
+            .finish()
+            .unwrap();
+        assert!(parallel_entity_instances(&ParseOptions::strict(), &input, 2).is_none());
+        assert_eq!(
+            parallel_entity_instances(&ParseOptions::strict(), &input, 1)
+                .unwrap()
+                .1,
+            expected
+        );
+    }
+}
@@ -1,44 +1,110 @@
 use crate::{
     ast::*,
-    parser::{combinator::*, token::*},
+    parser::{combinator::*, options, token::*, ParseOptions},
+};
+use nom::{
+    branch::alt,
+    combinator::{consumed, value},
+    Parser,
 };
-use nom::{branch::alt, combinator::value, Parser};
 
 /// list = `(` \[ [parameter] { `,` [parameter] } \] `)` .
 pub fn list(input: &str) -> ParseResult<Parameter> {
-    tuple_((char_('('), opt_(comma_separated(parameter)), char_(')')))
+    list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [list] with [ParseOptions]
+pub fn list_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            char_('('),
+            opt_(comma_separated(parameter_with(&options))),
+            char_(')'),
+        ))
         .map(|(_open, params, _close)| Parameter::List(params.unwrap_or_default()))
         .parse(input)
+    }
 }
 
 /// parameter = [typed_parameter] | [untyped_parameter] | [omitted_parameter] .
+///
+/// Error
+/// ------
+/// - Failure with [TokenError](crate::error::TokenError::DepthLimitExceeded)
+///   if parameters are nested deeper than [ParseOptions::max_depth]
 pub fn parameter(input: &str) -> ParseResult<Parameter> {
-    alt((typed_parameter, untyped_parameter, omitted_parameter)).parse(input)
+    parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [parameter] with [ParseOptions]
+pub fn parameter_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    let options = *options;
+    move |input: &'a str| {
+        options::nested(&options, input, |input| {
+            alt((
+                typed_parameter_with(&options),
+                untyped_parameter_with(&options),
+                omitted_parameter,
+            ))
+            .parse(input)
+        })
+    }
 }
 
 /// typed_parameter = [keyword] `(` [parameter] `)` .
 pub fn typed_parameter(input: &str) -> ParseResult<Parameter> {
-    tuple_((keyword, char_('('), parameter, char_(')')))
+    typed_parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [typed_parameter] with [ParseOptions]
+pub fn typed_parameter_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple_((
+            keyword_with(&options),
+            char_('('),
+            parameter_with(&options),
+            char_(')'),
+        ))
         .map(|(name, _open, ty, _close)| Parameter::Typed {
             keyword: name,
             parameter: Box::new(ty),
         })
         .parse(input)
+    }
 }
 
-/// untyped_parameter = `$` | [integer] | [real] | [string] | [rhs_occurrence_name] | [enumeration] | binary | [list] .
+/// untyped_parameter = `$` | [integer] | [real] | [string] | [rhs_occurrence_name] | [enumeration] | [binary] | [list] .
 pub fn untyped_parameter(input: &str) -> ParseResult<Parameter> {
-    alt((
-        char_('$').map(|_| Parameter::NotProvided),
-        real.map(Parameter::Real),
-        integer.map(Parameter::Integer),
-        string.map(Parameter::String),
-        rhs_occurrence_name.map(Parameter::Ref),
-        enumeration.map(Parameter::Enumeration),
-        // FIXME binary
-        list,
-    ))
-    .parse(input)
+    untyped_parameter_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [untyped_parameter] with [ParseOptions]
+pub fn untyped_parameter_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Parameter> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            char_('$').map(|_| Parameter::NotProvided),
+            consumed(real_with(&options))
+                .map(|(raw, value)| real_parameter(options.keep_real_text, value, raw)),
+            integer_with(&options).map(Parameter::Integer),
+            string.map(Parameter::String),
+            rhs_occurrence_name_with(&options).map(Parameter::Ref),
+            enumeration_with(&options).map(Parameter::Enumeration),
+            binary.map(Parameter::Binary),
+            list_with(&options),
+        ))
+        .parse(input)
+    }
+}
+
+/// [Parameter::Real] of `value` parsed from `raw`, see [ParseOptions::keep_real_text]
+pub(super) fn real_parameter(keep_real_text: bool, value: f64, raw: &str) -> Parameter {
+    Parameter::Real {
+        value,
+        raw: keep_real_text.then(|| raw.to_string()),
+    }
 }
 
 /// omitted_parameter = `*` .
@@ -48,7 +114,13 @@ pub fn omitted_parameter(input: &str) -> ParseResult<Parameter> {
 
 /// parameter_list = [parameter] { `,` [parameter] } .
 pub fn parameter_list(input: &str) -> ParseResult<Vec<Parameter>> {
-    comma_separated(parameter).parse(input)
+    parameter_list_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [parameter_list] with [ParseOptions]
+pub fn parameter_list_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Vec<Parameter>> {
+    let options = *options;
+    move |input: &'a str| comma_separated(parameter_with(&options)).parse(input)
 }
 
 #[cfg(test)]
@@ -0,0 +1,224 @@
+//! Specialized parsers for frequent records, see [crate::parser::ParseOptions::specialize_keywords]
+//!
+//! These parsers accept only a subset of [super::simple_record] and return `None` otherwise,
+//! e.g. for comments, escaped apostrophes, or spaces between a sign and digits.
+//! The caller must fall back to the generic parser in that case.
+
+use crate::{
+    ast::*,
+    parser::{token, ParseOptions},
+};
+
+/// Parse `input` starting with a keyword in [ParseOptions::specialized_keywords] followed by `(`
+pub(super) fn simple_record<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Option<(&'a str, Record)> {
+    let open = input.find('(')?;
+    let name = &input[..open];
+    if !options.specialized_keywords.contains(name) {
+        return None;
+    }
+    let mut cursor = Cursor(&input[open + 1..], options.keep_real_text);
+    let parameters = match name {
+        "CARTESIAN_POINT" | "DIRECTION" => point(&mut cursor)?,
+        "VERTEX_POINT" => vertex_point(&mut cursor)?,
+        "ORIENTED_EDGE" => oriented_edge(&mut cursor)?,
+        _ => return None,
+    };
+    cursor.char(')')?;
+    let record = Record {
+        name: name.to_string(),
+        user_defined: false,
+        parameter: Parameter::List(parameters),
+    };
+    Some((cursor.0, record))
+}
+
+/// `('label', (r, r[, r]))`
+fn point(cursor: &mut Cursor) -> Option<Vec<Parameter>> {
+    let label = cursor.string()?;
+    cursor.char(',')?;
+    cursor.char('(')?;
+    let mut coordinates = Vec::with_capacity(3);
+    coordinates.push(cursor.real()?);
+    while coordinates.len() < 3 && cursor.char(',').is_some() {
+        coordinates.push(cursor.real()?);
+    }
+    cursor.char(')')?;
+    Some(vec![Parameter::String(label), Parameter::List(coordinates)])
+}
+
+/// `('label', #id)`
+fn vertex_point(cursor: &mut Cursor) -> Option<Vec<Parameter>> {
+    let label = cursor.string()?;
+    cursor.char(',')?;
+    let point = cursor.entity_instance_name()?;
+    Some(vec![
+        Parameter::String(label),
+        Parameter::Ref(Name::Entity(point)),
+    ])
+}
+
+/// `('label', *, *, #id, .T.)`
+fn oriented_edge(cursor: &mut Cursor) -> Option<Vec<Parameter>> {
+    let label = cursor.string()?;
+    cursor.char(',')?;
+    cursor.char('*')?;
+    cursor.char(',')?;
+    cursor.char('*')?;
+    cursor.char(',')?;
+    let edge = cursor.entity_instance_name()?;
+    cursor.char(',')?;
+    let orientation = cursor.logical()?;
+    Some(vec![
+        Parameter::String(label),
+        Parameter::Omitted,
+        Parameter::Omitted,
+        Parameter::Ref(Name::Entity(edge)),
+        Parameter::Enumeration(orientation.to_string()),
+    ])
+}
+
+/// Residual input, and [ParseOptions::keep_real_text]. Every method skips spaces before the token.
+struct Cursor<'a>(&'a str, bool);
+
+impl<'a> Cursor<'a> {
+    /// Same as `multispace0` used in [crate::parser::combinator::ignorable]
+    fn skip_spaces(&mut self) {
+        self.0 = self.0.trim_start_matches([' ', '\t', '\r', '\n']);
+    }
+
+    fn char(&mut self, c: char) -> Option<()> {
+        self.skip_spaces();
+        self.0 = self.0.strip_prefix(c)?;
+        Some(())
+    }
+
+    /// String without `''` escape
+    fn string(&mut self) -> Option<String> {
+        self.skip_spaces();
+        let body = self.0.strip_prefix('\'')?;
+        let end = body.find('\'')?;
+        let residual = &body[end + 1..];
+        if residual.starts_with('\'') {
+            return None;
+        }
+        let s = token::decode_string(&body[..end]).ok()?.into_owned();
+        self.0 = residual;
+        Some(s)
+    }
+
+    fn digits(input: &str) -> usize {
+        input.bytes().take_while(u8::is_ascii_digit).count()
+    }
+
+    /// [crate::parser::token::real] without spaces after signs
+    fn real(&mut self) -> Option<Parameter> {
+        self.skip_spaces();
+        let bytes = self.0.as_bytes();
+        let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+        let integral = Self::digits(&self.0[end..]);
+        if integral == 0 || bytes.get(end + integral) != Some(&b'.') {
+            return None;
+        }
+        end += integral + 1;
+        end += Self::digits(&self.0[end..]);
+        if bytes.get(end) == Some(&b'E') {
+            let mut exp = end + 1;
+            exp += usize::from(matches!(bytes.get(exp), Some(b'+' | b'-')));
+            let digits = Self::digits(&self.0[exp..]);
+            if digits > 0 {
+                end = exp + digits;
+            }
+        }
+        let (raw, rest) = self.0.split_at(end);
+        let value = raw.parse().ok()?;
+        self.0 = rest;
+        Some(super::parameter::real_parameter(self.1, value, raw))
+    }
+
+    fn entity_instance_name(&mut self) -> Option<u64> {
+        self.skip_spaces();
+        let body = self.0.strip_prefix('#')?;
+        let digits = Self::digits(body);
+        let id = body[..digits].parse().ok()?;
+        self.0 = &body[digits..];
+        Some(id)
+    }
+
+    /// `.T.` or `.F.`
+    fn logical(&mut self) -> Option<&'static str> {
+        self.skip_spaces();
+        for value in ["T", "F"] {
+            if let Some(residual) = self
+                .0
+                .strip_prefix('.')
+                .and_then(|s| s.strip_prefix(value))
+                .and_then(|s| s.strip_prefix('.'))
+            {
+                self.0 = residual;
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{exchange, SpecializedKeywords};
+    use nom::Finish;
+
+    fn specialize(keywords: SpecializedKeywords) -> ParseOptions {
+        ParseOptions {
+            specialized_keywords: keywords,
+            ..Default::default()
+        }
+    }
+
+    fn assert_same(input: &str) {
+        let (residual, generic) = exchange::simple_record(input).finish().unwrap();
+        let (fast_residual, fast) = simple_record(input, &specialize(SpecializedKeywords::all()))
+            .unwrap_or_else(|| panic!("Not specialized: {}", input));
+        assert_eq!(fast, generic);
+        assert_eq!(fast_residual, residual);
+    }
+
+    #[test]
+    fn same_as_generic() {
+        assert_same("CARTESIAN_POINT('',(0.,1.5,-2.25E-3));");
+        assert_same("CARTESIAN_POINT( 'p' , ( +1.0 , 2.E5 ) ) ;");
+        assert_same("CARTESIAN_POINT('\\X2\\30B9\\X0\\',(1.0,2.0,3.0))");
+        assert_same("DIRECTION('',(0.70710678118654752,0.,0.70710678118654752))");
+        assert_same("VERTEX_POINT('',#18)");
+        assert_same("ORIENTED_EDGE('',*,*,#3,.F.)");
+        assert_same("ORIENTED_EDGE('', *, *, #3, .T.)");
+    }
+
+    #[test]
+    fn fallback() {
+        let options = specialize(SpecializedKeywords::all());
+        for input in [
+            // escaped apostrophe
+            "CARTESIAN_POINT('it''s',(0.,1.,2.))",
+            // malformed directive
+            "CARTESIAN_POINT('\\X2\\30B',(0.,1.,2.))",
+            // comment
+            "CARTESIAN_POINT('',/* origin */(0.,1.,2.))",
+            // integer
+            "CARTESIAN_POINT('',(0,1.,2.))",
+            // more than three coordinates
+            "CARTESIAN_POINT('',(0.,1.,2.,3.))",
+            "VERTEX_POINT('',$)",
+            "ORIENTED_EDGE('',*,*,#3,.UNKNOWN.)",
+            "LINE('',#1,#2)",
+        ] {
+            assert!(simple_record(input, &options).is_none(), "{}", input);
+        }
+        let mut keywords = SpecializedKeywords::default();
+        keywords.insert("DIRECTION");
+        assert!(simple_record("CARTESIAN_POINT('',(0.,1.,2.))", &specialize(keywords)).is_none());
+    }
+}
@@ -30,15 +30,63 @@
 //! ```
 
 pub mod basic;
+pub mod borrowed;
 pub mod combinator;
 pub mod exchange;
 pub mod token;
 
+mod iso8859;
+mod normalize;
+mod options;
+mod recover;
+mod stream;
+
+pub use normalize::Warning;
+pub use options::{ParseOptions, SpecializedKeywords};
+pub use stream::{read_instances, DispatchStream, InstanceReader, TypedStream};
+
 use crate::{
     ast,
-    error::{Result, TokenizeFailed},
+    capabilities::{Feature, Support},
+    error::{Error, Result, TokenizeFailed},
+};
+use combinator::*;
+use nom::{sequence::tuple, Finish, Parser};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    ops::ControlFlow,
+    path::Path,
 };
-use nom::Finish;
+
+/// Syntax of exchange structures accepted by the parsers, see [crate::capabilities]
+pub(crate) const CAPABILITIES: &[(Feature, Support)] = &[
+    (Feature::ComplexInstance, Support::Supported),
+    (Feature::ValueInstance, Support::Supported),
+    (Feature::AnchorSection, Support::Supported),
+    (Feature::ReferenceSection, Support::Supported),
+    (Feature::SignatureSection, Support::Supported),
+    (Feature::UserDefinedKeyword, Support::Supported),
+    (Feature::ControlDirective, Support::Supported),
+    (Feature::Binary, Support::Supported),
+    (Feature::ScopeBlock, Support::Supported),
+    (
+        Feature::FullwidthPunctuation,
+        Support::SupportedWithOption("ParseOptions::normalize_fullwidth_punctuation"),
+    ),
+    (
+        Feature::SpaceAfterSign,
+        Support::SupportedWithOption("ParseOptions::space_after_sign"),
+    ),
+    (
+        Feature::LooseReal,
+        Support::SupportedWithOption("ParseOptions::loose_reals"),
+    ),
+    (
+        Feature::LowercaseKeyword,
+        Support::SupportedWithOption("ParseOptions::case_insensitive_keywords"),
+    ),
+];
 
 /// Parse HEADER section
 ///
@@ -74,8 +122,262 @@ pub fn parse_header(input: &str) -> Result<(&str, Vec<ast::Record>)> {
 
 /// Parse entire STEP file
 pub fn parse(input: &str) -> Result<ast::Exchange> {
-    match exchange::exchange_file(input).finish() {
+    parse_with(input, &ParseOptions::strict())
+}
+
+/// Read and parse a STEP file at `path`, see [parse_bytes] for the encoding
+///
+/// Errors are reported as [Error::FileFailed] with `path`.
+///
+/// ```
+/// let path = concat!(
+///     env!("CARGO_MANIFEST_DIR"),
+///     "/tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step"
+/// );
+/// let exchange = ruststep::parser::parse_file(path).unwrap();
+/// assert_eq!(exchange.data.len(), 1);
+///
+/// let err = ruststep::parser::parse_file("not-found.step").unwrap_err();
+/// assert!(err.to_string().starts_with("not-found.step: "));
+/// ```
+pub fn parse_file(path: impl AsRef<Path>) -> Result<ast::Exchange> {
+    let path = path.as_ref();
+    std::fs::read(path)
+        .map_err(Error::from)
+        .and_then(|bytes| parse_bytes(&bytes))
+        .map_err(|e| Error::FileFailed {
+            path: path.to_path_buf(),
+            source: Box::new(e),
+        })
+}
+
+/// Parse entire STEP file in bytes
+///
+/// A UTF-8 byte order mark is removed,
+/// and the input is decoded as ISO 8859-1 (Latin-1) if it is not valid UTF-8.
+/// Only strings are affected by the encoding, since the other parts of exchange structure are ASCII.
+///
+/// ```
+/// // `ü` in ISO 8859-1
+/// let latin1 = b"ISO-10303-21; HEADER; A(); ENDSEC; DATA; #1 = B('M\xFCller'); ENDSEC; END-ISO-10303-21;";
+/// let exchange = ruststep::parser::parse_bytes(latin1).unwrap();
+/// assert_eq!(
+///     exchange.data[0].entities[0].to_string(),
+///     r"#1 = B('M\X2\00FC\X0\ller');"
+/// );
+///
+/// // UTF-8 with BOM
+/// let utf8 = "\u{FEFF}ISO-10303-21; HEADER; A(); ENDSEC; DATA; #1 = B('Müller'); ENDSEC; END-ISO-10303-21;";
+/// assert_eq!(ruststep::parser::parse_bytes(utf8.as_bytes()).unwrap(), exchange);
+/// ```
+pub fn parse_bytes(bytes: &[u8]) -> Result<ast::Exchange> {
+    parse(&decode(bytes))
+}
+
+/// Decode bytes as UTF-8 without BOM, or ISO 8859-1 if invalid as UTF-8
+fn decode(bytes: &[u8]) -> Cow<str> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Cow::Borrowed(s),
+        // Every byte is a code point in ISO 8859-1
+        Err(_) => Cow::Owned(bytes.iter().map(|&b| char::from(b)).collect()),
+    }
+}
+
+/// Parse entire STEP file into [ast::borrowed::Exchange], borrowing keywords and strings from `input`
+///
+/// This avoids allocating a [String] for each keyword and string,
+/// and [ast::borrowed::Exchange::into_owned] returns the same AST as [parse].
+///
+pub fn parse_borrowed(input: &str) -> Result<ast::borrowed::Exchange<'_>> {
+    parse_borrowed_with(input, &ParseOptions::strict())
+}
+
+/// [parse_borrowed] with [ParseOptions]
+///
+/// Fails with [Error::UnsupportedOption] if [ParseOptions::normalize_fullwidth_punctuation]
+/// or [ParseOptions::recover_header] is set, since the input cannot be rewritten or skipped.
+pub fn parse_borrowed_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<ast::borrowed::Exchange<'a>> {
+    for (enabled, option) in [
+        (
+            options.normalize_fullwidth_punctuation,
+            "normalize_fullwidth_punctuation",
+        ),
+        (options.recover_header, "recover_header"),
+    ] {
+        if enabled {
+            return Err(Error::UnsupportedOption {
+                option,
+                parser: "parse_borrowed",
+            });
+        }
+    }
+    match borrowed::exchange_file_with(options).parse(input).finish() {
         Ok((_residual, ex)) => Ok(ex),
         Err(e) => Err(TokenizeFailed::new(input, e).into()),
     }
 }
+
+/// Parse entire STEP file with [ParseOptions]
+///
+/// ```
+/// use ruststep::parser::{parse_with, ParseOptions};
+///
+/// let step_str = r#"
+/// ISO-10303-21;
+/// HEADER;
+///   FILE_DESCRIPTION((''), '2;1');
+///   FILE_NAME('', '', (''), (''), '', '', '');
+///   FILE_SCHEMA(('TEST_SCHEMA'));
+/// ENDSEC;
+/// DATA;
+///   #1 = POINT(- 1.0, + 2.0);
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#.trim();
+///
+/// assert!(ruststep::parser::parse(step_str).is_err());
+/// assert!(parse_with(step_str, &ParseOptions::lenient()).is_ok());
+/// ```
+pub fn parse_with(input: &str, options: &ParseOptions) -> Result<ast::Exchange> {
+    parse_with_warnings(input, options).map(|(ex, _warnings)| ex)
+}
+
+/// Parse entire STEP file with [ParseOptions], and report non-conformance accepted by them
+///
+/// When a normalization, e.g. [ParseOptions::normalize_fullwidth_punctuation], is applied,
+/// the normalized input is tokenized, and thus errors show the normalized input.
+/// Byte offsets of errors are same as the original input, since normalization keeps them.
+///
+/// ```
+/// use ruststep::parser::{parse_with_warnings, ParseOptions, Warning};
+///
+/// let step_str = "ISO-10303-21；
+/// HEADER;
+///   FILE_DESCRIPTION(('（；）'), '2;1');
+/// ENDSEC;
+/// DATA;
+///   ＃1\u{3000}= POINT（1.0, 2.0）；
+/// ENDSEC;
+/// END-ISO-10303-21;";
+///
+/// assert!(ruststep::parser::parse(step_str).is_err());
+/// let (exchange, warnings) = parse_with_warnings(step_str, &ParseOptions::lenient()).unwrap();
+/// assert_eq!(warnings.len(), 6);
+/// assert!(matches!(warnings[0], Warning::FullwidthPunctuation { found: '；', .. }));
+/// // Strings are kept, and written with control directives
+/// assert_eq!(
+///     exchange.header[0].to_string(),
+///     r"FILE_DESCRIPTION(('\X2\FF08FF1BFF09\X0\'),'2;1')"
+/// );
+/// ```
+pub fn parse_with_warnings(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(ast::Exchange, Vec<Warning>)> {
+    let (input, mut warnings) = normalize::normalize(input, options);
+    let options = normalize::without_normalization(options);
+    let (exchange, recovered) = match exchange::exchange_file_with_warnings(&options)
+        .parse(&input)
+        .finish()
+    {
+        Ok((_residual, recovered)) => recovered,
+        Err(e) => return Err(TokenizeFailed::new(&input, e).into()),
+    };
+    warnings.extend(recovered);
+    Ok((exchange, warnings))
+}
+
+/// Parse a `DATA` section without other parts of exchange structure
+///
+/// Comments and spaces around the section are allowed.
+///
+/// ```
+/// let section = ruststep::parser::parse_data_section(r#"
+/// /* fragment stored in a database */
+/// DATA;
+///   #1 = A(1.0);
+///   #2 = B(#1);
+/// ENDSEC;
+/// "#).unwrap();
+/// assert_eq!(section.entities.len(), 2);
+/// ```
+pub fn parse_data_section(input: &str) -> Result<ast::DataSection> {
+    parse_data_section_with(input, &ParseOptions::strict())
+}
+
+/// [parse_data_section] with [ParseOptions]
+///
+/// [ParseOptions::normalize_fullwidth_punctuation] and [ParseOptions::recover_header] are not applied,
+/// since they are applied to entire exchange structures.
+pub fn parse_data_section_with(input: &str, options: &ParseOptions) -> Result<ast::DataSection> {
+    parse_fragment(input, exchange::data_section_with(options))
+}
+
+/// Parse entity instances without `DATA;` and `ENDSEC;`
+///
+/// ```
+/// let instances = ruststep::parser::parse_record_list("#1 = A(1.0); #2 = B(#1);").unwrap();
+/// assert_eq!(instances.len(), 2);
+/// assert_eq!(instances[1].to_string(), "#2 = B(#1);");
+///
+/// assert!(ruststep::parser::parse_record_list("").unwrap().is_empty());
+/// ```
+pub fn parse_record_list(input: &str) -> Result<Vec<ast::EntityInstance>> {
+    parse_record_list_with(input, &ParseOptions::strict())
+}
+
+/// [parse_record_list] with [ParseOptions], see [parse_data_section_with]
+pub fn parse_record_list_with(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<ast::EntityInstance>> {
+    parse_fragment(input, exchange::entity_instance_list_with(options))
+}
+
+/// Parse entire `input` by `f` allowing comments and spaces around it
+fn parse_fragment<'a, O>(input: &'a str, f: impl combinator::ExchangeParser<'a, O>) -> Result<O> {
+    let (residual, (_, value, _)) = tuple((ignorable, f, ignorable))
+        .parse(input)
+        .finish()
+        .map_err(|e| TokenizeFailed::new(input, e))?;
+    if !residual.is_empty() {
+        return Err(Error::ExtraInputRemaining(residual.to_string()));
+    }
+    Ok(value)
+}
+
+/// Parse entire STEP file with `options`, calling `on_instance` every time an entity instance is parsed
+///
+/// Parsing stops with [Error::Cancelled] when `on_instance` returns [ControlFlow::Break].
+pub(crate) fn parse_each(
+    input: &str,
+    options: &ParseOptions,
+    on_instance: impl FnMut(&ast::EntityInstance) -> ControlFlow<()>,
+) -> Result<ast::Exchange> {
+    let (input, _warnings) = normalize::normalize(input, options);
+    let options = normalize::without_normalization(options);
+    let on_instance = RefCell::new(on_instance);
+    let cancelled = Cell::new(false);
+    let instance = |input| {
+        let (rest, instances) = exchange::scoped_entity_instance_with(&options).parse(input)?;
+        for instance in &instances {
+            if (on_instance.borrow_mut())(instance).is_break() {
+                cancelled.set(true);
+                return Err(nom::Err::Failure(ParserError::context(input, "cancelled")));
+            }
+        }
+        Ok((rest, instances))
+    };
+    let result = exchange::exchange_file_by(&options, instance)
+        .parse(&input)
+        .finish();
+    match result {
+        Ok((_residual, (exchange, _warnings))) => Ok(exchange),
+        Err(_) if cancelled.get() => Err(Error::Cancelled),
+        Err(e) => Err(TokenizeFailed::new(&input, e).into()),
+    }
+}
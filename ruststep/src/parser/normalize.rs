@@ -0,0 +1,157 @@
+//! Normalize input before tokenization, see [ParseOptions::normalize_fullwidth_punctuation]
+
+use super::{combinator::ParseResult, ParseOptions};
+use std::borrow::Cow;
+
+/// Non-conformance of the input accepted by [ParseOptions]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// Full-width character `found` at byte offset `position` of the input
+    /// is replaced by its ASCII counterpart
+    FullwidthPunctuation { position: usize, found: char },
+    /// `HEADER` section is not found at byte offset `position`,
+    /// see [ParseOptions::recover_header]
+    MissingHeader { position: usize },
+    /// Header entity starting at byte offset `position` cannot be parsed, and is skipped.
+    /// See [ParseOptions::recover_header]
+    MalformedHeaderEntity { position: usize },
+}
+
+/// ASCII counterpart padded by spaces to the same length in UTF-8, i.e. 3 bytes,
+/// so that byte offsets in the normalized input are same as the original input.
+/// `#` is padded before it, since an instance name must follow it immediately.
+fn ascii_counterpart(c: char) -> Option<&'static str> {
+    match c {
+        '\u{3000}' => Some("   "),
+        '；' => Some(";  "),
+        '（' => Some("(  "),
+        '）' => Some(")  "),
+        '＃' => Some("  #"),
+        '＝' => Some("=  "),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scan {
+    Normal,
+    String,
+    Comment,
+}
+
+/// Apply normalizations enabled in `options` to `input`
+///
+/// Characters in strings and comments are kept as they are.
+/// The output has the same length as `input`, and characters not normalized are at the same byte offsets.
+/// Returns [Cow::Borrowed] if nothing is normalized.
+pub(crate) fn normalize<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> (Cow<'a, str>, Vec<Warning>) {
+    if !options.normalize_fullwidth_punctuation || input.is_ascii() {
+        return (Cow::Borrowed(input), Vec::new());
+    }
+    let mut output = String::with_capacity(input.len());
+    let mut warnings = Vec::new();
+    let mut state = Scan::Normal;
+    let mut chars = input.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        match (state, c) {
+            (Scan::Normal, '\'') => state = Scan::String,
+            (Scan::Normal, '/') if next == Some('*') => state = Scan::Comment,
+            (Scan::String, '\'') => state = Scan::Normal,
+            (Scan::Comment, '*') if next == Some('/') => {
+                output.push(c);
+                chars.next();
+                output.push('/');
+                state = Scan::Normal;
+                continue;
+            }
+            (Scan::Normal, c) => {
+                if let Some(ascii) = ascii_counterpart(c) {
+                    warnings.push(Warning::FullwidthPunctuation { position, found: c });
+                    output.push_str(ascii);
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        output.push(c);
+    }
+    if warnings.is_empty() {
+        (Cow::Borrowed(input), warnings)
+    } else {
+        (Cow::Owned(output), warnings)
+    }
+}
+
+/// Options for the input normalized by [normalize], not to normalize it again
+pub(super) fn without_normalization(options: &ParseOptions) -> ParseOptions {
+    ParseOptions {
+        normalize_fullwidth_punctuation: false,
+        ..*options
+    }
+}
+
+/// Run `f` on `input` normalized by `options`
+///
+/// Since normalization keeps byte offsets, the residual and errors of `f` are mapped back into `input`.
+/// `f` is called with the options without [ParseOptions::normalize_fullwidth_punctuation] not to normalize again.
+pub(crate) fn normalized<'a, O>(
+    options: &ParseOptions,
+    input: &'a str,
+    f: impl for<'b> FnOnce(&ParseOptions, &'b str) -> ParseResult<'b, O>,
+) -> ParseResult<'a, O> {
+    if !options.normalize_fullwidth_punctuation {
+        return f(options, input);
+    }
+    let (normalized, _warnings) = normalize(input, options);
+    match f(&without_normalization(options), &normalized) {
+        Ok((rest, value)) => {
+            // Skip the rest of padding if `f` stops in it
+            let mut offset = input.len() - rest.len();
+            while !input.is_char_boundary(offset) {
+                offset += 1;
+            }
+            Ok((&input[offset..], value))
+        }
+        Err(e) => Err(e.map(|e| e.rebase(input))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> ParseOptions {
+        ParseOptions {
+            normalize_fullwidth_punctuation: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn structural_only() {
+        let input = "＃1\u{3000}＝A（'；（）', /* ； */ ＃2）；";
+        let (output, warnings) = normalize(input, &enabled());
+        assert_eq!(output, "  #1   =  A(  '；（）', /* ； */   #2)  ;  ");
+        assert_eq!(output.len(), input.len());
+        assert_eq!(warnings.len(), 7);
+        assert_eq!(
+            warnings[0],
+            Warning::FullwidthPunctuation {
+                position: 0,
+                found: '＃'
+            }
+        );
+    }
+
+    #[test]
+    fn disabled() {
+        let input = "#1 = A(1)；";
+        let (output, warnings) = normalize(input, &ParseOptions::strict());
+        assert!(matches!(output, Cow::Borrowed(_)));
+        assert!(warnings.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! Options for parsing non-conforming exchange structures, and for tuning the parser
+//!
+//! Parsers depending on options have a variant with `_with` suffix taking [ParseOptions],
+//! e.g. [exchange_file_with](super::exchange::exchange_file_with) for [exchange_file](super::exchange::exchange_file).
+//! The variant without suffix follows ISO-10303-21 strictly, i.e. uses [ParseOptions::strict]:
+//!
+//! ```
+//! use nom::{Finish, Parser};
+//! use ruststep::{ast::Parameter, parser::{exchange, ParseOptions}};
+//!
+//! // Space between sign and digits is not allowed in ISO-10303-21
+//! assert!(exchange::parameter("- 5").finish().is_err());
+//!
+//! let (_, p) = exchange::parameter_with(&ParseOptions::lenient())
+//!     .parse("- 5")
+//!     .finish()
+//!     .unwrap();
+//! assert_eq!(p, Parameter::Integer(-5));
+//! ```
+//!
+//! Specialized parsers
+//! --------------------
+//! A few keywords, e.g. `CARTESIAN_POINT`, usually account for most records in geometry files.
+//! [ParseOptions::specialize_keywords] enables hand-written parsers for records of these keywords
+//! with a fixed shape, e.g. `CARTESIAN_POINT('', (0.0, 1.0, 2.0))`.
+//! Records of other shapes, e.g. with comments or escaped strings, fall back to the generic parser,
+//! and thus the result is always same as the generic parser.
+//! [crate::ast::SectionSummary::keyword_counts] tells which keywords are frequent in your files.
+
+use super::combinator::{ParseResult, ParserError};
+use crate::error::TokenError;
+use std::cell::Cell;
+
+/// Tolerances for exchange structures which do not conform ISO-10303-21
+///
+/// The default is strict, i.e. every tolerance is disabled, and no keyword is specialized.
+/// Nesting of parameters is limited to [ParseOptions::DEFAULT_MAX_DEPTH].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Allow spaces between a sign and digits of numbers, e.g. `- 5` or `1.0E- 3`
+    pub space_after_sign: bool,
+    /// Accept reals without digits before the decimal point, e.g. `.5` or `-.5E2`,
+    /// and reals without the decimal point but with an exponent, e.g. `5E-2`
+    pub loose_reals: bool,
+    /// Accept lower case letters in keywords and enumerations, e.g. `Cartesian_Point` or `.true.`,
+    /// and convert them into upper case
+    pub case_insensitive_keywords: bool,
+    /// Treat ideographic space U+3000 as a space, and full-width `；（）＃＝` as `;()#=`
+    /// outside strings and comments, see [parse_with_warnings](super::parse_with_warnings).
+    ///
+    /// This is applied by the parsers of entire exchange structures, e.g. [exchange_file](super::exchange::exchange_file).
+    /// [read_instances](super::read_instances) and [parse_borrowed](super::parse_borrowed) fail with
+    /// [Error::UnsupportedOption](crate::error::Error::UnsupportedOption) instead.
+    pub normalize_fullwidth_punctuation: bool,
+    /// Skip header entities which cannot be parsed, and accept a file without `HEADER` section,
+    /// to read `DATA` sections of a file with broken header.
+    /// Skipped parts are reported by [parse_with_warnings](super::parse_with_warnings).
+    ///
+    /// This is applied by the parsers of entire exchange structures, e.g. [exchange_file](super::exchange::exchange_file),
+    /// and [read_instances](super::read_instances),
+    /// while [parse_borrowed](super::parse_borrowed) fails with [Error::UnsupportedOption](crate::error::Error::UnsupportedOption).
+    pub recover_header: bool,
+    /// Keywords parsed by specialized parsers, see [ParseOptions::specialize_keywords]
+    pub specialized_keywords: SpecializedKeywords,
+    /// Maximum depth of nested parameters, e.g. `A((1))` has depth 2.
+    ///
+    /// Deeper input fails with [TokenError::DepthLimitExceeded] instead of overflowing the stack.
+    pub max_depth: usize,
+    /// Keep the text of reals in [Parameter::Real](crate::ast::Parameter::Real) to write them back as they are read,
+    /// e.g. `0.000000000001` instead of `1.E-12`
+    pub keep_real_text: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            space_after_sign: false,
+            loose_reals: false,
+            case_insensitive_keywords: false,
+            normalize_fullwidth_punctuation: false,
+            recover_header: false,
+            specialized_keywords: SpecializedKeywords::default(),
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            keep_real_text: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Default of [ParseOptions::max_depth]
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+    /// Follow ISO-10303-21 strictly
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Enable all tolerances
+    pub fn lenient() -> Self {
+        ParseOptions {
+            space_after_sign: true,
+            loose_reals: true,
+            case_insensitive_keywords: true,
+            normalize_fullwidth_punctuation: true,
+            recover_header: true,
+            ..Default::default()
+        }
+    }
+
+    /// Use specialized parsers for records of `keywords`
+    ///
+    /// Keywords without specialized parser, i.e. not in [SpecializedKeywords::SUPPORTED], are ignored.
+    ///
+    /// ```
+    /// use ruststep::parser::{ParseOptions, SpecializedKeywords};
+    ///
+    /// let options = ParseOptions::strict().specialize_keywords(&["CARTESIAN_POINT", "UNKNOWN"]);
+    /// assert!(options.specialized_keywords.contains("CARTESIAN_POINT"));
+    /// assert!(!options.specialized_keywords.contains("UNKNOWN"));
+    ///
+    /// let all = ParseOptions::strict().specialize_keywords(&SpecializedKeywords::SUPPORTED);
+    /// assert_eq!(all.specialized_keywords, SpecializedKeywords::all());
+    /// ```
+    pub fn specialize_keywords(mut self, keywords: &[&str]) -> Self {
+        for keyword in keywords {
+            self.specialized_keywords.insert(keyword);
+        }
+        self
+    }
+}
+
+/// Set of keywords which have specialized parsers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpecializedKeywords(u8);
+
+impl SpecializedKeywords {
+    /// Keywords which have specialized parsers
+    pub const SUPPORTED: [&'static str; 4] = [
+        "CARTESIAN_POINT",
+        "DIRECTION",
+        "VERTEX_POINT",
+        "ORIENTED_EDGE",
+    ];
+
+    /// Every keyword in [SpecializedKeywords::SUPPORTED]
+    pub fn all() -> Self {
+        SpecializedKeywords((1 << Self::SUPPORTED.len()) - 1)
+    }
+
+    fn bit(keyword: &str) -> Option<u8> {
+        Self::SUPPORTED
+            .iter()
+            .position(|supported| *supported == keyword)
+            .map(|index| 1 << index)
+    }
+
+    /// Add `keyword`. Returns `false` if it does not have a specialized parser.
+    pub fn insert(&mut self, keyword: &str) -> bool {
+        match Self::bit(keyword) {
+            Some(bit) => {
+                self.0 |= bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn contains(&self, keyword: &str) -> bool {
+        Self::bit(keyword).is_some_and(|bit| self.0 & bit != 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+thread_local! {
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Parse `input` by `f` one level deeper in nested parameters
+///
+/// Fails with [TokenError::DepthLimitExceeded] at `input` if [ParseOptions::max_depth] is exceeded.
+pub(crate) fn nested<'a, T>(
+    options: &ParseOptions,
+    input: &'a str,
+    f: impl FnOnce(&'a str) -> ParseResult<'a, T>,
+) -> ParseResult<'a, T> {
+    struct Leave;
+    impl Drop for Leave {
+        fn drop(&mut self) {
+            DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+    let depth = DEPTH.with(|depth| depth.get()) + 1;
+    if depth > options.max_depth {
+        // The offset is filled by `TokenizeFailed::new` where the whole input is known
+        let token = TokenError::DepthLimitExceeded { depth, offset: 0 };
+        return Err(nom::Err::Failure(ParserError::token(input, token)));
+    }
+    DEPTH.with(|current| current.set(depth));
+    let _leave = Leave;
+    f(input)
+}
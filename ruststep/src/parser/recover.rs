@@ -0,0 +1,46 @@
+//! Parse exchange structure with broken header, see [ParseOptions::recover_header](super::ParseOptions::recover_header)
+
+use super::{combinator::*, exchange, stream::Scanner, ParseOptions, Warning};
+use crate::ast;
+use nom::Parser;
+
+/// Same as [exchange::header_section], but header entities which cannot be parsed are skipped,
+/// and `HEADER` section may be missing.
+///
+/// `rest` is a suffix of `input` where `HEADER` section is expected,
+/// and positions of warnings are byte offsets in `input`.
+pub(crate) fn header_section<'a>(
+    options: &ParseOptions,
+    input: &str,
+    rest: &'a str,
+) -> ParseResult<'a, (Vec<ast::Record>, Vec<Warning>)> {
+    let position = |rest: &str| input.len() - rest.len();
+    let mut header = Vec::new();
+    let mut warnings = Vec::new();
+
+    let Ok((mut rest, _)) = tag_("HEADER;").parse(rest) else {
+        warnings.push(Warning::MissingHeader {
+            position: position(rest),
+        });
+        return Ok((rest, (header, warnings)));
+    };
+    loop {
+        (rest, _) = ignorable(rest)?;
+        if let Ok((next, _end)) = tag_("ENDSEC;").parse(rest) {
+            return Ok((next, (header, warnings)));
+        }
+        match exchange::header_entity_with(options).parse(rest) {
+            Ok((next, record)) => {
+                header.push(record);
+                rest = next;
+            }
+            Err(e) => {
+                let end = Scanner::default().feed(rest.as_bytes()).ok_or(e)?;
+                warnings.push(Warning::MalformedHeaderEntity {
+                    position: position(rest),
+                });
+                rest = &rest[end..];
+            }
+        }
+    }
+}
@@ -0,0 +1,586 @@
+//! Read entity instances one by one without loading the entire file
+
+use crate::{
+    ast::{EntityInstance, Record},
+    error::{Error, Result, TokenizeFailed},
+    header::Header,
+    parser::{combinator::ignorable, exchange, ParseOptions},
+    tables::{parse_instance, Holder},
+};
+use nom::{Finish, Parser};
+use serde::de;
+use std::io::{self, BufRead, Read};
+
+/// Read entity instances in `DATA` sections from `reader`
+///
+/// The input is read one statement, i.e. up to `;`, at a time,
+/// and thus the memory usage does not depend on the size of the input.
+/// Statements in other sections are skipped without parsing.
+/// `&SCOPE` blocks of the first edition are not supported, since they contain `;`.
+/// Statements are parsed strictly unless [ParseOptions] are given by [InstanceReader::options].
+///
+/// ```
+/// use ruststep::parser::read_instances;
+///
+/// let step = r#"
+/// ISO-10303-21;
+/// HEADER;
+/// FILE_DESCRIPTION(('a;b'), '2;1');
+/// ENDSEC;
+/// DATA;
+/// #1 = A(1.0, 'x;y');
+/// /* #2 = B(); */
+/// #3 = B(#1);
+/// ENDSEC;
+/// END-ISO-10303-21;
+/// "#;
+///
+/// let ids: Vec<u64> = read_instances(step.as_bytes())
+///     .map(|instance| instance.unwrap().id())
+///     .collect();
+/// assert_eq!(ids, vec![1, 3]);
+/// ```
+pub fn read_instances<R: BufRead>(reader: R) -> InstanceReader<R> {
+    InstanceReader {
+        reader,
+        buffer: Vec::new(),
+        position: 0,
+        offset: 0,
+        in_header: false,
+        header: Vec::new(),
+        in_data: false,
+        finished: false,
+        max_statement_bytes: usize::MAX,
+        options: ParseOptions::strict(),
+    }
+}
+
+/// Iterator created by [read_instances]
+#[derive(Debug)]
+pub struct InstanceReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Bytes consumed from `reader`
+    position: usize,
+    /// Byte offset of the statement in `buffer`
+    offset: usize,
+    in_header: bool,
+    header: Vec<Record>,
+    in_data: bool,
+    finished: bool,
+    max_statement_bytes: usize,
+    options: ParseOptions,
+}
+
+/// Statement read by [InstanceReader::next_statement]
+enum Statement {
+    /// End of input
+    End,
+    /// Statement outside DATA sections, which has been handled
+    Other,
+    /// Entity instance starting at this index of the buffer
+    Instance(usize),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scan {
+    Normal,
+    String,
+    Comment,
+}
+
+/// Find the end of statements across chunks of input
+#[derive(Clone, Copy)]
+pub(super) struct Scanner {
+    state: Scan,
+    prev: u8,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Scanner {
+            state: Scan::Normal,
+            prev: 0,
+        }
+    }
+}
+
+impl Scanner {
+    /// Returns the position after `;` terminating the statement, if found in `bytes`
+    pub(super) fn feed(&mut self, bytes: &[u8]) -> Option<usize> {
+        for (i, &b) in bytes.iter().enumerate() {
+            let mut prev = b;
+            match (self.state, b) {
+                (Scan::Normal, b';') => {
+                    self.prev = 0;
+                    return Some(i + 1);
+                }
+                (Scan::Normal, b'\'') => self.state = Scan::String,
+                (Scan::Normal, b'*') if self.prev == b'/' => {
+                    self.state = Scan::Comment;
+                    prev = 0;
+                }
+                (Scan::String, b'\'') => self.state = Scan::Normal,
+                (Scan::Comment, b'/') if self.prev == b'*' => {
+                    self.state = Scan::Normal;
+                    prev = 0;
+                }
+                _ => {}
+            }
+            self.prev = prev;
+        }
+        None
+    }
+}
+
+impl<R: BufRead> InstanceReader<R> {
+    /// Reject statements longer than `limit` bytes with [Error::StatementTooLarge]
+    ///
+    /// The rest of such a statement is skipped without buffering, and reading continues to the next statement.
+    /// The default is unlimited.
+    ///
+    /// ```
+    /// use ruststep::{error::Error, parser::read_instances};
+    ///
+    /// let step = "DATA; #1 = A(1.0); #2 = B('very long string'); #3 = C(); ENDSEC;";
+    /// let results: Vec<_> = read_instances(step.as_bytes()).max_statement_bytes(20).collect();
+    /// assert_eq!(results.len(), 3);
+    /// assert!(matches!(results[1], Err(Error::StatementTooLarge { limit: 20 })));
+    /// assert_eq!(results[2].as_ref().unwrap().id(), 3);
+    /// ```
+    pub fn max_statement_bytes(mut self, limit: usize) -> Self {
+        self.max_statement_bytes = limit;
+        self
+    }
+
+    /// Parse statements with `options`
+    ///
+    /// Header entities which cannot be parsed are skipped if [ParseOptions::recover_header] is set.
+    /// [ParseOptions::normalize_fullwidth_punctuation] is not supported,
+    /// since a full-width `；` does not end a statement,
+    /// and reading fails with [Error::UnsupportedOption] if it is set.
+    ///
+    /// ```
+    /// use ruststep::parser::{read_instances, ParseOptions};
+    ///
+    /// let step = "DATA; #1 = Cartesian_Point('', (.5, 1.0)); ENDSEC;";
+    /// assert!(read_instances(step.as_bytes()).next().unwrap().is_err());
+    ///
+    /// let options = ParseOptions {
+    ///     case_insensitive_keywords: true,
+    ///     loose_reals: true,
+    ///     ..Default::default()
+    /// };
+    /// let mut reader = read_instances(step.as_bytes()).options(&options);
+    /// assert_eq!(reader.next().unwrap().unwrap().id(), 1);
+    /// ```
+    pub fn options(mut self, options: &ParseOptions) -> Self {
+        self.options = *options;
+        self
+    }
+
+    /// Records in the HEADER section, checked by [Header::from_records]
+    ///
+    /// The input is read until the first DATA section begins,
+    /// so this can be called before iterating over entity instances.
+    ///
+    /// ```
+    /// use ruststep::parser::read_instances;
+    ///
+    /// let step = r#"
+    /// ISO-10303-21;
+    /// HEADER;
+    /// FILE_DESCRIPTION(('a'), '2;1');
+    /// FILE_NAME('a.step', '2024-01-01T00:00:00', (''), (''), '', '', '');
+    /// FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+    /// ENDSEC;
+    /// DATA;
+    /// #1 = A(1.0);
+    /// #2 = B(#1, );
+    /// ENDSEC;
+    /// END-ISO-10303-21;
+    /// "#;
+    ///
+    /// let mut reader = read_instances(step.as_bytes());
+    /// let header = reader.header().unwrap();
+    /// assert_eq!(header.file_schema.schema, ["CONFIG_CONTROL_DESIGN"]);
+    ///
+    /// assert_eq!(reader.next().unwrap().unwrap().id(), 1);
+    /// // Find the statement which cannot be parsed
+    /// assert!(reader.next().unwrap().is_err());
+    /// assert_eq!(reader.offset(), step.find("#2").unwrap());
+    /// ```
+    ///
+    /// Error
+    /// ------
+    /// - [Error::InvalidHeader] if the mandatory records are invalid
+    /// - [Error::TokenizeFailed] if a record in the HEADER section cannot be parsed
+    pub fn header(&mut self) -> Result<Header> {
+        while !self.in_data {
+            if let Statement::End = self.next_statement()? {
+                break;
+            }
+        }
+        Header::from_records(&self.header)
+    }
+
+    /// Byte offset in the input of the statement read last, excluding leading comments and spaces
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Read next statement into `self.buffer`. Returns `false` at the end of input.
+    fn read_statement(&mut self) -> Result<bool> {
+        if self.options.normalize_fullwidth_punctuation {
+            return Err(Error::UnsupportedOption {
+                option: "normalize_fullwidth_punctuation",
+                parser: "InstanceReader",
+            });
+        }
+        self.buffer.clear();
+        self.offset = self.position;
+        let mut scanner = Scanner::default();
+        loop {
+            let room = self.max_statement_bytes - self.buffer.len();
+            if room == 0 {
+                self.buffer.clear();
+                self.skip_statement(scanner)?;
+                return Err(Error::StatementTooLarge {
+                    limit: self.max_statement_bytes,
+                });
+            }
+            let start = self.buffer.len();
+            let read = (&mut self.reader)
+                .take(room as u64)
+                .read_until(b';', &mut self.buffer)?;
+            self.position += read;
+            if read == 0 {
+                return Ok(!self.buffer.iter().all(u8::is_ascii_whitespace));
+            }
+            if scanner.feed(&self.buffer[start..]).is_some() {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Consume input until the end of the current statement
+    fn skip_statement(&mut self, mut scanner: Scanner) -> io::Result<()> {
+        loop {
+            let bytes = self.reader.fill_buf()?;
+            if bytes.is_empty() {
+                return Ok(());
+            }
+            match scanner.feed(bytes) {
+                Some(end) => {
+                    self.reader.consume(end);
+                    self.position += end;
+                    return Ok(());
+                }
+                None => {
+                    let len = bytes.len();
+                    self.reader.consume(len);
+                    self.position += len;
+                }
+            }
+        }
+    }
+
+    /// Read next statement, and handle it if it is not an entity instance in DATA sections
+    fn next_statement(&mut self) -> Result<Statement> {
+        if !self.read_statement()? {
+            return Ok(Statement::End);
+        }
+        let statement = std::str::from_utf8(&self.buffer)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let (trimmed, _) = ignorable(statement)
+            .finish()
+            .map_err(|e| TokenizeFailed::new(statement, e).shift(self.offset))?;
+        let start = statement.len() - trimmed.len();
+        self.offset += start;
+        if self.in_data {
+            if is_section(trimmed, "ENDSEC") {
+                self.in_data = false;
+                return Ok(Statement::Other);
+            }
+            return Ok(Statement::Instance(start));
+        }
+        if self.in_header {
+            if is_section(trimmed, "ENDSEC") {
+                self.in_header = false;
+            } else {
+                match exchange::header_entity_with(&self.options)
+                    .parse(trimmed)
+                    .finish()
+                {
+                    Ok((_, record)) => self.header.push(record),
+                    // Skip the statement as [super::recover::header_section]
+                    Err(_) if self.options.recover_header => {}
+                    Err(e) => {
+                        return Err(TokenizeFailed::new(trimmed, e).shift(self.offset).into())
+                    }
+                }
+            }
+        } else {
+            self.in_header = is_section(trimmed, "HEADER");
+            self.in_data = is_section(trimmed, "DATA");
+        }
+        Ok(Statement::Other)
+    }
+
+    /// Parse next entity instance whose statement satisfies `filter`
+    ///
+    /// `filter` is applied to the statement before parsing, e.g. `#1 = A(1.0);`.
+    /// Errors of parsing are reported with the id of the instance if it can be read.
+    fn next_instance_filtered(
+        &mut self,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Option<EntityInstance>> {
+        loop {
+            let start = match self.next_statement()? {
+                Statement::End => return Ok(None),
+                Statement::Other => continue,
+                Statement::Instance(start) => start,
+            };
+            // `next_statement` has checked that the buffer is UTF-8
+            let statement = std::str::from_utf8(&self.buffer[start..]).unwrap();
+            if !filter(statement) {
+                continue;
+            }
+            return parse_statement(&self.options, statement, self.offset)
+                .map(Some)
+                .map_err(|e| match raw_id(statement) {
+                    Some(id) => Error::InstanceFailed {
+                        id,
+                        source: Box::new(e),
+                    },
+                    None => e,
+                });
+        }
+    }
+}
+
+/// Whether `statement` starts a section, e.g. `DATA;` or `DATA('name', ('SCHEMA'));` for `DATA`
+fn is_section(statement: &str, keyword: &str) -> bool {
+    match statement.strip_prefix(keyword) {
+        Some(rest) => matches!(rest.trim_start().chars().next(), Some('(' | ';')),
+        None => false,
+    }
+}
+
+/// Common part of [Iterator::next] of streams. Iteration ends after an IO error or an unsupported option.
+fn into_item<T>(finished: &mut bool, result: Result<Option<T>>) -> Option<Result<T>> {
+    match result {
+        Ok(Some(value)) => Some(Ok(value)),
+        Ok(None) => {
+            *finished = true;
+            None
+        }
+        Err(e @ (Error::Io(_) | Error::UnsupportedOption { .. })) => {
+            *finished = true;
+            Some(Err(e))
+        }
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// Parse the statement starting at `offset` of the input
+fn parse_statement(
+    options: &ParseOptions,
+    statement: &str,
+    offset: usize,
+) -> Result<EntityInstance> {
+    let failed = |e| TokenizeFailed::new(statement, e).shift(offset);
+    let (residual, instance) = exchange::entity_instance_with(options)
+        .parse(statement)
+        .finish()
+        .map_err(failed)?;
+    let (residual, _) = ignorable(residual).finish().map_err(failed)?;
+    if !residual.is_empty() {
+        return Err(Error::ExtraInputRemaining(residual.to_string()));
+    }
+    Ok(instance)
+}
+
+/// Id of `#12 = ...` without parsing the rest
+fn raw_id(statement: &str) -> Option<u64> {
+    let digits = statement.strip_prefix('#')?;
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+/// Keyword of a simple entity instance `#12 = KEYWORD(...)` without parsing the rest.
+///
+/// `None` if it cannot be read cheaply, e.g. for complex entity instances.
+fn raw_keyword(statement: &str) -> Option<&str> {
+    let (_id, rest) = statement.split_once('=')?;
+    let rest = rest.trim_start();
+    let rest = rest.strip_prefix('!').unwrap_or(rest);
+    let end = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+    if end == 0 || !rest[end..].trim_start().starts_with('(') {
+        return None;
+    }
+    Some(&rest[..end])
+}
+
+impl<R: BufRead> Iterator for InstanceReader<R> {
+    type Item = Result<EntityInstance>;
+
+    /// Returns an error for a statement which cannot be parsed, and continues to the next statement.
+    /// Iteration ends after an IO error or [Error::UnsupportedOption].
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.next_instance_filtered(&|_| true);
+        into_item(&mut self.finished, result)
+    }
+}
+
+type Convert<E> = Box<dyn Fn(&EntityInstance) -> Result<E>>;
+
+/// Stream of entity instances converted into one of registered holder types
+///
+/// Each registered holder type is converted into the enum `E` by the function given to [DispatchStream::register].
+/// Statements whose keyword does not match to any registered type are skipped before parsing parameters.
+/// For complex entity instances, the first registered type found in their partial records is used,
+/// and instances without registered types are skipped.
+///
+/// For holders generated by espr, e.g. `CartesianPointHolder` and `VertexPointHolder`:
+///
+/// ```text
+/// enum Geometry {
+///     Point(CartesianPointHolder),
+///     Vertex(VertexPointHolder),
+/// }
+///
+/// let stream = DispatchStream::new(read_instances(reader))
+///     .register(Geometry::Point)
+///     .register(Geometry::Vertex);
+/// for item in stream {
+///     let (id, geometry) = item?;
+/// }
+/// ```
+pub struct DispatchStream<E, R> {
+    reader: InstanceReader<R>,
+    types: Vec<(&'static str, Convert<E>)>,
+}
+
+impl<E, R: BufRead> DispatchStream<E, R> {
+    /// Stream without registered types, which yields nothing
+    pub fn new(reader: InstanceReader<R>) -> Self {
+        DispatchStream {
+            reader,
+            types: Vec::new(),
+        }
+    }
+
+    /// Register holder type `T` and its conversion into `E`
+    pub fn register<T>(mut self, f: impl Fn(T) -> E + 'static) -> Self
+    where
+        T: Holder + de::DeserializeOwned,
+    {
+        self.types.push((
+            T::name(),
+            Box::new(move |instance| parse_instance::<T>(instance).map(&f)),
+        ));
+        self
+    }
+
+    fn next_item(&mut self) -> Result<Option<(u64, E)>> {
+        let types = &self.types;
+        let filter = |statement: &str| match raw_keyword(statement) {
+            Some(keyword) => types
+                .iter()
+                .any(|(name, _)| keyword.eq_ignore_ascii_case(name)),
+            None => true,
+        };
+        while let Some(instance) = self.reader.next_instance_filtered(&filter)? {
+            let names: Vec<&str> = instance.records().iter().map(|r| r.name.as_str()).collect();
+            let convert = types.iter().find(|(name, _)| {
+                names
+                    .iter()
+                    .any(|keyword| keyword.eq_ignore_ascii_case(name))
+            });
+            if let Some((_, convert)) = convert {
+                let id = instance.id();
+                return convert(&instance)
+                    .map(|item| Some((id, item)))
+                    .map_err(|e| Error::InstanceFailed {
+                        id,
+                        source: Box::new(e),
+                    });
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<E, R: BufRead> Iterator for DispatchStream<E, R> {
+    type Item = Result<(u64, E)>;
+
+    /// Returns an error with the id of instance for a statement which cannot be parsed or converted,
+    /// and continues to the next statement.
+    /// Iteration ends after an IO error or [Error::UnsupportedOption].
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.finished {
+            return None;
+        }
+        let result = self.next_item();
+        into_item(&mut self.reader.finished, result)
+    }
+}
+
+/// Stream of holders of type `T` read from [InstanceReader]
+///
+/// This is [DispatchStream] with single type `T`.
+/// Use `TypedStream::<CartesianPointHolder, _>::new(read_instances(reader))`
+/// to read only `CARTESIAN_POINT`s with their ids.
+pub struct TypedStream<T, R>(DispatchStream<T, R>);
+
+impl<T, R> TypedStream<T, R>
+where
+    T: Holder + de::DeserializeOwned,
+    R: BufRead,
+{
+    pub fn new(reader: InstanceReader<R>) -> Self {
+        TypedStream(DispatchStream::new(reader).register(|x: T| x))
+    }
+}
+
+impl<T, R: BufRead> Iterator for TypedStream<T, R> {
+    type Item = Result<(u64, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_as_parse() {
+        let step = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step"
+        ))
+        .unwrap();
+        let exchange = crate::parser::parse(&step).unwrap();
+        let instances: Vec<_> = read_instances(step.as_bytes())
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(instances, exchange.data[0].entities);
+    }
+
+    #[test]
+    fn continue_after_error() {
+        let step = "DATA; #1 = A(1); #2 = ; #3 = B(); ENDSEC;";
+        let results: Vec<_> = read_instances(step.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap().id(), 3);
+    }
+}
@@ -2,82 +2,302 @@
 
 use crate::{
     ast::*,
-    parser::{basic::*, combinator::*},
+    error::TokenError,
+    parser::{basic::*, combinator::*, iso8859, ParseOptions},
+    primitive::Binary,
 };
 use nom::{
     branch::alt,
-    character::complete::{char, digit0, digit1, multispace0, none_of, satisfy},
-    combinator::opt,
+    bytes::complete::tag,
+    character::complete::{char, digit0, digit1, multispace0, multispace1, none_of, satisfy},
+    combinator::{not, opt, recognize},
+    error::{ErrorKind, ParseError},
     multi::{many0, many1},
     sequence::tuple,
     Parser,
 };
-use nom::bytes::complete::tag;
-use nom::combinator::map;
+use std::{borrow::Cow, str::FromStr};
 
 /// sign = `+` | `-` .
 pub fn sign(input: &str) -> ParseResult<char> {
     alt((char('+'), char('-'))).parse(input)
 }
 
+// Spaces between sign and digits, allowed only if `ParseOptions::space_after_sign` is set
+fn space_after_sign<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, ()> {
+    let allowed = options.space_after_sign;
+    move |input: &'a str| {
+        if allowed {
+            multispace0.map(|_| ()).parse(input)
+        } else {
+            Ok((input, ()))
+        }
+    }
+}
+
 /// integer = \[ [sign] \] [digit] { [digit] } .
+///
+/// Leading zeros are ignored, e.g. `+05` is `5`.
+///
+/// Error
+/// -------
+/// - Failure with [TokenError::IntegerOverflow] if the input cannot be represented by `i64`
+///
 pub fn integer(input: &str) -> ParseResult<i64> {
-    tuple((opt(sign), multispace0, digit1))
-        .map(|(sign, _space, numbers)| {
-            let num: i64 = numbers.parse().expect("Failed to parse into integer");
-            match sign {
-                Some('-') => -num,
-                _ => num,
-            }
-        })
-        .parse(input)
+    integer_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [integer] with [ParseOptions::space_after_sign]
+pub fn integer_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, i64> {
+    let options = *options;
+    move |input: &'a str| {
+        let (residual, (sign, _space, digits)) =
+            tuple((opt(sign), space_after_sign(&options), digit1)).parse(input)?;
+        // Parse with the sign to accept `i64::MIN`
+        let num = match sign {
+            Some('-') => format!("-{}", digits).parse(),
+            _ => digits.parse(),
+        };
+        match num {
+            Ok(num) => Ok((residual, num)),
+            Err(_) => Err(overflow(
+                input,
+                TokenError::IntegerOverflow {
+                    text: input[..input.len() - residual.len()].to_string(),
+                },
+            )),
+        }
+    }
 }
 
 /// `E` \[ [sign] \] [digit] { [digit] } .
-fn exponent(input: &str) -> ParseResult<i64> {
-    tuple((char('E'), multispace0, opt(sign), multispace0, digit1))
-        .map(|(_e, _sp1, sign, _sp2, digit)| {
-            let num: i64 = digit.parse().expect("Failed to parse integer in exponent");
-            match sign {
-                Some('-') => -num,
-                _ => num,
-            }
-        })
-        .parse(input)
+fn exponent<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, (Option<char>, &'a str)> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('E'), opt(sign), space_after_sign(&options), digit1))
+            .map(|(_e, sign, _space, digits)| (sign, digits))
+            .parse(input)
+    }
 }
 
 /// real = \[ [sign] \] [digit] { [digit] } `.` { [digit] } \[ `E` \[ [sign] \] [digit] { [digit] } \] .
+///
+/// Leading zeros are ignored, e.g. `+0.500` is `0.5`.
 pub fn real(input: &str) -> ParseResult<f64> {
-    tuple((
-        opt(sign),
-        multispace0,
-        digit1,
-        char('.'),
-        digit0,
-        opt(exponent),
-    ))
-    .map(|(sign, _space, integral, _point, fractional, exp)| {
-        let num: f64 = format!("{}.{}e{}", integral, fractional, exp.unwrap_or(0))
-            .parse()
-            .expect("Failed to parse Float");
-        match sign {
-            Some('-') => -num,
-            _ => num,
+    real_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [real] with [ParseOptions::space_after_sign] and [ParseOptions::loose_reals]
+///
+/// If `loose_reals` is set, reals without digits before `.`, e.g. `.5`,
+/// and without `.` but with an exponent, e.g. `5E-2`, are also accepted.
+pub fn real_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, f64> {
+    let options = *options;
+    move |input: &'a str| {
+        let (residual, (sign, _space, integral, point, fractional, exp)) = tuple((
+            opt(sign),
+            space_after_sign(&options),
+            digit0,
+            opt(char('.')),
+            digit0,
+            opt(exponent(&options)),
+        ))
+        .parse(input)?;
+        let valid = match (point, exp) {
+            (Some(_), _) if !integral.is_empty() => true,
+            (Some(_), _) => options.loose_reals && !fractional.is_empty(),
+            (None, Some(_)) => options.loose_reals && !integral.is_empty(),
+            (None, None) => false,
+        };
+        if !valid {
+            return Err(nom::Err::Error(ParserError::from_error_kind(
+                input,
+                ErrorKind::Float,
+            )));
         }
-    })
-    .parse(input)
+        let (exp_sign, exp) = exp.unwrap_or((None, "0"));
+        let value = format!(
+            "{}0{}.{}e{}{}",
+            sign.unwrap_or('+'),
+            integral,
+            fractional,
+            exp_sign.unwrap_or('+'),
+            exp
+        )
+        .parse()
+        .expect("Failed to parse Float");
+        Ok((residual, value))
+    }
 }
 
 /// string = `'` { [special] | [digit] | [space] | [lower] | [upper] | high_codepoint | [apostrophe] [apostrophe] | [reverse_solidus] [reverse_solidus] | control_directive } `'` .
+///
+/// Line breaks in a string are not a part of the string, since writers may wrap long lines in strings.
+/// `\X2\` and `\X4\` control directives are decoded into Unicode characters,
+/// e.g. `'caf\X2\00E9\X0\'` into `café`, see [decode_string].
 pub fn string(input: &str) -> ParseResult<String> {
-    let escaped_char = map(tag("''"), |_| '\''); // Parse '' as a single '
-    let normal_char = none_of("'"); // Parse any character except '
+    let (rest, content) = string_literal(input)?;
+    let s = decode_string(content)?;
+    Ok((rest, s.into_owned()))
+}
 
-    let string_content = many0(escaped_char.or(normal_char.map(|c| c)));
+/// Content between apostrophes of a string, i.e. `''` is not decoded yet
+pub(crate) fn string_literal(input: &str) -> ParseResult<&str> {
+    let (content, _start) = char('\'')(input)?;
+    let mut end = 0;
+    loop {
+        match content[end..].find('\'') {
+            Some(pos) if content[end + pos + 1..].starts_with('\'') => end += pos + 2,
+            Some(pos) => {
+                end += pos;
+                break;
+            }
+            None => {
+                let rest = &content[content.len()..];
+                return Err(nom::Err::Error(ParserError::from_error_kind(
+                    rest,
+                    ErrorKind::Char,
+                )));
+            }
+        }
+    }
+    Ok((&content[end + 1..], &content[..end]))
+}
 
-    tuple((char('\''), string_content, char('\'')))
-        .map(|(_start, s, _end)| s.iter().collect())
-        .parse(input)
+/// Decode the content of a string
+///
+/// - `''` into `'`
+/// - `\\` into `\`
+/// - Line breaks are removed, also in directives
+/// - `\X\` followed by two hex digits of ISO 8859-1, e.g. `\X\A9` into `©`
+/// - `\X2\` followed by hex digits of UCS-2 code points, e.g. `\X2\00E9\X0\` into `é`
+/// - `\X4\` followed by hex digits of UCS-4 code points, e.g. `\X4\0001F600\X0\` into `😀`
+/// - `\S\c` into the character at `c + 0x80` in the part of ISO 8859 selected by the last `\PA\` .. `\PI\`,
+///   or ISO 8859-1 if no page directive appears before it in the string, e.g. `\PE\\S\?` into `П`
+/// - Other backslashes are kept as they are, e.g. `C:\Parts\a.stp` of a Windows path
+///
+/// Returns [Cow::Borrowed] if nothing is decoded.
+///
+/// Errors
+/// -------
+/// - Failure if the hex digits are not a multiple of 4 for `\X2\` or 8 for `\X4\`,
+///   do not represent Unicode characters, or the closing `\X0\` is missing.
+/// - Failure if `\X\` is not followed by two hex digits.
+/// - Failure if `\S\` is not followed by a character assigned in the current part.
+///
+pub(crate) fn decode_string(content: &str) -> Result<Cow<str>, nom::Err<ParserError>> {
+    if !content.contains(['\'', '\r', '\n', '\\']) {
+        return Ok(Cow::Borrowed(content));
+    }
+    let mut decoded = String::with_capacity(content.len());
+    let mut rest = content;
+    // Part of ISO 8859 for `\S\`
+    let mut page = 1;
+    while let Some(c) = rest.chars().next() {
+        if rest.starts_with("''") {
+            decoded.push('\'');
+            rest = &rest[2..];
+        } else if rest.starts_with("\\\\") {
+            decoded.push('\\');
+            rest = &rest[2..];
+        } else if rest.starts_with("\\X2\\") || rest.starts_with("\\X4\\") {
+            let (width, context) = if rest.starts_with("\\X2\\") {
+                (4, "\\X2\\ directive must be followed by 4 hex digits for each character and \\X0\\")
+            } else {
+                (8, "\\X4\\ directive must be followed by 8 hex digits for each character and \\X0\\")
+            };
+            let failure = || failure(rest, context);
+            let end = rest.find("\\X0\\").ok_or_else(failure)?;
+            // Line breaks may be inserted in long directives
+            let hex: String = rest[4..end]
+                .chars()
+                .filter(|c| !matches!(c, '\r' | '\n'))
+                .collect();
+            if hex.len() % width != 0 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(failure());
+            }
+            for i in (0..hex.len()).step_by(width) {
+                let code = u32::from_str_radix(&hex[i..i + width], 16).map_err(|_| failure())?;
+                decoded.push(char::from_u32(code).ok_or_else(failure)?);
+            }
+            rest = &rest[end + 4..];
+        } else if let Some(after) = rest.strip_prefix("\\X\\") {
+            let byte = after
+                .get(..2)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| {
+                    failure(rest, "\\X\\ directive must be followed by two hex digits")
+                })?;
+            decoded.push(char::from(byte));
+            rest = &after[2..];
+        } else if let Some(after) = rest.strip_prefix("\\S\\") {
+            let failure = || {
+                failure(
+                    rest,
+                    "\\S\\ directive must be followed by a character assigned in the current part of ISO 8859",
+                )
+            };
+            let (c, len) = if after.starts_with("''") {
+                ('\'', 2)
+            } else {
+                match after.chars().next() {
+                    Some(c @ ' '..='~') => (c, 1),
+                    _ => return Err(failure()),
+                }
+            };
+            decoded.push(iso8859::decode(page, c as u8 + 0x80).ok_or_else(failure)?);
+            rest = &after[len..];
+        } else if let Some(part) = page_directive(rest) {
+            page = part;
+            rest = &rest[4..];
+        } else {
+            if !matches!(c, '\r' | '\n') {
+                decoded.push(c);
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    Ok(Cow::Owned(decoded))
+}
+
+/// Part of ISO 8859 selected by a page directive `\PA\` .. `\PI\` at the head of `input`
+fn page_directive(input: &str) -> Option<u8> {
+    match input.as_bytes() {
+        [b'\\', b'P', part @ b'A'..=b'I', b'\\', ..] => Some(part - b'A' + 1),
+        _ => None,
+    }
+}
+
+fn failure<'a>(input: &'a str, context: &'static str) -> nom::Err<ParserError<'a>> {
+    nom::Err::Failure(ParserError::context(input, context))
+}
+
+/// binary = `"` ( `0` | `1` | `2` | `3` ) { [hex] } `"` .
+///
+/// The leading digit is the number of unused high bits in the first [hex] digit,
+/// e.g. `"23FF"` is 10 bits `11 1111 1111`, see [Binary].
+/// An empty binary `""` is also accepted as zero bits.
+///
+/// Errors
+/// -------
+/// - Failure if the leading digit is not `0` while no [hex] digits follow
+///
+pub fn binary(input: &str) -> ParseResult<Binary> {
+    let (residual, content) = tuple((
+        char('"'),
+        recognize(opt(tuple((
+            satisfy(|c| matches!(c, '0'..='3')),
+            many0(hex),
+        )))),
+        char('"'),
+    ))
+    .map(|(_open, content, _close)| content)
+    .parse(input)?;
+    match Binary::from_str(content) {
+        Ok(binary) => Ok((residual, binary)),
+        Err(_) => Err(failure(input, "binary-padding")),
+    }
 }
 
 /// resource = `<` UNIVERSAL_RESOURCE_IDENTIFIER `>` .
@@ -91,18 +311,29 @@ pub fn resource(input: &str) -> ParseResult<URI> {
 
 /// enumeration = `.` [upper] { [upper] | [digit] } `.` .
 pub fn enumeration(input: &str) -> ParseResult<String> {
-    tuple((char('.'), standard_keyword, char('.')))
-        .map(|(_head, name, _tail)| name)
-        .parse(input)
+    enumeration_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [enumeration] with [ParseOptions::case_insensitive_keywords]
+pub fn enumeration_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('.'), standard_keyword_with(&options), char('.')))
+            .map(|(_head, name, _tail)| name)
+            .parse(input)
+    }
 }
 
-// Root error for u64 overflow
-//
-// FIXME Though it works, should we use `VerboseErrorKind::Context` for this usage?
-fn u64_overflow(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
-    nom::Err::Failure(nom::error::VerboseError {
-        errors: vec![(input, nom::error::VerboseErrorKind::Context("u64-overflow"))],
-    })
+// Root error for overflow of the token at the head of `input`,
+// which is reported through [crate::error::TokenizeFailed::token_error]
+fn overflow(input: &str, token: TokenError) -> nom::Err<ParserError> {
+    nom::Err::Failure(ParserError::token(input, token))
+}
+
+// Instance name `input` up to `residual` overflowing `u64`
+fn id_overflow<'a>(input: &'a str, residual: &'a str) -> nom::Err<ParserError<'a>> {
+    let text = input[..input.len() - residual.len()].to_string();
+    overflow(input, TokenError::IdOverflow { text })
 }
 
 /// entity_instance_name = `#` ( [digit] ) { [digit] } .
@@ -115,16 +346,16 @@ fn u64_overflow(input: &str) -> nom::Err<nom::error::VerboseError<&str>> {
 ///
 /// Error
 /// -------
-/// - FIXME: If the input cannot be represented by `u64`, i.e. larger than [std::u64::MAX]
+/// - Failure with [TokenError::IdOverflow] if the input cannot be represented by `u64`,
+///   i.e. larger than [u64::MAX]
 ///
 pub fn entity_instance_name(input: &str) -> ParseResult<u64> {
-    let (input, name) = tuple((char('#'), digit1))
+    let (residual, name) = tuple((char('#'), digit1))
         .map(|(_sharp, name): (_, &str)| name.parse())
         .parse(input)?;
-    if let Ok(name) = name {
-        Ok((input, name))
-    } else {
-        Err(u64_overflow(input))
+    match name {
+        Ok(name) => Ok((residual, name)),
+        Err(_) => Err(id_overflow(input, residual)),
     }
 }
 
@@ -134,31 +365,47 @@ pub fn entity_instance_name(input: &str) -> ParseResult<u64> {
 ///
 /// Error
 /// -------
-/// - FIXME: If the input cannot be represented by `u64`, i.e. larger than [std::u64::MAX]
+/// - Failure with [TokenError::IdOverflow] if the input cannot be represented by `u64`,
+///   i.e. larger than [u64::MAX]
 ///
 pub fn value_instance_name(input: &str) -> ParseResult<u64> {
-    let (input, name) = tuple((char('@'), digit1))
+    let (residual, name) = tuple((char('@'), digit1))
         .map(|(_sharp, name): (_, &str)| name.parse())
         .parse(input)?;
-    if let Ok(name) = name {
-        Ok((input, name))
-    } else {
-        Err(u64_overflow(input))
+    match name {
+        Ok(name) => Ok((residual, name)),
+        Err(_) => Err(id_overflow(input, residual)),
     }
 }
 
 /// constant_entity_name = `#` ( [upper] ) { [upper] | [digit] } .
 pub fn constant_entity_name(input: &str) -> ParseResult<String> {
-    tuple((char('#'), standard_keyword))
-        .map(|(_sharp, name)| name)
-        .parse(input)
+    constant_entity_name_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [constant_entity_name] with [ParseOptions::case_insensitive_keywords]
+pub fn constant_entity_name_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('#'), standard_keyword_with(&options)))
+            .map(|(_sharp, name)| name)
+            .parse(input)
+    }
 }
 
 /// constant_value_name = `@` ( [upper] ) { [upper] | [digit] } .
 pub fn constant_value_name(input: &str) -> ParseResult<String> {
-    tuple((char('@'), standard_keyword))
-        .map(|(_sharp, name)| name)
-        .parse(input)
+    constant_value_name_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [constant_value_name] with [ParseOptions::case_insensitive_keywords]
+pub fn constant_value_name_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('@'), standard_keyword_with(&options)))
+            .map(|(_sharp, name)| name)
+            .parse(input)
+    }
 }
 
 /// lhs_occurrence_name = ( [entity_instance_name] | [value_instance_name] ) .
@@ -172,13 +419,21 @@ pub fn lhs_occurrence_name(input: &str) -> ParseResult<Name> {
 
 /// rhs_occurrence_name = ( [entity_instance_name] | [value_instance_name] | [constant_entity_name] | [constant_value_name]) .
 pub fn rhs_occurrence_name(input: &str) -> ParseResult<Name> {
-    alt((
-        entity_instance_name.map(Name::Entity),
-        value_instance_name.map(Name::Value),
-        constant_entity_name.map(Name::ConstantEntity),
-        constant_value_name.map(Name::ConstantValue),
-    ))
-    .parse(input)
+    rhs_occurrence_name_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [rhs_occurrence_name] with [ParseOptions::case_insensitive_keywords]
+pub fn rhs_occurrence_name_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, Name> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            entity_instance_name.map(Name::Entity),
+            value_instance_name.map(Name::Value),
+            constant_entity_name_with(&options).map(Name::ConstantEntity),
+            constant_value_name_with(&options).map(Name::ConstantValue),
+        ))
+        .parse(input)
+    }
 }
 
 /// anchor_name = `<` URI_FRAGMENT_IDENTIFIER `>` .
@@ -192,24 +447,90 @@ pub fn anchor_name(input: &str) -> ParseResult<String> {
 
 /// keyword = [user_defined_keyword] | [standard_keyword] .
 pub fn keyword(input: &str) -> ParseResult<String> {
-    alt((user_defined_keyword, standard_keyword)).parse(input)
+    keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [keyword] with [ParseOptions::case_insensitive_keywords]
+pub fn keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            user_defined_keyword_with(&options),
+            standard_keyword_with(&options),
+        ))
+        .parse(input)
+    }
+}
+
+/// [keyword] with whether it is [user_defined_keyword]
+///
+/// ```
+/// use nom::Finish;
+/// use ruststep::parser::token::marked_keyword;
+///
+/// assert_eq!(marked_keyword("!MY_EXTENSION").finish().unwrap().1, ("MY_EXTENSION".to_string(), true));
+/// assert_eq!(marked_keyword("POINT").finish().unwrap().1, ("POINT".to_string(), false));
+/// ```
+pub fn marked_keyword(input: &str) -> ParseResult<(String, bool)> {
+    marked_keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [marked_keyword] with [ParseOptions::case_insensitive_keywords]
+pub fn marked_keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, (String, bool)> {
+    let options = *options;
+    move |input: &'a str| {
+        alt((
+            user_defined_keyword_with(&options).map(|name| (name, true)),
+            standard_keyword_with(&options).map(|name| (name, false)),
+        ))
+        .parse(input)
+    }
 }
 
 /// standard_keyword = [upper] { [upper] | [digit] } .
 pub fn standard_keyword(input: &str) -> ParseResult<String> {
-    tuple((upper, many0(alt((upper, digit)))))
-        .map(|(first, tail)| {
-            let head = &[first];
-            head.iter().chain(tail.iter()).collect()
-        })
-        .parse(input)
+    standard_keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [standard_keyword] with [ParseOptions::case_insensitive_keywords]
+///
+/// If `case_insensitive_keywords` is set, [lower] is also accepted
+/// and converted into upper case, e.g. `Cartesian_Point` into `CARTESIAN_POINT`.
+pub fn standard_keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let case_insensitive = options.case_insensitive_keywords;
+    move |input: &'a str| {
+        if case_insensitive {
+            return tuple((alt((upper, lower)), many0(alt((upper, lower, digit)))))
+                .map(|(first, tail)| {
+                    std::iter::once(first)
+                        .chain(tail)
+                        .map(|c| c.to_ascii_uppercase())
+                        .collect()
+                })
+                .parse(input);
+        }
+        tuple((upper, many0(alt((upper, digit)))))
+            .map(|(first, tail)| {
+                let head = &[first];
+                head.iter().chain(tail.iter()).collect()
+            })
+            .parse(input)
+    }
 }
 
 /// user_defined_keyword = `!` [upper] { [upper] | [digit] } .
 pub fn user_defined_keyword(input: &str) -> ParseResult<String> {
-    tuple((char('!'), standard_keyword))
-        .map(|(_e, name)| name)
-        .parse(input)
+    user_defined_keyword_with(&ParseOptions::strict()).parse(input)
+}
+
+/// [user_defined_keyword] with [ParseOptions::case_insensitive_keywords]
+pub fn user_defined_keyword_with<'a>(options: &ParseOptions) -> impl ExchangeParser<'a, String> {
+    let options = *options;
+    move |input: &'a str| {
+        tuple((char('!'), standard_keyword_with(&options)))
+            .map(|(_e, name)| name)
+            .parse(input)
+    }
 }
 
 /// tag_name = ( [upper] | [lower] ) { [upper] | [lower] | [digit] } .
@@ -223,16 +544,31 @@ pub fn tag_name(input: &str) -> ParseResult<String> {
 }
 
 /// signature_content = BASE64 .
+///
+/// Signatures are usually wrapped, e.g. at 64 columns,
+/// and spaces and line breaks between the base64 characters are removed.
+/// The spaces before `ENDSEC;` are not consumed.
 pub fn signature_content(input: &str) -> ParseResult<String> {
-    let base_char = satisfy(|c| matches!(c, '0'..='9' | 'a'..='z' | 'A'..='Z' | '+' | '/' | '='));
-    many1(base_char)
-        .map(|chars| chars.iter().collect())
-        .parse(input)
+    let chunk = || {
+        recognize(many1(satisfy(
+            |c| matches!(c, '0'..='9' | 'a'..='z' | 'A'..='Z' | '+' | '/' | '='),
+        )))
+    };
+    tuple((
+        chunk(),
+        many0(
+            tuple((multispace1, not(tag("ENDSEC;")), chunk()))
+                .map(|(_space, _not_end, chunk)| chunk),
+        ),
+    ))
+    .map(|(first, tail)| std::iter::once(first).chain(tail).collect())
+    .parse(input)
 }
 
 #[cfg(test)]
 mod tests {
-    use nom::Finish;
+    use crate::error::{TokenError, TokenizeFailed};
+    use nom::{error::VerboseErrorKind, Finish};
 
     #[test]
     fn real() {
@@ -259,6 +595,53 @@ mod tests {
         assert!(super::real("123").finish().is_err());
     }
 
+    #[test]
+    fn loose_real() {
+        use crate::parser::ParseOptions;
+        use nom::Parser;
+        use std::str::FromStr;
+
+        // STEP literal and the same value in Rust syntax
+        let literals = [
+            (".5", "0.5"),
+            ("-.5", "-0.5"),
+            ("+.125E3", "125"),
+            ("-.0E0", "-0.0"),
+            ("5E-2", "5e-2"),
+            ("-5E+2", "-5e2"),
+            ("007E1", "70"),
+            ("1.", "1.0"),
+            ("-1.E-3", "-1e-3"),
+            ("123456789.E-300", "123456789e-300"),
+        ];
+        let options = ParseOptions {
+            loose_reals: true,
+            ..Default::default()
+        };
+        for (literal, expected) in literals {
+            let parsed = super::real_with(&options).parse(literal).finish();
+            let expected = f64::from_str(expected).unwrap();
+            assert_eq!(parsed, Ok(("", expected)), "{}", literal);
+            // Sign of zero is kept
+            assert_eq!(
+                parsed.unwrap().1.is_sign_negative(),
+                expected.is_sign_negative()
+            );
+        }
+
+        // Not reals even in the loose mode
+        for literal in ["5", ".", "-.E1", "E5", ".T."] {
+            assert!(
+                super::real_with(&options).parse(literal).finish().is_err(),
+                "{}",
+                literal
+            );
+        }
+        // Strict by default
+        assert!(super::real(".5").finish().is_err());
+        assert!(super::real("5E-2").finish().is_err());
+    }
+
     #[test]
     fn string() {
         let (res, s) = super::string("'vim'").finish().unwrap();
@@ -266,6 +649,12 @@ mod tests {
         assert_eq!(s, "vim");
     }
 
+    #[test]
+    fn wrapped_string() {
+        let (res, s) = super::string("'long\r\n text\nwrapped'").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "long textwrapped");
+    }
 
     #[test]
     fn escaped_string() {
@@ -274,6 +663,140 @@ mod tests {
         assert_eq!(s, "vim's");
     }
 
+    #[test]
+    fn unicode_string() {
+        let (res, s) = super::string(r"'caf\X2\00E9\X0\ shop'").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, "café shop");
+
+        let (_, s) = super::string(r"'\X2\30B930C630C330D7\X0\'")
+            .finish()
+            .unwrap();
+        assert_eq!(s, "ステップ");
+
+        let (_, s) = super::string(r"'\X4\0001F6000001F601\X0\!'")
+            .finish()
+            .unwrap();
+        assert_eq!(s, "😀😁!");
+
+        // Escaped backslash is not a directive
+        let (_, s) = super::string(r"'a\\X2\00E9\X0\'").finish().unwrap();
+        assert_eq!(s, r"a\X2\00E9\X0\");
+    }
+
+    #[test]
+    fn malformed_unicode_string() {
+        for input in [
+            r"'\X2\00E\X0\'",      // odd length
+            r"'\X2\00E9'",         // missing \X0\
+            r"'\X2\00G9\X0\'",     // not hex
+            r"'\X4\0000E9\X0\'",   // not a multiple of 8
+            r"'\X4\00110000\X0\'", // out of Unicode
+            r"'\X2\D800\X0\'",     // surrogate
+        ] {
+            assert!(
+                matches!(super::string(input), Err(nom::Err::Failure(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn binary() {
+        for (input, bytes, len) in [
+            (r#""0FF""#, vec![0xFF], 8),
+            (r#""3F""#, vec![0x01], 1),
+            (r#""23FF""#, vec![0x03, 0xFF], 10),
+            (r#""10A5""#, vec![0x00, 0xA5], 11),
+            (r#""0""#, vec![], 0),
+            (r#""""#, vec![], 0),
+        ] {
+            let (res, b) = super::binary(input).finish().unwrap();
+            assert_eq!(res, "");
+            assert_eq!(b.as_bytes(), bytes, "{}", input);
+            assert_eq!(b.len(), len, "{}", input);
+        }
+
+        // Padding without hex digits
+        assert!(matches!(super::binary(r#""2""#), Err(nom::Err::Failure(_))));
+        // Lower case and padding larger than 3 are not binary
+        assert!(super::binary(r#""0ff""#).finish().is_err());
+        assert!(super::binary(r#""4F""#).finish().is_err());
+    }
+
+    #[test]
+    fn latin1_string() {
+        for (input, expected) in [
+            (r"'\X\A9 2024'", "© 2024"),
+            (r"'caf\X\e9'", "café"),
+            (r"'tab\X\09'", "tab\t"),
+            // with apostrophes
+            (r"'''\X\A9'' it''s'", "'©' it's"),
+            (r"'\X\27'''", "''"),
+        ] {
+            let (res, s) = super::string(input).finish().unwrap();
+            assert_eq!(res, "");
+            assert_eq!(s, expected, "{}", input);
+        }
+        for input in [r"'\X\A'", r"'\X\G9'", r"'\X\'"] {
+            assert!(
+                matches!(super::string(input), Err(nom::Err::Failure(_))),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn iso8859_string() {
+        for (input, expected) in [
+            // ISO 8859-1 by default
+            (r"'\S\drger'", "ärger"),
+            (r"'Se\S\qor'", "Señor"),
+            // ISO 8859-5, Cyrillic
+            (r"'\PE\\S\?\S\`\S\X\S\R\S\U\S\b'", "Привет"),
+            // ISO 8859-7, Greek
+            (r"'\PG\\S\a\S\b\S\c'", "αβγ"),
+            // Page is switched in a string
+            (r"'\PB\\S\#\PA\\S\#'", "Ł£"),
+            // Apostrophe after \S\ is doubled
+            (r"'\S\'''", "§"),
+            // with other escapes
+            (r"'it''s \S\i\X2\00E9\X0\'", "it's éé"),
+            // Backslashes not starting a page directive are literal
+            (r"'\PA'", r"\PA"),
+            (r"'\PZ\'", r"\PZ\"),
+        ] {
+            let (res, s) = super::string(input).finish().unwrap();
+            assert_eq!(res, "");
+            assert_eq!(s, expected, "{}", input);
+        }
+
+        for input in [
+            r"'\S\'",      // at the end of string
+            r"'\PC\\S\%'", // unassigned 0xA5 in ISO 8859-3
+        ] {
+            match super::string(input) {
+                Err(nom::Err::Failure(e)) => {
+                    assert!(matches!(
+                        e.verbose.errors[0].1,
+                        VerboseErrorKind::Context(_)
+                    ));
+                    assert_eq!(e.token, None);
+                }
+                other => panic!("{}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn windows_path() {
+        let (res, s) = super::string(r"'C:\Parts\a.stp'").finish().unwrap();
+        assert_eq!(res, "");
+        assert_eq!(s, r"C:\Parts\a.stp");
+    }
+
     #[test]
     fn instance_name() {
         let (res, s) = super::entity_instance_name("#18446744073709551615" /* u64::MAX */)
@@ -289,16 +812,20 @@ mod tests {
         assert_eq!(s, std::u64::MAX);
 
         // u64 overflow
-        assert!(
-            super::entity_instance_name("#18446744073709551616" /* u64::MAX + 1 */)
-                .finish()
-                .is_err()
-        );
-        assert!(
-            super::value_instance_name("@18446744073709551616" /* u64::MAX + 1 */)
-                .finish()
-                .is_err()
-        );
+        for name in ["#18446744073709551616", "@018446744073709551616"] {
+            let parse = if name.starts_with('#') {
+                super::entity_instance_name
+            } else {
+                super::value_instance_name
+            };
+            let err = parse(name).finish().unwrap_err();
+            assert_eq!(
+                TokenizeFailed::new(name, err).token_error(),
+                Some(&TokenError::IdOverflow {
+                    text: name.to_string()
+                })
+            );
+        }
 
         // zeros should be ignored
         let (res, s) = super::entity_instance_name("#001").finish().unwrap();
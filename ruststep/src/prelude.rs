@@ -0,0 +1,33 @@
+//! Stable surface of this crate for glob import
+//!
+//! ```
+//! use ruststep::prelude::*;
+//!
+//! let step = r#"ISO-10303-21;
+//! HEADER;
+//! FILE_DESCRIPTION((''), '2;1');
+//! ENDSEC;
+//! DATA;
+//! #1 = POINT(0.0, #2);
+//! ENDSEC;
+//! END-ISO-10303-21;
+//! "#;
+//!
+//! let exchange: Exchange = parse_with(step, &ParseOptions::strict()).unwrap();
+//! let EntityInstance::Simple { record, .. } = &exchange.data[0].entities[0] else {
+//!     unreachable!()
+//! };
+//! assert_eq!(record.name, "POINT");
+//! assert!(matches!(record.parameter, Parameter::List(_)));
+//! ```
+//!
+//! Items re-exported here are kept through minor versions.
+//! Deeper paths, e.g. `ruststep::parser::exchange::exchange_file`, may be moved,
+//! and the helpers called by the code generated by [ruststep_derive] are hidden from the document.
+
+pub use crate::{
+    ast::{DataSection, EntityInstance, Exchange, Name, Parameter, Record, SubSuperRecord},
+    error::{Error, Result},
+    parser::{parse, parse_with, ParseOptions},
+    tables::{EntityTable, Holder, IntoOwned, PlaceHolder, TableInit},
+};
@@ -0,0 +1,181 @@
+use crate::error::Error;
+use serde::{de, ser};
+use std::{fmt, str::FromStr};
+
+/// Name of newtype struct in serde data model to distinguish [Binary] from strings
+pub(crate) const BINARY: &str = "$ruststep::Binary";
+
+/// `BINARY` type, a sequence of bits
+///
+/// Bits are packed into bytes as a big-endian unsigned integer,
+/// i.e. the last bit is the least significant bit of the last byte,
+/// and the unused high bits of the first byte are zero.
+///
+/// [Display](fmt::Display) and [FromStr] use the content of the binary token between `"`,
+/// i.e. a digit `0`..`3` for the number of unused high bits in the first hex digit,
+/// followed by hex digits of the bits:
+///
+/// ```
+/// use ruststep::primitive::Binary;
+/// use std::str::FromStr;
+///
+/// // 10 bits `11 1111 1111`
+/// let bits = Binary::from_str("23FF").unwrap();
+/// assert_eq!(bits.len(), 10);
+/// assert_eq!(bits.as_bytes(), &[0x03, 0xFF]);
+/// assert_eq!(bits.to_string(), "23FF");
+///
+/// // Bytes are read as multiple of 8 bits
+/// let bits = Binary::from(vec![0x0A, 0xBC]);
+/// assert_eq!(bits.len(), 16);
+/// assert_eq!(bits.to_string(), "00ABC");
+///
+/// // Empty binary is written as `"0"`, and `""` is also accepted
+/// assert_eq!(Binary::from_str("").unwrap(), Binary::default());
+/// assert_eq!(Binary::default().to_string(), "0");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Binary {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Binary {
+    /// Last `len` bits of `bytes`, where the unused high bits are dropped
+    ///
+    /// Panics if `bytes` is shorter than `len` bits.
+    pub fn new(mut bytes: Vec<u8>, len: usize) -> Self {
+        let used = len.div_ceil(8);
+        assert!(
+            bytes.len() >= used,
+            "{} bits do not fit in {} bytes",
+            len,
+            bytes.len()
+        );
+        bytes.drain(..bytes.len() - used);
+        if len % 8 != 0 {
+            bytes[0] &= (1 << (len % 8)) - 1;
+        }
+        Binary { bytes, len }
+    }
+
+    /// Number of bits
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl From<Vec<u8>> for Binary {
+    fn from(bytes: Vec<u8>) -> Self {
+        let len = bytes.len() * 8;
+        Binary { bytes, len }
+    }
+}
+
+impl fmt::Display for Binary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = self.len.div_ceil(4);
+        write!(f, "{}", digits * 4 - self.len)?;
+        // Skip the high nibble of the first byte if it is not used
+        for i in (self.bytes.len() * 2 - digits)..(self.bytes.len() * 2) {
+            let byte = self.bytes[i / 2];
+            let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0xF };
+            write!(f, "{:X}", nibble)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Binary {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidBinary(s.to_string());
+        let mut chars = s.chars();
+        let pad = match chars.next() {
+            Some(c @ '0'..='3') => c as usize - '0' as usize,
+            Some(_) => return Err(invalid()),
+            None => return Ok(Binary::default()),
+        };
+        let nibbles = chars
+            .map(|c| match c {
+                '0'..='9' | 'A'..='F' => Ok(c.to_digit(16).unwrap() as u8),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        if nibbles.is_empty() && pad != 0 {
+            return Err(invalid());
+        }
+        // Align to the last nibble
+        let offset = nibbles.len() % 2;
+        let mut bytes = vec![0; nibbles.len().div_ceil(2)];
+        for (i, nibble) in nibbles.iter().enumerate() {
+            let i = i + offset;
+            bytes[i / 2] |= if i % 2 == 0 { nibble << 4 } else { *nibble };
+        }
+        Ok(Binary::new(bytes, nibbles.len() * 4 - pad))
+    }
+}
+
+impl ser::Serialize for Binary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(BINARY, &self.to_string())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Binary {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(BINARY, BinaryVisitor)
+    }
+}
+
+struct BinaryVisitor;
+
+impl<'de> de::Visitor<'de> for BinaryVisitor {
+    type Value = Binary;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "binary")
+    }
+
+    // From deserializers other than [crate::ast::Parameter]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+
+    // From [crate::ast::Parameter], as the content of the binary token
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Binary::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Binary::from(v.to_vec()))
+    }
+}
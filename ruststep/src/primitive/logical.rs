@@ -186,6 +186,19 @@ impl<'de> serde::de::Visitor<'de> for SubVisitor {
             _ => Err(E::unknown_field(v, VARIANTS)),
         }
     }
+    // Index of the variant written by `Serialize` of [Logical] in binary formats
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match v {
+            0 => Ok(SubLogical::False),
+            1 => Ok(SubLogical::Unknown),
+            2 => Ok(SubLogical::True),
+            _ => Err(E::invalid_value(serde::de::Unexpected::Unsigned(v), &self)),
+        }
+    }
+
     fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
@@ -1,4 +1,9 @@
 //! Primitive types appears in STEP and not defined in Rust
 
+mod binary;
 mod logical;
+mod wrapped;
+
+pub use binary::*;
 pub use logical::*;
+pub use wrapped::*;
@@ -0,0 +1,146 @@
+use serde::{de, ser};
+use std::{fmt, marker::PhantomData, ops::*};
+
+/// Name of newtype struct in serde data model to distinguish [Wrapped] from other types
+pub(crate) const WRAPPED: &str = "$ruststep::Wrapped";
+
+/// Value of a simple type with the keyword of typed parameter, e.g. `LABEL('bolt')`
+///
+/// A typed parameter whose keyword is not declared in the schema,
+/// e.g. `LABEL('bolt')` for a `STRING` attribute, is read as its content `'bolt'`.
+/// The keyword is kept in [Wrapped::keyword] if
+/// [DeserializeOptions::preserve_type_wrappers](crate::tables::DeserializeOptions::preserve_type_wrappers) is enabled,
+/// and it is restored when serialized by [to_record](crate::ast::ser::to_record):
+///
+/// ```
+/// use ruststep::{ast::*, primitive::Wrapped, tables::*};
+/// use serde::{Deserialize, Serialize};
+/// use std::str::FromStr;
+///
+/// #[derive(Debug, Deserialize, Serialize)]
+/// #[allow(non_camel_case_types)]
+/// struct PRODUCT {
+///     id: Wrapped<String>,
+///     name: Wrapped<String>,
+/// }
+///
+/// let record = Record::from_str("PRODUCT(LABEL('bolt'), 'M6 bolt')").unwrap();
+///
+/// let product: PRODUCT = PRODUCT::deserialize(&record).unwrap();
+/// assert_eq!(*product.id, "bolt");
+/// assert_eq!(product.id.keyword, None);
+///
+/// let options = DeserializeOptions {
+///     preserve_type_wrappers: true,
+///     ..Default::default()
+/// };
+/// let product: PRODUCT = with_deserialize_options(&options, || PRODUCT::deserialize(&record)).unwrap();
+/// assert_eq!(product.id.keyword.as_deref(), Some("LABEL"));
+/// assert_eq!(product.name.keyword, None);
+/// assert_eq!(ser::to_record(&product).unwrap(), record);
+/// ```
+///
+/// Typed parameters of defined types, e.g. `TYPE label = STRING; END_TYPE;`,
+/// are read into their own newtypes, which imply the keyword, and thus they are not wrapped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Wrapped<T> {
+    /// Keyword of the typed parameter, `None` for a bare value
+    pub keyword: Option<String>,
+    pub value: T,
+}
+
+impl<T> Wrapped<T> {
+    pub fn new(keyword: impl Into<String>, value: T) -> Self {
+        Wrapped {
+            keyword: Some(keyword.into()),
+            value,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for Wrapped<T> {
+    fn from(value: T) -> Self {
+        Wrapped {
+            keyword: None,
+            value,
+        }
+    }
+}
+
+impl<T> Deref for Wrapped<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Wrapped<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: ser::Serialize> ser::Serialize for Wrapped<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        // Binary formats cannot tell a bare value from a pair of the keyword and the value
+        if !serializer.is_human_readable() {
+            return ser::Serialize::serialize(&(&self.keyword, &self.value), serializer);
+        }
+        match &self.keyword {
+            Some(keyword) => serializer.serialize_newtype_struct(WRAPPED, &(keyword, &self.value)),
+            None => self.value.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: de::Deserialize<'de>> de::Deserialize<'de> for Wrapped<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            let (keyword, value) = de::Deserialize::deserialize(deserializer)?;
+            return Ok(Wrapped { keyword, value });
+        }
+        deserializer.deserialize_newtype_struct(WRAPPED, WrappedVisitor(PhantomData))
+    }
+}
+
+struct WrappedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: de::Deserialize<'de>> de::Visitor<'de> for WrappedVisitor<T> {
+    type Value = Wrapped<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "value optionally wrapped by a keyword")
+    }
+
+    // From deserializers other than [crate::ast::Parameter]
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Ok(T::deserialize(deserializer)?.into())
+    }
+
+    // From [crate::ast::Parameter], as a pair of the keyword and the value
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let keyword = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Wrapped { keyword, value })
+    }
+}
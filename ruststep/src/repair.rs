@@ -0,0 +1,620 @@
+//! Repair non-conforming exchange structures into conforming ones
+//!
+//! [repair] rewrites the input in two passes:
+//!
+//! 1. Tokens are fixed in the text, i.e. keywords in lower case, malformed numbers,
+//!    backslashes not forming control directives, and characters not allowed in strings.
+//! 2. The fixed text is parsed, and the AST is fixed, i.e. duplicated ids and dangling references.
+//!
+//! Then the output is written from the AST, and it must be parsed again by the strict parser.
+//! Each change is recorded in [RepairLog] with the location in the input:
+//!
+//! ```
+//! use ruststep::repair::*;
+//!
+//! let input = r#"ISO-10303-21;
+//! HEADER;
+//! FILE_DESCRIPTION((''), '2;1');
+//! ENDSEC;
+//! DATA;
+//! #1 = point(.5, 1.0);
+//! ENDSEC;
+//! END-ISO-10303-21;
+//! "#;
+//! let (output, log) = repair(input, RepairPolicy::all()).unwrap();
+//! assert!(output.contains("#1 = POINT(0.5,1.0);"));
+//! assert_eq!(log.repairs.len(), 2);
+//! assert_eq!(log.repairs[0].category, RepairCategory::KeywordCase);
+//! assert_eq!(log.repairs[1].category, RepairCategory::Number);
+//! ```
+//!
+//! Repairs disabled in [RepairPolicy] are not applied,
+//! and [Error::RepairFailed] is returned with the log until the failure
+//! if the input cannot be repaired into a conforming one.
+
+use crate::{
+    ast::*,
+    error::{Error, Result},
+    parser::{self, ParseOptions},
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// Which instance is kept when multiple instances have the same id
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// Fail with [Error::DuplicatedEntity]
+    #[default]
+    Abort,
+    KeepFirst,
+    KeepLast,
+}
+
+/// Repairs allowed in [repair]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RepairPolicy {
+    /// Write keywords and enumerations in upper case, e.g. `cartesian_point` or `.t.`
+    pub keyword_case: bool,
+    /// Rewrite numbers into the syntax of exchange structure,
+    /// e.g. `.5` into `0.5`, `1e-3` into `1.E-3`, and `- 5` into `-5`
+    pub numbers: bool,
+    /// Escape backslashes not forming control directives in strings, e.g. `'C:\temp'` into `'C:\\temp'`
+    pub string_escapes: bool,
+    /// Encode non-ASCII and control characters in strings by control directives,
+    /// e.g. `'ñ'` into `'\X2\00F1\X0\'`
+    pub character_encoding: bool,
+    /// Replace references to undefined instances by `$`, or remove them from aggregates
+    pub dangling_references: bool,
+    pub duplicate_ids: DuplicatePolicy,
+}
+
+impl RepairPolicy {
+    /// No repair, i.e. only check the input conforms and write it again
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Every repair, and keep the first instance of duplicated ids
+    pub fn all() -> Self {
+        RepairPolicy {
+            keyword_case: true,
+            numbers: true,
+            string_escapes: true,
+            character_encoding: true,
+            dangling_references: true,
+            duplicate_ids: DuplicatePolicy::KeepFirst,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairCategory {
+    KeywordCase,
+    Number,
+    StringEscape,
+    CharacterEncoding,
+    DanglingReference,
+    DuplicateId,
+}
+
+/// A change made by [repair]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Repair {
+    /// Entity instance containing the change, `None` outside entity instances
+    pub id: Option<u64>,
+    /// Byte offset of the change in the input. The start of the instance for changes in the AST.
+    pub offset: usize,
+    /// Line number of `offset` starting from 1
+    pub line: usize,
+    pub category: RepairCategory,
+    pub before: String,
+    /// Empty if removed
+    pub after: String,
+}
+
+/// Changes made by [repair] in the order of offsets
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RepairLog {
+    pub repairs: Vec<Repair>,
+}
+
+impl RepairLog {
+    pub fn is_empty(&self) -> bool {
+        self.repairs.is_empty()
+    }
+
+    /// Number of repairs of the category
+    pub fn count(&self, category: RepairCategory) -> usize {
+        self.repairs
+            .iter()
+            .filter(|repair| repair.category == category)
+            .count()
+    }
+}
+
+impl fmt::Display for RepairLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for repair in &self.repairs {
+            write!(f, "line {}", repair.line)?;
+            if let Some(id) = repair.id {
+                write!(f, " (#{})", id)?;
+            }
+            writeln!(
+                f,
+                ": {:?} `{}` -> `{}`",
+                repair.category, repair.before, repair.after
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Repair `input` into a conforming exchange structure, see [module document](self)
+pub fn repair(input: &str, policy: RepairPolicy) -> Result<(String, RepairLog)> {
+    let mut fixer = TextFixer::new(input, &policy);
+    fixer.run();
+    let TextFixer {
+        out: text,
+        repairs,
+        definitions,
+        ..
+    } = fixer;
+    let mut log = Log {
+        input,
+        repairs,
+        definitions,
+    };
+
+    let mut exchange = match parser::parse_with(&text, &ParseOptions::strict()) {
+        Ok(exchange) => exchange,
+        Err(e) => return Err(log.fail(e)),
+    };
+    if let Err(e) = log.remove_duplicates(&mut exchange, policy.duplicate_ids) {
+        return Err(log.fail(e));
+    }
+    if policy.dangling_references {
+        log.stub_dangling_references(&mut exchange);
+    }
+
+    let output = exchange.to_string();
+    if let Err(e) = parser::parse_with(&output, &ParseOptions::strict()) {
+        return Err(log.fail(e));
+    }
+    Ok((output, log.finish()))
+}
+
+/// First pass fixing tokens in the text
+struct TextFixer<'a> {
+    input: &'a str,
+    policy: &'a RepairPolicy,
+    out: String,
+    repairs: Vec<Repair>,
+    /// Offsets of instances defining each id in the input
+    definitions: HashMap<u64, Vec<usize>>,
+    /// Instance currently scanned
+    id: Option<u64>,
+}
+
+impl<'a> TextFixer<'a> {
+    fn new(input: &'a str, policy: &'a RepairPolicy) -> Self {
+        TextFixer {
+            input,
+            policy,
+            out: String::with_capacity(input.len()),
+            repairs: Vec::new(),
+            definitions: HashMap::new(),
+            id: None,
+        }
+    }
+
+    fn record(&mut self, offset: usize, category: RepairCategory, before: &str, after: &str) {
+        self.repairs.push(Repair {
+            id: self.id,
+            offset,
+            line: line_of(self.input, offset),
+            category,
+            before: before.to_string(),
+            after: after.to_string(),
+        });
+    }
+
+    fn run(&mut self) {
+        let input = self.input;
+        let mut statement_start = true;
+        let mut i = 0;
+        while let Some(c) = input[i..].chars().next() {
+            let rest = &input[i..];
+            if rest.starts_with("/*") {
+                let end = rest.find("*/").map_or(rest.len(), |end| end + 2);
+                self.out.push_str(&rest[..end]);
+                i += end;
+                continue;
+            }
+            let start = i;
+            match c {
+                '\'' => i = self.string(i),
+                '"' => {
+                    let end = rest[1..].find('"').map_or(rest.len(), |end| end + 2);
+                    self.out.push_str(&rest[..end]);
+                    i += end;
+                }
+                ';' => {
+                    self.out.push(c);
+                    self.id = None;
+                    statement_start = true;
+                    i += 1;
+                    continue;
+                }
+                '#' | '@' => {
+                    let len = 1 + token_len(&rest[1..]);
+                    if c == '#' && statement_start && rest[len..].trim_start().starts_with('=') {
+                        self.id = rest[1..len].parse().ok();
+                        if let Some(id) = self.id {
+                            self.definitions.entry(id).or_default().push(i);
+                        }
+                    }
+                    self.out.push_str(&rest[..len]);
+                    i += len;
+                }
+                '+' | '-' if self.after_delimiter() => {
+                    let spaces = rest[1..].len() - rest[1..].trim_start_matches(' ').len();
+                    let digits = &rest[1 + spaces..];
+                    self.out.push(c);
+                    if spaces > 0 && self.policy.numbers && starts_number(digits) {
+                        self.record(i, RepairCategory::Number, &rest[..1 + spaces], &rest[..1]);
+                        i += spaces;
+                    }
+                    i += 1;
+                }
+                c if c.is_ascii_digit()
+                    || (c == '.' && self.after_delimiter() && starts_number(rest)) =>
+                {
+                    i = self.number(i)
+                }
+                c if c.is_ascii_alphabetic() || c == '!' => {
+                    let len = 1 + token_len(&rest[1..]);
+                    let token = &rest[..len];
+                    if self.policy.keyword_case && token.chars().any(|c| c.is_ascii_lowercase()) {
+                        let upper = token.to_ascii_uppercase();
+                        self.record(i, RepairCategory::KeywordCase, token, &upper);
+                        self.out.push_str(&upper);
+                    } else {
+                        self.out.push_str(token);
+                    }
+                    i += len;
+                }
+                c => {
+                    self.out.push(c);
+                    i += c.len_utf8();
+                    if c.is_whitespace() {
+                        continue;
+                    }
+                }
+            }
+            if start != i {
+                statement_start = false;
+            }
+        }
+    }
+
+    /// The last character written is a delimiter before a number
+    fn after_delimiter(&self) -> bool {
+        self.out.trim_end().ends_with(['(', ',', '=', '+', '-'])
+    }
+
+    /// Fix the number at `start`, and returns the position after it
+    fn number(&mut self, start: usize) -> usize {
+        let rest = &self.input[start..];
+        let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+        let integral = &rest[..digits(rest)];
+        let mut len = integral.len();
+        let mut fractional = None;
+        if rest[len..].starts_with('.') {
+            let n = digits(&rest[len + 1..]);
+            fractional = Some(&rest[len + 1..len + 1 + n]);
+            len += 1 + n;
+        }
+        let mut exponent = None;
+        if rest[len..].starts_with(['e', 'E']) {
+            let after = &rest[len + 1..];
+            let sign = if after.starts_with(['+', '-']) { 1 } else { 0 };
+            let n = digits(&after[sign..]);
+            if n > 0 {
+                exponent = Some(&after[..sign + n]);
+                len += 1 + sign + n;
+            }
+        }
+
+        let original = &rest[..len];
+        if !self.policy.numbers {
+            self.out.push_str(original);
+            return start + len;
+        }
+        let mut fixed = if integral.is_empty() {
+            "0".to_string()
+        } else {
+            integral.to_string()
+        };
+        if fractional.is_some() || exponent.is_some() {
+            fixed.push('.');
+            fixed.push_str(fractional.unwrap_or(""));
+        }
+        if let Some(exponent) = exponent {
+            fixed.push('E');
+            fixed.push_str(exponent);
+        }
+        if fixed != original {
+            self.record(start, RepairCategory::Number, original, &fixed);
+        }
+        self.out.push_str(&fixed);
+        start + len
+    }
+
+    /// Fix the string starting with `'` at `start`, and returns the position after it
+    fn string(&mut self, start: usize) -> usize {
+        let input = self.input;
+        self.out.push('\'');
+        let mut i = start + 1;
+        while let Some(c) = input[i..].chars().next() {
+            let rest = &input[i..];
+            match c {
+                '\'' if rest.starts_with("''") => {
+                    self.out.push_str("''");
+                    i += 2;
+                }
+                '\'' => {
+                    self.out.push('\'');
+                    return i + 1;
+                }
+                '\\' => match directive_len(rest) {
+                    Some(len) => {
+                        self.out.push_str(&rest[..len]);
+                        i += len;
+                    }
+                    None => {
+                        if self.policy.string_escapes {
+                            let next = rest[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+                            let before = &rest[..next];
+                            let after = format!("\\{}", before);
+                            self.record(i, RepairCategory::StringEscape, before, &after);
+                            self.out.push('\\');
+                        }
+                        self.out.push('\\');
+                        i += 1;
+                    }
+                },
+                c if self.policy.character_encoding && needs_encoding(c) => {
+                    let len = rest.len() - rest.trim_start_matches(needs_encoding).len();
+                    let before = &rest[..len];
+                    let after = encode(before);
+                    self.record(i, RepairCategory::CharacterEncoding, before, &after);
+                    self.out.push_str(&after);
+                    i += len;
+                }
+                c => {
+                    self.out.push(c);
+                    i += c.len_utf8();
+                }
+            }
+        }
+        i
+    }
+}
+
+/// Length of the keyword, enumeration, or instance name at the head of `s`
+fn token_len(s: &str) -> usize {
+    s.len()
+        - s.trim_start_matches(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            .len()
+}
+
+/// `s` starts with digits, or a point followed by a digit
+fn starts_number(s: &str) -> bool {
+    let s = s.strip_prefix('.').unwrap_or(s);
+    s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Length of the control directive at the head of `s`, e.g. `\X\F1` or `\X2\00F1\X0\`
+fn directive_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    let hex = |range: std::ops::Range<usize>| {
+        b.get(range)
+            .is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit))
+    };
+    if s.starts_with("\\\\") {
+        Some(2)
+    } else if s.starts_with("\\X\\") && hex(3..5) {
+        Some(5)
+    } else if s.starts_with("\\X2\\") || s.starts_with("\\X4\\") {
+        s.find("\\X0\\").map(|end| end + 4)
+    } else if s.starts_with("\\X0\\")
+        || (s.starts_with("\\S\\") && b.get(3).is_some_and(u8::is_ascii))
+        || (s.starts_with("\\P")
+            && matches!(b.get(2), Some(b'A'..=b'I'))
+            && b.get(3) == Some(&b'\\'))
+    {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+/// Characters not allowed in strings. Line breaks are ignored by the parser, and kept.
+fn needs_encoding(c: char) -> bool {
+    !c.is_ascii() || (c.is_ascii_control() && c != '\r' && c != '\n')
+}
+
+/// Encode characters by `\X2\` if all are in BMP, or by `\X4\` otherwise
+fn encode(s: &str) -> String {
+    if s.chars().all(|c| (c as u32) < 0x10000) {
+        let hex: String = s.chars().map(|c| format!("{:04X}", c as u32)).collect();
+        format!("\\X2\\{}\\X0\\", hex)
+    } else {
+        let hex: String = s.chars().map(|c| format!("{:08X}", c as u32)).collect();
+        format!("\\X4\\{}\\X0\\", hex)
+    }
+}
+
+fn line_of(input: &str, offset: usize) -> usize {
+    input[..offset].matches('\n').count() + 1
+}
+
+/// Repairs in the AST located by the definitions of instances in the input
+struct Log<'a> {
+    input: &'a str,
+    repairs: Vec<Repair>,
+    definitions: HashMap<u64, Vec<usize>>,
+}
+
+impl Log<'_> {
+    /// `n`-th definition of `id`
+    fn record(&mut self, id: u64, n: usize, category: RepairCategory, before: String, after: &str) {
+        let offset = self
+            .definitions
+            .get(&id)
+            .and_then(|offsets| offsets.get(n))
+            .copied()
+            .unwrap_or(0);
+        self.repairs.push(Repair {
+            id: Some(id),
+            offset,
+            line: line_of(self.input, offset),
+            category,
+            before,
+            after: after.to_string(),
+        });
+    }
+
+    fn finish(mut self) -> RepairLog {
+        self.repairs.sort_by_key(|repair| repair.offset);
+        RepairLog {
+            repairs: self.repairs,
+        }
+    }
+
+    fn fail(self, source: Error) -> Error {
+        Error::RepairFailed {
+            source: Box::new(source),
+            log: self.finish(),
+        }
+    }
+
+    fn remove_duplicates(
+        &mut self,
+        exchange: &mut Exchange,
+        policy: DuplicatePolicy,
+    ) -> Result<()> {
+        let mut count: HashMap<u64, usize> = HashMap::new();
+        for instance in exchange.data.iter().flat_map(|section| &section.entities) {
+            *count.entry(instance.id()).or_default() += 1;
+        }
+        let mut seen: HashMap<u64, usize> = HashMap::new();
+        for section in &mut exchange.data {
+            let mut kept = Vec::with_capacity(section.entities.len());
+            for instance in section.entities.drain(..) {
+                let id = instance.id();
+                let total = count[&id];
+                let n = seen.entry(id).or_default();
+                let keep = match policy {
+                    _ if total == 1 => true,
+                    DuplicatePolicy::Abort => return Err(Error::DuplicatedEntity(id)),
+                    DuplicatePolicy::KeepFirst => *n == 0,
+                    DuplicatePolicy::KeepLast => *n + 1 == total,
+                };
+                let n = std::mem::replace(n, *n + 1);
+                if keep {
+                    kept.push(instance);
+                } else {
+                    self.record(id, n, RepairCategory::DuplicateId, instance.to_string(), "");
+                }
+            }
+            section.entities = kept;
+        }
+        Ok(())
+    }
+
+    fn stub_dangling_references(&mut self, exchange: &mut Exchange) {
+        let mut defined: HashSet<u64> = exchange
+            .data
+            .iter()
+            .flat_map(|section| &section.entities)
+            .map(|instance| instance.id())
+            .collect();
+        defined.extend(
+            exchange
+                .reference
+                .iter()
+                .filter_map(|entry| match entry.name {
+                    Name::Entity(id) => Some(id),
+                    _ => None,
+                }),
+        );
+
+        for instance in exchange
+            .data
+            .iter_mut()
+            .flat_map(|section| &mut section.entities)
+        {
+            let id = instance.id();
+            let records = match instance {
+                EntityInstance::Simple { record, .. } => std::slice::from_mut(record),
+                EntityInstance::Complex { subsuper, .. } => subsuper.0.as_mut_slice(),
+            };
+            let mut stubbed = Vec::new();
+            for record in records {
+                match &mut record.parameter {
+                    Parameter::List(attributes) => {
+                        for attribute in attributes {
+                            stub(attribute, &defined, &mut stubbed);
+                        }
+                    }
+                    parameter => stub(parameter, &defined, &mut stubbed),
+                }
+            }
+            for (reference, after) in stubbed {
+                self.record(
+                    id,
+                    0,
+                    RepairCategory::DanglingReference,
+                    format!("#{}", reference),
+                    after,
+                );
+            }
+        }
+    }
+}
+
+/// Replace a dangling reference as an attribute by `$`, and remove ones in aggregates
+fn stub(parameter: &mut Parameter, defined: &HashSet<u64>, stubbed: &mut Vec<(u64, &'static str)>) {
+    let dangling = |p: &Parameter| match p {
+        Parameter::Ref(Name::Entity(id)) if !defined.contains(id) => Some(*id),
+        _ => None,
+    };
+    match parameter {
+        Parameter::Ref(_) => {
+            if let Some(id) = dangling(parameter) {
+                stubbed.push((id, "$"));
+                *parameter = Parameter::NotProvided;
+            }
+        }
+        Parameter::List(items) => {
+            items.retain(|item| match dangling(item) {
+                Some(id) => {
+                    stubbed.push((id, ""));
+                    false
+                }
+                None => true,
+            });
+            for item in items {
+                stub(item, defined, stubbed);
+            }
+        }
+        Parameter::Typed { parameter, .. } => stub(parameter, defined, stubbed),
+        _ => {}
+    }
+}
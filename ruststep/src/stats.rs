@@ -0,0 +1,428 @@
+//! Statistics of attribute values over tables for data quality checks
+//!
+//! [collect] reads attributes of holders through [AnyTables] without converting them into owned structs.
+//! [StatsSpec] lists pairs of an entity and its attribute with [Aggregation]s:
+//!
+//! - [Aggregation::CountByValue] counts instances for each value, e.g. variants of an enumeration.
+//!   Only the first `cap` distinct values are counted separately to bound the memory.
+//! - [Aggregation::Numeric] summarizes REAL or INTEGER values with quantiles
+//!   estimated by the streaming P² algorithm, which keeps five markers for each quantile.
+//! - [Aggregation::NullRate] counts `$` of an OPTIONAL attribute.
+//!
+//! Entities are specified by their names in exchange structure, e.g. `PRODUCT`,
+//! and attributes by the names of fields in the generated structs, e.g. `name`.
+//! Every item of the same entity is computed in one pass over the table of the entity.
+//! [StatsReport] is serializable, e.g. into JSON for dashboards.
+
+use crate::tables::{AnyTables, AttributeValue};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How values of an attribute are aggregated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregation {
+    /// Count instances for each value, up to `cap` distinct values
+    CountByValue { cap: usize },
+    /// Count, minimum, maximum, mean, and estimated quantiles, e.g. `0.5` for the median
+    Numeric { quantiles: Vec<f64> },
+    /// Rate of instances whose value is `$`
+    NullRate,
+}
+
+/// Attribute of an entity to be aggregated
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsItem {
+    /// Name of entity in exchange structure, e.g. `PRODUCT`
+    pub entity: String,
+    /// Name of field, e.g. `name`
+    pub attribute: String,
+    pub aggregation: Aggregation,
+}
+
+/// Items to be computed by [collect]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsSpec {
+    pub items: Vec<StatsItem>,
+}
+
+impl StatsSpec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count_by_value(self, entity: &str, attribute: &str, cap: usize) -> Self {
+        self.item(entity, attribute, Aggregation::CountByValue { cap })
+    }
+
+    pub fn numeric(self, entity: &str, attribute: &str, quantiles: &[f64]) -> Self {
+        let quantiles = quantiles.to_vec();
+        self.item(entity, attribute, Aggregation::Numeric { quantiles })
+    }
+
+    pub fn null_rate(self, entity: &str, attribute: &str) -> Self {
+        self.item(entity, attribute, Aggregation::NullRate)
+    }
+
+    fn item(mut self, entity: &str, attribute: &str, aggregation: Aggregation) -> Self {
+        self.items.push(StatsItem {
+            entity: entity.to_string(),
+            attribute: attribute.to_string(),
+            aggregation,
+        });
+        self
+    }
+}
+
+/// Result of [collect] in the order of [StatsSpec::items]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsReport {
+    pub items: Vec<ItemStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ItemStats {
+    pub entity: String,
+    pub attribute: String,
+    pub stats: Stats,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Stats {
+    ValueCounts(ValueCounts),
+    Numeric(NumericSummary),
+    NullRate(NullRate),
+    /// The tables do not have the entity, or the entity does not have the attribute
+    NotFound,
+}
+
+/// Result of [Aggregation::CountByValue]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValueCounts {
+    /// Values and their counts in descending order of counts, and then in the order of values
+    pub counts: Vec<(String, usize)>,
+    /// Instances whose values are not counted since `cap` distinct values have been found
+    pub overflow: usize,
+    pub nulls: usize,
+}
+
+/// Result of [Aggregation::Numeric]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NumericSummary {
+    /// Number of REAL or INTEGER values
+    pub count: usize,
+    pub nulls: usize,
+    /// Number of values neither REAL nor INTEGER, which are ignored
+    pub non_numeric: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    /// Estimated quantiles, empty if no value is found
+    pub quantiles: Vec<Quantile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Quantile {
+    /// Probability in `[0, 1]`
+    pub p: f64,
+    pub value: f64,
+}
+
+/// Result of [Aggregation::NullRate]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct NullRate {
+    pub total: usize,
+    pub nulls: usize,
+    /// `nulls / total`, or `0` if there is no instance
+    pub rate: f64,
+}
+
+/// Compute statistics of attributes listed in `spec`
+pub fn collect(tables: &dyn AnyTables, spec: &StatsSpec) -> StatsReport {
+    let mut stats: Vec<Stats> = vec![Stats::NotFound; spec.items.len()];
+
+    // Indices of items for each entity in the order of first appearance
+    let mut entities: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, item) in spec.items.iter().enumerate() {
+        let entity = item.entity.to_uppercase();
+        match entities.iter_mut().find(|(name, _)| *name == entity) {
+            Some((_, items)) => items.push(i),
+            None => entities.push((entity, vec![i])),
+        }
+    }
+
+    for (entity, items) in entities {
+        let Some(names) = tables.attr_names(&entity) else {
+            continue;
+        };
+        let mut accumulators: Vec<(usize, usize, Accumulator)> = items
+            .into_iter()
+            .filter_map(|i| {
+                let item = &spec.items[i];
+                let index = names.iter().position(|name| *name == item.attribute)?;
+                Some((i, index, Accumulator::new(&item.aggregation)))
+            })
+            .collect();
+        if accumulators.is_empty() {
+            continue;
+        }
+        tables.for_each_holder(&entity, &mut |_id, holder| {
+            for (_, index, accumulator) in &mut accumulators {
+                if let Some(value) = holder.read(*index) {
+                    accumulator.push(value);
+                }
+            }
+        });
+        for (i, _, accumulator) in accumulators {
+            stats[i] = accumulator.finish();
+        }
+    }
+
+    StatsReport {
+        items: spec
+            .items
+            .iter()
+            .zip(stats)
+            .map(|(item, stats)| ItemStats {
+                entity: item.entity.clone(),
+                attribute: item.attribute.clone(),
+                stats,
+            })
+            .collect(),
+    }
+}
+
+enum Accumulator {
+    ValueCounts {
+        cap: usize,
+        counts: HashMap<String, usize>,
+        overflow: usize,
+        nulls: usize,
+    },
+    Numeric {
+        summary: NumericSummary,
+        sum: f64,
+        sketches: Vec<P2Quantile>,
+    },
+    NullRate {
+        total: usize,
+        nulls: usize,
+    },
+}
+
+impl Accumulator {
+    fn new(aggregation: &Aggregation) -> Self {
+        match aggregation {
+            Aggregation::CountByValue { cap } => Accumulator::ValueCounts {
+                cap: *cap,
+                counts: HashMap::new(),
+                overflow: 0,
+                nulls: 0,
+            },
+            Aggregation::Numeric { quantiles } => Accumulator::Numeric {
+                summary: NumericSummary::default(),
+                sum: 0.0,
+                sketches: quantiles.iter().map(|p| P2Quantile::new(*p)).collect(),
+            },
+            Aggregation::NullRate => Accumulator::NullRate { total: 0, nulls: 0 },
+        }
+    }
+
+    fn push(&mut self, value: AttributeValue) {
+        match self {
+            Accumulator::ValueCounts {
+                cap,
+                counts,
+                overflow,
+                nulls,
+            } => {
+                let key = match value {
+                    AttributeValue::Null => {
+                        *nulls += 1;
+                        return;
+                    }
+                    AttributeValue::Real(x) => x.to_string(),
+                    AttributeValue::Integer(x) => x.to_string(),
+                    AttributeValue::String(s) => s.to_string(),
+                    AttributeValue::Logical(x) => format!("{:?}", x),
+                    AttributeValue::Reference => "<reference>".to_string(),
+                    AttributeValue::List(len) => format!("<list of {}>", len),
+                    AttributeValue::Other(s) => s,
+                };
+                if let Some(count) = counts.get_mut(&key) {
+                    *count += 1;
+                } else if counts.len() < *cap {
+                    counts.insert(key, 1);
+                } else {
+                    *overflow += 1;
+                }
+            }
+            Accumulator::Numeric {
+                summary,
+                sum,
+                sketches,
+            } => {
+                let x = match value {
+                    AttributeValue::Real(x) => x,
+                    AttributeValue::Integer(x) => x as f64,
+                    AttributeValue::Null => {
+                        summary.nulls += 1;
+                        return;
+                    }
+                    _ => {
+                        summary.non_numeric += 1;
+                        return;
+                    }
+                };
+                summary.count += 1;
+                *sum += x;
+                summary.min = Some(summary.min.map_or(x, |min| min.min(x)));
+                summary.max = Some(summary.max.map_or(x, |max| max.max(x)));
+                for sketch in sketches {
+                    sketch.push(x);
+                }
+            }
+            Accumulator::NullRate { total, nulls } => {
+                *total += 1;
+                if matches!(value, AttributeValue::Null) {
+                    *nulls += 1;
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> Stats {
+        match self {
+            Accumulator::ValueCounts {
+                counts,
+                overflow,
+                nulls,
+                ..
+            } => {
+                let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+                counts.sort_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+                Stats::ValueCounts(ValueCounts {
+                    counts,
+                    overflow,
+                    nulls,
+                })
+            }
+            Accumulator::Numeric {
+                mut summary,
+                sum,
+                sketches,
+            } => {
+                if summary.count > 0 {
+                    summary.mean = Some(sum / summary.count as f64);
+                    summary.quantiles = sketches
+                        .iter()
+                        .map(|sketch| Quantile {
+                            p: sketch.p,
+                            value: sketch.estimate(),
+                        })
+                        .collect();
+                }
+                Stats::Numeric(summary)
+            }
+            Accumulator::NullRate { total, nulls } => Stats::NullRate(NullRate {
+                total,
+                nulls,
+                rate: if total > 0 {
+                    nulls as f64 / total as f64
+                } else {
+                    0.0
+                },
+            }),
+        }
+    }
+}
+
+/// Streaming estimator of a quantile by the P² algorithm
+/// (R. Jain and I. Chlamtac, Communications of the ACM 28(10), 1985)
+///
+/// Five markers track the minimum, the `p/2`, `p`, `(1+p)/2` quantiles, and the maximum,
+/// and their heights are adjusted by piecewise-parabolic interpolation for each value.
+#[derive(Debug, Clone)]
+struct P2Quantile {
+    p: f64,
+    /// Heights of markers, or the first values until five values are given
+    heights: Vec<f64>,
+    /// Actual positions of markers starting from 1
+    positions: [f64; 5],
+    /// Desired positions of markers
+    desired: [f64; 5],
+    /// Increments of desired positions for each value
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    fn new(p: f64) -> Self {
+        let p = p.clamp(0.0, 1.0);
+        P2Quantile {
+            p,
+            heights: Vec::with_capacity(5),
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    fn push(&mut self, x: f64) {
+        if self.heights.len() < 5 {
+            self.heights.push(x);
+            if self.heights.len() == 5 {
+                self.heights.sort_by(f64::total_cmp);
+            }
+            return;
+        }
+
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (1..5).find(|&i| x < q[i]).unwrap() - 1
+        };
+        for n in &mut self.positions[k + 1..] {
+            *n += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+
+        let n = &mut self.positions;
+        for i in 1..4 {
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    /// Estimated quantile. Exact by linear interpolation while less than five values are given.
+    fn estimate(&self) -> f64 {
+        if self.heights.len() == 5 && self.positions[4] > 5.0 {
+            return self.heights[2];
+        }
+        let mut values = self.heights.clone();
+        values.sort_by(f64::total_cmp);
+        let rank = self.p * (values.len() - 1) as f64;
+        let lower = rank.floor() as usize;
+        let upper = rank.ceil() as usize;
+        values[lower] + (values[upper] - values[lower]) * (rank - lower as f64)
+    }
+}
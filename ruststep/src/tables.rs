@@ -100,13 +100,64 @@
 //! `AHolder` will also be introduced to keep consistency.
 //! These are automated by [ruststep_derive::Holder] proc-macro.
 //!
+//! Attribute order
+//! ----------------
+//! Parameters of a record are deserialized by position:
+//! the `i`-th parameter is assigned to the `i`-th attribute in the EXPRESS declaration,
+//! which is the order of fields in the generated struct.
+//! Names of attributes are not written in the exchange structure,
+//! and thus a file written for a schema whose attributes have been reordered
+//! is deserialized into wrong fields without any error if the values happen to be accepted,
+//! e.g. an integer is accepted for a REAL attribute.
+//!
+//! [DeserializeOptions] mitigates this:
+//!
+//! - [DeserializeOptions::check_attribute_kinds] rejects a record whose parameter
+//!   does not match the kind of the attribute, see [Holder::attr_kinds].
+//!   Note that swapping attributes of the same kind cannot be detected.
+//! - [DeserializeOptions::attribute_orders] overrides the order of parameters for each entity.
+//!
+//! These options are enabled by [with_deserialize_options],
+//! or [crate::load::LoadOptions::check_attribute_kinds] and [crate::load::LoadOptions::attribute_order].
+//!
+//! Dangling references
+//! --------------------
+//! A reference to an entity which is not in the tables, e.g. removed by a filter of the exporter,
+//! fails [IntoOwned::into_owned] of the instance referring it with [Error::UnknownEntity].
+//!
+//! - [TableIds::validate_references] lists them without resolving any instance,
+//!   and thus broken instances can be removed before [EntityTable::owned_iter].
+//! - In [with_lenient_references], a dangling reference in an OPTIONAL attribute
+//!   is resolved into `None` instead.
+//!
+//! Iteration order
+//! ----------------
+//! Fields of tables are `HashMap`s keyed by ids, while [EntityTable::entity_ids] and [EntityTable::owned_iter]
+//! yield entities in ascending order of ids for every run,
+//! also for a SELECT type whose entities are in several fields.
+//!
 
-use crate::{ast::*, error::*};
+use crate::{
+    ast::*,
+    capabilities::{Feature, Support},
+    error::*,
+    header::Header,
+    id::IdAllocator,
+    primitive::{Binary, Logical, Wrapped},
+};
 use serde::{
     de::{self, IntoDeserializer, VariantAccess},
-    Deserialize,
+    Deserialize, Serialize,
+};
+use std::{
+    any::{Any, TypeId},
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+    marker::PhantomData,
+    rc::Rc,
 };
-use std::{collections::HashMap, fmt, marker::PhantomData};
 
 /// Trait for resolving a reference through entity id
 pub trait IntoOwned: Clone + 'static {
@@ -123,10 +174,489 @@ impl<T: IntoOwned> IntoOwned for Vec<T> {
     }
 }
 
+/// For `ARRAY` with literal bounds
+impl<T: IntoOwned, const N: usize> IntoOwned for [T; N] {
+    type Owned = [T::Owned; N];
+    type Table = T::Table;
+    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned> {
+        let owned = self
+            .into_iter()
+            .map(|x| x.into_owned(table))
+            .collect::<Result<Vec<_>>>()?;
+        match owned.try_into() {
+            Ok(owned) => Ok(owned),
+            Err(_) => unreachable!("Length is kept in into_owned"),
+        }
+    }
+}
+
+/// For attributes boxed to break reference cycles of entities
+impl<T: IntoOwned> IntoOwned for Box<T> {
+    type Owned = Box<T::Owned>;
+    type Table = T::Table;
+    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned> {
+        Ok(Box::new((*self).into_owned(table)?))
+    }
+}
+
+/// For OPTIONAL attributes and elements of `ARRAY OF OPTIONAL`
+///
+/// A dangling reference is resolved into `None` in [with_lenient_references].
+impl<T: IntoOwned> IntoOwned for Option<T> {
+    type Owned = Option<T::Owned>;
+    type Table = T::Table;
+    fn into_owned(self, table: &Self::Table) -> Result<Self::Owned> {
+        match self.map(|x| x.into_owned(table)).transpose() {
+            Err(e)
+                if lenient_references()
+                    && matches!(e.without_context(), Error::UnknownEntity(_)) =>
+            {
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+}
+
+thread_local! {
+    static LENIENT_REFERENCES: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Call `f` with dangling references in OPTIONAL attributes resolved into `None`
+///
+/// A reference is dangling if the entity is not found in the tables of the attribute type,
+/// i.e. [Error::UnknownEntity], which is also the case for an entity of an unexpected type.
+/// A dangling reference in a mandatory attribute is still an error,
+/// and it makes the OPTIONAL attribute `None` when it is referred through the attribute.
+/// The mode is restored when `f` returns, even if it panics.
+pub fn with_lenient_references<T>(f: impl FnOnce() -> T) -> T {
+    struct Restore(bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            LENIENT_REFERENCES.with(|current| current.set(self.0));
+        }
+    }
+    let _restore = Restore(LENIENT_REFERENCES.with(|current| current.replace(true)));
+    f()
+}
+
+fn lenient_references() -> bool {
+    LENIENT_REFERENCES.with(|current| current.get())
+}
+
 /// Trait for a field of tables
 pub trait Holder: IntoOwned {
     fn name() -> &'static str;
     fn attr_len() -> usize;
+    /// Kinds of attributes in the declaration order. Empty if unknown.
+    fn attr_kinds() -> &'static [AttributeKind] {
+        &[]
+    }
+    /// Parameters after [Holder::attr_len] are also accepted, i.e. `#[holder(rest)]` is used
+    fn has_rest() -> bool {
+        false
+    }
+    /// Declared as `ABSTRACT SUPERTYPE`, i.e. `#[holder(abstract_entity)]` is used.
+    /// Such an entity is instantiated only through its subtypes, and not as a simple record.
+    fn is_abstract() -> bool {
+        false
+    }
+    /// Names of fields of the holder in the declaration order. Empty if unknown.
+    fn attr_names() -> &'static [&'static str] {
+        &[]
+    }
+    /// Value of the field at `index` of [Holder::attr_names] without converting into the owned struct
+    fn attr_value(&self, _index: usize) -> Option<AttributeValue<'_>> {
+        None
+    }
+    /// Call `f` with the index of [Holder::attr_names] and the id of each entity referred by the field,
+    /// including ones in inline instances
+    fn for_each_reference(&self, _f: &mut dyn FnMut(usize, u64)) {}
+}
+
+/// Entity references in a field of holders, used by [ruststep_derive::Holder]
+#[doc(hidden)]
+pub trait References {
+    fn references(&self, f: &mut dyn FnMut(u64));
+}
+
+impl<T: Holder> References for PlaceHolder<T> {
+    fn references(&self, f: &mut dyn FnMut(u64)) {
+        match self {
+            PlaceHolder::Ref(Name::Entity(id)) => f(*id),
+            // Constants are not in tables
+            PlaceHolder::Ref(_) => {}
+            PlaceHolder::Owned(holder) => holder.for_each_reference(&mut |_, id| f(id)),
+        }
+    }
+}
+
+impl<T: References> References for Option<T> {
+    fn references(&self, f: &mut dyn FnMut(u64)) {
+        if let Some(value) = self {
+            value.references(f)
+        }
+    }
+}
+
+impl<T: References> References for Vec<T> {
+    fn references(&self, f: &mut dyn FnMut(u64)) {
+        for value in self {
+            value.references(f)
+        }
+    }
+}
+
+impl<T: References, const N: usize> References for [T; N] {
+    fn references(&self, f: &mut dyn FnMut(u64)) {
+        for value in self {
+            value.references(f)
+        }
+    }
+}
+
+impl<T: References> References for Box<T> {
+    fn references(&self, f: &mut dyn FnMut(u64)) {
+        self.as_ref().references(f)
+    }
+}
+
+/// Value of a field read by [Holder::attr_value]
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue<'a> {
+    Real(f64),
+    Integer(i64),
+    String(&'a str),
+    Logical(crate::primitive::Logical),
+    /// Entity or select value in a [PlaceHolder], which is not resolved
+    Reference,
+    /// Aggregate with the number of elements
+    List(usize),
+    /// `$` for OPTIONAL attribute
+    Null,
+    /// `Debug` representation of other values, e.g. the variant of an enumeration
+    Other(String),
+}
+
+/// Object safe access to [Holder::attr_value]
+pub trait ReadAttribute {
+    fn read(&self, index: usize) -> Option<AttributeValue<'_>>;
+}
+
+impl<T: Holder> ReadAttribute for T {
+    fn read(&self, index: usize) -> Option<AttributeValue<'_>> {
+        self.attr_value(index)
+    }
+}
+
+/// Tables whose holders are accessed by entity names at runtime, implemented by [ruststep_derive::TableInit]
+pub trait AnyTables {
+    /// [Holder::attr_names] of the entity, e.g. `CARTESIAN_POINT`, or `None` if the tables do not have it
+    fn attr_names(&self, entity: &str) -> Option<&'static [&'static str]>;
+
+    /// Call `f` with each holder of the entity in the order of ids
+    fn for_each_holder(&self, entity: &str, f: &mut dyn FnMut(u64, &dyn ReadAttribute));
+}
+
+/// [Holder::attr_names] for a table, used by [ruststep_derive::TableInit]
+#[doc(hidden)]
+pub fn table_attr_names<T: Holder>(_table: &HashMap<u64, T>) -> &'static [&'static str] {
+    T::attr_names()
+}
+
+/// Visit holders of a table in the order of ids, used by [ruststep_derive::TableInit]
+#[doc(hidden)]
+pub fn visit_table<T: Holder>(table: &HashMap<u64, T>, f: &mut dyn FnMut(u64, &dyn ReadAttribute)) {
+    for id in entity_ids(table) {
+        f(id, &table[&id]);
+    }
+}
+
+/// Pairs of ids `(a, b)` with `a < b` whose holders are `same`, used by `Tables::check_unique_*` generated by espr
+///
+/// All pairs are compared since attributes, e.g. REAL, may not be hashed.
+#[doc(hidden)]
+pub fn unique_violations<T>(
+    table: &HashMap<u64, T>,
+    same: impl Fn(&T, &T) -> bool,
+) -> Vec<(u64, u64)> {
+    let ids = entity_ids(table);
+    let mut violations = Vec::new();
+    for (i, a) in ids.iter().enumerate() {
+        for b in &ids[i + 1..] {
+            if same(&table[a], &table[b]) {
+                violations.push((*a, *b));
+            }
+        }
+    }
+    violations
+}
+
+/// Ids of holders in `table` with `duplicated` `SET` attributes in ascending order,
+/// used by `Tables::check_set_*` generated by espr
+#[doc(hidden)]
+pub fn set_violations<T>(table: &HashMap<u64, T>, duplicated: impl Fn(&T) -> bool) -> Vec<u64> {
+    entity_ids(table)
+        .into_iter()
+        .filter(|id| duplicated(&table[id]))
+        .collect()
+}
+
+/// Check if `elements` of a `SET` contains the same value twice, used by `Tables::check_set_*` generated by espr
+///
+/// All pairs are compared since elements, e.g. REAL, may not be hashed.
+#[doc(hidden)]
+pub fn has_duplicates<T: PartialEq>(elements: &[T]) -> bool {
+    elements
+        .iter()
+        .enumerate()
+        .any(|(i, x)| elements[i + 1..].contains(x))
+}
+
+/// Entities in `map` whose attribute `attr` refers the entity `id` in ascending order of ids,
+/// used by `Tables::xxx_of` generated by espr for `INVERSE` attributes
+///
+/// The table is scanned since tables keep only forward references.
+#[doc(hidden)]
+pub fn inverse_of<T, Table, A>(
+    table: &Table,
+    map: &HashMap<u64, T>,
+    id: u64,
+    attr: impl Fn(&T) -> &A,
+) -> Result<Vec<T::Owned>>
+where
+    T: Holder<Table = Table>,
+    T::Owned: Clone + 'static,
+    Table: EntityTable<T>,
+    A: References,
+{
+    entity_ids(map)
+        .into_iter()
+        .filter(|i| {
+            let mut found = false;
+            attr(&map[i]).references(&mut |r| found |= r == id);
+            found
+        })
+        .map(|i| get_owned(table, map, i))
+        .collect()
+}
+
+/// Attribute declared in an entity, see [EntityDescriptor]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttributeDescriptor {
+    /// Name of the attribute in the schema, e.g. `edge_start`
+    pub name: &'static str,
+    /// Type of the attribute in EXPRESS, e.g. `LIST OF REAL`
+    pub ty: &'static str,
+    /// `OPTIONAL` attribute
+    pub optional: bool,
+}
+
+/// Entity or type in tables `T` described at runtime
+///
+/// espr generates `SCHEMA_REGISTRY: &[EntityDescriptor<Tables>]` for each schema
+/// with an entry for each field of `Tables`, and `Tables::descriptor_for` to look it up by keyword.
+pub struct EntityDescriptor<T: 'static> {
+    /// Keyword of the record, e.g. `ORIENTED_EDGE`
+    pub keyword: &'static str,
+    /// Names of direct supertypes, whose attributes precede [EntityDescriptor::attributes] in a record
+    pub supertypes: &'static [&'static str],
+    /// Attributes declared in the entity. Empty for types.
+    pub attributes: &'static [AttributeDescriptor],
+    /// Get the owned value of the instance of `id` from tables
+    pub get: fn(&T, u64) -> Result<Box<dyn fmt::Debug>>,
+}
+
+impl<T> fmt::Debug for EntityDescriptor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EntityDescriptor")
+            .field("keyword", &self.keyword)
+            .field("supertypes", &self.supertypes)
+            .field("attributes", &self.attributes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// [EntityTable::get_owned] as a `dyn Debug`, used for [EntityDescriptor::get] by espr
+#[doc(hidden)]
+pub fn get_debug<T, H>(tables: &T, id: u64) -> Result<Box<dyn fmt::Debug>>
+where
+    T: EntityTable<H>,
+    H: Holder<Table = T>,
+    H::Owned: fmt::Debug + 'static,
+{
+    Ok(Box::new(tables.get_owned(id)?))
+}
+
+/// Kind of values accepted by an attribute, see [AttributeKind]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Real,
+    Integer,
+    String,
+    /// BOOLEAN or LOGICAL, written as an enumeration, e.g. `.T.`
+    Logical,
+    List,
+    /// Other values, e.g. references, enumerations, or select types. Not checked.
+    Other,
+}
+
+/// Expected kind of an attribute used by [DeserializeOptions::check_attribute_kinds]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttributeKind {
+    pub kind: ValueKind,
+    /// `$` is accepted
+    pub optional: bool,
+}
+
+impl AttributeKind {
+    /// Check if the parameter is acceptable for this attribute
+    pub fn accepts(&self, parameter: &Parameter) -> bool {
+        match (parameter, self.kind) {
+            (Parameter::NotProvided, _) => self.optional,
+            (Parameter::Omitted, _) | (_, ValueKind::Other) => true,
+            (Parameter::Real { .. }, ValueKind::Real)
+            | (Parameter::Integer(_), ValueKind::Integer)
+            | (Parameter::String(_), ValueKind::String)
+            | (Parameter::Enumeration(_), ValueKind::Logical)
+            | (Parameter::List(_), ValueKind::List) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Options for deserializing records into holders, see [Attribute order](self#attribute-order)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    /// Reject a record whose parameter does not match [Holder::attr_kinds]
+    pub check_attribute_kinds: bool,
+    /// Order of attributes for each entity keyword in upper case, e.g. `CARTESIAN_POINT`.
+    /// `order[i]` is the position of the `i`-th attribute of the declaration in records.
+    pub attribute_orders: HashMap<String, Vec<usize>>,
+    /// Keep keywords of typed parameters read into [Wrapped](crate::primitive::Wrapped),
+    /// e.g. `LABEL` of `LABEL('bolt')`, to restore them when serialized
+    pub preserve_type_wrappers: bool,
+}
+
+thread_local! {
+    static DESERIALIZE_OPTIONS: RefCell<DeserializeOptions> = RefCell::new(DeserializeOptions::default());
+}
+
+/// Call `f` with `options` enabled for deserializing holders in it
+///
+/// Options are restored when `f` returns, even if it panics.
+pub fn with_deserialize_options<T>(options: &DeserializeOptions, f: impl FnOnce() -> T) -> T {
+    struct Restore(DeserializeOptions);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let options = std::mem::take(&mut self.0);
+            DESERIALIZE_OPTIONS.with(|current| current.replace(options));
+        }
+    }
+    let _restore = Restore(DESERIALIZE_OPTIONS.with(|current| current.replace(options.clone())));
+    f()
+}
+
+/// [DeserializeOptions::preserve_type_wrappers] of the current options
+pub(crate) fn preserve_type_wrappers() -> bool {
+    DESERIALIZE_OPTIONS.with(|options| options.borrow().preserve_type_wrappers)
+}
+
+/// Indices of attributes in the order of parameters in a record, see [attribute_order]
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct AttributeOrder {
+    // `inverse[position]` is the index of attribute. `None` for the declaration order.
+    inverse: Option<Vec<usize>>,
+    position: usize,
+    len: usize,
+}
+
+impl Iterator for AttributeOrder {
+    type Item = usize;
+    fn next(&mut self) -> Option<usize> {
+        if self.position >= self.len {
+            return None;
+        }
+        let position = self.position;
+        self.position += 1;
+        Some(match &self.inverse {
+            Some(inverse) => inverse[position],
+            None => position,
+        })
+    }
+}
+
+/// Order of attributes of the entity `name` with `len` attributes in the current [DeserializeOptions]
+///
+/// This is called by the deserializer generated by [ruststep_derive::Holder].
+///
+/// Errors
+/// -------
+/// - if the order registered in [DeserializeOptions::attribute_orders] is not a permutation of `0..len`
+///
+#[doc(hidden)]
+pub fn attribute_order(name: &str, len: usize) -> Result<AttributeOrder> {
+    let inverse = DESERIALIZE_OPTIONS.with(|options| {
+        let options = options.borrow();
+        let order = match options.attribute_orders.get(name) {
+            Some(order) => order,
+            None => return Ok(None),
+        };
+        let invalid = || {
+            Error::DeserializeFailed(format!(
+                "Attribute order {:?} of {} is not a permutation of {} attributes",
+                order, name, len
+            ))
+        };
+        if order.len() != len {
+            return Err(invalid());
+        }
+        let mut inverse = vec![len; len];
+        for (index, &position) in order.iter().enumerate() {
+            if position >= len || inverse[position] != len {
+                return Err(invalid());
+            }
+            inverse[position] = index;
+        }
+        Ok(Some(inverse))
+    })?;
+    Ok(AttributeOrder {
+        inverse,
+        position: 0,
+        len,
+    })
+}
+
+fn check_attribute_kinds<T: Holder>(record: &Record) -> Result<()> {
+    DESERIALIZE_OPTIONS.with(|options| {
+        let options = options.borrow();
+        if !options.check_attribute_kinds {
+            return Ok(());
+        }
+        let parameters = match &record.parameter {
+            Parameter::List(parameters) => parameters,
+            _ => return Ok(()),
+        };
+        let order = options.attribute_orders.get(T::name());
+        for (index, expected) in T::attr_kinds().iter().enumerate() {
+            let position = order
+                .and_then(|order| order.get(index).copied())
+                .unwrap_or(index);
+            match parameters.get(position) {
+                Some(found) if !expected.accepts(found) => {
+                    return Err(Error::AttributeKindMismatch {
+                        entity: T::name().to_string(),
+                        position,
+                        expected: *expected,
+                        found: found.clone(),
+                    })
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    })
 }
 
 pub trait WithVisitor {
@@ -139,13 +669,488 @@ pub trait EntityTable<T: Holder<Table = Self>> {
     /// Get owned entity from table
     fn get_owned(&self, entity_id: u64) -> Result<T::Owned>;
 
-    /// Get owned entities as an iterator
+    /// Ids of entities in ascending order
+    fn entity_ids(&self) -> Vec<u64>;
+
+    /// Get owned entities as an iterator in ascending order of ids
+    ///
+    /// Entities referred from several items are resolved once and cloned for others,
+    /// using the [ResolveCache] of [with_resolve_cache] if enabled, or a cache dropped with the iterator.
     fn owned_iter<'table>(&'table self) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>;
+
+    /// Ids of entities grouped by `key` of the owned entity, in ascending order of ids for each key
+    ///
+    /// The index is not updated when tables are modified, and must be built again.
+    /// This fails if an entity cannot be resolved, see [TableIds::validate_references] to find them.
+    ///
+    /// ```ignore
+    /// use ruststep::{ap203::*, tables::EntityTable};
+    ///
+    /// let products = EntityTable::<ProductHolder>::build_index(&tables, |p| p.name.0.clone())?;
+    /// let bolts: &[u64] = &products["bolt"];
+    /// ```
+    fn build_index<K, F>(&self, mut key: F) -> Result<HashMap<K, Vec<u64>>>
+    where
+        Self: Sized,
+        K: Eq + Hash,
+        F: FnMut(&T::Owned) -> K,
+    {
+        // Resolve entities referred from several items once, same as `owned_iter`
+        let cache = iterator_cache();
+        let mut index: HashMap<K, Vec<u64>> = HashMap::new();
+        for id in self.entity_ids() {
+            let owned = with_resolve_cache(&cache, || self.get_owned(id))?;
+            index.entry(key(&owned)).or_default().push(id);
+        }
+        Ok(index)
+    }
+}
+
+/// Resolve entities of [EntityTable] on threads of rayon, enabled by `rayon` feature
+///
+/// This is implemented for all tables, e.g. `ParEntityTable::<AHolder>::par_owned_iter(&tables)`.
+/// The iterator is indexed in ascending order of ids same as [EntityTable::owned_iter],
+/// and thus `collect::<Vec<_>>()` keeps the order.
+///
+/// Each instance is resolved with [with_lenient_references]
+/// and [with_deserialize_options] of the calling thread.
+/// Entities referred from several items are resolved once for each job of rayon,
+/// instead of the [ResolveCache] of [with_resolve_cache].
+#[cfg(feature = "rayon")]
+pub trait ParEntityTable<T: Holder<Table = Self>>: EntityTable<T> + Sync {
+    fn par_owned_iter(
+        &self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = Result<T::Owned>> + '_;
+}
+
+#[cfg(feature = "rayon")]
+impl<T, Table> ParEntityTable<T> for Table
+where
+    T: Holder<Table = Table>,
+    T::Owned: Send,
+    Table: EntityTable<T> + Sync,
+{
+    fn par_owned_iter(
+        &self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = Result<T::Owned>> + '_ {
+        use rayon::prelude::*;
+        let modes = ThreadModes::current();
+        self.entity_ids()
+            .into_par_iter()
+            .map_init(ResolveCache::new, move |cache, id| {
+                modes.run(cache, || self.get_owned(id))
+            })
+    }
+}
+
+/// Thread-local modes of the calling thread applied to workers of [ParEntityTable]
+#[cfg(feature = "rayon")]
+struct ThreadModes {
+    lenient_references: bool,
+    deserialize_options: DeserializeOptions,
+}
+
+#[cfg(feature = "rayon")]
+impl ThreadModes {
+    fn current() -> Self {
+        ThreadModes {
+            lenient_references: lenient_references(),
+            deserialize_options: DESERIALIZE_OPTIONS.with(|current| current.borrow().clone()),
+        }
+    }
+
+    fn run<T>(&self, cache: &ResolveCache, f: impl FnOnce() -> T) -> T {
+        with_deserialize_options(&self.deserialize_options, || {
+            if self.lenient_references {
+                with_lenient_references(|| with_resolve_cache(cache, f))
+            } else {
+                with_resolve_cache(cache, f)
+            }
+        })
+    }
+}
+
+/// Trait for tables whose entity ids are unique across all fields
+///
+/// This is implemented by [ruststep_derive::TableInit].
+pub trait TableIds {
+    /// Whether an instance of `id` is in any field
+    fn contains_id(&self, id: u64) -> bool;
+
+    /// Largest id in all fields, `None` if the table is empty
+    fn max_id(&self) -> Option<u64>;
+
+    /// Allocator whose reserved ranges and ceiling are respected by [TableIds::next_id],
+    /// given by `#[table_init(ids)]`
+    fn id_allocator(&self) -> Option<&IdAllocator> {
+        None
+    }
+
+    /// Id for a new instance, next to [TableIds::max_id] and starting from 1
+    ///
+    /// Ids reserved in [TableIds::id_allocator] are skipped.
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::IdExhausted] if the largest id is [u64::MAX],
+    ///   or there is no free id below the ceiling of [TableIds::id_allocator]
+    fn next_id(&self) -> Result<u64> {
+        let start = match self.max_id() {
+            Some(max) => max
+                .checked_add(1)
+                .ok_or(Error::IdExhausted { ceiling: u64::MAX })?,
+            None => 1,
+        };
+        match self.id_allocator() {
+            Some(ids) => ids.first_free(start),
+            None => Ok(start),
+        }
+    }
+
+    /// Record that an instance of `id` is inserted into the field of the entity `keyword`,
+    /// e.g. `CARTESIAN_POINT`, for the index of `#[table_init(index)]`
+    fn register_id(&mut self, _id: u64, _keyword: &str) {}
+
+    /// Call `f` with the id of each instance, the name of its attribute, and the id referred by the attribute,
+    /// see [Holder::for_each_reference]
+    fn for_each_reference(&self, _f: &mut dyn FnMut(u64, &'static str, u64)) {}
+
+    /// References to entities which are not in the tables, in the order of referring ids
+    fn validate_references(&self) -> Vec<DanglingRef> {
+        let mut dangling = Vec::new();
+        self.for_each_reference(&mut |from, attribute, to| {
+            if !self.contains_id(to) {
+                dangling.push(DanglingRef {
+                    from,
+                    attribute: attribute.to_string(),
+                    to,
+                });
+            }
+        });
+        dangling.sort_by_key(|r| r.from);
+        dangling
+    }
+}
+
+/// Reference to an entity not in the tables, found by [TableIds::validate_references]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DanglingRef {
+    /// Id of the instance referring
+    pub from: u64,
+    /// Name of the attribute of the instance, see [Holder::attr_names]
+    pub attribute: String,
+    /// Id of the entity not found
+    pub to: u64,
+}
+
+/// [Holder::for_each_reference] for each holder of a table, used by [ruststep_derive::TableInit]
+#[doc(hidden)]
+pub fn table_references<T: Holder>(
+    table: &HashMap<u64, T>,
+    f: &mut dyn FnMut(u64, &'static str, u64),
+) {
+    for (id, holder) in table {
+        holder.for_each_reference(&mut |index, to| {
+            f(*id, T::attr_names().get(index).copied().unwrap_or(""), to)
+        });
+    }
+}
+
+/// Value computed at most once from other fields of an owned struct, e.g. a `DERIVE` attribute
+///
+/// This is used by espr with `#[holder(cache)]`, and is not a part of the value of the struct,
+/// i.e. it is equal to any other and is not written back. Call [Memo::clear] after modifying
+/// the fields it is computed from.
+///
+/// ```
+/// use ruststep::tables::Memo;
+///
+/// let memo = Memo::default();
+/// assert_eq!(*memo.get_or_init(|| 1), 1);
+/// // Not computed again
+/// assert_eq!(*memo.get_or_init(|| 2), 1);
+/// assert_eq!(memo, Memo::default());
+/// ```
+#[derive(Clone)]
+pub struct Memo<T>(std::sync::OnceLock<T>);
+
+impl<T> Default for Memo<T> {
+    fn default() -> Self {
+        Memo(std::sync::OnceLock::new())
+    }
+}
+
+impl<T> Memo<T> {
+    /// Cached value, or the value computed by `f`
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        self.0.get_or_init(f)
+    }
+
+    /// Discard the cached value to compute it again
+    pub fn clear(&mut self) {
+        self.0.take();
+    }
+}
+
+impl<T> PartialEq for Memo<T> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl<T> fmt::Debug for Memo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Memo")
+    }
+}
+
+/// Fallible `[T; N]::map`, used by [ruststep_derive::Holder] for place holders in arrays
+#[doc(hidden)]
+pub fn try_map_array<T, U, const N: usize>(
+    array: [T; N],
+    f: impl FnMut(T) -> Result<U>,
+) -> Result<[U; N]> {
+    let mapped = array.into_iter().map(f).collect::<Result<Vec<U>>>()?;
+    match mapped.try_into() {
+        Ok(mapped) => Ok(mapped),
+        Err(_) => unreachable!("length is kept by map"),
+    }
+}
+
+/// Trait for converting an owned struct back into its holder, the inverse of [IntoOwned]
+///
+/// This is implemented by [ruststep_derive::Holder] with `#[holder(generate_into_holder)]`.
+/// Entities referred from the owned struct are inserted into the table,
+/// and the holder refers them by [PlaceHolder::Ref].
+pub trait IntoHolder: Sized {
+    type Holder: IntoOwned<Owned = Self>;
+
+    /// Convert into the holder, inserting referred entities into `table`
+    fn into_holder(self, table: &mut <Self::Holder as IntoOwned>::Table) -> Result<Self::Holder>;
+
+    /// Convert into a value of a place holder field
+    ///
+    /// An entity is inserted into `table` with [TableIds::next_id] and referred by its id.
+    /// Other values are kept as [PlaceHolder::Owned].
+    fn into_place_holder(
+        self,
+        table: &mut <Self::Holder as IntoOwned>::Table,
+    ) -> Result<PlaceHolder<Self::Holder>> {
+        Ok(PlaceHolder::Owned(self.into_holder(table)?))
+    }
+}
+
+/// Trait for writing a value as a parameter of an exchange structure, the inverse of deserialization
+///
+/// - `None` of an OPTIONAL attribute is written as `$`
+/// - [PlaceHolder::Ref] is written as the reference, e.g. `#1`
+/// - BOOLEAN and LOGICAL are written as enumerations, e.g. `.T.`
+///
+/// ```
+/// use ruststep::{ast::*, tables::*};
+///
+/// assert_eq!(Some(1.5).to_parameter(), Parameter::real(1.5));
+/// assert_eq!(None::<f64>.to_parameter(), Parameter::NotProvided);
+/// assert_eq!(
+///     vec![true, false].to_parameter().to_string(),
+///     "(.T.,.F.)"
+/// );
+/// ```
+pub trait ToParameter {
+    fn to_parameter(&self) -> Parameter;
+}
+
+impl ToParameter for f64 {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::real(*self)
+    }
+}
+
+impl ToParameter for i64 {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::Integer(*self)
+    }
+}
+
+impl ToParameter for String {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::String(self.clone())
+    }
+}
+
+impl ToParameter for bool {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::Enumeration(if *self { "T" } else { "F" }.to_string())
+    }
+}
+
+impl ToParameter for Logical {
+    fn to_parameter(&self) -> Parameter {
+        let value = match self {
+            Logical::True => "T",
+            Logical::False => "F",
+            Logical::Unknown => "U",
+        };
+        Parameter::Enumeration(value.to_string())
+    }
+}
+
+impl ToParameter for Binary {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::Binary(self.clone())
+    }
+}
+
+impl<T: ToParameter> ToParameter for Wrapped<T> {
+    fn to_parameter(&self) -> Parameter {
+        match &self.keyword {
+            Some(keyword) => Parameter::Typed {
+                keyword: keyword.clone(),
+                parameter: Box::new(self.value.to_parameter()),
+            },
+            None => self.value.to_parameter(),
+        }
+    }
+}
+
+impl ToParameter for Parameter {
+    fn to_parameter(&self) -> Parameter {
+        self.clone()
+    }
+}
+
+impl<T: ToParameter> ToParameter for Option<T> {
+    fn to_parameter(&self) -> Parameter {
+        match self {
+            Some(value) => value.to_parameter(),
+            None => Parameter::NotProvided,
+        }
+    }
+}
+
+impl<T: ToParameter> ToParameter for Vec<T> {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::List(self.iter().map(ToParameter::to_parameter).collect())
+    }
+}
+
+impl<T: ToParameter, const N: usize> ToParameter for [T; N] {
+    fn to_parameter(&self) -> Parameter {
+        Parameter::List(self.iter().map(ToParameter::to_parameter).collect())
+    }
+}
+
+impl<T: ToParameter> ToParameter for Box<T> {
+    fn to_parameter(&self) -> Parameter {
+        self.as_ref().to_parameter()
+    }
+}
+
+impl<T: ToParameter> ToParameter for PlaceHolder<T> {
+    fn to_parameter(&self) -> Parameter {
+        match self {
+            PlaceHolder::Ref(name) => Parameter::Ref(name.clone()),
+            PlaceHolder::Owned(owned) => owned.to_parameter(),
+        }
+    }
+}
+
+/// Trait for writing a holder as a record of an entity instance
+///
+/// This is implemented by [ruststep_derive::Holder] with `#[holder(generate_to_record)]`,
+/// where the parameters are the attributes in the EXPRESS declaration order
+/// including the inherited ones.
+pub trait ToRecord {
+    fn to_record(&self) -> Record;
+}
+
+impl<T: ToRecord> ToRecord for Box<T> {
+    fn to_record(&self) -> Record {
+        self.as_ref().to_record()
+    }
+}
+
+#[doc(hidden)]
+pub fn instances<T: ToRecord>(
+    table: &HashMap<u64, T>,
+) -> impl Iterator<Item = EntityInstance> + '_ {
+    table.iter().map(|(id, holder)| EntityInstance::Simple {
+        id: *id,
+        record: holder.to_record(),
+    })
+}
+
+#[doc(hidden)]
+pub fn data_section(instances: impl Iterator<Item = EntityInstance>) -> DataSection {
+    let mut entities: Vec<_> = instances.collect();
+    entities.sort_by_key(EntityInstance::id);
+    DataSection {
+        meta: Vec::new(),
+        entities,
+    }
+}
+
+#[doc(hidden)]
+pub fn step_string(header: &Header, data: DataSection) -> String {
+    Exchange {
+        header: header.to_records(),
+        anchor: Vec::new(),
+        reference: Vec::new(),
+        data: vec![data],
+        signature: Vec::new(),
+    }
+    .to_string()
+}
+
+/// Every data section is loaded by [TableInit::from_data_sections], see [crate::capabilities]
+pub(crate) const CAPABILITIES: &[(Feature, Support)] =
+    &[(Feature::MultipleDataSections, Support::Supported)];
+
+/// Complex entity instance skipped while populating tables, see [TableInit::append_data_section_with_report]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnplacedComplex {
+    pub id: u64,
+    /// Keywords of the partial records as written, e.g. `["LENGTH_UNIT", "NAMED_UNIT", "SI_UNIT"]`
+    pub keywords: Vec<String>,
 }
 
 /// Create Table from [DataSection]
 pub trait TableInit: Default {
-    fn append_data_section(&mut self, section: &DataSection) -> Result<()>;
+    /// Insert an entity instance into the table
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::UnknownEntityName] if the keyword is not an entity of the schema
+    /// - [Error::UnplacedComplexEntity] for a complex entity instance,
+    ///   which is not mapped to holders yet
+    /// - [Error::AbstractEntityInstance] for a simple record of an `ABSTRACT SUPERTYPE` entity
+    ///
+    fn append_entity_instance(&mut self, instance: &EntityInstance) -> Result<()>;
+
+    /// Insert entity instances of the data section into the table
+    ///
+    /// Complex entity instances which cannot be placed in the table are skipped,
+    /// and returned with their keywords.
+    fn append_data_section_with_report(
+        &mut self,
+        section: &DataSection,
+    ) -> Result<Vec<UnplacedComplex>> {
+        let mut unplaced = Vec::new();
+        for instance in &section.entities {
+            match self.append_entity_instance(instance) {
+                Err(Error::UnplacedComplexEntity { id, keywords }) => {
+                    unplaced.push(UnplacedComplex { id, keywords })
+                }
+                result => result?,
+            }
+        }
+        Ok(unplaced)
+    }
+
+    /// Same as [TableInit::append_data_section_with_report], but skipped complex entity instances are not returned
+    fn append_data_section(&mut self, section: &DataSection) -> Result<()> {
+        self.append_data_section_with_report(section).map(|_| ())
+    }
 
     fn from_data_section(section: &DataSection) -> Result<Self> {
         let mut table = Self::default();
@@ -153,6 +1158,15 @@ pub trait TableInit: Default {
         Ok(table)
     }
 
+    /// Same as [TableInit::from_data_section], and returns skipped complex entity instances
+    fn from_data_section_with_report(
+        section: &DataSection,
+    ) -> Result<(Self, Vec<UnplacedComplex>)> {
+        let mut table = Self::default();
+        let unplaced = table.append_data_section_with_report(section)?;
+        Ok((table, unplaced))
+    }
+
     fn from_data_sections(sections: &[DataSection]) -> Result<Self> {
         let mut table = Self::default();
         for section in sections {
@@ -160,50 +1174,609 @@ pub trait TableInit: Default {
         }
         Ok(table)
     }
+
+    /// Create table only from the data sections selected by [SectionSelector]
+    ///
+    /// Errors
+    /// -------
+    /// - [Error::EntityInUnloadedSection] if an instance in selected sections
+    ///   refers an instance only defined in an unselected section
+    ///
+    fn from_selected_data_sections(
+        sections: &[DataSection],
+        selector: &SectionSelector,
+    ) -> Result<Self> {
+        let (selected, unselected): (Vec<_>, Vec<_>) = sections
+            .iter()
+            .enumerate()
+            .partition(|(index, section)| selector.is_selected(*index, section));
+
+        let loaded: HashSet<u64> = selected
+            .iter()
+            .flat_map(|(_, section)| section.entities.iter().map(EntityInstance::id))
+            .collect();
+        let unloaded: HashMap<u64, usize> = unselected
+            .iter()
+            .flat_map(|(index, section)| {
+                section
+                    .entities
+                    .iter()
+                    .map(move |instance| (instance.id(), *index))
+            })
+            .collect();
+
+        let mut table = Self::default();
+        for (_, section) in selected {
+            for instance in &section.entities {
+                for id in instance.references() {
+                    if loaded.contains(&id) {
+                        continue;
+                    }
+                    if let Some(section) = unloaded.get(&id) {
+                        return Err(Error::EntityInUnloadedSection {
+                            id,
+                            section: *section,
+                        });
+                    }
+                }
+            }
+            table.append_data_section(section)?;
+        }
+        Ok(table)
+    }
+}
+
+/// Select data sections to be loaded into tables by [TableInit::from_selected_data_sections]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SectionSelector {
+    /// Load all sections
+    #[default]
+    All,
+    /// Load sections at the indices in the exchange structure, starting from 0
+    ByIndex(Vec<usize>),
+    /// Load sections whose [DataSection::name] is one of these names
+    ByName(Vec<String>),
+}
+
+impl SectionSelector {
+    pub fn is_selected(&self, index: usize, section: &DataSection) -> bool {
+        match self {
+            SectionSelector::All => true,
+            SectionSelector::ByIndex(indices) => indices.contains(&index),
+            SectionSelector::ByName(names) => section
+                .name()
+                .map(|name| names.iter().any(|n| n == name))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Holder type and entity id
+type ResolveKey = (TypeId, u64);
+
+/// Cache of owned values shared among [get_owned] calls, enabled by [with_resolve_cache]
+///
+/// Entries are keyed by the holder type and the entity id, and cloned when hit.
+/// This saves resolving sub-structures referred from many entities, e.g. a common context.
+/// The cache is not invalidated automatically, and must be cleared if tables are modified.
+#[derive(Clone, Default)]
+pub struct ResolveCache(Rc<RefCell<HashMap<ResolveKey, Box<dyn Any>>>>);
+
+impl fmt::Debug for ResolveCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResolveCache")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl ResolveCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.borrow().is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear()
+    }
+}
+
+/// Call `f` with `cache` used by [get_owned] called in it
+///
+/// The cache is kept after `f` returns, and thus can be shared among several calls.
+pub fn with_resolve_cache<T>(cache: &ResolveCache, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<ResolveCache>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let cache = self.0.take();
+            RESOLVE_STATE.with(|state| state.borrow_mut().cache = cache);
+        }
+    }
+    let _restore =
+        Restore(RESOLVE_STATE.with(|state| state.borrow_mut().cache.replace(cache.clone())));
+    f()
+}
+
+/// Depth of nested [get_owned] calls to start deferring, see [get_owned]
+const RESOLVE_DEPTH_LIMIT: usize = 64;
+
+#[derive(Default)]
+struct ResolveState {
+    cache: Option<ResolveCache>,
+    /// Entities being resolved from the outermost [get_owned] call
+    active: Vec<ResolveKey>,
+    /// Entity deferred at the depth limit,
+    /// and the index in `active` of the call resolving it instead
+    deferred: Option<(usize, u64)>,
+    /// Owned values of deferred entities, taken once unless `true`
+    deferred_results: HashMap<ResolveKey, (Box<dyn Any>, bool)>,
+    /// Deferred entities resolved at least once in the outermost call
+    deferred_resolved: HashSet<ResolveKey>,
 }
 
+thread_local! {
+    static RESOLVE_STATE: RefCell<ResolveState> = RefCell::new(ResolveState::default());
+}
+
+fn lookup_resolved<Owned: Clone + 'static>(key: ResolveKey) -> Option<Owned> {
+    RESOLVE_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if let Some(cache) = &state.cache {
+            if let Some(owned) = cache.0.borrow().get(&key) {
+                return owned.downcast_ref::<Owned>().cloned();
+            }
+        }
+        match state.deferred_results.get(&key) {
+            Some((owned, true)) => owned.downcast_ref::<Owned>().cloned(),
+            Some((_, false)) => {
+                let (owned, _) = state.deferred_results.remove(&key).unwrap();
+                owned.downcast::<Owned>().ok().map(|owned| *owned)
+            }
+            None => None,
+        }
+    })
+}
+
+fn cache_resolved<Owned: Clone + 'static>(key: ResolveKey, owned: &Owned) {
+    RESOLVE_STATE.with(|state| {
+        if let Some(cache) = &state.borrow().cache {
+            cache.0.borrow_mut().insert(key, Box::new(owned.clone()));
+        }
+    })
+}
+
+fn store_deferred<Owned: 'static>(key: ResolveKey, owned: Owned) {
+    RESOLVE_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        // Keep if the value is required again after taken
+        let keep = !state.deferred_resolved.insert(key);
+        state.deferred_results.insert(key, (Box::new(owned), keep));
+    })
+}
+
+/// Get owned value of the entity in `map`, resolving references in it
+///
+/// This is called through [EntityTable::get_owned] generated by [ruststep_derive::Holder].
+///
+/// - Owned values are cached in [ResolveCache] if enabled by [with_resolve_cache].
+/// - Nested calls do not grow the call stack without limit.
+///   When the nesting reaches a fixed depth, the entity is deferred to the outermost call
+///   resolving the same holder type, which resolves it first and then retries its own entity
+///   using the deferred value. Thus a chain of references tens of thousands long
+///   is resolved in linear time without stack overflow.
+///
+/// Errors
+/// -------
+/// - [Error::UnknownEntity] if the entity is not found in `map`
+/// - [Error::CyclicReference] if the entity refers itself transitively
+///
+#[doc(hidden)]
 pub fn get_owned<T, Table>(table: &Table, map: &HashMap<u64, T>, entity_id: u64) -> Result<T::Owned>
 where
     T: Holder<Table = Table>,
+    T::Owned: Clone + 'static,
     Table: EntityTable<T>,
 {
-    match map.get(&entity_id) {
-        Some(holder) => holder.clone().into_owned(table),
-        None => Err(Error::UnknownEntity(entity_id)),
+    let key = (TypeId::of::<T>(), entity_id);
+    if let Some(owned) = lookup_resolved(key) {
+        return Ok(owned);
+    }
+    if !map.contains_key(&entity_id) {
+        return Err(Error::UnknownEntity(entity_id));
     }
+
+    // Reset the state when the outermost call returns, even if it panics
+    struct Outermost;
+    impl Drop for Outermost {
+        fn drop(&mut self) {
+            RESOLVE_STATE.with(|state| {
+                let mut state = state.borrow_mut();
+                state.active.clear();
+                state.deferred = None;
+                state.deferred_results.clear();
+                state.deferred_resolved.clear();
+            });
+        }
+    }
+
+    let index = RESOLVE_STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        if state.active.contains(&key) {
+            return Err(Error::CyclicReference(entity_id));
+        }
+        if state.active.len() >= RESOLVE_DEPTH_LIMIT {
+            if let Some(handler) = state.active.iter().position(|(ty, _)| *ty == key.0) {
+                state.deferred = Some((handler, entity_id));
+                return Err(Error::ResolutionDeferred);
+            }
+        }
+        Ok(state.active.len())
+    })?;
+    let _outermost = if index == 0 { Some(Outermost) } else { None };
+
+    // Entities to be resolved by this call. The last one is resolved first.
+    let mut pending = vec![entity_id];
+    while let Some(&id) = pending.last() {
+        let key = (key.0, id);
+        let holder = map.get(&id).ok_or(Error::UnknownEntity(id))?;
+        RESOLVE_STATE.with(|state| state.borrow_mut().active.push(key));
+        let result = holder
+            .clone()
+            .into_owned(table)
+            .map_err(|e| e.in_instance(id, T::name()));
+        RESOLVE_STATE.with(|state| state.borrow_mut().active.pop());
+        match result {
+            Ok(owned) => {
+                pending.pop();
+                cache_resolved(key, &owned);
+                if pending.is_empty() {
+                    return Ok(owned);
+                }
+                store_deferred(key, owned);
+            }
+            Err(Error::ResolutionDeferred) => {
+                let deferred = RESOLVE_STATE.with(|state| {
+                    let mut state = state.borrow_mut();
+                    match state.deferred {
+                        Some((handler, id)) if handler == index => {
+                            state.deferred = None;
+                            Some(id)
+                        }
+                        _ => None,
+                    }
+                });
+                match deferred {
+                    Some(id) if pending.contains(&id) => return Err(Error::CyclicReference(id)),
+                    Some(id) => pending.push(id),
+                    // Resolved by an outer call
+                    None => return Err(Error::ResolutionDeferred),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!()
+}
+
+/// Ids of a table in ascending order, used for [EntityTable::entity_ids]
+#[doc(hidden)]
+pub fn entity_ids<T>(map: &HashMap<u64, T>) -> Vec<u64> {
+    let mut ids: Vec<u64> = map.keys().copied().collect();
+    ids.sort_unstable();
+    ids
 }
 
+#[doc(hidden)]
 pub fn owned_iter<'table, T, Table>(
     table: &'table Table,
     map: &'table HashMap<u64, T>,
 ) -> Box<dyn Iterator<Item = Result<T::Owned>> + 'table>
 where
     T: Holder<Table = Table>,
+    T::Owned: Clone + 'static,
     Table: EntityTable<T>,
 {
+    let cache = iterator_cache();
     Box::new(
-        map.values()
-            .cloned()
-            .map(move |value| value.into_owned(table)),
+        entity_ids(map)
+            .into_iter()
+            .map(move |id| with_resolve_cache(&cache, || get_owned(table, map, id))),
     )
 }
 
+/// Resolve instances of `ids` by `get` in the order of ids,
+/// used by `Tables::any_iter` generated by espr and [EntityTable::owned_iter] of a SELECT type
+///
+/// The cache is shared as [owned_iter].
+#[doc(hidden)]
+pub fn any_iter<'table, T: 'table>(
+    ids: impl Iterator<Item = u64>,
+    get: impl Fn(u64) -> Result<T> + 'table,
+) -> Box<dyn Iterator<Item = Result<T>> + 'table> {
+    let mut ids: Vec<u64> = ids.collect();
+    ids.sort_unstable();
+    let cache = iterator_cache();
+    Box::new(
+        ids.into_iter()
+            .map(move |id| with_resolve_cache(&cache, || get(id))),
+    )
+}
+
+/// Cache of [with_resolve_cache] if enabled, or a new one for an iterator.
+/// The cache of the iterator is never outdated since tables are borrowed while iterating.
+fn iterator_cache() -> ResolveCache {
+    RESOLVE_STATE
+        .with(|state| state.borrow().cache.clone())
+        .unwrap_or_default()
+}
+
+/// Instance failed to be resolved, collected by `Tables::check_all` generated by espr
+#[derive(Debug)]
+pub struct EntityError {
+    pub id: u64,
+    /// Name of the entity of the table, e.g. `ADVANCED_FACE`
+    pub keyword: String,
+    /// Error returned by [EntityTable::get_owned]
+    pub error: Error,
+}
+
+impl fmt::Display for EntityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error.context() {
+            Some(ErrorContext { id: Some(id), .. }) if *id == self.id => {
+                write!(f, "{}", self.error)
+            }
+            _ => write!(f, "in #{} ({}): {}", self.id, self.keyword, self.error),
+        }
+    }
+}
+
+/// Number of failed instances for each entity, followed by each [EntityError] in a line
+///
+/// ```
+/// use ruststep::{error::Error, tables::*};
+///
+/// let errors = [
+///     EntityError { id: 3, keyword: "EDGE".to_string(), error: Error::UnknownEntity(9) },
+///     EntityError { id: 4, keyword: "FACE".to_string(), error: Error::CyclicReference(4) },
+/// ];
+/// assert_eq!(
+///     ErrorSummary(&errors).to_string(),
+///     "2 instances failed: EDGE (1), FACE (1)\n\
+///      in #3 (EDGE): Lookup failed for #9\n\
+///      in #4 (FACE): Reference to #4 is cyclic\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorSummary<'a>(pub &'a [EntityError]);
+
+impl fmt::Display for ErrorSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut counts = std::collections::BTreeMap::new();
+        for error in self.0 {
+            *counts.entry(error.keyword.as_str()).or_insert(0) += 1;
+        }
+        let counts: Vec<String> = counts
+            .into_iter()
+            .map(|(keyword, count)| format!("{} ({})", keyword, count))
+            .collect();
+        writeln!(
+            f,
+            "{} instances failed: {}",
+            self.0.len(),
+            counts.join(", ")
+        )?;
+        for error in self.0 {
+            writeln!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve every holder of a table, and returns failed ones. Owned values are dropped immediately.
+#[doc(hidden)]
+pub fn table_errors<'table, T, Table>(
+    table: &'table Table,
+    map: &'table HashMap<u64, T>,
+) -> impl Iterator<Item = EntityError> + 'table
+where
+    T: Holder<Table = Table>,
+    T::Owned: Clone + 'static,
+    Table: EntityTable<T>,
+{
+    map.keys().filter_map(move |id| {
+        let error = get_owned(table, map, *id).err()?;
+        Some(EntityError {
+            id: *id,
+            keyword: T::name().to_string(),
+            error,
+        })
+    })
+}
+
+/// Collect errors of [table_errors] in the order of ids
+#[doc(hidden)]
+pub fn sorted_errors(errors: impl Iterator<Item = EntityError>) -> Vec<EntityError> {
+    let mut errors: Vec<_> = errors.collect();
+    errors.sort_by_key(|error| error.id);
+    errors
+}
+
 /// Helper function to implement TableInit trait
-pub fn insert_record<'de, T: de::Deserialize<'de>>(
+///
+/// Parameters are checked by [Holder::attr_kinds] if [DeserializeOptions::check_attribute_kinds] is enabled.
+#[doc(hidden)]
+pub fn insert_record<'de, T: Holder + de::Deserialize<'de>>(
     table: &mut HashMap<u64, T>,
     id: u64,
     record: &Record,
 ) -> crate::error::Result<()> {
-    if table
-        .insert(id, de::Deserialize::deserialize(record)?)
-        .is_some()
-    {
+    if T::is_abstract() {
+        return Err(
+            Error::AbstractEntityInstance(T::name().to_string()).in_instance(id, &record.name)
+        );
+    }
+    check_attribute_kinds::<T>(record)?;
+    let holder =
+        de::Deserialize::deserialize(record).map_err(|e: Error| e.in_instance(id, &record.name))?;
+    if table.insert(id, holder).is_some() {
         Err(Error::DuplicatedEntity(id))
     } else {
         Ok(())
     }
 }
 
+/// Deserialize a holder from a [Record] without creating tables
+///
+/// - The keyword of the record is compared with [Holder::name] case-insensitively.
+///   User-defined keywords never match.
+/// - The number of parameters must be same as [Holder::attr_len], or not less than it if [Holder::has_rest].
+/// - References in the record are kept as [PlaceHolder::Ref].
+/// - Records of `ABSTRACT SUPERTYPE` entities are rejected, see [Holder::is_abstract].
+///
+/// This is called through `XxxHolder::parse_record` generated by [ruststep_derive::Holder].
+#[doc(hidden)]
+pub fn parse_record<T>(record: &Record) -> Result<T>
+where
+    T: Holder + de::DeserializeOwned,
+{
+    if record.user_defined || !record.name.eq_ignore_ascii_case(T::name()) {
+        return Err(Error::KeywordMismatch {
+            expected: T::name().to_string(),
+            found: record.keyword(),
+        });
+    }
+    if T::is_abstract() {
+        return Err(Error::AbstractEntityInstance(T::name().to_string()));
+    }
+    if let Parameter::List(parameters) = &record.parameter {
+        let accepted = if T::has_rest() {
+            parameters.len() >= T::attr_len()
+        } else {
+            parameters.len() == T::attr_len()
+        };
+        if !accepted {
+            return Err(Error::DeserializeFailed(format!(
+                "{} takes {} parameters, but {} given",
+                T::name(),
+                T::attr_len(),
+                parameters.len()
+            )));
+        }
+    }
+    T::deserialize(crate::ast::de::RecordDeserializer::new(
+        T::name(),
+        &record.parameter,
+    ))
+}
+
+/// Deserialize a holder from an [EntityInstance] without creating tables
+///
+/// For a complex entity instance, the partial record whose keyword matches to [Holder::name]
+/// is deserialized by [parse_record].
+#[doc(hidden)]
+pub fn parse_instance<T>(instance: &EntityInstance) -> Result<T>
+where
+    T: Holder + de::DeserializeOwned,
+{
+    match instance {
+        EntityInstance::Simple { record, .. } => parse_record(record),
+        EntityInstance::Complex { subsuper, .. } => {
+            let record = subsuper
+                .into_iter()
+                .find(|record| !record.user_defined && record.name.eq_ignore_ascii_case(T::name()))
+                .ok_or_else(|| Error::KeywordMismatch {
+                    expected: T::name().to_string(),
+                    found: subsuper
+                        .into_iter()
+                        .map(Record::keyword)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                })?;
+            parse_record(record)
+        }
+    }
+}
+
+/// Values of constants referred as `#NAME` or `@NAME` in records, kept in tables by [TableConstants]
+///
+/// Constants are defined in EXPRESS schemas, and their values are not written in exchange structures.
+/// ISO 10303-21 itself defines no constants, so the values must be supplied by users.
+/// Names are compared case-insensitively.
+///
+/// ```
+/// use ruststep::{ast::*, tables::*};
+/// use std::str::FromStr;
+///
+/// let mut constants = Constants::new();
+/// constants.insert_value("PI", Parameter::real(std::f64::consts::PI));
+/// constants.insert_entity(
+///     "ORIGIN",
+///     Parameter::from_str("CARTESIAN_POINT(('', (0.0, 0.0, 0.0)))").unwrap(),
+/// );
+/// assert_eq!(constants.len(), 2);
+/// assert!(constants.get(&Name::ConstantValue("pi".to_string())).is_some());
+/// // Entity and value constants have separated namespaces
+/// assert!(constants.get(&Name::ConstantValue("ORIGIN".to_string())).is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Constants {
+    entities: HashMap<String, Parameter>,
+    values: HashMap<String, Parameter>,
+}
+
+impl Constants {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the value of a constant entity, e.g. `#ORIGIN`. Returns the previous value.
+    pub fn insert_entity(&mut self, name: &str, value: Parameter) -> Option<Parameter> {
+        self.entities.insert(name.to_uppercase(), value)
+    }
+
+    /// Register the value of a constant value, e.g. `@PI`. Returns the previous value.
+    pub fn insert_value(&mut self, name: &str, value: Parameter) -> Option<Parameter> {
+        self.values.insert(name.to_uppercase(), value)
+    }
+
+    /// Value of a constant name. Always `None` for [Name::Entity] and [Name::Value].
+    pub fn get(&self, name: &Name) -> Option<&Parameter> {
+        match name {
+            Name::ConstantEntity(name) => self.entities.get(&name.to_uppercase()),
+            Name::ConstantValue(name) => self.values.get(&name.to_uppercase()),
+            Name::Entity(_) | Name::Value(_) => None,
+        }
+    }
+
+    /// Number of registered constants
+    pub fn len(&self) -> usize {
+        self.entities.len() + self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty() && self.values.is_empty()
+    }
+}
+
+/// Tables holding [Constants] to resolve constant names in [PlaceHolder::into_owned]
+///
+/// This is implemented by [ruststep_derive::TableInit] for tables with a `#[table_init(constants)]` field.
+/// Hand-written tables without constants implement it by an empty `impl`.
+pub trait TableConstants {
+    /// Constants registered in the tables, `None` if the tables do not keep constants
+    fn constants(&self) -> Option<&Constants> {
+        None
+    }
+}
+
 /// Owned value or reference through entity/value id
 #[derive(Debug, Clone, PartialEq)]
 pub enum PlaceHolder<T> {
@@ -211,23 +1784,45 @@ pub enum PlaceHolder<T> {
     Owned(T),
 }
 
-impl<T: Holder> IntoOwned for PlaceHolder<T>
+impl<T> PlaceHolder<T> {
+    /// Convert the owned value by `f`, and keep the reference as is
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> PlaceHolder<U> {
+        match self {
+            PlaceHolder::Ref(name) => PlaceHolder::Ref(name),
+            PlaceHolder::Owned(owned) => PlaceHolder::Owned(f(owned)),
+        }
+    }
+}
+
+impl<T: Holder + WithVisitor + de::DeserializeOwned> IntoOwned for PlaceHolder<T>
 where
-    T::Table: EntityTable<T>,
+    T::Table: EntityTable<T> + TableConstants,
 {
     type Owned = T::Owned;
     type Table = T::Table;
     /// Get owned value, or look up entity table and clone it for a reference.
     ///
+    /// A constant name is replaced by its value registered in [TableConstants::constants] of the table.
+    ///
     /// Errors
     /// -------
     /// - if table lookup failed, i.e. unknown entity id not registered in the table
+    /// - [Error::UnknownConstant] if the constant is not registered
+    /// - [Error::UnsupportedValueInstance] for a value instance, e.g. `@1`
     ///
     fn into_owned(self, table: &Self::Table) -> Result<T::Owned> {
         match self {
             PlaceHolder::Ref(id) => match id {
                 Name::Entity(id) => table.get_owned(id),
-                _ => unimplemented!("ENTITY is only supported now"),
+                Name::Value(id) => Err(Error::UnsupportedValueInstance(id)),
+                constant => {
+                    let value = table
+                        .constants()
+                        .and_then(|constants| constants.get(&constant))
+                        .cloned()
+                        .ok_or_else(|| Error::UnknownConstant(constant.to_string()))?;
+                    PlaceHolder::<T>::deserialize(&value)?.into_owned(table)
+                }
             },
             PlaceHolder::Owned(a) => a.into_owned(table),
         }
@@ -246,11 +1841,58 @@ impl<T> From<Name> for PlaceHolder<T> {
     }
 }
 
+/// Form of [PlaceHolder] in binary formats, e.g. bincode for [crate::cache::TablesCache]
+#[derive(Serialize, Deserialize)]
+enum BinaryPlaceHolder<S, T> {
+    Entity(u64),
+    Value(u64),
+    ConstantEntity(S),
+    ConstantValue(S),
+    Owned(T),
+}
+
+impl<T: Serialize> Serialize for PlaceHolder<T> {
+    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let binary = match self {
+            PlaceHolder::Ref(Name::Entity(id)) => BinaryPlaceHolder::Entity(*id),
+            PlaceHolder::Ref(Name::Value(id)) => BinaryPlaceHolder::Value(*id),
+            PlaceHolder::Ref(Name::ConstantEntity(name)) => {
+                BinaryPlaceHolder::ConstantEntity(name.as_str())
+            }
+            PlaceHolder::Ref(Name::ConstantValue(name)) => {
+                BinaryPlaceHolder::ConstantValue(name.as_str())
+            }
+            PlaceHolder::Owned(owned) => BinaryPlaceHolder::Owned(owned),
+        };
+        binary.serialize(serializer)
+    }
+}
+
 impl<'de, T: Holder + WithVisitor + Deserialize<'de>> Deserialize<'de> for PlaceHolder<T> {
+    /// Read a parameter of the exchange structure,
+    /// or the form written by [Serialize] in binary formats.
     fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
     where
         D: de::Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return Ok(
+                match BinaryPlaceHolder::<String, T>::deserialize(deserializer)? {
+                    BinaryPlaceHolder::Entity(id) => PlaceHolder::Ref(Name::Entity(id)),
+                    BinaryPlaceHolder::Value(id) => PlaceHolder::Ref(Name::Value(id)),
+                    BinaryPlaceHolder::ConstantEntity(name) => {
+                        PlaceHolder::Ref(Name::ConstantEntity(name))
+                    }
+                    BinaryPlaceHolder::ConstantValue(name) => {
+                        PlaceHolder::Ref(Name::ConstantValue(name))
+                    }
+                    BinaryPlaceHolder::Owned(owned) => PlaceHolder::Owned(owned),
+                },
+            );
+        }
         deserializer.deserialize_tuple_struct(
             T::name(),
             T::attr_len(),
@@ -0,0 +1,352 @@
+//! Utilities for writing tests against generated holders
+//!
+//! This module is enabled by `testing` feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! ruststep = { version = "*", features = ["testing"] }
+//! ```
+//!
+//! - [assert_holder_eq!](crate::assert_holder_eq) compares two values,
+//!   and shows line-by-line diff of their pretty-printed [Debug](std::fmt::Debug) output when they differ.
+//!   Attributes of holders which differ are also reported by their paths, e.g. `POINT.x`.
+//! - [record!](crate::record) and [param!](crate::param) parse literals into [Record] and [Parameter],
+//!   and panic with the location of parse error.
+//! - [fixture] and [fixture_exchange] load STEP files, and cache them in the process
+//!   to share them between tests.
+//!
+//! ```
+//! use ruststep::{assert_holder_eq, param, record, ast::*};
+//!
+//! let p = param!("(1.0, $, #5)");
+//! assert_eq!(
+//!     p,
+//!     Parameter::List(vec![
+//!         Parameter::real(1.0),
+//!         Parameter::NotProvided,
+//!         Parameter::Ref(Name::Entity(5)),
+//!     ])
+//! );
+//!
+//! let r = record!("#1 = FOO('x', 1.0);");
+//! assert_holder_eq!(r, record!("FOO('x', 1.0)"));
+//! ```
+
+use crate::{
+    ast::*,
+    tables::{Holder, TableInit},
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{self, Write},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Mutex, MutexGuard, OnceLock},
+};
+
+/// Assert two values are equal, and shows the diff of their pretty-printed `Debug` output if not
+///
+/// For [Holder]s, attributes which differ in [Holder::attr_value] are listed before the diff
+/// by the paths of the entity and attribute names, e.g. `POINT.x`.
+#[macro_export]
+macro_rules! assert_holder_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    #[allow(unused_imports)]
+                    use $crate::testing::{DiffDebug as _, DiffHolder as _};
+                    panic!(
+                        "assertion `left == right` failed\n{}",
+                        (&$crate::testing::Diff(left, right)).diff()
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Parse a literal into [Record], e.g. `record!("#1 = FOO('x', 1.0);")` or `record!("FOO('x', 1.0)")`
+#[macro_export]
+macro_rules! record {
+    ($input:expr) => {
+        $crate::testing::parse_record($input)
+    };
+}
+
+/// Parse a literal into [Parameter], e.g. `param!("(1.0, $, #5)")`
+#[macro_export]
+macro_rules! param {
+    ($input:expr) => {
+        $crate::testing::parse_parameter($input)
+    };
+}
+
+/// Line-by-line diff of pretty-printed `Debug` output
+///
+/// Common lines start with a space, and the lines only in `left` (`right`) start with `-` (`+`).
+pub fn diff_debug<T: fmt::Debug + ?Sized>(left: &T, right: &T) -> String {
+    diff_lines(&format!("{:#?}", left), &format!("{:#?}", right))
+}
+
+/// Attributes which differ in [Holder::attr_value] followed by [diff_debug]
+///
+/// Each attribute is reported by its path, e.g. `POINT.x: Real(1.0) != Real(2.0)`.
+/// References and elements of aggregates are not compared by [Holder::attr_value],
+/// and they are only shown in the diff of `Debug` output.
+pub fn diff_holder<T: Holder + fmt::Debug>(left: &T, right: &T) -> String {
+    let mut out = String::new();
+    for (index, name) in T::attr_names().iter().enumerate() {
+        let (l, r) = (left.attr_value(index), right.attr_value(index));
+        if l != r {
+            let show = |value: Option<_>| match value {
+                Some(value) => format!("{:?}", value),
+                None => "(unknown)".to_string(),
+            };
+            writeln!(out, "{}.{}: {} != {}", T::name(), name, show(l), show(r)).unwrap();
+        }
+    }
+    out + &diff_debug(left, right)
+}
+
+/// Pair of values compared in [assert_holder_eq!](crate::assert_holder_eq)
+///
+/// The macro calls `(&Diff(left, right)).diff()`, which is resolved into [DiffHolder]
+/// for holders, and falls back into [DiffDebug] for other values by auto-ref.
+#[doc(hidden)]
+pub struct Diff<'a, T: ?Sized>(pub &'a T, pub &'a T);
+
+#[doc(hidden)]
+pub trait DiffHolder {
+    fn diff(&self) -> String;
+}
+
+impl<T: Holder + fmt::Debug> DiffHolder for Diff<'_, T> {
+    fn diff(&self) -> String {
+        diff_holder(self.0, self.1)
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffDebug {
+    fn diff(&self) -> String;
+}
+
+impl<T: fmt::Debug + ?Sized> DiffDebug for &Diff<'_, T> {
+    fn diff(&self) -> String {
+        diff_debug(self.0, self.1)
+    }
+}
+
+fn diff_lines(left: &str, right: &str) -> String {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+
+    // lcs[i][j] is the length of longest common subsequence of left[i..] and right[j..]
+    let mut lcs = vec![vec![0_usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lcs[i][j] = if left[i] == right[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if left[i] == right[j] {
+            writeln!(out, " {}", left[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            writeln!(out, "-{}", left[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(out, "+{}", right[j]).unwrap();
+            j += 1;
+        }
+    }
+    for line in &left[i..] {
+        writeln!(out, "-{}", line).unwrap();
+    }
+    for line in &right[j..] {
+        writeln!(out, "+{}", line).unwrap();
+    }
+    out
+}
+
+/// Parse [Record] or simple [EntityInstance], used in [record!](crate::record)
+///
+/// Panics
+/// -------
+/// - Failed to parse the input. The message contains the location of parse error.
+/// - The input is a complex entity instance.
+///
+#[track_caller]
+pub fn parse_record(input: &str) -> Record {
+    let trimmed = input.trim();
+    let result = if trimmed.starts_with('#') {
+        EntityInstance::from_str(trimmed).map(|instance| match instance {
+            EntityInstance::Simple { record, .. } => record,
+            EntityInstance::Complex { .. } => {
+                panic!(
+                    "record! does not accept complex entity instance: `{}`",
+                    input
+                )
+            }
+        })
+    } else {
+        Record::from_str(trimmed)
+    };
+    match result {
+        Ok(record) => record,
+        Err(e) => panic!("Failed to parse record literal `{}`:\n{}", input, e),
+    }
+}
+
+/// Parse [Parameter], used in [param!](crate::param)
+///
+/// Panics
+/// -------
+/// - Failed to parse the input. The message contains the location of parse error.
+///
+#[track_caller]
+pub fn parse_parameter(input: &str) -> Parameter {
+    match Parameter::from_str(input) {
+        Ok(parameter) => parameter,
+        Err(e) => panic!("Failed to parse parameter literal `{}`:\n{}", input, e),
+    }
+}
+
+// Lock ignoring poison since a panic in another test should not break the cache
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Load an exchange structure from file. The result is cached in the process.
+///
+/// Panics
+/// -------
+/// - Failed to read or parse the file
+///
+#[track_caller]
+pub fn fixture_exchange(path: impl AsRef<Path>) -> Exchange {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, Exchange>>> = OnceLock::new();
+    let path = path.as_ref();
+    let mut cache = lock(CACHE.get_or_init(Default::default));
+    if let Some(exchange) = cache.get(path) {
+        return exchange.clone();
+    }
+    let input = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", path.display(), e));
+    let exchange = crate::parser::parse(&input)
+        .unwrap_or_else(|e| panic!("Failed to parse fixture {}:\n{}", path.display(), e));
+    cache.insert(path.to_path_buf(), exchange.clone());
+    exchange
+}
+
+/// Load tables from all data sections of a file. The result is cached in the process.
+///
+/// ```no_run
+/// # mod test_schema {
+/// #     #[derive(Debug, Clone, Default)]
+/// #     pub struct Tables;
+/// #     impl ruststep::tables::TableInit for Tables {
+/// #         fn append_entity_instance(&mut self, _: &ruststep::ast::EntityInstance) -> ruststep::error::Result<()> { Ok(()) }
+/// #     }
+/// # }
+/// let tables: test_schema::Tables = ruststep::testing::fixture("tests/steps/example.step");
+/// ```
+///
+/// Panics
+/// -------
+/// - Failed to read or parse the file
+/// - Failed to create tables
+///
+#[track_caller]
+pub fn fixture<T>(path: impl AsRef<Path>) -> T
+where
+    T: TableInit + Clone + Send + 'static,
+{
+    type Cache = HashMap<(PathBuf, TypeId), Box<dyn Any + Send>>;
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    let path = path.as_ref();
+    let key = (path.to_path_buf(), TypeId::of::<T>());
+    if let Some(tables) = lock(CACHE.get_or_init(Default::default)).get(&key) {
+        return tables
+            .downcast_ref::<T>()
+            .expect("Cache is keyed by TypeId")
+            .clone();
+    }
+    let exchange = fixture_exchange(path);
+    let tables = T::from_data_sections(&exchange.data)
+        .unwrap_or_else(|e| panic!("Failed to create tables from {}: {}", path.display(), e));
+    lock(CACHE.get_or_init(Default::default)).insert(key, Box::new(tables.clone()));
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+
+    #[test]
+    fn diff_format() {
+        #[derive(Debug)]
+        struct A {
+            #[allow(dead_code)]
+            x: f64,
+            #[allow(dead_code)]
+            y: f64,
+        }
+        let diff = super::diff_debug(&A { x: 1.0, y: 2.0 }, &A { x: 1.0, y: 3.0 });
+        assert_eq!(diff, " A {\n     x: 1.0,\n-    y: 2.0,\n+    y: 3.0,\n }\n");
+    }
+
+    #[test]
+    fn record_literal() {
+        let record = record!("#1 = FOO('x', 1.0);");
+        assert_eq!(
+            record,
+            Record {
+                name: "FOO".to_string(),
+                user_defined: false,
+                parameter: vec![Parameter::string("x"), Parameter::real(1.0)].into(),
+            }
+        );
+        assert_eq!(record, record!("FOO('x', 1.0)"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to parse record literal `FOO('x', 1.0`")]
+    fn record_literal_error() {
+        record!("FOO('x', 1.0");
+    }
+
+    #[test]
+    #[should_panic(expected = "record! does not accept complex entity instance")]
+    fn record_literal_complex() {
+        record!("#1 = (A(1) B(2));");
+    }
+
+    #[test]
+    #[should_panic(expected = "line 1")]
+    fn param_literal_error_location() {
+        param!("(1.0, $, #)");
+    }
+
+    #[test]
+    #[should_panic(expected = "-    y: 2.0,")]
+    fn assert_holder_eq_diff() {
+        #[derive(Debug, PartialEq)]
+        struct A {
+            x: f64,
+            y: f64,
+        }
+        assert_holder_eq!(A { x: 1.0, y: 2.0 }, A { x: 1.0, y: 3.0 });
+    }
+}
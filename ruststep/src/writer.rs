@@ -0,0 +1,263 @@
+//! Streaming writer of entity instances in the order of dependencies
+//!
+//! Exchange structures may refer entity instances written later,
+//! but some single-pass readers require every instance to appear after the instances it refers.
+//! [DepOrderedWriter] writes an instance as soon as all instances it refers have been written,
+//! and holds only the instances waiting for others:
+//!
+//! ```
+//! use ruststep::{parser, writer::DepOrderedWriter};
+//!
+//! let instances = parser::parse_record_list("#3 = B(#2); #2 = A(#1); #1 = A(0.0);").unwrap();
+//!
+//! let mut writer = DepOrderedWriter::new(Vec::new());
+//! for instance in instances {
+//!     writer.write(instance).unwrap();
+//! }
+//! let (out, warnings) = writer.finish().unwrap();
+//! assert!(warnings.is_empty());
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     "#1 = A(0.0);\n#2 = A(#1);\n#3 = B(#2);\n"
+//! );
+//! ```
+//!
+//! Only entity instances are written, and `DATA;` and `ENDSEC;` are left to the caller.
+//! Cyclic references are legal in exchange structures, and they cannot be ordered.
+//! [DepOrderedWriter::finish] writes the instances in a cycle together, and reports [Warning::CyclicReference].
+//!
+//! The writer keeps the ids of written instances to check references,
+//! but instances themselves are buffered only while they wait for other instances.
+
+use crate::{
+    ast::EntityInstance,
+    error::{Error, Result},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+/// Problems found while ordering instances in [DepOrderedWriter::finish]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// Instances referring each other, written in the order of ids
+    CyclicReference { ids: Vec<u64> },
+    /// Referred instance is never written. Instances referring it are written at the finish.
+    MissingReference { id: u64 },
+}
+
+#[derive(Debug)]
+struct Pending {
+    instance: EntityInstance,
+    /// Number of referred instances not written yet
+    unmet: usize,
+}
+
+/// Writer of entity instances ensuring referred instances are written first, see [module document](self)
+#[derive(Debug)]
+pub struct DepOrderedWriter<W: io::Write> {
+    out: W,
+    written: HashSet<u64>,
+    pending: HashMap<u64, Pending>,
+    /// Pending instances waiting for the key id
+    waiting: HashMap<u64, Vec<u64>>,
+    peak_pending: usize,
+}
+
+impl<W: io::Write> DepOrderedWriter<W> {
+    pub fn new(out: W) -> Self {
+        DepOrderedWriter {
+            out,
+            written: HashSet::new(),
+            pending: HashMap::new(),
+            waiting: HashMap::new(),
+            peak_pending: 0,
+        }
+    }
+
+    /// Write `instance` if every instance it refers has been written, or hold it until then.
+    ///
+    /// Instances waiting only for this instance are also written.
+    /// Returns [Error::DuplicatedEntity] if the same id is given twice.
+    pub fn write(&mut self, instance: EntityInstance) -> Result<()> {
+        let id = instance.id();
+        if self.written.contains(&id) || self.pending.contains_key(&id) {
+            return Err(Error::DuplicatedEntity(id));
+        }
+        let mut unmet = instance.references();
+        unmet.sort_unstable();
+        unmet.dedup();
+        unmet.retain(|r| *r != id && !self.written.contains(r));
+
+        if unmet.is_empty() {
+            self.emit(instance)?;
+            return self.release(id);
+        }
+        for r in &unmet {
+            self.waiting.entry(*r).or_default().push(id);
+        }
+        self.pending.insert(
+            id,
+            Pending {
+                instance,
+                unmet: unmet.len(),
+            },
+        );
+        self.peak_pending = self.peak_pending.max(self.pending.len());
+        Ok(())
+    }
+
+    /// Number of instances waiting for other instances
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// The largest number of waiting instances so far
+    pub fn peak_pending(&self) -> usize {
+        self.peak_pending
+    }
+
+    /// Write all waiting instances, and returns the inner writer
+    ///
+    /// Instances referring never written ids are written as if the references were satisfied.
+    /// The rest are in or depending on cycles,
+    /// and each cycle is written after the instances it refers.
+    pub fn finish(mut self) -> Result<(W, Vec<Warning>)> {
+        let mut warnings = Vec::new();
+
+        let mut missing: Vec<u64> = self
+            .waiting
+            .keys()
+            .filter(|id| !self.pending.contains_key(id))
+            .copied()
+            .collect();
+        missing.sort_unstable();
+        for id in missing {
+            warnings.push(Warning::MissingReference { id });
+            self.release(id)?;
+        }
+
+        for component in self.components() {
+            let mut ids: Vec<u64> = component
+                .into_iter()
+                .filter(|id| self.pending.contains_key(id))
+                .collect();
+            if ids.is_empty() {
+                continue;
+            }
+            ids.sort_unstable();
+            for id in &ids {
+                let pending = self.pending.remove(id).unwrap();
+                self.emit(pending.instance)?;
+            }
+            for id in &ids {
+                self.release(*id)?;
+            }
+            if ids.len() > 1 {
+                warnings.push(Warning::CyclicReference { ids });
+            }
+        }
+        debug_assert!(self.pending.is_empty());
+
+        self.out.flush()?;
+        Ok((self.out, warnings))
+    }
+
+    fn emit(&mut self, instance: EntityInstance) -> Result<()> {
+        writeln!(self.out, "{}", instance)?;
+        self.written.insert(instance.id());
+        Ok(())
+    }
+
+    /// Write instances which wait only for `id`, and then instances waiting for them
+    fn release(&mut self, id: u64) -> Result<()> {
+        let mut released = vec![id];
+        while let Some(id) = released.pop() {
+            for waiter in self.waiting.remove(&id).unwrap_or_default() {
+                let Some(pending) = self.pending.get_mut(&waiter) else {
+                    continue;
+                };
+                pending.unmet -= 1;
+                if pending.unmet == 0 {
+                    let pending = self.pending.remove(&waiter).unwrap();
+                    self.emit(pending.instance)?;
+                    released.push(waiter);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Strongly connected components of pending instances by Tarjan's algorithm.
+    /// Each component comes after the components it refers.
+    fn components(&self) -> Vec<Vec<u64>> {
+        let mut ids: Vec<u64> = self.pending.keys().copied().collect();
+        ids.sort_unstable();
+        let edges: HashMap<u64, Vec<u64>> = ids
+            .iter()
+            .map(|id| {
+                let mut refs = self.pending[id].instance.references();
+                refs.retain(|r| self.pending.contains_key(r));
+                (*id, refs)
+            })
+            .collect();
+
+        let mut index = HashMap::new();
+        let mut low = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+        let mut next = 0;
+        let mut visit = |v: u64, index: &mut HashMap<u64, usize>, low: &mut HashMap<u64, usize>| {
+            index.insert(v, next);
+            low.insert(v, next);
+            next += 1;
+        };
+
+        for root in ids {
+            if index.contains_key(&root) {
+                continue;
+            }
+            // Explicit call stack of (node, position of the next edge) not to overflow for long chains
+            let mut calls = vec![(root, 0)];
+            visit(root, &mut index, &mut low);
+            stack.push(root);
+            on_stack.insert(root);
+
+            while let Some(&(v, i)) = calls.last() {
+                if let Some(&w) = edges[&v].get(i) {
+                    calls.last_mut().unwrap().1 += 1;
+                    if !index.contains_key(&w) {
+                        visit(w, &mut index, &mut low);
+                        stack.push(w);
+                        on_stack.insert(w);
+                        calls.push((w, 0));
+                    } else if on_stack.contains(&w) {
+                        let l = low[&v].min(index[&w]);
+                        low.insert(v, l);
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(u, _)) = calls.last() {
+                    let l = low[&u].min(low[&v]);
+                    low.insert(u, l);
+                }
+                if low[&v] == index[&v] {
+                    let mut component = Vec::new();
+                    while let Some(w) = stack.pop() {
+                        on_stack.remove(&w);
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+}
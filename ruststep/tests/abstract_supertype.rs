@@ -0,0 +1,124 @@
+use ruststep::{ast::*, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY shape ABSTRACT SUPERTYPE OF (ONEOF (circle, square));
+        label: STRING;
+      END_ENTITY;
+
+      ENTITY circle SUBTYPE OF (shape);
+        radius: REAL;
+      END_ENTITY;
+
+      ENTITY square SUBTYPE OF (shape);
+        side: REAL;
+      END_ENTITY;
+
+      ENTITY drawing;
+        item: shape;
+      END_ENTITY;
+
+      ENTITY pet;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY cat SUBTYPE OF (pet);
+      END_ENTITY;
+
+      ENTITY dog SUBTYPE OF (pet);
+      END_ENTITY;
+
+      SUBTYPE_CONSTRAINT separate_species FOR pet;
+        ABSTRACT SUPERTYPE;
+        ONEOF(cat, dog);
+      END_SUBTYPE_CONSTRAINT;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn is_abstract() {
+    assert!(ShapeHolder::is_abstract());
+    assert!(PetHolder::is_abstract());
+    assert!(!CircleHolder::is_abstract());
+    assert!(!DrawingHolder::is_abstract());
+}
+
+#[test]
+fn reject_simple_record() {
+    let err = Tables::from_str(
+        r#"
+        DATA;
+          #1 = SHAPE('a');
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(err.context().unwrap().id, Some(1));
+    assert!(matches!(
+        err.without_context(),
+        Error::AbstractEntityInstance(name) if name == "SHAPE"
+    ));
+
+    let err = Tables::from_str(
+        r#"
+        DATA;
+          #1 = PET('Tama');
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.without_context(),
+        Error::AbstractEntityInstance(name) if name == "PET"
+    ));
+}
+
+#[test]
+fn reject_parse_record() {
+    let record = Record::from_str("SHAPE('a')").unwrap();
+    assert!(matches!(
+        ShapeHolder::parse_record(&record),
+        Err(Error::AbstractEntityInstance(_))
+    ));
+}
+
+#[test]
+fn subtypes() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = CIRCLE(SHAPE(('c')), 1.0);
+          #2 = SQUARE(SHAPE(('s')), 2.0);
+          #3 = DRAWING(#1);
+          #4 = DRAWING(#2);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+
+    let drawing = EntityTable::<DrawingHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        drawing.item,
+        ShapeAny::Circle(Box::new(Circle {
+            shape: Shape {
+                label: "c".to_string()
+            },
+            radius: 1.0,
+        }))
+    );
+    let drawing = EntityTable::<DrawingHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(
+        drawing.item,
+        ShapeAny::Square(Box::new(Square {
+            shape: Shape {
+                label: "s".to_string()
+            },
+            side: 2.0,
+        }))
+    );
+}
@@ -0,0 +1,110 @@
+use ruststep::{error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE triple = ARRAY [1:3] OF REAL;
+      END_TYPE;
+
+      ENTITY point;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sample;
+        coordinates: ARRAY [1:3] OF REAL;
+        corners: ARRAY [0:1] OF point;
+        weights: ARRAY [-1:0] OF OPTIONAL REAL;
+        direction: triple;
+        tags: SET [1:?] OF STRING;
+        marks: OPTIONAL SET OF point;
+        counts: BAG OF INTEGER;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn fixed_size_array() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0);
+          #2 = POINT(2.0);
+          #3 = SAMPLE((1.0, 2.0, 3.0), (#1, #2), ($, 1.0), TRIPLE(((0.0, 0.0, 1.0))), ('a'), $, (1, 1));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let sample = EntityTable::<SampleHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        sample,
+        Sample {
+            coordinates: [1.0, 2.0, 3.0],
+            corners: [Point { x: 1.0 }, Point { x: 2.0 }],
+            weights: [None, Some(1.0)],
+            direction: Triple([0.0, 0.0, 1.0]),
+            tags: vec!["a".to_string()],
+            marks: None,
+            counts: vec![1, 1],
+        }
+    );
+}
+
+#[test]
+fn array_length_mismatch() {
+    let err = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0);
+          #3 = SAMPLE((1.0, 2.0), (#1, #1), ($, $), TRIPLE(((0.0, 0.0, 1.0))), (), $, ());
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    assert_eq!(err.context().unwrap().id, Some(3));
+    assert!(matches!(
+        err.without_context(),
+        Error::ArrayLengthMismatch {
+            expected: 3,
+            found: 2
+        }
+    ));
+    assert!(err.to_string().contains("coordinates"), "{}", err);
+
+    let err = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0);
+          #3 = SAMPLE((1.0, 2.0, 3.0), (#1, #1, #1), ($, $), TRIPLE(((0.0, 0.0, 1.0))), (), $, ());
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err.without_context(),
+        Error::ArrayLengthMismatch {
+            expected: 2,
+            found: 3
+        }
+    ));
+}
+
+#[test]
+fn check_set() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0);
+          #2 = SAMPLE((1.0, 2.0, 3.0), (#1, #1), ($, $), TRIPLE(((0.0, 0.0, 1.0))), ('a', 'b'), (#1), (1, 1));
+          #3 = SAMPLE((1.0, 2.0, 3.0), (#1, #1), ($, $), TRIPLE(((0.0, 0.0, 1.0))), ('a', 'a'), $, ());
+          #4 = SAMPLE((1.0, 2.0, 3.0), (#1, #1), ($, $), TRIPLE(((0.0, 0.0, 1.0))), ('a'), (#1, #1), ());
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    // Duplicated elements in BAG are allowed
+    assert_eq!(table.check_set_sample(), vec![3, 4]);
+}
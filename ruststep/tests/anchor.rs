@@ -0,0 +1,90 @@
+// Test for ANCHOR section of the second edition
+
+use ruststep::{ast::*, parser, primitive::Binary};
+
+const STEP: &str = include_str!("steps/anchors.step");
+
+fn string(s: &str) -> AnchorItem {
+    AnchorItem::String(s.to_string())
+}
+
+#[test]
+fn anchors() {
+    let exchange = parser::parse(STEP).unwrap();
+    let items: Vec<_> = exchange
+        .anchor
+        .iter()
+        .map(|anchor| (anchor.name.as_str(), &anchor.item))
+        .collect();
+    assert_eq!(
+        items,
+        [
+            ("bolt", &AnchorItem::Name(Name::Entity(1))),
+            ("bolt_shape", &AnchorItem::Name(Name::Entity(2))),
+            ("steel", &AnchorItem::Name(Name::Value(10))),
+            ("thread_pitch", &AnchorItem::Real(1.25)),
+            (
+                "alternates",
+                &AnchorItem::List(vec![
+                    AnchorItem::Name(Name::Entity(1)),
+                    AnchorItem::Name(Name::Value(10)),
+                    AnchorItem::NotProvided,
+                ])
+            ),
+            (
+                "datasheet",
+                &AnchorItem::Resource(URI("catalog.stp#m6_bolt".to_string()))
+            ),
+            (
+                "digest",
+                &AnchorItem::Binary(Binary::from(vec![0xDE, 0xAD, 0xBE, 0xEF]))
+            ),
+        ]
+    );
+    assert_eq!(
+        exchange.anchor[1].tags,
+        [
+            ("revision".to_string(), string("B")),
+            (
+                "checked".to_string(),
+                AnchorItem::Enumeration("T".to_string())
+            ),
+        ]
+    );
+    assert_eq!(
+        exchange.anchor[5].tags,
+        [(
+            "pages".to_string(),
+            AnchorItem::List(vec![AnchorItem::Integer(3), AnchorItem::Integer(4)])
+        )]
+    );
+
+    // Value names are defined in REFERENCE section
+    assert_eq!(exchange.reference[0].name, Name::Value(10));
+    assert_eq!(exchange.data[0].entities.len(), 2);
+}
+
+#[test]
+fn round_trip() {
+    let exchange = parser::parse(STEP).unwrap();
+    assert_eq!(parser::parse(&exchange.to_string()).unwrap(), exchange);
+    assert_eq!(parser::parse_borrowed(STEP).unwrap().into_owned(), exchange);
+}
+
+#[test]
+fn without_anchor_section() {
+    let start = STEP.find("ANCHOR;").unwrap();
+    let end = STEP.find("REFERENCE;").unwrap();
+    let input = format!("{}{}", &STEP[..start], &STEP[end..]);
+
+    let exchange = parser::parse(STEP).unwrap();
+    let without = parser::parse(&input).unwrap();
+    assert!(without.anchor.is_empty());
+    assert_eq!(
+        Exchange {
+            anchor: exchange.anchor.clone(),
+            ..without
+        },
+        exchange
+    );
+}
@@ -0,0 +1,218 @@
+use ruststep::{assembly::*, ast::DataSection, error::Error, parser};
+use std::str::FromStr;
+
+// Assembly A contains sub-assembly B as `B-1` and part C as `C-2`, and B contains C as `C-1`.
+// `C-2` does not have shape representation.
+const TWO_LEVEL: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1 = APPLICATION_CONTEXT('automotive design');
+#2 = PRODUCT_CONTEXT('', #1, 'mechanical');
+#3 = PRODUCT_DEFINITION_CONTEXT('part definition', #1, 'design');
+#4 = GEOMETRIC_REPRESENTATION_CONTEXT('', '', 3);
+#10 = PRODUCT('A', 'Assembly A', '', (#2));
+#11 = PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE('', '', #10, .NOT_KNOWN.);
+#12 = PRODUCT_DEFINITION('design', '', #11, #3);
+#13 = PRODUCT_DEFINITION_SHAPE('', '', #12);
+#14 = SHAPE_REPRESENTATION('', (#100, #101), #4);
+#15 = SHAPE_DEFINITION_REPRESENTATION(#13, #14);
+#20 = PRODUCT('B', 'Sub-assembly B', '', (#2));
+#21 = PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE('', '', #20, .NOT_KNOWN.);
+#22 = PRODUCT_DEFINITION('design', '', #21, #3);
+#23 = PRODUCT_DEFINITION_SHAPE('', '', #22);
+#24 = SHAPE_REPRESENTATION('', (#100, #110), #4);
+#25 = SHAPE_DEFINITION_REPRESENTATION(#23, #24);
+#30 = PRODUCT('C', 'Part C', '', (#2));
+#31 = PRODUCT_DEFINITION_FORMATION('', '', #30);
+#32 = PRODUCT_DEFINITION('design', '', #31, #3);
+#33 = PRODUCT_DEFINITION_SHAPE('', '', #32);
+#34 = SHAPE_REPRESENTATION('', (#100), #4);
+#35 = SHAPE_DEFINITION_REPRESENTATION(#33, #34);
+#40 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO1', 'B-1', '', #12, #22, $);
+#41 = PRODUCT_DEFINITION_SHAPE('', '', #40);
+#42 = ( REPRESENTATION_RELATIONSHIP('', '', #24, #14) REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION(#43) SHAPE_REPRESENTATION_RELATIONSHIP() );
+#43 = ITEM_DEFINED_TRANSFORMATION('', '', #100, #101);
+#44 = CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(#42, #41);
+#45 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO2', 'C-1', '', #22, #32, $);
+#46 = PRODUCT_DEFINITION_SHAPE('', '', #45);
+#47 = ( REPRESENTATION_RELATIONSHIP('', '', #34, #24) REPRESENTATION_RELATIONSHIP_WITH_TRANSFORMATION(#48) SHAPE_REPRESENTATION_RELATIONSHIP() );
+#48 = ITEM_DEFINED_TRANSFORMATION('', '', #100, #110);
+#49 = CONTEXT_DEPENDENT_SHAPE_REPRESENTATION(#47, #46);
+#60 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO3', 'C-2', '', #12, #32, $);
+#100 = AXIS2_PLACEMENT_3D('', #102, #103, #104);
+#101 = AXIS2_PLACEMENT_3D('', #105, #103, #106);
+#102 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#103 = DIRECTION('', (0.0, 0.0, 1.0));
+#104 = DIRECTION('', (1.0, 0.0, 0.0));
+#105 = CARTESIAN_POINT('', (10.0, 0.0, 0.0));
+#106 = DIRECTION('', (0.0, 1.0, 0.0));
+#110 = AXIS2_PLACEMENT_3D('', #111, $, $);
+#111 = CARTESIAN_POINT('', (1.0, 2.0, 3.0));
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+fn assert_close(a: [f64; 3], b: [f64; 3]) {
+    for (x, y) in a.iter().zip(&b) {
+        assert!((x - y).abs() < 1e-9, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn two_level() {
+    let exchange = parser::parse(TWO_LEVEL).unwrap();
+    let tree = build_tree(&exchange.data).unwrap();
+
+    assert_eq!(tree.roots.len(), 1);
+    let a = &tree.roots[0];
+    assert_eq!(a.product_id, "A");
+    assert_eq!(a.product_name, "Assembly A");
+    assert_eq!(a.definition, 12);
+    let names: Vec<_> = a
+        .children
+        .iter()
+        .map(|child| child.occurrence_name.as_str())
+        .collect();
+    assert_eq!(names, ["B-1", "C-2"]);
+    assert!(a.children[0].transform.is_some());
+    assert!(a.children[1].transform.is_none());
+
+    let b = tree.find(&["B-1"]).unwrap();
+    assert_eq!(b.product_id, "B");
+    assert_eq!(tree.find(&["B-1", "C-1"]).unwrap().product_id, "C");
+    assert_eq!(tree.find(&["NAUO1", "NAUO2"]).unwrap().product_id, "C");
+    assert!(tree.find(&["C-1"]).is_none());
+
+    let ids: Vec<_> = tree.iter().map(|node| node.product_id.as_str()).collect();
+    assert_eq!(ids, ["A", "B", "C", "C"]);
+
+    let instances = tree.flatten();
+    let paths: Vec<_> = instances.iter().map(|i| i.path.clone()).collect();
+    assert_eq!(
+        paths,
+        [vec![], vec!["B-1"], vec!["B-1", "C-1"], vec!["C-2"]]
+    );
+
+    // C is placed at (1, 2, 3) in B, and B is rotated by 90 degree around z-axis at (10, 0, 0) in A
+    let c = &instances[2];
+    assert_close(c.transform.apply([0.0, 0.0, 0.0]), [8.0, 1.0, 3.0]);
+    assert_close(c.transform.apply([1.0, 0.0, 0.0]), [8.0, 2.0, 3.0]);
+    assert_close(
+        c.transform.inverse().apply([8.0, 1.0, 3.0]),
+        [0.0, 0.0, 0.0],
+    );
+    // Missing placement is regarded as identity
+    assert_eq!(instances[3].transform, Transform::identity());
+}
+
+// Product B has two definitions, and only the second one has a child
+const MULTIPLE_DEFINITIONS: &str = r#"
+DATA;
+#1 = PRODUCT('A', 'Assembly A', '', ());
+#2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+#3 = PRODUCT_DEFINITION('design', '', #2, $);
+#4 = PRODUCT('B', 'Sub-assembly B', '', ());
+#5 = PRODUCT_DEFINITION_FORMATION('', '', #4);
+#6 = PRODUCT_DEFINITION('design', '', #5, $);
+#7 = PRODUCT_DEFINITION('design', '', #5, $);
+#8 = PRODUCT('C', 'Part C', '', ());
+#9 = PRODUCT_DEFINITION_FORMATION('', '', #8);
+#10 = PRODUCT_DEFINITION('design', '', #9, $);
+#11 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO1', 'B-1', '', #3, #6, $);
+#12 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO2', 'C-1', '', #7, #10, $);
+ENDSEC;
+"#;
+
+#[test]
+fn definition_selection() {
+    let data = [DataSection::from_str(MULTIPLE_DEFINITIONS).unwrap()];
+
+    let tree = build_tree(&data).unwrap();
+    assert_eq!(tree.roots.len(), 1);
+    let b = tree.find(&["B-1"]).unwrap();
+    assert_eq!(b.definition, 6);
+    assert!(b.children.is_empty());
+
+    let options = AssemblyOptions {
+        definition_selection: DefinitionSelection::MostChildren,
+    };
+    let tree = build_tree_with(&data, &options).unwrap();
+    let b = tree.find(&["B-1"]).unwrap();
+    assert_eq!(b.definition, 7);
+    assert_eq!(tree.find(&["B-1", "C-1"]).unwrap().product_id, "C");
+}
+
+#[test]
+fn cyclic() {
+    let data = [DataSection::from_str(
+        r#"
+        DATA;
+        #1 = PRODUCT('A', 'Assembly A', '', ());
+        #2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+        #3 = PRODUCT_DEFINITION('design', '', #2, $);
+        #4 = PRODUCT('R', 'Root', '', ());
+        #5 = PRODUCT_DEFINITION_FORMATION('', '', #4);
+        #6 = PRODUCT_DEFINITION('design', '', #5, $);
+        #7 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO1', 'A-1', '', #6, #3, $);
+        #8 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO2', 'A-2', '', #3, #3, $);
+        ENDSEC;
+        "#,
+    )
+    .unwrap()];
+    assert!(matches!(build_tree(&data), Err(Error::CyclicAssembly(3))));
+}
+
+// Distinct products sharing the `id` attribute, e.g. `'Part'` for every part
+const DUPLICATED_PRODUCT_IDS: &str = r#"
+DATA;
+#1 = PRODUCT('Part', 'Assembly', '', ());
+#2 = PRODUCT_DEFINITION_FORMATION('', '', #1);
+#3 = PRODUCT_DEFINITION('design', '', #2, $);
+#4 = PRODUCT('Part', 'Bolt', '', ());
+#5 = PRODUCT_DEFINITION_FORMATION('', '', #4);
+#6 = PRODUCT_DEFINITION('design', '', #5, $);
+#7 = PRODUCT('Part', 'Nut', '', ());
+#8 = PRODUCT_DEFINITION_FORMATION('', '', #7);
+#9 = PRODUCT_DEFINITION('design', '', #8, $);
+#10 = NEXT_ASSEMBLY_USAGE_OCCURRENCE('NAUO1', 'Bolt-1', '', #3, #6, $);
+ENDSEC;
+"#;
+
+#[test]
+fn duplicated_product_ids() {
+    let data = [DataSection::from_str(DUPLICATED_PRODUCT_IDS).unwrap()];
+    let tree = build_tree(&data).unwrap();
+    let roots: Vec<_> = tree
+        .roots
+        .iter()
+        .map(|root| root.product_name.as_str())
+        .collect();
+    assert_eq!(roots, ["Assembly", "Nut"]);
+    assert_eq!(tree.find(&["Bolt-1"]).unwrap().product_name, "Bolt");
+
+    let options = AssemblyOptions {
+        definition_selection: DefinitionSelection::MostChildren,
+    };
+    let tree = build_tree_with(&data, &options).unwrap();
+    assert_eq!(tree.find(&["Bolt-1"]).unwrap().definition, 6);
+}
+
+// Exported file in ABC Dataset, which has three parts without assembly
+#[test]
+fn abc_dataset() {
+    let step_file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    let exchange = parser::parse(&std::fs::read_to_string(step_file).unwrap()).unwrap();
+    let tree = build_tree(&exchange.data).unwrap();
+    let roots: Vec<_> = tree
+        .roots
+        .iter()
+        .map(|root| (root.product_id.as_str(), root.definition))
+        .collect();
+    assert_eq!(roots, [("Part 3", 163), ("Part 2", 167), ("Part 1", 171)]);
+    assert!(tree.roots.iter().all(|root| root.children.is_empty()));
+}
@@ -0,0 +1,103 @@
+// Test for detecting and fixing reordered attributes between a file and a schema
+
+use ruststep::{error::Error, load::*, tables::*};
+
+// `weight` and `count` were declared in the opposite order
+// in the schema used for writing the exchange structure below.
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY part;
+        name: STRING;
+        count: OPTIONAL INTEGER;
+        weight: OPTIONAL REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+// Written as `PART(name, weight, count)`
+const STEP: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+#1 = PART('bolt', $, 3);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn attr_kinds() {
+    assert_eq!(
+        PartHolder::attr_kinds(),
+        [
+            AttributeKind {
+                kind: ValueKind::String,
+                optional: false
+            },
+            AttributeKind {
+                kind: ValueKind::Integer,
+                optional: true
+            },
+            AttributeKind {
+                kind: ValueKind::Real,
+                optional: true
+            },
+        ]
+    );
+}
+
+#[test]
+fn silent_swap() {
+    // Integer is accepted for REAL attribute
+    let tables: Tables = load(STEP, LoadOptions::new()).unwrap();
+    let part: Part = EntityTable::<PartHolder>::get_owned(&tables, 1).unwrap();
+    assert_eq!(part.count, None);
+    assert_eq!(part.weight, Some(3.0));
+}
+
+#[test]
+fn check_attribute_kinds() {
+    let result: Result<Tables, _> = load(STEP, LoadOptions::new().check_attribute_kinds(true));
+    match result {
+        Err(Error::AttributeKindMismatch {
+            entity, position, ..
+        }) => {
+            assert_eq!(entity, "PART");
+            assert_eq!(position, 2);
+        }
+        _ => panic!("Swapped attributes are not detected"),
+    }
+}
+
+#[test]
+fn attribute_order() {
+    let options = LoadOptions::new()
+        .check_attribute_kinds(true)
+        .attribute_order("part", vec![0, 2, 1]);
+    let tables: Tables = load(STEP, options).unwrap();
+    let part: Part = EntityTable::<PartHolder>::get_owned(&tables, 1).unwrap();
+    assert_eq!(part.name, "bolt");
+    assert_eq!(part.count, Some(3));
+    assert_eq!(part.weight, None);
+}
+
+#[test]
+fn attribute_order_length_mismatch() {
+    let options = DeserializeOptions {
+        attribute_orders: [("PART".to_string(), vec![1, 0])].into_iter().collect(),
+        ..Default::default()
+    };
+    let result = with_deserialize_options(&options, || {
+        let mut tables = Tables::default();
+        tables.append_data_section(&ruststep::parser::parse(STEP).unwrap().data[0])
+    });
+    let err = result.unwrap_err();
+    assert!(matches!(err.without_context(), Error::DeserializeFailed(_)));
+    assert_eq!(err.context().unwrap().id, Some(1));
+}
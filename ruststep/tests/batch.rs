@@ -0,0 +1,154 @@
+use ruststep::{
+    batch::*,
+    parser::{self, ParseOptions},
+};
+use std::{fs, path::PathBuf};
+
+const VALID: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+#1 = A(1.0, 2.0);
+#2 = B(3.0, #1);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+const CORRUPT: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1'
+"#;
+
+const FULLWIDTH: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+#1 = A(1.0, 2.0)；
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+fn validate() -> PassManager {
+    let mut passes = PassManager::new();
+    passes.add_checked_pass(|input: &str, findings: &mut Vec<String>| {
+        let (_exchange, warnings) = parser::parse_with_warnings(input, &ParseOptions::lenient())?;
+        findings.extend(warnings.iter().map(|w| format!("{:?}", w)));
+        Ok(input.to_string())
+    });
+    passes
+}
+
+#[test]
+fn batch_with_corrupt_file() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("batch_with_corrupt_file");
+    let _ = fs::remove_dir_all(&root);
+    let input = root.join("input");
+    let output = root.join("output");
+    fs::create_dir_all(input.join("sub")).unwrap();
+    fs::write(input.join("a.step"), VALID).unwrap();
+    fs::write(input.join("sub/b.stp"), VALID).unwrap();
+    fs::write(input.join("c.p21"), CORRUPT).unwrap();
+    fs::write(input.join("d.step"), FULLWIDTH).unwrap();
+    fs::write(input.join("ignored.txt"), CORRUPT).unwrap();
+
+    let report = process_dir(
+        &input,
+        &output,
+        &validate(),
+        BatchOptions {
+            parallelism: 2,
+            ..Default::default()
+        },
+    );
+    assert_eq!(report.files.len(), 4);
+    assert_eq!(report.processed().count(), 3);
+    assert!(!report.is_success());
+
+    let failed: Vec<_> = report.failed().collect();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].input, input.join("c.p21"));
+    assert!(matches!(failed[0].status, FileStatus::Failed(_)));
+    assert!(failed[0].findings.is_empty());
+
+    let findings = |path: PathBuf| {
+        report
+            .files
+            .iter()
+            .find(|f| f.input == path)
+            .unwrap()
+            .findings
+            .clone()
+    };
+    assert!(findings(input.join("a.step")).is_empty());
+    assert!(findings(input.join("sub/b.stp")).is_empty());
+    assert_eq!(
+        findings(input.join("d.step")),
+        vec![Finding {
+            pass: 0,
+            message: format!(
+                "{:?}",
+                parser::Warning::FullwidthPunctuation {
+                    position: FULLWIDTH.find('；').unwrap(),
+                    found: '；'
+                }
+            ),
+        }]
+    );
+
+    for path in [output.join("a.step"), output.join("sub/b.stp")] {
+        let content = fs::read_to_string(path).unwrap();
+        parser::parse(&content).unwrap();
+    }
+    assert!(!output.join("c.p21").exists());
+}
+
+#[test]
+fn batch_panic_is_isolated() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("batch_panic_is_isolated");
+    let _ = fs::remove_dir_all(&root);
+    let input = root.join("input");
+    let output = root.join("output");
+    fs::create_dir_all(&input).unwrap();
+    fs::write(input.join("a.step"), VALID).unwrap();
+    fs::write(input.join("b.step"), CORRUPT).unwrap();
+
+    let mut passes = PassManager::new();
+    passes.add_pass(|input: &str| {
+        if parser::parse(input).is_err() {
+            panic!("cannot parse");
+        }
+        Ok(input.to_string())
+    });
+    let report = process_dir(&input, &output, &passes, BatchOptions::default());
+    assert_eq!(report.processed().count(), 1);
+    assert_eq!(
+        report.files[1].status,
+        FileStatus::Panicked("cannot parse".to_string())
+    );
+}
+
+#[test]
+fn batch_incremental() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("batch_incremental");
+    let _ = fs::remove_dir_all(&root);
+    let input = root.join("input");
+    let output = root.join("output");
+    fs::create_dir_all(&input).unwrap();
+    fs::write(input.join("a.step"), VALID).unwrap();
+
+    let options = BatchOptions {
+        incremental: true,
+        ..Default::default()
+    };
+    let report = process_dir(&input, &output, &validate(), options.clone());
+    assert_eq!(report.processed().count(), 1);
+
+    let report = process_dir(&input, &output, &validate(), options);
+    assert_eq!(report.skipped().count(), 1);
+}
@@ -0,0 +1,123 @@
+// Test for reading and writing BINARY attributes
+
+use ruststep::{ast::*, load::*, primitive::Binary, tables::*};
+use ruststep_derive::{as_holder, Holder, TableInit};
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Default, TableInit)]
+pub struct Tables {
+    presentation: HashMap<u64, as_holder!(Presentation)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder, serde::Serialize)]
+#[holder(table = Tables)]
+#[holder(field = presentation)]
+#[holder(generate_deserialize)]
+#[serde(rename = "PRESENTATION")]
+pub struct Presentation {
+    pub name: String,
+    pub data: Binary,
+    pub thumbnail: Option<Binary>,
+}
+
+/// Instances in the canonical form of [EntityInstance] display
+const INSTANCES: &[&str] = &[
+    "#1 = PRESENTATION('blob',\"0DEADBEEF\",\"31\");",
+    "#2 = PRESENTATION('bits',\"23FF\",$);",
+    "#3 = PRESENTATION('empty',\"0\",\"0\");",
+];
+
+fn step(instances: &[&str]) -> String {
+    format!(
+        r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+{}
+ENDSEC;
+END-ISO-10303-21;
+"#,
+        instances.join("\n")
+    )
+}
+
+#[test]
+fn load_binary() {
+    let tables: Tables = load(&step(INSTANCES), LoadOptions::new()).unwrap();
+
+    let blob = &tables.presentation[&1];
+    assert_eq!(blob.data.as_bytes(), [0xDE, 0xAD, 0xBE, 0xEF]);
+    assert_eq!(blob.data.len(), 32);
+    assert_eq!(blob.thumbnail, Some(Binary::new(vec![0x01], 1)));
+
+    let bits = &tables.presentation[&2];
+    assert_eq!(bits.data.len(), 10);
+    assert_eq!(bits.data.as_bytes(), [0x03, 0xFF]);
+    assert_eq!(bits.thumbnail, None);
+
+    assert!(tables.presentation[&3].data.is_empty());
+}
+
+#[test]
+fn empty_binary() {
+    let instance = r#"#1 = PRESENTATION('empty', "", "");"#;
+    let tables: Tables = load(&step(&[instance]), LoadOptions::new()).unwrap();
+    assert_eq!(tables.presentation[&1].data, Binary::default());
+}
+
+#[test]
+fn round_trip() {
+    let tables: Tables = load(&step(INSTANCES), LoadOptions::new()).unwrap();
+    let mut ids: Vec<u64> = tables.presentation.keys().copied().collect();
+    ids.sort_unstable();
+    let exported: Vec<String> = ids
+        .into_iter()
+        .map(|id| {
+            let presentation = tables.presentation[&id]
+                .clone()
+                .into_owned(&tables)
+                .unwrap();
+            EntityInstance::Simple {
+                id,
+                record: ser::to_record(&presentation).unwrap(),
+            }
+            .to_string()
+        })
+        .collect();
+    assert_eq!(exported, INSTANCES);
+}
+
+#[test]
+fn borrowed_parser() {
+    let step = step(INSTANCES);
+    assert_eq!(
+        ruststep::parser::parse_borrowed(&step)
+            .unwrap()
+            .into_owned(),
+        ruststep::parser::parse(&step).unwrap()
+    );
+}
+
+#[test]
+fn bytes_as_binary() {
+    // `Vec<u8>` through `serde_bytes`-like serializer is written as binary of whole bytes
+    struct Bytes(Vec<u8>);
+    impl serde::Serialize for Bytes {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+    #[derive(serde::Serialize)]
+    #[serde(rename = "BLOB")]
+    struct Blob {
+        data: Bytes,
+    }
+    let record = ser::to_record(&Blob {
+        data: Bytes(vec![0x0A, 0xBC]),
+    })
+    .unwrap();
+    assert_eq!(record, Record::from_str(r#"BLOB("00ABC")"#).unwrap());
+}
@@ -0,0 +1,75 @@
+//! Differential test of [ruststep::parser::parse_borrowed] against [ruststep::parser::parse]
+
+use ruststep::parser;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn read(name: &str) -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps")
+        .join(name);
+    fs::read_to_string(step_file).unwrap()
+}
+
+const FILES: [&str; 2] = [
+    "00000050_80d90bfdd2e74e709956122a_step_000.step",
+    "database.p21",
+];
+
+#[test]
+fn same_ast() {
+    for name in FILES {
+        let step = read(name);
+        let owned = parser::parse(&step).unwrap();
+        let borrowed = parser::parse_borrowed(&step).unwrap();
+        assert_eq!(borrowed.into_owned(), owned, "{}", name);
+    }
+}
+
+#[test]
+fn same_error() {
+    let step = read(FILES[0]).replacen("DIRECTION( '', (", "DIRECTION( '', ((", 1);
+    let owned = parser::parse(&step).unwrap_err();
+    let borrowed = parser::parse_borrowed(&step).unwrap_err();
+    assert_eq!(owned.to_string(), borrowed.to_string());
+}
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let value = f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    drop(value);
+    count
+}
+
+#[test]
+fn fewer_allocations() {
+    for name in FILES {
+        let step = read(name);
+        let owned = count_allocations(|| parser::parse(&step).unwrap());
+        let borrowed = count_allocations(|| parser::parse_borrowed(&step).unwrap());
+        assert!(borrowed < owned, "{}: {} >= {}", name, borrowed, owned);
+    }
+}
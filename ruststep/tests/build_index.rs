@@ -0,0 +1,62 @@
+// Test for indexing entities by a value of attributes
+
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY context;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY product;
+        id: STRING;
+        name: STRING;
+        frame_of_reference: context;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables(data: &str) -> Tables {
+    Tables::from_data_section(&DataSection::from_str(data).unwrap()).unwrap()
+}
+
+#[test]
+fn build_index() {
+    let tables = tables(
+        r#"
+        DATA;
+          #1 = CONTEXT('mechanical');
+          #5 = PRODUCT('P-3', 'bolt', #1);
+          #2 = PRODUCT('P-1', 'bolt', #1);
+          #3 = PRODUCT('P-2', 'nut', CONTEXT(('electrical')));
+        ENDSEC;
+        "#,
+    );
+    let products = EntityTable::<ProductHolder>::build_index(&tables, |p| p.name.clone()).unwrap();
+    assert_eq!(products.len(), 2);
+    assert_eq!(products["bolt"], [2, 5]);
+    assert_eq!(products["nut"], [3]);
+
+    let contexts =
+        EntityTable::<ProductHolder>::build_index(&tables, |p| p.frame_of_reference.name.clone())
+            .unwrap();
+    assert_eq!(contexts["mechanical"], [2, 5]);
+    assert_eq!(contexts["electrical"], [3]);
+}
+
+#[test]
+fn build_index_unresolved() {
+    let tables = tables(
+        r#"
+        DATA;
+          #2 = PRODUCT('P-1', 'bolt', #1);
+        ENDSEC;
+        "#,
+    );
+    assert!(EntityTable::<ProductHolder>::build_index(&tables, |p| p.id.clone()).is_err());
+}
@@ -0,0 +1,57 @@
+//! Build an exchange structure programmatically, and write it
+
+use ruststep::{ast::*, parser};
+use std::str::FromStr;
+
+#[test]
+fn three_entities() {
+    let mut data = DataSectionBuilder::new();
+    let origin = data.add(Record::from_str("CARTESIAN_POINT('origin', (0.0, 0.0, 0.0))").unwrap());
+    let axis = data.add(Record::from_str("DIRECTION('axis', (0.0, 0.0, 1.0))").unwrap());
+    let placement = data.add(Record {
+        name: "AXIS2_PLACEMENT_3D".to_string(),
+        user_defined: false,
+        parameter: vec![
+            Parameter::string("placement"),
+            Parameter::Ref(Name::Entity(origin)),
+            Parameter::Ref(Name::Entity(axis)),
+            Parameter::NotProvided,
+        ]
+        .into(),
+    });
+
+    let exchange = ExchangeBuilder::new()
+        .name("placement.stp")
+        .time_stamp("2024-01-01T00:00:00")
+        .author("author")
+        .schema("CONFIG_CONTROL_DESIGN")
+        .data_section(data.build())
+        .build();
+
+    let written = exchange.to_string();
+    assert_eq!(
+        written,
+        format!(
+            r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''),'2;1');
+FILE_NAME('placement.stp','2024-01-01T00:00:00',('author'),(''),'ruststep {}','','');
+FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('origin',(0.0,0.0,0.0));
+#2 = DIRECTION('axis',(0.0,0.0,1.0));
+#3 = AXIS2_PLACEMENT_3D('placement',#1,#2,$);
+ENDSEC;
+END-ISO-10303-21;
+"#,
+            env!("CARGO_PKG_VERSION")
+        )
+    );
+    let parsed = parser::parse(&written).unwrap();
+    assert_eq!(parsed, exchange);
+    assert_eq!(
+        parsed.data[0].instance(placement).unwrap().references(),
+        [origin, axis]
+    );
+}
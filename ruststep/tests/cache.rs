@@ -0,0 +1,220 @@
+// Test for persisting typed tables as `TablesCache` in a binary format and reloading them
+
+use ruststep::{ast::*, cache::*, primitive::*, tables::*};
+use ruststep_derive::{as_holder, Holder, TableInit};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Default, TableInit, Serialize, Deserialize)]
+pub struct Tables {
+    point: HashMap<u64, as_holder!(Point)>,
+    named: HashMap<u64, as_holder!(Named)>,
+    pair: HashMap<u64, as_holder!(Pair)>,
+}
+
+impl CachedTables for Tables {
+    fn schema_fingerprint() -> u64 {
+        1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(generate_deserialize)]
+#[holder(generate_serde)]
+pub enum Kind {
+    Fixed,
+    #[holder(rename = "LOOSE")]
+    Free,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = point)]
+#[holder(generate_deserialize)]
+#[holder(generate_serde)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(generate_deserialize)]
+#[holder(generate_serde)]
+pub enum Shape {
+    #[holder(use_place_holder)]
+    Point(Box<Point>),
+    Circle {
+        #[holder(use_place_holder)]
+        center: Point,
+        radius: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = named)]
+#[holder(generate_deserialize)]
+#[holder(generate_serde)]
+pub struct Named {
+    pub name: Wrapped<String>,
+    pub visible: Logical,
+    pub kind: Option<Kind>,
+    #[holder(use_place_holder)]
+    pub anchor: Option<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = pair)]
+#[holder(generate_deserialize)]
+#[holder(generate_serde)]
+pub struct Pair {
+    #[holder(use_place_holder)]
+    pub first: Point,
+    #[holder(use_place_holder)]
+    pub second: Vec<Point>,
+    #[holder(use_place_holder)]
+    pub shape: Shape,
+}
+
+mod old {
+    use super::*;
+
+    /// Tables of another schema
+    #[derive(Debug, Clone, PartialEq, Default, TableInit, Serialize, Deserialize)]
+    pub struct Tables {
+        point: HashMap<u64, as_holder!(Point)>,
+    }
+
+    impl CachedTables for Tables {
+        fn schema_fingerprint() -> u64 {
+            0
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Holder)]
+    #[holder(table = Tables)]
+    #[holder(field = point)]
+    #[holder(generate_deserialize)]
+    #[holder(generate_serde)]
+    pub struct Point {
+        pub x: f64,
+    }
+}
+
+fn tables() -> Tables {
+    let options = DeserializeOptions {
+        preserve_type_wrappers: true,
+        ..Default::default()
+    };
+    let data = DataSection::from_str(
+        r#"
+        DATA;
+          #1 = POINT(0.0, 1.5E-3);
+          #2 = POINT(-1.0, 2.0);
+          #3 = NAMED(LABEL('origin'), .T., .LOOSE., #1);
+          #4 = NAMED('none', .U., $, $);
+          #5 = PAIR(POINT((3.0, 4.0)), (#1, #2), CIRCLE((#2, 0.5)));
+          #6 = PAIR(#1, (), POINT((5.0, 6.0)));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    with_deserialize_options(&options, || Tables::from_data_section(&data)).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let tables = tables();
+    let cache = TablesCache::new(&tables);
+    assert_eq!(cache.fingerprint, 1);
+
+    let bytes = bincode::serialize(&cache).unwrap();
+    let cache: TablesCache<Tables> = bincode::deserialize(&bytes).unwrap();
+    let reloaded = cache.into_tables().unwrap();
+    assert_eq!(reloaded, tables);
+
+    let named = EntityTable::<NamedHolder>::get_owned(&reloaded, 3).unwrap();
+    assert_eq!(
+        named,
+        Named {
+            name: Wrapped::new("LABEL", "origin".to_string()),
+            visible: Logical::True,
+            kind: Some(Kind::Free),
+            anchor: Some(Point { x: 0.0, y: 1.5e-3 }),
+        }
+    );
+    let pair = EntityTable::<PairHolder>::get_owned(&reloaded, 5).unwrap();
+    assert_eq!(
+        pair.shape,
+        Shape::Circle {
+            center: Point { x: -1.0, y: 2.0 },
+            radius: 0.5,
+        }
+    );
+}
+
+#[test]
+fn exchange_structure() {
+    // Parameters are still read as the exchange structure
+    let parameter = Parameter::from_str("#1").unwrap();
+    let place_holder = PlaceHolder::<PointHolder>::deserialize(&parameter).unwrap();
+    assert_eq!(place_holder, PlaceHolder::Ref(Name::Entity(1)));
+    let parameter = Parameter::from_str("LABEL('origin')").unwrap();
+    let label = Wrapped::<String>::deserialize(&parameter).unwrap();
+    assert_eq!(label, Wrapped::from("origin".to_string()));
+}
+
+#[test]
+fn stale_cache() {
+    let tables = tables();
+    let bytes = bincode::serialize(&TablesCache::new(&tables)).unwrap();
+    let err = bincode::deserialize::<TablesCache<old::Tables>>(&bytes).unwrap_err();
+    assert!(err.to_string().contains("is stale for ruststep"), "{}", err);
+
+    let mut cache = TablesCache::new(&tables);
+    cache.version = "0.0.0".to_string();
+    let bytes = bincode::serialize(&cache).unwrap();
+    let err = bincode::deserialize::<TablesCache<Tables>>(&bytes).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Cache written by ruststep 0.0.0 for schema"),
+        "{}",
+        err
+    );
+
+    // Checked also without serde
+    let mut cache = TablesCache::new(tables);
+    cache.fingerprint = 0;
+    assert!(matches!(
+        cache.into_tables().unwrap_err(),
+        ruststep::error::Error::StaleCache { .. }
+    ));
+}
+
+mod generated {
+    espr_derive::inline_express!(
+        r#"
+        SCHEMA test_schema;
+          ENTITY point;
+            x: REAL;
+            y: REAL;
+          END_ENTITY;
+        END_SCHEMA;
+        "#
+    );
+}
+
+#[test]
+fn fingerprint_is_stable() {
+    use generated::test_schema;
+    assert_eq!(
+        schema_fingerprint(test_schema::SCHEMA_REGISTRY),
+        test_schema::Tables::schema_fingerprint()
+    );
+    assert_eq!(
+        schema_fingerprint::<test_schema::Tables>(&[]),
+        0xcbf29ce484222325
+    );
+}
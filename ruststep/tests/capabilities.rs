@@ -0,0 +1,140 @@
+// Test for detecting features of exchange structure used in a file
+
+use ruststep::capabilities::*;
+
+const EDITION3: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('complex and value instances'), '3;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+ANCHOR;
+<origin> = #1;
+ENDSEC;
+REFERENCE;
+@10 = <other.stp#origin>;
+ENDSEC;
+DATA;
+/* #9 = (NOT_COMPLEX()); in a comment */
+#1 = (NAMED_UNIT(*) LENGTH_UNIT() SI_UNIT(.MILLI., .METRE.));
+#2 = LABEL('@not_value \\X\\ ''!NOT_USER''');
+#3 = !MY_ENTITY(@10, #1);
+#4 = (A() B());
+ENDSEC;
+DATA;
+#5 = LABEL('second section');
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn edition3() {
+    let report = analyze(EDITION3);
+    let features: Vec<_> = report.features.iter().map(|u| u.feature).collect();
+    assert_eq!(
+        features,
+        vec![
+            Feature::ComplexInstance,
+            Feature::ValueInstance,
+            Feature::AnchorSection,
+            Feature::ReferenceSection,
+            Feature::UserDefinedKeyword,
+            Feature::MultipleDataSections,
+            Feature::Edition3,
+        ]
+    );
+    assert!(report.is_supported());
+    assert!(report.required_options().is_empty());
+
+    let complex = report.get(Feature::ComplexInstance).unwrap();
+    assert_eq!(complex.count, 2);
+    assert!(EDITION3[complex.first_offset..].starts_with("#1 = (NAMED_UNIT"));
+    assert_eq!(complex.support, Support::Supported);
+
+    let value = report.get(Feature::ValueInstance).unwrap();
+    assert_eq!(value.count, 2);
+    assert!(EDITION3[value.first_offset..].starts_with("@10 = <other.stp"));
+
+    let data = report.get(Feature::MultipleDataSections).unwrap();
+    assert_eq!(data.count, 1);
+    assert!(EDITION3[data.first_offset..].starts_with("DATA;\n#5"));
+}
+
+#[test]
+fn directive_binary_scope() {
+    let input = r#"DATA;
+#1 = LABEL('\X2\30B9\X0\', "0FA");
+#2 = PART('a\\X\b') &SCOPE
+#3 = PART('c\S\d');
+ENDSCOPE;
+ENDSEC;
+"#;
+    let report = analyze(input);
+    assert!(report.is_supported());
+
+    let directive = report.get(Feature::ControlDirective).unwrap();
+    // `\\X\` is an escaped backslash followed by `X\`
+    assert_eq!(directive.count, 2);
+    assert_eq!(directive.support, Support::Supported);
+
+    let binary = report.get(Feature::Binary).unwrap();
+    assert_eq!(binary.count, 1);
+    assert_eq!(binary.support, Support::Supported);
+
+    let scope = report.get(Feature::ScopeBlock).unwrap();
+    assert_eq!(scope.support, Support::Supported);
+    assert!(input[scope.first_offset..].starts_with("&SCOPE"));
+
+    assert_eq!(report.unsupported().count(), 0);
+    assert!(ruststep::parser::parse_data_section(input).is_ok());
+}
+
+#[test]
+fn supported_with_option() {
+    let report = analyze("DATA；\n#1 = POINT(- 5, 1.0-2.0)；\nENDSEC；\n");
+    assert_eq!(report.get(Feature::FullwidthPunctuation).unwrap().count, 3);
+    assert_eq!(report.get(Feature::SpaceAfterSign).unwrap().count, 1);
+    assert!(report.is_supported());
+    assert_eq!(
+        report.required_options(),
+        vec![
+            "ParseOptions::normalize_fullwidth_punctuation",
+            "ParseOptions::space_after_sign"
+        ]
+    );
+}
+
+#[test]
+fn loose_real() {
+    let report =
+        analyze("DATA;\n#1 = POINT(.5, -5E-2, 1.5E2, .T., 'x(.5');\n#2 = A5E2(1);\nENDSEC;\n");
+    let loose = report.get(Feature::LooseReal).unwrap();
+    assert_eq!(loose.count, 2);
+    assert_eq!(
+        loose.support,
+        Support::SupportedWithOption("ParseOptions::loose_reals")
+    );
+    assert_eq!(report.required_options(), vec!["ParseOptions::loose_reals"]);
+}
+
+#[test]
+fn lowercase_keyword() {
+    let input = "DATA;\n#1 = Cartesian_Point('a(b)', (0.0), .true.);\n#2 = A(B(.T.), 1.5E2, <a.b.c>);\n#3 = !my_entity();\nENDSEC;\n";
+    let report = analyze(input);
+    let lowercase = report.get(Feature::LowercaseKeyword).unwrap();
+    assert_eq!(lowercase.count, 3);
+    assert!(input[lowercase.first_offset..].starts_with("Cartesian_Point"));
+    assert_eq!(
+        report.required_options(),
+        vec!["ParseOptions::case_insensitive_keywords"]
+    );
+}
+
+#[test]
+fn abc_dataset() {
+    let input = include_str!("steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    let report = analyze(input);
+    assert!(report.is_supported());
+    assert!(report.get(Feature::ComplexInstance).is_some());
+    assert!(ruststep::parser::parse(input).is_ok());
+}
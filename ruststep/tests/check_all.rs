@@ -0,0 +1,125 @@
+// Test for collecting errors of all instances by `Tables::check_all`
+
+use ruststep::{ast::DataSection, error::Error, parser, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA geometry;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY direction;
+        name: STRING;
+        direction_ratios: LIST [2:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY placement;
+        name: STRING;
+        location: cartesian_point;
+        axis: OPTIONAL direction;
+        ref_direction: OPTIONAL direction;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA product;
+      ENTITY product_context;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY product;
+        id: STRING;
+        name: STRING;
+        description: STRING;
+        frame_of_reference: SET [0:?] OF product_context;
+      END_ENTITY;
+
+      ENTITY representation_item;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY representation_context;
+        context_identifier: STRING;
+      END_ENTITY;
+
+      ENTITY shape_representation;
+        name: STRING;
+        items: SET [0:?] OF representation_item;
+        context_of_items: OPTIONAL representation_context;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+#[test]
+fn fixture() {
+    let exchange = parser::parse(include_str!("steps/anchors.step")).unwrap();
+    let tables = product::Tables::from_data_section(&exchange.data[0]).unwrap();
+    assert!(tables.check_all().is_empty());
+}
+
+fn corrupted() -> geometry::Tables {
+    geometry::Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+              #2 = DIRECTION('axis', (0.0, 0.0, 1.0));
+              #3 = PLACEMENT('', #1, #2, $);
+              #4 = PLACEMENT('', #10, #2, $);
+              #5 = PLACEMENT('', #1, #11, #2);
+              #6 = PLACEMENT('', #1, #2, #3);
+              #7 = PLACEMENT('', #1, #2, #2);
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn corrupted_fixture() {
+    let tables = corrupted();
+    let errors = tables.check_all();
+    assert_eq!(
+        errors.iter().map(|e| e.id).collect::<Vec<_>>(),
+        vec![4, 5, 6]
+    );
+    assert!(errors.iter().all(|e| e.keyword == "PLACEMENT"));
+    assert!(matches!(
+        errors[0].error.without_context(),
+        Error::UnknownEntity(10)
+    ));
+    assert!(matches!(
+        errors[1].error.without_context(),
+        Error::UnknownEntity(11)
+    ));
+    // #3 is not a DIRECTION
+    assert!(matches!(
+        errors[2].error.without_context(),
+        Error::UnknownEntity(3)
+    ));
+
+    assert_eq!(
+        ErrorSummary(&errors).to_string(),
+        "3 instances failed: PLACEMENT (3)\n\
+         in #4 (PLACEMENT): Lookup failed for #10\n\
+         in #5 (PLACEMENT): Lookup failed for #11\n\
+         in #6 (PLACEMENT): Lookup failed for #3\n"
+    );
+}
+
+#[test]
+fn lenient() {
+    let tables = corrupted();
+    let errors = with_lenient_references(|| tables.check_all());
+    // Dangling references in OPTIONAL attributes are accepted
+    assert_eq!(errors.iter().map(|e| e.id).collect::<Vec<_>>(), vec![4]);
+}
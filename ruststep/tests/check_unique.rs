@@ -0,0 +1,64 @@
+// Test for `Tables::check_unique_*` generated for UNIQUE rules
+
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY context;
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY drawing_revision;
+        revision_identifier: STRING;
+        drawing_identifier: STRING;
+        frame_of_reference: context;
+      UNIQUE
+        ur1: revision_identifier, drawing_identifier;
+        ur2: frame_of_reference;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables(data: &str) -> Tables {
+    Tables::from_data_section(&DataSection::from_str(data).unwrap()).unwrap()
+}
+
+#[test]
+fn check_unique() {
+    let tables = tables(
+        r#"
+        DATA;
+          #1 = CONTEXT('mechanical');
+          #2 = CONTEXT('electrical');
+          #3 = CONTEXT('mechanical');
+          #13 = DRAWING_REVISION('A', 'D-1', #3);
+          #11 = DRAWING_REVISION('A', 'D-1', #1);
+          #12 = DRAWING_REVISION('B', 'D-1', #2);
+          #14 = DRAWING_REVISION('C', 'D-1', #1);
+        ENDSEC;
+        "#,
+    );
+    // #11 and #13 share identifiers, and #11 and #14 share the context instance,
+    // while #3 is another instance with the same value as #1
+    assert_eq!(tables.check_unique_drawing_revision(), [(11, 13), (11, 14)]);
+}
+
+#[test]
+fn check_unique_satisfied() {
+    let tables = tables(
+        r#"
+        DATA;
+          #1 = CONTEXT('mechanical');
+          #2 = CONTEXT('mechanical');
+          #11 = DRAWING_REVISION('A', 'D-1', #1);
+          #12 = DRAWING_REVISION('B', 'D-1', #2);
+        ENDSEC;
+        "#,
+    );
+    assert!(tables.check_unique_drawing_revision().is_empty());
+}
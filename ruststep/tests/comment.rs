@@ -0,0 +1,68 @@
+// Test for comments around sections and between tokens
+
+use ruststep::{error::Error, parser};
+
+const STEP: &str = include_str!("steps/comments.step");
+
+#[test]
+fn comments_are_ignored() {
+    let exchange = parser::parse(STEP).unwrap();
+    assert_eq!(exchange.header.len(), 3);
+    let entities = &exchange.data[0].entities;
+    assert_eq!(entities.len(), 3);
+    assert_eq!(
+        entities[0].to_string(),
+        "#1 = CARTESIAN_POINT('origin',(0.0,0.0,0.0));"
+    );
+    assert_eq!(
+        entities[1].to_string(),
+        "#2 = DIRECTION('axis',(0.0,0.0,1.0));"
+    );
+    assert_eq!(
+        entities[2].to_string(),
+        "#3 = AXIS2_PLACEMENT_3D('',#1,#2,$);"
+    );
+
+    // Same as the file without comments
+    let mut stripped = String::new();
+    let mut rest = STEP;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        let end = rest[start..].find("*/").unwrap();
+        rest = &rest[start + end + 2..];
+    }
+    stripped.push_str(rest);
+    assert!(!stripped.contains("/*"));
+    assert_eq!(exchange, parser::parse(&stripped).unwrap());
+}
+
+#[test]
+fn other_parsers() {
+    let exchange = parser::parse(STEP).unwrap();
+    assert_eq!(parser::parse_borrowed(STEP).unwrap().into_owned(), exchange);
+    let options = parser::ParseOptions {
+        recover_header: true,
+        ..Default::default()
+    };
+    assert_eq!(parser::parse_with(STEP, &options).unwrap(), exchange);
+}
+
+#[test]
+fn unterminated_comment() {
+    let truncated = &STEP[..STEP.find("between instances").unwrap()];
+    let trailing = STEP.replace("/* end of file */", "/* end of file");
+    for input in [truncated, &trailing] {
+        let offset = input.rfind("/*").unwrap();
+        let Err(Error::TokenizeFailed(e)) = parser::parse(input) else {
+            panic!("unterminated comment must be an error");
+        };
+        assert_eq!(e.offset(), offset);
+        let message = e.to_string();
+        assert!(
+            message.contains(&format!("at byte {}", offset)),
+            "{}",
+            message
+        );
+        assert!(message.contains("unterminated comment"), "{}", message);
+    }
+}
@@ -0,0 +1,101 @@
+// Test for resolving constant names, e.g. `#ORIGIN`, through `Constants` registered in tables
+
+use ruststep::{
+    ast::{DataSection, Parameter},
+    error::Error,
+    tables::*,
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables() -> Tables {
+    Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = POINT(1.0, 2.0);
+              #2 = SEGMENT(#ORIGIN, #1);
+              #3 = SEGMENT(#1, #UNIT_X);
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn segment(tables: &Tables, id: u64) -> ruststep::error::Result<Segment> {
+    EntityTable::<SegmentHolder>::get_owned(tables, id)
+}
+
+#[test]
+fn resolve_constant_entity() {
+    let mut tables = tables();
+    let constants = tables.constants_mut();
+    constants.insert_entity("ORIGIN", Parameter::from_str("POINT((0.0, 0.0))").unwrap());
+    // Constants may refer entity instances
+    constants.insert_entity("UNIT_X", Parameter::from_str("#1").unwrap());
+
+    let origin = segment(&tables, 2).unwrap();
+    let unit_x = segment(&tables, 3).unwrap();
+    assert_eq!(origin.head, Point { x: 0.0, y: 0.0 });
+    assert_eq!(origin.tail, Point { x: 1.0, y: 2.0 });
+    assert_eq!(unit_x.tail, Point { x: 1.0, y: 2.0 });
+}
+
+#[test]
+fn unknown_constant() {
+    let mut tables = tables();
+    let err = segment(&tables, 2).unwrap_err();
+    assert!(matches!(err.without_context(), Error::UnknownConstant(name) if name == "#ORIGIN"));
+    assert_eq!(
+        err.to_string(),
+        "in #2 (SEGMENT): Constant #ORIGIN is not defined"
+    );
+
+    // Constants are kept by each tables
+    let other = tables.clone();
+    tables
+        .constants_mut()
+        .insert_entity("ORIGIN", Parameter::from_str("POINT((0.0, 0.0))").unwrap());
+    assert!(segment(&tables, 2).is_ok());
+    assert!(segment(&other, 2).is_err());
+}
+
+#[test]
+fn value_instance() {
+    let tables = Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = POINT(1.0, 2.0);
+              #2 = SEGMENT(@3, #1);
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let err = segment(&tables, 2).unwrap_err();
+    assert!(matches!(
+        err.without_context(),
+        Error::UnsupportedValueInstance(3)
+    ));
+}
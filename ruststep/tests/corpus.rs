@@ -0,0 +1,38 @@
+// Code generated for the schema corpus of espr must compile without any warnings
+//
+// Snapshots of the generated code are checked by `espr/tests/corpus.rs`,
+// and this test compiles the same code by trybuild as `deny_warnings.rs`.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+use std::{fs, path::Path};
+
+#[test]
+fn compile_corpus() {
+    let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("../espr/tests/corpus");
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("corpus");
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut schemas: Vec<_> = fs::read_dir(&corpus)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "exp"))
+        .collect();
+    schemas.sort();
+    assert!(!schemas.is_empty());
+
+    let t = trybuild::TestCases::new();
+    for path in schemas {
+        let express = fs::read_to_string(&path).unwrap();
+        let st = SyntaxTree::parse(&express).unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let code = rustfmt(ir.to_token_stream(CratePrefix::External).to_string());
+        // Schema modules are not used in this binary
+        let main = format!(
+            "#![deny(warnings)]\n#![allow(dead_code)]\n\n{}\nfn main() {{}}\n",
+            code
+        );
+        let out = dir.join(path.with_extension("rs").file_name().unwrap());
+        fs::write(&out, main).unwrap();
+        t.pass(out);
+    }
+}
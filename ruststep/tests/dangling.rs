@@ -0,0 +1,118 @@
+// Test for dangling references, i.e. references to entities not in the tables
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+
+      ENTITY annotation;
+        text: STRING;
+        anchor: OPTIONAL point;
+        target: OPTIONAL shape;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: point_list;
+      END_ENTITY;
+
+      TYPE point_list = LIST [2:?] OF point;
+      END_TYPE;
+
+      TYPE shape = SELECT (segment, polyline);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables() -> Tables {
+    Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = POINT(0.0, 0.0);
+              #2 = SEGMENT(#1, #90);
+              #3 = ANNOTATION('a', #91, #2);
+              #4 = ANNOTATION('b', #1, SEGMENT((#1, #92)));
+              #5 = POLYLINE(POINT_LIST(((#1, #93))));
+              #6 = ANNOTATION('c', $, #1);
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn dangling(from: u64, attribute: &str, to: u64) -> DanglingRef {
+    DanglingRef {
+        from,
+        attribute: attribute.to_string(),
+        to,
+    }
+}
+
+#[test]
+fn validate_references() {
+    let tables = tables();
+    assert_eq!(
+        tables.validate_references(),
+        vec![
+            dangling(2, "tail", 90),
+            dangling(3, "anchor", 91),
+            // In inline instances
+            dangling(4, "target", 92),
+            dangling(5, "points", 93),
+        ]
+    );
+    assert!(Tables::default().validate_references().is_empty());
+}
+
+#[test]
+fn strict_by_default() {
+    let tables = tables();
+    let err = EntityTable::<AnnotationHolder>::get_owned(&tables, 3).unwrap_err();
+    assert!(matches!(err.without_context(), Error::UnknownEntity(91)));
+    assert!(EntityTable::<AnnotationHolder>::owned_iter(&tables).any(|a| a.is_err()));
+}
+
+#[test]
+fn lenient_optional_attributes() {
+    let tables = tables();
+    let annotation =
+        |id| with_lenient_references(|| EntityTable::<AnnotationHolder>::get_owned(&tables, id));
+
+    // Dangling references in OPTIONAL attributes are resolved into `None`
+    let a = annotation(3).unwrap();
+    assert_eq!(a.anchor, None);
+    // through a mandatory attribute of the referred entity
+    assert_eq!(a.target, None);
+
+    let b = annotation(4).unwrap();
+    assert_eq!(b.anchor, Some(Point { x: 0.0, y: 0.0 }));
+    assert_eq!(b.target, None);
+
+    // A reference to an entity of other type is not found in the tables of `shape` either
+    let c = annotation(6).unwrap();
+    assert_eq!(c.target, None);
+
+    // Mandatory attributes are still strict
+    let err = with_lenient_references(|| EntityTable::<SegmentHolder>::get_owned(&tables, 2))
+        .unwrap_err();
+    assert!(matches!(err.without_context(), Error::UnknownEntity(90)));
+
+    // The mode is restored
+    assert!(EntityTable::<AnnotationHolder>::get_owned(&tables, 3).is_err());
+}
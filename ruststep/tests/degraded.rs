@@ -0,0 +1,24 @@
+// Code generated for the supported subset of a schema must compile without any warnings
+//
+// The skipped declarations are checked by `espr/tests/degraded.rs`.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::Options};
+use std::{fs, path::Path};
+
+#[test]
+fn compile_degraded() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../espr/tests/degraded/mixed.exp");
+    let express = fs::read_to_string(path).unwrap();
+    let st = SyntaxTree::parse(&express).unwrap();
+    let outcome = generate_degraded(&st, &Options::default(), CratePrefix::External).unwrap();
+    assert!(!outcome.skipped.is_empty());
+
+    let code = rustfmt(outcome.tokens.to_string());
+    let main = format!(
+        "#![deny(warnings)]\n#![allow(dead_code)]\n\n{}\nfn main() {{}}\n",
+        code
+    );
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("degraded.rs");
+    fs::write(&out, main).unwrap();
+    trybuild::TestCases::new().pass(out);
+}
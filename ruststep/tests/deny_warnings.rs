@@ -0,0 +1,148 @@
+// Generated code must compile without any warnings
+//
+// Lints are not reported for the code expanded by `espr_derive::inline_express!`
+// since it comes from an external proc-macro.
+// Thus the generated code is written into files and compiled by trybuild.
+
+use espr::{ast::SyntaxTree, codegen::rust::*, ir::IR};
+use std::{fs, path::Path};
+
+const ONLY_ENTITIES: &str = r#"
+SCHEMA only_entities;
+  ENTITY a;
+    x: REAL;
+    y: OPTIONAL INTEGER;
+  END_ENTITY;
+
+  ENTITY b;
+    a: a;
+    name: STRING;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+const ONLY_ENUMERATION: &str = r#"
+SCHEMA only_enumeration;
+  TYPE color = ENUMERATION OF (red, green, blue);
+  END_TYPE;
+END_SCHEMA;
+"#;
+
+const TYPE_DECLS: &str = r#"
+SCHEMA type_decls;
+  TYPE label = STRING;
+  END_TYPE;
+
+  TYPE flag = LOGICAL;
+  END_TYPE;
+
+  TYPE color = ENUMERATION OF (red, green, blue);
+  END_TYPE;
+
+  TYPE named_color = color;
+  END_TYPE;
+
+  TYPE text = label;
+  END_TYPE;
+
+  TYPE positive = REAL;
+  WHERE
+    wr1: SELF > 0.0;
+  END_TYPE;
+
+  TYPE size = positive;
+  WHERE
+    wr1: SELF < 100.0;
+  END_TYPE;
+
+  TYPE item = SELECT (label, named_color);
+  END_TYPE;
+
+  ENTITY e;
+    label: label;
+    flag: flag;
+    color: named_color;
+    text: text;
+    size: size;
+    item: item;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+const SUBSUPER: &str = r#"
+SCHEMA subsuper;
+  ENTITY base SUPERTYPE OF (ONEOF (sub, other));
+    x: REAL;
+  END_ENTITY;
+
+  ENTITY sub SUPERTYPE OF (subsub) SUBTYPE OF (base);
+    y: REAL;
+  END_ENTITY;
+
+  ENTITY subsub SUBTYPE OF (sub);
+    z: REAL;
+  END_ENTITY;
+
+  ENTITY other SUBTYPE OF (base);
+    w: LIST [1:?] OF REAL;
+  END_ENTITY;
+
+  ENTITY user;
+    base: base;
+    items: SET [0:?] OF sub;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+const DERIVED: &str = r#"
+SCHEMA derived;
+  ENTITY point;
+    x: REAL;
+    y: OPTIONAL REAL;
+    n: INTEGER;
+  DERIVE
+    half: REAL := x / 2.0;
+    shifted: REAL := x + y;
+    twice: INTEGER := n * 2;
+  END_ENTITY;
+END_SCHEMA;
+"#;
+
+const MIXED_CASE: &str = r#"
+SCHEMA Mixed_Case;
+  ENTITY Cartesian_Point;
+    Coordinates: LIST [1:3] OF REAL;
+  END_ENTITY;
+
+  TYPE Label = STRING;
+  END_TYPE;
+END_SCHEMA;
+"#;
+
+#[test]
+fn compile_generated_code() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("deny_warnings");
+    fs::create_dir_all(&dir).unwrap();
+
+    let t = trybuild::TestCases::new();
+    for (name, express) in [
+        ("only_entities", ONLY_ENTITIES),
+        ("only_enumeration", ONLY_ENUMERATION),
+        ("type_decls", TYPE_DECLS),
+        ("subsuper", SUBSUPER),
+        ("derived", DERIVED),
+        ("mixed_case", MIXED_CASE),
+    ] {
+        let st = SyntaxTree::parse(express).unwrap();
+        let ir = IR::from_syntax_tree(&st).unwrap();
+        let code = rustfmt(ir.to_token_stream(CratePrefix::External).to_string());
+        // Schema modules are not used in this binary
+        let main = format!(
+            "#![deny(warnings)]\n#![allow(dead_code)]\n\n{}\nfn main() {{}}\n",
+            code
+        );
+        let path = dir.join(format!("{}.rs", name));
+        fs::write(&path, main).unwrap();
+        t.pass(path);
+    }
+}
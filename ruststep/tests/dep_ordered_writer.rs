@@ -0,0 +1,115 @@
+// Test for writing entity instances in the order of dependencies
+
+use ruststep::{
+    ast::EntityInstance,
+    error::Error,
+    parser,
+    writer::{DepOrderedWriter, Warning},
+};
+use std::collections::HashSet;
+
+fn write_all(input: &str) -> (Vec<EntityInstance>, Vec<Warning>, usize) {
+    let mut writer = DepOrderedWriter::new(Vec::new());
+    for instance in parser::parse_record_list(input).unwrap() {
+        writer.write(instance).unwrap();
+    }
+    let peak = writer.peak_pending();
+    let (out, warnings) = writer.finish().unwrap();
+    let out = String::from_utf8(out).unwrap();
+    (parser::parse_record_list(&out).unwrap(), warnings, peak)
+}
+
+fn ids(instances: &[EntityInstance]) -> Vec<u64> {
+    instances.iter().map(|instance| instance.id()).collect()
+}
+
+/// Every reference refers an instance written before
+fn assert_ordered(instances: &[EntityInstance]) {
+    let mut written = HashSet::new();
+    for instance in instances {
+        for r in instance.references() {
+            assert!(
+                written.contains(&r),
+                "#{} refers #{} written later",
+                instance.id(),
+                r
+            );
+        }
+        written.insert(instance.id());
+    }
+}
+
+#[test]
+fn reverse_chain() {
+    let input: String = (1..=100)
+        .rev()
+        .map(|i| match i {
+            1 => "#1 = POINT(0.0);".to_string(),
+            _ => format!("#{} = NEXT(#{});", i, i - 1),
+        })
+        .collect();
+    let (instances, warnings, peak) = write_all(&input);
+    assert!(warnings.is_empty());
+    assert_eq!(ids(&instances), (1..=100).collect::<Vec<_>>());
+    assert_eq!(peak, 99);
+}
+
+#[test]
+fn bounded_buffering() {
+    // Each edge is given before its vertices, and each vertex before its point
+    let mut input = String::new();
+    for i in 0..1000 {
+        let id = 10 * i;
+        input += &format!(
+            "#{e} = EDGE(#{v1}, #{v2}); #{v1} = VERTEX(#{p1}); #{p1} = POINT(1.0); #{v2} = VERTEX(#{p2}); #{p2} = POINT(2.0);",
+            e = id + 1,
+            v1 = id + 2,
+            p1 = id + 3,
+            v2 = id + 4,
+            p2 = id + 5,
+        );
+    }
+    let (instances, warnings, peak) = write_all(&input);
+    assert!(warnings.is_empty());
+    assert_eq!(instances.len(), 5000);
+    assert_ordered(&instances);
+    // Pending instances are at most the edge and a vertex, independent of the number of edges
+    assert_eq!(peak, 2);
+}
+
+#[test]
+fn cycle() {
+    let (instances, warnings, _peak) =
+        write_all("#4 = USER(#3); #3 = LOOP(#2, #1); #2 = NODE(#1); #1 = NODE(#2, #5);");
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::MissingReference { id: 5 },
+            Warning::CyclicReference { ids: vec![1, 2] },
+        ]
+    );
+    assert_eq!(ids(&instances), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn self_reference_is_not_cycle() {
+    let (instances, warnings, peak) = write_all("#2 = B(#1); #1 = A(#1);");
+    assert!(warnings.is_empty());
+    assert_eq!(ids(&instances), vec![1, 2]);
+    assert_eq!(peak, 1);
+}
+
+#[test]
+fn duplicated_id() {
+    let mut writer = DepOrderedWriter::new(Vec::new());
+    for instance in parser::parse_record_list("#1 = A(#2); #2 = B(0.0);").unwrap() {
+        writer.write(instance).unwrap();
+    }
+    for input in ["#1 = C(0.0);", "#2 = C(0.0);"] {
+        let instance = parser::parse_record_list(input).unwrap().remove(0);
+        assert!(matches!(
+            writer.write(instance),
+            Err(Error::DuplicatedEntity(_))
+        ));
+    }
+}
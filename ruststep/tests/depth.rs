@@ -0,0 +1,50 @@
+// Test for limiting the depth of nested parameters
+
+use nom::{Finish, Parser};
+use ruststep::{
+    error::{Error, TokenError},
+    parser::{exchange, parse_data_section, ParseOptions},
+};
+
+fn nested(depth: usize) -> String {
+    format!("{}1{}", "(".repeat(depth), ")".repeat(depth))
+}
+
+#[test]
+fn deep_input_fails() {
+    let depth = 10_000;
+    let input = format!("DATA; #1 = A({}); ENDSEC;", nested(depth));
+    let Error::TokenizeFailed(err) = parse_data_section(&input).unwrap_err() else {
+        panic!()
+    };
+    // The parameter after 128 parentheses is the 129th level
+    let open = input.find("A(").unwrap() + 2;
+    assert_eq!(
+        err.token_error(),
+        Some(&TokenError::DepthLimitExceeded {
+            depth: ParseOptions::DEFAULT_MAX_DEPTH + 1,
+            offset: open + ParseOptions::DEFAULT_MAX_DEPTH,
+        })
+    );
+    assert_eq!(err.offset(), open + ParseOptions::DEFAULT_MAX_DEPTH);
+}
+
+#[test]
+fn within_limit() {
+    let input = nested(ParseOptions::DEFAULT_MAX_DEPTH - 1);
+    assert!(exchange::parameter(&input).finish().is_ok());
+    let input = nested(ParseOptions::DEFAULT_MAX_DEPTH);
+    assert!(exchange::parameter(&input).finish().is_err());
+}
+
+#[test]
+fn configurable() {
+    let options = ParseOptions {
+        max_depth: 3,
+        ..ParseOptions::strict()
+    };
+    let ok = exchange::parameter_with(&options).parse("((1))").finish();
+    assert!(ok.is_ok());
+    let err = exchange::parameter_with(&options).parse("(((1)))").finish();
+    assert!(err.is_err());
+}
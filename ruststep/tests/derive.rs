@@ -0,0 +1,68 @@
+// Test for accessors of DERIVE attributes
+
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY a;
+        x: REAL;
+        y: OPTIONAL REAL;
+        n: INTEGER;
+      DERIVE
+        half: REAL := x / 2.0;
+        shifted: REAL := y + n;
+        twice: INTEGER := n * 2;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = A(3.0, 0.5, 2);
+  #2 = A(5.0, $, 9223372036854775807);
+ENDSEC;
+"#;
+
+#[test]
+fn evaluate_derived() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let a = EntityTable::<AHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(a.half(), Some(1.5));
+    assert_eq!(a.shifted(), Some(2.5));
+    assert_eq!(a.twice(), Some(4));
+}
+
+#[test]
+fn indeterminate() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let a = EntityTable::<AHolder>::get_owned(&table, 2).unwrap();
+    assert_eq!(a.half(), Some(2.5));
+    // `y` is not provided
+    assert_eq!(a.shifted(), None);
+    // overflow
+    assert_eq!(a.twice(), None);
+}
+
+#[test]
+fn memoized() {
+    let table = Tables::from_str(EXAMPLE).unwrap();
+    let mut a = EntityTable::<AHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(a.half(), Some(1.5));
+    // Cache is not a part of the value
+    assert_eq!(a, EntityTable::<AHolder>::get_owned(&table, 1).unwrap());
+
+    // Kept until cleared
+    a.x = 5.0;
+    assert_eq!(a.half(), Some(1.5));
+    a.half_cache.clear();
+    assert_eq!(a.half(), Some(2.5));
+
+    let a = A::new(1.0, None, 1);
+    assert_eq!(a.half(), Some(0.5));
+    assert_eq!(a.shifted(), None);
+}
@@ -0,0 +1,99 @@
+// Test for reading ISO 13399 dictionary and typed property lookups by generated markers
+
+use ruststep::{ast::*, dictionary::*, error::Error};
+use std::{fs, path::Path, str::FromStr};
+
+fn dictionary() -> Dictionary {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/steps/database.p21");
+    let exchange = ruststep::parser::parse(&fs::read_to_string(path).unwrap()).unwrap();
+    Dictionary::from_data_section(&exchange.data[0]).unwrap()
+}
+
+#[test]
+fn read_properties() {
+    let dictionary = dictionary();
+    assert_eq!(dictionary.properties.len(), 567 + 23);
+
+    let angle = dictionary.property("71D078F683C9B").unwrap();
+    assert_eq!(angle.name, "tool cutting edge angle");
+    assert_eq!(angle.version, "003");
+    assert_eq!(angle.revision, "002");
+    assert_eq!(
+        angle.data_type,
+        DataType::Real {
+            unit: Some("deg".to_string())
+        }
+    );
+    assert!(dictionary.property("71D078EB7C086").is_some());
+}
+
+/// Written by hand as [generate_properties] does
+struct OverallLength;
+
+impl PropertyDef for OverallLength {
+    const CODE: &'static str = "71D078EB7C086";
+    const VERSION: &'static str = "003";
+    type Value = Measure<units::Millimetre>;
+}
+
+#[test]
+fn catalogue() {
+    let mut catalogue = Catalogue::new();
+    catalogue.insert(
+        "T1",
+        OverallLength::CODE,
+        Parameter::from_str("LENGTH_MEASURE(120.5)").unwrap(),
+    );
+    catalogue.insert(
+        "T2",
+        OverallLength::CODE,
+        Parameter::from_str("'long'").unwrap(),
+    );
+
+    let length = catalogue.value_of::<OverallLength>("T1").unwrap().unwrap();
+    assert_eq!(length.value, 120.5);
+    assert_eq!(length.to_string(), "120.5 mm");
+    assert_eq!(catalogue.value_of::<OverallLength>("T3").unwrap(), None);
+    assert!(matches!(
+        catalogue.value_of::<OverallLength>("T2").unwrap_err(),
+        Error::DeserializeFailed(_)
+    ));
+}
+
+#[test]
+fn generated_properties() {
+    let code = generate_properties(&dictionary());
+    let main = format!(
+        r#"#![deny(warnings)]
+#![allow(dead_code)]
+
+mod properties {{
+{}
+}}
+
+use properties::*;
+use ruststep::{{ast::Parameter, dictionary::*}};
+use std::str::FromStr;
+
+fn main() {{
+    let mut catalogue = Catalogue::new();
+    catalogue.insert("T1", ToolCuttingEdgeAngle::CODE, Parameter::from_str("95.0").unwrap());
+    catalogue.insert("T1", OverallLength::CODE, Parameter::from_str("120.0").unwrap());
+
+    let angle: Measure<units::Degree> = catalogue.value_of::<ToolCuttingEdgeAngle>("T1").unwrap().unwrap();
+    assert_eq!(angle.value, 95.0);
+    let length: Measure<units::Millimetre> = catalogue.value_of::<OverallLength>("T1").unwrap().unwrap();
+    assert_eq!(length.value, 120.0);
+
+    let info = property("71D078F683C9B").unwrap();
+    assert_eq!(info.name, "tool cutting edge angle");
+    assert_eq!(info.unit, Some("deg"));
+    assert_eq!(ToolCuttingEdgeAngle::VERSION, info.version);
+}}
+"#,
+        code
+    );
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join("dictionary.rs");
+    fs::write(&out, main).unwrap();
+    trybuild::TestCases::new().pass(out);
+}
@@ -0,0 +1,143 @@
+// Test for structural diff of exchanges regardless of entity ids
+
+use ruststep::{ast::*, diff::*, parser};
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+
+const BEFORE: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('before'), '2;1');
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#3 = VERTEX_POINT('', #1);
+#4 = VERTEX_POINT('', #2);
+#5 = EDGE_CURVE('', #3, #4, $, .T.);
+#6 = PRODUCT('bolt', 'M6 bolt', '', ());
+#7 = (NAMED_UNIT(*) LENGTH_UNIT() SI_UNIT(.MILLI., .METRE.));
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+// Renumbered and reordered
+const RENUMBERED: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('after'), '2;1');
+ENDSEC;
+DATA;
+#100 = (NAMED_UNIT(*) LENGTH_UNIT() SI_UNIT(.MILLI., .METRE.));
+#101 = EDGE_CURVE('', #103, #102, $, .T.);
+#102 = VERTEX_POINT('', #105);
+#103 = VERTEX_POINT('', #104);
+#104 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#105 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+#106 = PRODUCT('bolt', 'M6 bolt', '', ());
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+fn read(name: &str) -> Exchange {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps")
+        .join(name);
+    parser::parse(&fs::read_to_string(step_file).unwrap()).unwrap()
+}
+
+#[test]
+fn renumbered_only() {
+    let before = Exchange::from_str(BEFORE).unwrap();
+    let after = Exchange::from_str(RENUMBERED).unwrap();
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert!(report.is_empty());
+    assert_eq!(report.to_string(), "no differences\n");
+    assert_eq!(
+        report.matched,
+        [
+            (1, 104),
+            (2, 105),
+            (3, 103),
+            (4, 102),
+            (5, 101),
+            (6, 106),
+            (7, 100)
+        ]
+    );
+}
+
+#[test]
+fn renumbered_cad_file() {
+    let before = read("00000050_80d90bfdd2e74e709956122a_step_000.step");
+    let mut after = before.clone();
+    let section = &mut after.data[0];
+    let max = section.entities.iter().map(|e| e.id()).max().unwrap();
+    let ids: HashMap<u64, u64> = section
+        .entities
+        .iter()
+        .map(|e| (e.id(), max + 1 - e.id()))
+        .collect();
+    for instance in &mut section.entities {
+        instance.renumber(&ids);
+    }
+    section.entities.reverse();
+
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert!(report.is_empty(), "{}", report);
+    assert_eq!(report.matched.len(), before.data[0].entities.len());
+}
+
+#[test]
+fn modified() {
+    let before = Exchange::from_str(BEFORE).unwrap();
+    let after = Exchange::from_str(
+        &RENUMBERED
+            .replace("(1.0, 0.0, 0.0)", "(1.0, 0.0, 2.5)")
+            .replace(
+                "#106 = PRODUCT('bolt', 'M6 bolt', '', ());",
+                "#106 = PRODUCT('nut', 'M6 nut', '', ());",
+            )
+            .replace(".MILLI.", ".CENTI."),
+    )
+    .unwrap();
+
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert!(report.removed.is_empty());
+    assert!(report.added.is_empty());
+    assert_eq!(
+        report.to_string(),
+        "modified #2 -> #105\n  CARTESIAN_POINT[1][2]: 0.0 -> 2.5\n\
+         modified #6 -> #106\n  PRODUCT[0]: 'bolt' -> 'nut'\n  PRODUCT[1]: 'M6 bolt' -> 'M6 nut'\n\
+         modified #7 -> #100\n  SI_UNIT[0]: .MILLI. -> .CENTI.\n"
+    );
+    assert_eq!(report.modified[2].changes[0].record, 2);
+
+    // Replaced by an instance of another keyword
+    let after = Exchange::from_str(&RENUMBERED.replace(
+        "#106 = PRODUCT('bolt', 'M6 bolt', '', ());",
+        "#106 = PRODUCT_DEFINITION('design', '', #101, #100);",
+    ))
+    .unwrap();
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert_eq!(report.removed, [6]);
+    assert_eq!(report.added, [106]);
+    assert!(report.modified.is_empty());
+}
+
+#[test]
+fn tolerance() {
+    let before = Exchange::from_str(BEFORE).unwrap();
+    let after =
+        Exchange::from_str(&RENUMBERED.replace("(1.0, 0.0, 0.0)", "(0.9999999, 1.E-7, 0.0)"))
+            .unwrap();
+
+    let report = diff(&before, &after, &DiffOptions { tolerance: 1e-6 });
+    assert!(report.is_empty(), "{}", report);
+
+    let report = diff(&before, &after, &DiffOptions::default());
+    assert_eq!(report.modified.len(), 1);
+    let paths: Vec<_> = report.modified[0]
+        .changes
+        .iter()
+        .map(|change| change.path.clone())
+        .collect();
+    assert_eq!(paths, [vec![1, 0], vec![1, 1]]);
+}
@@ -0,0 +1,199 @@
+// Test for undo/redo of edits on data section
+
+use ruststep::{ast::*, edit::EditSession, error::Error};
+use std::str::FromStr;
+
+const SECTION: &str = r#"
+DATA;
+  #1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+  #2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));
+  #3 = VERTEX_POINT('v', #1);
+  #4 = VERTEX_POINT('w', #2);
+ENDSEC;
+"#;
+
+fn section(s: &str) -> DataSection {
+    DataSection::from_str(s).unwrap()
+}
+
+#[test]
+fn undo_twice_redo_once() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data);
+
+    // 1. insert
+    session
+        .insert(EntityInstance::from_str("#5 = CARTESIAN_POINT('y', (0.0, 1.0, 0.0));").unwrap())
+        .unwrap();
+    let after_insert = session.section().clone();
+
+    // 2. rewrite
+    assert_eq!(session.rewrite_references(1, 5), 1);
+    let after_rewrite = session.section().clone();
+
+    // 3. gc
+    assert_eq!(session.gc(&[3, 4]), 1);
+    assert_eq!(
+        session.section(),
+        &section(
+            r#"
+            DATA;
+              #2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));
+              #3 = VERTEX_POINT('v', #5);
+              #4 = VERTEX_POINT('w', #2);
+              #5 = CARTESIAN_POINT('y', (0.0, 1.0, 0.0));
+            ENDSEC;
+            "#
+        )
+    );
+
+    assert!(session.undo());
+    assert_eq!(session.section(), &after_rewrite);
+    assert!(session.undo());
+    assert_eq!(session.section(), &after_insert);
+    assert_eq!(
+        session.section(),
+        &section(
+            r#"
+            DATA;
+              #1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+              #2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));
+              #3 = VERTEX_POINT('v', #1);
+              #4 = VERTEX_POINT('w', #2);
+              #5 = CARTESIAN_POINT('y', (0.0, 1.0, 0.0));
+            ENDSEC;
+            "#
+        )
+    );
+
+    assert!(session.redo());
+    assert_eq!(session.section(), &after_rewrite);
+
+    // New operation discards the redo stack
+    session.remove(4).unwrap();
+    assert!(!session.redo());
+
+    assert!(session.undo());
+    assert!(session.undo());
+    assert!(session.undo());
+    assert!(!session.undo());
+    drop(session);
+    assert_eq!(data, section(SECTION));
+}
+
+#[test]
+fn set_attribute() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data);
+    let previous = session
+        .set_attribute(3, 0, Parameter::String("renamed".to_string()))
+        .unwrap();
+    assert_eq!(previous, Parameter::String("v".to_string()));
+    assert_eq!(
+        session.section().instance(3).unwrap().to_string(),
+        "#3 = VERTEX_POINT('renamed',#1);"
+    );
+    assert!(session.undo());
+    assert_eq!(session.section(), &section(SECTION));
+}
+
+#[test]
+fn failed_operations_are_not_recorded() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data);
+    assert!(matches!(
+        session.insert(EntityInstance::from_str("#1 = A(1);").unwrap()),
+        Err(Error::DuplicatedEntity(1))
+    ));
+    assert!(matches!(session.remove(10), Err(Error::UnknownEntity(10))));
+    assert!(matches!(
+        session.set_attribute(10, 0, Parameter::Integer(0)),
+        Err(Error::UnknownEntity(10))
+    ));
+    assert!(matches!(
+        session.set_attribute(1, 2, Parameter::Integer(0)),
+        Err(Error::UnknownAttribute { id: 1, index: 2 })
+    ));
+    assert!(matches!(
+        session.revert_to("unknown"),
+        Err(Error::UnknownCheckpoint(_))
+    ));
+    assert_eq!(session.section(), &section(SECTION));
+    assert!(!session.undo());
+}
+
+#[test]
+fn revert_to_checkpoint() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data);
+    session.remove(4).unwrap();
+    let first = session.section().clone();
+    session.checkpoint("first");
+    session.remove(3).unwrap();
+    session.checkpoint("second");
+    session.remove(2).unwrap();
+    session.remove(1).unwrap();
+    assert!(session.section().entities.is_empty());
+
+    session.revert_to("first").unwrap();
+    assert_eq!(session.section(), &first);
+    // Checkpoints undone are moved to redo, and can be restored by redo
+    assert!(session.revert_to("second").is_err());
+    assert!(session.redo());
+    session.revert_to("second").unwrap();
+    assert_eq!(session.section().entities.len(), 2);
+
+    // Revert the first operation as well
+    assert!(session.undo());
+    assert!(session.undo());
+    assert_eq!(session.section(), &section(SECTION));
+}
+
+#[test]
+fn journal_size_evicts_oldest() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data).journal_size(2);
+    for id in 1..=4 {
+        session.remove(id).unwrap();
+    }
+    assert!(session.undo());
+    assert!(session.undo());
+    assert!(!session.undo());
+    assert_eq!(
+        session.section(),
+        &section(
+            r#"
+            DATA;
+              #3 = VERTEX_POINT('v', #1);
+              #4 = VERTEX_POINT('w', #2);
+            ENDSEC;
+            "#
+        )
+    );
+}
+
+#[test]
+fn journal_size_evicts_until_oldest_checkpoint() {
+    let mut data = section(SECTION);
+    let mut session = EditSession::new(&mut data).journal_size(3);
+    session.remove(1).unwrap();
+    session.remove(2).unwrap();
+    session.checkpoint("a");
+    session.remove(3).unwrap();
+    session.checkpoint("b");
+    session.remove(4).unwrap();
+    // Operations before "a" are evicted together, and "a" is kept
+    session.revert_to("a").unwrap();
+    assert_eq!(
+        session.section(),
+        &section(
+            r#"
+            DATA;
+              #3 = VERTEX_POINT('v', #1);
+              #4 = VERTEX_POINT('w', #2);
+            ENDSEC;
+            "#
+        )
+    );
+    assert!(!session.undo());
+}
@@ -0,0 +1,91 @@
+// Test for the instance and attribute path reported with deserialization errors
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY vertex;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY edge_loop;
+        vertices: LIST [1:?] OF vertex;
+      END_ENTITY;
+
+      ENTITY face_bound;
+        bound: edge_loop;
+        orientation: BOOLEAN;
+      END_ENTITY;
+
+      ENTITY advanced_face;
+        name: STRING;
+        bounds: SET [1:?] OF face_bound;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables(data: &str) -> ruststep::error::Result<Tables> {
+    Tables::from_data_section(&DataSection::from_str(data).unwrap())
+}
+
+#[test]
+fn corrupted_attribute() {
+    let err = tables(
+        r#"
+        DATA;
+          #1 = VERTEX(0.0);
+          #2 = EDGE_LOOP((#1));
+          #3 = FACE_BOUND(#2, .T.);
+          #4521 = ADVANCED_FACE('f', (#3, FACE_BOUND(('xyz', .T.))));
+        ENDSEC;
+        "#,
+    )
+    .unwrap_err();
+    let context = err.context().unwrap();
+    assert_eq!(context.id, Some(4521));
+    assert_eq!(context.keyword.as_deref(), Some("ADVANCED_FACE"));
+    assert_eq!(context.attribute.as_deref(), Some("bounds[1].bound"));
+    assert!(matches!(err.without_context(), Error::DeserializeFailed(_)));
+    assert!(
+        err.to_string()
+            .starts_with("in #4521 (ADVANCED_FACE), attribute 'bounds[1].bound': "),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn corrupted_top_level_attribute() {
+    let err = tables("DATA; #7 = VERTEX('origin'); ENDSEC;").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "in #7 (VERTEX), attribute 'x': invalid type: string \"origin\", expected f64"
+    );
+}
+
+#[test]
+fn dangling_reference() {
+    let tables = tables(
+        r#"
+        DATA;
+          #1 = VERTEX(0.0);
+          #2 = EDGE_LOOP((#1));
+          #3 = FACE_BOUND(#99, .T.);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let err = EntityTable::<FaceBoundHolder>::get_owned(&tables, 3).unwrap_err();
+    assert!(matches!(err.without_context(), Error::UnknownEntity(99)));
+    assert_eq!(err.context().unwrap().id, Some(3));
+    assert!(
+        err.to_string().starts_with("in #3 (FACE_BOUND): "),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,68 @@
+// Test for extracting a part of an exchange into a new file
+
+use ruststep::{ast::*, error::Error};
+use std::str::FromStr;
+
+const ASSEMBLY: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '3;1');
+FILE_NAME('assembly.stp', '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('SCHEMA'));
+ENDSEC;
+ANCHOR;
+<bolt_origin> = #1;
+<nut_origin> = #2;
+ENDSEC;
+REFERENCE;
+#100 = <materials.stp#steel>;
+#101 = <materials.stp#brass>;
+ENDSEC;
+DATA('geometry', ('SCHEMA'));
+#1 = POINT(0.0);
+#2 = POINT(1.0);
+ENDSEC;
+DATA('product', ('SCHEMA'));
+#10 = PRODUCT('bolt', #1, #100);
+#11 = PRODUCT('nut', #2, #101);
+#12 = ASSEMBLY((#10, #11));
+ENDSEC;
+END-ISO-10303-21;
+SIGNATURE QUJD ENDSEC;
+"#;
+
+#[test]
+fn extract_across_sections() {
+    let exchange = Exchange::from_str(ASSEMBLY).unwrap();
+    let bolt = exchange.extract(&[10]).unwrap();
+
+    let header = bolt.header_typed().unwrap();
+    assert_eq!(
+        header.file_description.description,
+        ["Extracted #10 from assembly.stp"]
+    );
+    assert_eq!(header.file_name, exchange.header_typed().unwrap().file_name);
+
+    let anchors: Vec<_> = bolt.anchor.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(anchors, ["bolt_origin"]);
+    let references: Vec<_> = bolt.reference.iter().map(|r| r.to_string()).collect();
+    assert_eq!(references, ["#100 = <materials.stp#steel>;"]);
+    let ids: Vec<Vec<u64>> = bolt
+        .data
+        .iter()
+        .map(|section| section.entities.iter().map(|e| e.id()).collect())
+        .collect();
+    assert_eq!(ids, [vec![1], vec![10]]);
+    assert_eq!(bolt.data[1].name(), Some("product"));
+    assert!(bolt.signature.is_empty());
+
+    // Written as a standalone file
+    assert_eq!(Exchange::from_str(&bolt.to_string()).unwrap(), bolt);
+}
+
+#[test]
+fn extract_dangling() {
+    let exchange = Exchange::from_str(&ASSEMBLY.replace("#2 = POINT(1.0);\n", "")).unwrap();
+    assert!(exchange.extract(&[10]).is_ok());
+    let err = exchange.extract(&[12, 3]).unwrap_err();
+    assert!(matches!(err, Error::DanglingReferences(ref ids) if ids == &[2, 3]));
+}
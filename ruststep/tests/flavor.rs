@@ -0,0 +1,168 @@
+// Test for adapting AP203 records into entities shared with AP214 while loading tables
+
+use ruststep::{
+    ast::{Parameter, Record},
+    flavor::*,
+    load::*,
+    tables::*,
+};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA shared_schema;
+      ENTITY application_context;
+        application: STRING;
+      END_ENTITY;
+
+      ENTITY product_context;
+        name: STRING;
+        frame_of_reference: application_context;
+        discipline_type: STRING;
+      END_ENTITY;
+
+      ENTITY product;
+        id: STRING;
+        name: STRING;
+        description: STRING;
+        frame_of_reference: SET [1:?] OF product_context;
+      END_ENTITY;
+
+      ENTITY product_definition_formation;
+        id: STRING;
+        description: STRING;
+        of_product: product;
+      END_ENTITY;
+
+      ENTITY product_definition_context;
+        name: STRING;
+        frame_of_reference: application_context;
+        life_cycle_stage: STRING;
+      END_ENTITY;
+
+      ENTITY product_definition;
+        id: STRING;
+        description: STRING;
+        formation: product_definition_formation;
+        frame_of_reference: product_definition_context;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use shared_schema::*;
+
+const AP203: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+ENDSEC;
+DATA;
+#1 = APPLICATION_CONTEXT('configuration controlled 3d designs of mechanical parts and assemblies');
+#2 = MECHANICAL_CONTEXT('', #1, 'mechanical');
+#3 = PRODUCT('bolt', 'Bolt', '', (#2));
+#4 = PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE('1', '', #3, .NOT_KNOWN.);
+#5 = DESIGN_CONTEXT('', #1, 'design');
+#6 = PRODUCT_DEFINITION_WITH_ASSOCIATED_DOCUMENTS('design', '', #4, #5, ());
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+const AP214: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1 = APPLICATION_CONTEXT('configuration controlled 3d designs of mechanical parts and assemblies');
+#2 = PRODUCT_CONTEXT('', #1, 'mechanical');
+#3 = PRODUCT('bolt', 'Bolt', '', (#2));
+#4 = PRODUCT_DEFINITION_FORMATION('1', '', #3);
+#5 = PRODUCT_DEFINITION_CONTEXT('', #1, 'design');
+#6 = PRODUCT_DEFINITION('design', '', #4, #5);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+fn options() -> LoadOptions<'static> {
+    LoadOptions::new()
+        .flavor_adapter(ProductStructureAdapter)
+        .flavor_adapter(ContextAdapter)
+}
+
+#[test]
+fn both_flavors() {
+    let (ap203, report): (Tables, _) = load_with_report(AP203, options()).unwrap();
+    assert_eq!(
+        report.adapted,
+        [
+            ("product_structure".to_string(), 2),
+            ("context".to_string(), 2)
+        ]
+    );
+    let (ap214, report): (Tables, _) = load_with_report(AP214, options()).unwrap();
+    assert_eq!(
+        report.adapted,
+        [
+            ("product_structure".to_string(), 0),
+            ("context".to_string(), 0)
+        ]
+    );
+
+    let definition = EntityTable::<ProductDefinitionHolder>::get_owned(&ap203, 6).unwrap();
+    assert_eq!(
+        definition,
+        EntityTable::<ProductDefinitionHolder>::get_owned(&ap214, 6).unwrap()
+    );
+    assert_eq!(definition.formation.of_product.frame_of_reference.len(), 1);
+    assert_eq!(
+        definition.formation.of_product.frame_of_reference[0].discipline_type,
+        "mechanical"
+    );
+    assert_eq!(definition.frame_of_reference.life_cycle_stage, "design");
+}
+
+#[test]
+fn toggle_adapters() {
+    // AP203 contexts are unknown without ContextAdapter
+    let options = LoadOptions::new().flavor_adapter(ProductStructureAdapter);
+    assert!(load::<Tables>(AP203, options).is_err());
+
+    // and AP203 product structures without ProductStructureAdapter
+    let options = LoadOptions::new().flavor_adapter(ContextAdapter);
+    assert!(load::<Tables>(AP203, options).is_err());
+}
+
+/// Insert a default for `description` missing in an older flavor
+struct MissingDescription;
+
+impl RecordAdapter for MissingDescription {
+    fn name(&self) -> &str {
+        "missing_description"
+    }
+
+    fn adapt(&self, record: &mut Record) -> bool {
+        match &mut record.parameter {
+            Parameter::List(parameters) if record.name == "PRODUCT" && parameters.len() == 3 => {
+                parameters.insert(2, Parameter::String(String::new()));
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[test]
+fn custom_adapter() {
+    let input = AP214.replace(
+        "PRODUCT('bolt', 'Bolt', '', (#2))",
+        "PRODUCT('bolt', 'Bolt', (#2))",
+    );
+    let options = LoadOptions::new().flavor_adapter(MissingDescription);
+    let (tables, report): (Tables, _) = load_with_report(&input, options).unwrap();
+    assert_eq!(report.instances, 6);
+    assert_eq!(report.adapted, [("missing_description".to_string(), 1)]);
+    let product = EntityTable::<ProductHolder>::get_owned(&tables, 3).unwrap();
+    assert_eq!(product.description, "");
+}
@@ -0,0 +1,166 @@
+// Test for parsing data-only fragments and files with broken header
+
+use nom::{Finish, Parser};
+use ruststep::{
+    error::Error,
+    parser::{self, exchange, read_instances, ParseOptions, Warning},
+};
+
+const INSTANCES: &str = r#"
+#1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+/* comment between instances */
+#2 = VERTEX_POINT('v', #1);
+"#;
+
+fn recover() -> ParseOptions {
+    ParseOptions {
+        recover_header: true,
+        ..ParseOptions::strict()
+    }
+}
+
+#[test]
+fn record_list() {
+    let instances = parser::parse_record_list(INSTANCES).unwrap();
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0].id(), 1);
+    assert_eq!(instances[1].to_string(), "#2 = VERTEX_POINT('v',#1);");
+
+    assert!(matches!(
+        parser::parse_record_list("#1 = A(1.0); ENDSEC;"),
+        Err(Error::ExtraInputRemaining(_))
+    ));
+}
+
+#[test]
+fn headerless_data_section() {
+    let section = parser::parse_data_section(&format!(
+        "/* stored fragment */ DATA(('geometry'), ('TEST_SCHEMA')); {} ENDSEC;\n",
+        INSTANCES
+    ))
+    .unwrap();
+    assert_eq!(section.meta.len(), 2);
+    assert_eq!(
+        section.entities,
+        parser::parse_record_list(INSTANCES).unwrap()
+    );
+}
+
+fn step(header: &str) -> String {
+    format!(
+        "ISO-10303-21;\n{}DATA;{}ENDSEC;\nEND-ISO-10303-21;\n",
+        header, INSTANCES
+    )
+}
+
+#[test]
+fn corrupted_file_name() {
+    let header = r#"HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('part.stp' '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+"#;
+    let input = step(header);
+    assert!(parser::parse(&input).is_err());
+
+    let (exchange, warnings) = parser::parse_with_warnings(&input, &recover()).unwrap();
+    assert_eq!(exchange.data[0].entities.len(), 2);
+    assert_eq!(exchange.header.len(), 2);
+    assert_eq!(exchange.header[1].name, "FILE_SCHEMA");
+    assert_eq!(warnings.len(), 1);
+    let Warning::MalformedHeaderEntity { position } = warnings[0] else {
+        panic!("Unexpected warning: {:?}", warnings[0]);
+    };
+    assert!(input[position..].starts_with("FILE_NAME('part.stp'"));
+}
+
+#[test]
+fn missing_header() {
+    let input = step("");
+    assert!(parser::parse(&input).is_err());
+
+    let (exchange, warnings) = parser::parse_with_warnings(&input, &recover()).unwrap();
+    assert!(exchange.header.is_empty());
+    assert_eq!(exchange.data[0].entities.len(), 2);
+    assert_eq!(
+        warnings,
+        vec![Warning::MissingHeader {
+            position: "ISO-10303-21;\n".len()
+        }]
+    );
+}
+
+#[test]
+fn valid_header_is_same_as_strict() {
+    let header = r#"HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+"#;
+    let input = step(header);
+    let (exchange, warnings) = parser::parse_with_warnings(&input, &recover()).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(exchange, parser::parse(&input).unwrap());
+}
+
+const CORRUPTED_FILE_NAME: &str = r#"HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('part.stp' '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+"#;
+
+#[test]
+fn every_entry_point() {
+    let input = step(CORRUPTED_FILE_NAME);
+    let (expected, _warnings) = parser::parse_with_warnings(&input, &recover()).unwrap();
+
+    let options = recover();
+    let (rest, exchange) = exchange::exchange_file_with(&options)
+        .parse(&input)
+        .finish()
+        .unwrap();
+    assert_eq!(rest, "");
+    assert_eq!(exchange, expected);
+
+    let (_, (exchange, issues)) = exchange::exchange_file_lenient_with(&options)
+        .parse(&input)
+        .finish()
+        .unwrap();
+    assert!(issues.is_empty());
+    assert_eq!(exchange, expected);
+
+    let (_, exchange) = exchange::exchange_file_parallel_with(&options)
+        .parse(&input)
+        .finish()
+        .unwrap();
+    assert_eq!(exchange, expected);
+
+    assert_eq!(parser::parse_with(&input, &options).unwrap(), expected);
+}
+
+#[test]
+fn read_instances_skip_header() {
+    let input = step(CORRUPTED_FILE_NAME);
+    assert!(read_instances(input.as_bytes()).any(|item| item.is_err()));
+
+    let instances: Vec<_> = read_instances(input.as_bytes())
+        .options(&recover())
+        .map(|item| item.unwrap())
+        .collect();
+    assert_eq!(instances, parser::parse_record_list(INSTANCES).unwrap());
+}
+
+#[test]
+fn borrowed_rejects_recover() {
+    let input = step(CORRUPTED_FILE_NAME);
+    assert!(matches!(
+        parser::parse_borrowed_with(&input, &recover()),
+        Err(Error::UnsupportedOption {
+            option: "recover_header",
+            ..
+        })
+    ));
+}
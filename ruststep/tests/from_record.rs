@@ -0,0 +1,56 @@
+// Test for deserializing entities from a record without tables
+
+use ruststep::{de::*, error::Error};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY polyline;
+        label: STRING;
+        points: LIST [2:?] OF point;
+      END_ENTITY;
+
+      ENTITY curve;
+        coefficients: LIST [0:?] OF REAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn from_record_str_simple() {
+    let point: Point = from_record_str("POINT(1.0, 2.0)").unwrap();
+    assert_eq!(point, Point { x: 1.0, y: 2.0 });
+
+    let curve: Curve = from_record_str("CURVE((1.0, 2.0, 3.0))").unwrap();
+    assert_eq!(curve.coefficients, [1.0, 2.0, 3.0]);
+}
+
+#[test]
+fn from_record_str_unresolved() {
+    let err = from_record_str::<Polyline>("POLYLINE('p', (#2, #1, #2))").unwrap_err();
+    assert!(matches!(&err, Error::UnresolvedReferences(ids) if ids == &[1, 2]));
+    assert_eq!(
+        err.to_string(),
+        "References #1, #2 cannot be resolved without tables"
+    );
+}
+
+#[test]
+fn from_record_str_keyword_mismatch() {
+    assert!(from_record_str::<Point>("CURVE((1.0, 2.0))").is_err());
+}
+
+#[test]
+fn from_parameter_value() {
+    let parameter = "((1.0, 2.0), (3.0, 4.0))".parse().unwrap();
+    let values: Vec<Vec<f64>> = from_parameter(&parameter).unwrap();
+    assert_eq!(values, [[1.0, 2.0], [3.0, 4.0]]);
+}
@@ -0,0 +1,133 @@
+//! Full-width punctuation in structural positions, see `ParseOptions::normalize_fullwidth_punctuation`
+
+use nom::{Finish, Parser};
+use ruststep::{
+    error::Error,
+    parser::{self, exchange, parse_with_warnings, read_instances, ParseOptions, Warning},
+};
+use std::{fs, path::PathBuf};
+
+fn ascii_clean() -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    fs::read_to_string(step_file).unwrap()
+}
+
+fn options() -> ParseOptions {
+    ParseOptions {
+        normalize_fullwidth_punctuation: true,
+        ..ParseOptions::strict()
+    }
+}
+
+/// Replace ASCII punctuation of `DIRECTION` records by full-width one as some CAD exports do,
+/// and put full-width characters into a string. Returns the number of replaced characters.
+fn fullwidth(step: &str) -> (String, usize) {
+    let mut count = 0;
+    let lines: Vec<String> = step
+        .lines()
+        .map(|line| {
+            if line.starts_with('#') && line.contains("= DIRECTION( '',") {
+                count += 7;
+                line.replacen('#', "＃", 1)
+                    .replacen(' ', "\u{3000}", 1)
+                    .replacen('=', "＝", 1)
+                    .replacen('(', "（", 1)
+                    .replacen(" );", " ）；", 1)
+                    + "\u{3000}"
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    (lines.join("\n"), count)
+}
+
+#[test]
+fn same_ast_as_ascii_clean() {
+    let clean = ascii_clean();
+    let (step, count) = fullwidth(&clean);
+    assert!(count > 0);
+    assert!(parser::parse(&step).is_err());
+
+    let (exchange, warnings) = parse_with_warnings(&step, &options()).unwrap();
+    assert_eq!(exchange, parser::parse(&clean).unwrap());
+    assert_eq!(warnings.len(), count);
+    for warning in warnings {
+        let Warning::FullwidthPunctuation { position, found } = warning else {
+            panic!("Unexpected warning: {:?}", warning);
+        };
+        assert!(step[position..].starts_with(found));
+    }
+}
+
+#[test]
+fn strings_are_kept() {
+    let step = ascii_clean().replacen(
+        "CARTESIAN_POINT( ''",
+        "CARTESIAN_POINT( '（＃１；）\u{3000}'",
+        1,
+    );
+    let (exchange, warnings) = parse_with_warnings(&step, &options()).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(exchange, parser::parse(&step).unwrap());
+}
+
+#[test]
+fn every_entry_point() {
+    let clean = ascii_clean();
+    let (step, _count) = fullwidth(&clean);
+    let expected = parser::parse(&clean).unwrap();
+
+    let (rest, exchange) = exchange::exchange_file_with(&options())
+        .parse(&step)
+        .finish()
+        .unwrap();
+    assert_eq!(rest, "");
+    assert_eq!(exchange, expected);
+
+    let (_, (exchange, issues)) = exchange::exchange_file_lenient_with(&options())
+        .parse(&step)
+        .finish()
+        .unwrap();
+    assert!(issues.is_empty());
+    assert_eq!(exchange, expected);
+
+    let (_, exchange) = exchange::exchange_file_parallel_with(&options())
+        .parse(&step)
+        .finish()
+        .unwrap();
+    assert_eq!(exchange, expected);
+}
+
+#[test]
+fn offset_in_original_input() {
+    let step = ascii_clean().replacen("#1 =", "＃1\u{3000}＝", 1).replacen(
+        "ENDSEC;\nEND-ISO",
+        "#0 = A(99999999999999999999);\nENDSEC;\nEND-ISO",
+        1,
+    );
+    let err = parser::parse_with(&step, &options()).unwrap_err();
+    let Error::TokenizeFailed(err) = err else {
+        panic!("{:?}", err)
+    };
+    assert_eq!(err.offset(), step.find("99999999999999999999").unwrap());
+}
+
+#[test]
+fn unsupported_readers() {
+    let (step, _count) = fullwidth(&ascii_clean());
+    let mut reader = read_instances(step.as_bytes()).options(&options());
+    assert!(matches!(
+        reader.next(),
+        Some(Err(Error::UnsupportedOption {
+            option: "normalize_fullwidth_punctuation",
+            ..
+        }))
+    ));
+    assert!(reader.next().is_none());
+    assert!(matches!(
+        parser::parse_borrowed_with(&step, &options()),
+        Err(Error::UnsupportedOption { .. })
+    ));
+}
@@ -0,0 +1,113 @@
+// Test for accessing instances only by ids through `Tables::get_any`
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+
+      TYPE label = STRING;
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables() -> Tables {
+    Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #3 = SEGMENT(#1, #2);
+              #1 = POINT(0.0, 0.0);
+              #2 = POINT(1.0, 2.0);
+              #4 = LABEL('name');
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn get_any() {
+    let tables = tables();
+    assert_eq!(tables.keyword_of(1), Some("POINT"));
+    assert_eq!(tables.keyword_of(3), Some("SEGMENT"));
+    assert_eq!(tables.keyword_of(4), Some("LABEL"));
+    assert_eq!(tables.keyword_of(5), None);
+
+    assert_eq!(
+        tables.get_any(2).unwrap(),
+        AnyEntity::Point(Box::new(Point { x: 1.0, y: 2.0 }))
+    );
+    assert_eq!(
+        tables.get_any(3).unwrap(),
+        AnyEntity::Segment(Box::new(Segment {
+            head: Point { x: 0.0, y: 0.0 },
+            tail: Point { x: 1.0, y: 2.0 },
+        }))
+    );
+    assert_eq!(
+        tables.get_any(4).unwrap(),
+        AnyEntity::Label(Box::new(Label("name".to_string())))
+    );
+    assert!(matches!(tables.get_any(5), Err(Error::UnknownEntity(5))));
+}
+
+#[test]
+fn any_iter() {
+    let tables = tables();
+    let keywords: Vec<_> = tables
+        .any_iter()
+        .map(|any| match any.unwrap() {
+            AnyEntity::Point(_) => "POINT",
+            AnyEntity::Segment(_) => "SEGMENT",
+            AnyEntity::Label(_) => "LABEL",
+        })
+        .collect();
+    // In the order of ids
+    assert_eq!(keywords, ["POINT", "POINT", "SEGMENT", "LABEL"]);
+}
+
+#[test]
+fn inserted() {
+    let mut tables = Tables::default();
+    let id = tables.insert_point(PointHolder { x: 0.0, y: 1.0 }).unwrap();
+    tables
+        .insert_segment_with_id(
+            10,
+            SegmentHolder {
+                head: ruststep::ast::Name::Entity(id).into(),
+                tail: ruststep::ast::Name::Entity(id).into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(tables.keyword_of(id), Some("POINT"));
+    assert_eq!(tables.keyword_of(10), Some("SEGMENT"));
+
+    // Entities inserted for place holders
+    let segment = Segment {
+        head: Point { x: 2.0, y: 3.0 },
+        tail: Point { x: 4.0, y: 5.0 },
+    };
+    let holder = segment.clone().into_holder(&mut tables).unwrap();
+    let id = tables.insert_segment(holder).unwrap();
+    assert_eq!(
+        tables.get_any(id).unwrap(),
+        AnyEntity::Segment(Box::new(segment))
+    );
+    assert_eq!(tables.any_iter().count(), 5);
+}
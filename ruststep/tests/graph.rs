@@ -0,0 +1,64 @@
+// Test for the graph of references between entity instances
+
+use ruststep::{ast::*, error::CycleError};
+use std::{collections::HashSet, str::FromStr};
+
+// Diamond of #1 -> (#2, #3) -> #4, and a cycle of #5 -> #6 -> #7 -> #5 referring #4
+const DIAMOND: &str = r#"
+DATA;
+  #4 = POINT(0.0);
+  #1 = TOP(#2, #3);
+  #2 = LEFT(#4);
+  #3 = RIGHT(#4);
+  #5 = CYCLE(#6, #4);
+  #6 = (CYCLE_A(#7) CYCLE_B(1));
+  #7 = CYCLE(#5);
+  #8 = ISOLATED($);
+ENDSEC;
+"#;
+
+fn ids(ids: &[u64]) -> HashSet<u64> {
+    ids.iter().copied().collect()
+}
+
+fn position(order: &[u64], id: u64) -> usize {
+    order.iter().position(|i| *i == id).unwrap()
+}
+
+#[test]
+fn topological_order_of_diamond() {
+    let mut section = DataSection::from_str(DIAMOND).unwrap();
+    section.gc(&[1, 8]);
+
+    let order = section.topological_order().unwrap();
+    assert_eq!(order, [4, 2, 3, 1, 8]);
+    for instance in &section.entities {
+        for r in instance.references() {
+            assert!(position(&order, r) < position(&order, instance.id()));
+        }
+    }
+}
+
+#[test]
+fn topological_order_of_cycle() {
+    let section = DataSection::from_str(DIAMOND).unwrap();
+    let CycleError { cycle } = section.topological_order().unwrap_err();
+    assert_eq!(cycle, [5, 6, 7]);
+
+    // Referring itself directly
+    let section = DataSection::from_str("DATA; #1 = A(#2); #2 = B(#2); ENDSEC;").unwrap();
+    let CycleError { cycle } = section.topological_order().unwrap_err();
+    assert_eq!(cycle, [2]);
+}
+
+#[test]
+fn reachable_from() {
+    let section = DataSection::from_str(DIAMOND).unwrap();
+    assert_eq!(section.reachable_from(&[1]), ids(&[1, 2, 3, 4]));
+    assert_eq!(section.reachable_from(&[2, 3]), ids(&[2, 3, 4]));
+    // through the complex instance #6
+    assert_eq!(section.reachable_from(&[7]), ids(&[4, 5, 6, 7]));
+    assert_eq!(section.reachable_from(&[8]), ids(&[8]));
+    // Unknown roots are ignored
+    assert_eq!(section.reachable_from(&[9]), ids(&[]));
+}
@@ -0,0 +1,99 @@
+use ruststep::{ast::DataSection, hash::*};
+use std::str::FromStr;
+
+// #1 <- #2 <- #3 <- #4, #10 <-> #11 -> #3, and #20 is independent
+const FIXTURE: &str = r#"
+DATA;
+#1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+#2 = VERTEX_POINT('', #1);
+#3 = EDGE_CURVE('', #2, #2, $, .T.);
+#4 = ORIENTED_EDGE('', *, *, #3, .T.);
+#10 = A(#11, #3);
+#11 = B(#10);
+#20 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+ENDSEC;
+"#;
+
+fn hashes(data: &str, mode: HashMode) -> std::collections::HashMap<u64, u128> {
+    entity_hashes(&DataSection::from_str(data).unwrap(), &HashOptions { mode })
+}
+
+#[test]
+fn modify_leaf() {
+    let modified = FIXTURE.replace("(0.0, 0.0, 0.0)", "(0.0, 0.0, 1.0)");
+
+    let changes = changed_since(
+        &hashes(FIXTURE, HashMode::Shallow),
+        &hashes(&modified, HashMode::Shallow),
+    );
+    assert_eq!(changes.modified, [1]);
+    assert!(changes.added.is_empty());
+    assert!(changes.removed.is_empty());
+
+    let changes = changed_since(
+        &hashes(FIXTURE, HashMode::Deep),
+        &hashes(&modified, HashMode::Deep),
+    );
+    assert_eq!(changes.modified, [1, 2, 3, 4, 10, 11]);
+}
+
+#[test]
+fn modify_cycle() {
+    let modified = FIXTURE.replace("B(#10)", "C(#10)");
+    let changes = changed_since(
+        &hashes(FIXTURE, HashMode::Deep),
+        &hashes(&modified, HashMode::Deep),
+    );
+    assert_eq!(changes.modified, [10, 11]);
+}
+
+#[test]
+fn add_and_remove() {
+    let modified = FIXTURE.replace("#20 =", "#21 =");
+    let changes = changed_since(
+        &hashes(FIXTURE, HashMode::Deep),
+        &hashes(&modified, HashMode::Deep),
+    );
+    assert_eq!(
+        changes,
+        ChangeSet {
+            added: vec![21],
+            removed: vec![20],
+            modified: vec![],
+        }
+    );
+}
+
+#[test]
+fn deterministic() {
+    // Same hashes regardless of the order of instances
+    let reordered = r#"
+    DATA;
+    #20 = CARTESIAN_POINT('', (1.0, 0.0, 0.0));
+    #11 = B(#10);
+    #10 = A(#11, #3);
+    #4 = ORIENTED_EDGE('', *, *, #3, .T.);
+    #3 = EDGE_CURVE('', #2, #2, $, .T.);
+    #2 = VERTEX_POINT('', #1);
+    #1 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+    ENDSEC;
+    "#;
+    for mode in [HashMode::Shallow, HashMode::Deep] {
+        assert!(changed_since(&hashes(FIXTURE, mode), &hashes(reordered, mode)).is_empty());
+    }
+}
+
+#[test]
+fn stable() {
+    // Changing these values requires incrementing ALGORITHM_VERSION
+    assert_eq!(ALGORITHM_VERSION, 1);
+    let data = "DATA; #1 = A(1, 2.0, 'x', .T., $, *, (#2)); ENDSEC;";
+    assert_eq!(
+        hashes(data, HashMode::Shallow)[&1],
+        48386468505904272300399127925662734227
+    );
+    assert_eq!(
+        hashes(data, HashMode::Deep)[&1],
+        24017124482103893880551513334071404356
+    );
+}
@@ -0,0 +1,210 @@
+// Test for FILE_DESCRIPTION keeping paragraphs as list elements
+
+use nom::Finish;
+use ruststep::{ast::*, header::*, parser};
+use std::str::FromStr;
+
+/// Synthesized from an exchange structure written by a quality system,
+/// whose description is wrapped in the middle of strings.
+const FIXTURE: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('Inspection report for part ''A-100''.',
+'Measured by \X2\6E2C5B9A\X0\ station 3;
+ results attached.','',
+'Approved (rev. 2)'),'2;1');
+FILE_NAME('part.stp','2024-01-01T00:00:00',(''),(''),'','','');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn fixture() {
+    let exchange = parser::parse(FIXTURE).unwrap();
+    let header = Header::from_records(&exchange.header).unwrap();
+    assert_eq!(
+        header.file_description.description,
+        vec![
+            "Inspection report for part 'A-100'.",
+            "Measured by 測定 station 3; results attached.",
+            "",
+            "Approved (rev. 2)",
+        ]
+    );
+    assert_eq!(header.file_description.implementation_level, "2;1");
+}
+
+/// Deterministic pseudo random numbers
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+const FRAGMENTS: &[&str] = &[
+    "",
+    " ",
+    "plain text",
+    "'",
+    "it's",
+    "''",
+    "ステップ",
+    "😀",
+    r"\X\E9",
+    r"\S\a",
+    r"\PA\",
+    r"\\",
+    "(nested, 'list');",
+    "/* not a comment */",
+    "2;1",
+];
+
+fn paragraph(rng: &mut Lcg) -> String {
+    (0..rng.next(6))
+        .map(|_| FRAGMENTS[rng.next(FRAGMENTS.len())])
+        .collect()
+}
+
+/// Length of the longest `\X2\` or `\X4\` control directive
+fn longest_directive(written: &str) -> usize {
+    written
+        .match_indices(r"\X0\")
+        .map(|(end, _)| {
+            let start = written[..end].rfind(r"\X").unwrap();
+            end + 4 - start
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = Lcg(0x5eed);
+    for _ in 0..500 {
+        let mut desc = FileDescription {
+            description: Vec::new(),
+            implementation_level: paragraph(&mut rng),
+        };
+        let paragraphs: Vec<String> = (0..rng.next(5) + 1).map(|_| paragraph(&mut rng)).collect();
+        desc.set_description_paragraphs(paragraphs.clone());
+
+        for width in [None, Some(1), Some(8), Some(20), Some(72)] {
+            let written = desc.write(width);
+            if let Some(width) = width {
+                // Control directives encoding non-ASCII characters and `FILE_DESCRIPTION` are not broken
+                let width = width.max(longest_directive(&written)).max(18);
+                assert!(
+                    written.lines().all(|line| line.chars().count() <= width),
+                    "{}",
+                    written
+                );
+            }
+            let (residual, record) = parser::exchange::header_entity(&written).finish().unwrap();
+            assert_eq!(residual, "");
+            let parsed: FileDescription = serde::Deserialize::deserialize(&record).unwrap();
+            assert_eq!(parsed, desc, "{}", written);
+            assert_eq!(parsed.description, paragraphs);
+        }
+    }
+}
+
+#[test]
+fn escape_sequences_are_not_split() {
+    let desc = FileDescription {
+        description: vec![r"it'sステcd\é".to_string()],
+        implementation_level: "2;1".to_string(),
+    };
+    for width in 1..40 {
+        let written = desc.write(Some(width));
+        for piece in ["''", r"\X2\30B930C6\X0\", r"\\", r"\X2\00E9\X0\"] {
+            assert!(
+                written.lines().any(|line| line.contains(piece)),
+                "{}",
+                written
+            );
+        }
+        let (_, record) = parser::exchange::header_entity(&written).finish().unwrap();
+        assert_eq!(record, desc.to_record());
+    }
+}
+
+#[test]
+fn description_text() {
+    let record = Record::from_str("FILE_DESCRIPTION(('a', 'b', ''), '2;1')").unwrap();
+    let desc: FileDescription = serde::Deserialize::deserialize(&record).unwrap();
+    assert_eq!(desc.description_text(" / "), "a / b / ");
+}
+
+fn header_records(records: &[&str]) -> Vec<Record> {
+    records
+        .iter()
+        .map(|record| Record::from_str(record).unwrap())
+        .collect()
+}
+
+const FILE_DESCRIPTION: &str = "FILE_DESCRIPTION(('bolt'), '2;1')";
+const FILE_NAME: &str =
+    "FILE_NAME('bolt.stp', '2024-01-01T00:00:00', ('a'), ('o'), 'pre', 'CAD 1.0', 'auth')";
+const FILE_SCHEMA: &str = "FILE_SCHEMA(('AUTOMOTIVE_DESIGN'))";
+
+#[test]
+fn typed_header() {
+    let exchange = parser::parse(FIXTURE).unwrap();
+    let header = exchange.header_typed().unwrap();
+    assert_eq!(header, Header::from_records(&exchange.header).unwrap());
+    assert_eq!(header.file_name.name, "part.stp");
+    assert_eq!(header.file_schema.schema, ["AUTOMOTIVE_DESIGN"]);
+    assert!(header.extra.is_empty());
+
+    let records = header_records(&[
+        FILE_DESCRIPTION,
+        FILE_NAME,
+        FILE_SCHEMA,
+        "FILE_POPULATION('AUTOMOTIVE_DESIGN', 'include_all_compatible', $)",
+        "SECTION_LANGUAGE('en')",
+    ]);
+    let header = Header::from_records(&records).unwrap();
+    assert_eq!(header.file_name.author, ["a"]);
+    assert_eq!(header.file_name.organization, ["o"]);
+    assert_eq!(header.file_name.preprocessor_version, "pre");
+    assert_eq!(header.file_name.originating_system, "CAD 1.0");
+    assert_eq!(header.file_name.authorization, "auth");
+    assert_eq!(header.extra, records[3..]);
+}
+
+#[test]
+fn invalid_header() {
+    let error = |records: &[&str]| match Header::from_records(&header_records(records)) {
+        Err(ruststep::error::Error::InvalidHeader { record, message }) => (record, message),
+        other => panic!("{:?}", other),
+    };
+
+    let (record, message) = error(&[FILE_DESCRIPTION, FILE_NAME]);
+    assert_eq!(record, "FILE_SCHEMA");
+    assert_eq!(message, "missing");
+
+    let (record, message) = error(&[FILE_DESCRIPTION, FILE_SCHEMA, FILE_NAME]);
+    assert_eq!(record, "FILE_NAME");
+    assert_eq!(message, "found FILE_SCHEMA instead");
+
+    let (record, _message) = error(&[
+        FILE_DESCRIPTION,
+        "FILE_NAME('bolt.stp', '2024-01-01T00:00:00')",
+        FILE_SCHEMA,
+    ]);
+    assert_eq!(record, "FILE_NAME");
+
+    let (record, _message) = error(&["FILE_DESCRIPTION('bolt', '2;1')", FILE_NAME, FILE_SCHEMA]);
+    assert_eq!(record, "FILE_DESCRIPTION");
+
+    let (record, _message) = error(&[]);
+    assert_eq!(record, "FILE_DESCRIPTION");
+}
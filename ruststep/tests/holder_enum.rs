@@ -0,0 +1,148 @@
+//! Hand-written enums with unit variants and struct variants
+
+use ruststep::{ast::*, tables::*};
+use ruststep_derive::{as_holder, Holder, TableInit};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Default, TableInit)]
+pub struct Tables {
+    point: HashMap<u64, as_holder!(Point)>,
+    unit: HashMap<u64, as_holder!(Unit)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(generate_deserialize)]
+#[holder(generate_to_record)]
+pub enum Prefix {
+    Kilo,
+    Milli,
+    #[holder(rename = "MICRO")]
+    Mu,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = point)]
+#[holder(generate_deserialize)]
+#[holder(generate_into_holder)]
+#[holder(generate_to_record)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(generate_deserialize)]
+#[holder(generate_into_holder)]
+#[holder(generate_to_record)]
+pub enum Shape {
+    #[holder(use_place_holder)]
+    Point(Box<Point>),
+    Circle {
+        #[holder(use_place_holder)]
+        center: Point,
+        radius: f64,
+    },
+    #[holder(rename = "RECTANGLE_2D")]
+    Rectangle { width: f64, height: Option<f64> },
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = unit)]
+#[holder(generate_deserialize)]
+#[holder(generate_into_holder)]
+#[holder(generate_to_record)]
+pub struct Unit {
+    pub prefix: Option<Prefix>,
+    #[holder(use_place_holder)]
+    pub shape: Shape,
+}
+
+#[test]
+fn enumeration() {
+    let parameter = Parameter::from_str(".MILLI.").unwrap();
+    assert_eq!(Prefix::deserialize(&parameter).unwrap(), Prefix::Milli);
+    let parameter = Parameter::from_str(".MICRO.").unwrap();
+    assert_eq!(Prefix::deserialize(&parameter).unwrap(), Prefix::Mu);
+    assert_eq!(Prefix::Mu.to_parameter().to_string(), ".MICRO.");
+
+    // Variant name is not used for a renamed variant
+    let parameter = Parameter::from_str(".MU.").unwrap();
+    assert!(Prefix::deserialize(&parameter).is_err());
+    let parameter = Parameter::from_str("'MILLI'").unwrap();
+    assert!(Prefix::deserialize(&parameter).is_err());
+}
+
+#[test]
+fn struct_variant() {
+    let tables = Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, 2.0);
+          #2 = UNIT(.KILO., CIRCLE((#1, 3.0)));
+          #3 = UNIT($, RECTANGLE_2D((1.0, $)));
+          #4 = UNIT($, #1);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let point = Point { x: 1.0, y: 2.0 };
+    assert_eq!(
+        EntityTable::<UnitHolder>::get_owned(&tables, 2).unwrap(),
+        Unit {
+            prefix: Some(Prefix::Kilo),
+            shape: Shape::Circle {
+                center: point.clone(),
+                radius: 3.0,
+            },
+        }
+    );
+    assert_eq!(
+        EntityTable::<UnitHolder>::get_owned(&tables, 3).unwrap(),
+        Unit {
+            prefix: None,
+            shape: Shape::Rectangle {
+                width: 1.0,
+                height: None,
+            },
+        }
+    );
+    assert_eq!(
+        EntityTable::<UnitHolder>::get_owned(&tables, 4).unwrap(),
+        Unit {
+            prefix: None,
+            shape: Shape::Point(Box::new(point)),
+        }
+    );
+
+    // References in struct variants
+    let mut references = Vec::new();
+    tables.for_each_reference(&mut |id, attr, to| references.push((id, attr, to)));
+    references.sort();
+    assert_eq!(references, [(2, "shape", 1), (4, "shape", 1)]);
+}
+
+#[test]
+fn into_holder() {
+    let mut tables = Tables::default();
+    let unit = Unit {
+        prefix: Some(Prefix::Mu),
+        shape: Shape::Circle {
+            center: Point { x: 0.0, y: 1.0 },
+            radius: 2.0,
+        },
+    };
+    let holder = unit.clone().into_holder(&mut tables).unwrap();
+    assert_eq!(
+        holder.to_record().to_string(),
+        "UNIT(.MICRO.,CIRCLE((#1,2.0)))"
+    );
+    tables.unit.insert(2, holder);
+    assert_eq!(
+        EntityTable::<UnitHolder>::get_owned(&tables, 2).unwrap(),
+        unit
+    );
+}
@@ -0,0 +1,156 @@
+//! Hand-written holders binding fields to parameter positions by `#[holder(index, skip, skip_params, rest)]`,
+//! and renaming fields by `#[holder(rename)]`
+
+use ruststep::{ast::*, tables::*};
+use ruststep_derive::{as_holder, Holder, TableInit};
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Default, TableInit)]
+pub struct Tables {
+    shape: HashMap<u64, as_holder!(Shape)>,
+    circle: HashMap<u64, as_holder!(Circle)>,
+    label: HashMap<u64, as_holder!(Label)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = shape)]
+#[holder(generate_deserialize)]
+pub struct Shape {
+    #[holder(index = 3)]
+    pub radius: f64,
+    #[holder(index = 0)]
+    pub name: String,
+    // position 1
+    #[holder(skip)]
+    pub description: String,
+    // position 2 is not bound to any field, and skipped
+    #[holder(index = 4)]
+    pub closed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = circle)]
+#[holder(generate_deserialize)]
+pub struct Circle {
+    #[holder(index = 1)]
+    #[holder(use_place_holder)]
+    pub shape: Shape,
+    #[holder(rest)]
+    pub extra: Vec<Parameter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder)]
+#[holder(table = Tables)]
+#[holder(field = label)]
+#[holder(generate_deserialize)]
+pub struct Label {
+    pub r#type: String,
+    #[holder(rename = "ref")]
+    #[holder(use_place_holder)]
+    pub reference: Option<Shape>,
+    // 3rd and 4th parameters are discarded
+    #[holder(skip_params = 2)]
+    pub size: f64,
+    // Parameters after 5th are discarded
+    #[holder(skip)]
+    #[holder(rest)]
+    pub ignored: (),
+}
+
+#[test]
+fn positions() {
+    let record = Record::from_str("SHAPE('c', 'ignored', .UNKNOWN., 2.0, .T.)").unwrap();
+    let holder = ShapeHolder::parse_record(&record).unwrap();
+    assert_eq!(
+        holder,
+        ShapeHolder {
+            radius: 2.0,
+            name: "c".to_string(),
+            closed: true,
+        }
+    );
+    assert_eq!(ShapeHolder::attr_len(), 5);
+
+    // Number of parameters must match
+    let record = Record::from_str("SHAPE('c', 'ignored', $, 2.0)").unwrap();
+    assert!(ShapeHolder::parse_record(&record).is_err());
+    let record = Record::from_str("SHAPE('c', 'ignored', $, 2.0, .T., 1)").unwrap();
+    assert!(ShapeHolder::parse_record(&record).is_err());
+}
+
+#[test]
+fn rest() {
+    let record = Record::from_str("CIRCLE($, #1)").unwrap();
+    let holder = CircleHolder::parse_record(&record).unwrap();
+    assert_eq!(holder.extra, Vec::new());
+
+    let record = Record::from_str("CIRCLE($, #1, 'a', (1, .B.), C(#2))").unwrap();
+    let holder = CircleHolder::parse_record(&record).unwrap();
+    assert_eq!(
+        holder.extra,
+        vec![
+            Parameter::String("a".to_string()),
+            Parameter::from_str("(1, .B.)").unwrap(),
+            Parameter::from_str("C(#2)").unwrap(),
+        ]
+    );
+
+    let record = Record::from_str("CIRCLE($)").unwrap();
+    assert!(CircleHolder::parse_record(&record).is_err());
+}
+
+#[test]
+fn owned() {
+    let tables = Tables::from_str(
+        r#"
+        DATA;
+          #1 = SHAPE('c', 'ignored', #3, 2.0, .F.);
+          #2 = CIRCLE(*, #1, 3);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let circle = EntityTable::<CircleHolder>::get_owned(&tables, 2).unwrap();
+    assert_eq!(
+        circle,
+        Circle {
+            shape: Shape {
+                radius: 2.0,
+                name: "c".to_string(),
+                description: String::new(),
+                closed: false,
+            },
+            extra: vec![Parameter::Integer(3)],
+        }
+    );
+}
+
+#[test]
+fn rename_and_skip_params() {
+    assert_eq!(LabelHolder::attr_names(), ["type", "ref", "size"]);
+    assert_eq!(LabelHolder::attr_len(), 5);
+    assert!(LabelHolder::has_rest());
+
+    let record = Record::from_str("LABEL('text', $, 1, 'ignored', 2.0)").unwrap();
+    let holder = LabelHolder::parse_record(&record).unwrap();
+    assert_eq!(
+        holder,
+        LabelHolder {
+            r#type: "text".to_string(),
+            reference: None,
+            size: 2.0,
+        }
+    );
+    let record = Record::from_str("LABEL('text', #1, $, $, 2.0, .T., (1, 2))").unwrap();
+    let holder = LabelHolder::parse_record(&record).unwrap();
+    assert_eq!(holder.reference, Some(PlaceHolder::Ref(Name::Entity(1))));
+    let record = Record::from_str("LABEL('text', #1, $, $)").unwrap();
+    assert!(LabelHolder::parse_record(&record).is_err());
+
+    // Renamed attribute is used in errors
+    let record = Record::from_str("LABEL('text', 1.0, $, $, 2.0)").unwrap();
+    let err = LabelHolder::parse_record(&record).unwrap_err();
+    assert_eq!(err.context().unwrap().attribute.as_deref(), Some("ref"));
+}
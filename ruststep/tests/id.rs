@@ -0,0 +1,142 @@
+// Test for id allocation with reserved ranges
+
+use ruststep::{ast::*, error::Error, id::IdAllocator};
+use std::str::FromStr;
+
+#[test]
+fn allocation_skips_reserved_ranges() {
+    let mut ids = IdAllocator::new();
+    ids.reserve_range(3..5);
+    ids.reserve_range(5..7); // adjacent, merged into 3..7
+    ids.reserve_range(10..12);
+    assert_eq!(ids.reserved(), &[3..7, 10..12]);
+
+    let allocated: Vec<u64> = (0..6).map(|_| ids.allocate().unwrap()).collect();
+    assert_eq!(allocated, vec![1, 2, 7, 8, 9, 12]);
+    assert!(allocated.iter().all(|id| !ids.is_reserved(*id)));
+}
+
+#[test]
+fn exhaustion() {
+    let mut ids = IdAllocator::new().ceiling(6);
+    ids.reserve_range(2..4);
+    assert_eq!(ids.allocate().unwrap(), 1);
+    assert_eq!(ids.allocate().unwrap(), 4);
+    assert_eq!(ids.allocate().unwrap(), 5);
+    assert!(matches!(
+        ids.allocate(),
+        Err(Error::IdExhausted { ceiling: 6 })
+    ));
+    // Stays exhausted
+    assert!(matches!(ids.allocate(), Err(Error::IdExhausted { .. })));
+
+    // Reserved range reaching the ceiling
+    let mut ids = IdAllocator::new().ceiling(u64::MAX);
+    ids.reserve_range(1..u64::MAX);
+    assert!(matches!(ids.allocate(), Err(Error::IdExhausted { .. })));
+}
+
+#[test]
+fn compaction_keeps_reserved_ids_and_references() {
+    let mut section = DataSection::from_str(
+        r#"
+        DATA;
+          #100 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+          #3 = VENDOR_POINT('kept', #100);
+          #205 = VERTEX_POINT('v', #100);
+          #4 = VENDOR_EDGE(#3, #205);
+          #300 = EDGE(#205, #4, (#100, #3));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+
+    let mut ids = IdAllocator::for_section(&section);
+    ids.reserve_range(2..5);
+    let renumbered = ids.compact_ids(&mut section).unwrap();
+    assert_eq!(
+        renumbered,
+        [(100, 1), (205, 5), (300, 6)].into_iter().collect()
+    );
+    assert_eq!(
+        section,
+        DataSection::from_str(
+            r#"
+            DATA;
+              #1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+              #3 = VENDOR_POINT('kept', #1);
+              #5 = VERTEX_POINT('v', #1);
+              #4 = VENDOR_EDGE(#3, #5);
+              #6 = EDGE(#5, #4, (#1, #3));
+            ENDSEC;
+            "#
+        )
+        .unwrap()
+    );
+
+    // Every reference points to an instance in the section
+    for instance in &section.entities {
+        for id in instance.references() {
+            assert!(section.instance(id).is_some());
+        }
+    }
+    assert_eq!(ids.allocate().unwrap(), 7);
+}
+
+#[test]
+fn compaction_fails_without_changes() {
+    let original =
+        DataSection::from_str("DATA; #1 = A(#2); #2 = B(1); #3 = C(#2); ENDSEC;").unwrap();
+    let mut section = original.clone();
+    let mut ids = IdAllocator::new().ceiling(3);
+    assert!(matches!(
+        ids.compact_ids(&mut section),
+        Err(Error::IdExhausted { ceiling: 3 })
+    ));
+    assert_eq!(section, original);
+}
+
+#[test]
+fn renumber_rewrites_complex_instances() {
+    let mut section = DataSection::from_str(
+        r#"
+        DATA;
+          #90374 = (NAMED('a') POINT(#4));
+          #4 = ORIGIN(0.0);
+          #90211 = EDGE(#90374, (#4, #90374));
+          #7 = CYCLE(#8);
+          #8 = CYCLE(#7);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    // Cyclic, so ids are assigned in the order of the section
+    let renumbered = section.renumber(10).unwrap();
+    assert_eq!(
+        renumbered,
+        [(90374, 10), (4, 11), (90211, 12), (7, 13), (8, 14)]
+            .into_iter()
+            .collect()
+    );
+    assert_eq!(
+        section.to_string(),
+        "DATA;\n#10 = (NAMED('a')POINT(#11));\n#11 = ORIGIN(0.0);\n#12 = EDGE(#10,(#11,#10));\n#13 = CYCLE(#14);\n#14 = CYCLE(#13);\nENDSEC;"
+    );
+}
+
+#[test]
+fn renumber_duplicated_ids() {
+    let input = "DATA; #1 = A(#2); #2 = B(1.0); #2 = C(2.0); ENDSEC;";
+    let mut section = DataSection::from_str(input).unwrap();
+    assert!(matches!(
+        section.renumber(1),
+        Err(Error::DuplicatedEntity(2))
+    ));
+    assert_eq!(section, DataSection::from_str(input).unwrap());
+
+    let mut section = DataSection::from_str("DATA; #1 = A(1.0); #2 = B(#1); ENDSEC;").unwrap();
+    assert!(matches!(
+        section.renumber(u64::MAX - 1),
+        Err(Error::IdExhausted { .. })
+    ));
+}
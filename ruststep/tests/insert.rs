@@ -0,0 +1,168 @@
+// Test for building tables in Rust through `insert_*` and `IntoHolder`
+
+use ruststep::{ast::*, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: point;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: LIST [2:?] OF point;
+        label: OPTIONAL shape;
+      END_ENTITY;
+
+      TYPE shape = SELECT (segment, polyline, label);
+      END_TYPE;
+
+      TYPE label = STRING;
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn point(x: f64, y: f64) -> Point {
+    Point { x, y }
+}
+
+#[test]
+fn insert_entities() {
+    let mut tables = Tables::default();
+    let segment = Segment {
+        head: point(0.0, 0.0),
+        tail: point(1.0, 2.0),
+    };
+    let holder = segment.clone().into_holder(&mut tables).unwrap();
+    // Referred points are inserted before the segment
+    assert_eq!(tables.point_holders().len(), 2);
+    assert_eq!(holder.head, PlaceHolder::Ref(Name::Entity(1)));
+    assert_eq!(holder.tail, PlaceHolder::Ref(Name::Entity(2)));
+
+    let id = tables.insert_segment(holder).unwrap();
+    assert_eq!(id, 3);
+    assert_eq!(
+        EntityTable::<SegmentHolder>::get_owned(&tables, id).unwrap(),
+        segment
+    );
+}
+
+#[test]
+fn insert_nested() {
+    let mut tables = Tables::default();
+    let polyline = Polyline {
+        points: vec![point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0)],
+        label: Some(Shape::Segment(Box::new(Segment {
+            head: point(0.0, 0.0),
+            tail: point(1.0, 1.0),
+        }))),
+    };
+    let holder = polyline.clone().into_holder(&mut tables).unwrap();
+    let id = tables.insert_polyline(holder).unwrap();
+    // 5 points and 1 segment
+    assert_eq!(id, 7);
+    assert_eq!(tables.point_holders().len(), 5);
+    assert_eq!(tables.segment_holders().len(), 1);
+    assert_eq!(
+        EntityTable::<PolylineHolder>::get_owned(&tables, id).unwrap(),
+        polyline
+    );
+
+    // Defined types in a select are kept in the holder instead of being inserted
+    let polyline = Polyline {
+        points: vec![point(0.0, 0.0), point(1.0, 0.0)],
+        label: Some(Shape::Label(Box::new(Label("name".to_string())))),
+    };
+    let holder = polyline.clone().into_holder(&mut tables).unwrap();
+    assert_eq!(
+        holder.label,
+        Some(PlaceHolder::Owned(ShapeHolder::Label(Box::new(
+            LabelHolder("name".to_string())
+        ))))
+    );
+    let id = tables.insert_polyline(holder).unwrap();
+    assert_eq!(
+        EntityTable::<PolylineHolder>::get_owned(&tables, id).unwrap(),
+        polyline
+    );
+}
+
+#[test]
+fn insert_with_id() {
+    let mut tables = Tables::from_data_section(
+        &DataSection::from_str("DATA; #10 = POINT(1.0, 2.0); ENDSEC;").unwrap(),
+    )
+    .unwrap();
+
+    let holder = point(3.0, 4.0).into_holder(&mut tables).unwrap();
+    tables.insert_point_with_id(5, holder.clone()).unwrap();
+
+    // Ids are unique across all entities
+    let segment = SegmentHolder {
+        head: Name::Entity(5).into(),
+        tail: Name::Entity(10).into(),
+    };
+    assert!(matches!(
+        tables.insert_segment_with_id(10, segment.clone()),
+        Err(Error::DuplicatedEntity(10))
+    ));
+    assert_eq!(tables.insert_segment(segment).unwrap(), 11);
+    assert_eq!(tables.insert_point(holder).unwrap(), 12);
+}
+
+#[test]
+fn insert_after_reserve_range() {
+    let mut tables = Tables::from_data_section(
+        &DataSection::from_str("DATA; #1 = POINT(0.0, 0.0); ENDSEC;").unwrap(),
+    )
+    .unwrap();
+    tables.id_allocator_mut().reserve_range(2..5);
+
+    // Points referred by the segment are also inserted skipping the reserved ids
+    let segment = Segment {
+        head: point(1.0, 0.0),
+        tail: point(1.0, 1.0),
+    };
+    let holder = segment.into_holder(&mut tables).unwrap();
+    assert_eq!(holder.head, PlaceHolder::Ref(Name::Entity(5)));
+    assert_eq!(holder.tail, PlaceHolder::Ref(Name::Entity(6)));
+    assert_eq!(tables.insert_segment(holder).unwrap(), 7);
+
+    // Reserved ids are still available by `insert_*_with_id`
+    let holder = point(2.0, 2.0).into_holder(&mut tables).unwrap();
+    tables.insert_point_with_id(3, holder.clone()).unwrap();
+    assert_eq!(tables.insert_point(holder.clone()).unwrap(), 8);
+
+    tables.id_allocator_mut().reserve_range(9..u64::MAX);
+    assert!(matches!(
+        tables.insert_point(holder),
+        Err(Error::IdExhausted { ceiling: u64::MAX })
+    ));
+}
+
+#[test]
+fn insert_exhausted() {
+    let mut tables = Tables::default();
+    let holder = point(0.0, 0.0).into_holder(&mut tables).unwrap();
+    tables
+        .insert_point_with_id(u64::MAX, holder.clone())
+        .unwrap();
+    assert!(matches!(
+        tables.insert_point(holder),
+        Err(Error::IdExhausted { ceiling: u64::MAX })
+    ));
+    assert!(matches!(
+        point(1.0, 1.0).into_place_holder(&mut tables),
+        Err(Error::IdExhausted { .. })
+    ));
+}
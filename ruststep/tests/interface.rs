@@ -0,0 +1,73 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA geometry;
+      TYPE label = STRING;
+      END_TYPE;
+
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY named_point;
+        name: label;
+        at: point;
+      END_ENTITY;
+    END_SCHEMA;
+
+    SCHEMA drawing;
+      USE FROM geometry (point AS pt);
+      REFERENCE FROM geometry (named_point);
+
+      ENTITY marker;
+        at: pt;
+        tag: OPTIONAL named_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+const EXAMPLE: &str = r#"
+DATA;
+  #1 = POINT(1.0, 2.0);
+  #2 = NAMED_POINT(LABEL(('origin')), POINT((0.0, 0.0)));
+  #3 = MARKER(#1, $);
+  #4 = MARKER(#1, #2);
+ENDSEC;
+"#;
+
+#[test]
+fn interfaced_entity() {
+    let table = drawing::Tables::from_str(EXAMPLE).unwrap();
+    let marker = EntityTable::<drawing::MarkerHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(marker.at, drawing::Pt { x: 1.0, y: 2.0 });
+    assert_eq!(marker.tag, None);
+
+    let marker = EntityTable::<drawing::MarkerHolder>::get_owned(&table, 4).unwrap();
+    assert_eq!(
+        marker.tag,
+        Some(drawing::NamedPoint {
+            name: drawing::Label("origin".to_string()),
+            at: drawing::Point { x: 0.0, y: 0.0 },
+        })
+    );
+}
+
+#[test]
+fn source_schema() {
+    // The schema interfaced from is not affected
+    let table = geometry::Tables::from_str(
+        r#"
+        DATA;
+          #1 = POINT(1.0, 2.0);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let point = EntityTable::<geometry::PointHolder>::get_owned(&table, 1).unwrap();
+    assert_eq!(point, geometry::Point { x: 1.0, y: 2.0 });
+    assert!(geometry::Tables::from_str(EXAMPLE).is_err());
+}
@@ -0,0 +1,69 @@
+// Test for `Tables::xxx_yyy_of` generated for INVERSE attributes
+
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY face;
+        name : STRING;
+      INVERSE
+        bounds : SET [1:?] OF face_bound FOR face;
+        owner : shell FOR faces;
+      END_ENTITY;
+
+      ENTITY face_bound;
+        face : face;
+        orientation : BOOLEAN;
+      END_ENTITY;
+
+      ENTITY shell;
+        faces : SET [1:?] OF face;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables() -> Tables {
+    Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = FACE('top');
+              #2 = FACE('bottom');
+              #13 = FACE_BOUND(#1, .F.);
+              #11 = FACE_BOUND(#1, .T.);
+              #12 = FACE_BOUND(#2, .T.);
+              #20 = SHELL((#1, #2));
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn inverse_of_single_reference() {
+    let tables = tables();
+    let bounds = tables.face_bounds_of(1).unwrap();
+    assert_eq!(bounds.len(), 2);
+    // in ascending order of ids, i.e. #11 and #13
+    assert!(bounds[0].orientation);
+    assert!(!bounds[1].orientation);
+    assert!(bounds.iter().all(|b| b.face.name == "top"));
+
+    assert_eq!(tables.face_bounds_of(2).unwrap().len(), 1);
+    assert!(tables.face_bounds_of(20).unwrap().is_empty());
+}
+
+#[test]
+fn inverse_of_aggregate() {
+    let tables = tables();
+    let owner = tables.face_owner_of(2).unwrap();
+    assert_eq!(owner.len(), 1);
+    assert_eq!(owner[0].faces.len(), 2);
+}
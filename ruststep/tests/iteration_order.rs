@@ -0,0 +1,66 @@
+// Test for iterating entities in ascending order of ids
+
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY a;
+        x: INTEGER;
+      END_ENTITY;
+
+      ENTITY b;
+        y: INTEGER;
+      END_ENTITY;
+
+      TYPE s = SELECT (a, b);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+/// Entities of `A` for odd ids and `B` for even ids, written in descending order
+fn tables() -> Tables {
+    let data: String = (1..=64)
+        .rev()
+        .map(|id| {
+            if id % 2 == 1 {
+                format!("#{} = A({});\n", id, id)
+            } else {
+                format!("#{} = B({});\n", id, id)
+            }
+        })
+        .collect();
+    Tables::from_data_section(&DataSection::from_str(&format!("DATA;\n{}ENDSEC;\n", data)).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn entity_iter() {
+    let tables = tables();
+    let odd: Vec<u64> = (1..=64).filter(|id| id % 2 == 1).collect();
+    assert_eq!(EntityTable::<AHolder>::entity_ids(&tables), odd);
+
+    let x: Vec<i64> = EntityTable::<AHolder>::owned_iter(&tables)
+        .map(|a| a.unwrap().x)
+        .collect();
+    assert_eq!(x, odd.iter().map(|id| *id as i64).collect::<Vec<_>>());
+}
+
+#[test]
+fn select_iter() {
+    let tables = tables();
+    let all: Vec<u64> = (1..=64).collect();
+    assert_eq!(EntityTable::<SHolder>::entity_ids(&tables), all);
+
+    let values: Vec<i64> = EntityTable::<SHolder>::owned_iter(&tables)
+        .map(|s| match s.unwrap() {
+            S::A(a) => a.x,
+            S::B(b) => b.y,
+        })
+        .collect();
+    assert_eq!(values, (1..=64).collect::<Vec<_>>());
+}
@@ -0,0 +1,105 @@
+//! Snapshot of [serde::Serialize] for AST in JSON
+
+use ruststep::parser;
+
+const STEP: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('sample'),'2;1');
+ENDSEC;
+DATA;
+#1 = CARTESIAN_POINT('origin',(0.0,1.5,-2));
+#2 = (A(*) B(.T.,"0F"));
+#3 = !MY_EXTENSION(#1,$,LABEL('x'),@4,#ORIGIN);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+const JSON: &str = r#"{
+  "header": [
+    {
+      "keyword": "FILE_DESCRIPTION",
+      "parameters": [
+        [
+          "sample"
+        ],
+        "2;1"
+      ]
+    }
+  ],
+  "anchor": [],
+  "reference": [],
+  "data": [
+    {
+      "meta": [],
+      "entities": [
+        {
+          "id": 1,
+          "record": {
+            "keyword": "CARTESIAN_POINT",
+            "parameters": [
+              "origin",
+              [
+                0.0,
+                1.5,
+                -2
+              ]
+            ]
+          }
+        },
+        {
+          "id": 2,
+          "records": [
+            {
+              "keyword": "A",
+              "parameters": [
+                {
+                  "omitted": true
+                }
+              ]
+            },
+            {
+              "keyword": "B",
+              "parameters": [
+                {
+                  "enumeration": "T"
+                },
+                {
+                  "binary": "0F"
+                }
+              ]
+            }
+          ]
+        },
+        {
+          "id": 3,
+          "record": {
+            "keyword": "!MY_EXTENSION",
+            "parameters": [
+              {
+                "entity": 1
+              },
+              null,
+              {
+                "keyword": "LABEL",
+                "parameter": "x"
+              },
+              {
+                "value": 4
+              },
+              {
+                "constant_entity": "ORIGIN"
+              }
+            ]
+          }
+        }
+      ]
+    }
+  ],
+  "signature": []
+}"#;
+
+#[test]
+fn snapshot() {
+    let exchange = parser::parse(STEP).unwrap();
+    assert_eq!(serde_json::to_string_pretty(&exchange).unwrap(), JSON);
+}
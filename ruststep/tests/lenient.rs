@@ -0,0 +1,49 @@
+// Test for skipping malformed entity instances
+
+use nom::Finish;
+use ruststep::parser::{exchange::*, parse};
+
+const STEP: &str = include_str!("steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+
+#[test]
+fn same_as_strict() {
+    let (_, (exchange, issues)) = exchange_file_lenient(STEP).finish().unwrap();
+    assert!(issues.is_empty());
+    assert_eq!(exchange, parse(STEP).unwrap());
+}
+
+#[test]
+fn skip_malformed() {
+    let exchange = parse(STEP).unwrap();
+    let first = exchange.data[0].entities[0].id();
+    let last = exchange.data[0].entities.last().unwrap().id();
+
+    // Break the first and last instances
+    let broken = STEP
+        .replacen(&format!("#{} =", first), &format!("#{} = ,", first), 1)
+        .replacen(
+            &format!("#{} =", last),
+            &format!("#{} = X('it's',", last),
+            1,
+        );
+    assert!(parse(&broken).is_err());
+
+    let (_, (recovered, issues)) = exchange_file_lenient(&broken).finish().unwrap();
+    let ids: Vec<_> = issues.iter().map(|issue| issue.id).collect();
+    assert_eq!(ids, [Some(first), Some(last)]);
+    assert_eq!(
+        issues[0].offset,
+        broken.find(&format!("#{} =", first)).unwrap()
+    );
+    assert_eq!(
+        recovered.data[0].entities,
+        exchange.data[0].entities[1..exchange.data[0].entities.len() - 1]
+    );
+}
+
+#[test]
+fn missing_endsec() {
+    let step = STEP.replacen("ENDSEC;\nEND-ISO-10303-21;", "END-ISO-10303-21;", 1);
+    assert_ne!(step, STEP);
+    assert!(exchange_file_lenient(&step).finish().is_err());
+}
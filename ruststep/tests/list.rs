@@ -1,5 +1,4 @@
-use nom::Finish;
-use ruststep::{ast::*, parser::exchange, tables::*};
+use ruststep::{assert_holder_eq, record, tables::*};
 use serde::Deserialize;
 
 espr_derive::inline_express!(
@@ -28,22 +27,16 @@ use test_schema::*;
 
 #[test]
 fn deserialize_list_a() {
-    let (residual, p): (_, Record) = exchange::simple_record("A((1.0, 2.0))").finish().unwrap();
-    dbg!(&p);
-    assert_eq!(residual, "");
+    let p = record!("A((1.0, 2.0))");
     let a: AHolder = Deserialize::deserialize(&p).unwrap();
-    dbg!(&a);
-    assert_eq!(a, AHolder { x: vec![1.0, 2.0] });
+    assert_holder_eq!(a, AHolder { x: vec![1.0, 2.0] });
 }
 
 #[test]
 fn deserialize_list_b() {
-    let (residual, p): (_, Record) = exchange::simple_record("B((A(((1.0)))))").finish().unwrap();
-    dbg!(&p);
-    assert_eq!(residual, "");
+    let p = record!("B((A(((1.0)))))");
     let b: BHolder = Deserialize::deserialize(&p).unwrap();
-    dbg!(&b);
-    assert_eq!(
+    assert_holder_eq!(
         b,
         BHolder {
             a: vec![PlaceHolder::Owned(AHolder { x: vec![1.0] })]
@@ -53,15 +46,9 @@ fn deserialize_list_b() {
 
 #[test]
 fn deserialize_list_c() {
-    let (residual, p): (_, Record) =
-        exchange::simple_record("C( ( ( A(((1.0))) ), ( A(((2.0))) ) ) )")
-            .finish()
-            .unwrap();
-    dbg!(&p);
-    assert_eq!(residual, "");
+    let p = record!("C( ( ( A(((1.0))) ), ( A(((2.0))) ) ) )");
     let c: CHolder = Deserialize::deserialize(&p).unwrap();
-    dbg!(&c);
-    assert_eq!(
+    assert_holder_eq!(
         c,
         CHolder {
             a: vec![
@@ -71,3 +58,9 @@ fn deserialize_list_c() {
         }
     );
 }
+
+#[test]
+#[should_panic(expected = "A.x: List(2) != List(1)")]
+fn assert_holder_eq_attribute_path() {
+    assert_holder_eq!(AHolder { x: vec![1.0, 2.0] }, AHolder { x: vec![1.0] });
+}
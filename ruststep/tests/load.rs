@@ -0,0 +1,172 @@
+// Test for progress reports and cancellation of loading tables
+
+use ruststep::{error::Error, load::*, tables::*};
+use std::ops::ControlFlow;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        name: STRING;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const STEP: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA('geometry', ('TEST_SCHEMA'));
+#1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));
+/* comment between instances */
+#3 = VERTEX_POINT('v', #1);
+ENDSEC;
+DATA('topology', ('TEST_SCHEMA'));
+#4 = VERTEX_POINT('w', #2);
+#5 = VERTEX_POINT('u', #1);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn progress() {
+    let mut reports = Vec::new();
+    let options = LoadOptions::new().interval(2).progress(|progress| {
+        reports.push((progress.phase, progress.processed, progress.total));
+        ControlFlow::Continue(())
+    });
+    let tables: Tables = load(STEP, options).unwrap();
+    assert_eq!(
+        reports,
+        [
+            (LoadPhase::Parsing, 2, None),
+            (LoadPhase::Parsing, 4, None),
+            (LoadPhase::Parsing, 5, Some(5)),
+            (LoadPhase::Populating, 2, Some(5)),
+            (LoadPhase::Populating, 4, Some(5)),
+            (LoadPhase::Populating, 5, Some(5)),
+            (LoadPhase::Checking, 2, Some(5)),
+            (LoadPhase::Checking, 4, Some(5)),
+            (LoadPhase::Checking, 5, Some(5)),
+        ]
+    );
+
+    let v: VertexPoint = EntityTable::<VertexPointHolder>::get_owned(&tables, 4).unwrap();
+    assert_eq!(v.vertex_geometry.name, "x");
+    assert_eq!(tables.vertex_point_holders().len(), 3);
+}
+
+#[test]
+fn cancel_while_populating() {
+    let mut reports = Vec::new();
+    let options = LoadOptions::new().interval(1).progress(|progress| {
+        reports.push((progress.phase, progress.processed));
+        if progress.phase == LoadPhase::Populating && progress.processed == 3 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    let result: Result<Tables, _> = load(STEP, options);
+    assert!(matches!(result, Err(Error::Cancelled)));
+    // Parsing is reported for each instance and at the end with the total number
+    assert_eq!(reports.len(), 6 + 3);
+    assert_eq!(reports.last(), Some(&(LoadPhase::Populating, 3)));
+}
+
+#[test]
+fn cancel_while_parsing() {
+    let options = LoadOptions::new()
+        .interval(1)
+        .progress(|_progress| ControlFlow::Break(()));
+    let result: Result<Tables, _> = load(STEP, options);
+    assert!(matches!(result, Err(Error::Cancelled)));
+}
+
+#[test]
+fn scope_and_trailing_comment() {
+    let step = STEP.replace(
+        "#5 = VERTEX_POINT('u', #1);",
+        "#5 = VERTEX_POINT('u', #6) &SCOPE #6 = CARTESIAN_POINT('y', (0.0, 1.0, 0.0)); ENDSCOPE;",
+    ) + "/* trailing comment */\n";
+    let mut parsed = Vec::new();
+    let options = LoadOptions::new().interval(1).progress(|progress| {
+        if progress.phase == LoadPhase::Parsing {
+            parsed.push(progress.processed);
+        }
+        ControlFlow::Continue(())
+    });
+    let tables: Tables = load(&step, options).unwrap();
+    assert_eq!(parsed, [1, 2, 3, 4, 5, 6, 6]);
+    assert_eq!(tables.cartesian_point_holders().len(), 3);
+}
+
+#[test]
+fn dangling_reference() {
+    let step = STEP.replace("#5 = VERTEX_POINT('u', #1);", "#5 = VERTEX_POINT('u', #9);");
+    let result: Result<Tables, _> = load(&step, LoadOptions::new());
+    assert!(matches!(result, Err(Error::UnknownEntity(9))));
+}
+
+#[test]
+fn fullwidth_punctuation() {
+    let step = STEP
+        .replace(
+            "#3 = VERTEX_POINT('v', #1);",
+            "＃3\u{3000}＝ VERTEX_POINT（'v', #1）；",
+        )
+        .replacen("ENDSEC;", "ENDSEC；", 1);
+    assert!(load::<Tables>(&step, LoadOptions::new()).is_err());
+    let options = LoadOptions::new().parse_options(ruststep::parser::ParseOptions {
+        normalize_fullwidth_punctuation: true,
+        ..Default::default()
+    });
+    let tables: Tables = load(&step, options).unwrap();
+    assert_eq!(tables, load::<Tables>(STEP, LoadOptions::new()).unwrap());
+}
+
+#[test]
+fn recover_header() {
+    let step = STEP.replacen("FILE_NAME('', ''", "FILE_NAME('' ''", 1);
+    assert!(load::<Tables>(&step, LoadOptions::new()).is_err());
+    let options = LoadOptions::new().parse_options(ruststep::parser::ParseOptions {
+        recover_header: true,
+        ..Default::default()
+    });
+    let tables: Tables = load(&step, options).unwrap();
+    assert_eq!(tables, load::<Tables>(STEP, LoadOptions::new()).unwrap());
+}
+
+#[test]
+fn unplaced_complex() {
+    let step = STEP.replace(
+        "#2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));",
+        "#2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));\n#6 = (LENGTH_UNIT() NAMED_UNIT(*) SI_UNIT(.MILLI.,.METRE.));",
+    );
+    let (tables, report): (Tables, _) = load_with_report(&step, LoadOptions::new()).unwrap();
+    assert_eq!(report.instances, 6);
+    assert_eq!(
+        report.unplaced,
+        [UnplacedComplex {
+            id: 6,
+            keywords: vec![
+                "LENGTH_UNIT".to_string(),
+                "NAMED_UNIT".to_string(),
+                "SI_UNIT".to_string()
+            ],
+        }]
+    );
+    assert_eq!(tables, load::<Tables>(STEP, LoadOptions::new()).unwrap());
+}
@@ -0,0 +1,123 @@
+// Test for merging a dictionary file and a data file with overlapping ids
+
+use ruststep::{
+    ast::*,
+    error::{Error, MergeError},
+    id::IdAllocator,
+};
+use std::str::FromStr;
+
+const DICTIONARY: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('dictionary'), '2;1');
+FILE_NAME('dictionary.p21', '2024-01-01T00:00:00', ('alice'), (''), '', '', '');
+FILE_SCHEMA(('ISO13584_IEC61360_DICTIONARY_SCHEMA'));
+ENDSEC;
+ANCHOR;
+<length> = #2;
+ENDSEC;
+DATA('dictionary', ('ISO13584_IEC61360_DICTIONARY_SCHEMA'));
+#1 = SUPPLIER_ELEMENT('71DCB7B4E0A3C');
+#2 = PROPERTY_DET('AAA001', #1);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+const DATA: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('data'), '2;1');
+FILE_NAME('data.p21', '2024-02-01T00:00:00', ('bob'), (''), '', '', '');
+FILE_SCHEMA(('ISO13584_IEC61360_DICTIONARY_SCHEMA', 'ISO13584_25_SCHEMA'));
+ENDSEC;
+ANCHOR;
+<tool> = #2;
+ENDSEC;
+REFERENCE;
+#3 = <dictionary.p21#length>;
+ENDSEC;
+DATA('data', ('ISO13584_25_SCHEMA'));
+#1 = PROPERTY_VALUE(#3, 12.5);
+#2 = TOOL(#1);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn merge_keeping_sections() {
+    let dictionary = Exchange::from_str(DICTIONARY).unwrap();
+    let data = Exchange::from_str(DATA).unwrap();
+    let merged = dictionary.merge(data).unwrap();
+
+    let header = merged.header_typed().unwrap();
+    assert_eq!(header.file_name.name, "dictionary.p21");
+    assert_eq!(
+        header.file_schema.schema,
+        ["ISO13584_IEC61360_DICTIONARY_SCHEMA", "ISO13584_25_SCHEMA"]
+    );
+
+    let sections: Vec<_> = merged.data.iter().map(|s| s.to_string()).collect();
+    assert_eq!(
+        sections,
+        [
+            "DATA('dictionary',('ISO13584_IEC61360_DICTIONARY_SCHEMA'));\n#1 = SUPPLIER_ELEMENT('71DCB7B4E0A3C');\n#2 = PROPERTY_DET('AAA001',#1);\nENDSEC;",
+            "DATA('data',('ISO13584_25_SCHEMA'));\n#3 = PROPERTY_VALUE(#5,12.5);\n#4 = TOOL(#3);\nENDSEC;",
+        ]
+    );
+    let anchors: Vec<_> = merged.anchor.iter().map(|a| a.to_string()).collect();
+    assert_eq!(anchors, ["<length> = #2;", "<tool> = #4;"]);
+    assert_eq!(
+        merged.reference[0].to_string(),
+        "#5 = <dictionary.p21#length>;"
+    );
+
+    // Written as a single file
+    assert_eq!(Exchange::from_str(&merged.to_string()).unwrap(), merged);
+}
+
+#[test]
+fn merge_skips_reserved_ids() {
+    let dictionary = Exchange::from_str(DICTIONARY).unwrap();
+    let data = Exchange::from_str(DATA).unwrap();
+    let mut ids = IdAllocator::new();
+    ids.reserve_range(4..100);
+    let merged = dictionary
+        .merge_with(
+            data,
+            &MergeOptions {
+                single_section: true,
+                ids,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        merged.data[0].to_string(),
+        "DATA('dictionary',('ISO13584_IEC61360_DICTIONARY_SCHEMA'));\n#1 = SUPPLIER_ELEMENT('71DCB7B4E0A3C');\n#2 = PROPERTY_DET('AAA001',#1);\n#3 = PROPERTY_VALUE(#101,12.5);\n#100 = TOOL(#3);\nENDSEC;"
+    );
+    assert_eq!(
+        merged.reference[0].to_string(),
+        "#101 = <dictionary.p21#length>;"
+    );
+}
+
+#[test]
+fn merge_conflicts() {
+    let dictionary = Exchange::from_str(DICTIONARY).unwrap();
+
+    let data = Exchange::from_str(&DATA.replace("'2;1'", "'3;1'")).unwrap();
+    let err = dictionary.clone().merge(data).unwrap_err();
+    assert!(matches!(
+        err,
+        MergeError::ImplementationLevelMismatch { ref first, ref second } if first == "2;1" && second == "3;1"
+    ));
+
+    let data = Exchange::from_str(&DATA.replace("<tool>", "<length>")).unwrap();
+    let err = dictionary.clone().merge(data).unwrap_err();
+    assert!(matches!(err, MergeError::DuplicatedAnchor(ref name) if name == "length"));
+
+    let data = Exchange::from_str(&DATA.replace("#2 = TOOL", "#1 = TOOL")).unwrap();
+    let err = dictionary.merge(data).unwrap_err();
+    assert!(matches!(
+        err,
+        MergeError::Invalid(Error::DuplicatedEntity(1))
+    ));
+}
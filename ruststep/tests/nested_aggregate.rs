@@ -0,0 +1,147 @@
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      TYPE point_grid = LIST [2:?] OF LIST [2:?] OF cartesian_point;
+      END_TYPE;
+
+      ENTITY quad_node;
+        children: ARRAY [1:2] OF ARRAY [1:2] OF OPTIONAL quad_node;
+      END_ENTITY;
+
+      ENTITY surface;
+        weights: LIST OF LIST OF REAL;
+        loops: LIST OF SET OF cartesian_point;
+        control_points_list: LIST [2:?] OF LIST [2:?] OF cartesian_point;
+        grid: point_grid;
+        corners: ARRAY [1:2] OF LIST OF cartesian_point;
+        knots: OPTIONAL LIST OF ARRAY [1:2] OF INTEGER;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn p(x: f64, y: f64) -> CartesianPoint {
+    CartesianPoint { x, y }
+}
+
+#[test]
+fn nested_aggregates() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = CARTESIAN_POINT(0.0, 0.0);
+          #2 = CARTESIAN_POINT(1.0, 0.0);
+          #3 = CARTESIAN_POINT(0.0, 1.0);
+          #4 = CARTESIAN_POINT(1.0, 1.0);
+          #5 = SURFACE(
+            ((1.0, 0.5), (0.5, 1.0)),
+            ((#1, #2), (#3)),
+            ((#1, #2), (#3, #4)),
+            POINT_GRID((((#1, #3), (#2, #4)))),
+            ((#1), (#2, CARTESIAN_POINT((2.0, 2.0)))),
+            ((1, 2), (3, 4))
+          );
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let surface = EntityTable::<SurfaceHolder>::get_owned(&table, 5).unwrap();
+    assert_eq!(
+        surface,
+        Surface {
+            weights: vec![vec![1.0, 0.5], vec![0.5, 1.0]],
+            loops: vec![vec![p(0.0, 0.0), p(1.0, 0.0)], vec![p(0.0, 1.0)]],
+            control_points_list: vec![
+                vec![p(0.0, 0.0), p(1.0, 0.0)],
+                vec![p(0.0, 1.0), p(1.0, 1.0)],
+            ],
+            grid: PointGrid(vec![
+                vec![p(0.0, 0.0), p(0.0, 1.0)],
+                vec![p(1.0, 0.0), p(1.0, 1.0)],
+            ]),
+            corners: [vec![p(0.0, 0.0)], vec![p(1.0, 0.0), p(2.0, 2.0)]],
+            knots: Some(vec![[1, 2], [3, 4]]),
+        }
+    );
+}
+
+#[test]
+fn write_back() {
+    let tables = Tables::from_str(
+        r#"
+        DATA;
+          #1 = CARTESIAN_POINT(0.0, 0.0);
+          #2 = CARTESIAN_POINT(1.0, 0.0);
+          #5 = SURFACE((), ((#1, #2)), ((#1, #2), (#2, #1)), POINT_GRID((((#1)))), ((), (#2)), $);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let written = tables.to_data_section().to_string();
+    assert!(
+        written.contains(
+            "#5 = SURFACE((),((#1,#2)),((#1,#2),(#2,#1)),POINT_GRID((((#1)))),((),(#2)),$);"
+        ),
+        "{}",
+        written
+    );
+    assert_eq!(Tables::from_str(&written).unwrap(), tables);
+}
+
+#[test]
+fn references_in_inner_lists() {
+    let tables = Tables::from_str(
+        r#"
+        DATA;
+          #1 = CARTESIAN_POINT(0.0, 0.0);
+          #5 = SURFACE((), ((#1), (#9)), ((#1), (#8)), POINT_GRID((((#1)))), ((), ()), $);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let dangling: Vec<_> = tables
+        .validate_references()
+        .into_iter()
+        .map(|r| (r.attribute, r.to))
+        .collect();
+    assert_eq!(
+        dangling,
+        [
+            ("loops".to_string(), 9),
+            ("control_points_list".to_string(), 8)
+        ]
+    );
+}
+
+#[test]
+fn fixed_size_arrays_in_cycle() {
+    let tables = Tables::from_str(
+        r#"
+        DATA;
+          #1 = QUAD_NODE((($, $), ($, $)));
+          #2 = QUAD_NODE(((#1, $), ($, #1)));
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let leaf = QuadNode {
+        children: Box::new([[None, None], [None, None]]),
+    };
+    let node = EntityTable::<QuadNodeHolder>::get_owned(&tables, 2).unwrap();
+    assert_eq!(
+        node,
+        QuadNode {
+            children: Box::new([[Some(leaf.clone()), None], [None, Some(leaf)]]),
+        }
+    );
+}
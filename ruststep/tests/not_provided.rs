@@ -0,0 +1,190 @@
+//! `$` and `*` in various places: parse, deserialize, and write back
+
+use ruststep::{
+    ast::{DataSection, EntityInstance, Parameter},
+    tables::*,
+};
+use serde::Deserialize;
+use std::{collections::HashMap, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+      END_ENTITY;
+
+      ENTITY sample;
+        a: OPTIONAL REAL;
+        b: LIST [0:?] OF REAL;
+        c: ARRAY [1:3] OF OPTIONAL REAL;
+        d: OPTIONAL ARRAY [1:2] OF OPTIONAL INTEGER;
+        e: ARRAY [1:2] OF OPTIONAL point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn sample() -> Sample {
+    Sample {
+        a: Some(1.0),
+        b: vec![2.0],
+        c: [Some(1.0), Some(2.0), Some(3.0)],
+        d: Some([Some(1), Some(2)]),
+        e: [Some(Point { x: 1.0 }), Some(Point { x: 1.0 })],
+    }
+}
+
+#[test]
+fn entity_attributes() {
+    let cases: Vec<(&str, Option<Sample>)> = vec![
+        (
+            "SAMPLE(1.0,(2.0),(1.0,2.0,3.0),(1,2),(#1,#1))",
+            Some(sample()),
+        ),
+        // OPTIONAL attribute
+        (
+            "SAMPLE($,(2.0),(1.0,2.0,3.0),(1,2),(#1,#1))",
+            Some(Sample {
+                a: None,
+                ..sample()
+            }),
+        ),
+        // Omitted attribute is also none
+        (
+            "SAMPLE(*,(2.0),(1.0,*,3.0),(1,2),(#1,#1))",
+            Some(Sample {
+                a: None,
+                c: [Some(1.0), None, Some(3.0)],
+                ..sample()
+            }),
+        ),
+        // Not optional
+        ("SAMPLE(1.0,$,(1.0,2.0,3.0),(1,2),(#1,#1))", None),
+        ("SAMPLE(1.0,*,(1.0,2.0,3.0),(1,2),(#1,#1))", None),
+        ("SAMPLE(1.0,(2.0),$,(1,2),(#1,#1))", None),
+        // Element of LIST OF non-optional type
+        ("SAMPLE(1.0,(2.0,$),(1.0,2.0,3.0),(1,2),(#1,#1))", None),
+        // Elements of ARRAY OF OPTIONAL
+        (
+            "SAMPLE(1.0,(2.0),(1.0,$,3.0),(1,2),(#1,#1))",
+            Some(Sample {
+                c: [Some(1.0), None, Some(3.0)],
+                ..sample()
+            }),
+        ),
+        (
+            "SAMPLE(1.0,(2.0),($,$,$),(1,2),(#1,#1))",
+            Some(Sample {
+                c: [None, None, None],
+                ..sample()
+            }),
+        ),
+        // OPTIONAL ARRAY OF OPTIONAL
+        (
+            "SAMPLE(1.0,(2.0),(1.0,2.0,3.0),$,(#1,#1))",
+            Some(Sample {
+                d: None,
+                ..sample()
+            }),
+        ),
+        (
+            "SAMPLE(1.0,(2.0),(1.0,2.0,3.0),($,2),(#1,#1))",
+            Some(Sample {
+                d: Some([None, Some(2)]),
+                ..sample()
+            }),
+        ),
+        // ARRAY OF OPTIONAL entity
+        (
+            "SAMPLE(1.0,(2.0),(1.0,2.0,3.0),(1,2),(#1,$))",
+            Some(Sample {
+                e: [Some(Point { x: 1.0 }), None],
+                ..sample()
+            }),
+        ),
+        (
+            "SAMPLE($,(),($,$,$),$,($,$))",
+            Some(Sample {
+                a: None,
+                b: Vec::new(),
+                c: [None, None, None],
+                d: None,
+                e: [None, None],
+            }),
+        ),
+    ];
+
+    for (record, expected) in cases {
+        let data =
+            DataSection::from_str(&format!("DATA; #1 = POINT(1.0); #2 = {}; ENDSEC;", record))
+                .unwrap();
+
+        // Written back as is
+        let instance = &data.entities[1];
+        assert_eq!(instance.to_string(), format!("#2 = {};", record));
+        assert_eq!(
+            &EntityInstance::from_str(&instance.to_string()).unwrap(),
+            instance
+        );
+
+        let owned = Tables::from_data_section(&data)
+            .and_then(|tables| EntityTable::<SampleHolder>::get_owned(&tables, 2));
+        match expected {
+            Some(expected) => assert_eq!(owned.unwrap(), expected, "{}", record),
+            None => assert!(owned.is_err(), "{}", record),
+        }
+    }
+}
+
+#[test]
+fn parameters() {
+    let cases = {
+        use Parameter::*;
+        [
+            ("$", NotProvided),
+            ("*", Omitted),
+            ("($)", List(vec![NotProvided])),
+            ("($,*)", List(vec![NotProvided, Omitted])),
+            ("($,1)", List(vec![NotProvided, Integer(1)])),
+            (
+                "((1,$),$)",
+                List(vec![List(vec![Integer(1), NotProvided]), NotProvided]),
+            ),
+            (
+                "A($)",
+                Typed {
+                    keyword: "A".to_string(),
+                    parameter: Box::new(NotProvided),
+                },
+            ),
+            (
+                "A(($,2.0))",
+                Typed {
+                    keyword: "A".to_string(),
+                    parameter: Box::new(List(vec![NotProvided, Parameter::real(2.0)])),
+                },
+            ),
+        ]
+    };
+    for (input, expected) in cases {
+        let p = Parameter::from_str(input).unwrap();
+        assert_eq!(p, expected);
+        assert_eq!(p.to_string(), input);
+    }
+
+    // Typed `$`
+    let p = Parameter::from_str("A($)").unwrap();
+    let map = HashMap::<String, Option<f64>>::deserialize(&p).unwrap();
+    assert_eq!(map, maplit::hashmap! { "A".to_string() => None });
+    assert!(HashMap::<String, f64>::deserialize(&p).is_err());
+
+    let p = Parameter::from_str("A(($,2.0))").unwrap();
+    let map = HashMap::<String, Vec<Option<f64>>>::deserialize(&p).unwrap();
+    assert_eq!(
+        map,
+        maplit::hashmap! { "A".to_string() => vec![None, Some(2.0)] }
+    );
+}
@@ -0,0 +1,86 @@
+// Test for resolving entities on threads of rayon
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY segment;
+        head: point;
+        tail: OPTIONAL point;
+      END_ENTITY;
+
+      TYPE shape = SELECT (point, segment);
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use rayon::prelude::*;
+use test_schema::*;
+
+/// Segments `#(2n)` between points `#(2n - 1)` and `#1`, and a segment to a missing point `#1000`
+fn tables() -> Tables {
+    let mut data = String::new();
+    for n in 1..=200 {
+        data += &format!("#{} = POINT({}.0, 0.0);\n", 2 * n - 1, n);
+        data += &format!("#{} = SEGMENT(#{}, #1);\n", 2 * n, 2 * n - 1);
+    }
+    data += "#999 = SEGMENT(#1, #1000);\n";
+    Tables::from_data_section(&DataSection::from_str(&format!("DATA;\n{}ENDSEC;\n", data)).unwrap())
+        .unwrap()
+}
+
+fn sorted<T: PartialOrd>(mut values: Vec<T>) -> Vec<T> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    values
+}
+
+#[test]
+fn same_as_sequential() {
+    let tables = tables();
+
+    let sequential: Vec<Point> = EntityTable::<PointHolder>::owned_iter(&tables)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let parallel: Vec<Point> = ParEntityTable::<PointHolder>::par_owned_iter(&tables)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        sorted(parallel.iter().map(|p| p.x).collect()),
+        sorted(sequential.iter().map(|p| p.x).collect())
+    );
+    // Indexed in ascending order of ids
+    assert_eq!(parallel, sequential);
+
+    let sequential: Vec<String> = EntityTable::<ShapeHolder>::owned_iter(&tables)
+        .map(|shape| format!("{:?}", shape))
+        .collect();
+    let parallel: Vec<String> = ParEntityTable::<ShapeHolder>::par_owned_iter(&tables)
+        .map(|shape| format!("{:?}", shape))
+        .collect();
+    assert_eq!(sorted(parallel), sorted(sequential));
+}
+
+#[test]
+fn modes_of_calling_thread() {
+    let tables = tables();
+    let errors = ParEntityTable::<SegmentHolder>::par_owned_iter(&tables)
+        .filter(|segment| segment.is_err())
+        .count();
+    assert_eq!(errors, 1);
+
+    let segments: Vec<Segment> = with_lenient_references(|| {
+        ParEntityTable::<SegmentHolder>::par_owned_iter(&tables)
+            .collect::<Result<_, Error>>()
+            .unwrap()
+    });
+    assert_eq!(segments.len(), 201);
+    assert_eq!(segments[200].tail, None);
+}
@@ -0,0 +1,79 @@
+// Test for parsing DATA sections on multiple threads
+
+use nom::Finish;
+use ruststep::{error::TokenizeFailed, parser::exchange::*};
+use std::{fs, path::PathBuf};
+
+fn fixtures() -> Vec<(PathBuf, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/steps");
+    let mut fixtures: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext != "md"))
+        .map(|path| {
+            let input = fs::read_to_string(&path).unwrap();
+            (path, input)
+        })
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+#[test]
+fn same_as_sequential() {
+    for (path, input) in fixtures() {
+        let sequential = exchange_file(&input).finish();
+        let parallel = exchange_file_parallel(&input).finish();
+        match (sequential, parallel) {
+            (Ok(sequential), Ok(parallel)) => assert_eq!(parallel, sequential, "{:?}", path),
+            (Err(sequential), Err(parallel)) => assert_eq!(
+                TokenizeFailed::new(&input, parallel).offset(),
+                TokenizeFailed::new(&input, sequential).offset(),
+                "{:?}",
+                path
+            ),
+            (sequential, parallel) => panic!(
+                "{:?}: sequential {}, parallel {}",
+                path,
+                sequential.is_ok(),
+                parallel.is_ok()
+            ),
+        }
+    }
+}
+
+#[test]
+fn large_section() {
+    // Enough instances to be split, with `;` in strings and comments, and a scope block
+    let mut input =
+        String::from("ISO-10303-21;\nHEADER;\nFILE_DESCRIPTION(('a;b'), '2;1');\nENDSEC;\nDATA;\n");
+    for id in 1..=20000 {
+        match id % 1000 {
+            0 => input += &format!("#{} = A('x;y') /* ; */;\n", id),
+            500 => {
+                input += &format!(
+                    "#{} = &SCOPE #{} = B(); ENDSCOPE A(#{});\n",
+                    id,
+                    id + 100000,
+                    id + 100000
+                )
+            }
+            _ => input += &format!("#{} = B({}.0, '#{}');\n", id, id, id),
+        }
+    }
+    input += "ENDSEC;\nEND-ISO-10303-21;\n";
+
+    let (_, sequential) = exchange_file(&input).finish().unwrap();
+    let (_, parallel) = exchange_file_parallel(&input).finish().unwrap();
+    assert_eq!(sequential.data[0].entities.len(), 20020);
+    assert_eq!(parallel, sequential);
+
+    // Error in a chunk is same as sequential
+    let broken = input.replace("#15000 = A('x;y')", "#15000 = A('x;y',)");
+    let sequential = exchange_file(&broken).finish().unwrap_err();
+    let parallel = exchange_file_parallel(&broken).finish().unwrap_err();
+    assert_eq!(
+        TokenizeFailed::new(&broken, parallel).offset(),
+        TokenizeFailed::new(&broken, sequential).offset()
+    );
+}
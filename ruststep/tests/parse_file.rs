@@ -0,0 +1,43 @@
+// Test for parsing STEP files from paths and bytes
+
+use ruststep::{error::Error, parser::*};
+use std::{fs, path::PathBuf};
+
+const STEP: &str = include_str!("steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+
+fn temp_file(name: &str, bytes: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("ruststep-{}-{}", std::process::id(), name));
+    fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn same_as_parse() {
+    let path = temp_file("bom.step", &[b"\xEF\xBB\xBF", STEP.as_bytes()].concat());
+    assert_eq!(parse_file(&path).unwrap(), parse(STEP).unwrap());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn latin1() {
+    let latin1: Vec<u8> = STEP
+        .replacen("''", "'Gr\u{FC}\u{DF}e'", 1)
+        .chars()
+        .map(|c| c as u8)
+        .collect();
+    let utf8 = STEP.replacen("''", "'Grüße'", 1);
+    assert_eq!(parse_bytes(&latin1).unwrap(), parse(&utf8).unwrap());
+}
+
+#[test]
+fn error_with_path() {
+    let path = temp_file("broken.step", b"ISO-10303-21; HEADER;");
+    match parse_file(&path).unwrap_err() {
+        Error::FileFailed { path: p, source } => {
+            assert_eq!(p, path);
+            assert!(matches!(*source, Error::TokenizeFailed(_)));
+        }
+        e => panic!("unexpected {:?}", e),
+    }
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,162 @@
+// Test for deserializing a single entity type without Tables
+
+use ruststep::{ast::*, error::Error, parser, tables::*};
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        name: STRING;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn abc_example() -> Exchange {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    let step_str = fs::read_to_string(step_file).unwrap();
+    parser::parse(&step_str).unwrap()
+}
+
+#[test]
+fn extract_points_without_tables() {
+    let exchange = abc_example();
+    let entities = &exchange.data[0].entities;
+
+    let extracted: HashMap<u64, CartesianPointHolder> = entities
+        .iter()
+        .filter_map(|instance| {
+            let id = match instance {
+                EntityInstance::Simple { id, .. } | EntityInstance::Complex { id, .. } => *id,
+            };
+            CartesianPointHolder::try_from_instance(instance)
+                .ok()
+                .map(|point| (id, point))
+        })
+        .collect();
+    assert!(!extracted.is_empty());
+
+    // Create Tables only from CARTESIAN_POINT
+    let points_only = DataSection {
+        meta: Vec::new(),
+        entities: entities
+            .iter()
+            .filter(|instance| {
+                matches!(instance, EntityInstance::Simple { record, .. } if record.name == "CARTESIAN_POINT")
+            })
+            .cloned()
+            .collect(),
+    };
+    let tables = Tables::from_data_section(&points_only).unwrap();
+    assert_eq!(&extracted, tables.cartesian_point_holders());
+}
+
+#[test]
+fn parse_record_keeps_reference() {
+    let record = Record::from_str("VERTEX_POINT('v', #3)").unwrap();
+    let vertex = VertexPointHolder::parse_record(&record).unwrap();
+    assert_eq!(
+        vertex,
+        VertexPointHolder {
+            name: "v".to_string(),
+            vertex_geometry: PlaceHolder::Ref(Name::Entity(3)),
+        }
+    );
+}
+
+#[test]
+fn parse_record_case_insensitive() {
+    let record = Record {
+        name: "cartesian_point".to_string(),
+        user_defined: false,
+        parameter: vec![Parameter::string("p"), vec![Parameter::real(1.0)].into()].into(),
+    };
+    let point = CartesianPointHolder::try_from(&record).unwrap();
+    assert_eq!(
+        point,
+        CartesianPointHolder {
+            name: "p".to_string(),
+            coordinates: vec![1.0],
+        }
+    );
+}
+
+#[test]
+fn parse_record_errors() {
+    let record = Record::from_str("VERTEX_POINT('v', #3)").unwrap();
+    assert!(matches!(
+        CartesianPointHolder::parse_record(&record),
+        Err(Error::KeywordMismatch { .. })
+    ));
+
+    let record = Record::from_str("CARTESIAN_POINT('p', (1.0), 2.0)").unwrap();
+    assert!(matches!(
+        CartesianPointHolder::parse_record(&record),
+        Err(Error::DeserializeFailed(_))
+    ));
+}
+
+#[test]
+fn parse_complex_instance() {
+    let instance =
+        EntityInstance::from_str("#1 = (CARTESIAN_POINT('p', (1.0, 2.0)) OTHER(1));").unwrap();
+    let point = CartesianPointHolder::try_from_instance(&instance).unwrap();
+    assert_eq!(point.coordinates, vec![1.0, 2.0]);
+
+    assert!(VertexPointHolder::try_from_instance(&instance).is_err());
+
+    // Tables skip the instance, and report the combination of keywords instead of dropping it
+    let section = DataSection::from_str(
+        "DATA; #1 = (CARTESIAN_POINT('p', (1.0, 2.0)) OTHER(1)); #2 = CARTESIAN_POINT('q', (3.0)); ENDSEC;",
+    )
+    .unwrap();
+    let (mut tables, unplaced) = Tables::from_data_section_with_report(&section).unwrap();
+    assert_eq!(
+        unplaced,
+        [UnplacedComplex {
+            id: 1,
+            keywords: vec!["CARTESIAN_POINT".to_string(), "OTHER".to_string()],
+        }]
+    );
+    assert_eq!(tables.cartesian_point_holders().len(), 1);
+    assert_eq!(Tables::from_data_section(&section).unwrap(), tables);
+
+    let err = tables
+        .append_entity_instance(&section.entities[0])
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        Error::UnplacedComplexEntity { id: 1, keywords } if keywords == &["CARTESIAN_POINT", "OTHER"]
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Complex entity instance #1 of (CARTESIAN_POINT OTHER) cannot be placed in tables"
+    );
+}
+
+#[test]
+fn user_defined_keyword_never_matches() {
+    let record = Record::from_str("!CARTESIAN_POINT('p', (1.0))").unwrap();
+    assert!(record.user_defined);
+    assert!(matches!(
+        CartesianPointHolder::parse_record(&record),
+        Err(Error::KeywordMismatch { found, .. }) if found == "!CARTESIAN_POINT"
+    ));
+
+    let section =
+        DataSection::from_str("DATA; #1 = !CARTESIAN_POINT('p', (1.0)); ENDSEC;").unwrap();
+    assert!(matches!(
+        Tables::from_data_section(&section),
+        Err(Error::UnknownEntityName { entity_name, .. }) if entity_name == "!CARTESIAN_POINT"
+    ));
+}
@@ -0,0 +1,210 @@
+// Test for the public surface of ruststep
+//
+// Update the listings below only when the change of the public API is intended.
+
+#[allow(unused_imports)]
+use ruststep::prelude::{
+    parse, parse_with, DataSection, EntityInstance, EntityTable, Error, Exchange, Holder,
+    IntoOwned, Name, Parameter, ParseOptions, PlaceHolder, Record, Result, SubSuperRecord,
+    TableInit,
+};
+
+/// Top-level public items in `source`, with `(hidden)` suffix for `#[doc(hidden)]` ones
+///
+/// `pub use a::{b, c::{d, e}}` is flattened into `use a::b`, `use a::c::d`, and `use a::c::e`.
+fn public_items(source: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut hidden = false;
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if line == "#[doc(hidden)]" {
+            hidden = true;
+            continue;
+        }
+        if line.starts_with("#[") || line.starts_with("///") {
+            continue;
+        }
+        let suffix = if std::mem::take(&mut hidden) {
+            " (hidden)"
+        } else {
+            ""
+        };
+        if let Some(path) = line.strip_prefix("pub use ") {
+            let mut statement = path.to_string();
+            while !statement.ends_with(';') {
+                statement += lines.next().unwrap().trim();
+            }
+            let mut paths = Vec::new();
+            flatten("", statement.trim_end_matches(';'), &mut paths);
+            items.extend(
+                paths
+                    .into_iter()
+                    .map(|path| format!("use {}{}", path, suffix)),
+            );
+            continue;
+        }
+        for kind in ["mod", "fn", "struct", "enum", "trait", "type", "const"] {
+            if let Some(rest) = line.strip_prefix(&format!("pub {} ", kind)) {
+                let name: String = rest
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                items.push(format!("{} {}{}", kind, name, suffix));
+            }
+        }
+    }
+    items
+}
+
+fn flatten(prefix: &str, tree: &str, paths: &mut Vec<String>) {
+    let tree = tree.trim().trim_end_matches(',');
+    let Some((head, group)) = tree.split_once('{') else {
+        paths.push(format!("{}{}", prefix, tree));
+        return;
+    };
+    let group = group.strip_suffix('}').unwrap();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in group.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                flatten(&format!("{}{}", prefix, head), &group[start..i], paths);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !group[start..].trim().is_empty() {
+        flatten(&format!("{}{}", prefix, head), &group[start..], paths);
+    }
+}
+
+#[test]
+fn prelude() {
+    assert_eq!(
+        public_items(include_str!("../src/prelude.rs")),
+        [
+            "use crate::ast::DataSection",
+            "use crate::ast::EntityInstance",
+            "use crate::ast::Exchange",
+            "use crate::ast::Name",
+            "use crate::ast::Parameter",
+            "use crate::ast::Record",
+            "use crate::ast::SubSuperRecord",
+            "use crate::error::Error",
+            "use crate::error::Result",
+            "use crate::parser::parse",
+            "use crate::parser::parse_with",
+            "use crate::parser::ParseOptions",
+            "use crate::tables::EntityTable",
+            "use crate::tables::Holder",
+            "use crate::tables::IntoOwned",
+            "use crate::tables::PlaceHolder",
+            "use crate::tables::TableInit",
+        ]
+    );
+}
+
+#[test]
+fn modules() {
+    assert_eq!(
+        public_items(include_str!("../src/lib.rs")),
+        [
+            "mod assembly",
+            "mod ast",
+            "mod batch",
+            "mod cache",
+            "mod capabilities",
+            "mod de",
+            "mod dictionary",
+            "mod diff",
+            "mod edit",
+            "mod error",
+            "mod flavor",
+            "mod hash",
+            "mod header",
+            "mod id",
+            "mod load",
+            "mod parser",
+            "mod prelude",
+            "mod primitive",
+            "mod repair",
+            "mod stats",
+            "mod tables",
+            "mod writer",
+            "mod testing",
+            "use derive_more (hidden)",
+            "use itertools (hidden)",
+            "use serde (hidden)",
+            "use ruststep_derive::*",
+            "mod ap201",
+            "mod ap203",
+        ]
+    );
+}
+
+#[test]
+fn tables() {
+    assert_eq!(
+        public_items(include_str!("../src/tables.rs")),
+        [
+            "trait IntoOwned",
+            "fn with_lenient_references",
+            "trait Holder",
+            "trait References (hidden)",
+            "enum AttributeValue",
+            "trait ReadAttribute",
+            "trait AnyTables",
+            "fn table_attr_names (hidden)",
+            "fn visit_table (hidden)",
+            "fn unique_violations (hidden)",
+            "fn set_violations (hidden)",
+            "fn has_duplicates (hidden)",
+            "fn inverse_of (hidden)",
+            "struct AttributeDescriptor",
+            "struct EntityDescriptor",
+            "fn get_debug (hidden)",
+            "enum ValueKind",
+            "struct AttributeKind",
+            "struct DeserializeOptions",
+            "fn with_deserialize_options",
+            "struct AttributeOrder (hidden)",
+            "fn attribute_order (hidden)",
+            "trait WithVisitor",
+            "trait EntityTable",
+            "trait ParEntityTable",
+            "trait TableIds",
+            "struct DanglingRef",
+            "fn table_references (hidden)",
+            "struct Memo",
+            "fn try_map_array (hidden)",
+            "trait IntoHolder",
+            "trait ToParameter",
+            "trait ToRecord",
+            "fn instances (hidden)",
+            "fn data_section (hidden)",
+            "fn step_string (hidden)",
+            "struct UnplacedComplex",
+            "trait TableInit",
+            "enum SectionSelector",
+            "struct ResolveCache",
+            "fn with_resolve_cache",
+            "fn get_owned (hidden)",
+            "fn entity_ids (hidden)",
+            "fn owned_iter (hidden)",
+            "fn any_iter (hidden)",
+            "struct EntityError",
+            "struct ErrorSummary",
+            "fn table_errors (hidden)",
+            "fn sorted_errors (hidden)",
+            "fn insert_record (hidden)",
+            "fn parse_record (hidden)",
+            "fn parse_instance (hidden)",
+            "struct Constants",
+            "trait TableConstants",
+            "enum PlaceHolder",
+        ]
+    );
+}
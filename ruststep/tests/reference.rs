@@ -0,0 +1,95 @@
+// Test for REFERENCE section of the second edition
+
+use ruststep::{ast::*, parser};
+
+const STEP: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('assembly distributed across files'), '3;1');
+FILE_NAME('assembly.stp', '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('AP242_MANAGED_MODEL_BASED_3D_ENGINEERING_MIM_LF'));
+ENDSEC;
+REFERENCE;
+#100 = <bolt.stp#bolt>;
+#101 = <nut.stp#nut>;
+@102 = <materials.stp#steel>;
+#103 = <bolt.stp#washer>;
+ENDSEC;
+DATA;
+#1 = ASSEMBLY('fastener', (#100, #101, #103), @102);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn references() {
+    let exchange = parser::parse(STEP).unwrap();
+    let names: Vec<_> = exchange.reference.iter().map(|entry| &entry.name).collect();
+    assert_eq!(
+        names,
+        [
+            &Name::Entity(100),
+            &Name::Entity(101),
+            &Name::Value(102),
+            &Name::Entity(103)
+        ]
+    );
+
+    let uris: Vec<_> = exchange
+        .external_references()
+        .map(|uri| uri.0.as_str())
+        .collect();
+    assert_eq!(
+        uris,
+        [
+            "bolt.stp#bolt",
+            "nut.stp#nut",
+            "materials.stp#steel",
+            "bolt.stp#washer"
+        ]
+    );
+
+    let mut documents: Vec<_> = exchange.external_references().map(URI::document).collect();
+    documents.sort_unstable();
+    documents.dedup();
+    assert_eq!(documents, ["bolt.stp", "materials.stp", "nut.stp"]);
+
+    let fragments: Vec<_> = exchange
+        .external_references()
+        .map(|uri| uri.fragment().unwrap())
+        .collect();
+    assert_eq!(fragments, ["bolt", "nut", "steel", "washer"]);
+}
+
+#[test]
+fn round_trip() {
+    let exchange = parser::parse(STEP).unwrap();
+    assert_eq!(parser::parse(&exchange.to_string()).unwrap(), exchange);
+    assert_eq!(parser::parse_borrowed(STEP).unwrap().into_owned(), exchange);
+}
+
+#[test]
+fn without_reference_section() {
+    let start = STEP.find("REFERENCE;").unwrap();
+    let end = STEP.find("DATA;").unwrap();
+    let input = format!("{}{}", &STEP[..start], &STEP[end..]).replace("@102", "$");
+    let exchange = parser::parse(&input).unwrap();
+    assert!(exchange.reference.is_empty());
+    assert_eq!(exchange.external_references().count(), 0);
+}
+
+#[test]
+fn unknown_section() {
+    let input = STEP.replace("REFERENCE;", "REFERENCES;");
+    assert!(parser::parse(&input).is_err());
+    assert!(parser::parse_borrowed(&input).is_err());
+
+    // REFERENCE section must be placed before DATA sections
+    let start = STEP.find("REFERENCE;").unwrap();
+    let end = STEP.find("DATA;").unwrap();
+    let section = &STEP[start..end];
+    let input = STEP.replace(section, "").replace(
+        "END-ISO-10303-21;",
+        &format!("{}END-ISO-10303-21;", section),
+    );
+    assert!(parser::parse(&input).is_err());
+}
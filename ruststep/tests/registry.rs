@@ -0,0 +1,90 @@
+// Test for looking up entities by keyword at runtime via `SCHEMA_REGISTRY`
+
+use ruststep::{ast::DataSection, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY point;
+        x: REAL;
+        y: REAL;
+      END_ENTITY;
+
+      ENTITY polyline SUBTYPE OF (curve);
+        points: LIST [2:?] OF point;
+        closed: OPTIONAL BOOLEAN;
+      END_ENTITY;
+
+      ENTITY curve;
+        name: STRING;
+      END_ENTITY;
+
+      TYPE label = STRING;
+      END_TYPE;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+fn tables() -> Tables {
+    Tables::from_data_section(
+        &DataSection::from_str(
+            r#"
+            DATA;
+              #1 = POINT(0.0, 0.0);
+              #2 = POINT(1.0, 2.0);
+              #3 = POLYLINE(CURVE(('c')), (#1, #2), $);
+              #4 = LABEL('name');
+            ENDSEC;
+            "#,
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn registry() {
+    let keywords: Vec<_> = SCHEMA_REGISTRY.iter().map(|d| d.keyword).collect();
+    assert_eq!(keywords, ["POINT", "POLYLINE", "CURVE", "LABEL"]);
+
+    let polyline = Tables::descriptor_for("POLYLINE").unwrap();
+    assert_eq!(polyline.supertypes, ["CURVE"]);
+    assert_eq!(
+        polyline.attributes,
+        [
+            AttributeDescriptor {
+                name: "points",
+                ty: "LIST OF POINT",
+                optional: false,
+            },
+            AttributeDescriptor {
+                name: "closed",
+                ty: "BOOLEAN",
+                optional: true,
+            },
+        ]
+    );
+
+    let label = Tables::descriptor_for("LABEL").unwrap();
+    assert!(label.supertypes.is_empty());
+    assert!(label.attributes.is_empty());
+
+    assert!(Tables::descriptor_for("UNKNOWN").is_none());
+}
+
+#[test]
+fn get_by_keyword() {
+    let tables = tables();
+    for id in 1..=4 {
+        let descriptor = Tables::descriptor_for(tables.keyword_of(id).unwrap()).unwrap();
+        assert!((descriptor.get)(&tables, id).is_ok());
+    }
+
+    let point = Tables::descriptor_for("POINT").unwrap();
+    let value = (point.get)(&tables, 2).unwrap();
+    assert_eq!(format!("{:?}", value), "Point { x: 1.0, y: 2.0 }");
+    assert!((point.get)(&tables, 3).is_err());
+}
@@ -0,0 +1,162 @@
+// Test for repairing non-conforming exchange structures
+
+use ruststep::{
+    error::Error,
+    parser::{self, ParseOptions},
+    repair::*,
+};
+
+const DEFECTS: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+#1 = POINT(0.5, 1.0);
+#2 = point(1.0, 2.0);
+#3 = POINT(.5, 3.0);
+#4 = FILE('C:\temp');
+#5 = LINE(#1, #9);
+#1 = POINT(9.0, 9.0);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn five_defects() {
+    assert!(parser::parse_with(DEFECTS, &ParseOptions::strict()).is_err());
+
+    let (output, log) = repair(DEFECTS, RepairPolicy::all()).unwrap();
+    let exchange = parser::parse_with(&output, &ParseOptions::strict()).unwrap();
+    assert_eq!(exchange.data[0].entities.len(), 5);
+
+    let summary: Vec<_> = log
+        .repairs
+        .iter()
+        .map(|repair| {
+            (
+                repair.id,
+                repair.line,
+                repair.category,
+                repair.before.as_str(),
+                repair.after.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (Some(2), 9, RepairCategory::KeywordCase, "point", "POINT"),
+            (Some(3), 10, RepairCategory::Number, ".5", "0.5"),
+            (Some(4), 11, RepairCategory::StringEscape, "\\t", "\\\\t"),
+            (Some(5), 12, RepairCategory::DanglingReference, "#9", "$"),
+            (
+                Some(1),
+                13,
+                RepairCategory::DuplicateId,
+                "#1 = POINT(9.0,9.0);",
+                ""
+            ),
+        ]
+    );
+}
+
+#[test]
+fn keep_last() {
+    let policy = RepairPolicy {
+        duplicate_ids: DuplicatePolicy::KeepLast,
+        ..RepairPolicy::all()
+    };
+    let (output, log) = repair(DEFECTS, policy).unwrap();
+    assert!(output.contains("#1 = POINT(9.0,9.0);"));
+    assert!(!output.contains("#1 = POINT(0.5,1.0);"));
+    assert_eq!(log.repairs[0].line, 8);
+    assert_eq!(log.repairs[0].category, RepairCategory::DuplicateId);
+}
+
+#[test]
+fn abort_with_partial_log() {
+    let policy = RepairPolicy {
+        duplicate_ids: DuplicatePolicy::Abort,
+        ..RepairPolicy::all()
+    };
+    match repair(DEFECTS, policy) {
+        Err(Error::RepairFailed { source, log }) => {
+            assert!(matches!(*source, Error::DuplicatedEntity(1)));
+            // Text repairs before the failure
+            assert_eq!(log.repairs.len(), 3);
+        }
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn disabled_repairs_fail() {
+    assert!(matches!(
+        repair(DEFECTS, RepairPolicy::none()),
+        Err(Error::RepairFailed { .. })
+    ));
+}
+
+#[test]
+fn encode_characters() {
+    let input = "DATA;\n#1 = PERSON('Muñoz', '東京', 'tab\there');\nENDSEC;\n";
+    let (output, log) = repair(
+        &format!(
+            "ISO-10303-21;\nHEADER;\nFILE_DESCRIPTION((''), '2;1');\nENDSEC;\n{}END-ISO-10303-21;\n",
+            input
+        ),
+        RepairPolicy::all(),
+    )
+    .unwrap();
+    assert!(output
+        .contains(r"#1 = PERSON('Mu\X2\00F1\X0\oz','\X2\67714EAC\X0\','tab\X2\0009\X0\here');"));
+    assert_eq!(log.count(RepairCategory::CharacterEncoding), 3);
+}
+
+#[test]
+fn numbers() {
+    let input = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+ENDSEC;
+DATA;
+#1 = A(1e5, - 2.5e-3, -.25, 3, (.5, 2.E1));
+ENDSEC;
+END-ISO-10303-21;
+"#;
+    let (output, log) = repair(input, RepairPolicy::all()).unwrap();
+    parser::parse_with(&output, &ParseOptions::strict()).unwrap();
+    let fixed: Vec<_> = log
+        .repairs
+        .iter()
+        .map(|repair| (repair.before.as_str(), repair.after.as_str()))
+        .collect();
+    assert_eq!(
+        fixed,
+        vec![
+            ("1e5", "1.E5"),
+            ("- ", "-"),
+            ("2.5e-3", "2.5E-3"),
+            (".25", "0.25"),
+            (".5", "0.5"),
+        ]
+    );
+}
+
+#[test]
+fn json() {
+    let (_, log) = repair(DEFECTS, RepairPolicy::all()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&log.repairs[1]).unwrap(),
+        serde_json::json!({
+            "id": 3,
+            "offset": DEFECTS.find("(.5").unwrap() + 1,
+            "line": 10,
+            "category": "number",
+            "before": ".5",
+            "after": "0.5",
+        })
+    );
+}
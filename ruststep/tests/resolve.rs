@@ -0,0 +1,141 @@
+// Test for resolving long chains of references
+
+use ruststep::{ast::DataSection, error::Error, tables::*};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY node SUPERTYPE OF (link);
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY link SUBTYPE OF (node);
+        next: node;
+        other: node;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+/// `#1` is a node, and `#k` is a link to `#k-1` and `#other(k)`
+fn chain(len: u64, other: impl Fn(u64) -> u64) -> Tables {
+    let mut step = String::from("DATA;\n#1 = NODE('0');\n");
+    for k in 2..=len {
+        step.push_str(&format!(
+            "#{k} = LINK(NODE(('{k}')), #{}, #{});\n",
+            k - 1,
+            other(k)
+        ));
+    }
+    step.push_str("ENDSEC;\n");
+    Tables::from_data_section(&DataSection::from_str(&step).unwrap()).unwrap()
+}
+
+/// Number of links following `next`. Consumes the value to drop it without recursion.
+fn length(mut node: NodeAny) -> usize {
+    let mut len = 0;
+    while let NodeAny::Link(link) = node {
+        len += 1;
+        node = link.next;
+    }
+    len
+}
+
+fn resolve(tables: &Tables, id: u64) -> ruststep::error::Result<NodeAny> {
+    EntityTable::<NodeAnyHolder>::get_owned(tables, id)
+}
+
+#[test]
+fn long_chain() {
+    // Run in a thread with the default small stack
+    std::thread::spawn(|| {
+        let tables = chain(50_000, |_| 1);
+        let head = resolve(&tables, 50_000).unwrap();
+        assert_eq!(length(head), 49_999);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn shared_in_deferred() {
+    // #200 refers #199 twice, and both are resolved through the deferred entities
+    let tables = chain(200, |k| if k == 200 { 199 } else { 1 });
+    let head = resolve(&tables, 200).unwrap();
+    let NodeAny::Link(link) = head else {
+        panic!("#200 must be a link")
+    };
+    assert_eq!(link.next, link.other);
+    assert_eq!(length(link.next), 198);
+}
+
+#[test]
+fn cyclic() {
+    let step = DataSection::from_str(
+        "DATA; #1 = LINK(NODE(('a')), #2, #2); #2 = LINK(NODE(('b')), #1, #1); ENDSEC;",
+    )
+    .unwrap();
+    let tables = Tables::from_data_section(&step).unwrap();
+    assert!(matches!(
+        resolve(&tables, 1).unwrap_err().without_context(),
+        Error::CyclicReference(1)
+    ));
+
+    // Longer than the depth for deferring
+    let tables = chain(300, |k| if k == 2 { 300 } else { 1 });
+    assert!(matches!(
+        resolve(&tables, 300).unwrap_err().without_context(),
+        Error::CyclicReference(_)
+    ));
+    // Errors do not leave states
+    assert!(resolve(&tables, 1).is_ok());
+}
+
+#[test]
+fn cache() {
+    let tables = chain(300, |k| (k % 7 + 1).min(k - 1));
+    let uncached: Vec<_> = (1..=300).map(|id| resolve(&tables, id).unwrap()).collect();
+
+    let cache = ResolveCache::new();
+    let cached: Vec<_> = with_resolve_cache(&cache, || {
+        (1..=300).map(|id| resolve(&tables, id).unwrap()).collect()
+    });
+    assert_eq!(uncached, cached);
+    assert!(!cache.is_empty());
+
+    // Cache is reused
+    let again: Vec<_> = with_resolve_cache(&cache, || {
+        (1..=300).map(|id| resolve(&tables, id).unwrap()).collect()
+    });
+    assert_eq!(uncached, again);
+
+    let mut owned: Vec<_> = EntityTable::<LinkHolder>::owned_iter(&tables)
+        .map(|link| link.unwrap())
+        .collect();
+    owned.sort_by_key(|link| link.node.name.parse::<u64>().unwrap());
+    for (link, expected) in owned.into_iter().zip(&uncached[1..]) {
+        assert_eq!(&NodeAny::Link(Box::new(link)), expected);
+    }
+}
+
+#[test]
+fn owned_iter_cache() {
+    let tables = chain(200, |k| (k % 7 + 1).min(k - 1));
+    let owned: Vec<_> = EntityTable::<NodeAnyHolder>::owned_iter(&tables)
+        .map(|node| node.unwrap())
+        .collect();
+    assert_eq!(owned.len(), 200);
+
+    // Cache given by `with_resolve_cache` is used instead of the one of the iterator
+    let cache = ResolveCache::new();
+    let cached: Vec<_> = with_resolve_cache(&cache, || {
+        EntityTable::<NodeAnyHolder>::owned_iter(&tables)
+            .map(|node| node.unwrap())
+            .collect()
+    });
+    assert_eq!(owned, cached);
+    assert!(!cache.is_empty());
+}
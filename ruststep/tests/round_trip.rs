@@ -0,0 +1,75 @@
+//! Write parsed exchange structures back by [std::fmt::Display], and parse them again
+
+use ruststep::{
+    ast::{Exchange, Parameter},
+    parser::{self, ParseOptions, SpecializedKeywords},
+};
+use std::{fs, path::PathBuf};
+
+fn read(name: &str) -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps")
+        .join(name);
+    fs::read_to_string(step_file).unwrap()
+}
+
+#[test]
+fn same_ast() {
+    for name in [
+        "00000050_80d90bfdd2e74e709956122a_step_000.step",
+        "database.p21",
+        "anchors.step",
+        "comments.step",
+    ] {
+        let exchange = parser::parse(&read(name)).unwrap();
+        let written = exchange.to_string();
+        assert!(written.starts_with("ISO-10303-21;\n"), "{}", name);
+        assert!(written.ends_with("END-ISO-10303-21;\n"), "{}", name);
+        assert_eq!(parser::parse(&written).unwrap(), exchange, "{}", name);
+
+        // Written text is stable
+        let rewritten: Exchange = parser::parse(&written).unwrap();
+        assert_eq!(rewritten.to_string(), written, "{}", name);
+    }
+}
+
+#[test]
+fn real_text() {
+    // Written back as is, although they differ from the shortest representation
+    let input = "DATA;\n\
+                 #1 = A(-0.0,1.0E+015,0.000000000001,3.14159265358979323846,1.50,+2.);\n\
+                 #2 = CARTESIAN_POINT('',(0.10000000000000001,-1.E-3,100.));\n\
+                 ENDSEC;";
+    let default_text = "DATA;\n\
+                        #1 = A(-0.0,1000000000000000.0,1.E-12,3.141592653589793,1.5,2.0);\n\
+                        #2 = CARTESIAN_POINT('',(0.1,-0.001,100.0));\n\
+                        ENDSEC;";
+    assert_eq!(
+        parser::parse_data_section(input).unwrap().to_string(),
+        default_text
+    );
+
+    let options = ParseOptions {
+        keep_real_text: true,
+        ..ParseOptions::strict()
+    };
+    for options in [
+        options,
+        options.specialize_keywords(&SpecializedKeywords::SUPPORTED),
+    ] {
+        let section = parser::parse_data_section_with(input, &options).unwrap();
+        assert_eq!(section.to_string(), input);
+    }
+
+    // Modified values are written by the value
+    let mut section = parser::parse_data_section_with(input, &options).unwrap();
+    if let Some(Parameter::Real { value, .. }) = section.entities[0]
+        .record_mut()
+        .and_then(|record| record.attribute_mut(1))
+    {
+        *value = 2.0e15;
+    }
+    assert!(section
+        .to_string()
+        .contains("#1 = A(-0.0,2000000000000000.0,0.000000000001,"));
+}
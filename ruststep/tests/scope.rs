@@ -0,0 +1,54 @@
+// Test for `&SCOPE` blocks of the first edition of ISO-10303-21
+
+use ruststep::parser::*;
+
+const STEP: &str = r#"
+ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('first edition'), '1;1');
+FILE_NAME('scope.stp', '1995-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+ENDSEC;
+DATA;
+#1 = &SCOPE
+  #2 = CARTESIAN_POINT('', (0.0, 0.0, 0.0));
+  #3 = &SCOPE #4 = DIRECTION('', (0.0, 0.0, 1.0)); ENDSCOPE
+    AXIS1_PLACEMENT('', #2, #4);
+ENDSCOPE /#2, #3/
+  PLACEMENT('', #2);
+#10 = PRODUCT('a', 'a', '', ()) &SCOPE
+  #11 = PRODUCT_CONTEXT('', #12, 'mechanical');
+  #12 = APPLICATION_CONTEXT('');
+ENDSCOPE;
+#20 = (A() B());
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+#[test]
+fn hoist_scoped_instances() {
+    let exchange = parse(STEP).unwrap();
+    let ids: Vec<u64> = exchange.data[0]
+        .entities
+        .iter()
+        .map(|instance| instance.id())
+        .collect();
+    assert_eq!(ids, [2, 4, 3, 1, 10, 11, 12, 20]);
+    assert_eq!(
+        exchange.data[0].entities[3].to_string(),
+        "#1 = PLACEMENT('',#2);"
+    );
+}
+
+#[test]
+fn same_as_borrowed() {
+    let owned = parse(STEP).unwrap();
+    let borrowed = parse_borrowed(STEP).unwrap();
+    assert_eq!(borrowed.into_owned(), owned);
+}
+
+#[test]
+fn unclosed_scope() {
+    let input = "DATA; #1 = &SCOPE #2 = B(); A(#2); ENDSEC;";
+    assert!(parse_data_section(input).is_err());
+}
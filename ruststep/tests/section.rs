@@ -0,0 +1,123 @@
+// Test for loading a part of data sections
+
+use ruststep::{ast::*, error::Error, parser, tables::*};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        name: STRING;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const TWO_SECTIONS: &str = r#"
+ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('two data sections'), '2;1');
+FILE_NAME('two_sections.step', '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA('geometry', ('TEST_SCHEMA'));
+#1 = CARTESIAN_POINT('origin', (0.0, 0.0, 0.0));
+#2 = CARTESIAN_POINT('x', (1.0, 0.0, 0.0));
+#3 = VERTEX_POINT('v', #1);
+ENDSEC;
+DATA('pmi', ('TEST_SCHEMA'));
+#10 = CARTESIAN_POINT('label', (0.0, 1.0, 0.0));
+#11 = VERTEX_POINT('w', #10);
+#12 = VERTEX_POINT('u', #2);
+ENDSEC;
+END-ISO-10303-21;
+"#;
+
+fn two_sections() -> Exchange {
+    parser::parse(TWO_SECTIONS.trim()).unwrap()
+}
+
+#[test]
+fn section_summaries() {
+    let exchange = two_sections();
+    let summaries = exchange.section_summaries();
+    assert_eq!(summaries.len(), 2);
+
+    assert_eq!(summaries[0].name.as_deref(), Some("geometry"));
+    assert_eq!(summaries[0].num_instances, 3);
+    assert_eq!(summaries[0].keyword_counts["CARTESIAN_POINT"], 2);
+    assert_eq!(summaries[0].keyword_counts["VERTEX_POINT"], 1);
+    assert_eq!(summaries[0].id_range, Some(1..=3));
+
+    assert_eq!(summaries[1].name.as_deref(), Some("pmi"));
+    assert_eq!(summaries[1].num_instances, 3);
+    assert_eq!(summaries[1].id_range, Some(10..=12));
+}
+
+#[test]
+fn load_selected_section() {
+    let exchange = two_sections();
+    let all = Tables::from_selected_data_sections(&exchange.data, &SectionSelector::All).unwrap();
+    assert_eq!(all.cartesian_point_holders().len(), 3);
+    assert_eq!(all.vertex_point_holders().len(), 3);
+
+    let geometry = Tables::from_selected_data_sections(
+        &exchange.data,
+        &SectionSelector::ByName(vec!["geometry".to_string()]),
+    )
+    .unwrap();
+    assert_eq!(geometry.cartesian_point_holders().len(), 2);
+    assert_eq!(geometry.vertex_point_holders().len(), 1);
+    let vertex = EntityTable::<VertexPointHolder>::get_owned(&geometry, 3).unwrap();
+    assert_eq!(vertex.vertex_geometry.name, "origin");
+
+    let geometry_by_index =
+        Tables::from_selected_data_sections(&exchange.data, &SectionSelector::ByIndex(vec![0]))
+            .unwrap();
+    assert_eq!(geometry, geometry_by_index);
+}
+
+#[test]
+fn reference_into_unloaded_section() {
+    let exchange = two_sections();
+    // #12 in "pmi" refers #2 in "geometry"
+    let err = Tables::from_selected_data_sections(
+        &exchange.data,
+        &SectionSelector::ByName(vec!["pmi".to_string()]),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::EntityInUnloadedSection { id: 2, section: 0 }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "Lookup failed for #2: target is in unloaded section 0"
+    );
+}
+
+#[test]
+fn resolve_by_index() {
+    let exchange = two_sections();
+    let geometry = &exchange.data[0];
+    let index = geometry.index().unwrap();
+    let resolved: Vec<_> = geometry
+        .entities_by_keyword("VERTEX_POINT")
+        .map(|(_, record)| {
+            let point = record.attribute(1).unwrap().as_entity_ref().unwrap();
+            let point = &index.get(point).unwrap().records()[0];
+            point.attribute(0).unwrap().as_string().unwrap()
+        })
+        .collect();
+    assert_eq!(resolved, ["origin"]);
+
+    // #2 is not in "pmi"
+    assert!(exchange.data[1].index().unwrap().get(2).is_none());
+}
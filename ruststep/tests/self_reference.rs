@@ -0,0 +1,120 @@
+// Test for entities referring themselves directly or through other entities
+
+use ruststep::tables::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY node;
+        label: STRING;
+        parent: OPTIONAL node;
+      END_ENTITY;
+
+      ENTITY a;
+        x: INTEGER;
+        b: OPTIONAL b;
+      END_ENTITY;
+
+      ENTITY b;
+        a: a;
+      END_ENTITY;
+
+      ENTITY p;
+        q: q;
+      END_ENTITY;
+
+      ENTITY q;
+        r: r;
+      END_ENTITY;
+
+      ENTITY r;
+        p: OPTIONAL p;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+#[test]
+fn self_referential() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = NODE('root', $);
+          #2 = NODE('child', #1);
+          #3 = NODE('grandchild', #2);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let node = EntityTable::<NodeHolder>::get_owned(&table, 3).unwrap();
+    let root = Node {
+        label: "root".into(),
+        parent: None,
+    };
+    let child = Node {
+        label: "child".into(),
+        parent: Some(Box::new(root)),
+    };
+    assert_eq!(
+        node,
+        Node {
+            label: "grandchild".into(),
+            parent: Some(Box::new(child)),
+        }
+    );
+}
+
+#[test]
+fn two_entity_cycle() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = A(1, $);
+          #2 = B(#1);
+          #3 = A(3, #2);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let a = EntityTable::<AHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(
+        a,
+        A {
+            x: 3,
+            b: Some(B {
+                a: Box::new(A { x: 1, b: None }),
+            }),
+        }
+    );
+}
+
+#[test]
+fn cycle_with_optional() {
+    let table = Tables::from_str(
+        r#"
+        DATA;
+          #1 = R($);
+          #2 = Q(#1);
+          #3 = P(#2);
+          #4 = R(#3);
+          #5 = Q(#4);
+        ENDSEC;
+        "#,
+    )
+    .unwrap();
+    let q = EntityTable::<QHolder>::get_owned(&table, 5).unwrap();
+    let p = P {
+        q: Q { r: R { p: None } },
+    };
+    assert_eq!(
+        q,
+        Q {
+            r: R {
+                p: Some(Box::new(p)),
+            },
+        }
+    );
+}
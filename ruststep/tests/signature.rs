@@ -0,0 +1,90 @@
+// Test for SIGNATURE sections after END-ISO-10303-21
+
+use ruststep::{ast::*, parser};
+
+const SIGNED: &str = r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('signed file'), '3;1');
+FILE_NAME('signed.stp', '2024-01-01T00:00:00', (''), (''), '', '', '');
+FILE_SCHEMA(('CONFIG_CONTROL_DESIGN'));
+ENDSEC;
+DATA;
+#1 = PRODUCT('bolt', 'M6 bolt', '', ());
+ENDSEC;
+END-ISO-10303-21;
+SIGNATURE
+MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAu1SU1LfVLPHCozMxH2Mo
+4lgOEePzNm0tRgeLezV6ffAt0gunVTLw7onLRnrq0/IzW7yWR7QkrmBL7jTKEn5u
++qKhbwKfBstIs+bMY2Zkp18gnTxKLxoS2tFczGkPLPgizskuemMghRniWaoLcyeh
+kd3qqGElvW/VDL5AaWTg0nLVkjRo9z+40RQzuVaE8AkAFmxZzow3x+VJYKdjykkJ
+0iT9wCS0DRTXu269V264Vf/3jvredZiKRkgwlL9xNAwxXFg0x/XFw005UWVRIkdg
+cKWTjpBP2dPwVZ4WWC+9aGVd+Gyn1o0CLelf4rEjGoXbAAEgAqeGUxrcIlbjXfbc
+mwIDAQAB
+ENDSEC;
+SIGNATURE QUJD ENDSEC;
+"#;
+
+const FIRST: &str = "MIIBIjANBgkqhkiG9w0BAQEFAAOCAQ8AMIIBCgKCAQEAu1SU1LfVLPHCozMxH2Mo\
+                     4lgOEePzNm0tRgeLezV6ffAt0gunVTLw7onLRnrq0/IzW7yWR7QkrmBL7jTKEn5u\
+                     +qKhbwKfBstIs+bMY2Zkp18gnTxKLxoS2tFczGkPLPgizskuemMghRniWaoLcyeh\
+                     kd3qqGElvW/VDL5AaWTg0nLVkjRo9z+40RQzuVaE8AkAFmxZzow3x+VJYKdjykkJ\
+                     0iT9wCS0DRTXu269V264Vf/3jvredZiKRkgwlL9xNAwxXFg0x/XFw005UWVRIkdg\
+                     cKWTjpBP2dPwVZ4WWC+9aGVd+Gyn1o0CLelf4rEjGoXbAAEgAqeGUxrcIlbjXfbc\
+                     mwIDAQAB";
+
+#[test]
+fn wrapped_signatures() {
+    let exchange = parser::parse(SIGNED).unwrap();
+    assert_eq!(exchange.signature, [FIRST, "QUJD"]);
+    assert_eq!(exchange.data[0].entities.len(), 1);
+}
+
+#[test]
+fn other_parsers() {
+    let exchange = parser::parse(SIGNED).unwrap();
+    assert_eq!(
+        parser::parse_borrowed(SIGNED).unwrap().into_owned(),
+        exchange
+    );
+    let options = parser::ParseOptions {
+        recover_header: true,
+        ..Default::default()
+    };
+    assert_eq!(parser::parse_with(SIGNED, &options).unwrap(), exchange);
+    assert_eq!(parser::parse(&exchange.to_string()).unwrap(), exchange);
+}
+
+#[test]
+fn unsigned() {
+    let end = SIGNED.find("SIGNATURE").unwrap();
+    let exchange = parser::parse(&SIGNED[..end]).unwrap();
+    assert!(exchange.signature.is_empty());
+    assert_eq!(
+        Exchange {
+            signature: vec![FIRST.to_string(), "QUJD".to_string()],
+            ..exchange
+        },
+        parser::parse(SIGNED).unwrap()
+    );
+}
+
+#[test]
+fn malformed_signature() {
+    use nom::Finish;
+    use ruststep::parser::exchange::signature_section;
+
+    let (residual, signature) = signature_section("SIGNATURE QU\n  JD\nENDSEC;")
+        .finish()
+        .unwrap();
+    assert_eq!(residual, "");
+    assert_eq!(signature, "QUJD");
+
+    // Not base64
+    assert!(signature_section("SIGNATURE QU;D ENDSEC;")
+        .finish()
+        .is_err());
+    // Missing ENDSEC
+    assert!(signature_section("SIGNATURE QUJD").finish().is_err());
+    // Empty
+    assert!(signature_section("SIGNATURE ENDSEC;").finish().is_err());
+}
@@ -0,0 +1,45 @@
+//! Differential test of specialized parsers, see `ParseOptions::specialize_keywords`
+
+use ruststep::parser::{self, parse_with, ParseOptions, SpecializedKeywords};
+use std::{fs, path::PathBuf};
+
+fn geometry_example() -> String {
+    let step_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+    fs::read_to_string(step_file).unwrap()
+}
+
+fn specialized() -> ParseOptions {
+    ParseOptions::strict().specialize_keywords(&SpecializedKeywords::SUPPORTED)
+}
+
+#[test]
+fn same_ast() {
+    let step = geometry_example();
+    let generic = parser::parse(&step).unwrap();
+    let fast = parse_with(&step, &specialized()).unwrap();
+    assert_eq!(generic, fast);
+}
+
+#[test]
+fn same_ast_lenient() {
+    let step = geometry_example().replace(
+        "CARTESIAN_POINT( '', ( 0.00000000000000,",
+        "CARTESIAN_POINT( '' /* origin */, ( - 0.00000000000000,",
+    );
+    let generic = parse_with(&step, &ParseOptions::lenient()).unwrap();
+    let fast = parse_with(
+        &step,
+        &ParseOptions::lenient().specialize_keywords(&["CARTESIAN_POINT"]),
+    )
+    .unwrap();
+    assert_eq!(generic, fast);
+}
+
+#[test]
+fn same_error() {
+    let step = geometry_example().replacen("DIRECTION( '', (", "DIRECTION( '', ((", 1);
+    let generic = parser::parse(&step).unwrap_err();
+    let fast = parse_with(&step, &specialized()).unwrap_err();
+    assert_eq!(generic.to_string(), fast.to_string());
+}
@@ -0,0 +1,161 @@
+// Test for statistics of attribute values over tables
+
+use ruststep::stats::*;
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      TYPE stage = ENUMERATION OF (design, prototype, production, retired);
+      END_TYPE;
+
+      ENTITY part;
+        name: STRING;
+        stage: stage;
+        mass: REAL;
+        note: OPTIONAL STRING;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+/// 100 parts whose name is `P{id % 7}`, stage is cycled, mass is the id, and note is given for every fifth part
+fn fixture() -> Tables {
+    let stages = ["DESIGN", "PROTOTYPE", "PRODUCTION", "RETIRED"];
+    let mut input = "DATA;\n".to_string();
+    for id in 1..=100 {
+        let note = if id % 5 == 0 { "'checked'" } else { "$" };
+        input += &format!(
+            "#{} = PART('P{}', .{}., {}.0, {});\n",
+            id,
+            id % 7,
+            stages[id % 4],
+            id,
+            note
+        );
+    }
+    input += "ENDSEC;\n";
+    Tables::from_str(&input).unwrap()
+}
+
+#[test]
+fn exact_counts() {
+    let spec = StatsSpec::new()
+        .count_by_value("PART", "stage", 10)
+        .null_rate("PART", "note")
+        .count_by_value("PART", "note", 10);
+    let report = collect(&fixture(), &spec);
+
+    assert_eq!(
+        report.items[0].stats,
+        Stats::ValueCounts(ValueCounts {
+            counts: vec![
+                ("Design".to_string(), 25),
+                ("Production".to_string(), 25),
+                ("Prototype".to_string(), 25),
+                ("Retired".to_string(), 25),
+            ],
+            overflow: 0,
+            nulls: 0,
+        })
+    );
+    assert_eq!(
+        report.items[1].stats,
+        Stats::NullRate(NullRate {
+            total: 100,
+            nulls: 80,
+            rate: 0.8,
+        })
+    );
+    assert_eq!(
+        report.items[2].stats,
+        Stats::ValueCounts(ValueCounts {
+            counts: vec![("checked".to_string(), 20)],
+            overflow: 0,
+            nulls: 80,
+        })
+    );
+}
+
+#[test]
+fn numeric_summary() {
+    let spec = StatsSpec::new().numeric("PART", "mass", &[0.1, 0.5, 0.9]);
+    let report = collect(&fixture(), &spec);
+    let Stats::Numeric(summary) = &report.items[0].stats else {
+        panic!("Unexpected stats: {:?}", report.items[0].stats);
+    };
+    assert_eq!(summary.count, 100);
+    assert_eq!(summary.nulls, 0);
+    assert_eq!(summary.min, Some(1.0));
+    assert_eq!(summary.max, Some(100.0));
+    assert_eq!(summary.mean, Some(50.5));
+    for (quantile, expected) in summary.quantiles.iter().zip([10.9, 50.5, 90.1]) {
+        assert!(
+            (quantile.value - expected).abs() < 3.0,
+            "{} quantile is estimated as {}",
+            quantile.p,
+            quantile.value
+        );
+    }
+}
+
+#[test]
+fn cardinality_cap() {
+    let spec = StatsSpec::new().count_by_value("PART", "name", 3);
+    let report = collect(&fixture(), &spec);
+    // The first three names in the order of ids are counted
+    assert_eq!(
+        report.items[0].stats,
+        Stats::ValueCounts(ValueCounts {
+            counts: vec![
+                ("P1".to_string(), 15),
+                ("P2".to_string(), 15),
+                ("P3".to_string(), 14),
+            ],
+            overflow: 56,
+            nulls: 0,
+        })
+    );
+}
+
+#[test]
+fn not_found() {
+    let spec = StatsSpec::new()
+        .null_rate("ASSEMBLY", "note")
+        .null_rate("PART", "color");
+    let report = collect(&fixture(), &spec);
+    assert_eq!(report.items[0].stats, Stats::NotFound);
+    assert_eq!(report.items[1].stats, Stats::NotFound);
+}
+
+#[test]
+fn json() {
+    let spec = StatsSpec::new()
+        .null_rate("PART", "note")
+        .count_by_value("PART", "stage", 1);
+    let report = collect(&fixture(), &spec);
+    assert_eq!(
+        serde_json::to_value(&report).unwrap(),
+        serde_json::json!({
+            "items": [
+                {
+                    "entity": "PART",
+                    "attribute": "note",
+                    "stats": { "kind": "null_rate", "total": 100, "nulls": 80, "rate": 0.8 },
+                },
+                {
+                    "entity": "PART",
+                    "attribute": "stage",
+                    "stats": {
+                        "kind": "value_counts",
+                        "counts": [["Prototype", 25]],
+                        "overflow": 75,
+                        "nulls": 0,
+                    },
+                },
+            ]
+        })
+    );
+}
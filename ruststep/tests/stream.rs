@@ -0,0 +1,151 @@
+// Test for typed streams of entity instances
+
+use ruststep::{error::Error, parser::*, tables::*};
+use std::io::{self, BufReader, Read};
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA test_schema;
+      ENTITY cartesian_point;
+        name: STRING;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY vertex_point;
+        name: STRING;
+        vertex_geometry: cartesian_point;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+use test_schema::*;
+
+const STEP: &str = include_str!("steps/00000050_80d90bfdd2e74e709956122a_step_000.step");
+
+#[derive(Debug)]
+enum Geometry {
+    Point(CartesianPointHolder),
+    Vertex(VertexPointHolder),
+}
+
+#[test]
+fn dispatch_two_types() {
+    let stream = DispatchStream::new(read_instances(STEP.as_bytes()))
+        .register(Geometry::Point)
+        .register(Geometry::Vertex);
+    let mut points = Vec::new();
+    let mut vertices = Vec::new();
+    for item in stream {
+        match item.unwrap() {
+            (id, Geometry::Point(p)) => points.push((id, p)),
+            (id, Geometry::Vertex(v)) => vertices.push((id, v)),
+        }
+    }
+    assert_eq!(points.len(), 214);
+    assert_eq!(vertices.len(), 70);
+
+    // Same as parsing entire file
+    let exchange = parse(STEP).unwrap();
+    let expected: Vec<(u64, CartesianPointHolder)> = exchange.data[0]
+        .entities
+        .iter()
+        .filter_map(|instance| Some((instance.id(), parse_instance(instance).ok()?)))
+        .collect();
+    assert_eq!(points, expected);
+}
+
+#[test]
+fn typed_stream_reports_id() {
+    let step = "DATA; #1 = CARTESIAN_POINT('a', (1.0)); #2 = CARTESIAN_POINT(1.0); ENDSEC;";
+    let results: Vec<_> =
+        TypedStream::<CartesianPointHolder, _>::new(read_instances(step.as_bytes())).collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().0, 1);
+    assert!(matches!(
+        results[1],
+        Err(Error::InstanceFailed { id: 2, .. })
+    ));
+}
+
+#[test]
+fn huge_record() {
+    // 64MiB string which is never buffered entirely
+    let input = "DATA; #1 = CARTESIAN_POINT('"
+        .as_bytes()
+        .chain(io::repeat(b'x').take(1 << 26))
+        .chain("', (0.0)); #2 = CARTESIAN_POINT('p', (1.0)); ENDSEC;".as_bytes());
+    let reader = read_instances(BufReader::new(input)).max_statement_bytes(1 << 20);
+    let results: Vec<_> = TypedStream::<CartesianPointHolder, _>::new(reader).collect();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(
+        results[0],
+        Err(Error::StatementTooLarge { limit: 1048576 })
+    ));
+    assert_eq!(results[1].as_ref().unwrap().0, 2);
+}
+
+#[test]
+fn header_before_instances() {
+    let mut reader = read_instances(BufReader::new(STEP.as_bytes()));
+    let header = reader.header().unwrap();
+    let expected = ruststep::header::Header::from_records(&parse(STEP).unwrap().header).unwrap();
+    assert_eq!(header, expected);
+
+    // Instances are not consumed by reading header
+    let points: Vec<_> = TypedStream::<CartesianPointHolder, _>::new(reader)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(points.len(), 214);
+}
+
+#[test]
+fn invalid_header() {
+    let step =
+        "ISO-10303-21; HEADER; FILE_NAME('a', '', (''), (''), '', '', ''); ENDSEC; DATA; ENDSEC;";
+    assert!(matches!(
+        read_instances(step.as_bytes()).header(),
+        Err(Error::InvalidHeader { .. })
+    ));
+}
+
+#[test]
+fn error_offset() {
+    let step = "DATA;\n#1 = A(1.0);\n/* comment */ #2 = B(1.0 2.0);\nENDSEC;";
+    let mut reader = read_instances(step.as_bytes());
+    assert!(reader.next().unwrap().is_ok());
+    let err = match reader.next().unwrap() {
+        Err(Error::InstanceFailed { id: 2, source }) => *source,
+        result => panic!("unexpected {:?}", result),
+    };
+    assert_eq!(reader.offset(), step.find("#2").unwrap());
+    match err {
+        Error::TokenizeFailed(e) => assert_eq!(e.offset(), step.find("B(1.0").unwrap()),
+        e => panic!("unexpected {:?}", e),
+    }
+}
+
+#[test]
+fn depth_limit_offset() {
+    let options = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::strict()
+    };
+    let step = "DATA;\n#1 = A(1.0);\n#2 = B(((1.0)));\nENDSEC;";
+    let mut reader = read_instances(step.as_bytes()).options(&options);
+    assert!(reader.next().unwrap().is_ok());
+    let err = match reader.next().unwrap() {
+        Err(Error::InstanceFailed { id: 2, source }) => *source,
+        result => panic!("unexpected {:?}", result),
+    };
+    match err {
+        Error::TokenizeFailed(e) => assert_eq!(
+            e.token_error(),
+            Some(&ruststep::error::TokenError::DepthLimitExceeded {
+                depth: 3,
+                offset: step.find("1.0)))").unwrap(),
+            })
+        ),
+        e => panic!("unexpected {:?}", e),
+    }
+}
@@ -0,0 +1,276 @@
+//! Write `Tables` back into a data section, and read it again
+//!
+//! Schemas are same as `espr/tests/corpus`
+
+use ruststep::{
+    ast::*,
+    header::*,
+    tables::{PlaceHolder, TableInit},
+};
+use std::str::FromStr;
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA aggregates;
+      ENTITY cartesian_point;
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      TYPE point_list = LIST [2:?] OF cartesian_point;
+      END_TYPE;
+
+      ENTITY b_spline_surface;
+        u_degree: INTEGER;
+        v_degree: INTEGER;
+        control_points: LIST [2:?] OF LIST [2:?] OF cartesian_point;
+        weights: ARRAY [1:4] OF OPTIONAL REAL;
+        knots: SET [0:?] OF REAL;
+      END_ENTITY;
+
+      ENTITY polyline;
+        points: point_list;
+        closed: BOOLEAN;
+        valid: LOGICAL;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA enumerations;
+      TYPE si_prefix = ENUMERATION OF (exa, peta, tera, giga, mega, kilo, milli, micro, nano);
+      END_TYPE;
+
+      TYPE si_unit_name = ENUMERATION OF (metre, gram, second, ampere, kelvin);
+      END_TYPE;
+
+      TYPE unit_name = si_unit_name;
+      END_TYPE;
+
+      ENTITY si_unit;
+        prefix: OPTIONAL si_prefix;
+        name: unit_name;
+        flags: LIST [0:?] OF si_prefix;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA inheritance;
+      ENTITY representation_item SUPERTYPE OF (ONEOF (geometric_representation_item, topological_representation_item));
+        name: STRING;
+      END_ENTITY;
+
+      ENTITY geometric_representation_item SUPERTYPE OF (point) SUBTYPE OF (representation_item);
+      END_ENTITY;
+
+      ENTITY point SUPERTYPE OF (cartesian_point) SUBTYPE OF (geometric_representation_item);
+      END_ENTITY;
+
+      ENTITY cartesian_point SUBTYPE OF (point);
+        coordinates: LIST [1:3] OF REAL;
+      END_ENTITY;
+
+      ENTITY topological_representation_item SUPERTYPE OF (vertex) SUBTYPE OF (representation_item);
+      END_ENTITY;
+
+      ENTITY vertex SUBTYPE OF (topological_representation_item);
+      END_ENTITY;
+
+      ENTITY vertex_point SUBTYPE OF (vertex);
+        vertex_geometry: point;
+      END_ENTITY;
+
+      ENTITY representation;
+        items: SET [1:?] OF representation_item;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+espr_derive::inline_express!(
+    r#"
+    SCHEMA selects;
+      TYPE label = STRING;
+      END_TYPE;
+
+      ENTITY person;
+        name: label;
+      END_ENTITY;
+
+      ENTITY organization;
+        name: label;
+      END_ENTITY;
+
+      TYPE person_or_organization = SELECT (person, organization);
+      END_TYPE;
+
+      TYPE identifier = SELECT (label, person_or_organization);
+      END_TYPE;
+
+      ENTITY approval;
+        approver: person_or_organization;
+        ids: SET [1:?] OF identifier;
+        delegate: OPTIONAL person_or_organization;
+      END_ENTITY;
+    END_SCHEMA;
+    "#
+);
+
+/// parse -> tables -> data section -> tables, and returns the written data section
+fn round_trip<
+    T: TableInit + FromStr<Err = ruststep::error::Error> + PartialEq + std::fmt::Debug,
+>(
+    input: &str,
+    to_data_section: impl Fn(&T) -> DataSection,
+) -> String {
+    let tables = T::from_str(input).unwrap();
+    let section = to_data_section(&tables);
+    let written = section.to_string();
+    let read = T::from_str(&written).unwrap();
+    assert_eq!(read, tables, "{}", written);
+    written
+}
+
+#[test]
+fn aggregates() {
+    let written = round_trip(
+        r#"
+        DATA;
+          #1 = CARTESIAN_POINT((0.0, 0.0, 0.0));
+          #2 = CARTESIAN_POINT((1.0, 0.0, 0.0));
+          #3 = POLYLINE(POINT_LIST(((#1, #2))), .T., .U.);
+          #5 = POINT_LIST((#2, #1));
+          #6 = POLYLINE(#5, .F., .F.);
+          #4 = B_SPLINE_SURFACE(1, 1, ((#1, #2), (#2, #1)), (1.0, $, 0.5, $), ());
+        ENDSEC;
+        "#,
+        aggregates::Tables::to_data_section,
+    );
+    assert!(written.contains("#3 = POLYLINE(POINT_LIST(((#1,#2))),.T.,.U.);"));
+    assert!(written.contains("#4 = B_SPLINE_SURFACE(1,1,((#1,#2),(#2,#1)),(1.0,$,0.5,$),());"));
+}
+
+#[test]
+fn enumerations() {
+    let written = round_trip(
+        r#"
+        DATA;
+          #1 = SI_UNIT(.MILLI., .METRE., (.KILO., .NANO.));
+          #2 = SI_UNIT($, .GRAM., ());
+        ENDSEC;
+        "#,
+        enumerations::Tables::to_data_section,
+    );
+    assert_eq!(
+        written,
+        "DATA;\n#1 = SI_UNIT(.MILLI.,.METRE.,(.KILO.,.NANO.));\n#2 = SI_UNIT($,.GRAM.,());\nENDSEC;"
+    );
+}
+
+#[test]
+fn inheritance() {
+    let written = round_trip(
+        r#"
+        DATA;
+          #1 = REPRESENTATION_ITEM('origin');
+          #2 = GEOMETRIC_REPRESENTATION_ITEM(#1);
+          #3 = POINT(#2);
+          #4 = CARTESIAN_POINT(#3, (0.0, 0.0, 0.0));
+          #5 = TOPOLOGICAL_REPRESENTATION_ITEM(REPRESENTATION_ITEM(('vertex')));
+          #6 = VERTEX(#5);
+          #7 = VERTEX_POINT(#6, #4);
+          #8 = REPRESENTATION((#1, #4, #7));
+        ENDSEC;
+        "#,
+        inheritance::Tables::to_data_section,
+    );
+    // Inherited attributes are written as the typed parameter of the supertype
+    assert!(written.contains("#4 = CARTESIAN_POINT(#3,(0.0,0.0,0.0));"));
+    assert!(
+        written.contains("#5 = TOPOLOGICAL_REPRESENTATION_ITEM(REPRESENTATION_ITEM(('vertex')));")
+    );
+}
+
+#[test]
+fn selects() {
+    let written = round_trip(
+        r#"
+        DATA;
+          #1 = PERSON(LABEL(('alice')));
+          #2 = ORGANIZATION(#5);
+          #3 = APPROVAL(#1, (LABEL(('a-1')), #2), $);
+          #4 = APPROVAL(#2, (#1), #2);
+          #5 = LABEL('ruststep');
+        ENDSEC;
+        "#,
+        selects::Tables::to_data_section,
+    );
+    assert!(written.contains("#3 = APPROVAL(#1,(LABEL(('a-1')),#2),$);"));
+    assert!(written.contains("#4 = APPROVAL(#2,(#1),#2);"));
+}
+
+#[test]
+fn to_step_string() {
+    let mut tables = enumerations::Tables::default();
+    tables
+        .insert_si_unit(enumerations::SiUnitHolder {
+            prefix: Some(enumerations::SiPrefix::Kilo),
+            name: enumerations::UnitName::Gram,
+            flags: Vec::new(),
+        })
+        .unwrap();
+    let header = Header {
+        file_description: FileDescription {
+            description: vec!["test".to_string()],
+            implementation_level: "2;1".to_string(),
+        },
+        file_name: FileName {
+            name: "units.stp".to_string(),
+            time_stamp: "2024-01-01T00:00:00".to_string(),
+            author: vec![String::new()],
+            organization: vec![String::new()],
+            preprocessor_version: String::new(),
+            originating_system: String::new(),
+            authorization: String::new(),
+        },
+        file_schema: FileSchema {
+            schema: vec!["ENUMERATIONS".to_string()],
+        },
+        extra: Vec::new(),
+    };
+    let written = tables.to_step_string(&header);
+    let exchange = Exchange::from_str(&written).unwrap();
+    assert_eq!(exchange.header_typed().unwrap(), header);
+    assert_eq!(
+        enumerations::Tables::from_data_section(&exchange.data[0]).unwrap(),
+        tables
+    );
+    assert!(written.contains("#1 = SI_UNIT(.KILO.,.GRAM.,());"));
+}
+
+#[test]
+fn owned_place_holder() {
+    let mut tables = selects::Tables::default();
+    let person = tables
+        .insert_person(selects::PersonHolder {
+            name: selects::LabelHolder("alice".to_string()).into(),
+        })
+        .unwrap();
+    tables
+        .insert_approval(selects::ApprovalHolder {
+            approver: PlaceHolder::Ref(Name::Entity(person)),
+            ids: vec![PlaceHolder::Owned(selects::IdentifierHolder::Label(
+                Box::new(selects::LabelHolder("a-1".to_string())),
+            ))],
+            delegate: None,
+        })
+        .unwrap();
+    assert_eq!(
+        tables.to_data_section().to_string(),
+        "DATA;\n#1 = PERSON(LABEL(('alice')));\n#2 = APPROVAL(#1,(LABEL(('a-1'))),$);\nENDSEC;"
+    );
+}
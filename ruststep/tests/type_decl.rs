@@ -34,12 +34,14 @@ espr_derive::inline_express!(
 
 use test_schema::*;
 
+// `c` and `d` are collapsed into aliases of `a` and `b`,
+// and their keywords are read as the keywords of `a` and `b`
 const EXAMPLE: &str = r#"
 DATA;
   #1 = A('KORE');
-  #3 = C(#1);
-  #4 = D(.DORE.);
-  #5 = E(#1, .SORE., #3, #4);
+  #3 = C('SORE');
+  #5 = E(#1, .SORE., #3, D(.DORE.));
+  #6 = E(C('KORE'), B(.ARE.), A('DORE'), .ARE.);
 ENDSEC;
 "#;
 
@@ -49,11 +51,8 @@ fn get_owned() {
     let a = EntityTable::<AHolder>::get_owned(&table, 1).unwrap();
     assert_eq!(a, A("KORE".to_string()));
 
-    let c = EntityTable::<CHolder>::get_owned(&table, 3).unwrap();
-    assert_eq!(c, C(A("KORE".to_string())));
-
-    let d = EntityTable::<DHolder>::get_owned(&table, 4).unwrap();
-    assert_eq!(d, D(B::Dore));
+    let c: C = EntityTable::<CHolder>::get_owned(&table, 3).unwrap();
+    assert_eq!(c, A("SORE".to_string()));
 
     let e = EntityTable::<EHolder>::get_owned(&table, 5).unwrap();
     assert_eq!(
@@ -61,8 +60,28 @@ fn get_owned() {
         E {
             a: A("KORE".to_string()),
             b: B::Sore,
-            c: C(A("KORE".to_string())),
-            d: D(B::Dore),
+            c: A("SORE".to_string()),
+            d: B::Dore,
         }
     );
+
+    let e = EntityTable::<EHolder>::get_owned(&table, 6).unwrap();
+    assert_eq!(
+        e,
+        E {
+            a: A("KORE".to_string()),
+            b: B::Are,
+            c: A("DORE".to_string()),
+            d: B::Are,
+        }
+    );
+}
+
+#[test]
+fn unknown_keyword() {
+    assert!(Tables::from_str("DATA; #1 = F('KORE'); ENDSEC;").is_err());
+    assert!(Tables::from_str(
+        "DATA; #1 = A('KORE'); #2 = E(#1, .SORE., F('KORE'), .ARE.); ENDSEC;"
+    )
+    .is_err());
 }
@@ -0,0 +1,95 @@
+// Test for keeping keywords of typed parameters through loading and serializing
+
+use ruststep::{ast::*, load::*, primitive::Wrapped, tables::*};
+use ruststep_derive::{as_holder, Holder, TableInit};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, TableInit)]
+pub struct Tables {
+    product: HashMap<u64, as_holder!(Product)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Holder, serde::Serialize)]
+#[holder(table = Tables)]
+#[holder(field = product)]
+#[holder(generate_deserialize)]
+#[serde(rename = "PRODUCT")]
+pub struct Product {
+    pub id: Wrapped<String>,
+    pub name: Wrapped<String>,
+    pub description: Option<Wrapped<String>>,
+    pub mass: Wrapped<f64>,
+}
+
+/// Instances in the canonical form of [EntityInstance] display
+const INSTANCES: &[&str] = &[
+    "#1 = PRODUCT(LABEL('bolt'),TEXT('M6 bolt'),$,4.5);",
+    "#2 = PRODUCT('nut',TEXT('M6 nut'),TEXT('hex, ''DIN 934'''),POSITIVE_MASS_MEASURE(1.25));",
+    "#3 = PRODUCT(LABEL('washer'),'washer',LABEL(''),0.5);",
+];
+
+fn step() -> String {
+    format!(
+        r#"ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION((''), '2;1');
+FILE_NAME('', '', (''), (''), '', '', '');
+FILE_SCHEMA(('TEST_SCHEMA'));
+ENDSEC;
+DATA;
+{}
+ENDSEC;
+END-ISO-10303-21;
+"#,
+        INSTANCES.join("\n")
+    )
+}
+
+fn export(tables: &Tables) -> Vec<String> {
+    let mut ids: Vec<u64> = tables.product.keys().copied().collect();
+    ids.sort_unstable();
+    ids.into_iter()
+        .map(|id| {
+            let product = tables.product[&id].clone().into_owned(tables).unwrap();
+            let instance = EntityInstance::Simple {
+                id,
+                record: ser::to_record(&product).unwrap(),
+            };
+            instance.to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn round_trip() {
+    let tables: Tables = load(&step(), LoadOptions::new().preserve_type_wrappers(true)).unwrap();
+    assert_eq!(export(&tables), INSTANCES);
+
+    let bolt = &tables.product[&1];
+    assert_eq!(bolt.id.keyword.as_deref(), Some("LABEL"));
+    assert_eq!(bolt.id.as_str(), "bolt");
+    assert_eq!(*bolt.mass, 4.5);
+    assert_eq!(bolt.mass.keyword, None);
+}
+
+#[test]
+fn wrappers_are_dropped_by_default() {
+    let tables: Tables = load(&step(), LoadOptions::new()).unwrap();
+    // Values are read in the same way
+    assert_eq!(*tables.product[&2].mass, 1.25);
+    assert_eq!(
+        tables.product[&2]
+            .description
+            .as_deref()
+            .map(String::as_str),
+        Some("hex, 'DIN 934'")
+    );
+    assert_eq!(
+        export(&tables),
+        vec![
+            "#1 = PRODUCT('bolt','M6 bolt',$,4.5);",
+            "#2 = PRODUCT('nut','M6 nut','hex, ''DIN 934''',1.25);",
+            "#3 = PRODUCT('washer','washer','',0.5);",
+        ]
+    );
+}